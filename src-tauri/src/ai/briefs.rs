@@ -0,0 +1,190 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::providers::AiProvider;
+use crate::domain_crawler::gsc::{normalise, GscQuery, GscRow};
+use crate::domain_crawler::models::DomainCrawlResults;
+use crate::domain_crawler::results_store::ResultsStore;
+use crate::settings::settings::load_settings;
+
+// How much of the page and its search data goes into the prompt
+const MAX_QUERIES: usize = 30;
+const MAX_TERMS: usize = 30;
+const MAX_HEADINGS: usize = 30;
+const MAX_LINKS: usize = 20;
+
+const SYSTEM_PROMPT: &str = "You are an SEO content strategist. You compare what a page covers \
+with what searchers look for and tell its editor what to add. Write in the language of the \
+page. Reply with JSON only, no backticks and nothing else.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaqSuggestion {
+    pub question: String,
+    /// What the answer should cover
+    pub answer: String,
+}
+
+/// Optimization brief of one page, generated from its crawl data and search queries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentBrief {
+    pub url: String,
+    pub model: String,
+    pub created_at: DateTime<Utc>,
+    pub summary: String,
+    /// Subtopics searchers look for that the page does not cover
+    pub missing_subtopics: Vec<String>,
+    pub faq: Vec<FaqSuggestion>,
+    pub recommendations: Vec<String>,
+    /// The Search Console queries the brief was based on, most clicked first
+    pub queries: Vec<GscQuery>,
+}
+
+#[derive(Deserialize)]
+struct BriefReply {
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    missing_subtopics: Vec<String>,
+    #[serde(default)]
+    faq: Vec<FaqSuggestion>,
+    #[serde(default)]
+    recommendations: Vec<String>,
+}
+
+fn headings(page: &DomainCrawlResults) -> Vec<String> {
+    let mut headings = Vec::new();
+    for level in ["h1", "h2", "h3"] {
+        for text in page.headings.get(level).into_iter().flatten() {
+            if !text.is_empty() {
+                headings.push(format!("{}: {}", level, text));
+            }
+        }
+    }
+    headings.truncate(MAX_HEADINGS);
+    headings
+}
+
+fn brief_prompt(
+    page: &DomainCrawlResults,
+    queries: &[GscQuery],
+    outgoing: &[String],
+    incoming: &[String],
+) -> String {
+    let terms: Vec<&str> = page
+        .term_counts
+        .terms
+        .iter()
+        .take(MAX_TERMS)
+        .map(|(term, _)| term.as_str())
+        .collect();
+    let snippet = &page.title_description;
+    let context = serde_json::json!({
+        "url": page.url,
+        "title": snippet.title.as_ref().map(|title| &title.text),
+        "description": snippet.description.as_ref().map(|description| &description.text),
+        "word_count": page.word_count,
+        "headings": headings(page),
+        "top_terms": terms,
+        "links_to": outgoing,
+        "linked_from": incoming,
+        "search_queries": queries,
+    });
+    format!(
+        "Write an optimization brief for this page. Compare its headings and terms with the \
+search queries it gets impressions for, list the subtopics it is missing, questions for an FAQ \
+section and other concrete improvements, such as internal links to add. Reply with \
+{{\"summary\": \"\", \"missing_subtopics\": [\"\"], \"faq\": [{{\"question\": \"\", \"answer\": \"\"}}], \
+\"recommendations\": [\"\"]}}. The page is:\n{}",
+        context
+    )
+}
+
+// Models wrap the JSON in prose or code fences despite the prompt
+fn parse_reply(reply: &str) -> Option<BriefReply> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    serde_json::from_str(reply.get(start..=end)?).ok()
+}
+
+// Search Console queries of the page, most clicked first
+fn page_queries(rows: Vec<GscRow>, url: &str) -> Vec<GscQuery> {
+    let key = normalise(url);
+    let mut queries: Vec<GscQuery> = rows
+        .into_iter()
+        .filter(|row| normalise(&row.page) == key)
+        .map(|row| GscQuery {
+            query: row.query,
+            clicks: row.clicks,
+            impressions: row.impressions,
+            position: row.position,
+        })
+        .collect();
+    queries.sort_by(|a, b| {
+        b.clicks
+            .total_cmp(&a.clicks)
+            .then(b.impressions.total_cmp(&a.impressions))
+    });
+    queries.truncate(MAX_QUERIES);
+    queries
+}
+
+// GENERATE AN OPTIMIZATION BRIEF FOR A CRAWLED PAGE
+#[tauri::command]
+pub async fn generate_content_brief(crawl_id: i64, url: String) -> Result<ContentBrief, String> {
+    let settings = load_settings().await?;
+    let provider = AiProvider::from_settings(&settings)?;
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let page = store
+        .get_page(crawl_id, &url)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("{} was not crawled in crawl {}", url, crawl_id))?;
+
+    let rows = store.gsc_rows(crawl_id).await.map_err(|e| e.to_string())?;
+    let queries = page_queries(rows, &url);
+    let links = store.links(crawl_id).await.map_err(|e| e.to_string())?;
+    let outgoing: Vec<String> = links
+        .iter()
+        .filter(|(source, _)| *source == url)
+        .map(|(_, target)| target.clone())
+        .take(MAX_LINKS)
+        .collect();
+    let incoming: Vec<String> = links
+        .iter()
+        .filter(|(_, target)| *target == url)
+        .map(|(source, _)| source.clone())
+        .take(MAX_LINKS)
+        .collect();
+
+    let reply = provider
+        .complete(
+            SYSTEM_PROMPT,
+            &brief_prompt(&page, &queries, &outgoing, &incoming),
+        )
+        .await?;
+    let reply = parse_reply(&reply)
+        .ok_or_else(|| "The AI provider did not answer with a JSON brief".to_string())?;
+
+    let brief = ContentBrief {
+        url,
+        model: provider.model.clone(),
+        created_at: Utc::now(),
+        summary: reply.summary.trim().to_string(),
+        missing_subtopics: reply.missing_subtopics,
+        faq: reply.faq,
+        recommendations: reply.recommendations,
+        queries,
+    };
+    store
+        .save_brief(crawl_id, &brief)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(brief)
+}
+
+// GET THE CONTENT BRIEFS GENERATED FOR A CRAWL
+#[tauri::command]
+pub async fn get_content_briefs(crawl_id: i64) -> Result<Vec<ContentBrief>, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    store.briefs(crawl_id).await.map_err(|e| e.to_string())
+}
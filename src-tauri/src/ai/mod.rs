@@ -0,0 +1,4 @@
+pub mod briefs;
+pub mod ollama;
+pub mod providers;
+pub mod suggestions;
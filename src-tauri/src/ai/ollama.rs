@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use super::providers::OLLAMA_URL;
+use crate::genai::get_ai_model;
+use crate::settings::settings::load_settings;
+
+/// A model installed in the local Ollama server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    /// Bytes on disk
+    pub size: u64,
+    pub modified_at: String,
+    pub family: Option<String>,
+    pub parameter_size: Option<String>,
+    pub quantization: Option<String>,
+    /// The model the AI features currently use
+    pub selected: bool,
+}
+
+/// Progress of a model download, emitted as `ollama_pull_progress`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullProgress {
+    pub model: String,
+    pub status: String,
+    pub completed: Option<u64>,
+    pub total: Option<u64>,
+    pub percent: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct TagDetails {
+    family: Option<String>,
+    parameter_size: Option<String>,
+    quantization_level: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Tag {
+    name: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    modified_at: String,
+    details: Option<TagDetails>,
+}
+
+#[derive(Deserialize)]
+struct Tags {
+    #[serde(default)]
+    models: Vec<Tag>,
+}
+
+#[derive(Deserialize)]
+struct PullLine {
+    #[serde(default)]
+    status: String,
+    total: Option<u64>,
+    completed: Option<u64>,
+    error: Option<String>,
+}
+
+// The Ollama server configured for the AI features, the local default otherwise
+async fn base_url() -> String {
+    match load_settings().await {
+        Ok(settings)
+            if settings.ai_provider.eq_ignore_ascii_case("ollama")
+                && !settings.ai_base_url.trim().is_empty() =>
+        {
+            settings
+                .ai_base_url
+                .trim()
+                .trim_end_matches('/')
+                .to_string()
+        }
+        _ => OLLAMA_URL.to_string(),
+    }
+}
+
+async fn installed_models() -> Result<Vec<Tag>, String> {
+    let url = format!("{}/api/tags", base_url().await);
+    let response = reqwest::get(&url)
+        .await
+        .map_err(|e| format!("Ollama is not reachable at {}: {}", url, e))?;
+    let tags: Tags = response
+        .error_for_status()
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse the Ollama model list: {}", e))?;
+    Ok(tags.models)
+}
+
+// LIST THE MODELS INSTALLED IN OLLAMA
+#[tauri::command]
+pub async fn list_ollama_models() -> Result<Vec<OllamaModel>, String> {
+    let selected = get_ai_model();
+    let mut models: Vec<OllamaModel> = installed_models()
+        .await?
+        .into_iter()
+        .map(|tag| {
+            let details = tag.details;
+            OllamaModel {
+                selected: tag.name == selected,
+                family: details.as_ref().and_then(|d| d.family.clone()),
+                parameter_size: details.as_ref().and_then(|d| d.parameter_size.clone()),
+                quantization: details.and_then(|d| d.quantization_level),
+                name: tag.name,
+                size: tag.size,
+                modified_at: tag.modified_at,
+            }
+        })
+        .collect();
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(models)
+}
+
+// DOWNLOAD A MODEL INTO OLLAMA, REPORTING PROGRESS AS EVENTS
+#[tauri::command]
+pub async fn pull_ollama_model(model: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let url = format!("{}/api/pull", base_url().await);
+    // Without a timeout, large models take as long as the connection needs
+    let mut response = reqwest::Client::new()
+        .post(&url)
+        .json(&serde_json::json!({ "model": model, "stream": true }))
+        .send()
+        .await
+        .map_err(|e| format!("Ollama is not reachable at {}: {}", url, e))?
+        .error_for_status()
+        .map_err(|e| e.to_string())?;
+
+    // The reply is one JSON object per line, lines may be split across chunks
+    let mut buffer = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        buffer.extend_from_slice(&chunk);
+        while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            let Ok(line) = serde_json::from_slice::<PullLine>(&line) else {
+                continue;
+            };
+            if let Some(error) = line.error {
+                return Err(format!("Failed to pull {}: {}", model, error));
+            }
+
+            let progress = PullProgress {
+                model: model.clone(),
+                percent: match (line.completed, line.total) {
+                    (Some(completed), Some(total)) if total > 0 => {
+                        Some((completed as f64 / total as f64 * 1000.0).round() / 10.0)
+                    }
+                    _ => None,
+                },
+                status: line.status,
+                completed: line.completed,
+                total: line.total,
+            };
+            if let Err(err) = app_handle.emit("ollama_pull_progress", &progress) {
+                eprintln!("Failed to emit Ollama pull progress: {}", err);
+            }
+        }
+    }
+    Ok(())
+}
+
+// USE AN INSTALLED OLLAMA MODEL FOR THE AI FEATURES
+#[tauri::command]
+pub async fn select_ollama_model(model: String) -> Result<String, String> {
+    if !installed_models()
+        .await?
+        .iter()
+        .any(|tag| tag.name == model)
+    {
+        return Err(format!(
+            "{} is not installed in Ollama, pull it first",
+            model
+        ));
+    }
+    crate::globals::actions::ai_model_selected("ollama".to_string());
+    crate::commands::write_model_to_disk(model)
+}
@@ -0,0 +1,185 @@
+use std::time::{Duration, Instant};
+
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::domain_crawler::rate_limiter::HostRateLimiter;
+use crate::settings::settings::Settings;
+
+pub(crate) const OLLAMA_URL: &str = "http://localhost:11434";
+const OPENAI_URL: &str = "https://api.openai.com/v1";
+// Local models can take minutes on long prompts
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+const MAX_ATTEMPTS: usize = 3;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ProviderKind {
+    /// Any server speaking the OpenAI chat completions API
+    OpenAiCompatible,
+    Ollama,
+}
+
+#[derive(Serialize)]
+struct Message<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+struct ChatBody<'a> {
+    model: &'a str,
+    messages: Vec<Message<'a>>,
+    stream: bool,
+    temperature: f32,
+}
+
+#[derive(Deserialize)]
+struct ReplyMessage {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct OpenAiChoice {
+    message: ReplyMessage,
+}
+
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    choices: Vec<OpenAiChoice>,
+}
+
+#[derive(Deserialize)]
+struct OllamaResponse {
+    message: ReplyMessage,
+}
+
+/// A chat model behind one of the supported APIs, rate limited to the configured pace.
+pub struct AiProvider {
+    pub kind: ProviderKind,
+    pub model: String,
+    endpoint: Url,
+    api_key: Option<String>,
+    client: Client,
+    limiter: HostRateLimiter,
+}
+
+impl AiProvider {
+    /// Builds the provider from the `ai_*` settings.
+    ///
+    /// An empty base URL falls back to the local Ollama server or the OpenAI API, and an
+    /// empty Ollama model to the one selected for the AI features of the app.
+    pub fn from_settings(settings: &Settings) -> Result<Self, String> {
+        let kind = match settings.ai_provider.trim().to_lowercase().as_str() {
+            "ollama" => ProviderKind::Ollama,
+            "openai" | "openai_compatible" => ProviderKind::OpenAiCompatible,
+            other => return Err(format!("Unknown AI provider: {}", other)),
+        };
+
+        let base_url = match settings.ai_base_url.trim() {
+            "" if kind == ProviderKind::Ollama => OLLAMA_URL,
+            "" => OPENAI_URL,
+            url => url,
+        };
+        let path = match kind {
+            ProviderKind::Ollama => "api/chat",
+            ProviderKind::OpenAiCompatible => "chat/completions",
+        };
+        let endpoint = Url::parse(&format!("{}/", base_url.trim_end_matches('/')))
+            .and_then(|base| base.join(path))
+            .map_err(|e| format!("Invalid AI base URL {}: {}", base_url, e))?;
+
+        let model = match settings.ai_model.trim() {
+            "" if kind == ProviderKind::Ollama => crate::genai::get_ai_model(),
+            "" => return Err("Set the AI model to use with the OpenAI compatible API".to_string()),
+            model => model.to_string(),
+        };
+
+        let api_key = Some(settings.ai_api_key.trim().to_string())
+            .filter(|key| !key.is_empty())
+            .or_else(|| std::env::var("OPENAI_API_KEY").ok());
+
+        let interval =
+            Duration::from_secs_f64(60.0 / settings.ai_requests_per_minute.max(1) as f64);
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            kind,
+            model,
+            endpoint,
+            api_key: api_key.filter(|_| kind == ProviderKind::OpenAiCompatible),
+            client,
+            limiter: HostRateLimiter::new(interval, 1),
+        })
+    }
+
+    /// Sends one system and user message and returns the text of the reply.
+    ///
+    /// Rate limited answers are retried once the limiter lets the next request through.
+    pub async fn complete(&self, system: &str, prompt: &str) -> Result<String, String> {
+        let body = ChatBody {
+            model: &self.model,
+            messages: vec![
+                Message {
+                    role: "system",
+                    content: system,
+                },
+                Message {
+                    role: "user",
+                    content: prompt,
+                },
+            ],
+            stream: false,
+            temperature: 0.4,
+        };
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            self.limiter.acquire(&self.endpoint).await;
+            let mut request = self.client.post(self.endpoint.clone()).json(&body);
+            if let Some(key) = &self.api_key {
+                request = request.bearer_auth(key);
+            }
+
+            let started = Instant::now();
+            let response = request
+                .send()
+                .await
+                .map_err(|e| format!("Failed to reach the AI provider: {}", e))?;
+            // The limiter backs off and holds the next request for any Retry-After
+            self.limiter
+                .record_response(&self.endpoint, started.elapsed(), &response)
+                .await;
+
+            let status = response.status();
+            if status == StatusCode::TOO_MANY_REQUESTS && attempt < MAX_ATTEMPTS {
+                continue;
+            }
+            if !status.is_success() {
+                let text = response.text().await.unwrap_or_default();
+                return Err(format!("AI provider answered {}: {}", status, text));
+            }
+
+            let content = match self.kind {
+                ProviderKind::Ollama => response
+                    .json::<OllamaResponse>()
+                    .await
+                    .map(|reply| reply.message.content),
+                ProviderKind::OpenAiCompatible => {
+                    response.json::<OpenAiResponse>().await.map(|reply| {
+                        reply
+                            .choices
+                            .into_iter()
+                            .next()
+                            .map(|choice| choice.message.content)
+                            .unwrap_or_default()
+                    })
+                }
+            };
+            return content.map_err(|e| format!("Failed to parse the AI reply: {}", e));
+        }
+        Err("The AI provider kept rate limiting the requests".to_string())
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::providers::AiProvider;
+use crate::domain_crawler::helpers::title_description::{
+    SnippetIssue, DESCRIPTION_MAX_CHARS, DESCRIPTION_MIN_CHARS, TITLE_MAX_CHARS, TITLE_MIN_CHARS,
+};
+use crate::domain_crawler::results_store::ResultsStore;
+use crate::domain_crawler::title_description_audit::{PageSnippetIssues, TitleDescriptionReport};
+use crate::settings::settings::load_settings;
+
+const SYSTEM_PROMPT: &str =
+    "You are an SEO copywriter. You write page titles and meta descriptions \
+that describe the page accurately, include its main keyword and make searchers want to click. \
+Write in the language of the page. Reply with JSON only, no backticks and nothing else.";
+
+/// A title and meta description proposed for a page with snippet issues.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetSuggestion {
+    /// None when the current title has no issue
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub model: String,
+}
+
+#[derive(Deserialize)]
+struct SuggestionReply {
+    url: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+fn needs_title(issues: &[SnippetIssue]) -> bool {
+    issues.iter().any(|issue| {
+        matches!(
+            issue,
+            SnippetIssue::MissingTitle
+                | SnippetIssue::TitleTooShort
+                | SnippetIssue::TitleTooLong
+                | SnippetIssue::MultipleTitles
+                | SnippetIssue::DuplicateTitle
+        )
+    })
+}
+
+fn needs_description(issues: &[SnippetIssue]) -> bool {
+    issues.iter().any(|issue| {
+        matches!(
+            issue,
+            SnippetIssue::MissingDescription
+                | SnippetIssue::DescriptionTooShort
+                | SnippetIssue::DescriptionTooLong
+                | SnippetIssue::MultipleDescriptions
+                | SnippetIssue::DuplicateDescription
+        )
+    })
+}
+
+fn batch_prompt(pages: &[&PageSnippetIssues]) -> String {
+    let pages: Vec<serde_json::Value> = pages
+        .iter()
+        .map(|page| {
+            serde_json::json!({
+                "url": page.url,
+                "current_title": page.title,
+                "current_description": page.description,
+                "h1": page.h1,
+                "issues": page.issues,
+                "rewrite_title": needs_title(&page.issues),
+                "rewrite_description": needs_description(&page.issues),
+            })
+        })
+        .collect();
+    format!(
+        "Rewrite the title and meta description of these pages where asked. Titles are {}-{} \
+characters and descriptions {}-{} characters, pages sharing a title or description must get \
+distinct ones. Reply with a JSON array of {{\"url\": \"\", \"title\": \"\", \"description\": \"\"}}, \
+one object per page, leaving out fields that were not asked for. The pages are:\n{}",
+        TITLE_MIN_CHARS,
+        TITLE_MAX_CHARS,
+        DESCRIPTION_MIN_CHARS,
+        DESCRIPTION_MAX_CHARS,
+        serde_json::Value::Array(pages)
+    )
+}
+
+// Models wrap the JSON in prose or code fences despite the prompt
+fn parse_replies(reply: &str) -> Option<Vec<SuggestionReply>> {
+    let start = reply.find('[')?;
+    let end = reply.rfind(']')?;
+    serde_json::from_str(reply.get(start..=end)?).ok()
+}
+
+fn clean(text: Option<String>) -> Option<String> {
+    text.map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|text| !text.is_empty())
+}
+
+/// Asks the provider for new titles and descriptions, `batch_size` pages per request.
+///
+/// Pages the model left out of its reply get no suggestion; a failed batch is logged and
+/// the others go ahead.
+pub async fn suggest_snippets(
+    provider: &AiProvider,
+    pages: &[&PageSnippetIssues],
+    batch_size: usize,
+) -> HashMap<String, SnippetSuggestion> {
+    let mut suggestions = HashMap::new();
+    for batch in pages.chunks(batch_size.max(1)) {
+        let reply = match provider.complete(SYSTEM_PROMPT, &batch_prompt(batch)).await {
+            Ok(reply) => reply,
+            Err(e) => {
+                eprintln!("Failed to generate snippet suggestions: {}", e);
+                continue;
+            }
+        };
+        let Some(replies) = parse_replies(&reply) else {
+            eprintln!("The AI provider did not answer with a JSON array");
+            continue;
+        };
+
+        for reply in replies {
+            let Some(page) = batch.iter().find(|page| page.url == reply.url) else {
+                continue;
+            };
+            let suggestion = SnippetSuggestion {
+                title: clean(reply.title).filter(|_| needs_title(&page.issues)),
+                description: clean(reply.description).filter(|_| needs_description(&page.issues)),
+                model: provider.model.clone(),
+            };
+            if suggestion.title.is_some() || suggestion.description.is_some() {
+                suggestions.insert(page.url.clone(), suggestion);
+            }
+        }
+    }
+    suggestions
+}
+
+/// Generates suggestions for the flagged pages of a crawl and stores them in its
+/// title and description report.
+///
+/// Without `urls` every page still lacking a suggestion is sent; with them only those pages,
+/// replacing any earlier suggestion.
+#[tauri::command]
+pub async fn generate_snippet_suggestions(
+    crawl_id: i64,
+    urls: Option<Vec<String>>,
+) -> Result<TitleDescriptionReport, String> {
+    let settings = load_settings().await?;
+    let provider = AiProvider::from_settings(&settings)?;
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let mut report: TitleDescriptionReport = store
+        .report(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| {
+            format!(
+                "No title and description report stored for crawl {}",
+                crawl_id
+            )
+        })?;
+
+    let pending: Vec<&PageSnippetIssues> = report
+        .pages
+        .iter()
+        .filter(|page| needs_title(&page.issues) || needs_description(&page.issues))
+        .filter(|page| match &urls {
+            Some(urls) => urls.contains(&page.url),
+            None => page.suggestion.is_none(),
+        })
+        .collect();
+    if pending.is_empty() {
+        return Ok(report);
+    }
+
+    let mut suggestions = suggest_snippets(&provider, &pending, settings.ai_batch_size).await;
+    for page in &mut report.pages {
+        if let Some(suggestion) = suggestions.remove(&page.url) {
+            page.suggestion = Some(suggestion);
+        }
+    }
+
+    store
+        .save_report(crawl_id, &report)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(report)
+}
@@ -0,0 +1,399 @@
+use actix_web::dev::ServerHandle;
+use actix_web::http::StatusCode;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::Manager;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::domain_crawler::crawl_control;
+use crate::domain_crawler::crawl_progress;
+use crate::domain_crawler::domain_commands;
+use crate::domain_crawler::results_store::{PageQuery, ResultsStore};
+use crate::projects::registry::ProjectRoots;
+use crate::settings::settings::override_settings;
+use crate::AppState;
+
+// Only scripts on this machine may drive the crawler
+const BIND_ADDRESS: &str = "127.0.0.1";
+
+// The running server and the port it listens on
+static SERVER: Lazy<Mutex<Option<(ServerHandle, u16)>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Clone)]
+struct ApiState {
+    app_handle: tauri::AppHandle,
+    token: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiServerStatus {
+    pub running: bool,
+    pub address: Option<String>,
+    pub token: String,
+}
+
+/// A crawl to start: a domain to follow links on, or a list of URLs and a sitemap.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct StartCrawl {
+    domain: Option<String>,
+    urls: Vec<String>,
+    sitemap: Option<String>,
+    profile: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PageUrl {
+    url: String,
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> HttpResponse {
+    HttpResponse::build(status).json(json!({ "error": message.into() }))
+}
+
+// Compares every byte so the time taken does not give away how much of the token matched
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+// The token comes as a bearer token or in the X-Api-Token header
+fn authorize(req: &HttpRequest, state: &ApiState) -> Result<(), HttpResponse> {
+    let headers = req.headers();
+    let given = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| {
+            headers
+                .get("x-api-token")
+                .and_then(|value| value.to_str().ok())
+        });
+    match given {
+        Some(given) if same_token(given.trim(), &state.token) => Ok(()),
+        _ => Err(error(
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid API token",
+        )),
+    }
+}
+
+async fn status(req: HttpRequest, state: web::Data<ApiState>) -> HttpResponse {
+    if let Err(response) = authorize(&req, &state) {
+        return response;
+    }
+    HttpResponse::Ok().json(json!({
+        "status": crawl_control::status(),
+        "progress": crawl_progress::latest(),
+    }))
+}
+
+async fn start_crawl(
+    req: HttpRequest,
+    state: web::Data<ApiState>,
+    body: web::Json<StartCrawl>,
+) -> HttpResponse {
+    if let Err(response) = authorize(&req, &state) {
+        return response;
+    }
+    let body = body.into_inner();
+    let target = match (&body.domain, body.urls.is_empty() && body.sitemap.is_none()) {
+        (Some(domain), true) => domain.trim().to_string(),
+        (Some(_), false) => {
+            return error(
+                StatusCode::BAD_REQUEST,
+                "Send either a domain or a list of URLs, not both",
+            )
+        }
+        (None, _) => match domain_commands::url_list(&body.urls, body.sitemap.clone()) {
+            Ok((first, _)) => first,
+            Err(e) => return error(StatusCode::BAD_REQUEST, e),
+        },
+    };
+
+    // Taken before answering, a second request sees the conflict instead of racing the first
+    let crawl = match crawl_control::try_start() {
+        Ok(crawl) => crawl,
+        Err(e) => return error(StatusCode::CONFLICT, e),
+    };
+
+    // The crawl outlives the request, progress is read through the status endpoint
+    let app_handle = state.app_handle.clone();
+    let settings = app_handle.state::<AppState>().settings.read().await.clone();
+    let roots = match ProjectRoots::active() {
+        Ok(roots) => roots,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+    tauri::async_runtime::spawn(async move {
+        let result = match body.domain {
+            Some(domain) => {
+                domain_commands::crawl_with_profile(
+                    &domain,
+                    app_handle.into(),
+                    &roots,
+                    settings,
+                    false,
+                    body.profile.as_deref(),
+                    crawl,
+                )
+                .await
+            }
+            None => {
+                domain_commands::list_crawl(
+                    &body.urls,
+                    body.sitemap,
+                    app_handle.into(),
+                    &roots,
+                    settings,
+                    body.profile.as_deref(),
+                    crawl,
+                )
+                .await
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("API crawl failed: {}", e);
+        }
+    });
+    HttpResponse::Accepted().json(json!({ "started": target }))
+}
+
+async fn cancel_crawl(req: HttpRequest, state: web::Data<ApiState>) -> HttpResponse {
+    if let Err(response) = authorize(&req, &state) {
+        return response;
+    }
+    match crawl_control::cancel_crawl(state.app_handle.clone()).await {
+        Ok(()) => HttpResponse::Ok().json(json!({ "status": crawl_control::status() })),
+        Err(e) => error(StatusCode::CONFLICT, e),
+    }
+}
+
+async fn open_store() -> Result<ResultsStore, HttpResponse> {
+    ResultsStore::open()
+        .await
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+async fn list_crawls(req: HttpRequest, state: web::Data<ApiState>) -> HttpResponse {
+    if let Err(response) = authorize(&req, &state) {
+        return response;
+    }
+    let store = match open_store().await {
+        Ok(store) => store,
+        Err(response) => return response,
+    };
+    match store.list_crawls().await {
+        Ok(crawls) => HttpResponse::Ok().json(crawls),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn get_crawl(
+    req: HttpRequest,
+    state: web::Data<ApiState>,
+    crawl_id: web::Path<i64>,
+) -> HttpResponse {
+    if let Err(response) = authorize(&req, &state) {
+        return response;
+    }
+    let store = match open_store().await {
+        Ok(store) => store,
+        Err(response) => return response,
+    };
+    match store.crawl(*crawl_id).await {
+        Ok(Some(crawl)) => HttpResponse::Ok().json(crawl),
+        Ok(None) => error(
+            StatusCode::NOT_FOUND,
+            format!("Crawl {} not found", crawl_id),
+        ),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+// The results table, paged and filtered with the same options as the UI
+async fn crawl_pages(
+    req: HttpRequest,
+    state: web::Data<ApiState>,
+    crawl_id: web::Path<i64>,
+    query: web::Query<PageQuery>,
+) -> HttpResponse {
+    if let Err(response) = authorize(&req, &state) {
+        return response;
+    }
+    let store = match open_store().await {
+        Ok(store) => store,
+        Err(response) => return response,
+    };
+    match store.query_pages(*crawl_id, query.into_inner()).await {
+        Ok(slice) => HttpResponse::Ok().json(slice),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn crawl_page(
+    req: HttpRequest,
+    state: web::Data<ApiState>,
+    crawl_id: web::Path<i64>,
+    query: web::Query<PageUrl>,
+) -> HttpResponse {
+    if let Err(response) = authorize(&req, &state) {
+        return response;
+    }
+    let store = match open_store().await {
+        Ok(store) => store,
+        Err(response) => return response,
+    };
+    match store.get_page(*crawl_id, &query.url).await {
+        Ok(Some(page)) => HttpResponse::Ok().json(page),
+        Ok(None) => error(
+            StatusCode::NOT_FOUND,
+            format!("{} is not in the crawl", query.url),
+        ),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// Starts the API server on the port from the settings, creating the token on first use.
+pub async fn start(app_handle: tauri::AppHandle) -> Result<ApiServerStatus, String> {
+    let mut server = SERVER.lock().await;
+    if server.is_some() {
+        return Err("The API server is already running".to_string());
+    }
+
+    let mut settings = app_handle.state::<AppState>().settings.read().await.clone();
+    if settings.api_server_token.is_empty() {
+        settings = override_settings(&format!(
+            "api_server_token = \"{}\"",
+            Uuid::new_v4().simple()
+        ))
+        .await?;
+        *app_handle.state::<AppState>().settings.write().await = settings.clone();
+    }
+
+    let state = web::Data::new(ApiState {
+        app_handle,
+        token: settings.api_server_token.clone(),
+    });
+    let port = settings.api_server_port;
+    let http_server = HttpServer::new(move || {
+        App::new().app_data(state.clone()).service(
+            web::scope("/api")
+                .route("/status", web::get().to(status))
+                .route("/crawls", web::get().to(list_crawls))
+                .route("/crawls", web::post().to(start_crawl))
+                .route("/crawls/cancel", web::post().to(cancel_crawl))
+                .route("/crawls/{id}", web::get().to(get_crawl))
+                .route("/crawls/{id}/pages", web::get().to(crawl_pages))
+                .route("/crawls/{id}/page", web::get().to(crawl_page)),
+        )
+    })
+    .workers(1)
+    .bind((BIND_ADDRESS, port))
+    .map_err(|e| format!("Failed to bind {}:{}: {}", BIND_ADDRESS, port, e))?
+    .run();
+
+    *server = Some((http_server.handle(), port));
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = http_server.await {
+            eprintln!("API server stopped: {}", e);
+        }
+    });
+    println!("API server listening on {}:{}", BIND_ADDRESS, port);
+
+    Ok(ApiServerStatus {
+        running: true,
+        address: Some(format!("http://{}:{}/api", BIND_ADDRESS, port)),
+        token: settings.api_server_token,
+    })
+}
+
+/// Starts the server at launch when it was left enabled.
+pub fn start_if_enabled(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let enabled = app_handle
+            .state::<AppState>()
+            .settings
+            .read()
+            .await
+            .api_server_enabled;
+        if enabled {
+            if let Err(e) = start(app_handle).await {
+                eprintln!("Failed to start the API server: {}", e);
+            }
+        }
+    });
+}
+
+async fn stop() {
+    if let Some((handle, _)) = SERVER.lock().await.take() {
+        handle.stop(true).await;
+    }
+}
+
+// START THE LOCAL API SERVER, AND AGAIN ON EVERY LAUNCH UNTIL IT IS STOPPED
+#[tauri::command]
+pub async fn start_api_server(
+    port: Option<u16>,
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<ApiServerStatus, String> {
+    let mut updates = "api_server_enabled = true".to_string();
+    if let Some(port) = port {
+        updates.push_str(&format!("\napi_server_port = {}", port));
+    }
+    *settings_state.settings.write().await = override_settings(&updates).await?;
+    start(app_handle).await
+}
+
+#[tauri::command]
+pub async fn stop_api_server(settings_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    stop().await;
+    *settings_state.settings.write().await =
+        override_settings("api_server_enabled = false").await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_api_server_status(
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<ApiServerStatus, String> {
+    let port = SERVER.lock().await.as_ref().map(|(_, port)| *port);
+    Ok(ApiServerStatus {
+        running: port.is_some(),
+        address: port.map(|port| format!("http://{}:{}/api", BIND_ADDRESS, port)),
+        token: settings_state
+            .settings
+            .read()
+            .await
+            .api_server_token
+            .clone(),
+    })
+}
+
+// REPLACE THE API TOKEN, RESTARTING THE SERVER SO THE OLD ONE STOPS WORKING
+#[tauri::command]
+pub async fn regenerate_api_token(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<ApiServerStatus, String> {
+    let settings = override_settings(&format!(
+        "api_server_token = \"{}\"",
+        Uuid::new_v4().simple()
+    ))
+    .await?;
+    *settings_state.settings.write().await = settings;
+
+    let running = SERVER.lock().await.is_some();
+    if running {
+        stop().await;
+        return start(app_handle).await;
+    }
+    get_api_server_status(settings_state).await
+}
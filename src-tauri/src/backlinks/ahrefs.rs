@@ -0,0 +1,105 @@
+use chrono::Utc;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde_json::Value;
+
+use super::provider::{send_json, Backlink, BacklinkProvider, BacklinkSummary, ProviderConfig};
+
+/// The Ahrefs API v3, or any endpoint that answers in its format.
+pub struct Ahrefs(pub ProviderConfig);
+
+impl Ahrefs {
+    pub const ENDPOINT: &'static str = "https://api.ahrefs.com/v3";
+
+    async fn get(&self, path: &str, query: &[(&str, &str)]) -> Result<Value, String> {
+        let request = self
+            .0
+            .client
+            .get(format!("{}/site-explorer/{}", self.0.endpoint, path))
+            .bearer_auth(&self.0.api_key)
+            .query(&[("mode", "domain")])
+            .query(query);
+        send_json(self.name(), request).await
+    }
+}
+
+impl BacklinkProvider for Ahrefs {
+    fn name(&self) -> &'static str {
+        "Ahrefs"
+    }
+
+    fn summary<'a>(&'a self, domain: &'a str) -> BoxFuture<'a, Result<BacklinkSummary, String>> {
+        async move {
+            let date = Utc::now().format("%Y-%m-%d").to_string();
+            let query = [("target", domain), ("date", date.as_str())];
+            let stats = self.get("backlinks-stats", &query).await?;
+            let rating = self.get("domain-rating", &query).await?;
+            Ok(BacklinkSummary {
+                provider: self.name().to_string(),
+                domain: domain.to_string(),
+                referring_domains: stats
+                    .pointer("/metrics/live_refdomains")
+                    .and_then(Value::as_u64),
+                backlinks: stats.pointer("/metrics/live").and_then(Value::as_u64),
+                authority: rating
+                    .pointer("/domain_rating/domain_rating")
+                    .and_then(Value::as_f64),
+            })
+        }
+        .boxed()
+    }
+
+    fn backlinks<'a>(
+        &'a self,
+        domain: &'a str,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<Backlink>, String>> {
+        async move {
+            let limit = limit.to_string();
+            let body = self
+                .get(
+                    "all-backlinks",
+                    &[
+                        ("target", domain),
+                        ("limit", limit.as_str()),
+                        ("history", "live"),
+                        (
+                            "select",
+                            "url_from,url_to,anchor,domain_rating_source,is_dofollow,first_seen",
+                        ),
+                        ("order_by", "domain_rating_source:desc"),
+                    ],
+                )
+                .await?;
+            Ok(body
+                .get("backlinks")
+                .and_then(Value::as_array)
+                .map(|rows| {
+                    rows.iter()
+                        .filter_map(|row| {
+                            Some(Backlink {
+                                source_url: row.get("url_from")?.as_str()?.to_string(),
+                                target_url: row.get("url_to")?.as_str()?.to_string(),
+                                anchor: row
+                                    .get("anchor")
+                                    .and_then(Value::as_str)
+                                    .filter(|anchor| !anchor.is_empty())
+                                    .map(str::to_string),
+                                nofollow: row.get("is_dofollow").and_then(Value::as_bool)
+                                    == Some(false),
+                                source_authority: row
+                                    .get("domain_rating_source")
+                                    .and_then(Value::as_f64),
+                                first_seen: row
+                                    .get("first_seen")
+                                    .and_then(Value::as_str)
+                                    .map(str::to_string),
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default())
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::domain_crawler::results_store::ResultsStore;
+use crate::AppState;
+
+use super::provider::{self, Backlink, BacklinkSummary};
+
+const TOP_BACKLINKS: usize = 10;
+
+/// What was pulled for a crawl, with the vendor's totals for the domain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacklinkFetch {
+    pub crawl_id: i64,
+    pub summary: BacklinkSummary,
+    pub stored: usize,
+}
+
+/// Backlinks of a URL joined onto its crawl result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacklinkPageMetrics {
+    pub url: String,
+    pub crawled: bool,
+    pub status_code: Option<u16>,
+    pub backlinks: usize,
+    pub referring_domains: usize,
+    pub nofollow: usize,
+    /// Strongest sources first
+    pub top_backlinks: Vec<Backlink>,
+}
+
+// Vendors drop the scheme or "www." on some URLs, links are matched without them
+fn link_key(url: &str) -> String {
+    let absolute = if url.contains("://") {
+        url.to_string()
+    } else {
+        format!("https://{}", url)
+    };
+    match Url::parse(&absolute) {
+        Ok(parsed) => {
+            let host = parsed.host_str().unwrap_or_default().to_lowercase();
+            let mut key = format!(
+                "{}{}",
+                host.trim_start_matches("www."),
+                parsed.path().trim_end_matches('/')
+            );
+            if let Some(query) = parsed.query() {
+                key.push('?');
+                key.push_str(query);
+            }
+            key
+        }
+        Err(_) => url.to_string(),
+    }
+}
+
+fn source_domain(url: &str) -> String {
+    let key = link_key(url);
+    key.split(['/', '?']).next().unwrap_or_default().to_string()
+}
+
+/// Groups backlinks by target and joins them onto the crawled pages.
+pub fn join_with_crawl(links: &[Backlink], pages: &[(String, u16)]) -> Vec<BacklinkPageMetrics> {
+    let mut metrics: HashMap<String, BacklinkPageMetrics> = HashMap::new();
+    for (url, status_code) in pages {
+        metrics.insert(
+            link_key(url),
+            BacklinkPageMetrics {
+                url: url.clone(),
+                crawled: true,
+                status_code: Some(*status_code),
+                backlinks: 0,
+                referring_domains: 0,
+                nofollow: 0,
+                top_backlinks: Vec::new(),
+            },
+        );
+    }
+
+    let mut domains: HashMap<String, HashSet<String>> = HashMap::new();
+    for link in links {
+        let key = link_key(&link.target_url);
+        let page = metrics
+            .entry(key.clone())
+            .or_insert_with(|| BacklinkPageMetrics {
+                url: link.target_url.clone(),
+                crawled: false,
+                status_code: None,
+                backlinks: 0,
+                referring_domains: 0,
+                nofollow: 0,
+                top_backlinks: Vec::new(),
+            });
+        page.backlinks += 1;
+        if link.nofollow {
+            page.nofollow += 1;
+        }
+        page.top_backlinks.push(link.clone());
+        domains
+            .entry(key)
+            .or_default()
+            .insert(source_domain(&link.source_url));
+    }
+
+    let mut joined: Vec<BacklinkPageMetrics> = metrics
+        .into_iter()
+        .map(|(key, mut page)| {
+            page.referring_domains = domains.get(&key).map_or(0, HashSet::len);
+            page.top_backlinks.sort_by(|a, b| {
+                b.source_authority
+                    .unwrap_or(0.0)
+                    .total_cmp(&a.source_authority.unwrap_or(0.0))
+                    .then(a.nofollow.cmp(&b.nofollow))
+            });
+            page.top_backlinks.truncate(TOP_BACKLINKS);
+            page
+        })
+        .collect();
+
+    joined.sort_by(|a, b| {
+        b.referring_domains
+            .cmp(&a.referring_domains)
+            .then(b.backlinks.cmp(&a.backlinks))
+            .then_with(|| a.url.cmp(&b.url))
+    });
+    joined
+}
+
+// PULL BACKLINKS FROM THE CONFIGURED PROVIDER AND STORE THEM WITH A CRAWL
+#[tauri::command]
+pub async fn fetch_backlinks_for_crawl(
+    crawl_id: i64,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<BacklinkFetch, String> {
+    let settings = settings_state.settings.read().await.clone();
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let crawl = store
+        .crawl(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Crawl {} not found", crawl_id))?;
+    let domain = source_domain(&crawl.domain);
+
+    let provider = provider::from_settings(&settings)?;
+    let summary = provider.summary(&domain).await?;
+    let links = provider
+        .backlinks(&domain, settings.backlinks_limit.max(1) as usize)
+        .await?;
+
+    store
+        .replace_backlinks(crawl_id, &links)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    println!(
+        "Stored {} {} backlinks for crawl {}",
+        links.len(),
+        provider.name(),
+        crawl_id
+    );
+    Ok(BacklinkFetch {
+        crawl_id,
+        summary,
+        stored: links.len(),
+    })
+}
+
+// GET THE BACKLINKS OF THE CRAWLED PAGES
+#[tauri::command]
+pub async fn get_backlink_page_metrics(crawl_id: i64) -> Result<Vec<BacklinkPageMetrics>, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let links = store.backlinks(crawl_id).await.map_err(|e| e.to_string())?;
+    let pages: Vec<(String, u16)> = store
+        .page_rows(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|row| (row.url, row.status_code))
+        .collect();
+
+    Ok(join_with_crawl(&links, &pages))
+}
@@ -0,0 +1,109 @@
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde_json::Value;
+
+use super::provider::{send_json, Backlink, BacklinkProvider, BacklinkSummary, ProviderConfig};
+
+pub struct Majestic(pub ProviderConfig);
+
+impl Majestic {
+    pub const ENDPOINT: &'static str = "https://api.majestic.com/api/json";
+
+    // Majestic answers 200 and reports failures in the body
+    async fn command(&self, query: &[(&str, &str)]) -> Result<Value, String> {
+        let request = self
+            .0
+            .client
+            .get(&self.0.endpoint)
+            .query(&[
+                ("app_api_key", self.0.api_key.as_str()),
+                ("datasource", "fresh"),
+            ])
+            .query(query);
+        let body = send_json(self.name(), request).await?;
+        match body.get("Code").and_then(Value::as_str) {
+            Some("OK") => Ok(body),
+            code => Err(format!(
+                "Majestic answered {}: {}",
+                code.unwrap_or("no code"),
+                body.get("ErrorMessage")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+            )),
+        }
+    }
+}
+
+fn table<'a>(body: &'a Value, name: &str) -> Vec<&'a Value> {
+    body.pointer(&format!("/DataTables/{}/Data", name))
+        .and_then(Value::as_array)
+        .map(|rows| rows.iter().collect())
+        .unwrap_or_default()
+}
+
+impl BacklinkProvider for Majestic {
+    fn name(&self) -> &'static str {
+        "Majestic"
+    }
+
+    fn summary<'a>(&'a self, domain: &'a str) -> BoxFuture<'a, Result<BacklinkSummary, String>> {
+        async move {
+            let body = self
+                .command(&[
+                    ("cmd", "GetIndexItemInfo"),
+                    ("items", "1"),
+                    ("item0", domain),
+                ])
+                .await?;
+            let row = table(&body, "Results").into_iter().next();
+            Ok(BacklinkSummary {
+                provider: self.name().to_string(),
+                domain: domain.to_string(),
+                referring_domains: row.and_then(|r| r.get("RefDomains")?.as_u64()),
+                backlinks: row.and_then(|r| r.get("ExtBackLinks")?.as_u64()),
+                authority: row.and_then(|r| r.get("TrustFlow")?.as_f64()),
+            })
+        }
+        .boxed()
+    }
+
+    fn backlinks<'a>(
+        &'a self,
+        domain: &'a str,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<Backlink>, String>> {
+        async move {
+            let count = limit.to_string();
+            let body = self
+                .command(&[
+                    ("cmd", "GetBackLinkData"),
+                    ("item", domain),
+                    ("Count", &count),
+                    // Leaves out links that were deleted since they were found
+                    ("Mode", "1"),
+                ])
+                .await?;
+            Ok(table(&body, "BackLinks")
+                .into_iter()
+                .filter_map(|row| {
+                    Some(Backlink {
+                        source_url: row.get("SourceURL")?.as_str()?.to_string(),
+                        target_url: row.get("TargetURL")?.as_str()?.to_string(),
+                        anchor: row
+                            .get("AnchorText")
+                            .and_then(Value::as_str)
+                            .filter(|anchor| !anchor.is_empty())
+                            .map(str::to_string),
+                        nofollow: row.get("FlagNoFollow").and_then(Value::as_u64) == Some(1),
+                        source_authority: row.get("SourceTrustFlow").and_then(Value::as_f64),
+                        first_seen: row
+                            .get("FirstIndexedDate")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                    })
+                })
+                .collect())
+        }
+        .boxed()
+    }
+}
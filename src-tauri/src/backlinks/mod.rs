@@ -0,0 +1,5 @@
+pub mod ahrefs;
+pub mod join;
+pub mod majestic;
+pub mod moz;
+pub mod provider;
@@ -0,0 +1,121 @@
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde_json::{json, Value};
+
+use super::provider::{send_json, Backlink, BacklinkProvider, BacklinkSummary, ProviderConfig};
+
+// The links endpoint returns at most 50 links per request
+const LINKS_PER_REQUEST: usize = 50;
+
+pub struct Moz(pub ProviderConfig);
+
+impl Moz {
+    pub const ENDPOINT: &'static str = "https://lsapi.seomoz.com/v2";
+
+    // The key is the access id and the secret joined by a colon
+    async fn post(&self, path: &str, body: Value) -> Result<Value, String> {
+        let (access_id, secret) = self
+            .0
+            .api_key
+            .split_once(':')
+            .ok_or("The Moz API key must be written as access_id:secret")?;
+        let request = self
+            .0
+            .client
+            .post(format!("{}/{}", self.0.endpoint, path))
+            .basic_auth(access_id, Some(secret))
+            .json(&body);
+        send_json(self.name(), request).await
+    }
+}
+
+impl BacklinkProvider for Moz {
+    fn name(&self) -> &'static str {
+        "Moz"
+    }
+
+    fn summary<'a>(&'a self, domain: &'a str) -> BoxFuture<'a, Result<BacklinkSummary, String>> {
+        async move {
+            let body = self
+                .post("url_metrics", json!({ "targets": [domain] }))
+                .await?;
+            let row = body.pointer("/results/0");
+            Ok(BacklinkSummary {
+                provider: self.name().to_string(),
+                domain: domain.to_string(),
+                referring_domains: row.and_then(|r| r.get("root_domains_to_root_domain")?.as_u64()),
+                backlinks: row.and_then(|r| r.get("external_pages_to_root_domain")?.as_u64()),
+                authority: row.and_then(|r| r.get("domain_authority")?.as_f64()),
+            })
+        }
+        .boxed()
+    }
+
+    fn backlinks<'a>(
+        &'a self,
+        domain: &'a str,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<Backlink>, String>> {
+        async move {
+            let mut links = Vec::new();
+            let mut next_token: Option<String> = None;
+            while links.len() < limit {
+                let mut body = json!({
+                    "target": format!("{}/", domain),
+                    "target_scope": "root_domain",
+                    "filter": "external",
+                    "sort": "source_domain_authority",
+                    "limit": LINKS_PER_REQUEST.min(limit - links.len()),
+                });
+                if let Some(token) = &next_token {
+                    body["next_token"] = json!(token);
+                }
+                let response = self.post("links", body).await?;
+
+                let page: Vec<Backlink> = response
+                    .get("results")
+                    .and_then(Value::as_array)
+                    .map(|rows| {
+                        rows.iter()
+                            .filter_map(|row| {
+                                Some(Backlink {
+                                    source_url: row.pointer("/source/page")?.as_str()?.to_string(),
+                                    target_url: row.pointer("/target/page")?.as_str()?.to_string(),
+                                    anchor: row
+                                        .get("anchor_text")
+                                        .and_then(Value::as_str)
+                                        .filter(|anchor| !anchor.is_empty())
+                                        .map(str::to_string),
+                                    nofollow: row
+                                        .get("nofollow")
+                                        .and_then(Value::as_bool)
+                                        .unwrap_or(false),
+                                    source_authority: row
+                                        .pointer("/source/domain_authority")
+                                        .and_then(Value::as_f64),
+                                    first_seen: row
+                                        .get("date_first_seen")
+                                        .and_then(Value::as_str)
+                                        .map(str::to_string),
+                                })
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let fetched = page.len();
+                links.extend(page);
+
+                next_token = response
+                    .get("next_token")
+                    .and_then(Value::as_str)
+                    .filter(|token| !token.is_empty())
+                    .map(str::to_string);
+                if fetched == 0 || next_token.is_none() {
+                    break;
+                }
+            }
+            Ok(links)
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::settings::Settings;
+
+use super::ahrefs::Ahrefs;
+use super::majestic::Majestic;
+use super::moz::Moz;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// One link from another site to a page of the domain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Backlink {
+    pub source_url: String,
+    pub target_url: String,
+    pub anchor: Option<String>,
+    pub nofollow: bool,
+    /// The vendor's authority score of the linking page or domain, not comparable across vendors
+    pub source_authority: Option<f64>,
+    pub first_seen: Option<String>,
+}
+
+/// Totals a vendor keeps for the whole domain.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BacklinkSummary {
+    pub provider: String,
+    pub domain: String,
+    pub referring_domains: Option<u64>,
+    pub backlinks: Option<u64>,
+    pub authority: Option<f64>,
+}
+
+/// A backlink API. Vendors differ in endpoints and fields, not in what they answer.
+pub trait BacklinkProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn summary<'a>(&'a self, domain: &'a str) -> BoxFuture<'a, Result<BacklinkSummary, String>>;
+
+    /// Up to `limit` backlinks of the domain, strongest sources first where the API sorts.
+    fn backlinks<'a>(
+        &'a self,
+        domain: &'a str,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Vec<Backlink>, String>>;
+}
+
+/// An API key, an HTTP client and the endpoint, which the user may point elsewhere.
+pub struct ProviderConfig {
+    pub api_key: String,
+    pub endpoint: String,
+    pub client: Client,
+}
+
+impl ProviderConfig {
+    fn new(settings: &Settings, default_endpoint: &str) -> Result<Self, String> {
+        let endpoint = settings.backlinks_endpoint.trim().trim_end_matches('/');
+        Ok(ProviderConfig {
+            api_key: settings.backlinks_api_key.trim().to_string(),
+            endpoint: if endpoint.is_empty() {
+                default_endpoint.to_string()
+            } else {
+                endpoint.to_string()
+            },
+            client: Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .map_err(|e| e.to_string())?,
+        })
+    }
+}
+
+/// The provider picked through the `backlinks_*` settings.
+pub fn from_settings(settings: &Settings) -> Result<Box<dyn BacklinkProvider>, String> {
+    if settings.backlinks_api_key.trim().is_empty() {
+        return Err("Set the backlinks API key to pull backlinks".to_string());
+    }
+    let provider: Box<dyn BacklinkProvider> =
+        match settings.backlinks_provider.trim().to_lowercase().as_str() {
+            "majestic" => Box::new(Majestic(ProviderConfig::new(settings, Majestic::ENDPOINT)?)),
+            "moz" => Box::new(Moz(ProviderConfig::new(settings, Moz::ENDPOINT)?)),
+            "ahrefs" => Box::new(Ahrefs(ProviderConfig::new(settings, Ahrefs::ENDPOINT)?)),
+            other => return Err(format!("Unknown backlinks provider: {}", other)),
+        };
+    Ok(provider)
+}
+
+/// Sends a request and returns the JSON body, with the API's own message on failure.
+pub async fn send_json(
+    provider: &str,
+    request: reqwest::RequestBuilder,
+) -> Result<serde_json::Value, String> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("{} request failed: {}", provider, e))?;
+    let status = response.status();
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse the {} response: {}", provider, e))?;
+    if !status.is_success() {
+        let message = ["error", "message", "ErrorMessage"]
+            .iter()
+            .find_map(|key| body.get(key))
+            .map(|m| m.to_string())
+            .unwrap_or_default();
+        return Err(format!("{} answered {}: {}", provider, status, message));
+    }
+    Ok(body)
+}
@@ -61,7 +61,7 @@ pub fn open_db_connection(db_name: &str) -> Result<Connection> {
         .ok_or_else(|| rusqlite::Error::QueryReturnedNoRows)?;
 
     // Define the directory for the DB file
-    let db_dir = project_dirs.data_dir().join("db"); // Append /db to data dir
+    let db_dir = crate::projects::registry::db_dir(project_dirs.data_dir());
     let db_path = db_dir.join(db_name);
 
     println!("DB path: {:?}", db_path);
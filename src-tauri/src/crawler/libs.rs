@@ -1,8 +1,6 @@
 use chrono::Utc;
 use directories::ProjectDirs;
 use google_sheets4::api::Response;
-use hyper::Client as HyperClient;
-use hyper_rustls::HttpsConnectorBuilder;
 use reqwest::Client;
 use rusqlite::{Connection, Result};
 use scraper::{Html, Selector};
@@ -16,6 +14,7 @@ use tauri::Config;
 use tokio::fs;
 use url::{ParseError, Url};
 use yup_oauth2 as oauth2;
+use yup_oauth2::authenticator_delegate::InstalledFlowDelegate;
 use yup_oauth2::{InstalledFlowAuthenticator, InstalledFlowReturnMethod};
 
 use reqwest::header::{HeaderMap, HeaderValue};
@@ -235,10 +234,11 @@ pub fn get_indexation_status(document: &Html) -> String {
 pub async fn load_api_keys() -> Result<ApiKeys, String> {
     let config_dir =
         ProjectDirs::from("", "", "rustyseo").ok_or_else(|| "Failed to get project directories")?;
-    let config_file = config_dir.config_dir().join("api_keys.toml");
+    let config_dir = crate::projects::registry::config_dir(&config_dir);
+    let config_file = config_dir.join("api_keys.toml");
 
     // Create the config directory if it doesn't exist
-    fs::create_dir_all(config_dir.config_dir())
+    fs::create_dir_all(&config_dir)
         .await
         .map_err(|e| format!("Failed to create config directory: {}", e))?;
 
@@ -420,15 +420,15 @@ pub struct Credentials {
 #[tauri::command]
 pub async fn read_credentials_file() -> Result<InstalledInfo, String> {
     let config_dirs =
-        ProjectDirs::from("", "", "rustyseo").expect("Failed to get project directories");
-    let config_dir = config_dirs.data_dir();
+        ProjectDirs::from("", "", "rustyseo").ok_or("Failed to get project directories")?;
+    let config_dir = crate::projects::registry::data_dir(&config_dirs);
     let secret_file = config_dir.join("client_secret.json");
 
     let data = fs::read_to_string(&secret_file)
         .await
-        .expect("Failed to read client secret file");
-    let secret: ClientSecret =
-        serde_json::from_str(&data).expect("Failed to parse client secret file");
+        .map_err(|e| format!("Failed to read client secret file: {}", e))?;
+    let secret: ClientSecret = serde_json::from_str(&data)
+        .map_err(|e| format!("Failed to parse client secret file: {}", e))?;
 
     let result = InstalledInfo {
         client_id: secret.installed.client_id,
@@ -494,10 +494,10 @@ pub async fn set_search_console_credentials(credentials: Credentials) -> Result<
         None => return Err("Failed to get project directories".to_string()),
     };
 
-    let config_dir = config_dirs.data_dir();
+    let config_dir = crate::projects::registry::data_dir(&config_dirs);
 
     // Create the config directory if it doesn't exist
-    if let Err(e) = fs::create_dir_all(config_dir).await {
+    if let Err(e) = fs::create_dir_all(&config_dir).await {
         return Err(format!("Failed to create config directory: {}", e));
     }
 
@@ -514,60 +514,102 @@ pub async fn set_search_console_credentials(credentials: Credentials) -> Result<
     Ok(secret_file)
 }
 
-pub async fn get_google_search_console() -> Result<Vec<JsonValue>, Box<dyn std::error::Error>> {
-    // RUN THE CHECK ON THE SECRET IN THE DISK
+pub(crate) const SEARCH_CONSOLE_SCOPE: &str = "https://www.googleapis.com/auth/webmasters.readonly";
+
+/// The Search Console property of the saved credentials, `sc-domain:` for domain properties.
+pub(crate) fn search_console_site_url(info: &InstalledInfo) -> String {
+    match info.search_type.as_str() {
+        "domain" => format!("sc-domain:{}", info.url),
+        _ => info.url.clone(),
+    }
+}
 
-    // Set up the OAuth2 flow
-    let secret_path = directories::ProjectDirs::from("", "", "rustyseo")
-        .expect("Failed to get project directories")
-        .data_dir()
-        .join("client_secret.json");
-    // println!("Secret path with error: {}", secret_path.display());
-    let secret = yup_oauth2::read_application_secret(&secret_path).await?;
-
-    // Create an authenticator
-    let auth_path = directories::ProjectDirs::from("", "", "rustyseo")
-        .expect("Failed to get project directories")
-        .data_dir()
-        .join("tokencache.json");
-    let auth = InstalledFlowAuthenticator::builder(secret, InstalledFlowReturnMethod::HTTPRedirect)
-        .persist_tokens_to_disk(&auth_path)
+/// Runs the installed app flow with `client_secret.json`, or reuses the cached token,
+/// and returns a bearer token for `scope`.
+///
+/// The consent URL goes to `delegate` when one is given, otherwise the browser is opened.
+pub(crate) async fn google_access_token(
+    api_name: &str,
+    scope: &str,
+    token_cache: &str,
+    delegate: Option<Box<dyn InstalledFlowDelegate>>,
+) -> Result<String, String> {
+    let dirs = ProjectDirs::from("", "", "rustyseo").ok_or("Failed to get project directories")?;
+    let data_dir = crate::projects::registry::data_dir(&dirs);
+
+    let secret = yup_oauth2::read_application_secret(data_dir.join("client_secret.json"))
+        .await
+        .map_err(|e| format!("{} credentials missing: {}", api_name, e))?;
+
+    let mut builder =
+        InstalledFlowAuthenticator::builder(secret, InstalledFlowReturnMethod::HTTPRedirect)
+            .persist_tokens_to_disk(data_dir.join(token_cache));
+    if let Some(delegate) = delegate {
+        builder = builder.flow_delegate(delegate);
+    }
+    let auth = builder
         .build()
         .await
-        .map_err(|e| {
-            eprintln!("Failed to create authenticator: {}", e);
-            format!("Failed to create authenticator: {}", e)
-        })
-        .and_then(|ok_result| {
-            println!("Authenticator created successfully");
-            Ok(ok_result)
-        })
-        .map(|result| result)?;
-
-    // Create an authorized client
-    let https = HttpsConnectorBuilder::new()
-        .with_native_roots()
-        .https_only()
-        .enable_http1()
-        .build();
-    let client = HyperClient::builder().build(https);
+        .map_err(|e| format!("Failed to create authenticator: {}", e))?;
 
-    // READ THE FILE ON THE DISK
-    let gsc_settings_info = read_credentials_file()
+    let token = auth
+        .token(&[scope])
+        .await
+        .map_err(|e| format!("Failed to get {} token: {}", api_name, e))?;
+
+    token
+        .token()
+        .map(String::from)
+        .ok_or_else(|| format!("{} returned an empty token", api_name))
+}
+
+/// Sends one Search Analytics query for a Search Console property.
+pub(crate) async fn search_analytics_query(
+    token: &str,
+    site_url: &str,
+    body: &Value,
+) -> Result<Value, String> {
+    let endpoint = format!(
+        "https://searchconsole.googleapis.com/webmasters/v3/sites/{}/searchAnalytics/query",
+        urlencoding::encode(site_url)
+    );
+
+    let response: Value = Client::new()
+        .post(&endpoint)
+        .bearer_auth(token)
+        .json(body)
+        .send()
         .await
-        .expect("Failed to read credentials file");
-    let credentials_url = gsc_settings_info.url;
-    let search_type = gsc_settings_info.search_type;
+        .map_err(|e| format!("Search Console request failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Search Console response: {}", e))?;
+
+    if let Some(error) = response.get("error") {
+        return Err(format!("Search Console API error: {}", error));
+    }
+    Ok(response)
+}
+
+pub async fn get_google_search_console() -> Result<Vec<JsonValue>, Box<dyn std::error::Error>> {
+    // RUN THE CHECK ON THE SECRET IN THE DISK
+    let token = google_access_token(
+        "Search Console",
+        SEARCH_CONSOLE_SCOPE,
+        "tokencache.json",
+        None,
+    )
+    .await?;
+
+    // READ THE FILE ON THE DISK
+    let gsc_settings_info = read_credentials_file().await?;
+    let site_url = search_console_site_url(&gsc_settings_info);
     let credentials_project_id = gsc_settings_info.project_id;
     let credentials_client_id = gsc_settings_info.client_id;
     let credentials_client_secret = gsc_settings_info.client_secret;
     let credentials_range = gsc_settings_info.range;
     let credentials_rows = gsc_settings_info.rows;
 
-    // Initialize variables
-    let mut domain = false;
-    let mut site = false;
-
     // Set the end date to TODAY's date
     let finish_date = Utc::now().format("%Y-%m-%d").to_string();
 
@@ -631,50 +673,10 @@ pub async fn get_google_search_console() -> Result<Vec<JsonValue>, Box<dyn std::
         search_type: "web".to_string(),
         row_limit: credentials_rows,
     };
-    let body = serde_json::to_string(&query)?;
+    let body = serde_json::to_value(&query)?;
 
     // Make the API request
-    let token = auth
-        .token(&["https://www.googleapis.com/auth/webmasters.readonly"])
-        .await?;
-
-    let site_url = match search_type.as_str() {
-        "domain" => {
-            domain = true;
-            println!("Domain selected, URL: {}", &credentials_url);
-            format!("sc-domain:{}", &credentials_url)
-        }
-        "site" => {
-            site = true;
-            println!("Site selected, URL: {}", &credentials_url);
-            credentials_url.clone()
-        }
-        _ => {
-            println!("Search type: Unknown");
-            credentials_url.clone()
-        }
-    };
-
-    let request = hyper::Request::builder()
-        .method("POST")
-        .uri(format!(
-            "https://searchconsole.googleapis.com/webmasters/v3/sites/{}/searchAnalytics/query",
-            urlencoding::encode(&site_url)
-        ))
-        .header(
-            "Authorization",
-            format!("Bearer {}", token.token().unwrap()),
-        )
-        .header("Content-Type", "application/json")
-        .body(hyper::Body::from(body))?;
-
-    let response = client.request(request).await?;
-    let body_bytes = hyper::body::to_bytes(response.into_body()).await?;
-    let body_str = String::from_utf8(body_bytes.to_vec())?;
-
-    // Parse and print the results
-    let data: JsonValue = serde_json::from_str(&body_str)?;
-    // println!("Search Console Data: {:#?}", &data);
+    let data = search_analytics_query(&token, &site_url, &body).await?;
     let mut gsc_data = Vec::new();
 
     println!("Search Console Data: {:#?}", &data);
@@ -698,7 +700,7 @@ pub async fn set_google_analytics_id(id: String) -> Result<String, String> {
     // set the directories
     let config_dir = ProjectDirs::from("", "", "rustyseo")
         .ok_or_else(|| "Failed to get project directories".to_string())?;
-    let config_dir = config_dir.data_dir();
+    let config_dir = crate::projects::registry::data_dir(&config_dir);
     let file_path = config_dir.join("ga_id.json");
 
     // write the id to the file
@@ -714,13 +716,13 @@ pub async fn set_google_analytics_id(id: String) -> Result<String, String> {
 pub async fn get_google_analytics_id() -> Result<String, String> {
     let config_dir = ProjectDirs::from("", "", "rustyseo")
         .ok_or_else(|| "Failed to get project directories".to_string())?;
-    let config_dir = config_dir.data_dir();
+    let config_dir = crate::projects::registry::data_dir(&config_dir);
     let file_path = config_dir.join("ga_id.json");
 
     // read the file
     let file_toml = fs::read_to_string(file_path)
         .await
-        .expect("Could not read GA4 ID file");
+        .map_err(|e| format!("Could not read GA4 ID file: {}", e))?;
 
     println!("This is the content of the file {:#?}", file_toml);
 
@@ -743,7 +745,7 @@ pub async fn get_google_analytics(
     // Set the directories for the client_secret.json file
     let config_dir =
         ProjectDirs::from("", "", "rustyseo").ok_or_else(|| "Failed to get project directories")?;
-    let config_dir = config_dir.data_dir();
+    let config_dir = crate::projects::registry::data_dir(&config_dir);
     let secret_path = config_dir.join("client_secret.json");
 
     // Set up the OAuth2 client
@@ -853,7 +855,7 @@ pub async fn set_microsoft_clarity_credentials(
     // set the directories
     let config_dir = ProjectDirs::from("", "", "rustyseo")
         .ok_or_else(|| "Failed to get project directories".to_string())?;
-    let config_dir = config_dir.data_dir();
+    let config_dir = crate::projects::registry::data_dir(&config_dir);
     let file_path = config_dir.join("clarity.toml");
 
     let credentials = ClarityCredentials { endpoint, token };
@@ -874,7 +876,7 @@ pub async fn set_microsoft_clarity_credentials(
 pub async fn get_microsoft_clarity_credentials() -> Result<Vec<String>, String> {
     let config_dir = ProjectDirs::from("", "", "rustyseo")
         .ok_or_else(|| "Failed to get project directories".to_string())?;
-    let config_dir = config_dir.data_dir();
+    let config_dir = crate::projects::registry::data_dir(&config_dir);
     let file_path = config_dir.join("clarity.toml");
 
     // read the file
@@ -0,0 +1,98 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::rules::{A11yIssue, A11yRule};
+use crate::domain_crawler::issues::{IssueKind, IssueRegistry};
+use crate::domain_crawler::models::DomainCrawlResults;
+use crate::domain_crawler::results_store::StoredReport;
+
+/// How often a rule failed across the crawl.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSummary {
+    pub rule: A11yRule,
+    pub wcag: String,
+    pub level: String,
+    pub pages: usize,
+    pub occurrences: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageA11yIssues {
+    pub url: String,
+    pub issues: Vec<A11yIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct A11yReport {
+    pub pages_checked: usize,
+    pub pages_with_issues: usize,
+    /// Failed rules, on the most pages first
+    pub rules: Vec<RuleSummary>,
+    /// Pages with issues, most issues first
+    pub pages: Vec<PageA11yIssues>,
+}
+
+pub fn register_issues(report: &A11yReport, issues: &mut IssueRegistry) {
+    for page in report.pages.iter().filter(|page| !page.issues.is_empty()) {
+        issues.flag(IssueKind::AccessibilityViolations, &page.url);
+    }
+}
+
+impl StoredReport for A11yReport {
+    const KIND: &'static str = "accessibility";
+}
+
+/// Groups the per-page accessibility issues by rule and page.
+#[derive(Default)]
+pub struct A11yCollector {
+    report: A11yReport,
+    rules: BTreeMap<A11yRule, (BTreeSet<String>, usize)>,
+}
+
+impl A11yCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        if result.status_code != 200 || !result.content_type.contains("text/html") {
+            return;
+        }
+        self.report.pages_checked += 1;
+        let issues = &result.accessibility.issues;
+        if issues.is_empty() {
+            return;
+        }
+        for issue in issues {
+            let entry = self.rules.entry(issue.rule).or_default();
+            entry.0.insert(result.url.clone());
+            entry.1 += 1;
+        }
+        self.report.pages.push(PageA11yIssues {
+            url: result.url.clone(),
+            issues: issues.clone(),
+        });
+    }
+
+    pub fn finish(self) -> A11yReport {
+        let mut report = self.report;
+        report.pages_with_issues = report.pages.len();
+        report.rules = self
+            .rules
+            .into_iter()
+            .map(|(rule, (pages, occurrences))| RuleSummary {
+                rule,
+                wcag: rule.wcag().to_string(),
+                level: rule.level().to_string(),
+                pages: pages.len(),
+                occurrences,
+            })
+            .collect();
+        report.rules.sort_by(|a, b| {
+            b.pages
+                .cmp(&a.pages)
+                .then(b.occurrences.cmp(&a.occurrences))
+        });
+        report
+            .pages
+            .sort_by(|a, b| b.issues.len().cmp(&a.issues.len()).then(a.url.cmp(&b.url)));
+        report
+    }
+}
@@ -0,0 +1,603 @@
+use std::collections::HashSet;
+
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+// Link texts that say nothing about where the link goes out of context
+const GENERIC_LINK_NAMES: [&str; 14] = [
+    "click here",
+    "click",
+    "here",
+    "more",
+    "read more",
+    "learn more",
+    "more info",
+    "more information",
+    "details",
+    "link",
+    "this link",
+    "continue",
+    "go",
+    "this",
+];
+
+// Inputs that are labelled by their value or need no label at all
+const UNLABELLED_INPUT_TYPES: [&str; 5] = ["hidden", "submit", "reset", "button", "image"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum A11yRule {
+    ImageMissingAlt,
+    FormControlWithoutLabel,
+    MissingMainLandmark,
+    MultipleMainLandmarks,
+    EmptyLinkName,
+    GenericLinkName,
+    EmptyButtonName,
+    MissingLang,
+    PositiveTabindex,
+    FocusableAriaHidden,
+    /// Checked on rendered pages only, see `add_contrast`
+    ContrastMinimum,
+    ContrastEnhanced,
+}
+
+const ALL_RULES: [A11yRule; 10] = [
+    A11yRule::ImageMissingAlt,
+    A11yRule::FormControlWithoutLabel,
+    A11yRule::MissingMainLandmark,
+    A11yRule::MultipleMainLandmarks,
+    A11yRule::EmptyLinkName,
+    A11yRule::GenericLinkName,
+    A11yRule::EmptyButtonName,
+    A11yRule::MissingLang,
+    A11yRule::PositiveTabindex,
+    A11yRule::FocusableAriaHidden,
+];
+
+impl A11yRule {
+    /// The WCAG 2.1 success criterion the rule checks.
+    pub fn wcag(&self) -> &'static str {
+        match self {
+            A11yRule::ImageMissingAlt => "1.1.1 Non-text Content",
+            A11yRule::FormControlWithoutLabel => "1.3.1 Info and Relationships",
+            A11yRule::MissingMainLandmark | A11yRule::MultipleMainLandmarks => {
+                "1.3.1 Info and Relationships"
+            }
+            A11yRule::EmptyLinkName | A11yRule::GenericLinkName => {
+                "2.4.4 Link Purpose (In Context)"
+            }
+            A11yRule::EmptyButtonName | A11yRule::FocusableAriaHidden => "4.1.2 Name, Role, Value",
+            A11yRule::MissingLang => "3.1.1 Language of Page",
+            A11yRule::PositiveTabindex => "2.4.3 Focus Order",
+            A11yRule::ContrastMinimum => "1.4.3 Contrast (Minimum)",
+            A11yRule::ContrastEnhanced => "1.4.6 Contrast (Enhanced)",
+        }
+    }
+
+    /// Conformance level of the criterion.
+    pub fn level(&self) -> &'static str {
+        match self {
+            A11yRule::ContrastMinimum => "AA",
+            A11yRule::ContrastEnhanced => "AAA",
+            // Every markup rule checks a level A criterion
+            _ => "A",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct A11yIssue {
+    pub rule: A11yRule,
+    pub wcag: String,
+    pub level: String,
+    /// CSS selector of the offending element, empty for page-wide issues
+    pub selector: String,
+    /// The start tag or the text that triggered the rule
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct A11yAudit {
+    pub issues: Vec<A11yIssue>,
+    /// Rules that found nothing on the page
+    pub passed: Vec<A11yRule>,
+    /// Text failing a contrast threshold, empty unless the page was rendered
+    #[serde(default)]
+    pub contrast: Vec<ContrastFailure>,
+}
+
+/// Text whose colours fail a WCAG contrast threshold.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContrastFailure {
+    pub selector: String,
+    pub text: String,
+    pub foreground: String,
+    pub background: String,
+    /// Rounded to two decimals
+    pub ratio: f64,
+    pub large_text: bool,
+    /// `AA` when even the minimum is missed, `AAA` when only the enhanced level is
+    pub level: String,
+}
+
+// Large text per WCAG: 18pt, or 14pt bold
+const LARGE_TEXT_PX: f32 = 24.0;
+const LARGE_BOLD_TEXT_PX: f32 = 18.66;
+
+// Keep pages with huge DOMs within a reasonable time
+const MAX_FAILURES: usize = 50;
+
+/// Reads the computed colours of every visible text in the rendered tab, with the
+/// backgrounds of its ancestors composited behind it. Text over images and gradients is
+/// skipped rather than guessed.
+pub const CONTRAST_SCRIPT: &str = r#"JSON.stringify((() => {
+  const rgba = (value) => {
+    const match = /rgba?\(([^)]+)\)/.exec(value);
+    const parts = match ? match[1].split(/[\s,\/]+/).filter(Boolean).map(Number) : [];
+    if (parts.length < 3 || parts.some(isNaN)) return null;
+    return [parts[0], parts[1], parts[2], parts.length > 3 ? parts[3] : 1];
+  };
+  const over = (top, bottom) => [0, 1, 2].map((i) => top[i] * top[3] + bottom[i] * (1 - top[3]));
+  const backdrop = (element) => {
+    const layers = [];
+    for (let el = element; el; el = el.parentElement) {
+      const style = getComputedStyle(el);
+      if (style.backgroundImage !== 'none') return null;
+      const color = rgba(style.backgroundColor);
+      if (color && color[3] > 0) {
+        layers.push(color);
+        if (color[3] >= 1) break;
+      }
+    }
+    return layers.reduceRight((bottom, top) => over(top, bottom), [255, 255, 255]);
+  };
+  const selector = (element) => {
+    const parts = [];
+    for (let el = element; el; el = el.parentElement) {
+      if (el.id && !/\s/.test(el.id)) {
+        parts.push('#' + el.id);
+        break;
+      }
+      const name = el.tagName.toLowerCase();
+      const siblings = el.parentElement
+        ? Array.from(el.parentElement.children).filter((s) => s.tagName === el.tagName)
+        : [el];
+      parts.push(siblings.length > 1 ? `${name}:nth-of-type(${siblings.indexOf(el) + 1})` : name);
+      if (name === 'html') break;
+    }
+    return parts.reverse().join(' > ');
+  };
+  const skipped = ['script', 'style', 'noscript', 'template', 'svg', 'option'];
+  const samples = [];
+  for (const element of document.body ? document.body.querySelectorAll('*') : []) {
+    if (samples.length >= 500) break;
+    if (skipped.includes(element.tagName.toLowerCase())) continue;
+    const text = Array.from(element.childNodes)
+      .filter((node) => node.nodeType === Node.TEXT_NODE)
+      .map((node) => node.textContent)
+      .join(' ')
+      .replace(/\s+/g, ' ')
+      .trim();
+    if (!/[\p{L}\p{N}]/u.test(text)) continue;
+    const style = getComputedStyle(element);
+    if (style.visibility !== 'visible' || element.getClientRects().length === 0) continue;
+    const color = rgba(style.color);
+    const background = backdrop(element);
+    if (!color || !background) continue;
+    samples.push({
+      selector: selector(element),
+      text: text.slice(0, 60),
+      color: over(color, background),
+      background,
+      font_px: parseFloat(style.fontSize) || 16,
+      bold: Number(style.fontWeight) >= 700,
+    });
+  }
+  return samples;
+})())"#;
+
+// One text as the browser laid it out, colours as opaque RGB
+#[derive(Deserialize)]
+struct TextSample {
+    selector: String,
+    text: String,
+    color: [f64; 3],
+    background: [f64; 3],
+    font_px: f32,
+    bold: bool,
+}
+
+fn luminance(rgb: [f64; 3]) -> f64 {
+    let channel = |c: f64| {
+        let c = c / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * channel(rgb[0]) + 0.7152 * channel(rgb[1]) + 0.0722 * channel(rgb[2])
+}
+
+fn contrast_ratio(a: [f64; 3], b: [f64; 3]) -> f64 {
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+fn hex(rgb: [f64; 3]) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        rgb[0].round() as u8,
+        rgb[1].round() as u8,
+        rgb[2].round() as u8
+    )
+}
+
+fn contrast_failure(sample: TextSample) -> Option<ContrastFailure> {
+    let ratio = contrast_ratio(sample.color, sample.background);
+    let large_text =
+        sample.font_px >= LARGE_TEXT_PX || (sample.bold && sample.font_px >= LARGE_BOLD_TEXT_PX);
+    let (minimum, enhanced) = if large_text { (3.0, 4.5) } else { (4.5, 7.0) };
+    let level = if ratio < minimum {
+        "AA"
+    } else if ratio < enhanced {
+        "AAA"
+    } else {
+        return None;
+    };
+    Some(ContrastFailure {
+        selector: sample.selector,
+        text: sample.text,
+        foreground: hex(sample.color),
+        background: hex(sample.background),
+        ratio: (ratio * 100.0).round() / 100.0,
+        large_text,
+        level: level.to_string(),
+    })
+}
+
+/// Adds the contrast failures among the texts measured by `CONTRAST_SCRIPT` to `audit`.
+pub fn add_contrast(audit: &mut A11yAudit, measured: &str) -> Result<(), String> {
+    let samples: Vec<TextSample> = serde_json::from_str(measured)
+        .map_err(|e| format!("Failed to read the measured colours: {}", e))?;
+    let failures: Vec<ContrastFailure> = samples
+        .into_iter()
+        .filter_map(contrast_failure)
+        .take(MAX_FAILURES)
+        .collect();
+
+    for failure in &failures {
+        let rule = if failure.level == "AA" {
+            A11yRule::ContrastMinimum
+        } else {
+            A11yRule::ContrastEnhanced
+        };
+        audit.issues.push(A11yIssue {
+            rule,
+            wcag: rule.wcag().to_string(),
+            level: rule.level().to_string(),
+            selector: failure.selector.clone(),
+            snippet: format!(
+                "{} ({}:1, {} on {})",
+                failure.text, failure.ratio, failure.foreground, failure.background
+            ),
+        });
+    }
+    for rule in [A11yRule::ContrastMinimum, A11yRule::ContrastEnhanced] {
+        if !audit.issues.iter().any(|issue| issue.rule == rule) {
+            audit.passed.push(rule);
+        }
+    }
+    audit.contrast = failures;
+    Ok(())
+}
+
+/// Builds a selector from the nearest ancestor with an id, using `:nth-of-type` where
+/// siblings share the tag.
+pub fn element_selector(element: &ElementRef) -> String {
+    let mut parts = Vec::new();
+    let mut current = Some(*element);
+    while let Some(el) = current {
+        let value = el.value();
+        if let Some(id) = value.id().filter(|id| !id.contains(char::is_whitespace)) {
+            parts.push(format!("#{}", id));
+            break;
+        }
+        let name = value.name();
+        let same_tag: Vec<ElementRef> = el
+            .parent()
+            .into_iter()
+            .flat_map(|parent| parent.children())
+            .filter_map(ElementRef::wrap)
+            .filter(|sibling| sibling.value().name() == name)
+            .collect();
+        if same_tag.len() > 1 {
+            let index = same_tag.iter().position(|s| s.id() == el.id()).unwrap_or(0) + 1;
+            parts.push(format!("{}:nth-of-type({})", name, index));
+        } else {
+            parts.push(name.to_string());
+        }
+        if name == "html" {
+            break;
+        }
+        current = el.parent().and_then(ElementRef::wrap);
+    }
+    parts.reverse();
+    parts.join(" > ")
+}
+
+fn start_tag(element: &ElementRef) -> String {
+    let value = element.value();
+    let mut tag = format!("<{}", value.name());
+    for (name, attr) in value.attrs() {
+        let attr: String = attr.chars().take(80).collect();
+        tag.push_str(&format!(" {}=\"{}\"", name, attr));
+    }
+    tag.push('>');
+    tag
+}
+
+fn is_hidden(element: &ElementRef) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .chain([*element])
+        .any(|el| {
+            let value = el.value();
+            value.attr("aria-hidden") == Some("true") || value.attr("hidden").is_some()
+        })
+}
+
+fn has_aria_name(element: &ElementRef) -> bool {
+    let value = element.value();
+    value
+        .attr("aria-label")
+        .is_some_and(|label| !label.trim().is_empty())
+        || value.attr("aria-labelledby").is_some()
+        || value
+            .attr("title")
+            .is_some_and(|title| !title.trim().is_empty())
+}
+
+/// Text content plus the alt text of images inside, the way screen readers name a link.
+fn accessible_text(element: &ElementRef) -> String {
+    let image_selector = Selector::parse("img[alt], [role='img'][aria-label]").unwrap();
+    let mut text: String = element.text().collect::<Vec<_>>().join(" ");
+    for image in element.select(&image_selector) {
+        let value = image.value();
+        text.push(' ');
+        text.push_str(value.attr("alt").or(value.attr("aria-label")).unwrap_or(""));
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_focusable(element: &ElementRef) -> bool {
+    let value = element.value();
+    let tabindex = value
+        .attr("tabindex")
+        .and_then(|t| t.trim().parse::<i32>().ok());
+    if tabindex.is_some_and(|t| t < 0) {
+        return false;
+    }
+    tabindex.is_some()
+        || matches!(value.name(), "button" | "select" | "textarea")
+        || (value.name() == "a" && value.attr("href").is_some())
+        || (value.name() == "input" && value.attr("type") != Some("hidden"))
+}
+
+/// Runs every rule over a page.
+pub fn check_page(html: &str) -> A11yAudit {
+    let document = Html::parse_document(html);
+    let mut issues = Vec::new();
+    let mut push = |rule: A11yRule, element: Option<&ElementRef>, snippet: String| {
+        issues.push(A11yIssue {
+            rule,
+            wcag: rule.wcag().to_string(),
+            level: rule.level().to_string(),
+            selector: element.map(element_selector).unwrap_or_default(),
+            snippet,
+        });
+    };
+
+    // Images, image buttons and image map areas need a text alternative
+    let image_selector = Selector::parse("img, input[type='image'], area[href]").unwrap();
+    for image in document.select(&image_selector) {
+        let value = image.value();
+        let decorative = matches!(value.attr("role"), Some("presentation") | Some("none"));
+        if value.attr("alt").is_none()
+            && !decorative
+            && !has_aria_name(&image)
+            && !is_hidden(&image)
+        {
+            push(A11yRule::ImageMissingAlt, Some(&image), start_tag(&image));
+        }
+    }
+
+    // Form controls need a label, wrapping one or pointed at by its `for`
+    let label_selector = Selector::parse("label[for]").unwrap();
+    let labelled: HashSet<&str> = document
+        .select(&label_selector)
+        .filter_map(|label| label.value().attr("for"))
+        .collect();
+    let control_selector = Selector::parse("input, select, textarea").unwrap();
+    for control in document.select(&control_selector) {
+        let value = control.value();
+        let input_type = value.attr("type").unwrap_or("text").to_lowercase();
+        if value.name() == "input" && UNLABELLED_INPUT_TYPES.contains(&input_type.as_str()) {
+            continue;
+        }
+        let wrapped = control
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|ancestor| ancestor.value().name() == "label");
+        let by_for = value.id().is_some_and(|id| labelled.contains(id));
+        if !wrapped && !by_for && !has_aria_name(&control) && !is_hidden(&control) {
+            push(
+                A11yRule::FormControlWithoutLabel,
+                Some(&control),
+                start_tag(&control),
+            );
+        }
+    }
+
+    let main_selector = Selector::parse("main, [role='main']").unwrap();
+    match document.select(&main_selector).count() {
+        0 => push(A11yRule::MissingMainLandmark, None, String::new()),
+        1 => {}
+        _ => {
+            for main in document.select(&main_selector).skip(1) {
+                push(
+                    A11yRule::MultipleMainLandmarks,
+                    Some(&main),
+                    start_tag(&main),
+                );
+            }
+        }
+    }
+
+    let link_selector = Selector::parse("a[href]").unwrap();
+    for link in document.select(&link_selector) {
+        if is_hidden(&link) {
+            continue;
+        }
+        let aria_label = link.value().attr("aria-label").map(str::trim);
+        let name = aria_label
+            .filter(|label| !label.is_empty())
+            .map(String::from)
+            .unwrap_or_else(|| accessible_text(&link));
+        if name.is_empty() && !has_aria_name(&link) {
+            push(A11yRule::EmptyLinkName, Some(&link), start_tag(&link));
+        } else if GENERIC_LINK_NAMES.contains(
+            &name
+                .trim_end_matches(['.', '…', '>', '»', '→'])
+                .trim()
+                .to_lowercase()
+                .as_str(),
+        ) {
+            push(A11yRule::GenericLinkName, Some(&link), name);
+        }
+    }
+
+    let button_selector = Selector::parse("button, [role='button']").unwrap();
+    for button in document.select(&button_selector) {
+        if accessible_text(&button).is_empty() && !has_aria_name(&button) && !is_hidden(&button) {
+            push(A11yRule::EmptyButtonName, Some(&button), start_tag(&button));
+        }
+    }
+
+    let html_selector = Selector::parse("html").unwrap();
+    let has_lang = document
+        .select(&html_selector)
+        .next()
+        .and_then(|root| root.value().attr("lang").or(root.value().attr("xml:lang")))
+        .is_some_and(|lang| !lang.trim().is_empty());
+    if !has_lang {
+        push(A11yRule::MissingLang, None, String::new());
+    }
+
+    // A positive tabindex pulls the element ahead of the document order
+    let tabindex_selector = Selector::parse("[tabindex]").unwrap();
+    for element in document.select(&tabindex_selector) {
+        let tabindex = element
+            .value()
+            .attr("tabindex")
+            .and_then(|t| t.trim().parse::<i32>().ok());
+        if tabindex.is_some_and(|t| t > 0) {
+            push(
+                A11yRule::PositiveTabindex,
+                Some(&element),
+                start_tag(&element),
+            );
+        }
+    }
+
+    // Keyboard users can reach these but screen readers announce nothing
+    let hidden_selector = Selector::parse("[aria-hidden='true']").unwrap();
+    let focusable_selector =
+        Selector::parse("a[href], button, input, select, textarea, [tabindex]").unwrap();
+    for hidden in document.select(&hidden_selector) {
+        let focusable = [hidden]
+            .into_iter()
+            .chain(hidden.select(&focusable_selector))
+            .filter(is_focusable);
+        for element in focusable {
+            push(
+                A11yRule::FocusableAriaHidden,
+                Some(&element),
+                start_tag(&element),
+            );
+        }
+    }
+
+    let failed: HashSet<A11yRule> = issues.iter().map(|issue| issue.rule).collect();
+    let passed = ALL_RULES
+        .into_iter()
+        .filter(|rule| !failed.contains(rule))
+        .collect();
+
+    A11yAudit {
+        issues,
+        passed,
+        contrast: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measured(color: [u8; 3], background: [u8; 3], font_px: f32, bold: bool) -> String {
+        serde_json::json!([{
+            "selector": "p",
+            "text": "Some text",
+            "color": color,
+            "background": background,
+            "font_px": font_px,
+            "bold": bold,
+        }])
+        .to_string()
+    }
+
+    #[test]
+    fn grey_on_white_fails_the_minimum() {
+        let mut audit = A11yAudit::default();
+        add_contrast(
+            &mut audit,
+            &measured([153, 153, 153], [255, 255, 255], 16.0, false),
+        )
+        .unwrap();
+        assert_eq!(audit.contrast.len(), 1);
+        let failure = &audit.contrast[0];
+        assert_eq!(failure.level, "AA");
+        assert_eq!(failure.ratio, 2.85);
+        assert_eq!(failure.foreground, "#999999");
+        assert_eq!(audit.issues[0].rule, A11yRule::ContrastMinimum);
+        assert!(audit.passed.contains(&A11yRule::ContrastEnhanced));
+    }
+
+    #[test]
+    fn large_text_has_lower_thresholds() {
+        let mut audit = A11yAudit::default();
+        // 3.95:1 misses AA for body text but only AAA for large bold text
+        add_contrast(
+            &mut audit,
+            &measured([128, 128, 128], [255, 255, 255], 19.0, true),
+        )
+        .unwrap();
+        assert!(audit.contrast[0].large_text);
+        assert_eq!(audit.contrast[0].level, "AAA");
+    }
+
+    #[test]
+    fn black_on_white_passes() {
+        let mut audit = A11yAudit::default();
+        add_contrast(
+            &mut audit,
+            &measured([0, 0, 0], [255, 255, 255], 16.0, false),
+        )
+        .unwrap();
+        assert!(audit.contrast.is_empty());
+        assert!(audit.passed.contains(&A11yRule::ContrastMinimum));
+        assert!(audit.passed.contains(&A11yRule::ContrastEnhanced));
+    }
+}
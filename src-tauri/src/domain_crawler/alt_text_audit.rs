@@ -0,0 +1,133 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::helpers::alt_tags::is_filename_alt;
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+/// An image whose alt text needs attention, with the pages showing it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AltTextImage {
+    pub src: String,
+    pub alt: Option<String>,
+    pub pages: Vec<String>,
+}
+
+/// The same alt text used for different images.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateAlt {
+    pub alt: String,
+    pub images: Vec<String>,
+    pub pages: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AltTextReport {
+    /// `<img>` elements across the crawled pages, an image shown twice counts twice
+    pub total_images: usize,
+    pub with_alt: usize,
+    pub decorative: usize,
+    pub missing_alt: usize,
+    pub missing_alt_percent: f64,
+    pub missing: Vec<AltTextImage>,
+    pub filename_alts: Vec<AltTextImage>,
+    pub duplicated: Vec<DuplicateAlt>,
+}
+
+// Alt text is fixed on the pages, they are the affected URLs
+pub fn register_issues(report: &AltTextReport, issues: &mut IssueRegistry) {
+    for page in report.missing.iter().flat_map(|image| &image.pages) {
+        issues.flag(IssueKind::MissingAltText, page);
+    }
+}
+
+impl StoredReport for AltTextReport {
+    const KIND: &'static str = "alt_text";
+}
+
+fn collect(images: HashMap<(String, Option<String>), BTreeSet<String>>) -> Vec<AltTextImage> {
+    let mut images: Vec<AltTextImage> = images
+        .into_iter()
+        .map(|((src, alt), pages)| AltTextImage {
+            src,
+            alt,
+            pages: pages.into_iter().collect(),
+        })
+        .collect();
+    images.sort_by(|a, b| b.pages.len().cmp(&a.pages.len()).then(a.src.cmp(&b.src)));
+    images
+}
+
+/// Aggregates the alt texts of every crawled image into an accessibility report.
+#[derive(Default)]
+pub struct AltTextCollector {
+    report: AltTextReport,
+    missing: HashMap<(String, Option<String>), BTreeSet<String>>,
+    filenames: HashMap<(String, Option<String>), BTreeSet<String>>,
+    // Alt text, lowercased, to the distinct images and pages using it
+    by_alt: HashMap<String, (String, BTreeSet<String>, BTreeSet<String>)>,
+}
+
+impl AltTextCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        let report = &mut self.report;
+        for image in &result.alt_tags.images {
+            report.total_images += 1;
+            let src = image.src.clone().unwrap_or_default();
+            match image.alt.as_deref() {
+                _ if image.decorative => report.decorative += 1,
+                None => {
+                    report.missing_alt += 1;
+                    self.missing
+                        .entry((src, None))
+                        .or_default()
+                        .insert(result.url.clone());
+                }
+                Some(alt) => {
+                    report.with_alt += 1;
+                    if is_filename_alt(alt, image.src.as_deref()) {
+                        self.filenames
+                            .entry((src.clone(), Some(alt.to_string())))
+                            .or_default()
+                            .insert(result.url.clone());
+                    }
+                    if !src.is_empty() {
+                        let entry = self
+                            .by_alt
+                            .entry(alt.to_lowercase())
+                            .or_insert_with(|| (alt.to_string(), BTreeSet::new(), BTreeSet::new()));
+                        entry.1.insert(src);
+                        entry.2.insert(result.url.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn finish(self) -> AltTextReport {
+        let mut report = self.report;
+        if report.total_images > 0 {
+            report.missing_alt_percent =
+                (report.missing_alt as f64 / report.total_images as f64 * 1000.0).round() / 10.0;
+        }
+        report.missing = collect(self.missing);
+        report.filename_alts = collect(self.filenames);
+
+        report.duplicated = self
+            .by_alt
+            .into_values()
+            .filter(|(_, images, _)| images.len() > 1)
+            .map(|(alt, images, pages)| DuplicateAlt {
+                alt,
+                images: images.into_iter().collect(),
+                pages: pages.len(),
+            })
+            .collect();
+        report
+            .duplicated
+            .sort_by(|a, b| b.images.len().cmp(&a.images.len()).then(a.alt.cmp(&b.alt)));
+        report
+    }
+}
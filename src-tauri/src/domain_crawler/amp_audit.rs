@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::helpers::amp_selector::extract_amp;
+use super::helpers::canonical_selector::{audit_canonical, normalise_url, same_url};
+use super::helpers::images_selector::{image_client, image_permit};
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+use super::subresources;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AmpIssue {
+    /// The linked AMP page does not answer 200
+    AmpUnreachable,
+    /// The linked AMP page has no `<html amp>` attribute
+    NotAmp,
+    /// The AMP page has no canonical tag
+    MissingCanonical,
+    /// The canonical of the AMP page does not answer 200
+    CanonicalUnreachable,
+    /// The AMP page canonicalizes to a different page than the one linking it
+    CanonicalMismatch,
+    /// The canonical page does not link back with `rel="amphtml"`
+    MissingAmphtml,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmpWarning {
+    pub url: String,
+    pub issue: AmpIssue,
+    pub message: String,
+}
+
+/// A canonical page and its AMP version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmpPair {
+    pub canonical_url: String,
+    pub amp_url: String,
+    pub canonical_status: Option<u16>,
+    pub amp_status: Option<u16>,
+    /// Both answer 200 and reference each other
+    pub valid: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AmpReport {
+    /// Crawled pages that are AMP documents
+    pub amp_pages: usize,
+    /// AMP pages that are their own canonical
+    pub standalone: usize,
+    pub pairs: Vec<AmpPair>,
+    pub warnings: Vec<AmpWarning>,
+}
+
+pub fn register_issues(report: &AmpReport, issues: &mut IssueRegistry) {
+    for warning in &report.warnings {
+        issues.flag(IssueKind::AmpError, &warning.url);
+    }
+}
+
+impl StoredReport for AmpReport {
+    const KIND: &'static str = "amp";
+}
+
+// What the pairing needs to know about either side of a pair
+#[derive(Debug, Clone, Default)]
+struct PageView {
+    status_code: Option<u16>,
+    is_amp: bool,
+    amphtml: Option<String>,
+    canonical: Option<String>,
+    error: Option<String>,
+}
+
+impl PageView {
+    fn from_result(result: &DomainCrawlResults) -> Self {
+        PageView {
+            status_code: Some(result.status_code),
+            is_amp: result.amp.is_amp,
+            amphtml: result.amp.amphtml.clone(),
+            canonical: result.canonical.resolved.clone(),
+            error: None,
+        }
+    }
+}
+
+// AMP pages are linked with <link>, not anchors, so the crawl usually never reaches them
+async fn fetch_view(url: Url) -> PageView {
+    let _permit = image_permit().await;
+    let response = match subresources::send(image_client().get(url.as_str()), url.as_str()).await {
+        Ok(response) => response,
+        Err(e) => {
+            return PageView {
+                error: Some(e.to_string()),
+                ..Default::default()
+            }
+        }
+    };
+    let status = response.status().as_u16();
+    let final_url = response.url().clone();
+    let mut view = PageView {
+        status_code: Some(status),
+        ..Default::default()
+    };
+    if status == 200 {
+        if let Ok(body) = response.text().await {
+            let amp = extract_amp(&body, &final_url);
+            view.is_amp = amp.is_amp;
+            view.amphtml = amp.amphtml;
+            view.canonical = audit_canonical(&body, &final_url).resolved;
+        }
+    }
+    view
+}
+
+fn points_to(reference: Option<&str>, target: &Url) -> bool {
+    reference
+        .and_then(|reference| Url::parse(reference).ok())
+        .is_some_and(|reference| same_url(&reference, target))
+}
+
+fn status_text(view: &PageView) -> String {
+    match (&view.error, view.status_code) {
+        (Some(error), _) => error.clone(),
+        (None, Some(status)) => format!("status {}", status),
+        (None, None) => "no response".to_string(),
+    }
+}
+
+/// Pairs canonical pages with their AMP versions and checks that both reference each other.
+#[derive(Default)]
+pub struct AmpCollector {
+    report: AmpReport,
+    views: HashMap<String, PageView>,
+    pairs: Vec<(Url, Url)>,
+}
+
+impl AmpCollector {
+    fn add_pair(&mut self, canonical: Url, amp: Url) {
+        if !self
+            .pairs
+            .iter()
+            .any(|(c, a)| same_url(c, &canonical) && same_url(a, &amp))
+        {
+            self.pairs.push((canonical, amp));
+        }
+    }
+
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        let Ok(page_url) = Url::parse(&result.url) else {
+            return;
+        };
+        self.views
+            .insert(normalise_url(&page_url), PageView::from_result(result));
+        if result.status_code != 200 {
+            return;
+        }
+        if let Some(amp_url) = result
+            .amp
+            .amphtml
+            .as_deref()
+            .and_then(|u| Url::parse(u).ok())
+        {
+            self.add_pair(page_url.clone(), amp_url);
+        }
+        if !result.amp.is_amp {
+            return;
+        }
+
+        self.report.amp_pages += 1;
+        match result
+            .canonical
+            .resolved
+            .as_deref()
+            .and_then(|u| Url::parse(u).ok())
+        {
+            Some(canonical) if same_url(&canonical, &page_url) => self.report.standalone += 1,
+            Some(canonical) => self.add_pair(canonical, page_url),
+            None => self.report.warnings.push(AmpWarning {
+                url: result.url.clone(),
+                issue: AmpIssue::MissingCanonical,
+                message: "AMP page has no canonical tag".to_string(),
+            }),
+        }
+    }
+
+    pub async fn finish(self) -> AmpReport {
+        let Self {
+            mut report,
+            mut views,
+            pairs,
+        } = self;
+
+        let mut missing: Vec<Url> = Vec::new();
+        for url in pairs.iter().flat_map(|(canonical, amp)| [canonical, amp]) {
+            if !views.contains_key(&normalise_url(url)) && !missing.iter().any(|m| same_url(m, url))
+            {
+                missing.push(url.clone());
+            }
+        }
+        let fetched = join_all(missing.iter().cloned().map(fetch_view)).await;
+        for (url, view) in missing.iter().zip(fetched) {
+            views.insert(normalise_url(url), view);
+        }
+
+        for (canonical_url, amp_url) in pairs {
+            let canonical = views
+                .get(&normalise_url(&canonical_url))
+                .cloned()
+                .unwrap_or_default();
+            let amp = views
+                .get(&normalise_url(&amp_url))
+                .cloned()
+                .unwrap_or_default();
+            let issues_before = report.warnings.len();
+            let mut warn = |url: &Url, issue, message| {
+                report.warnings.push(AmpWarning {
+                    url: url.to_string(),
+                    issue,
+                    message,
+                })
+            };
+
+            if amp.status_code != Some(200) {
+                warn(
+                    &amp_url,
+                    AmpIssue::AmpUnreachable,
+                    format!("AMP page could not be loaded: {}", status_text(&amp)),
+                );
+            } else if !amp.is_amp {
+                warn(
+                    &amp_url,
+                    AmpIssue::NotAmp,
+                    "Linked as amphtml but not marked as an AMP document".to_string(),
+                );
+            } else if !points_to(amp.canonical.as_deref(), &canonical_url) {
+                warn(
+                    &amp_url,
+                    AmpIssue::CanonicalMismatch,
+                    format!(
+                        "AMP page canonicalizes to {} instead of {}",
+                        amp.canonical.as_deref().unwrap_or("nothing"),
+                        canonical_url
+                    ),
+                );
+            }
+
+            if canonical.status_code != Some(200) {
+                warn(
+                    &canonical_url,
+                    AmpIssue::CanonicalUnreachable,
+                    format!(
+                        "Canonical of an AMP page could not be loaded: {}",
+                        status_text(&canonical)
+                    ),
+                );
+            } else if !points_to(canonical.amphtml.as_deref(), &amp_url) {
+                warn(
+                    &canonical_url,
+                    AmpIssue::MissingAmphtml,
+                    format!("Canonical page does not link back to {}", amp_url),
+                );
+            }
+
+            let valid = report.warnings.len() == issues_before;
+            report.pairs.push(AmpPair {
+                canonical_url: canonical_url.to_string(),
+                amp_url: amp_url.to_string(),
+                canonical_status: canonical.status_code,
+                amp_status: amp.status_code,
+                valid,
+            });
+        }
+
+        report
+    }
+}
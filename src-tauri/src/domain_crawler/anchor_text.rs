@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::results_store::{PageRow, ResultsStore};
+
+// Anchors that say nothing about the target page
+const GENERIC_ANCHORS: [&str; 20] = [
+    "click here",
+    "click",
+    "here",
+    "read more",
+    "more",
+    "learn more",
+    "see more",
+    "find out more",
+    "more info",
+    "more information",
+    "details",
+    "continue",
+    "continue reading",
+    "this",
+    "this page",
+    "link",
+    "go",
+    "website",
+    "page",
+    "view",
+];
+
+// The share of exact-match or generic anchors above which a target is flagged
+const FLAG_SHARE: f64 = 0.5;
+// Targets with fewer inlinks than this are too small a sample to flag
+const MIN_ANCHORS: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnchorCount {
+    pub anchor: String,
+    pub count: usize,
+    pub share: f64,
+    pub generic: bool,
+    /// The anchor repeats the target page's title or H1
+    pub exact_match: bool,
+}
+
+/// The anchor text distribution of the internal links pointing at one page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetAnchors {
+    pub url: String,
+    pub inlinks: usize,
+    pub empty: usize,
+    pub generic_share: f64,
+    pub exact_match_share: f64,
+    pub mostly_generic: bool,
+    pub over_optimized: bool,
+    pub anchors: Vec<AnchorCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AnchorReport {
+    pub crawl_id: i64,
+    pub links: usize,
+    pub pages: Vec<TargetAnchors>,
+}
+
+pub fn is_generic(anchor: &str) -> bool {
+    let anchor = anchor
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase();
+    GENERIC_ANCHORS.contains(&anchor.as_str())
+}
+
+fn normalise(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Groups the anchors of every internal link by target and flags generic or over-optimized ones.
+///
+/// # Arguments
+/// * `anchors` - The (source, target, anchor) triples of the crawl.
+/// * `pages` - The crawled pages, whose titles and H1s define an exact-match anchor.
+pub fn build_anchor_report(
+    crawl_id: i64,
+    anchors: &[(String, String, String)],
+    pages: &[PageRow],
+) -> AnchorReport {
+    let targets: HashMap<&str, &PageRow> = pages.iter().map(|p| (p.url.as_str(), p)).collect();
+
+    let mut by_target: BTreeMap<&str, BTreeMap<&str, usize>> = BTreeMap::new();
+    for (_, target, anchor) in anchors {
+        *by_target
+            .entry(target.as_str())
+            .or_default()
+            .entry(anchor.as_str())
+            .or_insert(0) += 1;
+    }
+
+    let pages = by_target
+        .into_iter()
+        .map(|(url, counts)| {
+            let inlinks: usize = counts.values().sum();
+            let exact: Vec<String> = targets
+                .get(url)
+                .map(|page| {
+                    [page.title.as_deref(), page.h1.as_deref()]
+                        .into_iter()
+                        .flatten()
+                        .map(normalise)
+                        .filter(|text| !text.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let mut anchors: Vec<AnchorCount> = counts
+                .into_iter()
+                .map(|(anchor, count)| AnchorCount {
+                    anchor: anchor.to_string(),
+                    count,
+                    share: count as f64 / inlinks as f64,
+                    generic: is_generic(anchor),
+                    exact_match: !anchor.is_empty() && exact.contains(&normalise(anchor)),
+                })
+                .collect();
+            anchors.sort_by(|a, b| b.count.cmp(&a.count).then(a.anchor.cmp(&b.anchor)));
+
+            let share_of = |f: fn(&AnchorCount) -> bool| -> f64 {
+                anchors.iter().filter(|a| f(a)).map(|a| a.share).sum()
+            };
+            let generic_share = share_of(|a| a.generic);
+            let exact_match_share = share_of(|a| a.exact_match);
+            let enough = inlinks >= MIN_ANCHORS;
+
+            TargetAnchors {
+                url: url.to_string(),
+                inlinks,
+                empty: anchors
+                    .iter()
+                    .filter(|a| a.anchor.is_empty())
+                    .map(|a| a.count)
+                    .sum(),
+                generic_share,
+                exact_match_share,
+                mostly_generic: enough && generic_share > FLAG_SHARE,
+                over_optimized: enough && exact_match_share > FLAG_SHARE,
+                anchors,
+            }
+        })
+        .collect();
+
+    AnchorReport {
+        crawl_id,
+        links: anchors.len(),
+        pages,
+    }
+}
+
+// GET THE ANCHOR TEXT DISTRIBUTION OF A STORED CRAWL
+#[tauri::command]
+pub async fn get_anchor_report(crawl_id: i64) -> Result<AnchorReport, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let anchors = store.anchors(crawl_id).await.map_err(|e| e.to_string())?;
+    let pages = store.page_rows(crawl_id).await.map_err(|e| e.to_string())?;
+
+    Ok(build_anchor_report(crawl_id, &anchors, &pages))
+}
@@ -0,0 +1,110 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::helpers::assets_selector::{AssetKind, PageAsset};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+// How many of the heaviest assets the report keeps
+const HEAVIEST_LIMIT: usize = 50;
+
+/// A script or stylesheet with the pages that load it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiteAsset {
+    pub url: String,
+    pub kind: AssetKind,
+    pub status_code: Option<u16>,
+    pub size: Option<u64>,
+    pub pages: usize,
+    /// Pages where it blocks rendering from `<head>`
+    pub render_blocking_pages: usize,
+    pub third_party: bool,
+    pub cacheable: bool,
+    pub cache_control: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AssetReport {
+    pub unique_scripts: usize,
+    pub unique_stylesheets: usize,
+    /// Assets loaded by more than one page, largest first
+    pub heaviest_shared: Vec<SiteAsset>,
+    /// Assets browsers have to download again on every visit
+    pub uncached: Vec<SiteAsset>,
+    /// Assets that answered with an error status
+    pub broken: Vec<SiteAsset>,
+}
+
+impl StoredReport for AssetReport {
+    const KIND: &'static str = "assets";
+}
+
+fn site_asset(asset: &PageAsset) -> SiteAsset {
+    let info = asset.info.clone().unwrap_or_default();
+    SiteAsset {
+        url: asset.url.clone(),
+        kind: asset.kind,
+        status_code: info.status_code,
+        size: info.size,
+        pages: 0,
+        render_blocking_pages: 0,
+        third_party: asset.third_party,
+        cacheable: info.cacheable,
+        cache_control: info.cache_control,
+    }
+}
+
+/// Aggregates the scripts and stylesheets of every page into a site-wide inventory.
+#[derive(Default)]
+pub struct AssetCollector {
+    assets: HashMap<String, SiteAsset>,
+}
+
+impl AssetCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        for asset in &result.assets.assets {
+            let entry = self
+                .assets
+                .entry(asset.url.clone())
+                .or_insert_with(|| site_asset(asset));
+            entry.pages += 1;
+            if asset.render_blocking {
+                entry.render_blocking_pages += 1;
+            }
+        }
+    }
+
+    pub fn finish(self) -> AssetReport {
+        finish_report(self.assets)
+    }
+}
+
+fn finish_report(assets: HashMap<String, SiteAsset>) -> AssetReport {
+    let is_broken = |a: &SiteAsset| a.status_code.is_some_and(|code| code >= 400);
+    let count = |kind| assets.values().filter(|a| a.kind == kind).count();
+    let mut heaviest_shared: Vec<SiteAsset> = assets
+        .values()
+        .filter(|a| a.pages > 1 && a.size.is_some())
+        .cloned()
+        .collect();
+    heaviest_shared.sort_by(|a, b| b.size.cmp(&a.size).then(b.pages.cmp(&a.pages)));
+    heaviest_shared.truncate(HEAVIEST_LIMIT);
+
+    let mut uncached: Vec<SiteAsset> = assets
+        .values()
+        // Assets that could not be checked say nothing about caching
+        .filter(|a| !a.cacheable && a.status_code.is_some() && !is_broken(a))
+        .cloned()
+        .collect();
+    uncached.sort_by_key(|a| Reverse(a.pages));
+
+    AssetReport {
+        unique_scripts: count(AssetKind::Script),
+        unique_stylesheets: count(AssetKind::Stylesheet),
+        heaviest_shared,
+        uncached,
+        broken: assets.values().filter(|a| is_broken(a)).cloned().collect(),
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use url::Url;
+use uuid::Uuid;
+
+use crate::settings::settings::Settings;
+use crate::AppState;
+
+use super::results_store::ResultsStore;
+
+const INDEXNOW_URL: &str = "https://api.indexnow.org/indexnow";
+const BING_API_URL: &str = "https://ssl.bing.com/webmaster/api.svc/json";
+// The protocol caps a single submission at 10k URLs
+const URLS_PER_SUBMISSION: usize = 10_000;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The answer to one IndexNow submission, all its URLs share a host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexNowBatch {
+    pub host: String,
+    pub urls: usize,
+    pub status: u16,
+    pub accepted: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexNowReport {
+    pub submitted: usize,
+    pub batches: Vec<IndexNowBatch>,
+    /// URLs that are not absolute http(s) URLs
+    pub skipped: Vec<String>,
+}
+
+/// Crawl activity of Bingbot on one day, as reported by Bing Webmaster Tools.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BingCrawlStats {
+    pub date: Option<String>,
+    pub crawled_pages: u64,
+    pub crawl_errors: u64,
+    pub in_index: u64,
+    pub in_links: u64,
+    pub code_2xx: u64,
+    pub code_301: u64,
+    pub code_302: u64,
+    pub code_4xx: u64,
+    pub code_5xx: u64,
+    pub blocked_by_robots_txt: u64,
+    pub connection_timeouts: u64,
+    pub dns_failures: u64,
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+fn indexnow_message(status: u16) -> &'static str {
+    match status {
+        200 => "Submitted",
+        202 => "Received, the key is validated later",
+        400 => "Invalid request",
+        403 => "The key was not found at the key location",
+        422 => "The URLs do not belong to the host or the key does not match",
+        429 => "Too many requests",
+        _ => "Unexpected response",
+    }
+}
+
+// The key file is looked up on every host, a relative location is resolved against each
+fn key_location(settings: &Settings, host: &Url) -> Option<String> {
+    let location = settings.indexnow_key_location.trim();
+    if location.is_empty() {
+        return None;
+    }
+    host.join(location).ok().map(|url| url.to_string())
+}
+
+/// Submits URLs through the IndexNow protocol, grouped per host as the protocol requires.
+pub async fn submit_urls(settings: &Settings, urls: &[String]) -> Result<IndexNowReport, String> {
+    let key = settings.indexnow_key.trim();
+    if key.is_empty() {
+        return Err("Set the IndexNow key to submit URLs".to_string());
+    }
+
+    let mut report = IndexNowReport::default();
+    let mut by_host: BTreeMap<String, (Url, Vec<String>)> = BTreeMap::new();
+    let mut seen = HashSet::new();
+    for url in urls {
+        let parsed = match Url::parse(url.trim()) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => parsed,
+            _ => {
+                report.skipped.push(url.clone());
+                continue;
+            }
+        };
+        let Some(host) = parsed.host_str().map(str::to_string) else {
+            report.skipped.push(url.clone());
+            continue;
+        };
+        if seen.insert(parsed.to_string()) {
+            by_host
+                .entry(host)
+                .or_insert_with(|| (parsed.clone(), Vec::new()))
+                .1
+                .push(parsed.to_string());
+        }
+    }
+
+    let client = client()?;
+    for (host, (origin, urls)) in by_host {
+        for chunk in urls.chunks(URLS_PER_SUBMISSION) {
+            let mut body = json!({ "host": host, "key": key, "urlList": chunk });
+            if let Some(location) = key_location(settings, &origin) {
+                body["keyLocation"] = json!(location);
+            }
+            let response = client
+                .post(INDEXNOW_URL)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| format!("IndexNow request failed: {}", e))?;
+            let status = response.status().as_u16();
+            let accepted = response.status().is_success();
+            if accepted {
+                report.submitted += chunk.len();
+            }
+            report.batches.push(IndexNowBatch {
+                host: host.clone(),
+                urls: chunk.len(),
+                status,
+                accepted,
+                message: indexnow_message(status).to_string(),
+            });
+        }
+    }
+
+    println!(
+        "Submitted {} URLs to IndexNow in {} batches",
+        report.submitted,
+        report.batches.len()
+    );
+    Ok(report)
+}
+
+// Dates come in the WCF format, "/Date(1316156400000-0700)/"
+fn parse_bing_date(value: &str) -> Option<String> {
+    let value = value.trim_start_matches("/Date(");
+    // The offset only says which zone the date was written in, the millis are UTC
+    let end = value
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| !c.is_ascii_digit())
+        .map_or(value.len(), |(index, _)| index);
+    let millis: i64 = value[..end].parse().ok()?;
+    DateTime::from_timestamp_millis(millis).map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+fn count(row: &Value, key: &str) -> u64 {
+    row.get(key).and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Fetches the daily crawl stats Bing Webmaster Tools keeps for a verified site.
+pub async fn crawl_stats(
+    settings: &Settings,
+    site_url: &str,
+) -> Result<Vec<BingCrawlStats>, String> {
+    let api_key = settings.bing_webmaster_api_key.trim();
+    if api_key.is_empty() {
+        return Err("Set the Bing Webmaster API key to pull crawl stats".to_string());
+    }
+
+    let response = client()?
+        .get(format!("{}/GetCrawlStats", BING_API_URL))
+        .query(&[("siteUrl", site_url), ("apikey", api_key)])
+        .send()
+        .await
+        .map_err(|e| format!("Bing Webmaster request failed: {}", e))?;
+    let status = response.status();
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Bing Webmaster response: {}", e))?;
+    if !status.is_success() {
+        let message = body
+            .get("Message")
+            .map(|m| m.to_string())
+            .unwrap_or_default();
+        return Err(format!(
+            "Bing Webmaster API answered {}: {}",
+            status, message
+        ));
+    }
+
+    let mut stats: Vec<BingCrawlStats> = body
+        .get("d")
+        .and_then(Value::as_array)
+        .map(|rows| {
+            rows.iter()
+                .map(|row| BingCrawlStats {
+                    date: row
+                        .get("Date")
+                        .and_then(Value::as_str)
+                        .and_then(parse_bing_date),
+                    crawled_pages: count(row, "CrawledPages"),
+                    crawl_errors: count(row, "CrawlErrors"),
+                    in_index: count(row, "InIndex"),
+                    in_links: count(row, "InLinks"),
+                    code_2xx: count(row, "Code2xx"),
+                    code_301: count(row, "Code301"),
+                    code_302: count(row, "Code302"),
+                    code_4xx: count(row, "Code4xx"),
+                    code_5xx: count(row, "Code5xx"),
+                    blocked_by_robots_txt: count(row, "BlockedByRobotsTxt"),
+                    connection_timeouts: count(row, "ConnectionTimeout"),
+                    dns_failures: count(row, "DnsFailures"),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    stats.sort_by(|a, b| a.date.cmp(&b.date));
+    Ok(stats)
+}
+
+// GENERATE A NEW INDEXNOW KEY TO HOST AS /<key>.txt
+#[tauri::command]
+pub fn generate_indexnow_key() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+// SUBMIT URLS TO INDEXNOW
+#[tauri::command]
+pub async fn submit_indexnow_urls(
+    urls: Vec<String>,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<IndexNowReport, String> {
+    let settings = settings_state.settings.read().await.clone();
+    submit_urls(&settings, &urls).await
+}
+
+// SUBMIT URLS SELECTED FROM A CRAWL, OR ALL ITS INDEXABLE PAGES, TO INDEXNOW
+#[tauri::command]
+pub async fn submit_crawl_to_indexnow(
+    crawl_id: i64,
+    urls: Option<Vec<String>>,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<IndexNowReport, String> {
+    let settings = settings_state.settings.read().await.clone();
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let pages = store.page_rows(crawl_id).await.map_err(|e| e.to_string())?;
+    if pages.is_empty() {
+        return Err(format!("Crawl {} has no pages", crawl_id));
+    }
+
+    let urls: Vec<String> = match urls {
+        // Only URLs of the crawl can be picked
+        Some(selected) => {
+            let selected: HashSet<String> = selected.into_iter().collect();
+            pages
+                .into_iter()
+                .filter(|page| selected.contains(&page.url))
+                .map(|page| page.url)
+                .collect()
+        }
+        None => pages
+            .into_iter()
+            .filter(|page| page.status_code == 200 && page.indexability > 0.5)
+            .map(|page| page.url)
+            .collect(),
+    };
+    if urls.is_empty() {
+        return Err("None of the selected URLs are in the crawl".to_string());
+    }
+    submit_urls(&settings, &urls).await
+}
+
+// GET THE BING WEBMASTER CRAWL STATS OF A SITE
+#[tauri::command]
+pub async fn get_bing_crawl_stats(
+    site_url: String,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<Vec<BingCrawlStats>, String> {
+    let settings = settings_state.settings.read().await.clone();
+    crawl_stats(&settings, &site_url).await
+}
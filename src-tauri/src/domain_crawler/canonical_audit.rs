@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::helpers::canonical_selector::{normalise_url, same_url, CanonicalAudit, CanonicalKind};
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::redirect_audit::RedirectHop;
+use super::results_store::StoredReport;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CanonicalIssueKind {
+    Missing,
+    Multiple,
+    Invalid,
+    /// The canonical target itself canonicalizes to another URL
+    Chain,
+    /// The canonical target returned a non-200 status
+    NonOkTarget,
+    /// The canonical target is noindexed
+    NoindexTarget,
+    /// The canonical target redirects to another URL
+    RedirectTarget,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanonicalIssue {
+    pub url: String,
+    pub canonical: Option<String>,
+    pub kind: CanonicalIssueKind,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CanonicalReport {
+    pub self_referencing: usize,
+    pub canonicalized: usize,
+    pub cross_domain: usize,
+    pub missing: usize,
+    pub issues: Vec<CanonicalIssue>,
+}
+
+pub fn is_noindex(result: &DomainCrawlResults) -> bool {
+    let meta = result
+        .meta_robots
+        .meta_robots
+        .iter()
+        .any(|m| m.to_lowercase().contains("noindex"));
+    let header = result.headers.iter().any(|(k, v)| {
+        k.eq_ignore_ascii_case("x-robots-tag") && v.to_lowercase().contains("noindex")
+    });
+    meta || header
+}
+
+// What the checks need to know of a page, as the source of a canonical or as its target
+struct CanonicalPage {
+    url: String,
+    status_code: u16,
+    canonical: CanonicalAudit,
+    noindex: bool,
+}
+
+/// Collects the crawled pages one at a time for the crawl-level canonical checks.
+#[derive(Default)]
+pub struct CanonicalCollector {
+    pages: Vec<CanonicalPage>,
+    by_url: HashMap<String, usize>,
+    // Requested URLs that redirected, with the first hop and the page they ended on
+    redirected: HashMap<String, (RedirectHop, usize)>,
+}
+
+impl CanonicalCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        let index = self.pages.len();
+        if let Ok(url) = Url::parse(&result.url) {
+            self.by_url.insert(normalise_url(&url), index);
+        }
+        for hop in &result.redirect_chain {
+            if let Ok(url) = Url::parse(&hop.url) {
+                self.redirected
+                    .insert(normalise_url(&url), (hop.clone(), index));
+            }
+        }
+        self.pages.push(CanonicalPage {
+            url: result.url.clone(),
+            status_code: result.status_code,
+            canonical: result.canonical.clone(),
+            noindex: is_noindex(result),
+        });
+    }
+
+    /// Runs the crawl-level canonical checks over every collected HTML page.
+    pub fn finish(self) -> CanonicalReport {
+        let mut report = CanonicalReport::default();
+
+        for result in self.pages.iter().filter(|r| r.status_code == 200) {
+            let canonical = &result.canonical;
+            let issue = |kind, detail: String| CanonicalIssue {
+                url: result.url.clone(),
+                canonical: canonical.resolved.clone(),
+                kind,
+                detail,
+            };
+
+            match canonical.kind {
+                CanonicalKind::SelfReferencing => report.self_referencing += 1,
+                CanonicalKind::Canonicalized => report.canonicalized += 1,
+                CanonicalKind::CrossDomain => report.cross_domain += 1,
+                CanonicalKind::Missing => {
+                    report.missing += 1;
+                    report.issues.push(issue(
+                        CanonicalIssueKind::Missing,
+                        "No canonical tag found".to_string(),
+                    ));
+                    continue;
+                }
+                CanonicalKind::Multiple => report.issues.push(issue(
+                    CanonicalIssueKind::Multiple,
+                    format!("{} canonical tags found", canonical.count),
+                )),
+                CanonicalKind::Invalid => {
+                    report.issues.push(issue(
+                        CanonicalIssueKind::Invalid,
+                        "Canonical URL could not be resolved".to_string(),
+                    ));
+                    continue;
+                }
+            }
+
+            if canonical.kind == CanonicalKind::SelfReferencing {
+                continue;
+            }
+
+            let Some(target_url) = canonical
+                .resolved
+                .as_deref()
+                .and_then(|u| Url::parse(u).ok())
+                .map(|u| normalise_url(&u))
+            else {
+                continue;
+            };
+
+            // A URL that was crawled itself is not a redirect, whatever another page went through
+            if !self.by_url.contains_key(&target_url) {
+                if let Some((hop, landing)) = self.redirected.get(&target_url) {
+                    report.issues.push(issue(
+                        CanonicalIssueKind::RedirectTarget,
+                        format!(
+                            "Canonical target returned {} and redirects to {}",
+                            hop.status_code, self.pages[*landing].url
+                        ),
+                    ));
+                    continue;
+                }
+            }
+
+            // Only targets that were crawled can be checked further
+            let Some(target) = self.by_url.get(&target_url).map(|&i| &self.pages[i]) else {
+                continue;
+            };
+
+            if (300..400).contains(&target.status_code) {
+                report.issues.push(issue(
+                    CanonicalIssueKind::RedirectTarget,
+                    format!("Canonical target returned {}", target.status_code),
+                ));
+                continue;
+            }
+
+            if target.status_code != 200 {
+                report.issues.push(issue(
+                    CanonicalIssueKind::NonOkTarget,
+                    format!("Canonical target returned {}", target.status_code),
+                ));
+            }
+
+            if target.noindex {
+                report.issues.push(issue(
+                    CanonicalIssueKind::NoindexTarget,
+                    "Canonical target is noindexed".to_string(),
+                ));
+            }
+
+            if let (Some(next), Ok(target_url)) =
+                (&target.canonical.resolved, Url::parse(&target.url))
+            {
+                let points_elsewhere = Url::parse(next)
+                    .map(|next| !same_url(&next, &target_url))
+                    .unwrap_or(false);
+                if points_elsewhere {
+                    report.issues.push(issue(
+                        CanonicalIssueKind::Chain,
+                        format!("Canonical target canonicalizes to {}", next),
+                    ));
+                }
+            }
+        }
+
+        report
+    }
+}
+
+pub fn register_issues(report: &CanonicalReport, issues: &mut IssueRegistry) {
+    for issue in &report.issues {
+        let kind = match issue.kind {
+            CanonicalIssueKind::Missing => IssueKind::MissingCanonical,
+            CanonicalIssueKind::Multiple | CanonicalIssueKind::Invalid => {
+                IssueKind::InvalidCanonical
+            }
+            CanonicalIssueKind::Chain => IssueKind::CanonicalChain,
+            CanonicalIssueKind::NonOkTarget => IssueKind::CanonicalToError,
+            CanonicalIssueKind::NoindexTarget => IssueKind::CanonicalToNoindex,
+            CanonicalIssueKind::RedirectTarget => IssueKind::CanonicalToRedirect,
+        };
+        issues.flag(kind, &issue.url);
+    }
+}
+
+impl StoredReport for CanonicalReport {
+    const KIND: &'static str = "canonical";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain_crawler::helpers::canonical_selector::audit_canonical;
+
+    fn audit_canonicals(results: &[DomainCrawlResults]) -> CanonicalReport {
+        let mut collector = CanonicalCollector::default();
+        for result in results {
+            collector.add(result);
+        }
+        collector.finish()
+    }
+
+    fn page(url: &str, status_code: u16, canonical: Option<&str>) -> DomainCrawlResults {
+        let html = match canonical {
+            Some(href) => format!(r#"<head><link rel="canonical" href="{}"></head>"#, href),
+            None => String::new(),
+        };
+        DomainCrawlResults {
+            status_code,
+            canonical: audit_canonical(&html, &Url::parse(url).unwrap()),
+            ..DomainCrawlResults::crawled(url)
+        }
+    }
+
+    fn kinds(report: &CanonicalReport, url: &str) -> Vec<CanonicalIssueKind> {
+        report
+            .issues
+            .iter()
+            .filter(|issue| issue.url == url)
+            .map(|issue| issue.kind.clone())
+            .collect()
+    }
+
+    #[test]
+    fn resolves_and_classifies_canonicals() {
+        let url = Url::parse("https://example.com/shoes/").unwrap();
+        let link = |href: &str| format!(r#"<link rel="canonical" href="{}">"#, href);
+
+        let relative = audit_canonical(&link("/shoes"), &url);
+        assert!(relative.relative);
+        assert_eq!(relative.kind, CanonicalKind::SelfReferencing);
+        assert_eq!(
+            relative.resolved.as_deref(),
+            Some("https://example.com/shoes")
+        );
+
+        let other = audit_canonical(&link("red"), &url);
+        assert_eq!(other.kind, CanonicalKind::Canonicalized);
+        assert_eq!(
+            other.resolved.as_deref(),
+            Some("https://example.com/shoes/red")
+        );
+
+        let cross = audit_canonical(&link("https://shop.example.com/shoes"), &url);
+        assert_eq!(cross.kind, CanonicalKind::CrossDomain);
+        assert!(!cross.relative);
+
+        let both = format!("{}{}", link("/a"), link("/b"));
+        assert_eq!(audit_canonical(&both, &url).kind, CanonicalKind::Multiple);
+        assert_eq!(audit_canonical("", &url).kind, CanonicalKind::Missing);
+    }
+
+    #[test]
+    fn flags_canonicals_to_redirecting_urls() {
+        let mut moved = page("https://example.com/new", 200, Some("/new"));
+        moved.redirect_chain = vec![RedirectHop {
+            url: "https://example.com/old".to_string(),
+            status_code: 301,
+            location: "/new".to_string(),
+        }];
+        let results = vec![
+            page("https://example.com/a", 200, Some("/old")),
+            page("https://example.com/b", 200, Some("/new/")),
+            moved,
+        ];
+
+        let report = audit_canonicals(&results);
+        assert_eq!(
+            kinds(&report, "https://example.com/a"),
+            [CanonicalIssueKind::RedirectTarget]
+        );
+        assert!(kinds(&report, "https://example.com/b").is_empty());
+        assert!(kinds(&report, "https://example.com/new").is_empty());
+    }
+
+    #[test]
+    fn flags_chains_and_error_targets() {
+        let results = vec![
+            page("https://example.com/a", 200, Some("/b")),
+            page("https://example.com/b", 200, Some("/c")),
+            page("https://example.com/c", 200, Some("/c")),
+            page("https://example.com/d", 200, Some("/gone")),
+            page("https://example.com/gone", 404, None),
+            page("https://example.com/e", 200, None),
+        ];
+
+        let report = audit_canonicals(&results);
+        assert_eq!(
+            kinds(&report, "https://example.com/a"),
+            [CanonicalIssueKind::Chain]
+        );
+        assert_eq!(
+            kinds(&report, "https://example.com/d"),
+            [CanonicalIssueKind::NonOkTarget]
+        );
+        assert_eq!(
+            kinds(&report, "https://example.com/e"),
+            [CanonicalIssueKind::Missing]
+        );
+        assert_eq!(report.self_referencing, 1);
+        assert_eq!(report.canonicalized, 3);
+        assert_eq!(report.missing, 1);
+    }
+}
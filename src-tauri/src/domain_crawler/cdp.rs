@@ -0,0 +1,377 @@
+use std::collections::VecDeque;
+use std::process::Stdio;
+
+use base64::{engine::general_purpose, Engine};
+use rand::Rng;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, SemaphorePermit};
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Duration, Instant};
+use url::Url;
+
+// Chrome prints the browser endpoint once the debugging port is open
+const LISTENING_PREFIX: &str = "DevTools listening on ";
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(20);
+// DevTools replies are read by their own id, events in between are kept for `wait_for_event`
+const CALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Target {
+    web_socket_debugger_url: String,
+}
+
+async fn read_message<R: AsyncRead + Unpin>(stream: &mut R) -> Result<Value, String> {
+    let mut message = Vec::new();
+    loop {
+        let mut header = [0u8; 2];
+        stream
+            .read_exact(&mut header)
+            .await
+            .map_err(|e| format!("DevTools connection lost: {}", e))?;
+        let fin = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0F;
+        let len = match header[1] & 0x7F {
+            126 => stream.read_u16().await.map_err(|e| e.to_string())? as usize,
+            127 => stream.read_u64().await.map_err(|e| e.to_string())? as usize,
+            len => len as usize,
+        };
+        // Servers do not mask, but skip a mask key if one is sent anyway
+        let mask = if header[1] & 0x80 != 0 {
+            let mut mask = [0u8; 4];
+            stream
+                .read_exact(&mut mask)
+                .await
+                .map_err(|e| e.to_string())?;
+            Some(mask)
+        } else {
+            None
+        };
+        let mut payload = vec![0u8; len];
+        stream
+            .read_exact(&mut payload)
+            .await
+            .map_err(|e| format!("DevTools connection lost: {}", e))?;
+        if let Some(mask) = mask {
+            payload
+                .iter_mut()
+                .enumerate()
+                .for_each(|(i, b)| *b ^= mask[i % 4]);
+        }
+
+        // Chrome does not ping its clients, control frames other than close are ignored
+        match opcode {
+            OPCODE_TEXT | OPCODE_CONTINUATION => {
+                message.extend_from_slice(&payload);
+                if fin {
+                    return serde_json::from_slice(&message).map_err(|e| e.to_string());
+                }
+            }
+            OPCODE_CLOSE => return Err("DevTools closed the connection".to_string()),
+            _ => {}
+        }
+    }
+}
+
+/// A final text frame of `payload`, masked with `mask` as frames from a client must be.
+fn text_frame(payload: &[u8], mask: [u8; 4]) -> Vec<u8> {
+    let mut frame = vec![0x80 | OPCODE_TEXT];
+    match payload.len() {
+        len if len < 126 => frame.push(0x80 | len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(0x80 | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    frame
+}
+
+/// A DevTools protocol session with one tab of a headless browser, which is closed on drop.
+///
+/// Speaks just enough WebSocket for the local, unencrypted debugging connection.
+pub struct DevTools {
+    writer: OwnedWriteHalf,
+    // Frames are read by a task of their own, a timed out wait never stops halfway through one
+    messages: mpsc::UnboundedReceiver<Value>,
+    reader: JoinHandle<()>,
+    next_id: u64,
+    events: VecDeque<Value>,
+    _browser: Child,
+    _slot: SemaphorePermit<'static>,
+}
+
+impl Drop for DevTools {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+impl DevTools {
+    /// Starts `command`, a headless browser with `--remote-debugging-port=0`, and attaches to
+    /// a new blank tab.
+    pub async fn launch(
+        mut command: Command,
+        slot: SemaphorePermit<'static>,
+    ) -> Result<Self, String> {
+        let mut browser = command
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start the browser: {}", e))?;
+        let stderr = browser
+            .stderr
+            .take()
+            .ok_or("The browser has no error output")?;
+
+        let mut lines = BufReader::new(stderr).lines();
+        let endpoint = timeout(STARTUP_TIMEOUT, async {
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Some(endpoint) = line.trim().strip_prefix(LISTENING_PREFIX) {
+                    return Some(endpoint.to_string());
+                }
+            }
+            None
+        })
+        .await
+        .ok()
+        .flatten()
+        .ok_or("The browser did not open a DevTools port")?;
+        // Keep draining the output so a chatty browser never blocks on a full pipe
+        tokio::spawn(async move { while let Ok(Some(_)) = lines.next_line().await {} });
+
+        let endpoint = Url::parse(&endpoint).map_err(|e| e.to_string())?;
+        let host = format!(
+            "{}:{}",
+            endpoint.host_str().unwrap_or("127.0.0.1"),
+            endpoint.port().unwrap_or(9222)
+        );
+        let target: Target = reqwest::Client::new()
+            .put(format!("http://{}/json/new?about:blank", host))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to open a browser tab: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to open a browser tab: {}", e))?;
+        let page = Url::parse(&target.web_socket_debugger_url).map_err(|e| e.to_string())?;
+
+        let mut stream = TcpStream::connect(&host)
+            .await
+            .map_err(|e| format!("Failed to connect to DevTools: {}", e))?;
+        let key: [u8; 16] = rand::thread_rng().gen();
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            page.path(),
+            host,
+            general_purpose::STANDARD.encode(key)
+        );
+        stream
+            .write_all(request.as_bytes())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        // Read the handshake byte by byte, frames may follow it right away
+        let mut response = Vec::new();
+        while !response.ends_with(b"\r\n\r\n") {
+            let byte = stream.read_u8().await.map_err(|e| e.to_string())?;
+            response.push(byte);
+        }
+        let status = String::from_utf8_lossy(&response);
+        if !status.starts_with("HTTP/1.1 101") {
+            return Err(format!(
+                "DevTools refused the connection: {}",
+                status.lines().next().unwrap_or("")
+            ));
+        }
+
+        let (mut reader, writer) = stream.into_split();
+        let (sender, messages) = mpsc::unbounded_channel();
+        let reader = tokio::spawn(async move {
+            while let Ok(message) = read_message(&mut reader).await {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            writer,
+            messages,
+            reader,
+            next_id: 0,
+            events: VecDeque::new(),
+            _browser: browser,
+            _slot: slot,
+        })
+    }
+
+    async fn send_text(&mut self, payload: &[u8]) -> Result<(), String> {
+        let frame = text_frame(payload, rand::thread_rng().gen());
+        self.writer
+            .write_all(&frame)
+            .await
+            .map_err(|e| format!("DevTools connection lost: {}", e))
+    }
+
+    /// Sends a DevTools command without waiting for its reply, which is then ignored.
+    pub async fn send(&mut self, method: &str, params: Value) -> Result<u64, String> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let request = json!({ "id": id, "method": method, "params": params });
+        self.send_text(request.to_string().as_bytes()).await?;
+        Ok(id)
+    }
+
+    /// Calls a DevTools method and returns its result.
+    pub async fn call(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.send(method, params).await?;
+
+        let deadline = Instant::now() + CALL_TIMEOUT;
+        loop {
+            let message = timeout(
+                deadline.saturating_duration_since(Instant::now()),
+                self.messages.recv(),
+            )
+            .await
+            .map_err(|_| format!("DevTools did not answer {}", method))?
+            .ok_or("DevTools connection lost")?;
+            if message.get("id").and_then(Value::as_u64) == Some(id) {
+                if let Some(error) = message.get("error") {
+                    return Err(format!("{} failed: {}", method, error));
+                }
+                return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+            }
+            if message.get("method").is_some() {
+                self.events.push_back(message);
+            }
+        }
+    }
+
+    /// Waits up to `limit` for the next event of any kind.
+    pub async fn next_event(&mut self, limit: Duration) -> Option<Value> {
+        if let Some(event) = self.events.pop_front() {
+            return Some(event);
+        }
+        let deadline = Instant::now() + limit;
+        while let Ok(Some(message)) = timeout(
+            deadline.saturating_duration_since(Instant::now()),
+            self.messages.recv(),
+        )
+        .await
+        {
+            if message.get("method").is_some() {
+                return Some(message);
+            }
+        }
+        None
+    }
+
+    /// Waits up to `limit` for an event, returning whether it arrived.
+    pub async fn wait_for_event(&mut self, method: &str, limit: Duration) -> bool {
+        let is_event =
+            |message: &Value| message.get("method").and_then(Value::as_str) == Some(method);
+        if let Some(position) = self.events.iter().position(is_event) {
+            self.events.remove(position);
+            return true;
+        }
+        let deadline = Instant::now() + limit;
+        while let Ok(Some(message)) = timeout(
+            deadline.saturating_duration_since(Instant::now()),
+            self.messages.recv(),
+        )
+        .await
+        {
+            if is_event(&message) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An unmasked server frame, as Chrome sends them
+    fn server_frame(fin: bool, opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![if fin { 0x80 } else { 0 } | opcode];
+        match payload.len() {
+            len if len < 126 => frame.push(len as u8),
+            len if len <= u16::MAX as usize => {
+                frame.push(126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn message_of(len: usize) -> (String, Value) {
+        let text = format!(r#"{{"id":1,"result":"{}"}}"#, "a".repeat(len));
+        let value = serde_json::from_str(&text).unwrap();
+        (text, value)
+    }
+
+    #[tokio::test]
+    async fn reads_a_message_split_over_continuation_frames() {
+        let mut bytes = server_frame(false, OPCODE_TEXT, br#"{"id":7,"#);
+        bytes.extend(server_frame(false, OPCODE_CONTINUATION, br#""result":"#));
+        bytes.extend(server_frame(true, OPCODE_CONTINUATION, br#"{"ok":true}}"#));
+
+        let message = read_message(&mut bytes.as_slice()).await.unwrap();
+        assert_eq!(message, json!({ "id": 7, "result": { "ok": true } }));
+    }
+
+    #[tokio::test]
+    async fn reads_16_and_64_bit_lengths() {
+        for len in [200, 70_000] {
+            let (text, value) = message_of(len);
+            let bytes = server_frame(true, OPCODE_TEXT, text.as_bytes());
+            assert_eq!(bytes[1], if len < 65_536 { 126 } else { 127 });
+            assert_eq!(read_message(&mut bytes.as_slice()).await.unwrap(), value);
+        }
+    }
+
+    #[tokio::test]
+    async fn close_frame_ends_the_session() {
+        let bytes = server_frame(true, OPCODE_CLOSE, &[]);
+        assert!(read_message(&mut bytes.as_slice()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn text_frames_are_masked_and_round_trip() {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        for (len, length_byte, header_len) in [(10, 30, 6), (300, 126, 8), (70_000, 127, 14)] {
+            let (text, value) = message_of(len);
+            let frame = text_frame(text.as_bytes(), mask);
+
+            assert_eq!(frame[0], 0x80 | OPCODE_TEXT);
+            assert_eq!(frame[1], 0x80 | length_byte);
+            assert_eq!(&frame[header_len - 4..header_len], &mask);
+            assert_eq!(frame.len(), header_len + text.len());
+            assert_ne!(&frame[header_len..], text.as_bytes());
+
+            assert_eq!(read_message(&mut frame.as_slice()).await.unwrap(), value);
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::projects::registry::ProjectRoots;
+use crate::settings::settings::Settings;
+use crate::AppState;
+
+use super::crawl_control;
+use super::domain_commands;
+use super::domain_crawler;
+use super::models::DomainCrawlResults;
+use super::results_store::{to_page_row, CrawlRecord, ResultsStore};
+
+const THIN_CONTENT_WORDS: usize = 200;
+const TITLE_SEPARATORS: [&str; 5] = [" | ", " - ", " – ", " — ", " :: "];
+const TOP_TITLE_WORDS: usize = 20;
+const TITLE_STOP_WORDS: [&str; 12] = [
+    "the", "and", "for", "with", "your", "you", "from", "our", "how", "what", "are", "all",
+];
+
+/// A registered competitor of a domain and its most recent crawl.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Competitor {
+    pub domain: String,
+    pub latest_crawl: Option<CrawlRecord>,
+}
+
+/// The shape of one crawl, the figures the comparison puts side by side.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrawlProfile {
+    pub crawl: Option<CrawlRecord>,
+    pub pages: usize,
+    /// Pages that answered 200 with HTML, the base for the shares below
+    pub html_pages: usize,
+    pub indexable_pages: usize,
+    pub status_codes: BTreeMap<String, usize>,
+    pub average_word_count: f64,
+    pub median_word_count: usize,
+    pub thin_pages: usize,
+    pub structured_data_pages: usize,
+    /// Share of the HTML pages with structured data, in percent
+    pub structured_data_coverage: f64,
+    /// Pages per schema type
+    pub schema_types: BTreeMap<String, usize>,
+    pub missing_titles: usize,
+    pub duplicate_titles: usize,
+    pub average_title_length: f64,
+    /// Titles using each separator, such as "Page | Brand"
+    pub title_separators: BTreeMap<String, usize>,
+    /// The most common text after the last separator, usually the brand
+    pub title_suffix: Option<String>,
+    pub title_suffix_pages: usize,
+    pub top_title_words: Vec<(String, usize)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompetitorComparison {
+    pub site: CrawlProfile,
+    pub competitor: CrawlProfile,
+    /// Schema types the competitor marks up and the site does not
+    pub missing_schema_types: Vec<String>,
+    pub exclusive_schema_types: Vec<String>,
+    pub shared_title_words: Vec<String>,
+}
+
+#[derive(Default)]
+struct ProfileCollector {
+    profile: CrawlProfile,
+    word_counts: Vec<usize>,
+    titles: HashMap<String, usize>,
+    suffixes: HashMap<String, usize>,
+    title_words: HashMap<String, usize>,
+    title_length: usize,
+}
+
+impl ProfileCollector {
+    fn add(&mut self, page: &DomainCrawlResults) {
+        let row = to_page_row(page);
+        let profile = &mut self.profile;
+        profile.pages += 1;
+        let bucket = match row.status_code {
+            0 => "Failed".to_string(),
+            code => format!("{}xx", code / 100),
+        };
+        *profile.status_codes.entry(bucket).or_insert(0) += 1;
+
+        if row.status_code != 200 || !row.content_type.contains("html") {
+            return;
+        }
+        profile.html_pages += 1;
+        if row.indexability > 0.5 {
+            profile.indexable_pages += 1;
+        }
+        self.word_counts.push(row.word_count);
+        if row.word_count < THIN_CONTENT_WORDS {
+            profile.thin_pages += 1;
+        }
+
+        if !page.structured_data.types.is_empty() {
+            profile.structured_data_pages += 1;
+        }
+        for schema_type in page.structured_data.types.iter().collect::<BTreeSet<_>>() {
+            *profile.schema_types.entry(schema_type.clone()).or_insert(0) += 1;
+        }
+
+        let Some(title) = row
+            .title
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+        else {
+            profile.missing_titles += 1;
+            return;
+        };
+        self.title_length += title.chars().count();
+        *self.titles.entry(title.to_lowercase()).or_insert(0) += 1;
+
+        let separator = TITLE_SEPARATORS
+            .iter()
+            .filter_map(|separator| title.rfind(separator).map(|index| (index, separator)))
+            .max_by_key(|(index, _)| *index);
+        let head = match separator {
+            Some((index, separator)) => {
+                *profile
+                    .title_separators
+                    .entry(separator.trim().to_string())
+                    .or_insert(0) += 1;
+                let suffix = title[index + separator.len()..].trim().to_lowercase();
+                if !suffix.is_empty() {
+                    *self.suffixes.entry(suffix).or_insert(0) += 1;
+                }
+                &title[..index]
+            }
+            None => title,
+        };
+        // The words of the title without the brand part
+        for word in head
+            .split(|c: char| !c.is_alphanumeric())
+            .map(str::to_lowercase)
+            .filter(|word| word.chars().count() >= 3 && !TITLE_STOP_WORDS.contains(&word.as_str()))
+        {
+            *self.title_words.entry(word).or_insert(0) += 1;
+        }
+    }
+
+    fn finish(mut self, crawl: Option<CrawlRecord>) -> CrawlProfile {
+        let mut profile = self.profile;
+        profile.crawl = crawl;
+
+        self.word_counts.sort_unstable();
+        if !self.word_counts.is_empty() {
+            profile.average_word_count =
+                self.word_counts.iter().sum::<usize>() as f64 / self.word_counts.len() as f64;
+            profile.median_word_count = self.word_counts[self.word_counts.len() / 2];
+        }
+        if profile.html_pages > 0 {
+            profile.structured_data_coverage =
+                profile.structured_data_pages as f64 / profile.html_pages as f64 * 100.0;
+        }
+
+        let titled = profile.html_pages - profile.missing_titles;
+        if titled > 0 {
+            profile.average_title_length = self.title_length as f64 / titled as f64;
+        }
+        profile.duplicate_titles = self.titles.values().filter(|count| **count > 1).sum();
+        // A suffix on a single page is just a title, not a pattern
+        if let Some((suffix, pages)) = self
+            .suffixes
+            .into_iter()
+            .filter(|(_, pages)| *pages > 1)
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        {
+            profile.title_suffix = Some(suffix);
+            profile.title_suffix_pages = pages;
+        }
+
+        let mut words: Vec<(String, usize)> = self.title_words.into_iter().collect();
+        words.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        words.truncate(TOP_TITLE_WORDS);
+        profile.top_title_words = words;
+        profile
+    }
+}
+
+/// Reads a stored crawl once and profiles its pages.
+pub async fn profile_crawl(store: &ResultsStore, crawl_id: i64) -> Result<CrawlProfile, String> {
+    let crawl = store
+        .crawl(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Crawl {} not found", crawl_id))?;
+
+    let collector = Arc::new(Mutex::new(ProfileCollector::default()));
+    let pages = collector.clone();
+    store
+        .for_each_page(crawl_id, move |page| {
+            pages.lock().map_err(|e| e.to_string())?.add(&page);
+            Ok(())
+        })
+        .await?;
+    let collector = std::mem::take(&mut *collector.lock().map_err(|e| e.to_string())?);
+    Ok(collector.finish(Some(crawl)))
+}
+
+/// Puts the profiles of a site crawl and a competitor crawl side by side.
+pub fn compare_profiles(site: CrawlProfile, competitor: CrawlProfile) -> CompetitorComparison {
+    let site_types: BTreeSet<&String> = site.schema_types.keys().collect();
+    let competitor_types: BTreeSet<&String> = competitor.schema_types.keys().collect();
+    let site_words: BTreeSet<&String> = site.top_title_words.iter().map(|(w, _)| w).collect();
+
+    CompetitorComparison {
+        missing_schema_types: competitor_types
+            .difference(&site_types)
+            .map(|t| t.to_string())
+            .collect(),
+        exclusive_schema_types: site_types
+            .difference(&competitor_types)
+            .map(|t| t.to_string())
+            .collect(),
+        shared_title_words: competitor
+            .top_title_words
+            .iter()
+            .filter(|(word, _)| site_words.contains(word))
+            .map(|(word, _)| word.clone())
+            .collect(),
+        site,
+        competitor,
+    }
+}
+
+// Competitors are filed by bare host, whatever form the domain was typed in
+fn host_key(domain: &str) -> Result<String, String> {
+    Url::parse(&super::helpers::domain_checker::url_check(domain.trim()))
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+        .map(|host| host.trim_start_matches("www.").to_string())
+        .ok_or_else(|| format!("Invalid domain: {}", domain))
+}
+
+// The site's own logins and headers must not be sent to another site
+fn competitor_settings(settings: &Settings) -> Settings {
+    let mut settings = settings.clone();
+    settings.auth_username.clear();
+    settings.auth_password.clear();
+    settings.auth_bearer_token.clear();
+    settings.custom_headers.clear();
+    settings.session_cookies.clear();
+    settings.cookies_file.clear();
+    settings.login_url.clear();
+    settings.login_fields.clear();
+    settings
+}
+
+async fn latest_crawl(store: &ResultsStore, host: &str) -> Result<Option<CrawlRecord>, String> {
+    Ok(store
+        .list_crawls()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|crawl| host_key(&crawl.domain).is_ok_and(|crawl_host| crawl_host == host)))
+}
+
+// REGISTER A COMPETITOR OF A DOMAIN
+#[tauri::command]
+pub async fn add_competitor(domain: String, competitor: String) -> Result<(), String> {
+    let (domain, competitor) = (host_key(&domain)?, host_key(&competitor)?);
+    if domain == competitor {
+        return Err("A domain cannot be its own competitor".to_string());
+    }
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    store
+        .add_competitor(&domain, &competitor)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_competitor(domain: String, competitor: String) -> Result<(), String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    store
+        .remove_competitor(&host_key(&domain)?, &host_key(&competitor)?)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// LIST THE COMPETITORS OF A DOMAIN WITH THEIR LATEST CRAWLS
+#[tauri::command]
+pub async fn list_competitors(domain: String) -> Result<Vec<Competitor>, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let mut competitors = Vec::new();
+    for competitor in store
+        .competitors(&host_key(&domain)?)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        competitors.push(Competitor {
+            latest_crawl: latest_crawl(&store, &competitor).await?,
+            domain: competitor,
+        });
+    }
+    Ok(competitors)
+}
+
+// CRAWL A COMPETITOR WITH THE SETTINGS OF THE SITE, WITHOUT ITS CREDENTIALS
+#[tauri::command]
+pub async fn competitor_crawl_command(
+    domain: String,
+    competitor: String,
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<domain_crawler::CrawlOutcome, String> {
+    let crawl = crawl_control::try_start()?;
+    add_competitor(domain, competitor.clone()).await?;
+    let settings = competitor_settings(&*settings_state.settings.read().await);
+    let roots = ProjectRoots::active()?;
+    let db = domain_commands::crawl_database(&roots).await?;
+    let outcome = domain_crawler::crawl_domain(
+        &competitor,
+        app_handle.into(),
+        &roots,
+        Ok(db),
+        settings,
+        false,
+        None,
+        crawl,
+    )
+    .await?;
+    println!(
+        "Crawled {} pages of competitor {}",
+        outcome.pages, competitor
+    );
+    Ok(outcome)
+}
+
+// COMPARE A CRAWL OF THE SITE WITH A CRAWL OF A COMPETITOR
+#[tauri::command]
+pub async fn compare_competitor_crawls(
+    crawl_id: i64,
+    competitor_crawl_id: i64,
+) -> Result<CompetitorComparison, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let site = profile_crawl(&store, crawl_id).await?;
+    let competitor = profile_crawl(&store, competitor_crawl_id).await?;
+    Ok(compare_profiles(site, competitor))
+}
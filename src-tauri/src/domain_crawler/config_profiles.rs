@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::fs;
+
+use crate::projects::registry::ProjectRoots;
+use crate::settings::settings::Settings;
+use crate::AppState;
+
+// Next to the settings file, so each project keeps its own profiles
+const PROFILES_FILE: &str = "crawl_profiles.toml";
+
+// The settings a profile may carry. Credentials, API keys, proxies and paths of this machine stay out,
+// so a profile can be shared as is
+const PROFILE_KEYS: [&str; 62] = [
+    "crawl_timeout",
+    "client_timeout",
+    "client_connect_timeout",
+    "redirect_policy",
+    "max_retries",
+    "base_delay",
+    "max_delay",
+    "concurrent_requests",
+    "batch_size",
+    "db_batch_size",
+    "html",
+    "links_max_concurrent_requests",
+    "links_initial_task_capacity",
+    "links_max_retries",
+    "links_retry_delay",
+    "links_request_timeout",
+    "images_request_timeout",
+    "images_connect_timeout",
+    "images_pool_max_idle_per_host",
+    "images_pool_idle_timeout",
+    "images_decode_dimensions",
+    "images_decode_max_bytes",
+    "link_checker",
+    "respect_robots",
+    "sitemap_discovery",
+    "per_host_delay_ms",
+    "per_host_burst",
+    "max_image_checks",
+    "redirect_chain_threshold",
+    "thin_content_threshold",
+    "near_duplicate_threshold",
+    "render_javascript",
+    "render_wait_ms",
+    "user_agent_profile",
+    "custom_user_agent",
+    "proxy_rotate",
+    "proxy_max_failures",
+    "scope_include",
+    "scope_exclude",
+    "scope_include_subdomains",
+    "scope_protocol",
+    "max_crawl_depth",
+    "max_crawl_urls",
+    "strip_query_params",
+    "sort_query_params",
+    "important_page_max_depth",
+    "disk_frontier",
+    "conditional_requests",
+    "tls_expiry_warning_days",
+    "preflight_checks",
+    "waterfall_timing",
+    "icon_checks",
+    "large_image_threshold_kb",
+    "transcode_image_samples",
+    "spell_check",
+    "entity_extraction",
+    "entity_extraction_pages",
+    "capture_screenshots",
+    "screenshot_full_page",
+    "lab_vitals",
+    "custom_search",
+    "tracking_expected_ids",
+];
+
+/// A named set of crawl settings applied over the current ones when a crawl starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Setting name to value, only the keys the profile changes
+    #[serde(default)]
+    pub settings: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProfilesFile {
+    #[serde(default)]
+    profiles: Vec<ConfigProfile>,
+}
+
+impl ConfigProfile {
+    /// The current values of every setting a profile may carry.
+    fn snapshot(name: &str, description: &str, settings: &Settings) -> Result<Self, String> {
+        let value = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+        Ok(ConfigProfile {
+            name: name.to_string(),
+            description: description.to_string(),
+            settings: value
+                .as_object()
+                .map(|fields| {
+                    fields
+                        .iter()
+                        .filter(|(key, value)| {
+                            PROFILE_KEYS.contains(&key.as_str()) && !value.is_null()
+                        })
+                        .map(|(key, value)| (key.clone(), value.clone()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    // Drops the keys a profile may not carry and checks the values fit the settings
+    fn clean(mut self) -> Result<Self, String> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            return Err("A profile needs a name".to_string());
+        }
+        self.settings.retain(|key, value| {
+            let keep = PROFILE_KEYS.contains(&key.as_str()) && !value.is_null();
+            if !keep {
+                eprintln!("Profile {}: ignoring setting {}", self.name, key);
+            }
+            keep
+        });
+        self.apply(&Settings::new())?;
+        Ok(self)
+    }
+
+    /// The settings with the profile's values written over them.
+    pub fn apply(&self, settings: &Settings) -> Result<Settings, String> {
+        let mut value = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+        let fields = value
+            .as_object_mut()
+            .ok_or("Settings are not an object".to_string())?;
+        for (key, setting) in &self.settings {
+            if PROFILE_KEYS.contains(&key.as_str()) && !setting.is_null() {
+                fields.insert(key.clone(), setting.clone());
+            }
+        }
+        serde_json::from_value(value)
+            .map_err(|e| format!("Profile {} has an invalid setting: {}", self.name, e))
+    }
+}
+
+fn profiles_path(roots: &ProjectRoots) -> PathBuf {
+    roots.config_file(PROFILES_FILE)
+}
+
+async fn load_profiles(roots: &ProjectRoots) -> Result<Vec<ConfigProfile>, String> {
+    let path = profiles_path(roots);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Failed to read crawl profiles: {}", e))?;
+    let file: ProfilesFile =
+        toml::from_str(&contents).map_err(|e| format!("Failed to parse crawl profiles: {}", e))?;
+    Ok(file.profiles)
+}
+
+async fn save_profiles(roots: &ProjectRoots, profiles: Vec<ConfigProfile>) -> Result<(), String> {
+    let path = profiles_path(roots);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let toml_str = toml::to_string(&ProfilesFile { profiles })
+        .map_err(|e| format!("Failed to serialize crawl profiles: {}", e))?;
+    fs::write(&path, toml_str)
+        .await
+        .map_err(|e| format!("Failed to write crawl profiles: {}", e))
+}
+
+// Adds the profile, or replaces the one with the same name
+async fn store_profile(
+    roots: &ProjectRoots,
+    profile: ConfigProfile,
+) -> Result<ConfigProfile, String> {
+    let mut profiles = load_profiles(roots).await?;
+    profiles.retain(|p| !p.name.eq_ignore_ascii_case(&profile.name));
+    profiles.push(profile.clone());
+    profiles.sort_by_key(|p| p.name.to_lowercase());
+    save_profiles(roots, profiles).await?;
+    Ok(profile)
+}
+
+async fn find_profile(roots: &ProjectRoots, name: &str) -> Result<ConfigProfile, String> {
+    load_profiles(roots)
+        .await?
+        .into_iter()
+        .find(|profile| profile.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| format!("Crawl profile {} not found", name))
+}
+
+/// The settings for a crawl: the project's, with the named profile of that project over them
+/// if one was picked.
+pub async fn crawl_settings(
+    roots: &ProjectRoots,
+    settings: Settings,
+    profile: Option<&str>,
+) -> Result<Settings, String> {
+    match profile.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => find_profile(roots, name).await?.apply(&settings),
+        None => Ok(settings),
+    }
+}
+
+#[tauri::command]
+pub async fn list_config_profiles() -> Result<Vec<ConfigProfile>, String> {
+    load_profiles(&ProjectRoots::active()?).await
+}
+
+// SAVE THE CURRENT CRAWL SETTINGS AS A NAMED PROFILE
+#[tauri::command]
+pub async fn save_config_profile(
+    name: String,
+    description: Option<String>,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<ConfigProfile, String> {
+    let settings = settings_state.settings.read().await.clone();
+    let profile =
+        ConfigProfile::snapshot(&name, description.as_deref().unwrap_or(""), &settings)?.clean()?;
+    store_profile(&ProjectRoots::active()?, profile).await
+}
+
+#[tauri::command]
+pub async fn delete_config_profile(name: String) -> Result<(), String> {
+    let roots = ProjectRoots::active()?;
+    let mut profiles = load_profiles(&roots).await?;
+    let count = profiles.len();
+    profiles.retain(|profile| !profile.name.eq_ignore_ascii_case(name.trim()));
+    if profiles.len() == count {
+        return Err(format!("Crawl profile {} not found", name));
+    }
+    save_profiles(&roots, profiles).await
+}
+
+// EXPORT A PROFILE AS TOML OR JSON TO SHARE IT
+#[tauri::command]
+pub async fn export_config_profile(name: String, format: String) -> Result<String, String> {
+    let profile = find_profile(&ProjectRoots::active()?, &name).await?;
+    match format.trim().to_lowercase().as_str() {
+        "toml" => toml::to_string(&profile).map_err(|e| e.to_string()),
+        "json" => serde_json::to_string_pretty(&profile).map_err(|e| e.to_string()),
+        other => Err(format!("Unknown profile format: {}", other)),
+    }
+}
+
+// IMPORT A PROFILE EXPORTED AS TOML OR JSON
+#[tauri::command]
+pub async fn import_config_profile(
+    contents: String,
+    overwrite: Option<bool>,
+) -> Result<ConfigProfile, String> {
+    let profile: ConfigProfile = if contents.trim_start().starts_with('{') {
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid JSON profile: {}", e))?
+    } else {
+        toml::from_str(&contents).map_err(|e| format!("Invalid TOML profile: {}", e))?
+    };
+    let profile = profile.clean()?;
+    let roots = ProjectRoots::active()?;
+    if !overwrite.unwrap_or(false) && find_profile(&roots, &profile.name).await.is_ok() {
+        return Err(format!(
+            "A crawl profile named {} already exists",
+            profile.name
+        ));
+    }
+    store_profile(&roots, profile).await
+}
@@ -0,0 +1,276 @@
+use std::collections::HashSet;
+
+use reqwest::Client;
+use serde::Serialize;
+
+use super::a11y::audit::{self as a11y_audit, A11yCollector};
+use super::alt_text_audit::{self, AltTextCollector};
+use super::amp_audit::{self, AmpCollector};
+use super::asset_audit::AssetCollector;
+use super::canonical_audit::{self, CanonicalCollector};
+use super::crawl_depth::{self, DepthCollector};
+use super::crawl_timing::TimingCollector;
+use super::custom_search::CustomSearchCollector;
+use super::duplicate_content::{self, DuplicateCollector};
+use super::entity_audit;
+use super::events::CrawlEvents;
+use super::helpers::sitemap::{SitemapEntry, SitemapReport};
+use super::hreflang_audit::{self, HreflangCollector};
+use super::image_audit::{self, ImageCollector};
+use super::issues::IssueRegistry;
+use super::keyword_audit::KeywordCollector;
+use super::models::DomainCrawlResults;
+use super::pagination_audit::{self, PaginationCollector};
+use super::redirect_audit::{self, RedirectCollector};
+use super::render_audit::RenderCollector;
+use super::renderer;
+use super::reports::summary;
+use super::results_store::{ResultsStore, StoredReport};
+use super::security_headers_audit::{self, SecurityHeadersCollector};
+use super::sitemap_gap::SitemapGapCollector;
+use super::title_description_audit::{self, TitleDescriptionCollector};
+use super::tls_audit::TlsCollector;
+use super::tracking_audit::{self, TrackingCollector};
+use super::url_normalizer::ParameterCollector;
+use crate::settings::settings::Settings;
+
+/// What the crawl itself knows beyond its pages, for the audits that need it.
+pub struct CrawlFacts<'a> {
+    pub normalized_links: usize,
+    pub limited_pages: &'a HashSet<String>,
+    /// Checks hreflang targets without following redirects
+    pub hreflang_client: &'a Client,
+    /// The sitemap report fetched for this crawl, if discovery ran
+    pub sitemap: Option<&'a SitemapReport>,
+    /// Where the reports are kept, with the crawl they belong to
+    pub store: Option<(&'a ResultsStore, i64)>,
+}
+
+/// Every crawl-level audit, fed the pages of a finished crawl one at a time.
+pub struct CrawlAudits {
+    settings: Settings,
+    issues: IssueRegistry,
+    canonical: CanonicalCollector,
+    hreflang: HreflangCollector,
+    redirects: RedirectCollector,
+    titles: TitleDescriptionCollector,
+    duplicates: DuplicateCollector,
+    parameters: ParameterCollector,
+    depths: DepthCollector,
+    timings: TimingCollector,
+    a11y: A11yCollector,
+    alt_texts: AltTextCollector,
+    images: ImageCollector,
+    keywords: KeywordCollector,
+    assets: AssetCollector,
+    security_headers: SecurityHeadersCollector,
+    tls: TlsCollector,
+    amp: AmpCollector,
+    pagination: PaginationCollector,
+    tracking: TrackingCollector,
+    render: RenderCollector,
+    custom_search: CustomSearchCollector,
+    sitemap_gap: SitemapGapCollector,
+}
+
+impl CrawlAudits {
+    pub fn new(settings: &Settings, sitemap_entries: &[SitemapEntry]) -> Self {
+        Self {
+            settings: settings.clone(),
+            issues: IssueRegistry::default(),
+            canonical: CanonicalCollector::default(),
+            hreflang: HreflangCollector::default(),
+            redirects: RedirectCollector::new(settings.redirect_chain_threshold),
+            titles: TitleDescriptionCollector::default(),
+            duplicates: DuplicateCollector::new(settings.near_duplicate_threshold),
+            parameters: ParameterCollector::default(),
+            depths: DepthCollector::new(sitemap_entries, settings.important_page_max_depth),
+            timings: TimingCollector::default(),
+            a11y: A11yCollector::default(),
+            alt_texts: AltTextCollector::default(),
+            images: ImageCollector::new(settings.large_image_threshold_kb),
+            keywords: KeywordCollector::default(),
+            assets: AssetCollector::default(),
+            security_headers: SecurityHeadersCollector::default(),
+            tls: TlsCollector::default(),
+            amp: AmpCollector::default(),
+            pagination: PaginationCollector::default(),
+            tracking: TrackingCollector::default(),
+            render: RenderCollector::default(),
+            custom_search: CustomSearchCollector::default(),
+            sitemap_gap: SitemapGapCollector::default(),
+        }
+    }
+
+    /// The registry the link checker and other crawl-wide checks file their findings in.
+    pub fn issues(&mut self) -> &mut IssueRegistry {
+        &mut self.issues
+    }
+
+    pub fn add(&mut self, page: &DomainCrawlResults) {
+        summary::register_page_issues(page, &mut self.issues);
+        security_headers_audit::register_page_issues(page, &mut self.issues);
+        self.canonical.add(page);
+        self.hreflang.add(page);
+        self.redirects.add(page);
+        self.titles.add(page);
+        self.duplicates.add(page);
+        self.parameters.add(page);
+        self.depths.add(page);
+        self.timings.add(page);
+        self.a11y.add(page);
+        self.alt_texts.add(page);
+        self.images.add(page);
+        self.keywords.add(page);
+        self.assets.add(page);
+        self.security_headers.add(page);
+        self.tls.add(page);
+        self.amp.add(page);
+        self.pagination.add(page);
+        self.tracking.add(page);
+        self.render.add(page);
+        self.custom_search.add(page);
+        self.sitemap_gap.add(page);
+    }
+
+    /// Builds, emits and stores every report, and returns the issues filed along the way.
+    pub async fn finish(self, events: &CrawlEvents, facts: CrawlFacts<'_>) -> IssueRegistry {
+        let Self {
+            settings,
+            mut issues,
+            ..
+        } = self;
+
+        let canonical_report = self.canonical.finish();
+        emit(events, "canonical_report", &canonical_report);
+        canonical_audit::register_issues(&canonical_report, &mut issues);
+        save_report(facts.store, &canonical_report).await;
+
+        let hreflang_report = self.hreflang.finish(facts.hreflang_client).await;
+        emit(events, "hreflang_report", &hreflang_report);
+        hreflang_audit::register_issues(&hreflang_report, &mut issues);
+        save_report(facts.store, &hreflang_report).await;
+
+        let redirect_report = self.redirects.finish();
+        emit(events, "redirect_report", &redirect_report);
+        redirect_audit::register_issues(&redirect_report, &mut issues);
+        save_report(facts.store, &redirect_report).await;
+
+        let title_description_report = self.titles.finish();
+        emit(
+            events,
+            "title_description_report",
+            &title_description_report,
+        );
+        title_description_audit::register_issues(&title_description_report, &mut issues);
+        save_report(facts.store, &title_description_report).await;
+
+        let duplicate_report = self.duplicates.finish();
+        emit(events, "duplicate_content_report", &duplicate_report);
+        duplicate_content::register_issues(&duplicate_report, &mut issues);
+        save_report(facts.store, &duplicate_report).await;
+
+        let parameter_report = self.parameters.finish(facts.normalized_links);
+        emit(events, "parameter_report", &parameter_report);
+        save_report(facts.store, &parameter_report).await;
+
+        let depth_report = self.depths.finish();
+        emit(events, "depth_report", &depth_report);
+        crawl_depth::register_issues(&depth_report, &mut issues);
+        save_report(facts.store, &depth_report).await;
+
+        let timing_report = self.timings.finish();
+        emit(events, "timing_report", &timing_report);
+        save_report(facts.store, &timing_report).await;
+
+        let a11y_report = self.a11y.finish();
+        emit(events, "a11y_report", &a11y_report);
+        a11y_audit::register_issues(&a11y_report, &mut issues);
+        save_report(facts.store, &a11y_report).await;
+
+        let alt_text_report = self.alt_texts.finish();
+        emit(events, "alt_text_report", &alt_text_report);
+        alt_text_audit::register_issues(&alt_text_report, &mut issues);
+        save_report(facts.store, &alt_text_report).await;
+
+        let mut image_report = self.images.finish();
+        if settings.transcode_image_samples > 0 {
+            image_audit::measure_savings(&mut image_report, settings.transcode_image_samples).await;
+        }
+        emit(events, "image_report", &image_report);
+        image_audit::register_issues(&image_report, &mut issues);
+        save_report(facts.store, &image_report).await;
+
+        let keyword_report = self.keywords.finish();
+        emit(events, "keyword_report", &keyword_report);
+        save_report(facts.store, &keyword_report).await;
+
+        let asset_report = self.assets.finish();
+        emit(events, "asset_report", &asset_report);
+        save_report(facts.store, &asset_report).await;
+
+        let security_headers_report = self.security_headers.finish();
+        emit(events, "security_headers_report", &security_headers_report);
+        save_report(facts.store, &security_headers_report).await;
+
+        let tls_report = self.tls.audit(settings.tls_expiry_warning_days).await;
+        emit(events, "tls_report", &tls_report);
+        self.tls.register_issues(&tls_report, &mut issues);
+        save_report(facts.store, &tls_report).await;
+
+        let amp_report = self.amp.finish().await;
+        emit(events, "amp_report", &amp_report);
+        amp_audit::register_issues(&amp_report, &mut issues);
+        save_report(facts.store, &amp_report).await;
+
+        let pagination_report = self.pagination.finish(facts.limited_pages);
+        emit(events, "pagination_report", &pagination_report);
+        pagination_audit::register_issues(&pagination_report, &mut issues);
+        save_report(facts.store, &pagination_report).await;
+
+        let tracking_report = self.tracking.finish(&settings.tracking_expected_ids);
+        emit(events, "tracking_report", &tracking_report);
+        tracking_audit::register_issues(&tracking_report, &mut issues);
+        save_report(facts.store, &tracking_report).await;
+
+        if entity_audit::is_active() {
+            let entity_report = entity_audit::extract_entities().await;
+            emit(events, "entity_report", &entity_report);
+            save_report(facts.store, &entity_report).await;
+        }
+
+        if renderer::is_active() {
+            let render_report = self.render.finish();
+            emit(events, "render_report", &render_report);
+            save_report(facts.store, &render_report).await;
+        }
+
+        if settings.custom_search {
+            let custom_search_report = self.custom_search.finish();
+            emit(events, "custom_search_report", &custom_search_report);
+            save_report(facts.store, &custom_search_report).await;
+        }
+
+        if let Some(sitemap_report) = facts.sitemap {
+            let gap_report = self.sitemap_gap.finish(sitemap_report);
+            save_report(facts.store, &gap_report).await;
+        }
+
+        issues
+    }
+}
+
+fn emit<S: Serialize + Clone>(events: &CrawlEvents, event: &str, report: &S) {
+    if let Err(err) = events.emit(event, report) {
+        eprintln!("Failed to emit {}: {}", event, err);
+    }
+}
+
+/// Stores a report under its crawl, a failure only costs the stored copy.
+pub async fn save_report<R: StoredReport>(store: Option<(&ResultsStore, i64)>, report: &R) {
+    if let Some((store, crawl_id)) = store {
+        if let Err(err) = store.save_report(crawl_id, report).await {
+            eprintln!("Failed to store {} report: {}", R::KIND, err);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::OnceCell;
+
+/// Results of the running crawl by key, each computed once however many pages ask for it.
+///
+/// Callers asking for a key that is still being computed wait for that computation instead
+/// of starting their own.
+pub struct CrawlCache<V> {
+    cells: Mutex<HashMap<String, Arc<OnceCell<V>>>>,
+}
+
+impl<V: Clone> Default for CrawlCache<V> {
+    fn default() -> Self {
+        Self {
+            cells: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<V: Clone> CrawlCache<V> {
+    /// Returns the value of `key`, running `init` if no caller has yet.
+    pub async fn get_or_init<F, Fut>(&self, key: &str, init: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let cell = self
+            .cells
+            .lock()
+            .ok()
+            .map(|mut cells| cells.entry(key.to_string()).or_default().clone());
+        match cell {
+            Some(cell) => cell.get_or_init(init).await.clone(),
+            // A poisoned map only costs the sharing
+            None => init().await,
+        }
+    }
+
+    /// Forgets the values of the previous crawl.
+    pub fn clear(&self) {
+        if let Ok(mut cells) = self.cells.lock() {
+            cells.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::future::join_all;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrent_callers_share_one_computation() {
+        let cache = CrawlCache::default();
+        let runs = AtomicUsize::new(0);
+        let values = join_all((0..10).map(|_| {
+            cache.get_or_init("https://example.com/a.css", || async {
+                runs.fetch_add(1, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+                42
+            })
+        }))
+        .await;
+
+        assert!(values.iter().all(|value| *value == 42));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn clear_starts_over() {
+        let cache = CrawlCache::default();
+        assert_eq!(cache.get_or_init("key", || async { 1 }).await, 1);
+        assert_eq!(cache.get_or_init("key", || async { 2 }).await, 1);
+
+        cache.clear();
+        assert_eq!(cache.get_or_init("key", || async { 2 }).await, 2);
+    }
+}
@@ -0,0 +1,114 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tokio::sync::watch;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CrawlStatus {
+    Idle,
+    Running,
+    Paused,
+    Cancelled,
+}
+
+// Shared by the running crawl and the control commands
+static STATUS: Lazy<watch::Sender<CrawlStatus>> = Lazy::new(|| watch::channel(CrawlStatus::Idle).0);
+
+pub fn status() -> CrawlStatus {
+    *STATUS.borrow()
+}
+
+pub fn is_cancelled() -> bool {
+    status() == CrawlStatus::Cancelled
+}
+
+pub fn is_paused() -> bool {
+    status() == CrawlStatus::Paused
+}
+
+/// Keeps the crawl marked as running until dropped, so every way out of a crawl returns to idle.
+#[must_use = "the crawl is marked idle again as soon as the guard is dropped"]
+pub struct CrawlGuard(());
+
+impl Drop for CrawlGuard {
+    fn drop(&mut self) {
+        finish();
+    }
+}
+
+/// Marks a new crawl as running, unless another crawl already holds the guard.
+///
+/// The check and the switch happen under one lock, two callers can never both get a guard.
+pub fn try_start() -> Result<CrawlGuard, String> {
+    let started = STATUS.send_if_modified(|status| {
+        if *status != CrawlStatus::Idle {
+            return false;
+        }
+        *status = CrawlStatus::Running;
+        true
+    });
+    if !started {
+        return Err("A crawl is already running".to_string());
+    }
+    Ok(CrawlGuard(()))
+}
+
+fn finish() {
+    STATUS.send_replace(CrawlStatus::Idle);
+}
+
+/// Blocks while the crawl is paused.
+///
+/// Returns `false` when the crawl was cancelled and the caller should stop.
+pub async fn wait_while_paused() -> bool {
+    let mut rx = STATUS.subscribe();
+    let resumed = match rx.wait_for(|status| *status != CrawlStatus::Paused).await {
+        Ok(status) => *status != CrawlStatus::Cancelled,
+        Err(_) => true,
+    };
+    resumed
+}
+
+fn set_status(
+    from: &[CrawlStatus],
+    to: CrawlStatus,
+    app_handle: &tauri::AppHandle,
+) -> Result<(), String> {
+    let current = status();
+    if !from.contains(&current) {
+        return Err(format!(
+            "Cannot change crawl from {:?} to {:?}",
+            current, to
+        ));
+    }
+
+    STATUS.send_replace(to);
+
+    app_handle
+        .emit("crawl_status", to)
+        .map_err(|e| format!("Failed to emit event: {}", e))
+}
+
+#[tauri::command]
+pub async fn pause_crawl(app_handle: tauri::AppHandle) -> Result<(), String> {
+    set_status(&[CrawlStatus::Running], CrawlStatus::Paused, &app_handle)
+}
+
+#[tauri::command]
+pub async fn resume_crawl(app_handle: tauri::AppHandle) -> Result<(), String> {
+    set_status(&[CrawlStatus::Paused], CrawlStatus::Running, &app_handle)
+}
+
+#[tauri::command]
+pub async fn cancel_crawl(app_handle: tauri::AppHandle) -> Result<(), String> {
+    set_status(
+        &[CrawlStatus::Running, CrawlStatus::Paused],
+        CrawlStatus::Cancelled,
+        &app_handle,
+    )
+}
+
+#[tauri::command]
+pub async fn get_crawl_status() -> Result<CrawlStatus, String> {
+    Ok(status())
+}
@@ -0,0 +1,104 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::helpers::sitemap::SitemapEntry;
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthLevel {
+    pub depth: usize,
+    pub pages: usize,
+    pub urls: Vec<String>,
+}
+
+/// A page listed in the sitemap that takes more clicks to reach than the threshold.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuriedPage {
+    pub url: String,
+    pub depth: usize,
+    pub sitemap_priority: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DepthReport {
+    pub threshold: usize,
+    pub levels: Vec<DepthLevel>,
+    /// Pages without a recorded depth, such as those resumed from a saved crawl
+    pub unknown_depth: usize,
+    pub buried: Vec<BuriedPage>,
+}
+
+pub fn register_issues(report: &DepthReport, issues: &mut IssueRegistry) {
+    for page in &report.buried {
+        issues.flag(IssueKind::DeepPage, &page.url);
+    }
+}
+
+impl StoredReport for DepthReport {
+    const KIND: &'static str = "depth";
+}
+
+/// Groups the crawled pages by click depth and flags sitemap pages deeper than `threshold`.
+pub struct DepthCollector {
+    // Sitemap URLs and their priority
+    in_sitemap: HashMap<String, Option<f32>>,
+    levels: BTreeMap<usize, Vec<String>>,
+    report: DepthReport,
+}
+
+impl DepthCollector {
+    pub fn new(sitemap: &[SitemapEntry], threshold: usize) -> Self {
+        Self {
+            in_sitemap: sitemap
+                .iter()
+                .map(|e| (e.loc.clone(), e.priority))
+                .collect(),
+            levels: BTreeMap::new(),
+            report: DepthReport {
+                threshold,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        let Some(depth) = result.crawl_depth else {
+            self.report.unknown_depth += 1;
+            return;
+        };
+        self.levels
+            .entry(depth)
+            .or_default()
+            .push(result.url.clone());
+
+        if let Some(&sitemap_priority) = self.in_sitemap.get(&result.url) {
+            if depth > self.report.threshold {
+                self.report.buried.push(BuriedPage {
+                    url: result.url.clone(),
+                    depth,
+                    sitemap_priority,
+                });
+            }
+        }
+    }
+
+    pub fn finish(self) -> DepthReport {
+        let mut report = self.report;
+        report.levels = self
+            .levels
+            .into_iter()
+            .map(|(depth, urls)| DepthLevel {
+                depth,
+                pages: urls.len(),
+                urls,
+            })
+            .collect();
+        report
+            .buried
+            .sort_by(|a, b| b.depth.cmp(&a.depth).then(a.url.cmp(&b.url)));
+        report
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::link_checker::BrokenLink;
+use super::results_store::{CrawlRecord, PageRow, ResultsStore};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusChange {
+    pub url: String,
+    pub before: u16,
+    pub after: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub url: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// What changed between an older crawl `crawl_a` and a newer crawl `crawl_b` of the same domain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawlDiff {
+    pub crawl_a: CrawlRecord,
+    pub crawl_b: CrawlRecord,
+    pub new_pages: Vec<String>,
+    pub removed_pages: Vec<String>,
+    pub status_changes: Vec<StatusChange>,
+    pub title_changes: Vec<FieldChange>,
+    pub description_changes: Vec<FieldChange>,
+    pub newly_broken_links: Vec<BrokenLink>,
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Compares the pages and broken links of two crawls.
+pub fn diff_crawls(
+    crawl_a: CrawlRecord,
+    crawl_b: CrawlRecord,
+    pages_a: &[PageRow],
+    pages_b: &[PageRow],
+    broken_a: &[BrokenLink],
+    broken_b: Vec<BrokenLink>,
+) -> CrawlDiff {
+    let before: HashMap<&str, &PageRow> = pages_a.iter().map(|p| (p.url.as_str(), p)).collect();
+    let after: HashMap<&str, &PageRow> = pages_b.iter().map(|p| (p.url.as_str(), p)).collect();
+
+    let mut diff = CrawlDiff {
+        crawl_a,
+        crawl_b,
+        new_pages: Vec::new(),
+        removed_pages: Vec::new(),
+        status_changes: Vec::new(),
+        title_changes: Vec::new(),
+        description_changes: Vec::new(),
+        newly_broken_links: Vec::new(),
+    };
+
+    for page in pages_b {
+        let Some(old) = before.get(page.url.as_str()) else {
+            diff.new_pages.push(page.url.clone());
+            continue;
+        };
+
+        if old.status_code != page.status_code {
+            diff.status_changes.push(StatusChange {
+                url: page.url.clone(),
+                before: old.status_code,
+                after: page.status_code,
+            });
+        }
+
+        let old_title = old.title.as_deref().and_then(non_empty);
+        let new_title = page.title.as_deref().and_then(non_empty);
+        if old_title != new_title {
+            diff.title_changes.push(FieldChange {
+                url: page.url.clone(),
+                before: old_title,
+                after: new_title,
+            });
+        }
+
+        let old_description = non_empty(&old.description);
+        let new_description = non_empty(&page.description);
+        if old_description != new_description {
+            diff.description_changes.push(FieldChange {
+                url: page.url.clone(),
+                before: old_description,
+                after: new_description,
+            });
+        }
+    }
+
+    diff.removed_pages = pages_a
+        .iter()
+        .filter(|page| !after.contains_key(page.url.as_str()))
+        .map(|page| page.url.clone())
+        .collect();
+
+    let already_broken: HashSet<&str> = broken_a.iter().map(|link| link.url.as_str()).collect();
+    diff.newly_broken_links = broken_b
+        .into_iter()
+        .filter(|link| !already_broken.contains(link.url.as_str()))
+        .collect();
+
+    diff
+}
+
+// COMPARE TWO STORED CRAWLS OF THE SAME DOMAIN
+#[tauri::command]
+pub async fn compare_crawls(crawl_a: i64, crawl_b: i64) -> Result<CrawlDiff, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+
+    let record_a = store
+        .crawl(crawl_a)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Crawl {} not found", crawl_a))?;
+    let record_b = store
+        .crawl(crawl_b)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Crawl {} not found", crawl_b))?;
+
+    if record_a.domain != record_b.domain {
+        return Err(format!(
+            "Cannot compare crawls of different domains: {} and {}",
+            record_a.domain, record_b.domain
+        ));
+    }
+
+    let pages_a = store.page_rows(crawl_a).await.map_err(|e| e.to_string())?;
+    let pages_b = store.page_rows(crawl_b).await.map_err(|e| e.to_string())?;
+    let broken_a = store
+        .broken_links(crawl_a)
+        .await
+        .map_err(|e| e.to_string())?;
+    let broken_b = store
+        .broken_links(crawl_b)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(diff_crawls(
+        record_a, record_b, &pages_a, &pages_b, &broken_a, broken_b,
+    ))
+}
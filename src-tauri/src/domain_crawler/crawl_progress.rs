@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration, Instant};
+
+use super::crawl_control::{self, CrawlStatus};
+use super::domain_crawler::CrawlerState;
+use super::events::CrawlEvents;
+
+pub const PROGRESS_EVENT: &str = "crawl://progress";
+
+const TICK: Duration = Duration::from_secs(1);
+// Requests per second are measured over this window so the rate reacts to slowdowns
+const RATE_WINDOW: Duration = Duration::from_secs(15);
+
+// The last snapshot sent, for callers that poll instead of listening to the events
+static LATEST: Lazy<RwLock<Option<CrawlProgress>>> = Lazy::new(|| RwLock::new(None));
+
+/// A snapshot of the running crawl for the live dashboard.
+#[derive(Debug, Clone, Serialize)]
+pub struct CrawlProgress {
+    pub status: CrawlStatus,
+    pub crawled_urls: usize,
+    pub failed_urls: usize,
+    pub queued_urls: usize,
+    pub total_urls: usize,
+    pub requests_per_sec: f64,
+    pub elapsed_secs: u64,
+    /// None until a rate is known or while paused
+    pub eta_secs: Option<u64>,
+}
+
+/// The last progress of the running or the most recent crawl.
+pub fn latest() -> Option<CrawlProgress> {
+    LATEST.read().ok().and_then(|progress| progress.clone())
+}
+
+/// Stops the progress events when the crawl ends, however it returns.
+pub struct ProgressReporter(JoinHandle<()>);
+
+impl Drop for ProgressReporter {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Emits a `crawl://progress` event every second while the returned reporter is alive.
+pub fn spawn_reporter(events: CrawlEvents, state: Arc<Mutex<CrawlerState>>) -> ProgressReporter {
+    ProgressReporter(tokio::spawn(async move {
+        let started = Instant::now();
+        let mut samples: VecDeque<(Instant, usize)> = VecDeque::new();
+        let mut ticker = interval(TICK);
+
+        loop {
+            ticker.tick().await;
+
+            let (crawled_urls, failed_urls, queued_urls, total_urls) = {
+                let state = state.lock().await;
+                (
+                    state.crawled_urls,
+                    state.failed_urls.len(),
+                    state.frontier.len(),
+                    state.total_urls,
+                )
+            };
+
+            let now = Instant::now();
+            let done = crawled_urls + failed_urls;
+            samples.push_back((now, done));
+            while samples
+                .front()
+                .is_some_and(|(at, _)| now.duration_since(*at) > RATE_WINDOW)
+            {
+                samples.pop_front();
+            }
+
+            let requests_per_sec = match samples.front() {
+                Some((at, first)) if now > *at => {
+                    done.saturating_sub(*first) as f64 / now.duration_since(*at).as_secs_f64()
+                }
+                _ => 0.0,
+            };
+
+            let status = crawl_control::status();
+            let remaining = total_urls.saturating_sub(done);
+            let eta_secs = (status == CrawlStatus::Running && requests_per_sec > 0.0)
+                .then(|| (remaining as f64 / requests_per_sec).round() as u64);
+
+            let progress = CrawlProgress {
+                status,
+                crawled_urls,
+                failed_urls,
+                queued_urls,
+                total_urls,
+                requests_per_sec: (requests_per_sec * 100.0).round() / 100.0,
+                elapsed_secs: started.elapsed().as_secs(),
+                eta_secs,
+            };
+            if let Err(err) = events.emit(PROGRESS_EVENT, &progress) {
+                eprintln!("Failed to emit crawl progress: {}", err);
+            }
+            if let Ok(mut latest) = LATEST.write() {
+                *latest = Some(progress);
+            }
+        }
+    }))
+}
@@ -0,0 +1,114 @@
+use regex::Regex;
+use url::Url;
+
+use crate::settings::settings::Settings;
+
+// Include and exclude patterns starting with this are regular expressions, the rest are globs
+const REGEX_PREFIX: &str = "regex:";
+
+/// Which URLs a crawl may add to its frontier.
+///
+/// Patterns are matched against the path and query of a URL, so `/blog/*` keeps the crawl
+/// inside the blog and `*?color=*` skips a faceted parameter.
+#[derive(Debug, Clone)]
+pub struct CrawlScope {
+    host: String,
+    include_subdomains: bool,
+    /// None allows both http and https
+    scheme: Option<String>,
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+    /// Clicks from the start URL, None for no limit
+    pub max_depth: Option<usize>,
+    /// URLs the frontier may hold in total, None for no limit
+    pub max_urls: Option<usize>,
+}
+
+/// Turns a glob where `*` matches anything and `?` one character into an anchored regex.
+fn glob_to_regex(glob: &str) -> String {
+    let mut pattern = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    pattern
+}
+
+/// Compiles include or exclude patterns, globs unless prefixed with `regex:`.
+pub(crate) fn compile_patterns(patterns: &[String]) -> Result<Vec<Regex>, String> {
+    patterns
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let pattern = match p.strip_prefix(REGEX_PREFIX) {
+                Some(regex) => regex.trim().to_string(),
+                None => glob_to_regex(p),
+            };
+            Regex::new(&pattern).map_err(|e| format!("Invalid scope pattern {}: {}", p, e))
+        })
+        .collect()
+}
+
+fn bare_host(url: &Url) -> Option<String> {
+    url.host_str()
+        .map(|host| host.trim_start_matches("www.").to_lowercase())
+}
+
+impl CrawlScope {
+    pub fn from_settings(settings: &Settings, base_url: &Url) -> Result<Self, String> {
+        let scheme = match settings.scope_protocol.trim().to_lowercase().as_str() {
+            "" | "any" => None,
+            scheme @ ("http" | "https") => Some(scheme.to_string()),
+            other => return Err(format!("Unknown scope protocol {}", other)),
+        };
+
+        Ok(Self {
+            host: bare_host(base_url).ok_or("Invalid URL")?,
+            include_subdomains: settings.scope_include_subdomains,
+            scheme,
+            include: compile_patterns(&settings.scope_include)?,
+            exclude: compile_patterns(&settings.scope_exclude)?,
+            max_depth: (settings.max_crawl_depth > 0).then_some(settings.max_crawl_depth),
+            max_urls: (settings.max_crawl_urls > 0).then_some(settings.max_crawl_urls),
+        })
+    }
+
+    /// Whether `url` belongs to the crawl, ignoring the depth and URL count limits.
+    pub fn allows(&self, url: &Url) -> bool {
+        if let Some(scheme) = &self.scheme {
+            if url.scheme() != scheme {
+                return false;
+            }
+        }
+
+        let Some(host) = bare_host(url) else {
+            return false;
+        };
+        let subdomain = host.ends_with(&format!(".{}", self.host));
+        if host != self.host && !(self.include_subdomains && subdomain) {
+            return false;
+        }
+
+        let target = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        if self.exclude.iter().any(|p| p.is_match(&target)) {
+            return false;
+        }
+        self.include.is_empty() || self.include.iter().any(|p| p.is_match(&target))
+    }
+
+    pub fn within_depth(&self, depth: usize) -> bool {
+        self.max_depth.map_or(true, |max| depth <= max)
+    }
+
+    pub fn has_room(&self, total_urls: usize) -> bool {
+        self.max_urls.map_or(true, |max| total_urls < max)
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::database::{Database, DatabaseError};
+use super::frontier::Frontier;
+use super::models::DomainCrawlResults;
+
+const STATE_DB: &str = "crawl_state.db";
+
+/// A crawl that was started but never completed, as listed to the frontend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumableCrawl {
+    pub domain: String,
+    pub total_urls: usize,
+    pub crawled_urls: usize,
+    pub updated_at: String,
+}
+
+/// Everything needed to pick a crawl back up after a restart.
+#[derive(Debug, Clone, Default)]
+///
+/// The queued and visited URLs are not loaded with it, `restore_frontier` streams them.
+pub struct SavedCrawl {
+    pub queued: usize,
+    pub failed_urls: HashSet<String>,
+    pub results: Vec<DomainCrawlResults>,
+    pub total_urls: usize,
+    pub crawled_urls: usize,
+}
+
+/// URLs and results produced by one crawl batch.
+#[derive(Debug, Default)]
+pub struct BatchProgress {
+    /// Newly queued URLs with their depth from the start URL
+    pub discovered: Vec<(String, Option<usize>)>,
+    pub visited: Vec<String>,
+    pub failed: Vec<String>,
+    pub results: Vec<DomainCrawlResults>,
+    pub total_urls: usize,
+    pub crawled_urls: usize,
+}
+
+/// On-disk frontier, visited set and partial results of in-progress crawls, keyed by domain.
+///
+/// Written after every batch so a crash or close loses at most one batch of work.
+#[derive(Clone)]
+pub struct CrawlStateStore {
+    db: Database,
+    // Last queue position handed out per domain, read from the database once
+    positions: Arc<Mutex<HashMap<String, i64>>>,
+}
+
+impl CrawlStateStore {
+    pub async fn open() -> Result<Self, DatabaseError> {
+        Self::open_db(Database::new(STATE_DB)?).await
+    }
+
+    /// Opens the store of a project other than the active one.
+    pub async fn open_in(db_dir: &Path) -> Result<Self, DatabaseError> {
+        Self::open_db(Database::in_dir(db_dir, STATE_DB)?).await
+    }
+
+    async fn open_db(db: Database) -> Result<Self, DatabaseError> {
+        let pool = db.get_pool();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS crawl_sessions (
+                    domain TEXT PRIMARY KEY,
+                    total_urls INTEGER NOT NULL DEFAULT 0,
+                    crawled_urls INTEGER NOT NULL DEFAULT 0,
+                    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                );
+                CREATE TABLE IF NOT EXISTS crawl_urls (
+                    domain TEXT NOT NULL,
+                    url TEXT NOT NULL,
+                    state TEXT NOT NULL,
+                    position INTEGER NOT NULL,
+                    depth INTEGER,
+                    PRIMARY KEY (domain, url)
+                );
+                CREATE TABLE IF NOT EXISTS crawl_partial_results (
+                    domain TEXT NOT NULL,
+                    url TEXT NOT NULL,
+                    data TEXT NOT NULL,
+                    PRIMARY KEY (domain, url)
+                );
+                CREATE INDEX IF NOT EXISTS idx_crawl_urls_state ON crawl_urls(domain, state);
+                "#,
+            )?;
+
+            // Stores created before depths were saved lack the column
+            let columns = conn
+                .prepare("SELECT name FROM pragma_table_info('crawl_urls')")?
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            if !columns.iter().any(|c| c == "depth") {
+                conn.execute("ALTER TABLE crawl_urls ADD COLUMN depth INTEGER", [])?;
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .await??;
+
+        Ok(Self {
+            db,
+            positions: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    fn lock_positions(&self) -> std::sync::MutexGuard<'_, HashMap<String, i64>> {
+        self.positions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Drops any saved state for `domain` and starts a fresh session.
+    pub async fn begin(&self, domain: &str) -> Result<(), DatabaseError> {
+        self.discard(domain).await?;
+        self.lock_positions().insert(domain.to_string(), 0);
+
+        let pool = self.db.get_pool();
+        let domain = domain.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            conn.execute(
+                "INSERT INTO crawl_sessions (domain) VALUES (?1)",
+                params![domain],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Persists one batch: new frontier entries, fetched and failed URLs and their results.
+    pub async fn record_batch(
+        &self,
+        domain: &str,
+        batch: BatchProgress,
+    ) -> Result<(), DatabaseError> {
+        let results = batch
+            .results
+            .iter()
+            .map(|result| Ok((result.url.clone(), serde_json::to_string(result)?)))
+            .collect::<Result<Vec<_>, DatabaseError>>()?;
+
+        let pool = self.db.get_pool();
+        let known_position = self.lock_positions().get(domain).copied();
+        let key = domain.to_string();
+        let domain = domain.to_string();
+        let position = tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let tx = conn.transaction()?;
+            let mut position = match known_position {
+                Some(position) => position,
+                None => tx.query_row(
+                    "SELECT COALESCE(MAX(position), 0) FROM crawl_urls WHERE domain = ?1",
+                    params![domain],
+                    |row| row.get(0),
+                )?,
+            };
+            {
+                let mut queued = tx.prepare_cached(
+                    "INSERT OR IGNORE INTO crawl_urls (domain, url, state, position, depth)
+                     VALUES (?1, ?2, 'queued', ?3, ?4)",
+                )?;
+                for (url, depth) in &batch.discovered {
+                    position += 1;
+                    queued.execute(params![domain, url, position, depth.map(|d| d as i64)])?;
+                }
+
+                let mut done = tx.prepare_cached(
+                    "INSERT INTO crawl_urls (domain, url, state, position) VALUES (?1, ?2, ?3, 0)
+                     ON CONFLICT(domain, url) DO UPDATE SET state = excluded.state",
+                )?;
+                for url in &batch.visited {
+                    done.execute(params![domain, url, "visited"])?;
+                }
+                for url in &batch.failed {
+                    done.execute(params![domain, url, "failed"])?;
+                }
+
+                let mut result_stmt = tx.prepare_cached(
+                    "INSERT OR REPLACE INTO crawl_partial_results (domain, url, data)
+                     VALUES (?1, ?2, ?3)",
+                )?;
+                for (url, data) in &results {
+                    result_stmt.execute(params![domain, url, data])?;
+                }
+
+                tx.execute(
+                    "UPDATE crawl_sessions
+                     SET total_urls = ?2, crawled_urls = ?3, updated_at = CURRENT_TIMESTAMP
+                     WHERE domain = ?1",
+                    params![domain, batch.total_urls, batch.crawled_urls],
+                )?;
+            }
+            tx.commit()?;
+            Ok::<_, DatabaseError>(position)
+        })
+        .await??;
+
+        self.lock_positions().insert(key, position);
+        Ok(())
+    }
+
+    /// Loads the saved state of an unfinished crawl for `domain`, if any.
+    pub async fn load(&self, domain: &str) -> Result<Option<SavedCrawl>, DatabaseError> {
+        let pool = self.db.get_pool();
+        let domain = domain.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+
+            let Some((total_urls, crawled_urls)) = conn
+                .query_row(
+                    "SELECT total_urls, crawled_urls FROM crawl_sessions WHERE domain = ?1",
+                    params![domain],
+                    |row| Ok((row.get::<_, usize>(0)?, row.get::<_, usize>(1)?)),
+                )
+                .optional()?
+            else {
+                return Ok(None);
+            };
+
+            let mut saved = SavedCrawl {
+                total_urls,
+                crawled_urls,
+                ..Default::default()
+            };
+
+            saved.queued = conn.query_row(
+                "SELECT COUNT(*) FROM crawl_urls WHERE domain = ?1 AND state = 'queued'",
+                params![domain],
+                |row| row.get(0),
+            )?;
+            let mut stmt =
+                conn.prepare("SELECT url FROM crawl_urls WHERE domain = ?1 AND state = 'failed'")?;
+            let rows = stmt.query_map(params![domain], |row| row.get::<_, String>(0))?;
+            for url in rows {
+                saved.failed_urls.insert(url?);
+            }
+
+            let mut stmt =
+                conn.prepare("SELECT data FROM crawl_partial_results WHERE domain = ?1")?;
+            let rows = stmt.query_map(params![domain], |row| row.get::<_, String>(0))?;
+            for data in rows {
+                saved.results.push(serde_json::from_str(&data?)?);
+            }
+
+            Ok(Some(saved))
+        })
+        .await?
+    }
+
+    /// Streams the queued and visited URLs of a saved crawl into `frontier`, in discovery order
+    /// and with their depths.
+    pub async fn restore_frontier(
+        &self,
+        domain: &str,
+        mut frontier: Frontier,
+    ) -> Result<Frontier, DatabaseError> {
+        let pool = self.db.get_pool();
+        let domain = domain.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT url, state, depth FROM crawl_urls
+                 WHERE domain = ?1 AND state != 'failed' ORDER BY position",
+            )?;
+            let mut rows = stmt.query(params![domain])?;
+
+            frontier.begin();
+            let restored = (|| {
+                while let Some(row) = rows.next()? {
+                    let url: String = row.get(0)?;
+                    let state: String = row.get(1)?;
+                    // Depths keep max_crawl_depth and the depth report right across the resume
+                    let depth: Option<i64> = row.get(2)?;
+                    frontier.restore(&url, state == "visited", depth.map(|d| d as usize));
+                }
+                Ok::<_, DatabaseError>(())
+            })();
+            frontier.commit();
+            restored?;
+            Ok(frontier)
+        })
+        .await?
+    }
+
+    pub async fn list(&self) -> Result<Vec<ResumableCrawl>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT domain, total_urls, crawled_urls, updated_at
+                 FROM crawl_sessions ORDER BY updated_at DESC",
+            )?;
+            let crawls = stmt
+                .query_map([], |row| {
+                    Ok(ResumableCrawl {
+                        domain: row.get(0)?,
+                        total_urls: row.get(1)?,
+                        crawled_urls: row.get(2)?,
+                        updated_at: row.get(3)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(crawls)
+        })
+        .await?
+    }
+
+    /// Removes all saved state for `domain`, called once its crawl completes.
+    pub async fn discard(&self, domain: &str) -> Result<(), DatabaseError> {
+        self.lock_positions().remove(domain);
+        let pool = self.db.get_pool();
+        let domain = domain.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            conn.execute(
+                "DELETE FROM crawl_sessions WHERE domain = ?1",
+                params![domain],
+            )?;
+            conn.execute("DELETE FROM crawl_urls WHERE domain = ?1", params![domain])?;
+            conn.execute(
+                "DELETE FROM crawl_partial_results WHERE domain = ?1",
+                params![domain],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+}
+
+// LIST THE CRAWLS THAT CAN BE RESUMED
+#[tauri::command]
+pub async fn get_resumable_crawls() -> Result<Vec<ResumableCrawl>, String> {
+    let store = CrawlStateStore::open().await.map_err(|e| e.to_string())?;
+    store.list().await.map_err(|e| e.to_string())
+}
+
+// FORGET THE SAVED STATE OF AN UNFINISHED CRAWL
+#[tauri::command]
+pub async fn discard_saved_crawl(domain: String) -> Result<(), String> {
+    let store = CrawlStateStore::open().await.map_err(|e| e.to_string())?;
+    store.discard(&domain).await.map_err(|e| e.to_string())
+}
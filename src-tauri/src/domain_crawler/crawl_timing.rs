@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+/// Percentiles of one timing across the crawl, in milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Percentiles {
+    pub samples: usize,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlowPage {
+    pub url: String,
+    pub ttfb_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TimingReport {
+    /// Time to first byte of every crawled page
+    pub ttfb: Percentiles,
+    /// The waterfall phases, None unless `waterfall_timing` was on
+    pub dns: Option<Percentiles>,
+    pub connect: Option<Percentiles>,
+    pub tls: Option<Percentiles>,
+    pub download: Option<Percentiles>,
+    /// Pages above the 95th percentile TTFB, slowest first
+    pub slowest: Vec<SlowPage>,
+}
+
+impl StoredReport for TimingReport {
+    const KIND: &'static str = "timing";
+}
+
+// Nearest-rank percentile over sorted samples
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn percentiles(mut samples: Vec<f64>) -> Option<Percentiles> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_by(|a, b| a.total_cmp(b));
+    Some(Percentiles {
+        samples: samples.len(),
+        p50: percentile(&samples, 50.0),
+        p95: percentile(&samples, 95.0),
+        p99: percentile(&samples, 99.0),
+        max: samples[samples.len() - 1],
+    })
+}
+
+/// Summarizes TTFB and the waterfall phases of the crawled pages as percentiles.
+#[derive(Default)]
+pub struct TimingCollector {
+    ttfb: Vec<(String, f64)>,
+    dns: Vec<f64>,
+    connect: Vec<f64>,
+    tls: Vec<f64>,
+    download: Vec<f64>,
+}
+
+impl TimingCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        // Pages that were never downloaded, such as those blocked by robots.txt, have no TTFB
+        if !result.transfer.http_version.is_empty() {
+            self.ttfb
+                .push((result.url.clone(), result.transfer.ttfb_ms));
+        }
+        if let Some(waterfall) = &result.waterfall {
+            self.dns.push(waterfall.dns_ms);
+            self.connect.push(waterfall.connect_ms);
+            self.tls.extend(waterfall.tls_ms);
+            self.download.push(waterfall.download_ms);
+        }
+    }
+
+    pub fn finish(self) -> TimingReport {
+        let ttfb =
+            percentiles(self.ttfb.iter().map(|(_, ttfb)| *ttfb).collect()).unwrap_or_default();
+        let mut slowest: Vec<SlowPage> = self
+            .ttfb
+            .into_iter()
+            .filter(|(_, ttfb_ms)| ttfb.samples > 0 && *ttfb_ms > ttfb.p95)
+            .map(|(url, ttfb_ms)| SlowPage { url, ttfb_ms })
+            .collect();
+        slowest.sort_by(|a, b| b.ttfb_ms.total_cmp(&a.ttfb_ms));
+
+        TimingReport {
+            dns: percentiles(self.dns),
+            connect: percentiles(self.connect),
+            tls: percentiles(self.tls),
+            download: percentiles(self.download),
+            ttfb,
+            slowest,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings::settings::{override_settings, Settings};
+use crate::AppState;
+
+/// The crawler throughput settings the frontend can change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawlerConfig {
+    /// Maximum number of pages fetched at the same time
+    pub concurrent_requests: usize,
+    /// Minimum delay between two requests to the same host, in milliseconds
+    pub per_host_delay_ms: u64,
+    /// Requests a host may receive back to back before the delay kicks in
+    pub per_host_burst: u32,
+    /// Maximum number of image checks in flight across the crawl
+    pub max_image_checks: usize,
+}
+
+impl CrawlerConfig {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            concurrent_requests: settings.concurrent_requests,
+            per_host_delay_ms: settings.per_host_delay_ms,
+            per_host_burst: settings.per_host_burst,
+            max_image_checks: settings.max_image_checks,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.concurrent_requests == 0 {
+            return Err("Concurrent requests must be at least 1".to_string());
+        }
+        if self.per_host_burst == 0 {
+            return Err("Per-host burst must be at least 1".to_string());
+        }
+        if self.max_image_checks == 0 {
+            return Err("Image checks must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    fn to_toml(&self) -> String {
+        format!(
+            "concurrent_requests = {}\nper_host_delay_ms = {}\nper_host_burst = {}\nmax_image_checks = {}",
+            self.concurrent_requests,
+            self.per_host_delay_ms,
+            self.per_host_burst,
+            self.max_image_checks
+        )
+    }
+}
+
+#[tauri::command]
+pub async fn get_crawler_config(
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<CrawlerConfig, String> {
+    let settings = settings_state.settings.read().await;
+    Ok(CrawlerConfig::from_settings(&settings))
+}
+
+#[tauri::command]
+pub async fn set_crawler_config(
+    config: CrawlerConfig,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<CrawlerConfig, String> {
+    config.validate()?;
+
+    // Persist to disk, then refresh the in-memory settings used by the next crawl
+    let settings = override_settings(&config.to_toml()).await?;
+    *settings_state.settings.write().await = settings.clone();
+
+    Ok(CrawlerConfig::from_settings(&settings))
+}
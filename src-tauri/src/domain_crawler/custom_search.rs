@@ -0,0 +1,288 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex, RwLock};
+
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::models::DomainCrawlResults;
+use super::results_store::{ResultsStore, StoredReport};
+use crate::projects::registry::ProjectRoots;
+use crate::settings::settings::Settings;
+
+const PATTERNS_FILE: &str = "custom_search.json";
+
+// Patterns of the running crawl, compiled once when it starts
+static ACTIVE_PATTERNS: Lazy<RwLock<Arc<Vec<CompiledPattern>>>> =
+    Lazy::new(|| RwLock::new(Arc::new(Vec::new())));
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchSource {
+    /// The HTML as the server sent it
+    #[default]
+    Raw,
+    /// The DOM after scripts ran, the raw HTML when rendering is off
+    Rendered,
+}
+
+/// A string or regex to look for in every crawled page.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchPattern {
+    pub id: String,
+    pub name: String,
+    pub pattern: String,
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub source: SearchSource,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+struct CompiledPattern {
+    name: String,
+    regex: Regex,
+    source: SearchSource,
+}
+
+impl SearchPattern {
+    fn compile(&self) -> Result<CompiledPattern, String> {
+        let pattern = if self.regex {
+            self.pattern.clone()
+        } else {
+            regex::escape(&self.pattern)
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .map_err(|e| format!("Invalid regex for {}: {}", self.name, e))?;
+        Ok(CompiledPattern {
+            name: self.name.trim().to_string(),
+            regex,
+            source: self.source,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageMatch {
+    pub url: String,
+    pub occurrences: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternReport {
+    pub name: String,
+    pub occurrences: usize,
+    /// Pages containing the pattern, most occurrences first
+    pub containing: Vec<PageMatch>,
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CustomSearchReport {
+    pub pages_searched: usize,
+    pub patterns: Vec<PatternReport>,
+}
+
+fn patterns_path(roots: &ProjectRoots) -> PathBuf {
+    roots.config_file(PATTERNS_FILE)
+}
+
+fn load_patterns(roots: &ProjectRoots) -> Result<Vec<SearchPattern>, String> {
+    let path = patterns_path(roots);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read search patterns: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse search patterns: {}", e))
+}
+
+fn save_patterns(roots: &ProjectRoots, patterns: &[SearchPattern]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(patterns).map_err(|e| e.to_string())?;
+    fs::write(patterns_path(roots), json)
+        .map_err(|e| format!("Failed to save search patterns: {}", e))
+}
+
+/// Compiles the enabled patterns for the crawl about to start, none with the option off.
+pub fn configure(settings: &Settings, roots: &ProjectRoots) -> Result<(), String> {
+    let patterns = if settings.custom_search {
+        load_patterns(roots)?
+            .iter()
+            .filter(|pattern| pattern.enabled)
+            .map(SearchPattern::compile)
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        Vec::new()
+    };
+    *ACTIVE_PATTERNS.write().map_err(|e| e.to_string())? = Arc::new(patterns);
+    Ok(())
+}
+
+/// Counts the occurrences of every pattern of the crawl in a page, by pattern name.
+pub fn scan_page(raw: &str, rendered: &str) -> BTreeMap<String, usize> {
+    let patterns = match ACTIVE_PATTERNS.read() {
+        Ok(patterns) => patterns.clone(),
+        Err(_) => return BTreeMap::new(),
+    };
+    patterns
+        .iter()
+        .map(|pattern| {
+            let html = match pattern.source {
+                SearchSource::Raw => raw,
+                SearchSource::Rendered => rendered,
+            };
+            (pattern.name.clone(), pattern.regex.find_iter(html).count())
+        })
+        .collect()
+}
+
+/// Sorts the searched HTML pages into those containing and those missing each pattern.
+#[derive(Default)]
+pub struct CustomSearchCollector {
+    searched: Vec<(String, BTreeMap<String, usize>)>,
+}
+
+impl CustomSearchCollector {
+    pub fn add(&mut self, page: &DomainCrawlResults) {
+        if page.status_code == 200
+            && page.content_type.contains("html")
+            && !page.custom_search.is_empty()
+        {
+            self.searched
+                .push((page.url.clone(), page.custom_search.clone()));
+        }
+    }
+
+    pub fn finish(self) -> CustomSearchReport {
+        let searched = self.searched;
+        let names: BTreeSet<&String> = searched
+            .iter()
+            .flat_map(|(_, counts)| counts.keys())
+            .collect();
+
+        let patterns = names
+            .into_iter()
+            .map(|name| {
+                let mut report = PatternReport {
+                    name: name.clone(),
+                    occurrences: 0,
+                    containing: Vec::new(),
+                    missing: Vec::new(),
+                };
+                for (url, counts) in &searched {
+                    match counts.get(name).copied().unwrap_or(0) {
+                        0 => report.missing.push(url.clone()),
+                        occurrences => {
+                            report.occurrences += occurrences;
+                            report.containing.push(PageMatch {
+                                url: url.clone(),
+                                occurrences,
+                            });
+                        }
+                    }
+                }
+                report
+                    .containing
+                    .sort_by(|a, b| b.occurrences.cmp(&a.occurrences).then(a.url.cmp(&b.url)));
+                report
+            })
+            .collect();
+
+        CustomSearchReport {
+            pages_searched: searched.len(),
+            patterns,
+        }
+    }
+}
+
+impl StoredReport for CustomSearchReport {
+    const KIND: &'static str = "custom_search";
+}
+
+#[tauri::command]
+pub async fn list_search_patterns() -> Result<Vec<SearchPattern>, String> {
+    load_patterns(&ProjectRoots::active()?)
+}
+
+// ADD A SEARCH PATTERN, OR REPLACE THE ONE WITH THE SAME ID
+#[tauri::command]
+pub async fn save_search_pattern(mut pattern: SearchPattern) -> Result<SearchPattern, String> {
+    pattern.name = pattern.name.trim().to_string();
+    if pattern.pattern.is_empty() {
+        return Err("Search patterns cannot be empty".to_string());
+    }
+    if pattern.name.is_empty() {
+        pattern.name = pattern.pattern.trim().to_string();
+    }
+    pattern.compile()?;
+    if pattern.id.is_empty() {
+        pattern.id = Uuid::new_v4().to_string();
+    }
+
+    let roots = ProjectRoots::active()?;
+    let mut patterns = load_patterns(&roots)?;
+    // Results are keyed by name, two patterns cannot share it
+    if patterns
+        .iter()
+        .any(|other| other.id != pattern.id && other.name == pattern.name)
+    {
+        return Err(format!(
+            "A search pattern named {} already exists",
+            pattern.name
+        ));
+    }
+    match patterns.iter_mut().find(|other| other.id == pattern.id) {
+        Some(existing) => *existing = pattern.clone(),
+        None => patterns.push(pattern.clone()),
+    }
+    save_patterns(&roots, &patterns)?;
+    Ok(pattern)
+}
+
+#[tauri::command]
+pub async fn delete_search_pattern(id: String) -> Result<(), String> {
+    let roots = ProjectRoots::active()?;
+    let mut patterns = load_patterns(&roots)?;
+    let count = patterns.len();
+    patterns.retain(|pattern| pattern.id != id);
+    if patterns.len() == count {
+        return Err(format!("Search pattern {} not found", id));
+    }
+    save_patterns(&roots, &patterns)
+}
+
+#[tauri::command]
+pub async fn get_custom_search_report(crawl_id: i64) -> Result<Option<CustomSearchReport>, String> {
+    ResultsStore::open()
+        .await
+        .map_err(|e| e.to_string())?
+        .report(crawl_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// GET THE CUSTOM SEARCH RESULTS OF A STORED CRAWL
+#[tauri::command]
+pub async fn get_crawl_custom_search(crawl_id: i64) -> Result<CustomSearchReport, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let collector = Arc::new(StdMutex::new(CustomSearchCollector::default()));
+    let sink = collector.clone();
+    store
+        .for_each_page(crawl_id, move |page| {
+            sink.lock().map_err(|e| e.to_string())?.add(&page);
+            Ok(())
+        })
+        .await?;
+    let collector = std::mem::take(&mut *collector.lock().map_err(|e| e.to_string())?);
+    Ok(collector.finish())
+}
@@ -68,9 +68,12 @@ impl Database {
         })?;
 
         let data_dir = project_dirs.data_dir();
-        let db_dir = data_dir.join("db");
+        Self::in_dir(&crate::projects::registry::db_dir(data_dir), db_name)
+    }
 
-        fs::create_dir_all(&db_dir).map_err(|e| {
+    /// Opens `db_name` in `db_dir` instead of the database folder of the active project.
+    pub fn in_dir(db_dir: &Path, db_name: &str) -> Result<Self, DatabaseError> {
+        fs::create_dir_all(db_dir).map_err(|e| {
             DatabaseError::DirectoryError(format!(
                 "Failed to create database directory {}: {}",
                 db_dir.display(),
@@ -78,7 +81,7 @@ impl Database {
             ))
         })?;
 
-        let metadata = fs::metadata(&db_dir).map_err(|e| {
+        let metadata = fs::metadata(db_dir).map_err(|e| {
             DatabaseError::DirectoryError(format!(
                 "Failed to get directory metadata for {}: {}",
                 db_dir.display(),
@@ -328,15 +331,8 @@ pub async fn insert_bulk_crawl_data(
     .await?
 }
 
-pub fn create_diff_tables() -> Result<(), DatabaseError> {
-    let project_dirs = ProjectDirs::from("", "", "rustyseo").ok_or_else(|| {
-        DatabaseError::DirectoryError("Failed to get project directories".to_string())
-    })?;
-
-    let data_dir = project_dirs.data_dir();
-    let db_dir = data_dir.join("db");
-
-    fs::create_dir_all(&db_dir).map_err(|e| {
+pub fn create_diff_tables(db_dir: &Path) -> Result<(), DatabaseError> {
+    fs::create_dir_all(db_dir).map_err(|e| {
         DatabaseError::DirectoryError(format!("Failed to create db directory: {}", e))
     })?;
 
@@ -362,14 +358,8 @@ pub fn create_diff_tables() -> Result<(), DatabaseError> {
     Ok(())
 }
 
-pub async fn clone_batched_crawl_into_persistent_db() -> Result<(), DatabaseError> {
-    let project_dirs = ProjectDirs::from("", "", "rustyseo").ok_or_else(|| {
-        DatabaseError::DirectoryError("Failed to get project directories".to_string())
-    })?;
-
-    let data_dir = project_dirs.data_dir();
-    let db_dir = data_dir.join("db");
-
+pub async fn clone_batched_crawl_into_persistent_db(db_dir: &Path) -> Result<(), DatabaseError> {
+    let db_dir = db_dir.to_path_buf();
     let db = Database::initialize_db(&db_dir.join("deep_crawl_batches.db")).await?;
     let urls = db.get_urls().await?;
 
@@ -416,7 +406,7 @@ pub async fn analyse_diffs() -> Result<DiffAnalysis, DatabaseError> {
         DatabaseError::DirectoryError("Failed to get project directories".to_string())
     })?;
 
-    let db_dir = project_dirs.data_dir().join("db");
+    let db_dir = crate::projects::registry::db_dir(project_dirs.data_dir());
     let db_path = db_dir.join("diff.db");
 
     // Verify database file exists
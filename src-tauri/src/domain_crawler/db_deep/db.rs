@@ -27,7 +27,7 @@ pub fn open_domain_db_connection(db_name: &str) -> Result<Connection> {
         ProjectDirs::from("", "", "rustyseo").expect("Error creating directory for DB");
 
     // Define the directory of the domain db file
-    let db_dir = project_dirs.data_dir().join("db"); // appends /db to the data dir
+    let db_dir = crate::projects::registry::db_dir(project_dirs.data_dir());
     let db_path = db_dir.join(db_name);
 
     println!("Opening domain db at {:?}", db_path);
@@ -221,7 +221,7 @@ pub async fn fetch_custom_search() -> Result<Vec<ExtractorConfig>, String> {
         ProjectDirs::from("", "", "rustyseo").expect("Failed to get project directories");
 
     // Define the directory of the domain db file
-    let db_dir = project_dirs.data_dir().join("db"); // appends /db to the data dir
+    let db_dir = crate::projects::registry::db_dir(project_dirs.data_dir());
     let db_path = db_dir.join("deep_crawl.db");
 
     // Ensure the directory exists
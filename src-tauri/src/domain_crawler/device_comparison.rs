@@ -0,0 +1,257 @@
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+use url::Url;
+
+use super::helpers::anchor_links::extract_internal_external_links;
+use super::helpers::canonical_selector::audit_canonical;
+use super::renderer::Renderer;
+use super::request_auth;
+use super::results_store::ResultsStore;
+use super::session;
+use super::user_agents::{GOOGLEBOT_DESKTOP, GOOGLEBOT_SMARTPHONE};
+use crate::AppState;
+
+const DEFAULT_SAMPLE_SIZE: usize = 25;
+const MAX_PARALLEL_PAGES: usize = 4;
+// Content length and link counts may drift this much before they count as different
+const TOLERANCE: f64 = 0.1;
+const MOBILE_VIEWPORT: (u32, u32) = (412, 915);
+const DESKTOP_VIEWPORT: (u32, u32) = (1920, 1080);
+
+/// What one user agent was served for a URL.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DeviceSnapshot {
+    pub status_code: u16,
+    pub final_url: Option<String>,
+    pub canonical: Option<String>,
+    pub content_length: usize,
+    pub internal_links: usize,
+    pub external_links: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceDifference {
+    pub field: String,
+    pub mobile: String,
+    pub desktop: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceComparison {
+    pub url: String,
+    pub mobile: DeviceSnapshot,
+    pub desktop: DeviceSnapshot,
+    pub differences: Vec<DeviceDifference>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceComparisonReport {
+    pub crawl_id: i64,
+    pub rendered: bool,
+    pub compared: usize,
+    pub pages: Vec<DeviceComparison>,
+}
+
+fn differs(a: usize, b: usize) -> bool {
+    let (a, b) = (a as f64, b as f64);
+    (a - b).abs() > a.max(b) * TOLERANCE
+}
+
+fn compare(url: String, mobile: DeviceSnapshot, desktop: DeviceSnapshot) -> DeviceComparison {
+    let mut differences = Vec::new();
+    let mut diff = |field: &str, mobile: String, desktop: String| {
+        differences.push(DeviceDifference {
+            field: field.to_string(),
+            mobile,
+            desktop,
+        });
+    };
+    let text = |value: &Option<String>| value.clone().unwrap_or_else(|| "none".to_string());
+
+    if mobile.status_code != desktop.status_code {
+        diff(
+            "status_code",
+            mobile.status_code.to_string(),
+            desktop.status_code.to_string(),
+        );
+    }
+    if mobile.final_url != desktop.final_url {
+        diff(
+            "final_url",
+            text(&mobile.final_url),
+            text(&desktop.final_url),
+        );
+    }
+    if mobile.canonical != desktop.canonical {
+        diff(
+            "canonical",
+            text(&mobile.canonical),
+            text(&desktop.canonical),
+        );
+    }
+    if differs(mobile.content_length, desktop.content_length) {
+        diff(
+            "content_length",
+            mobile.content_length.to_string(),
+            desktop.content_length.to_string(),
+        );
+    }
+    if differs(mobile.internal_links, desktop.internal_links) {
+        diff(
+            "internal_links",
+            mobile.internal_links.to_string(),
+            desktop.internal_links.to_string(),
+        );
+    }
+    if differs(mobile.external_links, desktop.external_links) {
+        diff(
+            "external_links",
+            mobile.external_links.to_string(),
+            desktop.external_links.to_string(),
+        );
+    }
+
+    DeviceComparison {
+        url,
+        mobile,
+        desktop,
+        differences,
+    }
+}
+
+async fn snapshot(client: &Client, renderer: Option<&Renderer>, url: &Url) -> DeviceSnapshot {
+    let response = match request_auth::apply(client.get(url.as_str()), url.as_str())
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            return DeviceSnapshot {
+                error: Some(e.to_string()),
+                ..Default::default()
+            }
+        }
+    };
+    let status_code = response.status().as_u16();
+    let final_url = response.url().clone();
+
+    let mut body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => {
+            return DeviceSnapshot {
+                status_code,
+                final_url: Some(final_url.to_string()),
+                error: Some(e.to_string()),
+                ..Default::default()
+            }
+        }
+    };
+
+    let mut error = None;
+    if let Some(renderer) = renderer {
+        match renderer.render(&final_url).await {
+            Ok(html) => body = html,
+            Err(e) => error = Some(e),
+        }
+    }
+
+    let links = extract_internal_external_links(&body, &final_url);
+    DeviceSnapshot {
+        status_code,
+        canonical: audit_canonical(&body, &final_url).resolved,
+        content_length: body.len(),
+        internal_links: links.as_ref().map_or(0, |l| l.internal.links.len()),
+        external_links: links.as_ref().map_or(0, |l| l.external.links.len()),
+        final_url: Some(final_url.to_string()),
+        error,
+    }
+}
+
+fn client_for(user_agent: &str, timeout: u64) -> Result<Client, String> {
+    Client::builder()
+        .user_agent(user_agent)
+        .timeout(Duration::from_secs(timeout))
+        .cookie_provider(session::cookies())
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+// COMPARE WHAT MOBILE AND DESKTOP USER AGENTS ARE SERVED FOR A SAMPLE OF CRAWLED URLS
+#[tauri::command]
+pub async fn compare_mobile_desktop(
+    crawl_id: i64,
+    urls: Option<Vec<String>>,
+    sample_size: Option<usize>,
+    render: Option<bool>,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<DeviceComparisonReport, String> {
+    let settings = settings_state.settings.read().await.clone();
+
+    let urls = match urls {
+        Some(urls) if !urls.is_empty() => urls,
+        _ => {
+            // Spread the sample over the whole crawl rather than taking the first pages
+            let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+            let pages: Vec<String> = store
+                .page_rows(crawl_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter(|page| page.status_code == 200)
+                .map(|page| page.url)
+                .collect();
+            let size = sample_size.unwrap_or(DEFAULT_SAMPLE_SIZE).max(1);
+            let step = (pages.len() / size).max(1);
+            pages.into_iter().step_by(step).take(size).collect()
+        }
+    };
+
+    let mobile_client = client_for(GOOGLEBOT_SMARTPHONE, settings.client_timeout)?;
+    let desktop_client = client_for(GOOGLEBOT_DESKTOP, settings.client_timeout)?;
+
+    let rendered = render.unwrap_or(false);
+    let (mobile_renderer, desktop_renderer) = if rendered {
+        (
+            Some(Renderer::new(
+                &settings,
+                GOOGLEBOT_SMARTPHONE,
+                Some(MOBILE_VIEWPORT),
+            )?),
+            Some(Renderer::new(
+                &settings,
+                GOOGLEBOT_DESKTOP,
+                Some(DESKTOP_VIEWPORT),
+            )?),
+        )
+    } else {
+        (None, None)
+    };
+
+    let pages: Vec<DeviceComparison> = stream::iter(urls)
+        .filter_map(|url| async move { Url::parse(&url).ok() })
+        .map(|url| {
+            let (mobile_client, desktop_client) = (&mobile_client, &desktop_client);
+            let (mobile_renderer, desktop_renderer) =
+                (mobile_renderer.as_ref(), desktop_renderer.as_ref());
+            async move {
+                let (mobile, desktop) = tokio::join!(
+                    snapshot(mobile_client, mobile_renderer, &url),
+                    snapshot(desktop_client, desktop_renderer, &url)
+                );
+                compare(url.to_string(), mobile, desktop)
+            }
+        })
+        .buffer_unordered(MAX_PARALLEL_PAGES)
+        .collect()
+        .await;
+
+    Ok(DeviceComparisonReport {
+        crawl_id,
+        rendered,
+        compared: pages.len(),
+        pages,
+    })
+}
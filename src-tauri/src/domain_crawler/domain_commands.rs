@@ -5,26 +5,54 @@ use std::{
 
 use rust_xlsxwriter::XlsxError;
 use serde_json::Value;
+use url::Url;
 
-use crate::{domain_crawler::domain_crawler, settings::settings::Settings, AppState};
+use crate::{
+    domain_crawler::domain_crawler, projects::registry::ProjectRoots, settings::settings::Settings,
+    AppState,
+};
+
+use super::events::CrawlEvents;
 
 use super::{
+    a11y::audit::A11yReport,
+    alt_text_audit::AltTextReport,
+    amp_audit::AmpReport,
+    asset_audit::AssetReport,
+    canonical_audit::CanonicalReport,
+    config_profiles,
+    crawl_control::{self, CrawlGuard},
+    crawl_depth::DepthReport,
+    crawl_timing::TimingReport,
     database::{self, analyse_diffs, DiffAnalysis, Differential},
+    duplicate_content::DuplicateContentReport,
+    entity_audit::EntityReport,
     excel::create_xlsx::{
         generate_css_table, generate_excel_main_table, generate_excel_two_cols,
         generate_keywords_excel, generate_links_table_excel, generate_xlsx,
     },
-    models::DomainCrawlResults,
+    helpers::{domain_checker::url_check, site_icons::IconReport, sitemap::SitemapReport},
+    hreflang_audit::HreflangReport,
+    image_audit::ImageReport,
+    keyword_audit::{KeywordReport, PageKeywords},
+    link_checker::BrokenLinksReport,
+    pagination_audit::PaginationReport,
+    preflight::PreflightReport,
+    redirect_audit::RedirectReport,
+    render_audit::RenderReport,
+    results_store::{ResultsStore, StoredReport},
+    security_headers_audit::SecurityHeadersReport,
+    sitemap_gap::SitemapGapReport,
+    title_description_audit::TitleDescriptionReport,
+    tls_audit::TlsReport,
+    tracking_audit::TrackingReport,
+    url_normalizer::ParameterReport,
 };
 
-#[tauri::command]
-pub async fn domain_crawl_command(
-    domain: String,
-    app_handle: tauri::AppHandle,
-    settings_state: tauri::State<'_, AppState>,
-) -> Result<Vec<DomainCrawlResults>, String> {
+// A fresh batch database, in the project's folder, for the crawl to stream its pages into
+pub(crate) async fn crawl_database(roots: &ProjectRoots) -> Result<database::Database, String> {
     // Create and initialize the database
-    let mut db = match database::Database::new("deep_crawl_batches.db") {
+    let mut db = match database::Database::in_dir(&roots.db_dir, "deep_crawl_batches.db") {
         Ok(db) => db,
         Err(e) => {
             let error_msg = format!("Failed to create database: {}", e);
@@ -47,16 +75,62 @@ pub async fn domain_crawl_command(
         return Err(error_msg);
     }
 
+    Ok(db)
+}
+
+#[tauri::command]
+pub async fn domain_crawl_command(
+    domain: String,
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, AppState>,
+    resume: Option<bool>,
+    profile: Option<String>,
+) -> Result<domain_crawler::CrawlOutcome, String> {
+    let crawl = crawl_control::try_start()?;
+    let settings = settings_state.settings.read().await.clone();
+    crawl_with_profile(
+        &domain,
+        app_handle.into(),
+        &ProjectRoots::active()?,
+        settings,
+        resume.unwrap_or(false),
+        profile.as_deref(),
+        crawl,
+    )
+    .await
+}
+
+// A domain crawl with a saved profile applied, returning the id the crawl is filed under
+// The caller takes the crawl guard first and passes the settings and folders of the project
+// the crawl is for, the batch database is cleared below
+pub(crate) async fn crawl_with_profile(
+    domain: &str,
+    events: CrawlEvents,
+    roots: &ProjectRoots,
+    settings: Settings,
+    resume: bool,
+    profile: Option<&str>,
+    crawl: CrawlGuard,
+) -> Result<domain_crawler::CrawlOutcome, String> {
+    let settings = config_profiles::crawl_settings(roots, settings, profile).await?;
+    let db = crawl_database(roots).await?;
+
     // Call the crawl_domain function with a clone of the database
-    match domain_crawler::crawl_domain(&domain, app_handle, Ok(db.clone()), settings_state).await {
-        Ok(links) => {
-            println!("Discovered {} links", links.len());
-            for data in &links {
-                println!(
-                    "URL: {:?}, Title: {:?}, Description: {:?}",
-                    data.url, data.title, data.description
-                );
-            }
+    // Pick up a crawl of the same domain that was interrupted, when asked to
+    match domain_crawler::crawl_domain(
+        domain,
+        events,
+        roots,
+        Ok(db.clone()),
+        settings,
+        resume,
+        None,
+        crawl,
+    )
+    .await
+    {
+        Ok(outcome) => {
+            println!("Discovered {} links", outcome.pages);
 
             // Verify database contents using the original db
             match db.count_rows().await {
@@ -64,7 +138,7 @@ pub async fn domain_crawl_command(
                 Err(e) => eprintln!("Failed to count rows: {}", e),
             }
 
-            Ok(links)
+            Ok(outcome)
         }
         Err(e) => {
             eprintln!("Crawl error: {}", e);
@@ -73,6 +147,103 @@ pub async fn domain_crawl_command(
     }
 }
 
+// The URL list of a list crawl and the URL the crawl is filed under
+pub(crate) fn url_list(
+    urls: &[String],
+    sitemap_url: Option<String>,
+) -> Result<(String, domain_crawler::UrlList), String> {
+    // Pasted lists come with blank lines and the odd URL without a scheme
+    let mut list = domain_crawler::UrlList::default();
+    for line in urls
+        .iter()
+        .map(|url| url.trim())
+        .filter(|url| !url.is_empty())
+    {
+        match Url::parse(&url_check(line)) {
+            Ok(url) => list.urls.push(url),
+            Err(e) => eprintln!("Skipping invalid URL {}: {}", line, e),
+        }
+    }
+    if let Some(sitemap_url) = sitemap_url.filter(|url| !url.trim().is_empty()) {
+        let sitemap = Url::parse(&url_check(sitemap_url.trim()))
+            .map_err(|e| format!("Invalid sitemap URL: {}", e))?;
+        list.sitemap = Some(sitemap);
+    }
+
+    // The crawl is filed under its first URL
+    let first = match (list.urls.first(), &list.sitemap) {
+        (Some(url), _) | (None, Some(url)) => url.to_string(),
+        (None, None) => return Err("No valid URLs to crawl".to_string()),
+    };
+    Ok((first, list))
+}
+
+// CRAWL EXACTLY THE GIVEN URLS, OR THOSE OF A SITEMAP, WITHOUT FOLLOWING LINKS
+#[tauri::command]
+pub async fn list_crawl_command(
+    urls: Vec<String>,
+    sitemap_url: Option<String>,
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, AppState>,
+    profile: Option<String>,
+) -> Result<domain_crawler::CrawlOutcome, String> {
+    let crawl = crawl_control::try_start()?;
+    let settings = settings_state.settings.read().await.clone();
+    list_crawl(
+        &urls,
+        sitemap_url,
+        app_handle.into(),
+        &ProjectRoots::active()?,
+        settings,
+        profile.as_deref(),
+        crawl,
+    )
+    .await
+}
+
+// A list crawl with a saved profile applied, once the caller holds the crawl guard
+pub(crate) async fn list_crawl(
+    urls: &[String],
+    sitemap_url: Option<String>,
+    events: CrawlEvents,
+    roots: &ProjectRoots,
+    settings: Settings,
+    profile: Option<&str>,
+    crawl: CrawlGuard,
+) -> Result<domain_crawler::CrawlOutcome, String> {
+    let (first, list) = url_list(urls, sitemap_url)?;
+    let settings = config_profiles::crawl_settings(roots, settings, profile).await?;
+    let db = crawl_database(roots).await?;
+    let outcome = domain_crawler::crawl_domain(
+        &first,
+        events,
+        roots,
+        Ok(db),
+        settings,
+        false,
+        Some(list),
+        crawl,
+    )
+    .await
+    .map_err(|e| {
+        eprintln!("List crawl error: {}", e);
+        e
+    })?;
+    println!("Crawled {} listed URLs", outcome.pages);
+    Ok(outcome)
+}
+
+// AUDIT A SINGLE PAGE WITH EVERY EXTRACTOR, WITHOUT A CRAWL
+#[tauri::command]
+pub async fn audit_url(
+    url: String,
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<domain_crawler::PageAudit, String> {
+    let settings = settings_state.settings.read().await.clone();
+    domain_crawler::audit_page(&url, &app_handle.into(), &settings).await
+}
+
 #[tauri::command]
 pub async fn create_excel(data: Vec<Value>) -> Result<Vec<u8>, String> {
     // Call the export_to_excel function and handle its result
@@ -154,7 +325,184 @@ pub async fn get_url_diff_command() -> Result<DiffAnalysis, String> {
 
 #[tauri::command]
 pub async fn clone_crawl_data_command() -> Result<(), String> {
-    database::clone_batched_crawl_into_persistent_db()
+    database::clone_batched_crawl_into_persistent_db(&ProjectRoots::active()?.db_dir)
         .await
         .map_err(|e| e.to_string())
 }
+
+// GET THE BROKEN LINKS FOUND IN A CRAWL
+#[tauri::command]
+pub async fn get_broken_links_command(crawl_id: i64) -> Result<BrokenLinksReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE CANONICAL AUDIT OF A CRAWL
+#[tauri::command]
+pub async fn get_canonical_report_command(crawl_id: i64) -> Result<CanonicalReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE HREFLANG VALIDATION OF A CRAWL
+#[tauri::command]
+pub async fn get_hreflang_report_command(crawl_id: i64) -> Result<HreflangReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE SITEMAP ENTRIES DISCOVERED IN A CRAWL
+#[tauri::command]
+pub async fn get_sitemap_report_command(crawl_id: i64) -> Result<SitemapReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE SITEMAP VS CRAWL COVERAGE GAPS OF A CRAWL
+#[tauri::command]
+pub async fn get_sitemap_gap_report(crawl_id: i64) -> Result<SitemapGapReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE REDIRECT CHAINS OF A CRAWL
+#[tauri::command]
+pub async fn get_redirect_report_command(crawl_id: i64) -> Result<RedirectReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE TITLE AND META DESCRIPTION AUDIT OF A CRAWL
+#[tauri::command]
+pub async fn get_title_description_report_command(
+    crawl_id: i64,
+) -> Result<TitleDescriptionReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE NEAR-DUPLICATE CONTENT CLUSTERS OF A CRAWL
+#[tauri::command]
+pub async fn get_duplicate_content_report_command(
+    crawl_id: i64,
+) -> Result<DuplicateContentReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE RAW VS RENDERED HTML COMPARISON OF A CRAWL
+#[tauri::command]
+pub async fn get_render_report_command(crawl_id: i64) -> Result<RenderReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE CRAWLED URLS THAT ONLY DIFFER IN THEIR QUERY PARAMETERS
+#[tauri::command]
+pub async fn get_parameter_report_command(crawl_id: i64) -> Result<ParameterReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE PAGES PER CLICK DEPTH OF A CRAWL
+#[tauri::command]
+pub async fn get_depth_report_command(crawl_id: i64) -> Result<DepthReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE TTFB AND WATERFALL PERCENTILES OF A CRAWL
+#[tauri::command]
+pub async fn get_timing_report_command(crawl_id: i64) -> Result<TimingReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE BROKEN, OVERSIZED AND THIRD-PARTY IMAGES OF A CRAWL
+#[tauri::command]
+pub async fn get_image_report_command(crawl_id: i64) -> Result<ImageReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE IMAGE ALT TEXT REPORT OF A CRAWL
+#[tauri::command]
+pub async fn get_alt_text_report_command(crawl_id: i64) -> Result<AltTextReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE TF-IDF WEIGHTED TERMS OF A CRAWL
+#[tauri::command]
+pub async fn get_keyword_report_command(crawl_id: i64) -> Result<KeywordReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE TF-IDF WEIGHTED TERMS OF ONE PAGE OF A CRAWL
+#[tauri::command]
+pub async fn get_page_keywords_command(crawl_id: i64, url: String) -> Result<PageKeywords, String> {
+    stored_report::<KeywordReport>(crawl_id)
+        .await?
+        .pages
+        .into_iter()
+        .find(|page| page.url == url)
+        .ok_or_else(|| format!("No keywords recorded for {}", url))
+}
+
+// GET THE SCRIPT AND STYLESHEET INVENTORY OF A CRAWL
+#[tauri::command]
+pub async fn get_asset_report_command(crawl_id: i64) -> Result<AssetReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE ACCESSIBILITY ISSUES OF A CRAWL
+#[tauri::command]
+pub async fn get_a11y_report_command(crawl_id: i64) -> Result<A11yReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE AMP PAIRS OF A CRAWL
+#[tauri::command]
+pub async fn get_amp_report_command(crawl_id: i64) -> Result<AmpReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE ENTITIES AND TOPICS EXTRACTED FROM A CRAWL
+#[tauri::command]
+pub async fn get_entity_report_command(crawl_id: i64) -> Result<EntityReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE FAVICON AND WEB MANIFEST CHECKS OF A CRAWL
+#[tauri::command]
+pub async fn get_icon_report_command(crawl_id: i64) -> Result<IconReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE SECURITY HEADER SCORECARD OF A CRAWL
+#[tauri::command]
+pub async fn get_security_headers_report_command(
+    crawl_id: i64,
+) -> Result<SecurityHeadersReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE TLS CERTIFICATES OF THE HOSTS IN A CRAWL
+#[tauri::command]
+pub async fn get_tls_report_command(crawl_id: i64) -> Result<TlsReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE ANALYTICS AND CONSENT TAGS FOUND IN A CRAWL
+#[tauri::command]
+pub async fn get_tracking_report_command(crawl_id: i64) -> Result<TrackingReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE PAGINATED SERIES OF A CRAWL AND THEIR ISSUES
+#[tauri::command]
+pub async fn get_pagination_report_command(crawl_id: i64) -> Result<PaginationReport, String> {
+    stored_report(crawl_id).await
+}
+
+// GET THE DNS AND HOST VARIANT CHECKS RUN BEFORE A CRAWL
+#[tauri::command]
+pub async fn get_preflight_report_command(crawl_id: i64) -> Result<PreflightReport, String> {
+    stored_report(crawl_id).await
+}
+
+async fn stored_report<R: StoredReport>(crawl_id: i64) -> Result<R, String> {
+    ResultsStore::open()
+        .await
+        .map_err(|e| e.to_string())?
+        .report(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No {} report stored for crawl {}", R::KIND, crawl_id))
+}
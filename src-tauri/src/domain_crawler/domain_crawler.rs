@@ -1,53 +1,87 @@
 use colored::*;
-use futures::stream::{self, StreamExt};
+use futures::stream::{self, FuturesUnordered, StreamExt};
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
 use rand::Rng;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::sync::{Arc, RwLock};
 use std::time::Instant;
-use tauri::{Emitter, Manager};
 use tokio::sync::{Mutex, Semaphore};
 use tokio::task;
 use tokio::time::{sleep, Duration};
 use url::Url;
 
 use crate::crawler::get_page_speed_insights;
+use crate::domain_crawler::a11y;
+use crate::domain_crawler::crawl_audits::{self, CrawlAudits, CrawlFacts};
+use crate::domain_crawler::crawl_control::{self, CrawlGuard};
+use crate::domain_crawler::crawl_progress;
+use crate::domain_crawler::crawl_scope::CrawlScope;
+use crate::domain_crawler::crawl_state_store::{BatchProgress, CrawlStateStore};
+use crate::domain_crawler::custom_search;
 use crate::domain_crawler::database::{Database, DatabaseResults};
+use crate::domain_crawler::entity_audit;
+use crate::domain_crawler::events::CrawlEvents;
+use crate::domain_crawler::extractors::custom;
 use crate::domain_crawler::extractors::html::extract_html;
+use crate::domain_crawler::frontier::Frontier;
 use crate::domain_crawler::helpers::https_checker::valid_https;
+use crate::domain_crawler::link_checker::{self, LinkChecker};
 use crate::domain_crawler::models::Extractor;
+use crate::domain_crawler::preflight;
+use crate::domain_crawler::proxies::{self, ProxyPool};
+use crate::domain_crawler::rate_limiter::HostRateLimiter;
+use crate::domain_crawler::redirect_audit::{self, RedirectHop};
+use crate::domain_crawler::renderer;
+use crate::domain_crawler::request_auth;
+use crate::domain_crawler::response_cache;
+use crate::domain_crawler::results_store::ResultsStore;
+use crate::domain_crawler::screenshots;
+use crate::domain_crawler::session;
+use crate::domain_crawler::spell_check;
+use crate::domain_crawler::subresources;
+use crate::domain_crawler::url_normalizer::UrlNormalizer;
 use crate::domain_crawler::user_agents;
+use crate::domain_crawler::webhooks;
+use crate::projects::registry::ProjectRoots;
 use crate::settings::settings::Settings;
-use crate::AppState;
 
 use super::database::{self, DatabaseError};
-use super::helpers::canonical_selector::get_canonical;
+use super::helpers::canonical_selector::{audit_canonical, get_canonical};
 use super::helpers::cross_origin::analyze_cross_origin_security;
-use super::helpers::flesch_reader::get_flesch_score;
-use super::helpers::hreflang_selector::select_hreflang;
+use super::helpers::flesch_reader::{get_flesch_score, readability};
+use super::helpers::hreflang_selector::{select_hreflang, select_hreflang_headers};
 use super::helpers::html_size_calculator::calculate_html_size;
+use super::helpers::indexability::Indexability;
 use super::helpers::keyword_selector::extract_keywords;
-use super::helpers::language_selector::detect_language;
+use super::helpers::language_selector::{audit_language, detect_language};
 use super::helpers::links_status_code_checker::get_links_status_code;
 use super::helpers::meta_robots_selector::{get_meta_robots, MetaRobots};
+use super::helpers::robots::RobotsCache;
+use super::helpers::site_icons;
+use super::helpers::sitemap;
 use super::helpers::text_ratio::{get_text_ratio, TextRatio};
 use super::helpers::{
-    alt_tags, anchor_links, check_html_page,
+    alt_tags, amp_selector, anchor_links, assets_selector, check_html_page, content_analyzer,
     css_selector::{self, extract_css},
     domain_checker::url_check,
-    headings_selector, iframe_selector, images_selector, indexability, javascript_selector,
-    links_selector,
+    font_selector, headings_selector, iframe_selector, images_selector, indexability,
+    javascript_selector, lazy_loading, links_selector, media_selector,
     mobile_checker::is_mobile,
-    page_description,
+    page_description, pagination_selector,
     pdf_selector::extract_pdf_links,
-    schema_selector, title_selector,
+    render_diff, schema_selector, security_headers, social_tags_selector, structured_data_selector,
+    term_analysis, title_description, title_selector, tracking_selector, transfer_diagnostics,
+    waterfall,
     word_count::{self, get_word_count},
 };
 use super::helpers::{pdf_checker, pdf_selector};
 use super::models::DomainCrawlResults;
 use super::page_speed::bulk::fetch_psi_bulk;
+use super::page_speed::lab;
 use super::page_speed::model::Crawler;
 
 // Constants for crawler behavior
@@ -76,27 +110,67 @@ struct CrawlResultData {
 
 // Structure to track crawler state
 pub struct CrawlerState {
-    pub visited: HashSet<String>,
+    // Queued, in-flight and visited URLs with their link depth
+    pub frontier: Frontier,
     pub failed_urls: HashSet<String>,
-    pub pending_urls: HashSet<String>,
     pub results: Vec<DomainCrawlResults>,
-    pub queue: VecDeque<Url>,
     pub total_urls: usize,
     pub crawled_urls: usize,
     pub db: Option<Database>,
+    pub link_checker: LinkChecker,
+    // URLs added to the queue since the last batch was persisted
+    pub discovered: Vec<(String, Option<usize>)>,
+    pub scope: CrawlScope,
+    pub normalizer: UrlNormalizer,
+    // Discovered links rewritten by the normalizer
+    pub normalized_links: usize,
+    // Input position of every page of a list crawl, which does not follow links
+    pub list_positions: Option<HashMap<String, usize>>,
+    // Single-page audits run outside of a crawl and report no progress
+    pub report_progress: bool,
+    // Paginated links left out by the depth or page limit, not gaps in their series
+    pub limited_pages: HashSet<String>,
+}
+
+/// The URLs of a list crawl, fetched exactly as given instead of spidering from a start page.
+#[derive(Debug, Clone, Default)]
+pub struct UrlList {
+    pub urls: Vec<Url>,
+    /// A sitemap, or sitemap index, whose URLs are appended to `urls`
+    pub sitemap: Option<Url>,
+}
+
+/// The id a crawl is filed under in the results store, its pages are paged through from there.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CrawlOutcome {
+    /// None when the results store was unavailable
+    pub crawl_id: Option<i64>,
+    /// Unique pages crawled
+    pub pages: usize,
 }
 
 impl CrawlerState {
-    fn new(db: Option<Database>) -> Self {
+    fn new(
+        db: Option<Database>,
+        frontier: Frontier,
+        scope: CrawlScope,
+        normalizer: UrlNormalizer,
+    ) -> Self {
         CrawlerState {
-            visited: HashSet::new(),
+            frontier,
             failed_urls: HashSet::new(),
-            pending_urls: HashSet::new(),
             results: Vec::new(),
-            queue: VecDeque::new(),
             total_urls: 0,
             crawled_urls: 0,
             db,
+            link_checker: LinkChecker::new(),
+            discovered: Vec::new(),
+            scope,
+            normalizer,
+            normalized_links: 0,
+            list_positions: None,
+            report_progress: true,
+            limited_pages: HashSet::new(),
         }
     }
 }
@@ -109,27 +183,45 @@ fn to_database_results(result: &DomainCrawlResults) -> DatabaseResults {
     }
 }
 
-// Fetch URL with exponential backoff
+// Fetch URL with exponential backoff, pacing each host through the rate limiter
 async fn fetch_with_exponential_backoff(
     client: &Client,
-    url: &str,
+    url: &Url,
     settings: &Settings,
+    rate_limiter: &HostRateLimiter,
 ) -> Result<(reqwest::Response, f64), reqwest::Error> {
     let mut attempt = 0;
     loop {
+        rate_limiter.acquire(url).await;
+
         let start = Instant::now();
-        match client.get(url).send().await {
+        let request = request_auth::apply(client.get(url.as_str()), url.as_str()).header(
+            reqwest::header::ACCEPT_ENCODING,
+            transfer_diagnostics::ACCEPT_ENCODING,
+        );
+        match response_cache::conditional(request, url.as_str())
+            .await
+            .send()
+            .await
+        {
             Ok(response) => {
-                let duration = start.elapsed().as_secs_f64();
-                if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                let elapsed = start.elapsed();
+                let duration = elapsed.as_secs_f64();
+                let retry_after = rate_limiter.record_response(url, elapsed, &response).await;
+
+                let status = response.status();
+                if status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                    || status == reqwest::StatusCode::SERVICE_UNAVAILABLE
+                {
                     if attempt >= settings.max_retries {
                         return Ok((response, duration));
                     }
-                    let delay = std::cmp::min(
+                    let backoff = Duration::from_millis(std::cmp::min(
                         settings.max_delay,
                         settings.base_delay * 2u64.pow(attempt as u32),
-                    );
-                    sleep(Duration::from_millis(delay)).await;
+                    ));
+                    // The server knows best how long it needs
+                    sleep(retry_after.map_or(backoff, |r| r.max(backoff))).await;
                     attempt += 1;
                     continue;
                 }
@@ -150,23 +242,91 @@ async fn fetch_with_exponential_backoff(
     }
 }
 
+// Follow redirects by hand so every hop is recorded, stopping on loops and after `redirect_policy` hops
+async fn fetch_following_redirects(
+    client: &Client,
+    url: &Url,
+    settings: &Settings,
+    rate_limiter: &HostRateLimiter,
+) -> Result<(reqwest::Response, f64, f64, Vec<RedirectHop>), reqwest::Error> {
+    let mut current = url.clone();
+    let mut hops: Vec<RedirectHop> = Vec::new();
+    let mut total_time = 0.0;
+
+    loop {
+        let (response, time) =
+            fetch_with_exponential_backoff(client, &current, settings, rate_limiter).await?;
+        total_time += time;
+
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|h| h.to_str().ok())
+            .map(String::from);
+
+        let (Some(location), true) = (location, response.status().is_redirection()) else {
+            return Ok((response, total_time, time, hops));
+        };
+
+        hops.push(RedirectHop {
+            url: current.to_string(),
+            status_code: response.status().as_u16(),
+            location: location.clone(),
+        });
+
+        let next = match current.join(&location) {
+            Ok(next) => next,
+            Err(_) => return Ok((response, total_time, time, hops)),
+        };
+        if redirect_audit::is_loop(&hops) || hops.len() >= settings.redirect_policy {
+            return Ok((response, total_time, time, hops));
+        }
+        current = next;
+    }
+}
+
+// Write a chunk of finished pages to the results store and the crawl database in one go
+async fn store_results(
+    db_pool: Option<&Arc<Pool<SqliteConnectionManager>>>,
+    results_store: Option<&(ResultsStore, i64)>,
+    pending: &mut Vec<DomainCrawlResults>,
+) {
+    if pending.is_empty() {
+        return;
+    }
+    let results = std::mem::take(pending);
+
+    if let Some((store, crawl_id)) = results_store {
+        if let Err(e) = store.insert_pages(*crawl_id, &results).await {
+            eprintln!("Failed to store {} results: {}", results.len(), e);
+        }
+    }
+    if let Some(pool) = db_pool {
+        let db_results = results.iter().map(to_database_results).collect();
+        if let Err(e) = database::insert_bulk_crawl_data(pool.clone(), db_results).await {
+            eprintln!("Failed to batch insert results: {}", e);
+        }
+    }
+}
+
 // Process single URL
 async fn process_url(
     url: Url,
     client: &Client,
     base_url: &Url,
     state: Arc<Mutex<CrawlerState>>,
-    app_handle: &tauri::AppHandle,
+    events: &CrawlEvents,
     settings: &Settings,
+    rate_limiter: &HostRateLimiter,
 ) -> Result<DomainCrawlResults, String> {
     let response_result = tokio::time::timeout(
         Duration::from_secs(60),
-        fetch_with_exponential_backoff(client, url.as_str(), settings),
+        fetch_following_redirects(client, &url, settings, rate_limiter),
     )
     .await;
 
-    let (response, response_time) = match response_result {
-        Ok(Ok((response, time))) => (response, time),
+    let (response, response_time, ttfb, redirect_chain) = match response_result {
+        Ok(Ok((response, time, ttfb, hops))) => (response, time, ttfb, hops),
         Ok(Err(e)) => {
             let mut state = state.lock().await;
             state.failed_urls.insert(url.to_string());
@@ -197,10 +357,16 @@ async fn process_url(
         .map(|s| s.parse::<usize>().unwrap_or(0));
 
     let content_len = content_length.clone();
-    let redirection = response
-        .headers()
-        .get("Location")
-        .and_then(|h| h.to_str().ok().map(String::from));
+    // The first hop tells where the requested URL redirects to
+    let redirection = redirect_chain
+        .first()
+        .map(|hop| hop.location.clone())
+        .or_else(|| {
+            response
+                .headers()
+                .get("Location")
+                .and_then(|h| h.to_str().ok().map(String::from))
+        });
 
     let headers = response
         .headers()
@@ -215,14 +381,50 @@ async fn process_url(
         sleep(Duration::from_secs(2)).await;
     }
 
-    let body = match response.text().await {
-        Ok(body) => body,
+    // Unchanged since the last crawl, the stored result stands in for downloading and parsing
+    if status_code == 304 {
+        if let Some(cached) = response_cache::cached(final_url.as_str()).await {
+            let mut result = cached.result;
+            result.response_time = Some(response_time);
+            result.redirect_chain = redirect_chain;
+            result.changed_since_last_crawl = Some(false);
+
+            let mut state = state.lock().await;
+            record_page(&mut state, &url, &mut result, cached.links, events);
+            return Ok(result);
+        }
+    }
+
+    // Compression is decoded here rather than by reqwest so the transfer size stays known
+    let version = response.version();
+    let content_encoding = response
+        .headers()
+        .get(reqwest::header::CONTENT_ENCODING)
+        .and_then(|h| h.to_str().ok())
+        .map(String::from);
+    let decoded = match response.bytes().await {
+        Ok(bytes) => transfer_diagnostics::decode_body(&bytes, content_encoding.as_deref())
+            .map(|decoded| (bytes.len(), decoded)),
+        Err(e) => Err(format!("Failed to read response body: {}", e)),
+    };
+    let (transfer_size, decoded) = match decoded {
+        Ok(decoded) => decoded,
         Err(e) => {
             let mut state = state.lock().await;
             state.failed_urls.insert(url.to_string());
-            return Err(format!("Failed to read response body: {}", e));
+            return Err(e);
         }
     };
+    let transfer = transfer_diagnostics::diagnose(
+        version,
+        https,
+        content_encoding.as_deref(),
+        content_type.as_deref(),
+        transfer_size,
+        decoded.len(),
+        ttfb,
+    );
+    let mut body = transfer_diagnostics::decode_text(&decoded, content_type.as_deref());
 
     let mut pdf_files: Vec<String> = Vec::new();
     if !check_html_page::is_html_page(&body, content_type.as_deref()).await {
@@ -230,16 +432,61 @@ async fn process_url(
 
         let mut state = state.lock().await;
         state.crawled_urls += 1;
-        state.visited.insert(url.to_string());
-        state.pending_urls.remove(url.as_str());
+        state.frontier.mark_visited(url.as_str());
         return Ok(DomainCrawlResults {
             url: final_url.to_string(),
             status_code,
+            crawl_depth: state.frontier.depth(url.as_str()),
             pdf_files,
+            transfer,
             ..Default::default()
         });
     }
 
+    // A second request on a fresh connection, it would bypass the proxies so skip it behind one
+    let waterfall =
+        if settings.waterfall_timing && settings.proxies.iter().all(|p| p.trim().is_empty()) {
+            rate_limiter.acquire(&final_url).await;
+            match waterfall::measure(&final_url, &user_agents::current()).await {
+                Ok(waterfall) => Some(waterfall),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+    // With rendering on everything below works on the DOM after scripts ran
+    // Colours are only meaningful once scripts applied their styles, they are read in the
+    // tab the page was rendered in
+    let mut raw_html = None;
+    let mut rendered_html = None;
+    let mut measured_contrast = None;
+    if let Some(rendered) =
+        renderer::render_evaluating(&final_url, a11y::rules::CONTRAST_SCRIPT).await
+    {
+        match rendered {
+            Ok((html, contrast)) => {
+                raw_html = Some(std::mem::replace(&mut body, html.clone()));
+                rendered_html = Some(html);
+                measured_contrast = contrast.as_str().map(str::to_string);
+            }
+            Err(e) => eprintln!("{}, using the raw HTML", e),
+        }
+    }
+    // Screenshots take several browser runs, the page does not wait for them
+    if rendered_html.is_some() && screenshots::is_active() {
+        tokio::spawn(screenshots::capture(final_url.clone()));
+    }
+    let render_diff = match (&raw_html, &rendered_html) {
+        (Some(raw), Some(rendered)) => Some(render_diff::diff_rendered(raw, rendered, &final_url)),
+        _ => None,
+    };
+
+    let custom_search = custom_search::scan_page(raw_html.as_deref().unwrap_or(&body), &body);
+
     let internal_external_links = anchor_links::extract_internal_external_links(&body, base_url);
 
     let check_links_status_code = get_links_status_code(
@@ -254,6 +501,7 @@ async fn process_url(
 
     // Cross-origin checker funtion
     let cross_origin = analyze_cross_origin_security(&body, base_url);
+    let security_headers = security_headers::audit_security_headers(&headers, https);
 
     // Page Speed Insights Checker
     // Check if the key exists to make the call otherwise return an empty vector
@@ -277,26 +525,101 @@ async fn process_url(
         None => Ok(Vec::new()),                             // No PSI requested
     };
 
-    let result = DomainCrawlResults {
+    // Only download image bytes when the user opted in, it is expensive on large sites
+    let image_candidates = images_selector::extract_image_candidates(&body, base_url);
+    let image_dimensions = if settings.images_decode_dimensions {
+        images_selector::audit_image_dimensions(&image_candidates, settings.images_decode_max_bytes)
+            .await
+    } else {
+        Vec::new()
+    };
+
+    // Audit the PDFs linked from this page
+    let pdf_audits = match extract_pdf_links(&body, base_url) {
+        Some(links) => pdf_selector::audit_pdf_links(&links).await,
+        None => Vec::new(),
+    };
+
+    let canonical = audit_canonical(&body, &final_url);
+
+    // Combine the <link> annotations with the ones sent in HTTP headers
+    let mut hreflangs = select_hreflang(&body).unwrap_or_default();
+    hreflangs.extend(select_hreflang_headers(&headers));
+    let language_audit = audit_language(&body, &final_url, &hreflangs);
+    let readability = readability(&body, language_audit.detected.as_deref());
+    // Fall back to the primary subtag of <html lang> when the text is too short to detect
+    let spelling_language = language_audit.detected.clone().or_else(|| {
+        language_audit
+            .declared
+            .as_deref()
+            .and_then(|lang| lang.split(['-', '_']).next())
+            .map(str::to_lowercase)
+    });
+    let spelling = spell_check::check_page(&body, spelling_language.as_deref());
+    entity_audit::collect(final_url.as_str(), &body);
+
+    // A separate browser run, the DOM dump cannot read performance entries
+    let lab_vitals = if rendered_html.is_some() && settings.lab_vitals {
+        match lab::measure(&final_url).await {
+            Some(Ok(vitals)) => Some(vitals),
+            Some(Err(e)) => {
+                eprintln!("Lab vitals of {} failed: {}", final_url, e);
+                None
+            }
+            None => None,
+        }
+    } else {
+        None
+    };
+
+    let mut accessibility = a11y::rules::check_page(&body);
+    if let Some(measured) = &measured_contrast {
+        if let Err(e) = a11y::rules::add_contrast(&mut accessibility, measured) {
+            eprintln!("Contrast check of {} failed: {}", final_url, e);
+        }
+    }
+
+    let mut social_tags = social_tags_selector::extract_social_tags(&body);
+    social_tags_selector::check_og_image(&mut social_tags, &final_url).await;
+
+    let mut result = DomainCrawlResults {
         url: final_url.to_string(),
         title: title_selector::extract_title(&body),
         description: page_description::extract_page_description(&body)
             .unwrap_or_else(|| "".to_string()),
+        title_description: title_description::audit_title_description(&body),
         headings: headings_selector::headings_selector(&body),
+        heading_outline: headings_selector::extract_heading_outline(&body),
         javascript: javascript_selector::extract_javascript(&body, base_url),
         images: images_selector::extract_images_with_sizes_and_alts(&body, base_url).await,
+        image_candidates,
+        image_dimensions,
+        media: media_selector::extract_media_with_sizes(&body, base_url).await,
+        lazy_loading: lazy_loading::audit_lazy_loading(&body, &final_url),
+        assets: assets_selector::extract_assets_with_sizes(&body, &final_url).await,
+        fonts: font_selector::audit_fonts(&body, &final_url).await,
         status_code,
+        blocked_by_robots: false,
+        crawl_depth: None,
+        changed_since_last_crawl: None,
         anchor_links: anchor_links::extract_internal_external_links(&body, base_url),
         inoutlinks_status_codes: check_links_status_code,
-        indexability: indexability::extract_indexability(&body),
-        alt_tags: alt_tags::get_alt_tags(&body),
+        // robots.txt is checked by the caller, which overrides the verdict when disallowed
+        indexability: indexability::classify_indexability(&body, &headers, &canonical, true),
+        alt_tags: alt_tags::get_alt_tags(&body, &final_url),
         schema: schema_selector::get_schema(&body),
+        structured_data: structured_data_selector::extract_structured_data(&body),
+        social_tags,
         css: css_selector::extract_css(&body, base_url.clone()),
-        iframe: iframe_selector::extract_iframe(&body),
+        iframe: iframe_selector::extract_iframe(&body, &final_url),
         word_count: get_word_count(&body),
+        content: content_analyzer::analyze_content(&body, settings.thin_content_threshold),
         response_time: Some(response_time),
         mobile: is_mobile(&body),
         canonicals: get_canonical(&body).map(|c| c.canonicals),
+        canonical,
+        amp: amp_selector::extract_amp(&body, &final_url),
+        pagination: pagination_selector::extract_pagination(&body, &final_url),
         meta_robots: get_meta_robots(&body).unwrap_or(MetaRobots {
             meta_robots: Vec::new(),
         }),
@@ -310,109 +633,268 @@ async fn process_url(
                 text_ratio: 0.0,
             })]),
         redirection,
+        redirect_chain,
+        raw_html,
+        rendered_html,
+        render_diff,
         keywords: extract_keywords(&body),
+        term_counts: term_analysis::count_terms(&body),
         page_size: calculate_html_size(content_len),
-        hreflangs: select_hreflang(&body),
+        hreflangs: (!hreflangs.is_empty()).then_some(hreflangs),
         language: detect_language(&body),
+        language_audit,
         flesch: get_flesch_score(&body),
+        readability,
+        spelling,
+        accessibility,
         psi_results,
+        lab_vitals,
         extractor: Extractor {
             html: extract_html(&body).await,
             css: false,
             regex: false,
         },
+        custom_extraction: custom::extract(&body),
+        custom_search,
+        tracking: tracking_selector::extract_tracking(&body),
         headers,
         pdf_files,
+        pdf_audits,
         https,
         cross_origin,
+        security_headers,
+        transfer,
+        waterfall,
     };
 
-    {
-        let mut state = state.lock().await;
-        state.results.push(result.clone());
-        state.crawled_urls += 1;
-        state.visited.insert(url.to_string());
-        state.pending_urls.remove(url.as_str());
+    let links = links_selector::extract_links(&body, base_url);
+    if status_code == 200 {
+        // Rendered markup can vary between loads, the served HTML is what validators describe
+        let served = result.raw_html.as_deref().unwrap_or(&body);
+        result.changed_since_last_crawl =
+            response_cache::store(final_url.as_str(), &result.headers, served, &links, &result)
+                .await;
+    }
 
-        let links = links_selector::extract_links(&body, base_url);
-        for link in links {
-            let link_str = link.as_str();
-            if should_skip_url(link_str) {
-                continue;
-            }
+    let mut state = state.lock().await;
+    if settings.link_checker {
+        state.link_checker.collect(&final_url, &body, base_url);
+    }
+    record_page(&mut state, &url, &mut result, links, events);
 
-            if !state.visited.contains(link_str)
-                && !state.queue.contains(&link)
-                && !state.pending_urls.contains(link_str)
-            {
-                state.queue.push_back(link.clone());
-                state.total_urls += 1;
-                state.pending_urls.insert(link_str.to_string());
-            }
-        }
+    Ok(result)
+}
 
-        let progress = ProgressData {
-            total_urls: state.total_urls,
-            crawled_urls: state.crawled_urls,
-            percentage: (state.crawled_urls as f32 / state.total_urls as f32) * 100.0,
-            failed_urls: state.failed_urls.len(),
-        };
+// Count a finished page and queue its links, one click deeper, within the crawl scope
+fn record_page(
+    state: &mut CrawlerState,
+    url: &Url,
+    result: &mut DomainCrawlResults,
+    links: Vec<Url>,
+    events: &CrawlEvents,
+) {
+    result.crawl_depth = state.frontier.depth(url.as_str());
+    state.results.push(result.clone());
+    state.crawled_urls += 1;
+    state.frontier.mark_visited(url.as_str());
 
-        if let Err(err) = app_handle.emit("progress_update", progress) {
-            eprintln!("Failed to emit progress update: {}", err);
+    let depth = state.frontier.depth(url.as_str()).unwrap_or(0) + 1;
+    let links = if state.list_positions.is_some() {
+        Vec::new()
+    } else {
+        links
+    };
+    state.frontier.begin();
+    for link in links {
+        let normalized = state.normalizer.normalize(&link);
+        if normalized != link {
+            state.normalized_links += 1;
+        }
+        let link = normalized;
+        let link_str = link.as_str();
+        if should_skip_url(link_str) || !state.scope.allows(&link) {
+            continue;
+        }
+        if !state.scope.within_depth(depth) || !state.scope.has_room(state.total_urls) {
+            if pagination_selector::page_number(&link).is_some() {
+                state.limited_pages.insert(link_str.to_string());
+            }
+            continue;
         }
 
-        let result_data = CrawlResultData {
-            result: result.clone(),
-        };
-        if let Err(err) = app_handle.emit("crawl_result", result_data) {
-            eprintln!("Failed to emit crawl result: {}", err);
+        if state.frontier.push(link.clone(), Some(depth)) {
+            state.total_urls += 1;
+            state.discovered.push((link_str.to_string(), Some(depth)));
         }
+    }
+    state.frontier.commit();
 
-        let percentage = (state.crawled_urls as f32 / state.total_urls as f32) * 100.0;
-        print!(
-            "\r{}: {:.2}% {}",
-            "Progress".green().bold(),
-            percentage,
-            "complete".green().bold()
-        );
-        std::io::stdout().flush().unwrap();
+    if !state.report_progress {
+        return;
     }
 
-    Ok(result)
+    let progress = ProgressData {
+        total_urls: state.total_urls,
+        crawled_urls: state.crawled_urls,
+        percentage: (state.crawled_urls as f32 / state.total_urls as f32) * 100.0,
+        failed_urls: state.failed_urls.len(),
+    };
+
+    if let Err(err) = events.emit("progress_update", progress) {
+        eprintln!("Failed to emit progress update: {}", err);
+    }
+
+    let percentage = (state.crawled_urls as f32 / state.total_urls as f32) * 100.0;
+    print!(
+        "\r{}: {:.2}% {}",
+        "Progress".green().bold(),
+        percentage,
+        "complete".green().bold()
+    );
+    std::io::stdout().flush().unwrap();
 }
 
 fn should_skip_url(url: &str) -> bool {
     url.contains('#') || url.contains("login")
 }
 
+// The client for robots.txt, sitemaps and such, and the pool pages are fetched with
+fn crawl_clients(settings: &Settings, user_agent: &str) -> Result<(Client, ProxyPool), String> {
+    let client = proxies::apply(Client::builder())
+        // .user_agent(&user_agents[rand::thread_rng().gen_range(0..user_agents.len())])
+        // Instead use the user agents in the configuration files
+        .user_agent(user_agent)
+        .timeout(Duration::from_secs(settings.client_timeout)) // 60 seconds
+        .connect_timeout(Duration::from_secs(settings.client_connect_timeout)) // 15
+        .redirect(reqwest::redirect::Policy::limited(settings.redirect_policy)) // 5
+        .cookie_provider(session::cookies())
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    // Pages are fetched without automatic redirects so each hop can be recorded,
+    // rotating over the configured proxies
+    let page_clients = ProxyPool::build(settings, || {
+        Client::builder()
+            .user_agent(user_agent)
+            .timeout(Duration::from_secs(settings.client_timeout))
+            .connect_timeout(Duration::from_secs(settings.client_connect_timeout))
+            .redirect(reqwest::redirect::Policy::none())
+            .cookie_provider(session::cookies())
+    })?;
+
+    Ok((client, page_clients))
+}
+
+// Resets the per-crawl caches and sets up the extractors that depend on the settings
+async fn configure_crawl(
+    settings: &Settings,
+    base_url: &Url,
+    client: &Client,
+    user_agent: &str,
+    roots: &ProjectRoots,
+    rate_limiter: &Arc<HostRateLimiter>,
+) -> Result<(), String> {
+    // Shared pooled client for the per-page image checks
+    images_selector::init_image_client(settings);
+    subresources::configure(rate_limiter.clone());
+    assets_selector::reset_asset_cache();
+    pdf_selector::reset_pdf_cache();
+    font_selector::reset_font_cache();
+
+    request_auth::configure(settings, base_url)?;
+    response_cache::configure(settings, &roots.db_dir)?;
+    spell_check::configure(settings)?;
+    entity_audit::configure(settings);
+    custom::configure(roots)?;
+    custom_search::configure(settings, roots)?;
+    session::start(settings, base_url, client).await?;
+    renderer::configure(settings, user_agent)
+}
+
+/// Crawls a domain, or only the URLs of `url_list`, and sends the crawl webhooks once it ends.
+///
+/// The guard from [`crawl_control::try_start`] keeps other crawls out until this one is done.
+/// Everything the crawl stores goes to the folders of `roots`, whichever project is active.
+#[allow(clippy::too_many_arguments)]
 pub async fn crawl_domain(
     domain: &str,
-    app_handle: tauri::AppHandle,
+    events: CrawlEvents,
+    roots: &ProjectRoots,
     db: Result<Database, DatabaseError>,
-    settings_state: tauri::State<'_, AppState>,
-) -> Result<Vec<DomainCrawlResults>, String> {
+    settings: Settings,
+    resume: bool,
+    url_list: Option<UrlList>,
+    crawl: CrawlGuard,
+) -> Result<CrawlOutcome, String> {
+    let result = run_crawl(domain, events, roots, db, settings, resume, url_list).await;
+    // Errors return early from the crawl, dropping the guard sets it back to idle on every path
+    drop(crawl);
+
+    // A crawl that failed before it was registered has no id of its own
+    let crawl_id = result.as_ref().ok().and_then(|outcome| outcome.crawl_id);
+    let error = result.as_ref().err().cloned();
+    webhooks::crawl_finished(roots, &url_check(domain), crawl_id, error).await;
+    result
+}
+
+async fn run_crawl(
+    domain: &str,
+    events: CrawlEvents,
+    roots: &ProjectRoots,
+    db: Result<Database, DatabaseError>,
+    settings: Settings,
+    resume: bool,
+    url_list: Option<UrlList>,
+) -> Result<CrawlOutcome, String> {
     // Import the user agents from another module to use across domain crawler
     // // Using the ones from global state/memory that are placed in the HD
     // let user_agents = user_agents::agents();
 
-    let settings = Arc::new(settings_state.settings.read().await.clone());
+    let settings = Arc::new(settings);
 
-    let client = Client::builder()
-        // .user_agent(&user_agents[rand::thread_rng().gen_range(0..user_agents.len())])
-        // Instead use the user agents in the configuration files
-        .user_agent(
-            &settings.user_agents[rand::thread_rng().gen_range(0..settings.user_agents.len())],
-        )
-        .timeout(Duration::from_secs(settings.client_timeout)) // 60 seconds
-        .connect_timeout(Duration::from_secs(settings.client_connect_timeout)) // 15
-        .redirect(reqwest::redirect::Policy::limited(settings.redirect_policy)) // 5
-        .build()
-        .map_err(|e| e.to_string())?;
+    // Keep the chosen user agent around, robots.txt rules are matched against it
+    let (user_agent_profile, user_agent) = user_agents::resolve(&settings)?;
+    let user_agent = Arc::new(user_agent);
+    user_agents::set_current(&user_agent);
+    proxies::configure(&settings)?;
+
+    let (client, page_clients) = crawl_clients(&settings, &user_agent)?;
+    let page_clients = Arc::new(page_clients);
+
+    let robots = Arc::new(RobotsCache::new(client.clone()));
+    let rate_limiter = Arc::new(HostRateLimiter::new(
+        Duration::from_millis(settings.per_host_delay_ms),
+        settings.per_host_burst,
+    ));
 
     let url_checked = url_check(domain);
     let base_url = Url::parse(&url_checked).map_err(|_| "Invalid URL")?;
 
+    configure_crawl(
+        &settings,
+        &base_url,
+        &client,
+        &user_agent,
+        roots,
+        &rate_limiter,
+    )
+    .await?;
+
+    // Report DNS and host variant problems up front, the crawl goes ahead regardless.
+    // Reports from before the crawl has an id are stored once it does
+    let mut preflight_report = None;
+    if settings.preflight_checks {
+        match preflight::run(&base_url, &user_agent).await {
+            Ok(report) => {
+                if let Err(err) = events.emit("preflight_report", &report) {
+                    eprintln!("Failed to emit preflight report: {}", err);
+                }
+                preflight_report = Some(report);
+            }
+            Err(e) => eprintln!("Preflight checks failed: {}", e),
+        }
+    }
+
     let db_option = match db {
         Ok(database) => Some(database),
         Err(e) => {
@@ -421,61 +903,331 @@ pub async fn crawl_domain(
         }
     };
 
-    let state = Arc::new(Mutex::new(CrawlerState::new(db_option)));
+    // Frontier, visited set and partial results survive restarts through the state store
+    let state_store = match CrawlStateStore::open_in(&roots.db_dir).await {
+        Ok(store) => Some(store),
+        Err(e) => {
+            eprintln!("Crawl state store unavailable: {}", e);
+            None
+        }
+    };
+    // List crawls are short and fetch a fixed set of URLs, they always start over
+    let saved_crawl = match (&state_store, resume && url_list.is_none()) {
+        (Some(store), true) => store.load(&url_checked).await.unwrap_or_else(|e| {
+            eprintln!("Failed to load saved crawl: {}", e);
+            None
+        }),
+        _ => None,
+    };
+
+    let scope = CrawlScope::from_settings(&settings, &base_url)?;
+    let normalizer = UrlNormalizer::from_settings(&settings);
+    let mut frontier = if settings.disk_frontier {
+        Frontier::on_disk(&roots.db_dir, &url_checked, saved_crawl.is_some())?
+    } else {
+        Frontier::in_memory()
+    };
+    if let (Some(store), Some(_)) = (&state_store, &saved_crawl) {
+        frontier = store
+            .restore_frontier(&url_checked, frontier)
+            .await
+            .map_err(|e| format!("Failed to restore saved crawl: {}", e))?;
+    }
+    let state = Arc::new(Mutex::new(CrawlerState::new(
+        db_option, frontier, scope, normalizer,
+    )));
     {
         let mut state = state.lock().await;
-        state.queue.push_back(base_url.clone());
-        state.total_urls = 1;
-        state.pending_urls.insert(base_url.to_string());
+        match saved_crawl {
+            Some(saved) => {
+                println!(
+                    "Resuming crawl of {} with {} URLs left",
+                    url_checked, saved.queued
+                );
+                state.failed_urls = saved.failed_urls;
+                state.results = saved.results;
+                state.total_urls = saved.total_urls;
+                state.crawled_urls = saved.crawled_urls;
+            }
+            None => {
+                if let Some(store) = &state_store {
+                    if let Err(e) = store.begin(&url_checked).await {
+                        eprintln!("Failed to start crawl session: {}", e);
+                    }
+                }
+                if url_list.is_none() {
+                    state.frontier.push(base_url.clone(), Some(0));
+                    state.total_urls = 1;
+                    state.discovered.push((base_url.to_string(), Some(0)));
+                }
+            }
+        }
+    }
+
+    // A list crawl queues its URLs in input order, out of scope or not
+    if let Some(list) = url_list {
+        let mut urls = list.urls;
+        if let Some(sitemap_url) = &list.sitemap {
+            let report = sitemap::read_sitemap(&client, sitemap_url.as_str()).await;
+            for error in &report.errors {
+                eprintln!("{}", error);
+            }
+            urls.extend(
+                report
+                    .entries
+                    .iter()
+                    .filter_map(|entry| Url::parse(&entry.loc).ok()),
+            );
+        }
+
+        let mut state = state.lock().await;
+        let mut positions = HashMap::new();
+        state.frontier.begin();
+        for url in urls {
+            if state.frontier.push(url.clone(), Some(0)) {
+                positions.insert(url.to_string(), positions.len());
+                state.discovered.push((url.to_string(), Some(0)));
+                state.total_urls += 1;
+            }
+        }
+        state.frontier.commit();
+        if positions.is_empty() {
+            return Err("The URL list has no URLs to crawl".to_string());
+        }
+        println!("Crawling a list of {} URLs", positions.len());
+        state.list_positions = Some(positions);
+    }
+
+    // Seed the frontier with the URLs listed in the sitemaps
+    let mut sitemap_entries = Vec::new();
+    let mut sitemap_report = None;
+    let list_crawl = state.lock().await.list_positions.is_some();
+    if settings.sitemap_discovery && !list_crawl {
+        let robots_rules = robots.rules_for(&base_url).await;
+        let report = sitemap::crawl_sitemaps(&client, &base_url, &robots_rules.sitemaps).await;
+        println!(
+            "Found {} URLs in {} sitemaps",
+            report.entries.len(),
+            report.sitemaps.len()
+        );
+
+        let mut state = state.lock().await;
+        state.frontier.begin();
+        for entry in &report.entries {
+            let Ok(url) = Url::parse(&entry.loc) else {
+                continue;
+            };
+            let url = state.normalizer.normalize(&url);
+            if should_skip_url(url.as_str()) || !state.scope.allows(&url) {
+                continue;
+            }
+            // Sitemap URLs count as linked from the start page
+            if !state.scope.within_depth(1) || !state.scope.has_room(state.total_urls) {
+                if pagination_selector::page_number(&url).is_some() {
+                    state.limited_pages.insert(url.to_string());
+                }
+                continue;
+            }
+            if state.frontier.push(url.clone(), Some(1)) {
+                state.discovered.push((url.to_string(), Some(1)));
+                state.total_urls += 1;
+            }
+        }
+        state.frontier.commit();
+        drop(state);
+
+        sitemap_entries = report.entries.clone();
+        sitemap_report = Some(report);
+    }
+
+    let mut icon_report = None;
+    if settings.icon_checks {
+        let report = site_icons::check_site_icons(&base_url).await;
+        if let Err(err) = events.emit("icon_report", &report) {
+            eprintln!("Failed to emit icon report: {}", err);
+        }
+        icon_report = Some(report);
+    }
+
+    // Every crawl gets an id in the results store, pages are written there batch by batch
+    let results_store = match ResultsStore::open_in(&roots.db_dir).await {
+        Ok(store) => match store
+            .create_crawl(&url_checked, user_agent_profile.as_str(), &user_agent)
+            .await
+        {
+            Ok(crawl_id) => Some((store, crawl_id)),
+            Err(e) => {
+                eprintln!("Failed to register crawl in results store: {}", e);
+                None
+            }
+        },
+        Err(e) => {
+            eprintln!("Results store unavailable: {}", e);
+            None
+        }
+    };
+    // Screenshots are filed under the crawl id, so capturing starts once it is known.
+    // They are optional, the crawl goes ahead without them
+    if let Err(e) = screenshots::configure(
+        &settings,
+        &user_agent,
+        results_store
+            .as_ref()
+            .map(|(_, crawl_id)| (*crawl_id, roots.data_dir.as_path())),
+    ) {
+        eprintln!("Screenshots disabled for this crawl: {}", e);
+    }
+    let report_store = results_store
+        .as_ref()
+        .map(|(store, crawl_id)| (store, *crawl_id));
+    if let Some(report) = &preflight_report {
+        crawl_audits::save_report(report_store, report).await;
+    }
+    if let Some(report) = &sitemap_report {
+        crawl_audits::save_report(report_store, report).await;
+    }
+    if let Some(report) = &icon_report {
+        crawl_audits::save_report(report_store, report).await;
+    }
+    if let Some((store, crawl_id)) = &results_store {
+        if let Err(err) = events.emit("crawl_started", *crawl_id) {
+            eprintln!("Failed to emit crawl start event: {}", err);
+        }
+
+        // Pages restored from a resumed crawl belong to the new crawl as well
+        let state = state.lock().await;
+        if let Err(e) = store.insert_pages(*crawl_id, &state.results).await {
+            eprintln!("Failed to store restored results: {}", e);
+        }
+        if let Some(db) = state.db.as_ref().filter(|_| !state.results.is_empty()) {
+            let db_results = state.results.iter().map(to_database_results).collect();
+            if let Err(e) = database::insert_bulk_crawl_data(db.get_pool(), db_results).await {
+                eprintln!("Failed to insert restored results: {}", e);
+            }
+        }
+        drop(state);
+
+        // Kept per crawl so later passes such as orphan detection can use them
+        if let Err(e) = store
+            .insert_sitemap_entries(*crawl_id, &sitemap_entries)
+            .await
+        {
+            eprintln!("Failed to store sitemap entries: {}", e);
+        }
     }
 
     // Using the settings here to replace the hardcoded concurrent requests
     // let semaphore = Arc::new(Semaphore::new(CONCURRENT_REQUESTS));
     let semaphore = Arc::new(Semaphore::new(settings.concurrent_requests));
+    let db_pool = state.lock().await.db.as_ref().map(Database::get_pool);
     let crawl_start_time = Instant::now();
-    let mut batch_counter = 0;
+    let progress_reporter = crawl_progress::spawn_reporter(events.clone(), state.clone());
+    let mut timed_out = false;
 
     loop {
-        let current_batch: Vec<Url> = {
+        // The frontier is persisted after every batch, pausing only needs to wait
+        if crawl_control::is_paused() {
+            println!(
+                "Crawl paused with {} URLs left",
+                state.lock().await.frontier.len()
+            );
+        }
+        if !crawl_control::wait_while_paused().await {
+            break;
+        }
+
+        let (current_batch, results_before): (Vec<Url>, usize) = {
             let mut state = state.lock().await;
-            if state.queue.is_empty() {
+            if state.frontier.is_empty() {
                 if state.crawled_urls + state.failed_urls.len() >= state.total_urls {
                     break;
                 }
                 break;
             }
-            let batch_size = std::cmp::min(settings.batch_size, state.queue.len());
-            state.queue.drain(..batch_size).collect()
+            (
+                state.frontier.pop_batch(settings.batch_size),
+                state.results.len(),
+            )
         };
 
-        let mut handles = Vec::with_capacity(current_batch.len());
+        let mut handles = FuturesUnordered::new();
         for url in current_batch.clone() {
-            let client = client.clone();
+            let page_clients = page_clients.clone();
             let base_url = base_url.clone();
             let state = state.clone();
-            let app_handle = app_handle.clone();
+            let events = events.clone();
             let semaphore = semaphore.clone();
 
             let settings_clone = settings.clone();
+            let robots = robots.clone();
+            let user_agent = user_agent.clone();
+            let rate_limiter = rate_limiter.clone();
 
             let handle = tokio::spawn(async move {
                 let _permit = semaphore.acquire().await.unwrap();
+
+                // Hold in-flight URLs while paused, hand them back to the queue on cancel
+                if !crawl_control::wait_while_paused().await {
+                    return (url, Err("Crawl cancelled".to_string()));
+                }
+
+                // Skip URLs disallowed by robots.txt unless the user overrides it
+                let robots_rules = robots.rules_for(&url).await;
+                let robots_allowed = robots_rules.is_allowed(&user_agent, &url);
+                if settings_clone.respect_robots {
+                    if let Some(delay) = robots_rules
+                        .crawl_delay(&user_agent)
+                        .filter(|delay| delay.is_finite() && *delay > 0.0)
+                    {
+                        rate_limiter
+                            .set_min_interval(&url, Duration::from_secs_f64(delay))
+                            .await;
+                    }
+                }
+                if settings_clone.respect_robots && !robots_allowed {
+                    let mut state = state.lock().await;
+                    let result = DomainCrawlResults {
+                        url: url.to_string(),
+                        blocked_by_robots: true,
+                        crawl_depth: state.frontier.depth(url.as_str()),
+                        indexability: Indexability::blocked(),
+                        ..Default::default()
+                    };
+
+                    state.results.push(result.clone());
+                    state.crawled_urls += 1;
+                    state.frontier.mark_visited(url.as_str());
+                    return (url, Ok(result));
+                }
+
                 let jitter = rand::thread_rng().gen_range(500..2000);
                 sleep(Duration::from_millis(jitter)).await;
 
                 let mut retries = 0;
                 let result: Result<DomainCrawlResults, String> = loop {
-                    match process_url(
+                    // Each attempt may go out through a different proxy
+                    let (proxy, client) = page_clients.pick();
+                    let outcome = process_url(
                         url.clone(),
                         &client,
                         &base_url,
                         state.clone(),
-                        &app_handle,
+                        &events,
                         &settings_clone,
+                        &rate_limiter,
                     )
-                    .await
-                    {
-                        Ok(result) => break Ok(result),
+                    .await;
+                    page_clients.report(proxy, outcome.is_ok());
+
+                    match outcome {
+                        Ok(mut result) => {
+                            // Fetched despite robots.txt, so it still cannot be indexed
+                            if !robots_allowed {
+                                result.blocked_by_robots = true;
+                                result.indexability = Indexability::blocked();
+                            }
+                            break Ok(result);
+                        }
                         Err(e) => {
                             eprintln!("Error processing URL: {}", e);
                             if retries >= settings_clone.max_retries {
@@ -498,109 +1250,319 @@ pub async fn crawl_domain(
             handles.push(handle);
         }
 
-        for handle in handles {
-            match handle.await {
+        // Pages are shown as soon as they finish, and written in chunks of db_batch_size
+        let mut pending = Vec::new();
+        while let Some(joined) = handles.next().await {
+            match joined {
                 Ok((url, Ok(result))) => {
-                    let mut state = state.lock().await;
-                    if !state.visited.contains(&url.to_string()) {
-                        state.results.push(result.clone());
-
-                        // Handle database insertion
-                        if let Some(db) = &state.db {
-                            batch_counter += 1;
-
-                            let batch_start = state.results.len().saturating_sub(batch_counter);
-                            let recent_results = &state.results[batch_start..];
-                            let db_results = recent_results
-                                .iter()
-                                .map(to_database_results)
-                                .collect::<Vec<_>>();
-
-                            // Insert every DB_BATCH_SIZE or if we have any results
-                            if batch_counter >= settings.db_batch_size || !recent_results.is_empty()
-                            {
-                                match database::insert_bulk_crawl_data(db.get_pool(), db_results)
-                                    .await
-                                {
-                                    Ok(()) => println!(
-                                        "Successfully inserted batch of {} results",
-                                        recent_results.len()
-                                    ),
-                                    Err(e) => eprintln!("Failed to batch insert results: {}", e),
-                                }
-                                batch_counter = 0;
+                    {
+                        let mut state = state.lock().await;
+                        // Pages that failed every retry never went through process_url's bookkeeping
+                        if !state.frontier.is_visited(url.as_str()) {
+                            state.results.push(result.clone());
+                        }
+                        // Redirected list URLs come back under their target
+                        if let Some(positions) = state.list_positions.as_mut() {
+                            if let Some(&position) = positions.get(url.as_str()) {
+                                positions.entry(result.url.clone()).or_insert(position);
+                            }
+                        }
+                        // Internal links are judged by what the crawl got, pages blocked by
+                        // robots.txt were never asked for
+                        if settings.link_checker && !result.blocked_by_robots {
+                            state.link_checker.record_crawled(
+                                url.as_str(),
+                                result.status_code,
+                                result.redirection.clone(),
+                            );
+                            if result.url != url.as_str() {
+                                state.link_checker.record_crawled(
+                                    &result.url,
+                                    result.status_code,
+                                    None,
+                                );
                             }
                         }
                     }
+
+                    let result_data = CrawlResultData {
+                        result: result.clone(),
+                    };
+                    if let Err(err) = events.emit("crawl_result", result_data) {
+                        eprintln!("Failed to emit crawl result: {}", err);
+                    }
+                    pending.push(result);
+                    if pending.len() >= settings.db_batch_size.max(1) {
+                        store_results(db_pool.as_ref(), results_store.as_ref(), &mut pending).await;
+                    }
                 }
                 Ok((url, Err(e))) => {
                     let mut state = state.lock().await;
-                    if !state.failed_urls.contains(url.as_str())
-                        && !state.visited.contains(url.as_str())
-                    {
-                        state.queue.push_back(url.clone());
+                    if !state.failed_urls.contains(url.as_str()) {
+                        state.frontier.requeue(url.clone());
                     }
                 }
                 Err(e) => eprintln!("Task failed: {:?}", e),
             }
         }
+        store_results(db_pool.as_ref(), results_store.as_ref(), &mut pending).await;
+
+        let batch = {
+            let mut state = state.lock().await;
+            BatchProgress {
+                discovered: std::mem::take(&mut state.discovered),
+                visited: current_batch
+                    .iter()
+                    .filter(|url| state.frontier.is_visited(url.as_str()))
+                    .map(|url| url.to_string())
+                    .collect(),
+                failed: current_batch
+                    .iter()
+                    .filter(|url| state.failed_urls.contains(url.as_str()))
+                    .map(|url| url.to_string())
+                    .collect(),
+                results: state.results[results_before.min(state.results.len())..].to_vec(),
+                total_urls: state.total_urls,
+                crawled_urls: state.crawled_urls,
+            }
+        };
+        if let Some(store) = &state_store {
+            if let Err(e) = store.record_batch(&url_checked, batch).await {
+                eprintln!("Failed to persist crawl progress: {}", e);
+            }
+        }
+        // Streamed pages live in the results store, only keep them around without one
+        if results_store.is_some() {
+            state.lock().await.results.clear();
+        }
 
         if crawl_start_time.elapsed() > Duration::from_secs(settings.crawl_timeout) {
-            if let Err(err) = app_handle.emit("crawl_interrupted", ()) {
+            if let Err(err) = events.emit("crawl_interrupted", ()) {
                 eprintln!("Failed to emit crawl interruption event: {}", err);
             }
+            timed_out = true;
             break;
         }
     }
 
-    // Insert any remaining results
-    let final_state = state.lock().await;
-    if let Some(db) = &final_state.db {
-        if !final_state.results.is_empty() {
-            let db_results = final_state
-                .results
-                .iter()
-                .map(to_database_results)
-                .collect::<Vec<_>>();
-
-            match database::insert_bulk_crawl_data(db.get_pool(), db_results).await {
-                Ok(()) => println!(
-                    "Successfully inserted final batch of {} results",
-                    final_state.results.len()
-                ),
-                Err(e) => eprintln!("Failed to insert final batch: {}", e),
+    drop(progress_reporter);
+
+    // Cancelled and timed out crawls keep their saved state so they can be resumed later
+    let cancelled = crawl_control::is_cancelled();
+    if cancelled {
+        if let Err(err) = events.emit("crawl_cancelled", ()) {
+            eprintln!("Failed to emit crawl cancellation event: {}", err);
+        }
+    } else if let Some(store) = state_store.as_ref().filter(|_| !timed_out) {
+        if let Err(e) = store.discard(&url_checked).await {
+            eprintln!("Failed to clear saved crawl state: {}", e);
+        }
+    }
+
+    // Every analyzer below files its findings with the issue engine
+    let mut audits = CrawlAudits::new(&settings, &sitemap_entries);
+
+    // Verify every unique link found during the crawl
+    if settings.link_checker && !cancelled {
+        let checker = std::mem::take(&mut state.lock().await.link_checker);
+        println!("Checking {} unique links for breakage", checker.len());
+        let report = checker.verify(&settings).await;
+
+        if let Some((store, crawl_id)) = &results_store {
+            if let Err(e) = store.insert_broken_links(*crawl_id, &report.broken).await {
+                eprintln!("Failed to store broken links: {}", e);
             }
         }
+
+        if let Err(err) = events.emit("broken_links", &report) {
+            eprintln!("Failed to emit broken links report: {}", err);
+        }
+        link_checker::register_issues(&report, audits.issues());
+        crawl_audits::save_report(report_store, &report).await;
     }
 
-    // Remove duplicates from the results
-    let mut unique_results = Vec::new();
-    let mut seen_urls = HashSet::new();
-    for result in final_state.results.clone() {
-        if seen_urls.insert(result.url.clone()) {
-            unique_results.push(result);
+    let final_state = state.lock().await;
+
+    // Crawl-level reports see every page once, streamed back from the results store so the
+    // crawl never holds all of its pages at the same time
+    let streamed = match &results_store {
+        Some((store, crawl_id)) => {
+            let audits = Arc::new(std::sync::Mutex::new(audits));
+            let sink = audits.clone();
+            let pages = store
+                .for_each_page(*crawl_id, move |page| {
+                    sink.lock().map_err(|e| e.to_string())?.add(&page);
+                    Ok(())
+                })
+                .await;
+            let audits = Arc::try_unwrap(audits)
+                .map_err(|_| "Crawl audits are still shared".to_string())
+                .and_then(|audits| audits.into_inner().map_err(|e| e.to_string()));
+            match (pages, audits) {
+                (Ok(pages), Ok(audits)) => Ok((pages, audits)),
+                (Err(e), _) | (_, Err(e)) => Err(e),
+            }
+        }
+        None => Err("No results store".to_string()),
+    };
+    let (pages, audits) = match streamed {
+        Ok(streamed) => streamed,
+        Err(e) => {
+            if results_store.is_some() {
+                eprintln!("Failed to read back stored results: {}", e);
+            }
+            // Without a store the pages stayed in memory, possibly more than once
+            let mut audits = CrawlAudits::new(&settings, &sitemap_entries);
+            let mut seen_urls = HashSet::new();
+            for result in &final_state.results {
+                if seen_urls.insert(result.url.as_str()) {
+                    audits.add(result);
+                }
+            }
+            (seen_urls.len(), audits)
+        }
+    };
+
+    // Checked without following redirects, so alternates that redirect are reported
+    let (_, hreflang_client) = page_clients.pick();
+    let facts = CrawlFacts {
+        normalized_links: final_state.normalized_links,
+        limited_pages: &final_state.limited_pages,
+        hreflang_client: &hreflang_client,
+        sitemap: sitemap_report.as_ref(),
+        store: report_store,
+    };
+    let issues = audits.finish(&events, facts).await;
+
+    let issues = issues.into_issues();
+    if let Some((store, crawl_id)) = &results_store {
+        if let Err(e) = store.replace_issues(*crawl_id, &issues).await {
+            eprintln!("Failed to store crawl issues: {}", e);
         }
     }
+    if let Err(err) = events.emit("issue_report", &issues) {
+        eprintln!("Failed to emit issue report: {}", err);
+    }
 
-    if let Err(err) = app_handle.emit("crawl_complete", ()) {
+    if let Err(err) = events.emit("crawl_complete", ()) {
         eprintln!("Failed to emit crawl completion event: {}", err);
     }
 
-    println!(
-        "Crawl completed with {} unique results",
-        unique_results.len()
-    );
+    println!("Crawl completed with {} unique results", pages);
 
     // CREATE THE DATABSES FOR THE DIFF TABLES
-    match database::create_diff_tables() {
+    match database::create_diff_tables(&roots.db_dir) {
         Ok(()) => println!("Successfully created diff tables"),
         Err(e) => eprintln!("Failed to create diff tables: {}", e),
     };
 
-    match database::clone_batched_crawl_into_persistent_db().await {
+    match database::clone_batched_crawl_into_persistent_db(&roots.db_dir).await {
         Ok(()) => println!("Successfully cloned batched crawl into persistent db"),
         Err(e) => eprintln!("Failed to clone batched crawl into persistent db: {}", e),
     }
 
-    Ok(unique_results)
+    if let Some((store, crawl_id)) = &results_store {
+        let status = if cancelled {
+            "cancelled"
+        } else if timed_out {
+            "interrupted"
+        } else {
+            "completed"
+        };
+        if let Err(e) = store.finish_crawl(*crawl_id, status).await {
+            eprintln!("Failed to finish crawl in results store: {}", e);
+        }
+    }
+
+    Ok(CrawlOutcome {
+        crawl_id: results_store.map(|(_, crawl_id)| crawl_id),
+        pages,
+    })
+}
+
+/// Everything the crawler extracts from one page, without crawling the rest of the site.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageAudit {
+    pub page: DomainCrawlResults,
+    /// The links of the page that are broken, when the link checker is on
+    pub broken_links: Option<link_checker::BrokenLinksReport>,
+    pub duration_ms: u64,
+}
+
+/// Runs the full extraction pipeline on a single URL.
+///
+/// The extractors share their setup with the crawler, so this refuses to run during a crawl.
+pub async fn audit_page(
+    url: &str,
+    events: &CrawlEvents,
+    settings: &Settings,
+) -> Result<PageAudit, String> {
+    let _crawl = crawl_control::try_start()
+        .map_err(|_| "A crawl is running, audit the page once it is done".to_string())?;
+    let started = Instant::now();
+
+    // A cached answer would hide what the page serves right now
+    let mut settings = settings.clone();
+    settings.conditional_requests = false;
+
+    let url = Url::parse(&url_check(url.trim())).map_err(|e| format!("Invalid URL: {}", e))?;
+    let (_, user_agent) = user_agents::resolve(&settings)?;
+    user_agents::set_current(&user_agent);
+    proxies::configure(&settings)?;
+    let (client, page_clients) = crawl_clients(&settings, &user_agent)?;
+    // One page needs no politeness delay towards its host
+    let rate_limiter = Arc::new(HostRateLimiter::new(
+        Duration::ZERO,
+        settings.per_host_burst,
+    ));
+    configure_crawl(
+        &settings,
+        &url,
+        &client,
+        &user_agent,
+        &ProjectRoots::active()?,
+        &rate_limiter,
+    )
+    .await?;
+    screenshots::configure(&settings, &user_agent, None)?;
+
+    let scope = CrawlScope::from_settings(&settings, &url)?;
+    let mut state = CrawlerState::new(
+        None,
+        Frontier::in_memory(),
+        scope,
+        UrlNormalizer::from_settings(&settings),
+    );
+    state.frontier.push(url.clone(), Some(0));
+    state.list_positions = Some(HashMap::new());
+    state.report_progress = false;
+    let state = Arc::new(Mutex::new(state));
+
+    let (proxy, page_client) = page_clients.pick();
+    let outcome = process_url(
+        url.clone(),
+        &page_client,
+        &url,
+        state.clone(),
+        events,
+        &settings,
+        &rate_limiter,
+    )
+    .await;
+    page_clients.report(proxy, outcome.is_ok());
+    let page = outcome?;
+
+    let broken_links = if settings.link_checker {
+        let mut checker = std::mem::take(&mut state.lock().await.link_checker);
+        checker.record_crawled(url.as_str(), page.status_code, page.redirection.clone());
+        Some(checker.verify(&settings).await)
+    } else {
+        None
+    };
+
+    Ok(PageAudit {
+        page,
+        broken_links,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
 }
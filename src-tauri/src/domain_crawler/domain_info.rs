@@ -0,0 +1,290 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+use super::results_store::ResultsStore;
+
+// Bootstrap service that redirects to the RDAP server of each TLD
+const RDAP_URL: &str = "https://rdap.org/domain";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const EXPIRY_WARNING_DAYS: i64 = 30;
+const CONCURRENT_LOOKUPS: usize = 4;
+const DEFAULT_MAX_EXTERNAL: usize = 50;
+
+/// Registration data of a domain, from its registry's RDAP service.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DomainInfo {
+    pub domain: String,
+    pub registrar: Option<String>,
+    pub registered: Option<String>,
+    pub expires: Option<String>,
+    pub updated: Option<String>,
+    pub nameservers: Vec<String>,
+    pub status: Vec<String>,
+    pub days_until_expiry: Option<i64>,
+    /// Set when the domain expires within 30 days
+    pub expiry_warning: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawlDomainInfo {
+    pub crawl_id: i64,
+    pub domain: DomainInfo,
+    /// Domains the crawled pages link to
+    pub external: Vec<DomainInfo>,
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+// The host of a URL or the domain itself, lowercased and without "www."
+fn host_of(domain: &str) -> Option<String> {
+    let domain = domain.trim();
+    let url = if domain.contains("://") {
+        domain.to_string()
+    } else {
+        format!("https://{}", domain)
+    };
+    let host = Url::parse(&url).ok()?.host_str()?.to_lowercase();
+    // IP addresses are not registered, there is no domain to look up
+    if host.parse::<std::net::IpAddr>().is_ok() || host.starts_with('[') {
+        return None;
+    }
+    Some(host.trim_start_matches("www.").to_string())
+}
+
+// Candidates for the registered domain, most likely first. Without the public suffix
+// list a short label under a country TLD, as in co.uk or com.au, is taken for a registry.
+fn registrable_candidates(host: &str) -> Vec<String> {
+    let labels: Vec<&str> = host.split('.').collect();
+    let second_level_registry = labels.len() >= 3
+        && labels[labels.len() - 1].len() == 2
+        && labels[labels.len() - 2].len() <= 3;
+    let counts = if second_level_registry {
+        [3, 2]
+    } else {
+        [2, 3]
+    };
+    counts
+        .into_iter()
+        .filter(|count| labels.len() >= *count)
+        .map(|count| labels[labels.len() - count..].join("."))
+        .collect()
+}
+
+fn event_date(body: &Value, action: &str) -> Option<DateTime<Utc>> {
+    body.get("events")?
+        .as_array()?
+        .iter()
+        .find(|event| event.get("eventAction").and_then(Value::as_str) == Some(action))?
+        .get("eventDate")?
+        .as_str()
+        .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+        .map(|date| date.with_timezone(&Utc))
+}
+
+// The "fn" property of the vCard of the entity with the registrar role
+fn registrar(body: &Value) -> Option<String> {
+    let entity = body.get("entities")?.as_array()?.iter().find(|entity| {
+        entity
+            .get("roles")
+            .and_then(Value::as_array)
+            .is_some_and(|roles| roles.iter().any(|role| role == "registrar"))
+    })?;
+    entity
+        .get("vcardArray")?
+        .get(1)?
+        .as_array()?
+        .iter()
+        .find(|property| property.get(0).and_then(Value::as_str) == Some("fn"))?
+        .get(3)?
+        .as_str()
+        .map(str::to_string)
+}
+
+fn format_date(date: Option<DateTime<Utc>>) -> Option<String> {
+    date.map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+async fn query_rdap(client: &reqwest::Client, domain: &str) -> Result<Option<Value>, String> {
+    let response = client
+        .get(format!("{}/{}", RDAP_URL, domain))
+        .header("Accept", "application/rdap+json")
+        .send()
+        .await
+        .map_err(|e| format!("RDAP request failed: {}", e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(format!("RDAP answered {}", response.status()));
+    }
+    response
+        .json()
+        .await
+        .map(Some)
+        .map_err(|e| format!("Failed to parse RDAP response: {}", e))
+}
+
+async fn lookup(client: &reqwest::Client, host: &str) -> Result<DomainInfo, String> {
+    let mut found = None;
+    for candidate in registrable_candidates(host) {
+        if let Some(body) = query_rdap(client, &candidate).await? {
+            found = Some((candidate, body));
+            break;
+        }
+    }
+    let (domain, body) = found.ok_or_else(|| format!("No registration data found for {}", host))?;
+
+    let expires = event_date(&body, "expiration");
+    let days_until_expiry = expires.map(|date| (date - Utc::now()).num_days());
+    let expiry_warning = days_until_expiry
+        .filter(|days| *days <= EXPIRY_WARNING_DAYS)
+        .map(|days| {
+            if days < 0 {
+                format!("{} expired {} days ago", domain, -days)
+            } else {
+                format!("{} expires in {} days", domain, days)
+            }
+        });
+    let nameservers = body
+        .get("nameservers")
+        .and_then(Value::as_array)
+        .map(|servers| {
+            servers
+                .iter()
+                .filter_map(|server| server.get("ldhName")?.as_str())
+                .map(|name| name.to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default();
+    let status = body
+        .get("status")
+        .and_then(Value::as_array)
+        .map(|status| {
+            status
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(DomainInfo {
+        registrar: registrar(&body),
+        registered: format_date(event_date(&body, "registration")),
+        expires: format_date(expires),
+        updated: format_date(event_date(&body, "last changed")),
+        nameservers,
+        status,
+        days_until_expiry,
+        expiry_warning,
+        error: None,
+        domain,
+    })
+}
+
+/// Looks up the registration of a domain or of the host of a URL.
+///
+/// Failures are reported on the result so a batch of lookups carries on.
+pub async fn domain_info(client: &reqwest::Client, domain: &str) -> DomainInfo {
+    let Some(host) = host_of(domain) else {
+        return DomainInfo {
+            domain: domain.to_string(),
+            error: Some(format!("{} is not a domain name", domain)),
+            ..Default::default()
+        };
+    };
+    lookup(client, &host).await.unwrap_or_else(|e| DomainInfo {
+        domain: host,
+        error: Some(e),
+        ..Default::default()
+    })
+}
+
+// GET THE WHOIS REGISTRATION DATA OF A DOMAIN
+#[tauri::command]
+pub async fn get_domain_info(domain: String) -> Result<DomainInfo, String> {
+    Ok(domain_info(&client()?, &domain).await)
+}
+
+// GET THE REGISTRATION DATA OF A CRAWLED DOMAIN AND OPTIONALLY OF THE DOMAINS IT LINKS TO
+#[tauri::command]
+pub async fn get_crawl_domain_info(
+    crawl_id: i64,
+    include_external: Option<bool>,
+    max_external: Option<usize>,
+) -> Result<CrawlDomainInfo, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let crawl = store
+        .crawl(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Crawl {} not found", crawl_id))?;
+    let client = client()?;
+    let domain = domain_info(&client, &crawl.domain).await;
+
+    let mut external = Vec::new();
+    if include_external.unwrap_or(false) {
+        let collected = Arc::new(Mutex::new(BTreeSet::new()));
+        let sink = collected.clone();
+        store
+            .for_each_page(crawl_id, move |page| {
+                let Some(anchors) = page.anchor_links else {
+                    return Ok(());
+                };
+                let mut hosts = sink.lock().map_err(|e| e.to_string())?;
+                hosts.extend(
+                    anchors
+                        .external
+                        .links
+                        .iter()
+                        .filter_map(|link| host_of(link)),
+                );
+                Ok(())
+            })
+            .await?;
+        let hosts = std::mem::take(&mut *collected.lock().map_err(|e| e.to_string())?);
+
+        // Subdomains of one registration are looked up once
+        let mut domains = BTreeSet::new();
+        for host in hosts {
+            if let Some(candidate) = registrable_candidates(&host).into_iter().next() {
+                if candidate != domain.domain {
+                    domains.insert(candidate);
+                }
+            }
+        }
+
+        let client = &client;
+        external = stream::iter(
+            domains
+                .into_iter()
+                .take(max_external.unwrap_or(DEFAULT_MAX_EXTERNAL)),
+        )
+        .map(|domain| async move { domain_info(client, &domain).await })
+        .buffered(CONCURRENT_LOOKUPS)
+        .collect()
+        .await;
+    }
+
+    if let Some(warning) = &domain.expiry_warning {
+        println!("Domain warning for crawl {}: {}", crawl_id, warning);
+    }
+    Ok(CrawlDomainInfo {
+        crawl_id,
+        domain,
+        external,
+    })
+}
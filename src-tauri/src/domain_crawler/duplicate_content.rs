@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicatePage {
+    pub url: String,
+    /// Share of matching fingerprint bits with the representative, 1.0 is identical
+    pub similarity: f64,
+}
+
+/// Pages whose main text is near-identical.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateCluster {
+    pub representative: String,
+    pub pages: Vec<DuplicatePage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DuplicateContentReport {
+    pub threshold: f64,
+    pub pages_compared: usize,
+    pub clusters: Vec<DuplicateCluster>,
+}
+
+pub fn register_issues(report: &DuplicateContentReport, issues: &mut IssueRegistry) {
+    for cluster in &report.clusters {
+        issues.flag(IssueKind::DuplicateContent, &cluster.representative);
+        for page in &cluster.pages {
+            issues.flag(IssueKind::DuplicateContent, &page.url);
+        }
+    }
+}
+
+impl StoredReport for DuplicateContentReport {
+    const KIND: &'static str = "duplicate_content";
+}
+
+fn similarity(a: u64, b: u64) -> f64 {
+    1.0 - (a ^ b).count_ones() as f64 / 64.0
+}
+
+// Splits the fingerprints into more bands than they may differ in bits, so every pair above
+// the threshold has one identical band and only pages that share a band are compared
+fn band_buckets(pages: &[(&str, u64)], threshold: f64) -> HashMap<(usize, u64), Vec<usize>> {
+    let max_distance = (64.0 * (1.0 - threshold) + 1e-9).floor().clamp(0.0, 63.0) as usize;
+    let bands = max_distance + 1;
+
+    let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+    for (i, (_, fingerprint)) in pages.iter().enumerate() {
+        for band in 0..bands {
+            let (start, end) = (band * 64 / bands, (band + 1) * 64 / bands);
+            let mask = u64::MAX >> (64 - (end - start));
+            let key = (fingerprint >> start) & mask;
+            buckets.entry((band, key)).or_default().push(i);
+        }
+    }
+    buckets
+}
+
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+/// Collects the content fingerprints of the crawled pages one page at a time.
+pub struct DuplicateCollector {
+    threshold: f64,
+    fingerprints: Vec<(String, u64)>,
+}
+
+impl DuplicateCollector {
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            fingerprints: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        if result.status_code == 200 && result.content.simhash != 0 {
+            self.fingerprints
+                .push((result.url.clone(), result.content.simhash));
+        }
+    }
+
+    pub fn finish(self) -> DuplicateContentReport {
+        let pages: Vec<(&str, u64)> = self
+            .fingerprints
+            .iter()
+            .map(|(url, fingerprint)| (url.as_str(), *fingerprint))
+            .collect();
+        detect_duplicates(&pages, self.threshold)
+    }
+}
+
+/// Clusters pages whose SimHash similarity is at least `threshold`.
+///
+/// Pairs above the threshold are merged transitively, and each cluster is
+/// represented by its shortest URL. Fingerprints are bucketed by band so the
+/// comparisons stay close to linear on large crawls.
+fn detect_duplicates(pages: &[(&str, u64)], threshold: f64) -> DuplicateContentReport {
+    let mut parents: Vec<usize> = (0..pages.len()).collect();
+    for bucket in band_buckets(pages, threshold).values() {
+        for (n, &i) in bucket.iter().enumerate() {
+            for &j in &bucket[n + 1..] {
+                let (a, b) = (find(&mut parents, i), find(&mut parents, j));
+                if a != b && similarity(pages[i].1, pages[j].1) >= threshold {
+                    parents[b] = a;
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..pages.len() {
+        let root = find(&mut parents, i);
+        groups.entry(root).or_default().push(i);
+    }
+
+    let mut clusters: Vec<DuplicateCluster> = groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let representative = *members
+                .iter()
+                .min_by_key(|&&i| (pages[i].0.len(), pages[i].0))
+                .unwrap();
+            let (url, fingerprint) = pages[representative];
+            DuplicateCluster {
+                representative: url.to_string(),
+                pages: members
+                    .iter()
+                    .filter(|&&i| i != representative)
+                    .map(|&i| DuplicatePage {
+                        url: pages[i].0.to_string(),
+                        similarity: similarity(fingerprint, pages[i].1),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.pages
+            .len()
+            .cmp(&a.pages.len())
+            .then(a.representative.cmp(&b.representative))
+    });
+
+    DuplicateContentReport {
+        threshold,
+        pages_compared: pages.len(),
+        clusters,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain_crawler::helpers::content_analyzer::ContentAnalysis;
+
+    fn detect(results: &[DomainCrawlResults], threshold: f64) -> DuplicateContentReport {
+        let mut collector = DuplicateCollector::new(threshold);
+        for result in results {
+            collector.add(result);
+        }
+        collector.finish()
+    }
+
+    fn page(url: &str, simhash: u64) -> DomainCrawlResults {
+        DomainCrawlResults {
+            content: ContentAnalysis {
+                simhash,
+                ..Default::default()
+            },
+            ..DomainCrawlResults::crawled(url)
+        }
+    }
+
+    #[test]
+    fn pages_within_the_threshold_are_clustered() {
+        let base = 0xDEAD_BEEF_0123_4567u64;
+        // Flipped bits spread over the hash, so no band but one survives unchanged
+        let near = base ^ 0x8000_0000_0000_0001 ^ (1 << 21) ^ (1 << 42);
+        let far = !base;
+        let results = [
+            page("https://example.com/a", base),
+            page("https://example.com/a-copy", near),
+            page("https://example.com/other", far),
+        ];
+
+        let report = detect(&results, 0.9);
+        assert_eq!(report.pages_compared, 3);
+        assert_eq!(report.clusters.len(), 1);
+        let cluster = &report.clusters[0];
+        assert_eq!(cluster.representative, "https://example.com/a");
+        assert_eq!(cluster.pages.len(), 1);
+        assert_eq!(cluster.pages[0].url, "https://example.com/a-copy");
+        assert_eq!(cluster.pages[0].similarity, 1.0 - 4.0 / 64.0);
+    }
+
+    #[test]
+    fn pages_just_past_the_threshold_are_kept_apart() {
+        let base = 0x0F0F_F0F0_1234_ABCDu64;
+        // Seven differing bits, one more than 0.9 allows
+        let results = [
+            page("https://example.com/a", base),
+            page("https://example.com/b", base ^ 0x7F),
+        ];
+        assert!(detect(&results, 0.9).clusters.is_empty());
+    }
+}
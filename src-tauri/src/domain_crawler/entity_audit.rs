@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use futures::stream::{self, StreamExt};
+use genai::chat::{ChatMessage, ChatRequest};
+use genai::client::Client;
+use once_cell::sync::Lazy;
+use scraper::Html;
+use serde::{Deserialize, Serialize};
+
+use super::helpers::content_analyzer::visible_text;
+use super::results_store::StoredReport;
+use crate::genai::get_ai_model;
+use crate::settings::settings::Settings;
+
+// Main text of the pages queued for extraction, None when extraction is off
+static PENDING: Lazy<std::sync::Mutex<Option<Pending>>> = Lazy::new(|| std::sync::Mutex::new(None));
+
+// Local models are slow, longer texts are cut to keep each call in the context window
+const MAX_TEXT_CHARS: usize = 6000;
+// Pages with less text than this have nothing to extract
+const MIN_TEXT_CHARS: usize = 200;
+const CONCURRENT_REQUESTS: usize = 2;
+
+const PROMPT: &str = "Extract the named entities and the main topics of the following web page text. \
+Reply with JSON only, no backticks and nothing else, in the form \
+{\"entities\": [{\"name\": \"\", \"kind\": \"PERSON|ORGANIZATION|LOCATION|PRODUCT|EVENT|OTHER\"}], \"topics\": [\"\"]}. \
+List at most 15 entities and 5 topics, topics are short noun phrases. The text is:\n";
+
+struct Pending {
+    limit: usize,
+    pages: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Entity {
+    pub name: String,
+    /// PERSON, ORGANIZATION, LOCATION, PRODUCT, EVENT or OTHER
+    #[serde(default)]
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PageEntities {
+    pub url: String,
+    pub entities: Vec<Entity>,
+    pub topics: Vec<String>,
+}
+
+/// A topic or entity with the pages covering it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Coverage {
+    pub name: String,
+    /// The entity kind, None for topics
+    pub kind: Option<String>,
+    pub pages: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EntityReport {
+    pub model: String,
+    pub pages: Vec<PageEntities>,
+    /// Topics across the site, most covered first
+    pub topics: Vec<Coverage>,
+    pub entities: Vec<Coverage>,
+    /// Pages the model failed on or answered without valid JSON
+    pub failed: Vec<String>,
+}
+
+impl StoredReport for EntityReport {
+    const KIND: &'static str = "entities";
+}
+
+/// Turns extraction on or off for a crawl and drops the texts of the previous one.
+pub fn configure(settings: &Settings) {
+    let pending = settings.entity_extraction.then(|| Pending {
+        limit: settings.entity_extraction_pages,
+        pages: Vec::new(),
+    });
+    if let Ok(mut guard) = PENDING.lock() {
+        *guard = pending;
+    }
+}
+
+pub fn is_active() -> bool {
+    PENDING.lock().is_ok_and(|guard| guard.is_some())
+}
+
+/// Keeps the main text of a crawled page for the extraction pass at the end of the crawl.
+pub fn collect(url: &str, body: &str) {
+    let Ok(mut guard) = PENDING.lock() else {
+        return;
+    };
+    let Some(pending) = guard.as_mut() else {
+        return;
+    };
+    if pending.pages.len() >= pending.limit {
+        return;
+    }
+    let text = visible_text(&Html::parse_document(body));
+    if text.chars().count() < MIN_TEXT_CHARS {
+        return;
+    }
+    let text: String = text.chars().take(MAX_TEXT_CHARS).collect();
+    pending.pages.push((url.to_string(), text));
+}
+
+#[derive(Deserialize)]
+struct Extraction {
+    #[serde(default)]
+    entities: Vec<Entity>,
+    #[serde(default)]
+    topics: Vec<String>,
+}
+
+// Models wrap the JSON in prose or code fences despite the prompt
+fn parse_extraction(reply: &str) -> Option<Extraction> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    serde_json::from_str(reply.get(start..=end)?).ok()
+}
+
+async fn extract_page(client: &Client, model: &str, text: &str) -> Result<Extraction, String> {
+    let request = ChatRequest::new(vec![ChatMessage::user(format!("{}{}", PROMPT, text))]);
+    let response = client
+        .exec_chat(model, request, None)
+        .await
+        .map_err(|e| e.to_string())?;
+    let reply = response.content.unwrap_or_default();
+    parse_extraction(&reply).ok_or_else(|| "The model did not answer with JSON".to_string())
+}
+
+fn coverage(map: BTreeMap<(String, Option<String>), BTreeSet<String>>) -> Vec<Coverage> {
+    let mut items: Vec<Coverage> = map
+        .into_iter()
+        .map(|((name, kind), pages)| Coverage {
+            name,
+            kind,
+            pages: pages.into_iter().collect(),
+        })
+        .collect();
+    items.sort_by(|a, b| b.pages.len().cmp(&a.pages.len()).then(a.name.cmp(&b.name)));
+    items
+}
+
+/// Asks the local Ollama model for the entities and topics of every collected page.
+pub async fn extract_entities() -> EntityReport {
+    let pages = PENDING
+        .lock()
+        .ok()
+        .and_then(|mut guard| {
+            guard
+                .as_mut()
+                .map(|pending| std::mem::take(&mut pending.pages))
+        })
+        .unwrap_or_default();
+    let model = get_ai_model();
+    let client = Client::default();
+
+    let results: Vec<(String, Result<Extraction, String>)> = stream::iter(pages)
+        .map(|(url, text)| {
+            let client = &client;
+            let model = model.as_str();
+            async move {
+                let extraction = extract_page(client, model, &text).await;
+                (url, extraction)
+            }
+        })
+        .buffer_unordered(CONCURRENT_REQUESTS)
+        .collect()
+        .await;
+
+    let mut report = EntityReport {
+        model,
+        ..Default::default()
+    };
+    // Topics and entity names are grouped in lowercase
+    let mut topics: BTreeMap<(String, Option<String>), BTreeSet<String>> = BTreeMap::new();
+    let mut entities: BTreeMap<(String, Option<String>), BTreeSet<String>> = BTreeMap::new();
+    for (url, extraction) in results {
+        let extraction = match extraction {
+            Ok(extraction) => extraction,
+            Err(e) => {
+                eprintln!("Entity extraction failed for {}: {}", url, e);
+                report.failed.push(url);
+                continue;
+            }
+        };
+        let mut page = PageEntities {
+            url: url.clone(),
+            ..Default::default()
+        };
+        for topic in extraction.topics {
+            let topic = topic.trim().to_lowercase();
+            if topic.is_empty() || page.topics.contains(&topic) {
+                continue;
+            }
+            topics
+                .entry((topic.clone(), None))
+                .or_default()
+                .insert(url.clone());
+            page.topics.push(topic);
+        }
+        for entity in extraction.entities {
+            let entity = Entity {
+                name: entity.name.trim().to_string(),
+                kind: entity.kind.trim().to_uppercase(),
+            };
+            if entity.name.is_empty() || page.entities.contains(&entity) {
+                continue;
+            }
+            entities
+                .entry((entity.name.to_lowercase(), Some(entity.kind.clone())))
+                .or_default()
+                .insert(url.clone());
+            page.entities.push(entity);
+        }
+        report.pages.push(page);
+    }
+
+    report.pages.sort_by(|a, b| a.url.cmp(&b.url));
+    report.failed.sort();
+    report.topics = coverage(topics);
+    report.entities = coverage(entities);
+    report
+}
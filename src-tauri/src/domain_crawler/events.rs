@@ -0,0 +1,45 @@
+use serde::Serialize;
+use serde_json::Value;
+use tauri::Emitter;
+
+use super::crawl_progress::PROGRESS_EVENT;
+
+/// Where a crawl reports its pages, progress and reports.
+#[derive(Clone)]
+pub enum CrawlEvents {
+    /// Events for the app's windows
+    App(tauri::AppHandle),
+    /// Headless runs have no window, progress is logged to stderr and the rest dropped
+    Console,
+}
+
+impl From<tauri::AppHandle> for CrawlEvents {
+    fn from(app_handle: tauri::AppHandle) -> Self {
+        CrawlEvents::App(app_handle)
+    }
+}
+
+impl CrawlEvents {
+    pub fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) -> Result<(), String> {
+        match self {
+            CrawlEvents::App(app_handle) => {
+                app_handle.emit(event, payload).map_err(|e| e.to_string())
+            }
+            CrawlEvents::Console => {
+                if event == PROGRESS_EVENT {
+                    let progress = serde_json::to_value(payload).map_err(|e| e.to_string())?;
+                    let count =
+                        |field: &str| progress.get(field).and_then(Value::as_u64).unwrap_or(0);
+                    eprintln!(
+                        "Crawled {} of {} URLs, {} queued, {} failed",
+                        count("crawled_urls"),
+                        count("total_urls"),
+                        count("queued_urls"),
+                        count("failed_urls")
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+}
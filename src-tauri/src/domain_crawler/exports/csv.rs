@@ -0,0 +1,143 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use csv::Writer;
+use url::Url;
+
+use crate::domain_crawler::extractors::custom::VALUE_SEPARATOR;
+use crate::domain_crawler::helpers::anchor_links::resolved_internal_links;
+use crate::domain_crawler::models::DomainCrawlResults;
+use crate::domain_crawler::results_store::{to_page_row, ResultsStore};
+
+const HEADERS: [&str; 10] = [
+    "URL",
+    "Status Code",
+    "Title",
+    "Meta Description",
+    "H1",
+    "Word Count",
+    "Images",
+    "Inlinks",
+    "Internal Outlinks",
+    "External Outlinks",
+];
+
+fn image_count(page: &DomainCrawlResults) -> usize {
+    match &page.images {
+        Ok(images) => images.len(),
+        Err(_) => page.image_candidates.len(),
+    }
+}
+
+fn internal_targets(page: &DomainCrawlResults) -> Vec<String> {
+    let (Some(links), Ok(page_url)) = (&page.anchor_links, Url::parse(&page.url)) else {
+        return Vec::new();
+    };
+    resolved_internal_links(links, &page_url)
+        .into_iter()
+        .map(|url| url.to_string())
+        .collect()
+}
+
+/// Writes every page of a stored crawl to `path` as CSV, one row per page.
+///
+/// Pages are read from the results store twice, once to count inlinks and find the custom
+/// extraction columns and once to write the rows, so only those are ever held in memory.
+pub async fn export_pages(crawl_id: i64, path: PathBuf) -> Result<usize, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+
+    let inlinks = Arc::new(Mutex::new(HashMap::<String, usize>::new()));
+    let extractions = Arc::new(Mutex::new(BTreeSet::<String>::new()));
+    let counter = inlinks.clone();
+    let columns = extractions.clone();
+    store
+        .for_each_page(crawl_id, move |page| {
+            let mut counts = counter.lock().map_err(|e| e.to_string())?;
+            for target in internal_targets(&page) {
+                *counts.entry(target).or_insert(0) += 1;
+            }
+            columns
+                .lock()
+                .map_err(|e| e.to_string())?
+                .extend(page.custom_extraction.into_keys());
+            Ok(())
+        })
+        .await?;
+    let inlinks = std::mem::take(&mut *inlinks.lock().map_err(|e| e.to_string())?);
+    let extractions: Vec<String> =
+        std::mem::take(&mut *extractions.lock().map_err(|e| e.to_string())?)
+            .into_iter()
+            .collect();
+
+    let file = File::create(&path).map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+    let mut writer = Writer::from_writer(file);
+    writer
+        .write_record(
+            HEADERS
+                .iter()
+                .copied()
+                .chain(extractions.iter().map(String::as_str)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let writer = Arc::new(Mutex::new(writer));
+    let row_writer = writer.clone();
+    let rows = store
+        .for_each_page(crawl_id, move |page| {
+            let row = to_page_row(&page);
+            let outlinks = page.anchor_links.as_ref();
+
+            let custom = extractions.iter().map(|column| {
+                page.custom_extraction
+                    .get(column)
+                    .map(|values| values.join(VALUE_SEPARATOR))
+                    .unwrap_or_default()
+            });
+
+            row_writer
+                .lock()
+                .map_err(|e| e.to_string())?
+                .write_record(
+                    [
+                        row.url.clone(),
+                        row.status_code.to_string(),
+                        row.title.unwrap_or_default(),
+                        row.description,
+                        row.h1.unwrap_or_default(),
+                        row.word_count.to_string(),
+                        image_count(&page).to_string(),
+                        inlinks.get(&row.url).copied().unwrap_or(0).to_string(),
+                        outlinks
+                            .map_or(0, |links| links.internal.links.len())
+                            .to_string(),
+                        outlinks
+                            .map_or(0, |links| links.external.links.len())
+                            .to_string(),
+                    ]
+                    .into_iter()
+                    .chain(custom),
+                )
+                .map_err(|e| e.to_string())
+        })
+        .await?;
+
+    writer
+        .lock()
+        .map_err(|e| e.to_string())?
+        .flush()
+        .map_err(|e| e.to_string())?;
+
+    println!(
+        "Exported {} pages of crawl {} to {:?}",
+        rows, crawl_id, path
+    );
+    Ok(rows)
+}
+
+// EXPORT ALL PAGE LEVEL DATA OF A STORED CRAWL TO CSV
+#[tauri::command]
+pub async fn export_crawl_csv(crawl_id: i64, path: String) -> Result<usize, String> {
+    export_pages(crawl_id, PathBuf::from(path)).await
+}
@@ -0,0 +1,4 @@
+pub mod csv;
+pub mod policies;
+pub mod sitemap;
+pub mod xlsx;
@@ -0,0 +1,416 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::sitemap::is_sitemap_page;
+use crate::domain_crawler::duplicate_content::DuplicateCollector;
+use crate::domain_crawler::helpers::indexability::IndexabilityVerdict;
+use crate::domain_crawler::helpers::robots::RobotsRules;
+use crate::domain_crawler::models::DomainCrawlResults;
+use crate::domain_crawler::results_store::ResultsStore;
+use crate::settings::settings::load_settings;
+
+// A parameter or section needs this many URLs before it is worth a directive
+const MIN_URLS: usize = 3;
+// Share of a section's pages that must be duplicates before the section is blocked
+const DUPLICATE_SECTION_SHARE: f64 = 0.8;
+const MAX_EXAMPLES: usize = 5;
+const MAX_LLMS_SECTIONS: usize = 12;
+const MAX_LLMS_LINKS: usize = 20;
+
+// Query parameters that never change what a page shows
+const TRACKING_PARAMETERS: [&str; 12] = [
+    "utm_source",
+    "utm_medium",
+    "utm_campaign",
+    "utm_term",
+    "utm_content",
+    "gclid",
+    "fbclid",
+    "msclkid",
+    "sessionid",
+    "sid",
+    "ref",
+    "_ga",
+];
+
+// Paths of back offices, accounts and checkouts that have no place in search results
+const PRIVATE_PATHS: [&str; 12] = [
+    "/wp-admin/",
+    "/wp-login.php",
+    "/admin/",
+    "/administrator/",
+    "/login",
+    "/logout",
+    "/account/",
+    "/my-account/",
+    "/cart",
+    "/checkout",
+    "/search",
+    "/cgi-bin/",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum PolicyFindingKind {
+    ParameterUrls,
+    PrivatePath,
+    DuplicateSection,
+}
+
+/// A crawl finding and the robots.txt directive proposed for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyFinding {
+    pub kind: PolicyFindingKind,
+    pub directive: String,
+    pub reason: String,
+    pub urls: usize,
+    pub examples: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyProposal {
+    pub robots_txt: String,
+    pub llms_txt: String,
+    pub findings: Vec<PolicyFinding>,
+    /// Directives left out because they would block indexable, unique pages
+    pub rejected: Vec<PolicyFinding>,
+}
+
+fn examples(urls: &[&str]) -> Vec<String> {
+    urls.iter()
+        .take(MAX_EXAMPLES)
+        .map(|url| url.to_string())
+        .collect()
+}
+
+fn first_segment(url: &Url) -> Option<String> {
+    let mut segments = url.path_segments()?;
+    let first = segments.next().filter(|s| !s.is_empty())?;
+    // A bare file at the root is not a section
+    segments.next()?;
+    Some(first.to_string())
+}
+
+fn parameter_findings(
+    pages: &[(&DomainCrawlResults, Url)],
+    duplicates: &HashSet<&str>,
+) -> Vec<PolicyFinding> {
+    let mut parameters: BTreeMap<String, Vec<&DomainCrawlResults>> = BTreeMap::new();
+    for (page, url) in pages {
+        let names: HashSet<String> = url
+            .query_pairs()
+            .map(|(name, _)| name.to_lowercase())
+            .collect();
+        for name in names {
+            parameters.entry(name).or_default().push(page);
+        }
+    }
+
+    parameters
+        .into_iter()
+        .filter(|(_, pages)| pages.len() >= MIN_URLS)
+        .filter_map(|(name, pages)| {
+            let tracking = TRACKING_PARAMETERS.contains(&name.as_str());
+            let wasted = pages
+                .iter()
+                .filter(|page| {
+                    page.indexability.verdict != IndexabilityVerdict::Indexable
+                        || duplicates.contains(page.url.as_str())
+                })
+                .count();
+            // Parameters that lead to unique, indexable content are left alone
+            if !tracking && wasted < pages.len() {
+                return None;
+            }
+            let urls: Vec<&str> = pages.iter().map(|page| page.url.as_str()).collect();
+            Some(PolicyFinding {
+                kind: PolicyFindingKind::ParameterUrls,
+                directive: format!("Disallow: /*?*{}=", name),
+                reason: if tracking {
+                    format!("{} is a tracking parameter", name)
+                } else {
+                    format!(
+                        "Every crawled URL with {} is a duplicate or not indexable",
+                        name
+                    )
+                },
+                urls: urls.len(),
+                examples: examples(&urls),
+            })
+        })
+        .collect()
+}
+
+fn is_private(url: &Url) -> bool {
+    let path = url.path().to_lowercase();
+    PRIVATE_PATHS.iter().any(|prefix| path.starts_with(prefix))
+}
+
+fn private_path_findings(pages: &[(&DomainCrawlResults, Url)]) -> Vec<PolicyFinding> {
+    PRIVATE_PATHS
+        .iter()
+        .filter_map(|prefix| {
+            let urls: Vec<&str> = pages
+                .iter()
+                .filter(|(_, url)| url.path().to_lowercase().starts_with(prefix))
+                .map(|(page, _)| page.url.as_str())
+                .collect();
+            if urls.is_empty() {
+                return None;
+            }
+            let mut directive = format!("Disallow: {}", prefix);
+            // WordPress themes call this endpoint from the front end
+            if *prefix == "/wp-admin/" {
+                directive.push_str("\nAllow: /wp-admin/admin-ajax.php");
+            }
+            Some(PolicyFinding {
+                kind: PolicyFindingKind::PrivatePath,
+                directive,
+                reason: format!("{} is an admin, account or checkout path", prefix),
+                urls: urls.len(),
+                examples: examples(&urls),
+            })
+        })
+        .collect()
+}
+
+fn duplicate_section_findings(
+    pages: &[(&DomainCrawlResults, Url)],
+    duplicates: &HashSet<&str>,
+) -> Vec<PolicyFinding> {
+    let mut sections: BTreeMap<String, (usize, Vec<&str>)> = BTreeMap::new();
+    for (page, url) in pages.iter().filter(|(page, _)| is_sitemap_page(page)) {
+        let Some(section) = first_segment(url) else {
+            continue;
+        };
+        let entry = sections.entry(section).or_default();
+        entry.0 += 1;
+        if duplicates.contains(page.url.as_str()) {
+            entry.1.push(&page.url);
+        }
+    }
+
+    sections
+        .into_iter()
+        .filter(|(_, (total, duplicate))| {
+            duplicate.len() >= MIN_URLS
+                && duplicate.len() as f64 >= *total as f64 * DUPLICATE_SECTION_SHARE
+        })
+        .map(|(section, (total, duplicate))| PolicyFinding {
+            kind: PolicyFindingKind::DuplicateSection,
+            directive: format!("Disallow: /{}/", section),
+            reason: format!(
+                "{} of {} pages in /{}/ duplicate pages elsewhere",
+                duplicate.len(),
+                total,
+                section
+            ),
+            urls: duplicate.len(),
+            examples: examples(&duplicate),
+        })
+        .collect()
+}
+
+fn robots_txt(
+    domain: &str,
+    crawl_id: i64,
+    findings: &[PolicyFinding],
+    sitemap: Option<&str>,
+) -> String {
+    let mut robots = format!(
+        "# Proposed from crawl {} of {}, review before deploying\nUser-agent: *\n",
+        crawl_id, domain
+    );
+    if findings.is_empty() {
+        robots.push_str("Disallow:\n");
+    }
+    for finding in findings {
+        robots.push_str(&format!("# {}\n{}\n", finding.reason, finding.directive));
+    }
+    if let Some(sitemap) = sitemap {
+        robots.push_str(&format!("\nSitemap: {}\n", sitemap));
+    }
+    robots
+}
+
+fn page_title(page: &DomainCrawlResults) -> String {
+    page.title
+        .as_ref()
+        .and_then(|titles| titles.first())
+        .map(|title| title.title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| page.url.clone())
+}
+
+fn section_name(section: &str) -> String {
+    let words: Vec<String> = section
+        .split(['-', '_'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    words.join(" ")
+}
+
+/// Lists the pages an assistant should read, grouped by section, in the llms.txt format.
+fn llms_txt(
+    domain: &str,
+    home: Option<&DomainCrawlResults>,
+    pages: &[&DomainCrawlResults],
+) -> String {
+    let name = home.map(page_title).unwrap_or_else(|| domain.to_string());
+    let mut llms = format!("# {}\n", name);
+    if let Some(description) = home
+        .map(|home| home.description.trim())
+        .filter(|description| !description.is_empty())
+    {
+        llms.push_str(&format!("\n> {}\n", description));
+    }
+
+    let mut sections: BTreeMap<String, Vec<&DomainCrawlResults>> = BTreeMap::new();
+    for page in pages {
+        let section = Url::parse(&page.url)
+            .ok()
+            .and_then(|url| first_segment(&url))
+            .map(|section| section_name(&section))
+            .filter(|section| !section.is_empty())
+            .unwrap_or_else(|| "Main".to_string());
+        sections.entry(section).or_default().push(page);
+    }
+
+    // The largest sections say the most about the site
+    let mut sections: Vec<(String, Vec<&DomainCrawlResults>)> = sections.into_iter().collect();
+    sections.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(&b.0)));
+    for (section, mut pages) in sections.into_iter().take(MAX_LLMS_SECTIONS) {
+        // Pages closer to the start URL are the more important ones
+        pages.sort_by_key(|page| (page.crawl_depth.unwrap_or(usize::MAX), page.url.len()));
+        llms.push_str(&format!("\n## {}\n\n", section));
+        for page in pages.into_iter().take(MAX_LLMS_LINKS) {
+            let description = page.description.trim();
+            if description.is_empty() {
+                llms.push_str(&format!("- [{}]({})\n", page_title(page), page.url));
+            } else {
+                llms.push_str(&format!(
+                    "- [{}]({}): {}\n",
+                    page_title(page),
+                    page.url,
+                    description
+                ));
+            }
+        }
+    }
+    llms
+}
+
+/// Proposes a robots.txt and an llms.txt for the site of a stored crawl.
+pub async fn propose_policies(crawl_id: i64) -> Result<PolicyProposal, String> {
+    let settings = load_settings().await?;
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let crawl = store
+        .crawl(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Crawl {} not found", crawl_id))?;
+    let results = store.pages(crawl_id).await.map_err(|e| e.to_string())?;
+
+    let mut collector = DuplicateCollector::new(settings.near_duplicate_threshold);
+    results.iter().for_each(|page| collector.add(page));
+    let report = collector.finish();
+    // Representatives stay, the other members of a cluster are the duplicates
+    let duplicates: HashSet<&str> = report
+        .clusters
+        .iter()
+        .flat_map(|cluster| cluster.pages.iter().map(|page| page.url.as_str()))
+        .collect();
+    let pages: Vec<(&DomainCrawlResults, Url)> = results
+        .iter()
+        .filter_map(|page| Some((page, Url::parse(&page.url).ok()?)))
+        .collect();
+
+    let mut candidates = private_path_findings(&pages);
+    candidates.extend(parameter_findings(&pages, &duplicates));
+    candidates.extend(duplicate_section_findings(&pages, &duplicates));
+
+    // Keep only directives that block no indexable, unique page outside the private paths
+    let keepers: Vec<&Url> = pages
+        .iter()
+        .filter(|(page, url)| {
+            is_sitemap_page(page) && !duplicates.contains(page.url.as_str()) && !is_private(url)
+        })
+        .map(|(_, url)| url)
+        .collect();
+    let (mut findings, mut rejected) = (Vec::new(), Vec::new());
+    for finding in candidates {
+        let rules = RobotsRules::parse(&format!("User-agent: *\n{}", finding.directive));
+        let blocks_keeper = keepers.iter().any(|url| !rules.is_allowed("*", url));
+        if blocks_keeper {
+            rejected.push(finding);
+        } else {
+            findings.push(finding);
+        }
+    }
+
+    let root = pages
+        .iter()
+        .find_map(|(_, url)| url.join("/").ok())
+        .map(|root| root.to_string());
+    let sitemap = root.as_ref().map(|root| format!("{}sitemap.xml", root));
+    let robots = robots_txt(&crawl.domain, crawl_id, &findings, sitemap.as_deref());
+
+    // The llms.txt links only what the proposed robots.txt still allows
+    let proposed = RobotsRules::parse(&robots);
+    let home = results
+        .iter()
+        .find(|page| is_sitemap_page(page) && root.as_deref().is_some_and(|root| page.url == root));
+    let listed: Vec<&DomainCrawlResults> = pages
+        .iter()
+        .filter(|(page, url)| {
+            is_sitemap_page(page)
+                && !duplicates.contains(page.url.as_str())
+                && proposed.is_allowed("*", url)
+        })
+        .map(|(page, _)| *page)
+        .collect();
+
+    Ok(PolicyProposal {
+        robots_txt: robots,
+        llms_txt: llms_txt(&crawl.domain, home, &listed),
+        findings,
+        rejected,
+    })
+}
+
+// PROPOSE A ROBOTS.TXT AND LLMS.TXT FROM THE FINDINGS OF A STORED CRAWL
+#[tauri::command]
+pub async fn generate_crawl_policies(crawl_id: i64) -> Result<PolicyProposal, String> {
+    propose_policies(crawl_id).await
+}
+
+// WRITE THE REVIEWED ROBOTS.TXT AND LLMS.TXT TO A FOLDER
+#[tauri::command]
+pub async fn export_crawl_policies(
+    dir: String,
+    robots_txt: Option<String>,
+    llms_txt: Option<String>,
+) -> Result<Vec<String>, String> {
+    let dir = PathBuf::from(dir);
+    let mut written = Vec::new();
+    for (name, content) in [("robots.txt", robots_txt), ("llms.txt", llms_txt)] {
+        let Some(content) = content else {
+            continue;
+        };
+        let path = dir.join(name);
+        tokio::fs::write(&path, content)
+            .await
+            .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+        written.push(path.to_string_lossy().into_owned());
+    }
+    Ok(written)
+}
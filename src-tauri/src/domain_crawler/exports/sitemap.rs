@@ -0,0 +1,315 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::domain_crawler::crawl_scope::compile_patterns;
+use crate::domain_crawler::helpers::hreflang_selector::is_valid_hreflang_code;
+use crate::domain_crawler::helpers::indexability::IndexabilityVerdict;
+use crate::domain_crawler::models::DomainCrawlResults;
+use crate::domain_crawler::results_store::ResultsStore;
+
+// Limit of the sitemaps protocol, larger sets are split and listed in an index
+const MAX_URLS_PER_SITEMAP: usize = 50_000;
+const SITEMAP_NS: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+const XHTML_NS: &str = "http://www.w3.org/1999/xhtml";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SitemapExportOptions {
+    /// Take `<lastmod>` from the Last-Modified response header
+    #[serde(default)]
+    pub lastmod: bool,
+    /// Globs, or `regex:` patterns, matched against the path and query like the crawl scope
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Where the split sitemaps will be hosted, next to the site root when empty
+    #[serde(default)]
+    pub sitemap_base_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SitemapExport {
+    pub urls: usize,
+    pub excluded: usize,
+    /// The written files, the index first when the URLs were split
+    pub files: Vec<String>,
+}
+
+/// One `<url>` of a sitemap.
+#[derive(Debug, Clone)]
+pub struct SitemapEntry {
+    pub loc: String,
+    pub lastmod: Option<String>,
+    /// hreflang code and URL of each language version, written as `xhtml:link` alternates
+    pub alternates: Vec<(String, String)>,
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn last_modified(page: &DomainCrawlResults) -> Option<String> {
+    let (_, value) = page
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("last-modified"))?;
+    let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    Some(
+        date.with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::Secs, true),
+    )
+}
+
+/// Whether a page belongs in a sitemap: a successful, indexable HTML page.
+pub(crate) fn is_sitemap_page(page: &DomainCrawlResults) -> bool {
+    page.status_code == 200
+        && page.content_type.contains("text/html")
+        && page.indexability.verdict == IndexabilityVerdict::Indexable
+}
+
+fn target(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+fn write_file(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> std::io::Result<()>,
+) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+    let mut writer = BufWriter::new(file);
+    write(&mut writer)
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+fn write_urlset(path: &Path, entries: &[SitemapEntry]) -> Result<(), String> {
+    write_file(path, |out| {
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        if entries.iter().any(|entry| !entry.alternates.is_empty()) {
+            writeln!(
+                out,
+                r#"<urlset xmlns="{}" xmlns:xhtml="{}">"#,
+                SITEMAP_NS, XHTML_NS
+            )?;
+        } else {
+            writeln!(out, r#"<urlset xmlns="{}">"#, SITEMAP_NS)?;
+        }
+        for entry in entries {
+            writeln!(out, "  <url>")?;
+            writeln!(out, "    <loc>{}</loc>", escape_xml(&entry.loc))?;
+            if let Some(lastmod) = &entry.lastmod {
+                writeln!(out, "    <lastmod>{}</lastmod>", lastmod)?;
+            }
+            for (code, href) in &entry.alternates {
+                writeln!(
+                    out,
+                    r#"    <xhtml:link rel="alternate" hreflang="{}" href="{}"/>"#,
+                    escape_xml(code),
+                    escape_xml(href)
+                )?;
+            }
+            writeln!(out, "  </url>")?;
+        }
+        writeln!(out, "</urlset>")
+    })
+}
+
+// The URL the split sitemaps are published under: the option, or the root of the site
+fn sitemap_base(option: Option<&str>, first_page: &str) -> Result<Url, String> {
+    let base = match option.map(str::trim).filter(|base| !base.is_empty()) {
+        Some(base) => Url::parse(base).map_err(|e| format!("Invalid sitemap base URL: {}", e))?,
+        None => Url::parse(first_page)
+            .and_then(|page| page.join("/"))
+            .map_err(|e| format!("Invalid page URL {}: {}", first_page, e))?,
+    };
+    // A base without a trailing slash would have its last segment replaced on join
+    if base.path().ends_with('/') {
+        Ok(base)
+    } else {
+        Url::parse(&format!("{}/", base)).map_err(|e| e.to_string())
+    }
+}
+
+/// Writes `entries` to `path`, or to numbered sitemaps next to it with an index at `path`
+/// once they exceed the protocol's limit of 50,000 URLs.
+///
+/// `base_url` is where the numbered sitemaps will be published, the index links to them there.
+pub(crate) fn write_sitemaps(
+    path: &Path,
+    entries: &[SitemapEntry],
+    base_url: Option<&str>,
+) -> Result<Vec<String>, String> {
+    if entries.len() <= MAX_URLS_PER_SITEMAP {
+        write_urlset(path, entries)?;
+        return Ok(vec![path.to_string_lossy().into_owned()]);
+    }
+    let base_url = sitemap_base(base_url, &entries[0].loc)?;
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "sitemap".to_string());
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut parts = Vec::new();
+    for (index, chunk) in entries.chunks(MAX_URLS_PER_SITEMAP).enumerate() {
+        let name = format!("{}-{}.xml", stem, index + 1);
+        let part = dir.join(&name);
+        write_urlset(&part, chunk)?;
+        let loc = base_url
+            .join(&name)
+            .map_err(|e| format!("Invalid sitemap base URL: {}", e))?;
+        parts.push((part, loc));
+    }
+
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    write_file(path, |out| {
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(out, r#"<sitemapindex xmlns="{}">"#, SITEMAP_NS)?;
+        for (_, loc) in &parts {
+            writeln!(out, "  <sitemap>")?;
+            writeln!(out, "    <loc>{}</loc>", escape_xml(loc.as_str()))?;
+            writeln!(out, "    <lastmod>{}</lastmod>", now)?;
+            writeln!(out, "  </sitemap>")?;
+        }
+        writeln!(out, "</sitemapindex>")
+    })?;
+
+    let mut files = vec![path.to_string_lossy().into_owned()];
+    files.extend(
+        parts
+            .into_iter()
+            .map(|(part, _)| part.to_string_lossy().into_owned()),
+    );
+    Ok(files)
+}
+
+/// The valid hreflang annotations of a page, resolved against its URL, one per code.
+fn alternates(page: &DomainCrawlResults) -> Vec<(String, String)> {
+    let Ok(page_url) = Url::parse(&page.url) else {
+        return Vec::new();
+    };
+    let mut codes = HashSet::new();
+    let mut alternates: Vec<(String, String)> = page
+        .hreflangs
+        .iter()
+        .flatten()
+        .filter(|hreflang| is_valid_hreflang_code(&hreflang.code))
+        .filter_map(|hreflang| {
+            let href = page_url.join(hreflang.url.trim()).ok()?;
+            Some((hreflang.code.to_lowercase(), href.to_string()))
+        })
+        .filter(|(code, _)| codes.insert(code.clone()))
+        .collect();
+    alternates.sort();
+    alternates
+}
+
+/// Builds a sitemap of the indexable pages of a stored crawl.
+///
+/// With `hreflang` only pages with language annotations are listed, each with its alternates.
+pub async fn export_sitemap_file(
+    crawl_id: i64,
+    path: PathBuf,
+    options: SitemapExportOptions,
+    hreflang: bool,
+) -> Result<SitemapExport, String> {
+    let exclude = compile_patterns(&options.exclude)?;
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+
+    let collected = Arc::new(Mutex::new((Vec::<SitemapEntry>::new(), 0usize)));
+    let sink = collected.clone();
+    let with_lastmod = options.lastmod;
+    store
+        .for_each_page(crawl_id, move |page| {
+            if !is_sitemap_page(&page) {
+                return Ok(());
+            }
+            let mut sink = sink.lock().map_err(|e| e.to_string())?;
+            let excluded = Url::parse(&page.url)
+                .map(|url| exclude.iter().any(|p| p.is_match(&target(&url))))
+                .unwrap_or(true);
+            if excluded {
+                sink.1 += 1;
+                return Ok(());
+            }
+            let alternates = if hreflang {
+                alternates(&page)
+            } else {
+                Vec::new()
+            };
+            if hreflang && alternates.is_empty() {
+                return Ok(());
+            }
+            sink.0.push(SitemapEntry {
+                lastmod: with_lastmod.then(|| last_modified(&page)).flatten(),
+                loc: page.url,
+                alternates,
+            });
+            Ok(())
+        })
+        .await?;
+    let (mut entries, excluded) =
+        std::mem::take(&mut *collected.lock().map_err(|e| e.to_string())?);
+
+    // Redirects and trailing slashes can bring the same URL in twice
+    let mut seen = HashSet::new();
+    entries.retain(|entry| seen.insert(entry.loc.clone()));
+
+    let files = write_sitemaps(&path, &entries, options.sitemap_base_url.as_deref())?;
+
+    println!(
+        "Exported {} URLs of crawl {} to {:?}",
+        entries.len(),
+        crawl_id,
+        path
+    );
+    Ok(SitemapExport {
+        urls: entries.len(),
+        excluded,
+        files,
+    })
+}
+
+// EXPORT THE INDEXABLE PAGES OF A STORED CRAWL AS SITEMAP XML
+#[tauri::command]
+pub async fn export_sitemap(
+    crawl_id: i64,
+    path: String,
+    options: Option<SitemapExportOptions>,
+) -> Result<SitemapExport, String> {
+    export_sitemap_file(
+        crawl_id,
+        PathBuf::from(path),
+        options.unwrap_or_default(),
+        false,
+    )
+    .await
+}
+
+// EXPORT THE HREFLANG ANNOTATIONS OF A STORED CRAWL AS SITEMAP XML
+#[tauri::command]
+pub async fn export_hreflang_sitemap(
+    crawl_id: i64,
+    path: String,
+    options: Option<SitemapExportOptions>,
+) -> Result<SitemapExport, String> {
+    export_sitemap_file(
+        crawl_id,
+        PathBuf::from(path),
+        options.unwrap_or_default(),
+        true,
+    )
+    .await
+}
@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use rust_xlsxwriter::{Format, FormatAlign, FormatBorder, Workbook, Worksheet, XlsxError};
+
+use crate::domain_crawler::extractors::custom;
+use crate::domain_crawler::models::DomainCrawlResults;
+use crate::domain_crawler::results_store::{to_page_row, ResultsStore};
+use crate::projects::registry::ProjectRoots;
+
+const PAGES_HEADERS: [&str; 9] = [
+    "URL",
+    "Status Code",
+    "Title",
+    "Meta Description",
+    "H1",
+    "Word Count",
+    "Response Time (s)",
+    "Content Type",
+    "Indexability",
+];
+const IMAGES_HEADERS: [&str; 6] = [
+    "Page",
+    "Image URL",
+    "Alt Text",
+    "Size (KB)",
+    "Type",
+    "Status Code",
+];
+const BROKEN_LINKS_HEADERS: [&str; 6] = [
+    "URL",
+    "Internal",
+    "Status Code",
+    "Redirect Target",
+    "Error",
+    "Found On",
+];
+const REDIRECTS_HEADERS: [&str; 3] = ["URL", "Status Code", "Redirects To"];
+const DUPLICATE_TITLES_HEADERS: [&str; 3] = ["Title", "Pages", "URLs"];
+const PDFS_HEADERS: [&str; 9] = [
+    "Page",
+    "PDF URL",
+    "Status Code",
+    "Redirects To",
+    "Size (bytes)",
+    "Version",
+    "Title",
+    "Page Count",
+    "Oversized",
+];
+
+// A worksheet together with the next free row
+struct Sheet {
+    worksheet: Worksheet,
+    row: u32,
+}
+
+impl Sheet {
+    fn new(name: &str, headers: &[&str]) -> Result<Self, XlsxError> {
+        let header_format = Format::new()
+            .set_bold()
+            .set_border(FormatBorder::Thin)
+            .set_align(FormatAlign::Center);
+
+        let mut worksheet = Worksheet::new();
+        worksheet.set_name(name)?;
+        for (col, header) in headers.iter().enumerate() {
+            worksheet.write_with_format(0, col as u16, *header, &header_format)?;
+            worksheet.set_column_width(col as u16, 22)?;
+        }
+        worksheet.set_freeze_panes(1, 0)?;
+
+        Ok(Self { worksheet, row: 1 })
+    }
+
+    fn push(&mut self, cells: &[String]) -> Result<(), XlsxError> {
+        for (col, cell) in cells.iter().enumerate() {
+            self.worksheet.write(self.row, col as u16, cell.as_str())?;
+        }
+        self.row += 1;
+        Ok(())
+    }
+}
+
+struct ReportSheets {
+    pages: Sheet,
+    images: Sheet,
+    redirects: Sheet,
+    pdfs: Sheet,
+    titles: BTreeMap<String, Vec<String>>,
+    extractions: Vec<(String, BTreeMap<String, Vec<String>>)>,
+}
+
+impl ReportSheets {
+    fn add_page(&mut self, page: &DomainCrawlResults) -> Result<(), XlsxError> {
+        let row = to_page_row(page);
+
+        self.pages.push(&[
+            row.url.clone(),
+            row.status_code.to_string(),
+            row.title.clone().unwrap_or_default(),
+            row.description,
+            row.h1.unwrap_or_default(),
+            row.word_count.to_string(),
+            row.response_time
+                .map(|time| format!("{:.3}", time))
+                .unwrap_or_default(),
+            row.content_type,
+            page.indexability.indexability_reason.clone(),
+        ])?;
+
+        if let Ok(images) = &page.images {
+            for (url, alt, size, kind, status, _) in images {
+                self.images.push(&[
+                    row.url.clone(),
+                    url.clone(),
+                    alt.clone(),
+                    size.to_string(),
+                    kind.clone(),
+                    status.to_string(),
+                ])?;
+            }
+        }
+
+        if (300..400).contains(&row.status_code) || page.redirection.is_some() {
+            self.redirects.push(&[
+                row.url.clone(),
+                row.status_code.to_string(),
+                page.redirection.clone().unwrap_or_default(),
+            ])?;
+        }
+
+        for pdf in &page.pdf_audits {
+            self.pdfs.push(&[
+                row.url.clone(),
+                pdf.url.clone(),
+                pdf.status.map(|s| s.to_string()).unwrap_or_default(),
+                pdf.redirected_to.clone().unwrap_or_default(),
+                pdf.size_bytes.map(|s| s.to_string()).unwrap_or_default(),
+                pdf.version.clone().unwrap_or_default(),
+                pdf.title.clone().unwrap_or_default(),
+                pdf.page_count.map(|c| c.to_string()).unwrap_or_default(),
+                pdf.oversized.to_string(),
+            ])?;
+        }
+
+        if !page.custom_extraction.is_empty() {
+            self.extractions
+                .push((row.url.clone(), page.custom_extraction.clone()));
+        }
+
+        if let Some(title) = row.title.filter(|title| !title.trim().is_empty()) {
+            self.titles
+                .entry(title.trim().to_string())
+                .or_default()
+                .push(row.url);
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a multi-sheet workbook for a stored crawl and saves it to `path`.
+///
+/// Sheets: Pages, Images, Broken Links, Redirects, Duplicate Titles and PDFs, plus Custom
+/// Extraction when the crawl ran extraction rules.
+pub async fn export_report(
+    roots: &ProjectRoots,
+    crawl_id: i64,
+    path: PathBuf,
+) -> Result<usize, String> {
+    let store = ResultsStore::open_in(&roots.db_dir)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let sheets = ReportSheets {
+        pages: Sheet::new("Pages", &PAGES_HEADERS).map_err(|e| e.to_string())?,
+        images: Sheet::new("Images", &IMAGES_HEADERS).map_err(|e| e.to_string())?,
+        redirects: Sheet::new("Redirects", &REDIRECTS_HEADERS).map_err(|e| e.to_string())?,
+        pdfs: Sheet::new("PDFs", &PDFS_HEADERS).map_err(|e| e.to_string())?,
+        titles: BTreeMap::new(),
+        extractions: Vec::new(),
+    };
+
+    let sheets = Arc::new(Mutex::new(sheets));
+    let collector = sheets.clone();
+    let pages = store
+        .for_each_page(crawl_id, move |page| {
+            collector
+                .lock()
+                .map_err(|e| e.to_string())?
+                .add_page(&page)
+                .map_err(|e| e.to_string())
+        })
+        .await?;
+
+    let sheets = Arc::try_unwrap(sheets)
+        .map_err(|_| "Report sheets are still in use".to_string())?
+        .into_inner()
+        .map_err(|e| e.to_string())?;
+
+    let mut broken_links =
+        Sheet::new("Broken Links", &BROKEN_LINKS_HEADERS).map_err(|e| e.to_string())?;
+    for link in store
+        .broken_links(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        broken_links
+            .push(&[
+                link.url,
+                link.internal.to_string(),
+                link.status.map(|s| s.to_string()).unwrap_or_default(),
+                link.redirect_target.unwrap_or_default(),
+                link.error.unwrap_or_default(),
+                link.referrers.join("\n"),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+
+    let mut duplicate_titles =
+        Sheet::new("Duplicate Titles", &DUPLICATE_TITLES_HEADERS).map_err(|e| e.to_string())?;
+    for (title, urls) in sheets.titles.iter().filter(|(_, urls)| urls.len() > 1) {
+        duplicate_titles
+            .push(&[title.clone(), urls.len().to_string(), urls.join("\n")])
+            .map_err(|e| e.to_string())?;
+    }
+
+    let mut workbook = Workbook::new();
+    for sheet in [
+        sheets.pages,
+        sheets.images,
+        broken_links,
+        sheets.redirects,
+        duplicate_titles,
+        sheets.pdfs,
+    ] {
+        workbook.push_worksheet(sheet.worksheet);
+    }
+
+    if !sheets.extractions.is_empty() {
+        let table = custom::table(sheets.extractions);
+        let headers: Vec<&str> = std::iter::once("URL")
+            .chain(table.columns.iter().map(String::as_str))
+            .collect();
+        let mut extractions =
+            Sheet::new("Custom Extraction", &headers).map_err(|e| e.to_string())?;
+        for row in table.rows {
+            let cells: Vec<String> = std::iter::once(row.url).chain(row.values).collect();
+            extractions.push(&cells).map_err(|e| e.to_string())?;
+        }
+        workbook.push_worksheet(extractions.worksheet);
+    }
+    workbook.save(&path).map_err(|e| e.to_string())?;
+
+    println!(
+        "Exported {} pages of crawl {} to {:?}",
+        pages, crawl_id, path
+    );
+    Ok(pages)
+}
+
+// EXPORT A STORED CRAWL AS A MULTI SHEET EXCEL REPORT
+#[tauri::command]
+pub async fn export_crawl_xlsx(crawl_id: i64, path: String) -> Result<usize, String> {
+    export_report(&ProjectRoots::active()?, crawl_id, PathBuf::from(path)).await
+}
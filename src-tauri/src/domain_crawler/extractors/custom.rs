@@ -0,0 +1,334 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::xpath::{self, XPathValue};
+use crate::domain_crawler::results_store::ResultsStore;
+use crate::projects::registry::ProjectRoots;
+
+const RULES_FILE: &str = "extraction_rules.json";
+// Values joined into one cell of the exports
+pub const VALUE_SEPARATOR: &str = " | ";
+
+// Rules of the running crawl, compiled once when it starts
+static ACTIVE_RULES: Lazy<RwLock<Arc<Vec<CompiledRule>>>> =
+    Lazy::new(|| RwLock::new(Arc::new(Vec::new())));
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectorKind {
+    #[default]
+    Css,
+    Xpath,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractTarget {
+    #[default]
+    Text,
+    InnerHtml,
+    OuterHtml,
+    Attribute,
+}
+
+/// A named value to pull out of every crawled page, shown as an extra column.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtractionRule {
+    pub id: String,
+    pub name: String,
+    pub kind: SelectorKind,
+    pub selector: String,
+    pub target: ExtractTarget,
+    /// The attribute read when the target is `attribute`
+    pub attribute: Option<String>,
+    /// Keeps the first capture group of the match, or the whole match without groups
+    pub regex: Option<String>,
+    /// Every matching element instead of the first one
+    pub all_matches: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug)]
+pub struct CompiledRule {
+    name: String,
+    selector: Selector,
+    target: ExtractTarget,
+    attribute: Option<String>,
+    regex: Option<Regex>,
+    all_matches: bool,
+}
+
+impl ExtractionRule {
+    pub fn compile(&self) -> Result<CompiledRule, String> {
+        let (css, target, mut attribute) = match self.kind {
+            SelectorKind::Css => (self.selector.clone(), self.target, self.attribute.clone()),
+            SelectorKind::Xpath => match xpath::to_css(&self.selector)? {
+                (css, Some(XPathValue::Attribute(name))) => {
+                    (css, ExtractTarget::Attribute, Some(name))
+                }
+                (css, Some(XPathValue::Text)) => (css, ExtractTarget::Text, None),
+                (css, None) => (css, self.target, self.attribute.clone()),
+            },
+        };
+        let selector = Selector::parse(&css)
+            .map_err(|e| format!("Invalid selector for {}: {}", self.name, e))?;
+
+        attribute = attribute
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        if target == ExtractTarget::Attribute && attribute.is_none() {
+            return Err(format!(
+                "{} extracts an attribute but names none",
+                self.name
+            ));
+        }
+        if target != ExtractTarget::Attribute {
+            attribute = None;
+        }
+
+        let regex = match self.regex.as_deref().map(str::trim) {
+            Some(pattern) if !pattern.is_empty() => Some(
+                Regex::new(pattern)
+                    .map_err(|e| format!("Invalid regex for {}: {}", self.name, e))?,
+            ),
+            _ => None,
+        };
+
+        Ok(CompiledRule {
+            name: self.name.trim().to_string(),
+            selector,
+            target,
+            attribute,
+            regex,
+            all_matches: self.all_matches,
+        })
+    }
+}
+
+impl CompiledRule {
+    fn value(&self, element: ElementRef) -> Option<String> {
+        let value = match self.target {
+            ExtractTarget::Text => element
+                .text()
+                .collect::<String>()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+            ExtractTarget::InnerHtml => element.inner_html(),
+            ExtractTarget::OuterHtml => element.html(),
+            ExtractTarget::Attribute => element
+                .value()
+                .attr(self.attribute.as_deref().unwrap_or_default())?
+                .to_string(),
+        };
+        let value = match &self.regex {
+            Some(regex) => {
+                let captures = regex.captures(&value)?;
+                captures
+                    .get(1)
+                    .or_else(|| captures.get(0))?
+                    .as_str()
+                    .to_string()
+            }
+            None => value,
+        };
+        let value = value.trim();
+        (!value.is_empty()).then(|| value.to_string())
+    }
+
+    pub fn extract(&self, document: &Html) -> Vec<String> {
+        let values = document
+            .select(&self.selector)
+            .filter_map(|element| self.value(element));
+        if self.all_matches {
+            values.collect()
+        } else {
+            values.take(1).collect()
+        }
+    }
+}
+
+fn rules_path(roots: &ProjectRoots) -> PathBuf {
+    roots.config_file(RULES_FILE)
+}
+
+fn load_rules(roots: &ProjectRoots) -> Result<Vec<ExtractionRule>, String> {
+    let path = rules_path(roots);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read extraction rules: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse extraction rules: {}", e))
+}
+
+fn save_rules(roots: &ProjectRoots, rules: &[ExtractionRule]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(rules).map_err(|e| e.to_string())?;
+    fs::write(rules_path(roots), json)
+        .map_err(|e| format!("Failed to save extraction rules: {}", e))
+}
+
+/// Compiles the enabled rules of the crawled project for the crawl about to start.
+pub fn configure(roots: &ProjectRoots) -> Result<(), String> {
+    let rules = load_rules(roots)?
+        .iter()
+        .filter(|rule| rule.enabled)
+        .map(ExtractionRule::compile)
+        .collect::<Result<Vec<_>, _>>()?;
+    *ACTIVE_RULES.write().map_err(|e| e.to_string())? = Arc::new(rules);
+    Ok(())
+}
+
+/// Runs the rules of the crawl on a page, rule name to the values found.
+pub fn extract(body: &str) -> BTreeMap<String, Vec<String>> {
+    let rules = match ACTIVE_RULES.read() {
+        Ok(rules) => rules.clone(),
+        Err(_) => return BTreeMap::new(),
+    };
+    if rules.is_empty() {
+        return BTreeMap::new();
+    }
+    let document = Html::parse_document(body);
+    rules
+        .iter()
+        .map(|rule| (rule.name.clone(), rule.extract(&document)))
+        .collect()
+}
+
+/// The extracted values of a stored crawl as a table, one column per rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionTable {
+    pub columns: Vec<String>,
+    pub rows: Vec<ExtractionRow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionRow {
+    pub url: String,
+    /// One cell per column, the values of multi-match rules joined
+    pub values: Vec<String>,
+}
+
+/// The rule columns found in a set of pages and each page's cells, in column order.
+pub fn table(pages: Vec<(String, BTreeMap<String, Vec<String>>)>) -> ExtractionTable {
+    let mut columns: Vec<String> = pages
+        .iter()
+        .flat_map(|(_, values)| values.keys().cloned())
+        .collect();
+    columns.sort();
+    columns.dedup();
+
+    let rows = pages
+        .into_iter()
+        .map(|(url, values)| ExtractionRow {
+            values: columns
+                .iter()
+                .map(|column| {
+                    values
+                        .get(column)
+                        .map(|found| found.join(VALUE_SEPARATOR))
+                        .unwrap_or_default()
+                })
+                .collect(),
+            url,
+        })
+        .collect();
+    ExtractionTable { columns, rows }
+}
+
+#[tauri::command]
+pub async fn list_extraction_rules() -> Result<Vec<ExtractionRule>, String> {
+    load_rules(&ProjectRoots::active()?)
+}
+
+// ADD AN EXTRACTION RULE, OR REPLACE THE ONE WITH THE SAME ID
+#[tauri::command]
+pub async fn save_extraction_rule(mut rule: ExtractionRule) -> Result<ExtractionRule, String> {
+    rule.name = rule.name.trim().to_string();
+    rule.selector = rule.selector.trim().to_string();
+    if rule.name.is_empty() {
+        return Err("Extraction rules need a name".to_string());
+    }
+    if rule.selector.is_empty() {
+        return Err(format!("{} has no selector", rule.name));
+    }
+    rule.compile()?;
+    if rule.id.is_empty() {
+        rule.id = Uuid::new_v4().to_string();
+    }
+
+    let roots = ProjectRoots::active()?;
+    let mut rules = load_rules(&roots)?;
+    // The name is the column header, two rules cannot share it
+    if rules
+        .iter()
+        .any(|other| other.id != rule.id && other.name.eq_ignore_ascii_case(&rule.name))
+    {
+        return Err(format!(
+            "An extraction rule named {} already exists",
+            rule.name
+        ));
+    }
+    match rules.iter_mut().find(|other| other.id == rule.id) {
+        Some(existing) => *existing = rule.clone(),
+        None => rules.push(rule.clone()),
+    }
+    save_rules(&roots, &rules)?;
+    Ok(rule)
+}
+
+#[tauri::command]
+pub async fn delete_extraction_rule(id: String) -> Result<(), String> {
+    let roots = ProjectRoots::active()?;
+    let mut rules = load_rules(&roots)?;
+    let count = rules.len();
+    rules.retain(|rule| rule.id != id);
+    if rules.len() == count {
+        return Err(format!("Extraction rule {} not found", id));
+    }
+    save_rules(&roots, &rules)
+}
+
+// RUN AN EXTRACTION RULE ON A PIECE OF HTML TO PREVIEW WHAT IT FINDS
+#[tauri::command]
+pub async fn test_extraction_rule(
+    rule: ExtractionRule,
+    html: String,
+) -> Result<Vec<String>, String> {
+    let compiled = rule.compile()?;
+    Ok(compiled.extract(&Html::parse_document(&html)))
+}
+
+// GET THE CUSTOM EXTRACTION COLUMNS OF A STORED CRAWL
+#[tauri::command]
+pub async fn get_custom_extractions(crawl_id: i64) -> Result<ExtractionTable, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let pages = Arc::new(Mutex::new(Vec::new()));
+    let sink = pages.clone();
+    store
+        .for_each_page(crawl_id, move |page| {
+            if !page.custom_extraction.is_empty() {
+                sink.lock()
+                    .map_err(|e| e.to_string())?
+                    .push((page.url, page.custom_extraction));
+            }
+            Ok(())
+        })
+        .await?;
+    let pages = std::mem::take(&mut *pages.lock().map_err(|e| e.to_string())?);
+    Ok(table(pages))
+}
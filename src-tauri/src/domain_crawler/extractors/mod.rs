@@ -1 +1,3 @@
+pub mod custom;
 pub mod html;
+pub mod xpath;
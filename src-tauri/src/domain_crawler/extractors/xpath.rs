@@ -0,0 +1,220 @@
+/// What a trailing `/@name` or `/text()` step of an XPath asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XPathValue {
+    Attribute(String),
+    Text,
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.input.len() - trimmed.len();
+    }
+
+    fn name(&mut self) -> Result<&'a str, String> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | ':')))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(format!("Expected a name at \"{}\"", rest));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn literal(&mut self) -> Result<&'a str, String> {
+        let rest = self.rest();
+        let quote = rest
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '\'' | '"'))
+            .ok_or_else(|| format!("Expected a quoted value at \"{}\"", rest))?;
+        let end = rest[1..].find(quote).ok_or("Unterminated quoted value")?;
+        self.pos += end + 2;
+        Ok(&rest[1..end + 1])
+    }
+
+    // One condition of a predicate, as a CSS attribute selector or pseudo-class
+    fn condition(&mut self, any: bool) -> Result<String, String> {
+        self.skip_spaces();
+        if let Some(index) = self
+            .rest()
+            .split(|c: char| !c.is_ascii_digit())
+            .next()
+            .filter(|digits| !digits.is_empty())
+        {
+            self.pos += index.len();
+            // Positions count siblings of the same name, or all of them for `*`
+            let pseudo = if any { "nth-child" } else { "nth-of-type" };
+            return Ok(format!(":{}({})", pseudo, index));
+        }
+        if self.eat("last()") {
+            let pseudo = if any { ":last-child" } else { ":last-of-type" };
+            return Ok(pseudo.to_string());
+        }
+        for (function, operator) in [("contains(", "*="), ("starts-with(", "^=")] {
+            if self.eat(function) {
+                self.skip_spaces();
+                if !self.eat("@") {
+                    return Err(format!("Only attributes can be tested with {})", function));
+                }
+                let attribute = self.name()?;
+                self.skip_spaces();
+                if !self.eat(",") {
+                    return Err(format!("Expected a comma in {})", function));
+                }
+                self.skip_spaces();
+                let value = self.literal()?;
+                self.skip_spaces();
+                if !self.eat(")") {
+                    return Err(format!("Expected ) to close {})", function));
+                }
+                return Ok(format!(
+                    "[{}{}\"{}\"]",
+                    attribute,
+                    operator,
+                    css_escape(value)
+                ));
+            }
+        }
+        if self.eat("@") {
+            let attribute = self.name()?;
+            self.skip_spaces();
+            if self.eat("=") {
+                self.skip_spaces();
+                let value = self.literal()?;
+                return Ok(format!("[{}=\"{}\"]", attribute, css_escape(value)));
+            }
+            return Ok(format!("[{}]", attribute));
+        }
+        Err(format!(
+            "Unsupported XPath predicate at \"{}\", match text with a regex instead",
+            self.rest()
+        ))
+    }
+
+    fn predicates(&mut self, css: &mut String, any: bool) -> Result<(), String> {
+        while self.eat("[") {
+            loop {
+                css.push_str(&self.condition(any)?);
+                self.skip_spaces();
+                if !self.eat("and ") {
+                    break;
+                }
+            }
+            if !self.eat("]") {
+                return Err(format!("Expected ] at \"{}\"", self.rest()));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn css_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Translates the common subset of XPath into a CSS selector the crawler can run.
+///
+/// Supports `/` and `//` steps with tag names or `*`, the predicates `[n]`, `[last()]`,
+/// `[@a]`, `[@a='v']`, `contains(@a,'v')` and `starts-with(@a,'v')` joined with `and`,
+/// and a final `/@name` or `/text()` step. Relative paths are rejected, extractions run
+/// on the whole document and have no element for them to start from.
+pub fn to_css(xpath: &str) -> Result<(String, Option<XPathValue>), String> {
+    let mut parser = Parser {
+        input: xpath.trim(),
+        pos: 0,
+    };
+    if parser.rest().starts_with('.') {
+        return Err(format!(
+            "Relative XPath \"{}\" is not supported, start it with / or //",
+            parser.rest()
+        ));
+    }
+
+    let mut css = String::new();
+    let mut value = None;
+    while !parser.rest().is_empty() {
+        let combinator = if parser.eat("//") {
+            " "
+        } else if parser.eat("/") {
+            " > "
+        } else {
+            return Err(format!("Expected / at \"{}\"", parser.rest()));
+        };
+
+        if parser.eat("@") {
+            value = Some(XPathValue::Attribute(parser.name()?.to_string()));
+        } else if parser.eat("text()") {
+            value = Some(XPathValue::Text);
+        }
+        if value.is_some() {
+            if !parser.rest().is_empty() {
+                return Err("Attribute and text() steps must come last".to_string());
+            }
+            break;
+        }
+
+        let first = css.is_empty();
+        if !first {
+            css.push_str(combinator);
+        }
+        let any = parser.eat("*");
+        css.push_str(if any { "*" } else { parser.name()? });
+        parser.predicates(&mut css, any)?;
+        // An absolute path starts at the root element
+        if first && combinator == " > " {
+            css.push_str(":root");
+        }
+    }
+
+    if css.is_empty() {
+        return Err("The XPath selects no elements".to_string());
+    }
+    Ok((css, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absolute_paths_start_at_the_root_element() {
+        assert_eq!(
+            to_css("/html/body/div[2]").unwrap(),
+            ("html:root > body > div:nth-of-type(2)".to_string(), None)
+        );
+        assert_eq!(
+            to_css("//a[contains(@href,'shop')]/@href").unwrap(),
+            (
+                "a[href*=\"shop\"]".to_string(),
+                Some(XPathValue::Attribute("href".to_string()))
+            )
+        );
+    }
+
+    #[test]
+    fn relative_paths_are_rejected() {
+        assert!(to_css("./div").is_err());
+        assert!(to_css(".//span/text()").is_err());
+    }
+}
@@ -0,0 +1,499 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+use r2d2::PooledConnection;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, OptionalExtension};
+use url::Url;
+
+use super::database::{Database, DatabaseError};
+
+const FRONTIER_DB: &str = "frontier.db";
+
+// Sized for ten million URLs at roughly a 1% false positive rate, about 12 MB
+const BLOOM_BITS: usize = 96_000_000;
+const BLOOM_HASHES: u64 = 7;
+
+const QUEUED: i64 = 0;
+const IN_FLIGHT: i64 = 1;
+const VISITED: i64 = 2;
+
+/// Fixed-size bloom filter in front of the disk index.
+///
+/// A miss means the URL was never seen, so new links skip the database lookup entirely.
+struct BloomFilter {
+    bits: Vec<u64>,
+}
+
+impl BloomFilter {
+    fn new() -> Self {
+        Self {
+            bits: vec![0; BLOOM_BITS / 64],
+        }
+    }
+
+    fn positions(url: &str) -> impl Iterator<Item = usize> {
+        let hash = |seed: u64| {
+            let mut hasher = DefaultHasher::new();
+            seed.hash(&mut hasher);
+            url.hash(&mut hasher);
+            hasher.finish()
+        };
+        let (h1, h2) = (hash(0), hash(1));
+        (0..BLOOM_HASHES)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % BLOOM_BITS as u64) as usize)
+    }
+
+    fn insert(&mut self, url: &str) {
+        for bit in Self::positions(url) {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    fn may_contain(&self, url: &str) -> bool {
+        Self::positions(url).all(|bit| self.bits[bit / 64] & 1 << (bit % 64) != 0)
+    }
+}
+
+/// Frontier and visited index kept in SQLite so memory stays flat however large the site.
+struct DiskFrontier {
+    // A single crawl owns the frontier, one connection serves it and its transactions
+    conn: PooledConnection<SqliteConnectionManager>,
+    seen: BloomFilter,
+    next_seq: i64,
+    queued: usize,
+}
+
+impl DiskFrontier {
+    fn open(db_dir: &Path, domain: &str, resume: bool) -> Result<Self, DatabaseError> {
+        let conn = Database::in_dir(db_dir, FRONTIER_DB)?.get_pool().get()?;
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS frontier (
+                url TEXT PRIMARY KEY,
+                depth INTEGER,
+                seq INTEGER NOT NULL,
+                state INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_frontier_queue ON frontier(state, seq);
+            CREATE TABLE IF NOT EXISTS frontier_meta (domain TEXT NOT NULL);
+            "#,
+        )?;
+
+        let mut frontier = Self {
+            conn,
+            seen: BloomFilter::new(),
+            next_seq: 0,
+            queued: 0,
+        };
+        // One crawl runs at a time, the frontier left behind is only kept to resume it
+        let owner: Option<String> = frontier
+            .conn
+            .query_row("SELECT domain FROM frontier_meta LIMIT 1", [], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        if resume && owner.as_deref() == Some(domain) {
+            frontier.reopen()?;
+        } else {
+            frontier.conn.execute_batch(
+                "DELETE FROM frontier;
+                 DELETE FROM frontier_meta;",
+            )?;
+            frontier.conn.execute(
+                "INSERT INTO frontier_meta (domain) VALUES (?1)",
+                params![domain],
+            )?;
+        }
+        Ok(frontier)
+    }
+
+    // Picks up the frontier of an interrupted crawl, with its in-flight URLs queued again
+    fn reopen(&mut self) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "UPDATE frontier SET state = ?1 WHERE state = ?2",
+            params![QUEUED, IN_FLIGHT],
+        )?;
+        {
+            let mut stmt = self.conn.prepare("SELECT url FROM frontier")?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let url: String = row.get(0)?;
+                self.seen.insert(&url);
+            }
+        }
+        let (next_seq, queued): (i64, i64) = self.conn.query_row(
+            "SELECT COALESCE(MAX(seq), 0), COALESCE(SUM(state = ?1), 0) FROM frontier",
+            params![QUEUED],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        self.next_seq = next_seq;
+        self.queued = queued as usize;
+        Ok(())
+    }
+
+    fn begin(&self) -> Result<(), DatabaseError> {
+        if self.conn.is_autocommit() {
+            self.conn.execute_batch("BEGIN")?;
+        }
+        Ok(())
+    }
+
+    fn commit(&self) -> Result<(), DatabaseError> {
+        if !self.conn.is_autocommit() {
+            self.conn.execute_batch("COMMIT")?;
+        }
+        Ok(())
+    }
+
+    fn state_of(&self, url: &str) -> Result<Option<i64>, DatabaseError> {
+        if !self.seen.may_contain(url) {
+            return Ok(None);
+        }
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT state FROM frontier WHERE url = ?1",
+                params![url],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    fn push(&mut self, url: &str, depth: Option<usize>) -> Result<bool, DatabaseError> {
+        if self.state_of(url)?.is_some() {
+            return Ok(false);
+        }
+        self.next_seq += 1;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO frontier (url, depth, seq, state) VALUES (?1, ?2, ?3, ?4)",
+            params![url, depth.map(|d| d as i64), self.next_seq, QUEUED],
+        )?;
+        self.seen.insert(url);
+        self.queued += 1;
+        Ok(true)
+    }
+
+    fn requeue(&mut self, url: &str) -> Result<(), DatabaseError> {
+        self.next_seq += 1;
+        let updated = self.conn.execute(
+            "UPDATE frontier SET state = ?1, seq = ?2 WHERE url = ?3 AND state = ?4",
+            params![QUEUED, self.next_seq, url, IN_FLIGHT],
+        )?;
+        self.queued += updated;
+        Ok(())
+    }
+
+    // A saved crawl that never stored the page's result wants it fetched again
+    fn unvisit(&mut self, url: &str) -> Result<(), DatabaseError> {
+        self.next_seq += 1;
+        let updated = self.conn.execute(
+            "UPDATE frontier SET state = ?1, seq = ?2 WHERE url = ?3 AND state = ?4",
+            params![QUEUED, self.next_seq, url, VISITED],
+        )?;
+        self.queued += updated;
+        Ok(())
+    }
+
+    fn pop_batch(&mut self, size: usize) -> Result<Vec<Url>, DatabaseError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let urls: Vec<String> = {
+            let mut stmt = tx.prepare_cached(
+                "SELECT url FROM frontier WHERE state = ?1 ORDER BY seq LIMIT ?2",
+            )?;
+            let rows = stmt.query_map(params![QUEUED, size as i64], |row| row.get(0))?;
+            rows.collect::<Result<_, _>>()?
+        };
+        {
+            let mut stmt = tx.prepare_cached("UPDATE frontier SET state = ?1 WHERE url = ?2")?;
+            for url in &urls {
+                stmt.execute(params![IN_FLIGHT, url])?;
+            }
+        }
+        tx.commit()?;
+
+        self.queued = self.queued.saturating_sub(urls.len());
+        Ok(urls.iter().filter_map(|url| Url::parse(url).ok()).collect())
+    }
+
+    fn mark_visited(&mut self, url: &str) -> Result<(), DatabaseError> {
+        self.next_seq += 1;
+        self.conn.execute(
+            "INSERT INTO frontier (url, depth, seq, state) VALUES (?1, NULL, ?2, ?3)
+             ON CONFLICT(url) DO UPDATE SET state = excluded.state",
+            params![url, self.next_seq, VISITED],
+        )?;
+        self.seen.insert(url);
+        Ok(())
+    }
+
+    fn depth(&self, url: &str) -> Result<Option<usize>, DatabaseError> {
+        if !self.seen.may_contain(url) {
+            return Ok(None);
+        }
+        let depth: Option<Option<i64>> = self
+            .conn
+            .query_row(
+                "SELECT depth FROM frontier WHERE url = ?1",
+                params![url],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(depth.flatten().map(|d| d as usize))
+    }
+}
+
+#[derive(Default)]
+struct MemoryFrontier {
+    queue: VecDeque<Url>,
+    /// Queued and in-flight URLs
+    pending: HashSet<String>,
+    visited: HashSet<String>,
+    depths: HashMap<String, usize>,
+}
+
+enum Backend {
+    Memory(MemoryFrontier),
+    Disk(DiskFrontier),
+}
+
+/// The URLs waiting to be crawled and the ones already seen, deduplicated.
+///
+/// Kept in memory by default. With `disk_frontier` on, both live in a SQLite file in front
+/// of a bloom filter, trading some speed for memory that no longer grows with the site.
+pub struct Frontier {
+    backend: Backend,
+}
+
+impl Frontier {
+    pub fn in_memory() -> Self {
+        Self {
+            backend: Backend::Memory(MemoryFrontier::default()),
+        }
+    }
+
+    /// Opens the disk frontier, keeping the one left by an interrupted crawl of `domain` when resuming.
+    pub fn on_disk(db_dir: &Path, domain: &str, resume: bool) -> Result<Self, String> {
+        let frontier = DiskFrontier::open(db_dir, domain, resume).map_err(|e| e.to_string())?;
+        Ok(Self {
+            backend: Backend::Disk(frontier),
+        })
+    }
+
+    fn log<T: Default>(result: Result<T, DatabaseError>) -> T {
+        result.unwrap_or_else(|e| {
+            eprintln!("Frontier database error: {}", e);
+            T::default()
+        })
+    }
+
+    /// Queues `url` unless it was queued or visited before, returning whether it was added.
+    pub fn push(&mut self, url: Url, depth: Option<usize>) -> bool {
+        match &mut self.backend {
+            Backend::Memory(memory) => {
+                let key = url.to_string();
+                if memory.visited.contains(&key) || !memory.pending.insert(key.clone()) {
+                    return false;
+                }
+                if let Some(depth) = depth {
+                    memory.depths.insert(key, depth);
+                }
+                memory.queue.push_back(url);
+                true
+            }
+            Backend::Disk(disk) => Self::log(disk.push(url.as_str(), depth)),
+        }
+    }
+
+    /// Hands an in-flight URL back to the queue, for fetches to retry in a later batch.
+    pub fn requeue(&mut self, url: Url) {
+        match &mut self.backend {
+            Backend::Memory(memory) => {
+                if !memory.visited.contains(url.as_str()) {
+                    memory.queue.push_back(url);
+                }
+            }
+            Backend::Disk(disk) => Self::log(disk.requeue(url.as_str())),
+        }
+    }
+
+    /// Takes up to `size` URLs off the front of the queue.
+    pub fn pop_batch(&mut self, size: usize) -> Vec<Url> {
+        match &mut self.backend {
+            Backend::Memory(memory) => {
+                let size = size.min(memory.queue.len());
+                memory.queue.drain(..size).collect()
+            }
+            Backend::Disk(disk) => Self::log(disk.pop_batch(size)),
+        }
+    }
+
+    pub fn mark_visited(&mut self, url: &str) {
+        match &mut self.backend {
+            Backend::Memory(memory) => {
+                memory.pending.remove(url);
+                memory.visited.insert(url.to_string());
+            }
+            Backend::Disk(disk) => Self::log(disk.mark_visited(url)),
+        }
+    }
+
+    pub fn is_visited(&self, url: &str) -> bool {
+        match &self.backend {
+            Backend::Memory(memory) => memory.visited.contains(url),
+            Backend::Disk(disk) => Self::log(disk.state_of(url)) == Some(VISITED),
+        }
+    }
+
+    /// Clicks from the start URL, None for URLs saved before crawls kept their depth.
+    pub fn depth(&self, url: &str) -> Option<usize> {
+        match &self.backend {
+            Backend::Memory(memory) => memory.depths.get(url).copied(),
+            Backend::Disk(disk) => Self::log(disk.depth(url)),
+        }
+    }
+
+    /// Number of URLs waiting in the queue.
+    pub fn len(&self) -> usize {
+        match &self.backend {
+            Backend::Memory(memory) => memory.queue.len(),
+            Backend::Disk(disk) => disk.queued,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Groups the pushes and visits that follow into one transaction on disk, until `commit`.
+    pub fn begin(&mut self) {
+        if let Backend::Disk(disk) = &self.backend {
+            Self::log(disk.begin());
+        }
+    }
+
+    pub fn commit(&mut self) {
+        if let Backend::Disk(disk) = &self.backend {
+            Self::log(disk.commit());
+        }
+    }
+
+    /// Restores one URL of a saved crawl, queued again or visited.
+    pub fn restore(&mut self, url: &str, visited: bool, depth: Option<usize>) {
+        if visited {
+            self.mark_visited(url);
+            return;
+        }
+        let Ok(parsed) = Url::parse(url) else {
+            return;
+        };
+        if !self.push(parsed, depth) {
+            if let Backend::Disk(disk) = &mut self.backend {
+                Self::log(disk.unvisit(url));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(path: &str) -> Url {
+        Url::parse(&format!("https://example.com/{}", path)).unwrap()
+    }
+
+    fn popped(frontier: &mut Frontier) -> Vec<String> {
+        frontier
+            .pop_batch(100)
+            .iter()
+            .map(|url| url.path().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn bloom_filter_never_misses_an_inserted_url() {
+        let mut filter = BloomFilter::new();
+        for i in 0..10_000 {
+            filter.insert(&format!("https://example.com/page/{}", i));
+        }
+        assert!((0..10_000).all(|i| filter.may_contain(&format!("https://example.com/page/{}", i))));
+
+        let false_positives = (0..10_000)
+            .filter(|i| filter.may_contain(&format!("https://example.com/other/{}", i)))
+            .count();
+        assert!(false_positives < 10);
+    }
+
+    #[test]
+    fn disk_frontier_deduplicates_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut frontier = Frontier::on_disk(dir.path(), "example.com", false).unwrap();
+
+        assert!(frontier.push(url("a"), Some(0)));
+        assert!(frontier.push(url("b"), Some(1)));
+        assert!(!frontier.push(url("a"), Some(2)));
+        assert_eq!(frontier.len(), 2);
+
+        assert_eq!(popped(&mut frontier), ["/a", "/b"]);
+        assert!(frontier.is_empty());
+        frontier.mark_visited(url("a").as_str());
+        assert!(frontier.is_visited(url("a").as_str()));
+        assert!(!frontier.push(url("a"), Some(0)));
+        assert_eq!(frontier.depth(url("b").as_str()), Some(1));
+    }
+
+    #[test]
+    fn resume_requeues_the_urls_in_flight() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let mut frontier = Frontier::on_disk(dir.path(), "example.com", false).unwrap();
+            frontier.begin();
+            for (depth, path) in ["a", "b", "c"].iter().enumerate() {
+                frontier.push(url(path), Some(depth));
+            }
+            frontier.commit();
+            // The crawl stops with a visited and b still being fetched
+            frontier.pop_batch(2);
+            frontier.mark_visited(url("a").as_str());
+        }
+
+        let mut resumed = Frontier::on_disk(dir.path(), "example.com", true).unwrap();
+        assert_eq!(resumed.len(), 2);
+        assert!(resumed.is_visited(url("a").as_str()));
+        assert!(!resumed.push(url("a"), Some(0)));
+        assert!(!resumed.push(url("c"), Some(0)));
+        assert_eq!(resumed.depth(url("c").as_str()), Some(2));
+        assert_eq!(popped(&mut resumed), ["/b", "/c"]);
+    }
+
+    #[test]
+    fn another_domain_starts_afresh() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let mut frontier = Frontier::on_disk(dir.path(), "example.com", false).unwrap();
+            frontier.push(url("a"), None);
+            frontier.mark_visited(url("b").as_str());
+        }
+
+        let mut other = Frontier::on_disk(dir.path(), "other.com", true).unwrap();
+        assert!(other.is_empty());
+        assert!(!other.is_visited(url("b").as_str()));
+        assert!(other.push(url("a"), None));
+    }
+
+    #[test]
+    fn restore_queues_again_what_a_saved_crawl_did_not_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut frontier = Frontier::on_disk(dir.path(), "example.com", false).unwrap();
+        frontier.mark_visited(url("a").as_str());
+
+        frontier.restore(url("a").as_str(), false, Some(0));
+        frontier.restore(url("b").as_str(), true, None);
+        assert!(!frontier.is_visited(url("a").as_str()));
+        assert!(frontier.is_visited(url("b").as_str()));
+        assert_eq!(popped(&mut frontier), ["/a"]);
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::crawler::libs::get_google_analytics_id;
+
+use super::gsc::{access_token, GoogleApi};
+use super::reports::summary::page_issues;
+use super::results_store::ResultsStore;
+
+const GOOGLE_ANALYTICS: GoogleApi = GoogleApi {
+    name: "Google Analytics",
+    scope: "https://www.googleapis.com/auth/analytics.readonly",
+    token_cache: "ga_tokencache.json",
+    auth_event: "ga4_auth_url",
+};
+const ROWS_PER_REQUEST: usize = 100_000;
+const MAX_ROWS: usize = 250_000;
+// GA4 renamed conversions to key events
+const METRICS: [&str; 4] = [
+    "sessions",
+    "engagedSessions",
+    "averageSessionDuration",
+    "keyEvents",
+];
+
+/// URL, status code and the weighted issues of a crawled page.
+pub type CrawledPage = (String, u16, Vec<(&'static str, usize)>);
+
+/// One row of a GA4 report, by landing page path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ga4Row {
+    pub landing_page: String,
+    pub sessions: f64,
+    pub engaged_sessions: f64,
+    /// Seconds
+    pub average_session_duration: f64,
+    pub conversions: f64,
+}
+
+/// Analytics traffic of a URL joined onto its crawl result and audit issues.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ga4PageMetrics {
+    pub url: String,
+    pub crawled: bool,
+    pub status_code: Option<u16>,
+    pub sessions: f64,
+    pub engaged_sessions: f64,
+    pub engagement_rate: f64,
+    pub average_session_duration: f64,
+    pub conversions: f64,
+    pub issues: Vec<String>,
+    /// Issues weighted by severity
+    pub issue_score: usize,
+    /// High for pages with many issues and little traffic, the ones to fix first
+    pub priority: f64,
+}
+
+impl Ga4PageMetrics {
+    fn new(url: String, crawled: bool) -> Self {
+        Ga4PageMetrics {
+            url,
+            crawled,
+            status_code: None,
+            sessions: 0.0,
+            engaged_sessions: 0.0,
+            engagement_rate: 0.0,
+            average_session_duration: 0.0,
+            conversions: 0.0,
+            issues: Vec::new(),
+            issue_score: 0,
+            priority: 0.0,
+        }
+    }
+}
+
+fn metric(row: &Value, index: usize) -> Option<f64> {
+    row.get("metricValues")?
+        .get(index)?
+        .get("value")?
+        .as_str()?
+        .parse()
+        .ok()
+}
+
+async fn fetch_rows(token: &str, property_id: &str, days: i64) -> Result<Vec<Ga4Row>, String> {
+    let client = reqwest::Client::new();
+    let endpoint = format!(
+        "https://analyticsdata.googleapis.com/v1beta/properties/{}:runReport",
+        property_id
+    );
+    let metrics: Vec<Value> = METRICS.iter().map(|name| json!({ "name": name })).collect();
+
+    let mut rows = Vec::new();
+    let mut offset = 0;
+    loop {
+        let body = json!({
+            "dateRanges": [{ "startDate": format!("{}daysAgo", days), "endDate": "today" }],
+            "dimensions": [{ "name": "landingPage" }],
+            "metrics": metrics,
+            "limit": ROWS_PER_REQUEST,
+            "offset": offset,
+        });
+
+        let response: Value = client
+            .post(&endpoint)
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("Google Analytics request failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse Google Analytics response: {}", e))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(format!("Google Analytics API error: {}", error));
+        }
+
+        let page = response
+            .get("rows")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        let fetched = page.len();
+        offset += fetched;
+        rows.extend(page.iter().filter_map(|row| {
+            let landing_page = row.get("dimensionValues")?.get(0)?.get("value")?.as_str()?;
+            // Sessions without a landing page are reported as "(not set)"
+            if !landing_page.starts_with('/') {
+                return None;
+            }
+            Some(Ga4Row {
+                landing_page: landing_page.to_string(),
+                sessions: metric(row, 0)?,
+                engaged_sessions: metric(row, 1)?,
+                average_session_duration: metric(row, 2)?,
+                conversions: metric(row, 3)?,
+            })
+        }));
+
+        let total = response
+            .get("rowCount")
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as usize;
+        if fetched < ROWS_PER_REQUEST || offset >= total || offset >= MAX_ROWS {
+            break;
+        }
+    }
+
+    Ok(rows)
+}
+
+// Landing pages and crawled URLs are compared the way the url crate normalizes paths
+fn normalize_path(path: &str) -> String {
+    Url::parse("http://localhost")
+        .and_then(|base| base.join(path))
+        .map(|url| url.path().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+/// Sums the landing page rows per path and joins them onto the crawled pages.
+///
+/// GA4 reports landing pages without the host, so pages are matched on their path.
+pub fn join_with_crawl(rows: &[Ga4Row], pages: Vec<CrawledPage>) -> Vec<Ga4PageMetrics> {
+    let mut metrics: HashMap<String, Ga4PageMetrics> = HashMap::new();
+    for (url, status_code, issues) in pages {
+        let Ok(parsed) = Url::parse(&url) else {
+            continue;
+        };
+        let mut page = Ga4PageMetrics::new(url, true);
+        page.status_code = Some(status_code);
+        page.issue_score = issues.iter().map(|(_, weight)| weight).sum();
+        page.issues = issues.iter().map(|(name, _)| name.to_string()).collect();
+        metrics.insert(parsed.path().to_string(), page);
+    }
+
+    // Session weighted before dividing by the sessions of the path
+    let mut total_duration: HashMap<String, f64> = HashMap::new();
+    for row in rows {
+        let key = normalize_path(&row.landing_page);
+        let entry = metrics
+            .entry(key.clone())
+            .or_insert_with(|| Ga4PageMetrics::new(row.landing_page.clone(), false));
+        entry.sessions += row.sessions;
+        entry.engaged_sessions += row.engaged_sessions;
+        entry.conversions += row.conversions;
+        *total_duration.entry(key).or_insert(0.0) += row.average_session_duration * row.sessions;
+    }
+
+    let mut joined: Vec<Ga4PageMetrics> = metrics
+        .into_iter()
+        .map(|(key, mut page)| {
+            if page.sessions > 0.0 {
+                page.engagement_rate = page.engaged_sessions / page.sessions;
+                page.average_session_duration =
+                    total_duration.get(&key).copied().unwrap_or(0.0) / page.sessions;
+            }
+            // Traffic damps the score logarithmically, a page never divides by zero
+            page.priority = page.issue_score as f64 / (2.0 + page.sessions).ln();
+            page
+        })
+        .collect();
+
+    joined.sort_by(|a, b| {
+        b.priority
+            .total_cmp(&a.priority)
+            .then(a.sessions.total_cmp(&b.sessions))
+            .then_with(|| a.url.cmp(&b.url))
+    });
+    joined
+}
+
+// PULL GOOGLE ANALYTICS LANDING PAGES AND STORE THEM WITH A CRAWL
+#[tauri::command]
+pub async fn fetch_ga4_for_crawl(
+    crawl_id: i64,
+    property_id: Option<String>,
+    days: Option<i64>,
+    app_handle: tauri::AppHandle,
+) -> Result<usize, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    store
+        .crawl(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Crawl {} not found", crawl_id))?;
+
+    // Falls back to the property saved with the Google Analytics settings
+    let property_id = match property_id {
+        Some(property_id) => property_id,
+        None => get_google_analytics_id().await?,
+    };
+    let property_id = property_id
+        .trim()
+        .trim_matches('"')
+        .trim_start_matches("properties/")
+        .to_string();
+    if property_id.is_empty() {
+        return Err("Set the GA4 property ID to pull analytics data".to_string());
+    }
+
+    let token = access_token(&app_handle, &GOOGLE_ANALYTICS).await?;
+    let rows = fetch_rows(&token, &property_id, days.unwrap_or(90)).await?;
+
+    store
+        .replace_ga4_rows(crawl_id, &rows)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    println!(
+        "Stored {} Google Analytics landing pages for crawl {}",
+        rows.len(),
+        crawl_id
+    );
+    Ok(rows.len())
+}
+
+// GET THE ANALYTICS TRAFFIC AND ISSUES OF THE CRAWLED PAGES, WORST FIRST
+#[tauri::command]
+pub async fn get_ga4_page_metrics(crawl_id: i64) -> Result<Vec<Ga4PageMetrics>, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let rows = store.ga4_rows(crawl_id).await.map_err(|e| e.to_string())?;
+
+    let collected = Arc::new(Mutex::new(Vec::new()));
+    let sink = collected.clone();
+    store
+        .for_each_page(crawl_id, move |page| {
+            let issues = page_issues(&page)
+                .into_iter()
+                .map(|kind| (kind.name(), kind.severity().weight()))
+                .collect();
+            sink.lock()
+                .map_err(|e| e.to_string())?
+                .push((page.url, page.status_code, issues));
+            Ok(())
+        })
+        .await?;
+    let pages = std::mem::take(&mut *collected.lock().map_err(|e| e.to_string())?);
+
+    Ok(join_with_crawl(&rows, pages))
+}
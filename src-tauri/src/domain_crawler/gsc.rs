@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::Emitter;
+use url::Url;
+use yup_oauth2::authenticator_delegate::InstalledFlowDelegate;
+
+use crate::crawler::libs::{
+    google_access_token, read_credentials_file, search_analytics_query, search_console_site_url,
+    SEARCH_CONSOLE_SCOPE,
+};
+
+use super::helpers::canonical_selector::normalise_url;
+use super::results_store::ResultsStore;
+
+// The API caps a single response at 25k rows
+const ROWS_PER_REQUEST: usize = 25_000;
+const MAX_ROWS: usize = 250_000;
+const TOP_QUERIES: usize = 10;
+
+/// One row of the Search Analytics API, by page and query.
+///
+/// Rows with an empty `query` hold the page's totals, which include the anonymized
+/// queries the per-query rows leave out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GscRow {
+    pub page: String,
+    pub query: String,
+    pub clicks: f64,
+    pub impressions: f64,
+    pub ctr: f64,
+    pub position: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GscQuery {
+    pub query: String,
+    pub clicks: f64,
+    pub impressions: f64,
+    pub position: f64,
+}
+
+/// Search traffic of a URL joined onto its crawl result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GscPageMetrics {
+    pub url: String,
+    pub crawled: bool,
+    pub status_code: Option<u16>,
+    pub indexability: Option<f32>,
+    pub clicks: f64,
+    pub impressions: f64,
+    pub ctr: f64,
+    /// Impression weighted average position
+    pub position: Option<f64>,
+    pub top_queries: Vec<GscQuery>,
+}
+
+/// Hands the Google consent URL to the frontend instead of printing it to stdout.
+struct TauriFlowDelegate {
+    app_handle: tauri::AppHandle,
+    event: &'static str,
+}
+
+impl InstalledFlowDelegate for TauriFlowDelegate {
+    fn present_user_url<'a>(
+        &'a self,
+        url: &'a str,
+        _need_code: bool,
+    ) -> Pin<Box<dyn Future<Output = Result<String, String>> + Send + 'a>> {
+        Box::pin(async move {
+            self.app_handle
+                .emit(self.event, url)
+                .map_err(|e| format!("Failed to emit event: {}", e))?;
+            Ok(String::new())
+        })
+    }
+}
+
+/// A Google API that is authorised through the installed app flow with `client_secret.json`.
+pub(crate) struct GoogleApi {
+    pub name: &'static str,
+    pub scope: &'static str,
+    pub token_cache: &'static str,
+    /// Event the consent URL is emitted on
+    pub auth_event: &'static str,
+}
+
+const SEARCH_CONSOLE: GoogleApi = GoogleApi {
+    name: "Search Console",
+    scope: SEARCH_CONSOLE_SCOPE,
+    token_cache: "tokencache.json",
+    auth_event: "gsc_auth_url",
+};
+
+/// Runs the OAuth flow, or reuses the cached token, and returns a bearer token.
+pub(crate) async fn access_token(
+    app_handle: &tauri::AppHandle,
+    api: &GoogleApi,
+) -> Result<String, String> {
+    google_access_token(
+        api.name,
+        api.scope,
+        api.token_cache,
+        Some(Box::new(TauriFlowDelegate {
+            app_handle: app_handle.clone(),
+            event: api.auth_event,
+        })),
+    )
+    .await
+}
+
+// Pages every row of one dimension set, `query` is left empty for page-only rows
+async fn fetch_rows(
+    token: &str,
+    site_url: &str,
+    days: i64,
+    dimensions: &[&str],
+) -> Result<Vec<GscRow>, String> {
+    let end_date = Utc::now().format("%Y-%m-%d").to_string();
+    let start_date = (Utc::now() - chrono::Duration::days(days))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let mut rows = Vec::new();
+    loop {
+        let body = json!({
+            "startDate": start_date,
+            "endDate": end_date,
+            "dimensions": dimensions,
+            "searchType": "web",
+            "rowLimit": ROWS_PER_REQUEST,
+            "startRow": rows.len(),
+        });
+        let response = search_analytics_query(token, site_url, &body).await?;
+
+        let page: Vec<GscRow> = response
+            .get("rows")
+            .and_then(Value::as_array)
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| {
+                        let keys = row.get("keys")?.as_array()?;
+                        Some(GscRow {
+                            page: keys.first()?.as_str()?.to_string(),
+                            query: keys
+                                .get(1)
+                                .and_then(Value::as_str)
+                                .unwrap_or_default()
+                                .to_string(),
+                            clicks: row.get("clicks")?.as_f64()?,
+                            impressions: row.get("impressions")?.as_f64()?,
+                            ctr: row.get("ctr")?.as_f64()?,
+                            position: row.get("position")?.as_f64()?,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let fetched = page.len();
+        rows.extend(page);
+        if fetched < ROWS_PER_REQUEST || rows.len() >= MAX_ROWS {
+            break;
+        }
+    }
+
+    Ok(rows)
+}
+
+pub(crate) fn normalise(url: &str) -> String {
+    Url::parse(url)
+        .map(|url| normalise_url(&url))
+        .unwrap_or_else(|_| url.to_string())
+}
+
+/// Aggregates the page and query rows per URL and joins them onto the crawled pages.
+///
+/// Stores from before page totals were fetched fall back to summing the query rows.
+pub fn join_with_crawl(rows: &[GscRow], pages: &[(String, u16, f32)]) -> Vec<GscPageMetrics> {
+    let mut metrics: HashMap<String, GscPageMetrics> = HashMap::new();
+    let mut weighted_position: HashMap<String, f64> = HashMap::new();
+
+    for (url, status_code, indexability) in pages {
+        metrics.insert(
+            normalise(url),
+            GscPageMetrics {
+                url: url.clone(),
+                crawled: true,
+                status_code: Some(*status_code),
+                indexability: Some(*indexability),
+                clicks: 0.0,
+                impressions: 0.0,
+                ctr: 0.0,
+                position: None,
+                top_queries: Vec::new(),
+            },
+        );
+    }
+
+    // Page totals win over the sum of the query rows, which misses anonymized queries
+    let totals: HashMap<String, &GscRow> = rows
+        .iter()
+        .filter(|row| row.query.is_empty())
+        .map(|row| (normalise(&row.page), row))
+        .collect();
+
+    for row in rows {
+        let key = normalise(&row.page);
+        let entry = metrics
+            .entry(key.clone())
+            .or_insert_with(|| GscPageMetrics {
+                url: row.page.clone(),
+                crawled: false,
+                status_code: None,
+                indexability: None,
+                clicks: 0.0,
+                impressions: 0.0,
+                ctr: 0.0,
+                position: None,
+                top_queries: Vec::new(),
+            });
+        if row.query.is_empty() {
+            entry.clicks = row.clicks;
+            entry.impressions = row.impressions;
+            *weighted_position.entry(key).or_insert(0.0) = row.position * row.impressions;
+            continue;
+        }
+        entry.top_queries.push(GscQuery {
+            query: row.query.clone(),
+            clicks: row.clicks,
+            impressions: row.impressions,
+            position: row.position,
+        });
+        if !totals.contains_key(&key) {
+            entry.clicks += row.clicks;
+            entry.impressions += row.impressions;
+            *weighted_position.entry(key).or_insert(0.0) += row.position * row.impressions;
+        }
+    }
+
+    let mut joined: Vec<GscPageMetrics> = metrics
+        .into_iter()
+        .map(|(key, mut page)| {
+            if page.impressions > 0.0 {
+                page.ctr = page.clicks / page.impressions;
+                page.position = weighted_position
+                    .get(&key)
+                    .map(|sum| sum / page.impressions);
+            }
+            page.top_queries.sort_by(|a, b| {
+                b.clicks
+                    .total_cmp(&a.clicks)
+                    .then(b.impressions.total_cmp(&a.impressions))
+            });
+            page.top_queries.truncate(TOP_QUERIES);
+            page
+        })
+        .collect();
+
+    joined.sort_by(|a, b| {
+        b.clicks
+            .total_cmp(&a.clicks)
+            .then(b.impressions.total_cmp(&a.impressions))
+    });
+    joined
+}
+
+// PULL SEARCH CONSOLE DATA AND STORE IT WITH A CRAWL
+#[tauri::command]
+pub async fn fetch_gsc_for_crawl(
+    crawl_id: i64,
+    site_url: Option<String>,
+    days: Option<i64>,
+    app_handle: tauri::AppHandle,
+) -> Result<usize, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let crawl = store
+        .crawl(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Crawl {} not found", crawl_id))?;
+
+    // The property saved with the Search Console credentials wins over the crawl domain
+    let site_url = match site_url {
+        Some(site_url) => site_url,
+        None => match read_credentials_file().await {
+            Ok(info) if !info.url.is_empty() => search_console_site_url(&info),
+            _ => crawl.domain.clone(),
+        },
+    };
+
+    let token = access_token(&app_handle, &SEARCH_CONSOLE).await?;
+    let days = days.unwrap_or(90);
+    let mut rows = fetch_rows(&token, &site_url, days, &["page"]).await?;
+    rows.extend(fetch_rows(&token, &site_url, days, &["page", "query"]).await?);
+
+    store
+        .replace_gsc_rows(crawl_id, &rows)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    println!(
+        "Stored {} Search Console rows for crawl {}",
+        rows.len(),
+        crawl_id
+    );
+    Ok(rows.len())
+}
+
+// GET THE SEARCH TRAFFIC OF THE CRAWLED PAGES
+#[tauri::command]
+pub async fn get_gsc_page_metrics(crawl_id: i64) -> Result<Vec<GscPageMetrics>, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let rows = store.gsc_rows(crawl_id).await.map_err(|e| e.to_string())?;
+    let pages: Vec<(String, u16, f32)> = store
+        .page_rows(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|row| (row.url, row.status_code, row.indexability))
+        .collect();
+
+    Ok(join_with_crawl(&rows, &pages))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(page: &str, query: &str, clicks: f64, impressions: f64, position: f64) -> GscRow {
+        GscRow {
+            page: page.to_string(),
+            query: query.to_string(),
+            clicks,
+            impressions,
+            ctr: clicks / impressions,
+            position,
+        }
+    }
+
+    #[test]
+    fn totals_come_from_page_rows_when_present() {
+        let rows = vec![
+            row("https://example.com/a", "", 10.0, 200.0, 4.0),
+            row("https://example.com/a", "shoes", 6.0, 100.0, 3.0),
+            row("https://example.com/a/", "boots", 2.0, 50.0, 6.0),
+            row("https://example.com/b", "hats", 1.0, 10.0, 2.0),
+            row("https://example.com/b", "caps", 3.0, 30.0, 6.0),
+        ];
+        let pages = vec![("https://example.com/a".to_string(), 200, 1.0)];
+        let joined = join_with_crawl(&rows, &pages);
+
+        let a = joined
+            .iter()
+            .find(|p| p.url == "https://example.com/a")
+            .unwrap();
+        assert!(a.crawled);
+        assert_eq!((a.clicks, a.impressions), (10.0, 200.0));
+        assert_eq!(a.ctr, 0.05);
+        assert_eq!(a.position, Some(4.0));
+        let queries: Vec<&str> = a.top_queries.iter().map(|q| q.query.as_str()).collect();
+        assert_eq!(queries, ["shoes", "boots"]);
+
+        // Stored before page totals were fetched, the query rows are summed
+        let b = joined
+            .iter()
+            .find(|p| p.url == "https://example.com/b")
+            .unwrap();
+        assert!(!b.crawled);
+        assert_eq!((b.clicks, b.impressions), (4.0, 40.0));
+        assert_eq!(b.position, Some(5.0));
+    }
+}
@@ -1,14 +1,56 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
+use url::Url;
+
+// Alt texts that are an upload's file name rather than a description
+static FILENAME_ALT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(\S+\.(jpe?g|png|gif|webp|avif|svg|bmp|tiff?)|(img|dsc|dscn|dscf|pxl|pic|image|screenshot)[\s_-]*\d+)$")
+        .unwrap()
+});
+
+/// One `<img>` and how its alt text is set.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImageAlt {
+    pub src: Option<String>,
+    /// None when the attribute is absent
+    pub alt: Option<String>,
+    /// Empty alt, `role="presentation"` or `aria-hidden`, skipped by screen readers on purpose
+    pub decorative: bool,
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct AltTags {
     pub with_alt_tags: Vec<String>,
     pub without_alt_tags: Vec<String>,
     pub alt_tags_total: Vec<String>,
+    #[serde(default)]
+    pub images: Vec<ImageAlt>,
+}
+
+/// Tells whether an alt text is just the image's file name.
+pub fn is_filename_alt(alt: &str, src: Option<&str>) -> bool {
+    let alt = alt.trim();
+    if alt.is_empty() {
+        return false;
+    }
+    if FILENAME_ALT.is_match(alt) {
+        return true;
+    }
+    let Some(file) = src
+        .and_then(|src| Url::parse(src).ok())
+        .and_then(|url| url.path_segments()?.next_back().map(String::from))
+    else {
+        return false;
+    };
+    let stem = file
+        .rsplit_once('.')
+        .map_or(file.as_str(), |(stem, _)| stem);
+    alt.eq_ignore_ascii_case(&file) || alt.eq_ignore_ascii_case(stem)
 }
 
-pub fn get_alt_tags(html: &str) -> AltTags {
+pub fn get_alt_tags(html: &str, base_url: &Url) -> AltTags {
     let document = Html::parse_document(html);
     let selector = Selector::parse("img").unwrap();
     let images = document.select(&selector);
@@ -16,9 +58,11 @@ pub fn get_alt_tags(html: &str) -> AltTags {
     let mut with_alt_tags = Vec::new();
     let mut without_alt_tags = Vec::new();
     let mut alt_tags_total = Vec::new();
+    let mut image_alts = Vec::new();
 
     for image in images {
-        let alt_tag = image.value().attr("alt");
+        let value = image.value();
+        let alt_tag = value.attr("alt");
         match alt_tag {
             Some(alt) => {
                 with_alt_tags.push(alt.to_string());
@@ -29,11 +73,27 @@ pub fn get_alt_tags(html: &str) -> AltTags {
                 alt_tags_total.push(String::new());
             }
         }
+
+        let hidden = value
+            .attr("role")
+            .is_some_and(|role| role == "presentation" || role == "none")
+            || value.attr("aria-hidden") == Some("true");
+        image_alts.push(ImageAlt {
+            src: value
+                .attr("src")
+                .or_else(|| value.attr("data-src"))
+                .filter(|src| !src.starts_with("data:"))
+                .and_then(|src| base_url.join(src.trim()).ok())
+                .map(|url| url.to_string()),
+            alt: alt_tag.map(|alt| alt.trim().to_string()),
+            decorative: hidden || alt_tag.is_some_and(|alt| alt.trim().is_empty()),
+        });
     }
 
     AltTags {
         with_alt_tags,
         without_alt_tags,
         alt_tags_total,
+        images: image_alts,
     }
 }
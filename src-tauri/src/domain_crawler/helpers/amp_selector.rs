@@ -0,0 +1,31 @@
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AmpInfo {
+    /// The page is an AMP document, marked with `<html amp>` or `<html ⚡>`
+    pub is_amp: bool,
+    /// The AMP version linked with `<link rel="amphtml">`, resolved against the page
+    pub amphtml: Option<String>,
+}
+
+pub fn extract_amp(html: &str, page_url: &Url) -> AmpInfo {
+    let document = Html::parse_document(html);
+    let html_selector = Selector::parse("html").unwrap();
+    let amphtml_selector = Selector::parse("link[rel~='amphtml'][href]").unwrap();
+
+    let is_amp = document.select(&html_selector).next().is_some_and(|html| {
+        html.value()
+            .attrs()
+            .any(|(name, _)| name == "amp" || name == "⚡")
+    });
+    let amphtml = document
+        .select(&amphtml_selector)
+        .next()
+        .and_then(|link| link.value().attr("href"))
+        .and_then(|href| page_url.join(href.trim()).ok())
+        .map(|url| url.to_string());
+
+    AmpInfo { is_amp, amphtml }
+}
@@ -178,3 +178,50 @@ fn is_internal_link(url: &Url, base_url: &Url) -> bool {
     url.domain()
         .map_or(false, |domain| domain == base_url.domain().unwrap_or(""))
 }
+
+/// Resolves the internal hrefs of a page against the page URL, dropping fragments.
+///
+/// # Arguments
+/// * `links` - The links extracted from the page.
+/// * `page_url` - The URL of the page the links were found on.
+///
+/// # Returns
+/// The absolute internal link targets, in document order.
+pub fn resolved_internal_links(links: &InternalExternalLinks, page_url: &Url) -> Vec<Url> {
+    links
+        .internal
+        .links
+        .iter()
+        .filter_map(|href| page_url.join(href.trim()).ok())
+        .map(|mut url| {
+            url.set_fragment(None);
+            url
+        })
+        .collect()
+}
+
+/// Pairs each internal link target of a page with its anchor text, whitespace collapsed.
+///
+/// # Arguments
+/// * `links` - The links extracted from the page.
+/// * `page_url` - The URL of the page the links were found on.
+///
+/// # Returns
+/// The absolute internal link targets and their anchors, in document order.
+pub fn resolved_internal_anchors(
+    links: &InternalExternalLinks,
+    page_url: &Url,
+) -> Vec<(Url, String)> {
+    links
+        .internal
+        .links
+        .iter()
+        .zip(links.internal.anchors.iter())
+        .filter_map(|(href, anchor)| {
+            let mut url = page_url.join(href.trim()).ok()?;
+            url.set_fragment(None);
+            let anchor = anchor.split_whitespace().collect::<Vec<_>>().join(" ");
+            Some((url, anchor))
+        })
+        .collect()
+}
@@ -0,0 +1,198 @@
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::images_selector::{image_client, image_permit};
+use super::transfer_diagnostics::ACCEPT_ENCODING;
+use crate::domain_crawler::crawl_cache::CrawlCache;
+use crate::domain_crawler::subresources;
+
+// Shared scripts and stylesheets are checked once per crawl rather than once per page
+static ASSET_INFO: Lazy<CrawlCache<AssetInfo>> = Lazy::new(CrawlCache::default);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    Script,
+    Stylesheet,
+}
+
+/// What a HEAD request tells about an asset.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AssetInfo {
+    pub status_code: Option<u16>,
+    /// Content-Length as served, compressed when the server compresses
+    pub size: Option<u64>,
+    pub content_encoding: Option<String>,
+    pub cache_control: Option<String>,
+    pub max_age: Option<u64>,
+    pub has_validator: bool,
+    /// Browsers may reuse it without asking: a max-age or Expires and no no-store
+    pub cacheable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageAsset {
+    pub url: String,
+    pub kind: AssetKind,
+    pub in_head: bool,
+    /// Holds up the first paint while it downloads
+    pub render_blocking: bool,
+    pub third_party: bool,
+    pub info: Option<AssetInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PageAssets {
+    pub assets: Vec<PageAsset>,
+    pub render_blocking: usize,
+    /// Sum of the known asset sizes in bytes
+    pub total_size: u64,
+}
+
+/// Forgets the asset checks of the previous crawl.
+pub fn reset_asset_cache() {
+    ASSET_INFO.clear();
+}
+
+fn is_render_blocking(element: &ElementRef, kind: AssetKind) -> bool {
+    let value = element.value();
+    match kind {
+        AssetKind::Script => {
+            value.attr("async").is_none()
+                && value.attr("defer").is_none()
+                && !value
+                    .attr("type")
+                    .is_some_and(|t| t.eq_ignore_ascii_case("module"))
+        }
+        // Print and other non-matching media load without blocking
+        AssetKind::Stylesheet => {
+            value.attr("disabled").is_none()
+                && value.attr("media").map_or(true, |media| {
+                    let media = media.trim().to_lowercase();
+                    media.is_empty() || media == "all" || media.contains("screen")
+                })
+        }
+    }
+}
+
+/// Lists the external scripts and stylesheets of a page, flagging render-blocking ones.
+pub fn extract_assets(html: &str, page_url: &Url) -> Vec<PageAsset> {
+    let document = Html::parse_document(html);
+    let page_host = page_url.host_str().unwrap_or("").trim_start_matches("www.");
+    let mut assets: Vec<PageAsset> = Vec::new();
+
+    for (selector, attr, kind, in_head) in [
+        ("head script[src]", "src", AssetKind::Script, true),
+        ("body script[src]", "src", AssetKind::Script, false),
+        (
+            "head link[rel~='stylesheet'][href]",
+            "href",
+            AssetKind::Stylesheet,
+            true,
+        ),
+        (
+            "body link[rel~='stylesheet'][href]",
+            "href",
+            AssetKind::Stylesheet,
+            false,
+        ),
+    ] {
+        let selector = Selector::parse(selector).unwrap();
+        for element in document.select(&selector) {
+            let Some(url) = element
+                .value()
+                .attr(attr)
+                .and_then(|src| page_url.join(src.trim()).ok())
+            else {
+                continue;
+            };
+            if !url.scheme().starts_with("http") || assets.iter().any(|a| a.url == url.as_str()) {
+                continue;
+            }
+            let host = url.host_str().unwrap_or("").trim_start_matches("www.");
+            assets.push(PageAsset {
+                url: url.to_string(),
+                kind,
+                in_head,
+                render_blocking: in_head && is_render_blocking(&element, kind),
+                third_party: host != page_host && !host.ends_with(&format!(".{}", page_host)),
+                info: None,
+            });
+        }
+    }
+    assets
+}
+
+fn max_age(cache_control: &str) -> Option<u64> {
+    cache_control
+        .split(',')
+        .filter_map(|directive| directive.trim().split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("max-age"))
+        .and_then(|(_, age)| age.trim().trim_matches('"').parse().ok())
+}
+
+async fn fetch_asset_info(url: &str) -> AssetInfo {
+    let _permit = image_permit().await;
+    let request = image_client()
+        .head(url)
+        .header(reqwest::header::ACCEPT_ENCODING, ACCEPT_ENCODING);
+    let response = match subresources::send(request, url).await {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("Failed to check asset {}: {}", url, e);
+            return AssetInfo::default();
+        }
+    };
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    };
+    let cache_control = header(reqwest::header::CACHE_CONTROL);
+    let max_age = cache_control.as_deref().and_then(max_age);
+    let no_store = cache_control
+        .as_deref()
+        .is_some_and(|cc| cc.to_lowercase().contains("no-store"));
+
+    AssetInfo {
+        status_code: Some(response.status().as_u16()),
+        size: header(reqwest::header::CONTENT_LENGTH).and_then(|s| s.parse().ok()),
+        content_encoding: header(reqwest::header::CONTENT_ENCODING),
+        has_validator: header(reqwest::header::ETAG).is_some()
+            || header(reqwest::header::LAST_MODIFIED).is_some(),
+        cacheable: !no_store
+            && (max_age.is_some_and(|age| age > 0) || header(reqwest::header::EXPIRES).is_some()),
+        cache_control,
+        max_age,
+    }
+}
+
+/// Checks an asset with HEAD once per crawl, later calls get the cached result.
+pub(crate) async fn asset_info(url: String) -> AssetInfo {
+    ASSET_INFO
+        .get_or_init(&url, || fetch_asset_info(&url))
+        .await
+}
+
+/// Extracts the page's scripts and stylesheets and checks their size and caching with HEAD.
+pub async fn extract_assets_with_sizes(html: &str, page_url: &Url) -> PageAssets {
+    let mut assets = extract_assets(html, page_url);
+    let infos = join_all(assets.iter().map(|asset| asset_info(asset.url.clone()))).await;
+    for (asset, info) in assets.iter_mut().zip(infos) {
+        asset.info = Some(info);
+    }
+
+    PageAssets {
+        render_blocking: assets.iter().filter(|a| a.render_blocking).count(),
+        total_size: assets
+            .iter()
+            .filter_map(|a| a.info.as_ref().and_then(|i| i.size))
+            .sum(),
+        assets,
+    }
+}
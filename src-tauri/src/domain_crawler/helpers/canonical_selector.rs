@@ -1,9 +1,42 @@
 use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
 
 pub struct Canonicals {
     pub canonicals: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CanonicalKind {
+    SelfReferencing,
+    Canonicalized,
+    CrossDomain,
+    Missing,
+    Multiple,
+    Invalid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanonicalAudit {
+    pub canonical: Option<String>,
+    pub resolved: Option<String>,
+    pub kind: CanonicalKind,
+    pub relative: bool,
+    pub count: usize,
+}
+
+impl Default for CanonicalAudit {
+    fn default() -> Self {
+        Self {
+            canonical: None,
+            resolved: None,
+            kind: CanonicalKind::Missing,
+            relative: false,
+            count: 0,
+        }
+    }
+}
+
 pub fn get_canonical(body: &str) -> Option<Canonicals> {
     let document = Html::parse_document(body); // No need for &body here
     let canonical_selector = Selector::parse("link[rel='canonical']").unwrap();
@@ -27,3 +60,55 @@ pub fn get_canonical(body: &str) -> Option<Canonicals> {
         None
     }
 }
+
+/// Normalises a URL for comparison by dropping the fragment and a trailing slash.
+pub fn normalise_url(url: &Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    let path = url.path().trim_end_matches('/').to_string();
+    url.set_path(&path);
+    url.to_string()
+}
+
+/// Compares URLs ignoring the fragment and a trailing slash on the path.
+pub fn same_url(a: &Url, b: &Url) -> bool {
+    normalise_url(a) == normalise_url(b)
+}
+
+/// Resolves the canonical tag against the page URL and classifies it.
+///
+/// # Arguments
+/// * `body` - The HTML content as a string.
+/// * `page_url` - The final URL of the page, used to resolve relative canonicals.
+///
+/// # Returns
+/// * `CanonicalAudit` - The declared and resolved canonical and how it relates to the page.
+pub fn audit_canonical(body: &str, page_url: &Url) -> CanonicalAudit {
+    let canonicals = get_canonical(body)
+        .map(|c| c.canonicals)
+        .unwrap_or_default();
+
+    let Some(first) = canonicals.first() else {
+        return CanonicalAudit::default();
+    };
+
+    let declared = first.trim();
+    let relative = Url::parse(declared).is_err();
+    let resolved = page_url.join(declared).ok();
+
+    let kind = match &resolved {
+        _ if canonicals.len() > 1 => CanonicalKind::Multiple,
+        None => CanonicalKind::Invalid,
+        Some(url) if url.host_str() != page_url.host_str() => CanonicalKind::CrossDomain,
+        Some(url) if same_url(url, page_url) => CanonicalKind::SelfReferencing,
+        Some(_) => CanonicalKind::Canonicalized,
+    };
+
+    CanonicalAudit {
+        canonical: Some(declared.to_string()),
+        resolved: resolved.map(|u| u.to_string()),
+        kind,
+        relative,
+        count: canonicals.len(),
+    }
+}
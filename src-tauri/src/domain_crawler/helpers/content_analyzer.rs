@@ -0,0 +1,121 @@
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+// Elements that never hold the main content of a page
+const BOILERPLATE_TAGS: [&str; 13] = [
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "nav", "header",
+    "footer", "aside", "form", "button",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ContentAnalysis {
+    pub word_count: usize,
+    pub text_length: usize,
+    pub html_length: usize,
+    /// Visible text as a percentage of the HTML size
+    pub text_ratio: f64,
+    pub thin: bool,
+    /// SimHash fingerprint of the visible text, 0 when there is too little text to compare
+    #[serde(default)]
+    pub simhash: u64,
+}
+
+fn collect_text(element: ElementRef, out: &mut String) {
+    for child in element.children() {
+        if let Some(text) = child.value().as_text() {
+            out.push_str(text);
+            out.push(' ');
+        } else if let Some(child) = ElementRef::wrap(child) {
+            let el = child.value();
+            let hidden = el.attr("hidden").is_some() || el.attr("aria-hidden") == Some("true");
+            if !hidden && !BOILERPLATE_TAGS.contains(&el.name()) {
+                collect_text(child, out);
+            }
+        }
+    }
+}
+
+/// Returns the visible text of the page body with navigation, scripts and other
+/// boilerplate removed and whitespace collapsed.
+pub fn visible_text(document: &Html) -> String {
+    let selector = Selector::parse("body").unwrap();
+    let mut text = String::new();
+    if let Some(body) = document.select(&selector).next() {
+        collect_text(body, &mut text);
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Words per shingle and the fewest shingles worth fingerprinting
+const SHINGLE_SIZE: usize = 3;
+const MIN_SHINGLES: usize = 10;
+
+// FNV-1a, stable across builds so stored fingerprints stay comparable
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Computes a 64-bit SimHash over word shingles of `text`.
+pub fn simhash(text: &str) -> u64 {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect();
+    if words.len() < SHINGLE_SIZE + MIN_SHINGLES - 1 {
+        return 0;
+    }
+
+    let mut weights = [0i32; 64];
+    for shingle in words.windows(SHINGLE_SIZE) {
+        let hash = fnv1a(&shingle.join(" "));
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash >> bit & 1 == 1 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0, |fingerprint, (bit, _)| fingerprint | 1 << bit)
+}
+
+/// Measures the visible content of a page and flags it as thin below `thin_threshold` words.
+///
+/// # Arguments
+/// * `body` - The HTML content as a string.
+/// * `thin_threshold` - The minimum number of words for a page not to be thin.
+///
+/// # Returns
+/// * `ContentAnalysis` - Word count, text-to-HTML ratio and the thin content flag.
+pub fn analyze_content(body: &str, thin_threshold: usize) -> ContentAnalysis {
+    let document = Html::parse_document(body);
+    let text = visible_text(&document);
+
+    let word_count = text
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count();
+    let html_length = body.len();
+    let text_length = text.len();
+    let text_ratio = if html_length == 0 {
+        0.0
+    } else {
+        text_length as f64 / html_length as f64 * 100.0
+    };
+
+    ContentAnalysis {
+        word_count,
+        text_length,
+        html_length,
+        text_ratio,
+        thin: word_count < thin_threshold,
+        simhash: simhash(&text),
+    }
+}
@@ -1,4 +1,7 @@
 use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use super::content_analyzer::visible_text;
 
 pub fn get_flesch_score(body: &str) -> Result<(f64, String), String> {
     // Parse the HTML document
@@ -86,3 +89,111 @@ fn classify_flesch_score(score: f64) -> String {
         _ => "Very Difficult".to_string(),
     }
 }
+
+// SMOG is defined over 30 sentences, shorter texts give unreliable grades
+const SMOG_MIN_SENTENCES: usize = 30;
+const MIN_READABILITY_WORDS: usize = 100;
+
+/// Readability of the main content of a page.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Readability {
+    /// The language the reading ease formula was picked for, None for the English formula
+    pub language: Option<String>,
+    /// Flesch Reading Ease, or its adaptation for `language`
+    pub reading_ease: f64,
+    pub classification: String,
+    /// US school grade from the Flesch-Kincaid formula, only meaningful for English
+    pub flesch_kincaid_grade: f64,
+    /// SMOG grade, None below 30 sentences
+    pub smog_grade: Option<f64>,
+    pub sentences: usize,
+    pub words: usize,
+    pub words_per_sentence: f64,
+    pub syllables_per_word: f64,
+}
+
+fn is_vowel(c: char) -> bool {
+    "aeiouyàáâãäåæèéêëìíîïòóôõöøùúûüýÿąęœ".contains(c)
+}
+
+// Counts vowel groups, the accented vowels make it usable beyond English
+fn count_word_syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+    let mut syllables = 0;
+    let mut prev_vowel = false;
+    for c in word.chars() {
+        let vowel = is_vowel(c);
+        if vowel && !prev_vowel {
+            syllables += 1;
+        }
+        prev_vowel = vowel;
+    }
+    if word.ends_with('e') && !word.ends_with("le") && syllables > 1 {
+        syllables -= 1;
+    }
+    syllables.max(1)
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// Scores the readability of the page's main content.
+///
+/// # Arguments
+/// * `body` - The HTML content as a string.
+/// * `language` - ISO 639-1 code of the text, picks the matching Flesch adaptation.
+///
+/// # Returns
+/// * `Option<Readability>` - None when there is too little text to score.
+pub fn readability(body: &str, language: Option<&str>) -> Option<Readability> {
+    let document = Html::parse_document(body);
+    let text = visible_text(&document);
+
+    let sentences = text
+        .split(['.', '!', '?', ';', ':'])
+        .filter(|sentence| sentence.split_whitespace().count() > 2)
+        .count()
+        .max(1);
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphabetic()))
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.len() < MIN_READABILITY_WORDS {
+        return None;
+    }
+
+    let syllables: Vec<usize> = words
+        .iter()
+        .map(|word| count_word_syllables(word))
+        .collect();
+    let polysyllables = syllables.iter().filter(|s| **s >= 3).count();
+    let asl = words.len() as f64 / sentences as f64;
+    let asw = syllables.iter().sum::<usize>() as f64 / words.len() as f64;
+
+    let language = language.map(|l| l.to_lowercase());
+    // Adaptations of Flesch's formula fitted to each language's word and sentence lengths
+    let (reading_ease, formula_language) = match language.as_deref() {
+        Some("de") => (180.0 - asl - 58.5 * asw, language.clone()),
+        Some("fr") => (207.0 - 1.015 * asl - 73.6 * asw, language.clone()),
+        Some("es") => (206.84 - 0.60 * asw * 100.0 - 1.02 * asl, language.clone()),
+        Some("it") => (217.0 - 1.3 * asl - 0.6 * asw * 100.0, language.clone()),
+        Some("nl") => (206.835 - 0.93 * asl - 77.0 * asw, language.clone()),
+        _ => (206.835 - 1.015 * asl - 84.6 * asw, None),
+    };
+
+    Some(Readability {
+        language: formula_language,
+        reading_ease: round(reading_ease),
+        classification: classify_flesch_score(reading_ease),
+        flesch_kincaid_grade: round(0.39 * asl + 11.8 * asw - 15.59),
+        smog_grade: (sentences >= SMOG_MIN_SENTENCES).then(|| {
+            round(1.043 * (polysyllables as f64 * 30.0 / sentences as f64).sqrt() + 3.1291)
+        }),
+        sentences,
+        words: words.len(),
+        words_per_sentence: round(asl),
+        syllables_per_word: (asw * 100.0).round() / 100.0,
+    })
+}
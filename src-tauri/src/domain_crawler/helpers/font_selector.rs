@@ -0,0 +1,262 @@
+use std::collections::{HashMap, HashSet};
+
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::assets_selector::asset_info;
+use super::images_selector::{image_client, image_permit};
+use crate::domain_crawler::crawl_cache::CrawlCache;
+use crate::domain_crawler::subresources;
+
+// More weight and style variants than this on one page slows down text rendering
+const MAX_FONT_VARIANTS: usize = 6;
+
+static COMMENTS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)/\*.*?\*/").unwrap());
+static FONT_FACE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)@font-face\s*\{([^}]*)\}").unwrap());
+static FONT_SRC: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)url\(\s*['"]?([^'")]+)['"]?\s*\)(?:\s*format\(\s*['"]?([^'")]+)['"]?\s*\))?"#)
+        .unwrap()
+});
+
+// Linked stylesheets are shared by most pages, each one is downloaded once per crawl
+static STYLESHEET_FACES: Lazy<CrawlCache<Vec<FontFace>>> = Lazy::new(CrawlCache::default);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FontSource {
+    pub url: String,
+    /// From `format()`, or guessed from the file extension
+    pub format: Option<String>,
+    pub size: Option<u64>,
+    pub status_code: Option<u16>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FontFace {
+    pub family: String,
+    pub weight: String,
+    pub style: String,
+    pub display: Option<String>,
+    /// The stylesheet declaring the face, None for inline `<style>`
+    pub stylesheet: Option<String>,
+    pub sources: Vec<FontSource>,
+    /// Offers a woff2 file, the smallest format every current browser reads
+    pub woff2: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FontPreload {
+    pub url: String,
+    pub mime_type: Option<String>,
+    /// Font preloads without `crossorigin` are fetched twice
+    pub crossorigin: bool,
+    /// Matches a source of one of the page's font faces
+    pub used: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FontAudit {
+    pub faces: Vec<FontFace>,
+    pub preloads: Vec<FontPreload>,
+    /// Distinct family, weight and style combinations
+    pub variants: usize,
+    pub excessive_weights: bool,
+    /// Sum of the preferred file of each face, in bytes
+    pub declared_size: u64,
+}
+
+/// Forgets the stylesheets downloaded during the previous crawl.
+pub fn reset_font_cache() {
+    STYLESHEET_FACES.clear();
+}
+
+fn guess_format(url: &str) -> Option<String> {
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+    let format = match path.rsplit('.').next()? {
+        "woff2" => "woff2",
+        "woff" => "woff",
+        "ttf" => "truetype",
+        "otf" => "opentype",
+        "eot" => "embedded-opentype",
+        "svg" => "svg",
+        _ => return None,
+    };
+    Some(format.to_string())
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches(['"', '\'']).trim().to_string()
+}
+
+/// Reads the `@font-face` rules of a stylesheet, resolving sources against `base_url`.
+pub fn parse_font_faces(css: &str, base_url: &Url, stylesheet: Option<&str>) -> Vec<FontFace> {
+    let css = COMMENTS.replace_all(css, "");
+    FONT_FACE
+        .captures_iter(&css)
+        .filter_map(|rule| {
+            let mut descriptors: HashMap<String, &str> = HashMap::new();
+            for declaration in rule[1].split(';') {
+                if let Some((name, value)) = declaration.split_once(':') {
+                    descriptors.insert(name.trim().to_lowercase(), value.trim());
+                }
+            }
+
+            let sources: Vec<FontSource> = FONT_SRC
+                .captures_iter(descriptors.get("src").copied().unwrap_or(""))
+                .filter_map(|src| {
+                    let url = base_url.join(src[1].trim()).ok()?.to_string();
+                    Some(FontSource {
+                        format: src
+                            .get(2)
+                            .map(|f| f.as_str().trim().to_lowercase())
+                            .or_else(|| guess_format(&url)),
+                        url,
+                        size: None,
+                        status_code: None,
+                    })
+                })
+                .collect();
+            if sources.is_empty() {
+                return None;
+            }
+
+            Some(FontFace {
+                family: unquote(descriptors.get("font-family")?),
+                weight: descriptors
+                    .get("font-weight")
+                    .map(|w| w.to_lowercase())
+                    .unwrap_or_else(|| "normal".to_string()),
+                style: descriptors
+                    .get("font-style")
+                    .map(|s| s.to_lowercase())
+                    .unwrap_or_else(|| "normal".to_string()),
+                display: descriptors.get("font-display").map(|d| d.to_lowercase()),
+                stylesheet: stylesheet.map(String::from),
+                woff2: sources.iter().any(|s| s.format.as_deref() == Some("woff2")),
+                sources,
+            })
+        })
+        .collect()
+}
+
+/// Downloads a linked stylesheet with the crawl's credentials and user agent.
+pub(crate) async fn fetch_stylesheet(url: &Url) -> Option<String> {
+    let _permit = image_permit().await;
+    match subresources::send(image_client().get(url.as_str()), url.as_str()).await {
+        Ok(response) if response.status().is_success() => response.text().await.ok(),
+        Ok(_) => None,
+        Err(e) => {
+            eprintln!("Failed to fetch stylesheet {}: {}", url, e);
+            None
+        }
+    }
+}
+
+async fn stylesheet_faces(url: Url) -> Vec<FontFace> {
+    STYLESHEET_FACES
+        .get_or_init(url.as_str(), || async {
+            fetch_stylesheet(&url)
+                .await
+                .map(|css| parse_font_faces(&css, &url, Some(url.as_str())))
+                .unwrap_or_default()
+        })
+        .await
+}
+
+/// Finds the web fonts of a page in its inline and linked CSS and checks the font files.
+pub async fn audit_fonts(html: &str, page_url: &Url) -> FontAudit {
+    let (stylesheets, mut faces, mut preloads) = {
+        let document = Html::parse_document(html);
+        let link_selector = Selector::parse("link[rel~='stylesheet'][href]").unwrap();
+        let style_selector = Selector::parse("style").unwrap();
+        let preload_selector = Selector::parse("link[rel~='preload'][as='font'][href]").unwrap();
+
+        let mut stylesheets: Vec<Url> = Vec::new();
+        for url in document
+            .select(&link_selector)
+            .filter_map(|link| page_url.join(link.value().attr("href")?.trim()).ok())
+        {
+            if url.scheme().starts_with("http") && !stylesheets.contains(&url) {
+                stylesheets.push(url);
+            }
+        }
+        let faces: Vec<FontFace> = document
+            .select(&style_selector)
+            .flat_map(|style| parse_font_faces(&style.text().collect::<String>(), page_url, None))
+            .collect();
+        let preloads: Vec<FontPreload> = document
+            .select(&preload_selector)
+            .filter_map(|link| {
+                let value = link.value();
+                Some(FontPreload {
+                    url: page_url.join(value.attr("href")?.trim()).ok()?.to_string(),
+                    mime_type: value.attr("type").map(String::from),
+                    crossorigin: value.attr("crossorigin").is_some(),
+                    used: false,
+                })
+            })
+            .collect();
+        (stylesheets, faces, preloads)
+    };
+
+    for linked in join_all(stylesheets.into_iter().map(stylesheet_faces)).await {
+        faces.extend(linked);
+    }
+
+    // Browsers download the first source they support, so that is the one that counts
+    let preferred: Vec<String> = faces
+        .iter()
+        .filter_map(|face| face.sources.first())
+        .map(|source| source.url.clone())
+        .filter(|url| url.starts_with("http"))
+        .collect();
+    let infos: HashMap<String, _> = join_all(
+        preferred
+            .iter()
+            .map(|url| async move { (url.clone(), asset_info(url.clone()).await) }),
+    )
+    .await
+    .into_iter()
+    .collect();
+
+    let mut declared_size = 0;
+    for face in faces.iter_mut() {
+        if let Some(source) = face.sources.first_mut() {
+            if let Some(info) = infos.get(&source.url) {
+                source.size = info.size;
+                source.status_code = info.status_code;
+                declared_size += info.size.unwrap_or(0);
+            }
+        }
+    }
+
+    for preload in preloads.iter_mut() {
+        preload.used = faces
+            .iter()
+            .flat_map(|face| face.sources.iter())
+            .any(|source| source.url == preload.url);
+    }
+
+    let variants = faces
+        .iter()
+        .map(|face| {
+            (
+                face.family.to_lowercase(),
+                face.weight.as_str(),
+                face.style.as_str(),
+            )
+        })
+        .collect::<HashSet<_>>()
+        .len();
+
+    FontAudit {
+        faces,
+        preloads,
+        variants,
+        excessive_weights: variants > MAX_FONT_VARIANTS,
+        declared_size,
+    }
+}
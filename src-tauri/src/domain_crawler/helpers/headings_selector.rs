@@ -1,4 +1,5 @@
 use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 pub fn headings_selector(html: &str) -> HashMap<String, Vec<String>> {
@@ -30,3 +31,114 @@ pub fn headings_selector(html: &str) -> HashMap<String, Vec<String>> {
 
     headings_map
 }
+
+/// A heading and the lower-level headings nested under it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeadingNode {
+    pub level: u8,
+    pub text: String,
+    pub children: Vec<HeadingNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum HeadingIssueKind {
+    MissingH1,
+    MultipleH1,
+    SkippedLevel,
+    EmptyHeading,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeadingIssue {
+    pub kind: HeadingIssueKind,
+    pub detail: String,
+}
+
+/// The H1–H6 tree of a page in document order, with structural issues.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HeadingOutline {
+    pub outline: Vec<HeadingNode>,
+    pub issues: Vec<HeadingIssue>,
+}
+
+// Nests each heading under the closest preceding heading of a higher level
+fn build_tree(flat: &[(u8, String)], index: &mut usize, parent_level: u8) -> Vec<HeadingNode> {
+    let mut nodes = Vec::new();
+    while let Some((level, text)) = flat.get(*index) {
+        if *level <= parent_level {
+            break;
+        }
+        *index += 1;
+        let children = build_tree(flat, index, *level);
+        nodes.push(HeadingNode {
+            level: *level,
+            text: text.clone(),
+            children,
+        });
+    }
+    nodes
+}
+
+/// Extracts the heading outline of a page and validates its structure.
+///
+/// # Arguments
+/// * `html` - The HTML content as a string.
+///
+/// # Returns
+/// * `HeadingOutline` - The heading tree and any missing, duplicate, skipped or empty headings.
+pub fn extract_heading_outline(html: &str) -> HeadingOutline {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("h1, h2, h3, h4, h5, h6").unwrap();
+
+    let flat: Vec<(u8, String)> = document
+        .select(&selector)
+        .map(|heading| {
+            let level = heading.value().name()[1..].parse().unwrap_or(6);
+            let text = heading.text().collect::<Vec<_>>().join(" ");
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            (level, text)
+        })
+        .collect();
+
+    let mut issues = Vec::new();
+
+    match flat.iter().filter(|(level, _)| *level == 1).count() {
+        0 => issues.push(HeadingIssue {
+            kind: HeadingIssueKind::MissingH1,
+            detail: "No H1 heading found".to_string(),
+        }),
+        1 => {}
+        count => issues.push(HeadingIssue {
+            kind: HeadingIssueKind::MultipleH1,
+            detail: format!("{} H1 headings found", count),
+        }),
+    }
+
+    let mut previous = 0;
+    for (level, text) in &flat {
+        // Going deeper by more than one level skips a level, going back up is fine
+        if *level > previous + 1 {
+            issues.push(HeadingIssue {
+                kind: HeadingIssueKind::SkippedLevel,
+                detail: if previous == 0 {
+                    format!("First heading is H{}: '{}'", level, text)
+                } else {
+                    format!("H{} follows H{}: '{}'", level, previous, text)
+                },
+            });
+        }
+        if text.is_empty() {
+            issues.push(HeadingIssue {
+                kind: HeadingIssueKind::EmptyHeading,
+                detail: format!("Empty H{} heading", level),
+            });
+        }
+        previous = *level;
+    }
+
+    let mut index = 0;
+    HeadingOutline {
+        outline: build_tree(&flat, &mut index, 0),
+        issues,
+    }
+}
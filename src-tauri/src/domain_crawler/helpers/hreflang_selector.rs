@@ -1,3 +1,4 @@
+use regex::Regex;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 
@@ -5,6 +6,8 @@ use serde::{Deserialize, Serialize};
 pub struct HreflangObject {
     pub code: String,
     pub url: String,
+    #[serde(default)]
+    pub from_header: bool,
 }
 
 pub fn select_hreflang(body: &str) -> Option<Vec<HreflangObject>> {
@@ -19,6 +22,7 @@ pub fn select_hreflang(body: &str) -> Option<Vec<HreflangObject>> {
                 hreflangs.push(HreflangObject {
                     code: hreflang.to_string(),
                     url: href.to_string(),
+                    from_header: false,
                 });
             }
         }
@@ -30,3 +34,50 @@ pub fn select_hreflang(body: &str) -> Option<Vec<HreflangObject>> {
         Some(hreflangs)
     }
 }
+
+/// Extracts hreflang annotations from HTTP `Link` headers, e.g.
+/// `Link: <https://example.com/de/>; rel="alternate"; hreflang="de"`.
+pub fn select_hreflang_headers(headers: &[(String, String)]) -> Vec<HreflangObject> {
+    let target = Regex::new(r"<([^>]*)>").unwrap();
+
+    headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("link"))
+        .flat_map(|(_, value)| value.split(','))
+        .filter_map(|link| {
+            let url = target.captures(link)?.get(1)?.as_str().trim().to_string();
+
+            let mut is_alternate = false;
+            let mut code = None;
+            for param in link.split(';').skip(1) {
+                let (key, val) = param.split_once('=')?;
+                let val = val.trim().trim_matches('"');
+                match key.trim().to_lowercase().as_str() {
+                    "rel" => is_alternate = val.split_whitespace().any(|r| r == "alternate"),
+                    "hreflang" => code = Some(val.to_string()),
+                    _ => {}
+                }
+            }
+
+            is_alternate.then_some(())?;
+            Some(HreflangObject {
+                code: code?,
+                url,
+                from_header: true,
+            })
+        })
+        .collect()
+}
+
+/// Checks that an hreflang value is a valid BCP-47 language tag (or `x-default`).
+///
+/// Accepts a 2-3 letter language, an optional 4 letter script and an optional
+/// 2 letter or 3 digit region, which covers what search engines support.
+pub fn is_valid_hreflang_code(code: &str) -> bool {
+    if code.eq_ignore_ascii_case("x-default") {
+        return true;
+    }
+
+    let re = Regex::new(r"^(?i)[a-z]{2,3}(-[a-z]{4})?(-([a-z]{2}|\d{3}))?$").unwrap();
+    re.is_match(code)
+}
@@ -1,12 +1,66 @@
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
+use url::Url;
+
+// Hosts whose frames exist to track visitors rather than show content
+const TRACKING_HOSTS: [&str; 5] = [
+    "googletagmanager.com",
+    "doubleclick.net",
+    "bat.bing.com",
+    "hotjar.com",
+    "adsrvr.org",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IframeDetails {
+    /// Resolved against the page, None for `srcdoc` frames
+    pub src: Option<String>,
+    /// The sandbox attribute value, an empty string applies every restriction
+    pub sandbox: Option<String>,
+    /// The title screen readers announce for the frame
+    pub title: Option<String>,
+    pub third_party: bool,
+    /// Zero sized or hidden with inline CSS, typical of tracking frames
+    pub hidden: bool,
+    /// A known tracker host, or a hidden third-party frame
+    pub tracking: bool,
+    pub lazy: bool,
+    pub allow: Option<String>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Iframe {
     iframe: Vec<String>,
+    #[serde(default)]
+    pub details: Vec<IframeDetails>,
+}
+
+fn bare_host(url: &Url) -> String {
+    url.host_str()
+        .unwrap_or("")
+        .trim_start_matches("www.")
+        .to_lowercase()
 }
 
-pub fn extract_iframe(body: &str) -> Option<Iframe> {
+fn is_hidden(element: &scraper::node::Element) -> bool {
+    let zero = |name| {
+        element
+            .attr(name)
+            .is_some_and(|v| v.trim().trim_end_matches("px").trim() == "0")
+    };
+    let style = element
+        .attr("style")
+        .unwrap_or("")
+        .to_lowercase()
+        .replace(' ', "");
+    zero("width")
+        || zero("height")
+        || element.attr("hidden").is_some()
+        || style.contains("display:none")
+        || style.contains("visibility:hidden")
+}
+
+pub fn extract_iframe(body: &str, page_url: &Url) -> Option<Iframe> {
     // Use parse_document if the input is a full HTML document
     let document = Html::parse_document(body);
 
@@ -16,19 +70,57 @@ pub fn extract_iframe(body: &str) -> Option<Iframe> {
         Err(_) => return None, // Return None if the selector is invalid
     };
 
+    let page_host = bare_host(page_url);
     let mut iframes = Vec::new();
+    let mut details = Vec::new();
 
     // Extract iframe src attributes
     for element in document.select(&iframe_selector) {
-        if let Some(src) = element.value().attr("src") {
+        let value = element.value();
+        if let Some(src) = value.attr("src") {
             iframes.push(src.to_string());
         }
+
+        let src = value
+            .attr("src")
+            .or_else(|| value.attr("data-src"))
+            .map(str::trim)
+            .filter(|src| !src.is_empty() && !src.starts_with("about:"))
+            .and_then(|src| page_url.join(src).ok());
+        // Subdomains of the page count as the same site
+        let host = src.as_ref().map(bare_host).unwrap_or_default();
+        let third_party =
+            !host.is_empty() && host != page_host && !host.ends_with(&format!(".{}", page_host));
+        let hidden = is_hidden(value);
+        let tracking = (third_party && hidden)
+            || TRACKING_HOSTS
+                .iter()
+                .any(|tracker| host == *tracker || host.ends_with(&format!(".{}", tracker)));
+
+        details.push(IframeDetails {
+            src: src.map(|src| src.to_string()),
+            sandbox: value.attr("sandbox").map(|s| s.trim().to_string()),
+            title: value
+                .attr("title")
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty()),
+            third_party,
+            hidden,
+            tracking,
+            lazy: value
+                .attr("loading")
+                .is_some_and(|l| l.eq_ignore_ascii_case("lazy")),
+            allow: value.attr("allow").map(String::from),
+        });
     }
 
     // Return Some(Iframe) if iframes were found, otherwise None
-    if iframes.is_empty() {
+    if details.is_empty() {
         None
     } else {
-        Some(Iframe { iframe: iframes })
+        Some(Iframe {
+            iframe: iframes,
+            details,
+        })
     }
 }
@@ -1,48 +1,284 @@
 use futures::future::join_all;
-use reqwest::StatusCode;
-use scraper::{Html, Selector};
-use tokio::time::{timeout, Duration};
+use once_cell::sync::Lazy;
+use reqwest::{Client, StatusCode};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Duration;
 use url::Url;
 
-/// Extracts image URLs, alt tags, and a boolean indicating if width or height is not specified.
+use crate::domain_crawler::{proxies, session, subresources};
+use crate::settings::settings::Settings;
+
+// Crawler-wide client so image checks reuse pooled TCP/TLS connections across pages
+static IMAGE_CLIENT: Lazy<RwLock<Option<Client>>> = Lazy::new(|| RwLock::new(None));
+
+// Caps the image checks in flight across all pages, resized at the start of each crawl
+static IMAGE_PERMITS: Lazy<RwLock<Arc<Semaphore>>> = Lazy::new(|| {
+    RwLock::new(Arc::new(Semaphore::new(
+        Settings::default().max_image_checks,
+    )))
+});
+
+// Size, content type and status of an image, from its HEAD request
+type ImageSize = (u64, String, u16);
+
+fn build_image_client(settings: &Settings) -> Client {
+    let builder = Client::builder()
+        .timeout(Duration::from_secs(settings.images_request_timeout))
+        .connect_timeout(Duration::from_secs(settings.images_connect_timeout))
+        .pool_max_idle_per_host(settings.images_pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(settings.images_pool_idle_timeout))
+        .tcp_keepalive(Duration::from_secs(60))
+        .cookie_provider(session::cookies());
+    proxies::apply(builder)
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
+/// Initialises the shared image client from the crawler settings.
+///
+/// Called at the start of each crawl so proxy changes apply; the pool lives for the crawl.
+pub fn init_image_client(settings: &Settings) {
+    if let Ok(mut client) = IMAGE_CLIENT.write() {
+        *client = Some(build_image_client(settings));
+    }
+
+    if let Ok(mut permits) = IMAGE_PERMITS.write() {
+        *permits = Arc::new(Semaphore::new(settings.max_image_checks.max(1)));
+    }
+}
+
+/// Waits for a free image check slot.
+pub(crate) async fn image_permit() -> Option<OwnedSemaphorePermit> {
+    let semaphore = IMAGE_PERMITS.read().ok()?.clone();
+    semaphore.acquire_owned().await.ok()
+}
+
+/// Returns the shared image client, falling back to the default settings if the
+/// crawler has not initialised it yet.
+pub(crate) fn image_client() -> Client {
+    if let Some(client) = IMAGE_CLIENT.read().ok().and_then(|client| client.clone()) {
+        return client;
+    }
+    let client = build_image_client(&Settings::default());
+    if let Ok(mut current) = IMAGE_CLIENT.write() {
+        current.get_or_insert_with(|| client.clone());
+    }
+    client
+}
+
+/// Attributes that lazy-loading libraries commonly use to hold the real image URL.
+const LAZY_SRC_ATTRS: [&str; 4] = ["src", "data-src", "data-lazy-src", "data-original"];
+
+/// Attributes that hold a `srcset` style list of image candidates.
+const SRCSET_ATTRS: [&str; 3] = ["srcset", "data-srcset", "data-lazy-srcset"];
+
+/// A single image candidate found on the page, together with where it came from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageCandidate {
+    pub url: String,
+    pub alt: String,
+    /// Width (`480w`) or pixel density (`2x`) descriptor from a `srcset`, if any.
+    pub descriptor: Option<String>,
+    /// The element and attribute the candidate was read from, e.g. `img[srcset]`.
+    pub source: String,
+    pub size_not_specified: bool,
+    pub declared_width: Option<u32>,
+    pub declared_height: Option<u32>,
+}
+
+/// Natural pixel dimensions of an image compared against its `width`/`height` attributes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageDimensions {
+    pub url: String,
+    pub natural_width: u32,
+    pub natural_height: u32,
+    pub declared_width: Option<u32>,
+    pub declared_height: Option<u32>,
+    /// True when the image is more than `OVERSIZED_RATIO` times larger than it is rendered.
+    pub oversized: bool,
+}
+
+/// Allow up to 2x the rendered size so high-density (retina) images are not flagged.
+const OVERSIZED_RATIO: f32 = 2.0;
+
+/// Parses a `srcset` attribute into `(url, descriptor)` pairs.
+///
+/// Follows the HTML candidate rules loosely: URLs are separated from their descriptors
+/// by whitespace and candidates by commas, so commas inside a URL (e.g. CDN transforms)
+/// are kept intact.
+pub fn parse_srcset(srcset: &str) -> Vec<(String, Option<String>)> {
+    let mut candidates = Vec::new();
+    let mut chars = srcset.chars().peekable();
+
+    loop {
+        // Skip leading whitespace and stray commas
+        while matches!(chars.peek(), Some(c) if c.is_whitespace() || *c == ',') {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut url = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                break;
+            }
+            url.push(c);
+            chars.next();
+        }
+
+        // A URL ending in commas has no descriptor
+        let mut descriptor = String::new();
+        if url.ends_with(',') {
+            url = url.trim_end_matches(',').to_string();
+        } else {
+            let mut in_parens = false;
+            for c in chars.by_ref() {
+                match c {
+                    '(' => in_parens = true,
+                    ')' => in_parens = false,
+                    ',' if !in_parens => break,
+                    _ => {}
+                }
+                descriptor.push(c);
+            }
+        }
+
+        if url.is_empty() {
+            continue;
+        }
+
+        let descriptor = descriptor.trim();
+        candidates.push((
+            url,
+            (!descriptor.is_empty()).then(|| descriptor.to_string()),
+        ));
+    }
+
+    candidates
+}
+
+/// Extracts every image candidate from `<img>` and `<picture><source>` elements,
+/// including `srcset` entries and common lazy-load attributes.
 ///
 /// # Arguments
 /// * `html` - The HTML content as a string.
 /// * `base_url` - The base URL used to resolve relative image URLs.
 ///
 /// # Returns
-/// A vector of tuples containing the image URL, alt text, and a boolean indicating if width or height is not specified.
-pub fn extract_image_urls_and_alts(html: &str, base_url: &Url) -> Vec<(Url, String, bool)> {
-    // Parse the HTML document
+/// A vector of image candidates, each with its descriptor and source attribute.
+pub fn extract_image_candidates(html: &str, base_url: &Url) -> Vec<ImageCandidate> {
     let document = Html::parse_document(html);
-
-    // Create a selector for `<img>` tags
+    let selector = Selector::parse("img, picture source").expect("Failed to parse img selector");
     let img_selector = Selector::parse("img").expect("Failed to parse img selector");
 
-    // Iterate over all `<img>` elements in the document
-    document
-        .select(&img_selector)
-        .filter_map(|element| {
-            // Check both `src` and `data-src` attributes for the image URL
-            let src = element
+    let mut candidates = Vec::new();
+
+    for element in document.select(&selector) {
+        let tag = element.value().name();
+
+        // `<source>` elements take their alt text and sizes from the sibling `<img>`
+        let fallback_img = if tag == "source" {
+            element
+                .parent()
+                .and_then(ElementRef::wrap)
+                .and_then(|picture| picture.select(&img_selector).next())
+        } else {
+            None
+        };
+
+        let attr = |name: &str| {
+            element
                 .value()
-                .attr("src")
-                .or_else(|| element.value().attr("data-src"))?;
+                .attr(name)
+                .or_else(|| fallback_img.and_then(|img| img.value().attr(name)))
+        };
 
-            // Convert the relative URL to an absolute URL using the base URL
-            let url = base_url.join(src).ok()?;
+        let alt = attr("alt").unwrap_or("").to_string();
+        let size_not_specified = attr("width").is_none() || attr("height").is_none();
+        let declared_width = attr("width").and_then(parse_dimension);
+        let declared_height = attr("height").and_then(parse_dimension);
 
-            // Get the `alt` attribute or use an empty string if it doesn't exist
-            let alt = element.value().attr("alt").unwrap_or("").to_string();
+        let mut push = |raw: &str, descriptor: Option<String>, source: String| {
+            // Inline data URIs are placeholders, not real image assets
+            if raw.starts_with("data:") {
+                return;
+            }
+            if let Ok(url) = base_url.join(raw.trim()) {
+                candidates.push(ImageCandidate {
+                    url: url.to_string(),
+                    alt: alt.clone(),
+                    descriptor,
+                    source,
+                    size_not_specified,
+                    declared_width,
+                    declared_height,
+                });
+            }
+        };
 
-            // Check if width or height is not specified
-            let is_size_not_specified =
-                element.value().attr("width").is_none() || element.value().attr("height").is_none();
+        if tag == "img" {
+            for name in LAZY_SRC_ATTRS {
+                if let Some(src) = element.value().attr(name) {
+                    push(src, None, format!("img[{}]", name));
+                }
+            }
+        }
+
+        for name in SRCSET_ATTRS {
+            if let Some(srcset) = element.value().attr(name) {
+                for (url, descriptor) in parse_srcset(srcset) {
+                    let source = if tag == "source" {
+                        format!("picture>source[{}]", name)
+                    } else {
+                        format!("img[{}]", name)
+                    };
+                    push(&url, descriptor, source);
+                }
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Parses a `width`/`height` attribute such as `640` or `640px` into pixels.
+fn parse_dimension(value: &str) -> Option<u32> {
+    value
+        .trim()
+        .trim_end_matches("px")
+        .trim()
+        .parse::<u32>()
+        .ok()
+}
+
+/// Extracts image URLs, alt tags, and a boolean indicating if width or height is not specified.
+///
+/// Every `src`, lazy-load attribute, `srcset` and `<picture><source>` candidate is included,
+/// deduplicated by URL.
+///
+/// # Arguments
+/// * `html` - The HTML content as a string.
+/// * `base_url` - The base URL used to resolve relative image URLs.
+///
+/// # Returns
+/// A vector of tuples containing the image URL, alt text, and a boolean indicating if width or height is not specified.
+pub fn extract_image_urls_and_alts(html: &str, base_url: &Url) -> Vec<(Url, String, bool)> {
+    let mut seen = HashSet::new();
 
-            // Return a tuple of the image URL, alt text, and the boolean
-            Some((url, alt, is_size_not_specified))
+    extract_image_candidates(html, base_url)
+        .into_iter()
+        .filter(|candidate| seen.insert(candidate.url.clone()))
+        .filter_map(|candidate| {
+            let url = Url::parse(&candidate.url).ok()?;
+            Some((url, candidate.alt, candidate.size_not_specified))
         })
-        .collect() // Collect all results into a vector
+        .collect()
 }
 
 /// Fetches the size, content type, and status code of an image using a HEAD request.
@@ -52,46 +288,28 @@ pub fn extract_image_urls_and_alts(html: &str, base_url: &Url) -> Vec<(Url, Stri
 ///
 /// # Returns
 /// A tuple containing the image size in KB, content type, and status code as u16.
-async fn fetch_image_size(url: &Url) -> Result<(u64, String, u16), String> {
-    // Set a timeout duration for the request (e.g., 5 seconds)
-    let timeout_duration = Duration::from_secs(5);
-
-    // Send a HEAD request to the image URL with a timeout
-    let response = timeout(
-        timeout_duration,
-        reqwest::Client::new().head(url.as_str()).send(),
-    )
-    .await
-    .map_err(|_| format!("Timeout while fetching image: {}", url))?
-    .map_err(|e| format!("Failed to send request for {}: {}", url, e))?;
-
-    // Get the HTTP status code from the response
-    let status_code = response.status();
-    let status_code_int = status_code.as_u16();
+/// The HEAD answer for an image as size, content type and status, shared by every page using it.
+async fn image_size(url: &Url) -> Result<ImageSize, String> {
+    let head = subresources::head(url.as_str()).await?;
 
     // Extract the content type from the response headers
-    let content_type = response
-        .headers()
-        .get(reqwest::header::CONTENT_TYPE)
-        .and_then(|value| value.to_str().ok())
-        .unwrap_or("")
-        .to_string();
+    let content_type = head
+        .header(reqwest::header::CONTENT_TYPE)
+        .unwrap_or_default();
 
     // Initialize content_length with a default value of 0
     let mut content_length: u64 = 0;
 
     // If the status code is OK (200), proceed to extract the content type and size
-    if status_code == StatusCode::OK {
+    if head.status == StatusCode::OK {
         // Ensure the content type is an image
         if !content_type.contains("image") {
             return Err(format!("Non-image content type: {}", url));
         }
 
         // Extract the content length (size in bytes) from the response headers
-        content_length = response
-            .headers()
-            .get(reqwest::header::CONTENT_LENGTH)
-            .and_then(|value| value.to_str().ok())
+        content_length = head
+            .header(reqwest::header::CONTENT_LENGTH)
             .and_then(|s| s.parse::<u64>().ok())
             .unwrap_or(0);
     }
@@ -100,11 +318,13 @@ async fn fetch_image_size(url: &Url) -> Result<(u64, String, u16), String> {
     let size_kb = content_length / 1024;
 
     // Return the size in KB, content type, and status code as u16
-    Ok((size_kb, content_type, status_code_int))
+    Ok((size_kb, content_type, head.status.as_u16()))
 }
 
 /// Extracts image URLs, alt tags, sizes, content types, status codes, and a boolean indicating if width or height is not specified.
 ///
+/// Each image is checked once per crawl, and the checks are spaced like page requests.
+///
 /// # Arguments
 /// * `html` - The HTML content as a string.
 /// * `base_url` - The base URL used to resolve relative image URLs.
@@ -126,7 +346,7 @@ pub async fn extract_images_with_sizes_and_alts(
                 // Always return image URL and alt text, even if fetch fails
                 let url_string = image_url.to_string();
 
-                match fetch_image_size(&image_url).await {
+                match image_size(&image_url).await {
                     Ok((size, content_type, status_code)) => {
                         // If successful, return a tuple with the image details and the boolean
                         (
@@ -152,3 +372,114 @@ pub async fn extract_images_with_sizes_and_alts(
     // Return the collected image details
     Ok(results)
 }
+
+/// Downloads the first `max_bytes` of an image and decodes its natural pixel dimensions.
+///
+/// A `Range` header is sent so servers that support it only return the header bytes; for
+/// servers that ignore it the body is streamed and cut off once `max_bytes` is reached.
+async fn fetch_image_dimensions(url: &str, max_bytes: usize) -> Result<(u32, u32), String> {
+    let _permit = image_permit().await;
+
+    let request = image_client().get(url).header(
+        reqwest::header::RANGE,
+        format!("bytes=0-{}", max_bytes.saturating_sub(1)),
+    );
+    let mut response = subresources::send(request, url)
+        .await
+        .map_err(|e| format!("Failed to fetch image bytes for {}: {}", url, e))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "HTTP {} while fetching image: {}",
+            response.status(),
+            url
+        ));
+    }
+
+    let mut bytes: Vec<u8> = Vec::with_capacity(max_bytes);
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read image bytes for {}: {}", url, e))?
+    {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() >= max_bytes {
+            break;
+        }
+    }
+
+    image::io::Reader::new(std::io::Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to guess image format for {}: {}", url, e))?
+        .into_dimensions()
+        .map_err(|e| format!("Failed to decode image dimensions for {}: {}", url, e))
+}
+
+/// The pixel density a candidate is meant for, `None` for `w` descriptors.
+///
+/// Width variants exist to be larger than the rendered size on wide screens,
+/// so only `src` and density (`2x`) candidates are compared against it.
+fn candidate_density(candidate: &ImageCandidate) -> Option<f32> {
+    match candidate.descriptor.as_deref().map(str::trim) {
+        None => Some(1.0),
+        Some(descriptor) => descriptor
+            .strip_suffix(['x', 'X'])
+            .and_then(|density| density.parse::<f32>().ok())
+            .filter(|density| *density > 0.0),
+    }
+}
+
+/// Decodes the real pixel dimensions of every distinct `src` and density candidate and
+/// compares them against the rendered `width`/`height` attributes.
+///
+/// # Arguments
+/// * `candidates` - Image candidates extracted from the page.
+/// * `max_bytes` - How many bytes of each image to download for decoding.
+///
+/// # Returns
+/// A vector with the natural and declared dimensions of each image that could be decoded.
+pub async fn audit_image_dimensions(
+    candidates: &[ImageCandidate],
+    max_bytes: usize,
+) -> Vec<ImageDimensions> {
+    let mut seen = HashSet::new();
+
+    let fetch_futures = candidates
+        .iter()
+        .filter_map(|candidate| candidate_density(candidate).map(|density| (candidate, density)))
+        .filter(|(candidate, _)| seen.insert(candidate.url.clone()))
+        .map(|(candidate, density)| async move {
+            match fetch_image_dimensions(&candidate.url, max_bytes).await {
+                Ok((natural_width, natural_height)) => {
+                    // A `3x` candidate is meant to be three times the rendered size
+                    let ratio = density.max(OVERSIZED_RATIO);
+                    let exceeds = |natural: u32, declared: Option<u32>| {
+                        declared
+                            .filter(|d| *d > 0)
+                            .map(|d| natural as f32 > d as f32 * ratio)
+                            .unwrap_or(false)
+                    };
+
+                    Some(ImageDimensions {
+                        url: candidate.url.clone(),
+                        natural_width,
+                        natural_height,
+                        declared_width: candidate.declared_width,
+                        declared_height: candidate.declared_height,
+                        oversized: exceeds(natural_width, candidate.declared_width)
+                            || exceeds(natural_height, candidate.declared_height),
+                    })
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    None
+                }
+            }
+        });
+
+    join_all(fetch_futures)
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+}
@@ -1,91 +1,157 @@
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 
+use super::canonical_selector::{CanonicalAudit, CanonicalKind};
+
+/// Why a page can or cannot end up in the index, in order of precedence.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexabilityVerdict {
+    #[default]
+    Indexable,
+    Noindexed,
+    Blocked,
+    Canonicalized,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Indexability {
     pub indexability: f32,
     pub indexability_reason: String,
+    #[serde(default)]
+    pub verdict: IndexabilityVerdict,
 }
 
-pub fn extract_indexability(html: &str) -> Indexability {
-    let document = Html::parse_document(html);
+impl Indexability {
+    /// Verdict for a URL disallowed by robots.txt.
+    pub fn blocked() -> Self {
+        Self {
+            indexability: 0.0,
+            indexability_reason: "Blocked by robots.txt".to_string(),
+            verdict: IndexabilityVerdict::Blocked,
+        }
+    }
+}
+
+/// Robots directives gathered from meta tags and `X-Robots-Tag` headers.
+#[derive(Debug, Default)]
+struct Directives {
+    noindex: bool,
+    nofollow: bool,
+    sources: Vec<String>,
+}
 
-    // Check for meta robots tags
-    let robots_indexability = check_meta_robots(&document);
-    if robots_indexability.indexability != 0.5 {
-        return robots_indexability;
+impl Directives {
+    fn add(&mut self, source: &str, content: &str) {
+        for directive in content.split(',').map(|d| d.trim().to_lowercase()) {
+            match directive.as_str() {
+                "noindex" => self.noindex = true,
+                "nofollow" => self.nofollow = true,
+                "none" => {
+                    self.noindex = true;
+                    self.nofollow = true;
+                }
+                _ => continue,
+            }
+            self.sources.push(format!("{} '{}'", source, directive));
+        }
     }
+}
 
-    // Check for canonical tags
-    let canonical_indexability = check_canonical_tag(&document);
-    if canonical_indexability.indexability != 0.5 {
-        return canonical_indexability;
+/// Computes a single indexability verdict for a fetched page.
+///
+/// # Arguments
+/// * `html` - The HTML content as a string.
+/// * `headers` - The response headers, checked for `X-Robots-Tag`.
+/// * `canonical` - The audited canonical tag of the page.
+/// * `robots_allowed` - Whether robots.txt allows the URL for the crawling user agent.
+///
+/// # Returns
+/// * `Indexability` - The verdict with a score and a human readable reason.
+pub fn classify_indexability(
+    html: &str,
+    headers: &[(String, String)],
+    canonical: &CanonicalAudit,
+    robots_allowed: bool,
+) -> Indexability {
+    if !robots_allowed {
+        return Indexability::blocked();
     }
 
-    // Default case: No meta robots or canonical tags found
-    Indexability {
-        indexability: 0.5,
-        indexability_reason: "No meta robots or canonical tags found".to_string(),
+    let document = Html::parse_document(html);
+    let mut directives = Directives::default();
+    check_meta_robots(&document, &mut directives);
+    check_x_robots_tag(headers, &mut directives);
+
+    if directives.noindex {
+        return Indexability {
+            indexability: 0.0,
+            indexability_reason: format!("Not indexable: {}", directives.sources.join(", ")),
+            verdict: IndexabilityVerdict::Noindexed,
+        };
     }
-}
 
-/// Checks for `<meta name="robots">` or `<meta name="googlebot">` tags.
-fn check_meta_robots(document: &Html) -> Indexability {
-    let meta_selectors = [
-        Selector::parse("meta[name='robots']").unwrap(),
-        Selector::parse("meta[name='googlebot']").unwrap(),
-    ];
+    if matches!(
+        canonical.kind,
+        CanonicalKind::Canonicalized | CanonicalKind::CrossDomain
+    ) {
+        return Indexability {
+            indexability: 0.2,
+            indexability_reason: format!(
+                "Canonicalized to: {}",
+                canonical.resolved.as_deref().unwrap_or_default()
+            ),
+            verdict: IndexabilityVerdict::Canonicalized,
+        };
+    }
 
-    for selector in &meta_selectors {
-        if let Some(meta_element) = document.select(selector).next() {
-            if let Some(content) = meta_element.value().attr("content") {
-                let content = content.trim().to_lowercase();
-                let (indexability, reason) = match content.as_str() {
-                    "index, follow" => {
-                        (1.0, "Indexable: 'index, follow' meta tag found".to_string())
-                    }
-                    "noindex, follow" => (
-                        0.0,
-                        "Not indexable: 'noindex, follow' meta tag found".to_string(),
-                    ),
-                    "index, nofollow" => (
-                        0.8,
-                        "Partially indexable: 'index, nofollow' meta tag found".to_string(),
-                    ),
-                    "noindex, nofollow" => (
-                        0.0,
-                        "Not indexable: 'noindex, nofollow' meta tag found".to_string(),
-                    ),
-                    _ => (0.5, format!("Unknown meta tag content: '{}'", content)),
-                };
-                return Indexability {
-                    indexability,
-                    indexability_reason: reason,
-                };
-            }
-        }
+    if directives.nofollow {
+        return Indexability {
+            indexability: 0.8,
+            indexability_reason: format!(
+                "Indexable, links not followed: {}",
+                directives.sources.join(", ")
+            ),
+            verdict: IndexabilityVerdict::Indexable,
+        };
     }
 
     Indexability {
-        indexability: 0.5,
-        indexability_reason: "No meta robots tags found".to_string(),
+        indexability: 1.0,
+        indexability_reason: "Indexable".to_string(),
+        verdict: IndexabilityVerdict::Indexable,
     }
 }
 
-/// Checks for canonical tags.
-fn check_canonical_tag(document: &Html) -> Indexability {
-    let canonical_selector = Selector::parse("link[rel='canonical']").unwrap();
-    if let Some(canonical_element) = document.select(&canonical_selector).next() {
-        if let Some(href) = canonical_element.value().attr("href") {
-            return Indexability {
-                indexability: 0.8, // Canonical tags suggest partial indexability
-                indexability_reason: format!("Canonical tag found pointing to: {}", href),
-            };
+/// Reads `<meta name="robots">` and `<meta name="googlebot">` tags.
+fn check_meta_robots(document: &Html, directives: &mut Directives) {
+    for name in ["robots", "googlebot"] {
+        let selector = Selector::parse(&format!("meta[name='{}']", name)).unwrap();
+        for element in document.select(&selector) {
+            if let Some(content) = element.value().attr("content") {
+                directives.add(&format!("meta {}", name), content);
+            }
         }
     }
+}
 
-    Indexability {
-        indexability: 0.5,
-        indexability_reason: "No canonical tag found".to_string(),
+/// Reads `X-Robots-Tag` headers, skipping values scoped to crawlers other than Googlebot.
+fn check_x_robots_tag(headers: &[(String, String)], directives: &mut Directives) {
+    for (_, value) in headers
+        .iter()
+        .filter(|(k, _)| k.eq_ignore_ascii_case("x-robots-tag"))
+    {
+        // A value such as "bingbot: noindex" only applies to that crawler
+        let content = match value.split_once(':') {
+            Some((agent, rest)) if !agent.contains(',') && !agent.trim().contains(' ') => {
+                let agent = agent.trim().to_lowercase();
+                if agent != "googlebot" && agent != "*" {
+                    continue;
+                }
+                rest
+            }
+            _ => value.as_str(),
+        };
+        directives.add("X-Robots-Tag", content);
     }
 }
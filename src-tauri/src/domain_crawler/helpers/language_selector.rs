@@ -1,4 +1,12 @@
+use std::cmp::Reverse;
+
 use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::canonical_selector::same_url;
+use super::content_analyzer::visible_text;
+use super::hreflang_selector::HreflangObject;
 
 pub fn detect_language(body: &str) -> Option<String> {
     let document = Html::parse_document(&body);
@@ -36,3 +44,222 @@ pub fn detect_language(body: &str) -> Option<String> {
     // No language found
     None
 }
+
+// Frequent function words, enough to tell these languages apart in running text
+const PROFILES: [(&str, &[&str]); 11] = [
+    (
+        "en",
+        &[
+            "the", "and", "of", "to", "is", "in", "that", "it", "for", "with", "as", "was", "on",
+            "are", "this", "be", "by", "you", "have", "not", "or", "from", "at", "which", "but",
+            "they", "we", "will",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "und", "das", "ist", "nicht", "ein", "eine", "zu", "den", "von", "mit",
+            "sich", "des", "auf", "für", "im", "dem", "auch", "es", "als", "wird", "werden", "bei",
+            "oder", "sind", "wir", "ich",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "et", "des", "est", "un", "une", "du", "en", "que", "qui", "dans",
+            "pour", "pas", "sur", "au", "avec", "ce", "il", "sont", "par", "plus", "nous", "vous",
+            "mais", "ou",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "la", "los", "las", "y", "de", "que", "en", "un", "una", "es", "por", "con",
+            "para", "del", "se", "no", "al", "lo", "como", "más", "pero", "sus", "su", "está",
+            "son", "muy",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "di", "che", "e", "la", "un", "una", "per", "non", "sono", "con", "del", "della",
+            "le", "gli", "si", "da", "in", "è", "al", "come", "più", "ma", "anche", "questo",
+            "nel", "alla",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "van", "is", "dat", "niet", "op", "te", "zijn", "voor",
+            "met", "die", "aan", "er", "ook", "als", "bij", "maar", "om", "wordt", "dit", "naar",
+            "worden", "wij", "ik", "je",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "a", "os", "as", "de", "que", "e", "do", "da", "em", "um", "uma", "para", "com",
+            "não", "por", "mais", "dos", "das", "se", "na", "no", "ao", "é", "são", "mas", "como",
+            "foi", "você",
+        ],
+    ),
+    (
+        "pl",
+        &[
+            "i", "w", "na", "z", "się", "nie", "do", "to", "że", "jest", "o", "jak", "po", "co",
+            "od", "ale", "za", "dla", "są", "jego", "przez", "tak", "czy", "już", "oraz", "który",
+            "będzie", "może", "tym",
+        ],
+    ),
+    (
+        "sv",
+        &[
+            "och", "att", "det", "som", "en", "är", "på", "för", "med", "av", "den", "till",
+            "inte", "har", "om", "ett", "de", "jag", "vi", "men", "kan", "så", "från", "var",
+            "eller", "sig",
+        ],
+    ),
+    (
+        "da",
+        &[
+            "og", "i", "at", "det", "er", "en", "til", "på", "som", "med", "af", "for", "ikke",
+            "der", "den", "har", "de", "et", "om", "vi", "jeg", "kan", "men", "fra", "eller",
+            "også",
+        ],
+    ),
+    (
+        "cs",
+        &[
+            "a", "se", "na", "je", "v", "že", "to", "s", "z", "do", "o", "jako", "pro", "by",
+            "ale", "jsou", "jeho", "tak", "jak", "který", "po", "už", "nebo", "jsem", "také",
+            "být",
+        ],
+    ),
+];
+
+// Shorter texts, such as image galleries, cannot be told apart reliably
+const MIN_WORDS: usize = 40;
+// The share of words a profile must match before it counts as detected
+const MIN_MATCH_SHARE: f64 = 0.08;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum LanguageIssue {
+    /// No `<html lang>` attribute
+    MissingLang,
+    /// The text is written in another language than `<html lang>` claims
+    LangMismatch,
+    /// The page's own hreflang annotation names another language than the text
+    HreflangMismatch,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LanguageAudit {
+    /// The `<html lang>` attribute as written
+    pub declared: Option<String>,
+    /// ISO 639-1 code detected from the main text
+    pub detected: Option<String>,
+    /// How far ahead of the runner-up the detected language is, from 0 to 1
+    pub confidence: f64,
+    /// The hreflang code annotated for the page's own URL
+    pub hreflang: Option<String>,
+    pub issues: Vec<LanguageIssue>,
+}
+
+/// Detects the language of `text` from its function words, with a confidence from 0 to 1.
+pub fn detect_text_language(text: &str) -> Option<(&'static str, f64)> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect();
+    if words.len() < MIN_WORDS {
+        return None;
+    }
+
+    let mut scores: Vec<(&'static str, usize)> = PROFILES
+        .iter()
+        .map(|(code, profile)| {
+            let hits = words
+                .iter()
+                .filter(|word| profile.contains(&word.as_str()))
+                .count();
+            (*code, hits)
+        })
+        .collect();
+    scores.sort_by_key(|(_, hits)| Reverse(*hits));
+
+    let (code, best) = scores[0];
+    if (best as f64 / words.len() as f64) < MIN_MATCH_SHARE {
+        return None;
+    }
+    let runner_up = scores[1].1;
+    let confidence = ((best - runner_up) as f64 / best as f64 * 100.0).round() / 100.0;
+    Some((code, confidence))
+}
+
+/// Tells whether `word`, lowercased, is a function word in any of the profiled languages.
+pub fn is_function_word(word: &str) -> bool {
+    PROFILES.iter().any(|(_, profile)| profile.contains(&word))
+}
+
+// `en-GB` and `EN_gb` both compare as `en`
+fn primary_subtag(code: &str) -> String {
+    code.split(['-', '_'])
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_lowercase()
+}
+
+fn is_supported(code: &str) -> bool {
+    PROFILES.iter().any(|(profile, _)| *profile == code)
+}
+
+/// Compares the detected language of the main text with `<html lang>` and hreflang.
+pub fn audit_language(body: &str, page_url: &Url, hreflangs: &[HreflangObject]) -> LanguageAudit {
+    let document = Html::parse_document(body);
+    let lang_selector = Selector::parse("html[lang]").unwrap();
+    let mut audit = LanguageAudit {
+        declared: document
+            .select(&lang_selector)
+            .next()
+            .and_then(|html| html.value().attr("lang"))
+            .map(|lang| lang.trim().to_string())
+            .filter(|lang| !lang.is_empty()),
+        hreflang: hreflangs
+            .iter()
+            .filter(|h| !h.code.eq_ignore_ascii_case("x-default"))
+            .find(|h| {
+                page_url
+                    .join(h.url.trim())
+                    .is_ok_and(|url| same_url(&url, page_url))
+            })
+            .map(|h| h.code.clone()),
+        ..Default::default()
+    };
+
+    if let Some((code, confidence)) = detect_text_language(&visible_text(&document)) {
+        audit.detected = Some(code.to_string());
+        audit.confidence = confidence;
+    }
+
+    if audit.declared.is_none() {
+        audit.issues.push(LanguageIssue::MissingLang);
+    }
+    // Languages without a profile cannot be detected, so they are never reported as mismatched
+    let mismatch = |declared: &Option<String>| {
+        let (Some(declared), Some(detected)) = (declared.as_deref(), audit.detected.as_deref())
+        else {
+            return false;
+        };
+        let declared = primary_subtag(declared);
+        is_supported(&declared) && declared != detected
+    };
+    if mismatch(&audit.declared) {
+        audit.issues.push(LanguageIssue::LangMismatch);
+    }
+    if mismatch(&audit.hreflang) {
+        audit.issues.push(LanguageIssue::HreflangMismatch);
+    }
+    audit
+}
@@ -0,0 +1,154 @@
+use scraper::{ElementRef, Html, Node, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+// Without layout the fold is guessed from the document: the first few images and frames,
+// before this much visible text, are assumed to be in the first viewport
+const ABOVE_FOLD_ELEMENTS: usize = 3;
+const ABOVE_FOLD_TEXT_CHARS: usize = 800;
+
+// Declared widths below this are icons and logos rather than the largest paint
+const LCP_MIN_WIDTH: u32 = 300;
+
+// Classes lazy loading libraries such as lazysizes use instead of the loading attribute
+const LAZY_CLASSES: [&str; 3] = ["lazyload", "lazy", "lozad"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum LazyIssue {
+    /// Below the fold and loaded straight away
+    EagerBelowFold,
+    /// In the first viewport but deferred
+    LazyAboveFold,
+    /// The likely Largest Contentful Paint image is deferred
+    LazyLcp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LazyElement {
+    /// `img` or `iframe`
+    pub tag: String,
+    pub src: Option<String>,
+    /// Order among the page's images and frames, starting at 0
+    pub position: usize,
+    /// `loading="lazy"`, or deferred by a script loader
+    pub lazy: bool,
+    pub above_fold: bool,
+    pub issue: Option<LazyIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LazyLoadingAudit {
+    pub elements: Vec<LazyElement>,
+    pub lazy: usize,
+    pub eager_below_fold: usize,
+    pub lazy_above_fold: usize,
+    /// The first large image in the first viewport
+    pub lcp_candidate: Option<String>,
+}
+
+fn is_lazy(element: &ElementRef) -> bool {
+    let value = element.value();
+    let native = value
+        .attr("loading")
+        .is_some_and(|loading| loading.eq_ignore_ascii_case("lazy"));
+    let scripted = value.attr("src").is_none() && value.attr("data-src").is_some();
+    let class = value.classes().any(|class| {
+        LAZY_CLASSES
+            .iter()
+            .any(|lazy| class.eq_ignore_ascii_case(lazy))
+    });
+    native || scripted || class
+}
+
+fn is_lcp_sized(element: &ElementRef) -> bool {
+    // Images without a declared width may be anything, only tiny declared ones are excluded
+    element
+        .value()
+        .attr("width")
+        .and_then(|width| width.trim().trim_end_matches("px").parse::<u32>().ok())
+        .map_or(true, |width| width >= LCP_MIN_WIDTH)
+}
+
+/// Finds images and iframes that are lazy loaded in the wrong place of the page.
+pub fn audit_lazy_loading(html: &str, page_url: &Url) -> LazyLoadingAudit {
+    let document = Html::parse_document(html);
+    let body_selector = Selector::parse("body").unwrap();
+    let mut audit = LazyLoadingAudit::default();
+    let Some(body) = document.select(&body_selector).next() else {
+        return audit;
+    };
+
+    let mut text_chars = 0;
+    let mut lcp_found = false;
+    for node in body.descendants() {
+        match node.value() {
+            Node::Text(text) => {
+                let hidden = node
+                    .parent()
+                    .and_then(ElementRef::wrap)
+                    .is_some_and(|parent| {
+                        matches!(parent.value().name(), "script" | "style" | "noscript")
+                    });
+                if !hidden {
+                    text_chars += text.trim().chars().count();
+                }
+            }
+            Node::Element(element) if matches!(element.name(), "img" | "iframe") => {
+                let Some(element_ref) = ElementRef::wrap(node) else {
+                    continue;
+                };
+                // Tracking pixels and hidden frames have no place in the viewport
+                if element.attr("width") == Some("1") || element.attr("height") == Some("1") {
+                    continue;
+                }
+
+                let position = audit.elements.len();
+                let above_fold =
+                    position < ABOVE_FOLD_ELEMENTS && text_chars < ABOVE_FOLD_TEXT_CHARS;
+                let lazy = is_lazy(&element_ref);
+                let lcp = above_fold
+                    && !lcp_found
+                    && element.name() == "img"
+                    && is_lcp_sized(&element_ref);
+                if lcp {
+                    lcp_found = true;
+                }
+
+                let issue = match (above_fold, lazy) {
+                    (true, true) if lcp => Some(LazyIssue::LazyLcp),
+                    (true, true) => Some(LazyIssue::LazyAboveFold),
+                    (false, false) => Some(LazyIssue::EagerBelowFold),
+                    _ => None,
+                };
+                let src = element
+                    .attr("src")
+                    .or_else(|| element.attr("data-src"))
+                    .filter(|src| !src.starts_with("data:"))
+                    .and_then(|src| page_url.join(src.trim()).ok())
+                    .map(|url| url.to_string());
+                if lcp {
+                    audit.lcp_candidate = src.clone();
+                }
+
+                match issue {
+                    Some(LazyIssue::EagerBelowFold) => audit.eager_below_fold += 1,
+                    Some(_) => audit.lazy_above_fold += 1,
+                    None => {}
+                }
+                if lazy {
+                    audit.lazy += 1;
+                }
+                audit.elements.push(LazyElement {
+                    tag: element.name().to_string(),
+                    src,
+                    position,
+                    lazy,
+                    above_fold,
+                    issue,
+                });
+            }
+            _ => {}
+        }
+    }
+    audit
+}
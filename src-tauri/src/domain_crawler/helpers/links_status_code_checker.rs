@@ -3,7 +3,6 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use futures::future::join_all;
-use rand::seq::SliceRandom;
 use rand::Rng;
 use reqwest::{
     header::{
@@ -17,7 +16,9 @@ use tokio::sync::{Mutex, Semaphore};
 use tokio::task::{JoinError, JoinHandle};
 use tokio::time::{sleep, timeout};
 
-use crate::domain_crawler::{helpers::anchor_links::InternalExternalLinks, user_agents};
+use crate::domain_crawler::{
+    helpers::anchor_links::InternalExternalLinks, proxies, session, subresources, user_agents,
+};
 
 // Constants configuration
 const MAX_CONCURRENT_REQUESTS: usize = 8;
@@ -161,18 +162,15 @@ fn build_client() -> Client {
     headers.insert(DNT, "1".parse().unwrap());
     headers.insert(UPGRADE_INSECURE_REQUESTS, "1".parse().unwrap());
 
-    Client::builder()
+    proxies::apply(Client::builder())
         .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
         .connect_timeout(Duration::from_secs(CONNECTION_TIMEOUT_SECS))
         .pool_idle_timeout(Duration::from_secs(POOL_IDLE_TIMEOUT_SECS))
         .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
-        .user_agent(
-            user_agents::agents()
-                .choose(&mut rand::thread_rng())
-                .unwrap(),
-        )
+        .user_agent(user_agents::current())
         .redirect(reqwest::redirect::Policy::limited(5))
         .default_headers(headers)
+        .cookie_provider(session::cookies())
         .danger_accept_invalid_certs(false)
         .build()
         .expect("Failed to create HTTP client")
@@ -394,11 +392,11 @@ async fn try_head_then_get(
     url: &str,
 ) -> Result<reqwest::Response, reqwest::Error> {
     // Try HEAD request first
-    match client.head(url).send().await {
+    match subresources::send(client.head(url), url).await {
         Ok(response) => Ok(response),
         Err(head_err) => {
             // Fallback to GET if HEAD fails
-            match client.get(url).send().await {
+            match subresources::send(client.get(url), url).await {
                 Ok(response) => Ok(response),
                 Err(get_err) => Err(get_err),
             }
@@ -0,0 +1,215 @@
+use futures::future::join_all;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::domain_crawler::subresources;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum MediaKind {
+    Video,
+    Audio,
+    /// A player iframe from a video host
+    Embed,
+}
+
+/// A media file from a `src` attribute or a `<source>` child.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaSource {
+    pub url: String,
+    /// The `type` attribute, such as `video/mp4`
+    pub mime_type: Option<String>,
+    /// From a HEAD request, None when the server did not say
+    pub size: Option<u64>,
+    pub status_code: Option<u16>,
+    pub content_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaTrack {
+    pub kind: String,
+    pub src: Option<String>,
+    pub srclang: Option<String>,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaElement {
+    pub kind: MediaKind,
+    /// YouTube or Vimeo for embeds
+    pub provider: Option<String>,
+    pub embed_url: Option<String>,
+    pub poster: Option<String>,
+    pub sources: Vec<MediaSource>,
+    pub tracks: Vec<MediaTrack>,
+    pub autoplay: bool,
+    pub muted: bool,
+    pub controls: bool,
+    /// A video without a captions or subtitles track, None when it cannot be told
+    pub missing_captions: Option<bool>,
+}
+
+// Hosts whose iframes are video players, matched against the iframe host
+const EMBED_PROVIDERS: [(&str, &str); 4] = [
+    ("youtube.com", "YouTube"),
+    ("youtube-nocookie.com", "YouTube"),
+    ("youtu.be", "YouTube"),
+    ("vimeo.com", "Vimeo"),
+];
+
+fn attr(element: &ElementRef, name: &str) -> Option<String> {
+    element
+        .value()
+        .attr(name)
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn resolve(base_url: &Url, src: &str) -> Option<String> {
+    base_url.join(src).ok().map(|url| url.to_string())
+}
+
+fn embed_provider(src: &Url) -> Option<&'static str> {
+    let host = src.host_str()?.trim_start_matches("www.");
+    EMBED_PROVIDERS
+        .iter()
+        .find(|(domain, _)| host == *domain || host.ends_with(&format!(".{}", domain)))
+        .map(|(_, provider)| *provider)
+}
+
+fn read_media(element: &ElementRef, kind: MediaKind, base_url: &Url) -> MediaElement {
+    let source_selector = Selector::parse("source").unwrap();
+    let track_selector = Selector::parse("track").unwrap();
+
+    let mut sources: Vec<MediaSource> = Vec::new();
+    let child_sources = element
+        .select(&source_selector)
+        .filter_map(|source| Some((attr(&source, "src")?, attr(&source, "type"))));
+    // Lazy loaders keep the file in data-src until the player starts
+    let own_source = attr(element, "src")
+        .or_else(|| attr(element, "data-src"))
+        .map(|src| (src, None));
+    for (src, mime_type) in own_source.into_iter().chain(child_sources) {
+        let Some(url) = resolve(base_url, &src) else {
+            continue;
+        };
+        if sources.iter().any(|s| s.url == url) {
+            continue;
+        }
+        sources.push(MediaSource {
+            url,
+            mime_type,
+            size: None,
+            status_code: None,
+            content_type: None,
+        });
+    }
+
+    let tracks: Vec<MediaTrack> = element
+        .select(&track_selector)
+        .map(|track| MediaTrack {
+            // A track without a kind is subtitles
+            kind: attr(&track, "kind")
+                .map(|k| k.to_lowercase())
+                .unwrap_or_else(|| "subtitles".to_string()),
+            src: attr(&track, "src").and_then(|src| resolve(base_url, &src)),
+            srclang: attr(&track, "srclang"),
+            label: attr(&track, "label"),
+        })
+        .collect();
+
+    let missing_captions = (kind == MediaKind::Video).then(|| {
+        !tracks
+            .iter()
+            .any(|t| t.kind == "captions" || t.kind == "subtitles")
+    });
+
+    MediaElement {
+        kind,
+        provider: None,
+        embed_url: None,
+        poster: attr(element, "poster").and_then(|poster| resolve(base_url, &poster)),
+        sources,
+        tracks,
+        autoplay: element.value().attr("autoplay").is_some(),
+        muted: element.value().attr("muted").is_some(),
+        controls: element.value().attr("controls").is_some(),
+        missing_captions,
+    }
+}
+
+/// Finds the `<video>` and `<audio>` elements and the YouTube and Vimeo players on a page.
+pub fn extract_media(html: &str, base_url: &Url) -> Vec<MediaElement> {
+    let document = Html::parse_document(html);
+    let mut media = Vec::new();
+
+    for (selector, kind) in [("video", MediaKind::Video), ("audio", MediaKind::Audio)] {
+        let selector = Selector::parse(selector).unwrap();
+        for element in document.select(&selector) {
+            media.push(read_media(&element, kind, base_url));
+        }
+    }
+
+    let iframe_selector = Selector::parse("iframe").unwrap();
+    for iframe in document.select(&iframe_selector) {
+        let Some(src) = attr(&iframe, "src").or_else(|| attr(&iframe, "data-src")) else {
+            continue;
+        };
+        let Ok(src) = base_url.join(&src) else {
+            continue;
+        };
+        let Some(provider) = embed_provider(&src) else {
+            continue;
+        };
+        let query = |name: &str| {
+            src.query_pairs()
+                .any(|(key, value)| key == name && value == "1")
+        };
+        media.push(MediaElement {
+            kind: MediaKind::Embed,
+            provider: Some(provider.to_string()),
+            embed_url: Some(src.to_string()),
+            poster: None,
+            sources: Vec::new(),
+            tracks: Vec::new(),
+            autoplay: query("autoplay"),
+            muted: query("mute") || query("muted"),
+            controls: !src
+                .query_pairs()
+                .any(|(key, value)| key == "controls" && value == "0"),
+            // Captions of hosted players live on the host, not in the page
+            missing_captions: None,
+        });
+    }
+    media
+}
+
+async fn head_source(source: &mut MediaSource) {
+    let Ok(url) = Url::parse(&source.url) else {
+        return;
+    };
+    // Shared by every page embedding the file, it is requested once per crawl
+    match subresources::head(url.as_str()).await {
+        Ok(head) => {
+            source.status_code = Some(head.status.as_u16());
+            source.content_type = head.header(reqwest::header::CONTENT_TYPE);
+            source.size = head
+                .header(reqwest::header::CONTENT_LENGTH)
+                .and_then(|s| s.parse().ok());
+        }
+        Err(e) => eprintln!("Failed to check media file {}: {}", url, e),
+    }
+}
+
+/// Extracts the media on a page and checks the size and status of every file with HEAD.
+pub async fn extract_media_with_sizes(html: &str, base_url: &Url) -> Vec<MediaElement> {
+    let mut media = extract_media(html, base_url);
+    join_all(
+        media
+            .iter_mut()
+            .flat_map(|element| element.sources.iter_mut())
+            .map(head_source),
+    )
+    .await;
+    media
+}
@@ -1,12 +1,16 @@
 pub mod alt_tags;
+pub mod amp_selector;
 pub mod anchor_links;
+pub mod assets_selector;
 pub mod blocked_robots;
 pub mod canonical_selector;
 pub mod check_html_page;
+pub mod content_analyzer;
 pub mod cross_origin;
 pub mod css_selector;
 pub mod domain_checker;
 pub mod flesch_reader;
+pub mod font_selector;
 pub mod headings_selector;
 pub mod hreflang_selector;
 pub mod html_size_calculator;
@@ -17,16 +21,30 @@ pub mod indexability;
 pub mod javascript_selector;
 pub mod keyword_selector;
 pub mod language_selector;
+pub mod lazy_loading;
 pub mod links_selector;
 pub mod links_status_code_checker;
+pub mod media_selector;
 pub mod meta_robots_selector;
 pub mod mobile_checker;
 pub mod page_description;
+pub mod pagination_selector;
 pub mod pdf_checker;
 pub mod pdf_selector;
+pub mod render_diff;
 pub mod robots;
 pub mod schema_selector;
+pub mod security_headers;
+pub mod site_icons;
 pub mod sitemap;
+pub mod social_tags_selector;
+pub mod structured_data_selector;
+pub mod term_analysis;
 pub mod text_ratio;
+pub mod title_description;
 pub mod title_selector;
+pub mod tls_certificate;
+pub mod tracking_selector;
+pub mod transfer_diagnostics;
+pub mod waterfall;
 pub mod word_count;
@@ -0,0 +1,86 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+// Query parameters that carry a page number, `p` is left out as it is a post ID on WordPress
+const PAGE_PARAMETERS: [&str; 6] = ["page", "pg", "paged", "pagenum", "page_number", "pagina"];
+
+static PAGE_PATH: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)/page/(\d+)/?$").unwrap());
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct PaginationInfo {
+    /// `<link rel="prev">`, resolved against the page
+    pub prev: Option<String>,
+    /// `<link rel="next">`, resolved against the page
+    pub next: Option<String>,
+    /// The page number in the URL, `?page=3` or `/page/3/`
+    pub page: Option<u32>,
+    /// The query parameter carrying the number, none for path-style pagination
+    pub parameter: Option<String>,
+}
+
+impl PaginationInfo {
+    pub fn is_paginated(&self) -> bool {
+        self.prev.is_some() || self.next.is_some() || self.page.is_some()
+    }
+}
+
+/// The page number of a URL and the query parameter carrying it.
+pub fn page_number(url: &Url) -> Option<(u32, Option<String>)> {
+    for (name, value) in url.query_pairs() {
+        if PAGE_PARAMETERS.iter().any(|p| name.eq_ignore_ascii_case(p)) {
+            if let Ok(page) = value.trim().parse() {
+                return Some((page, Some(name.into_owned())));
+            }
+        }
+    }
+    PAGE_PATH
+        .captures(url.path())
+        .and_then(|captures| captures[1].parse().ok())
+        .map(|page| (page, None))
+}
+
+/// The first page of the series a URL belongs to, the URL without its page number.
+pub fn first_page_url(url: &Url) -> Url {
+    let mut first = url.clone();
+    first.set_fragment(None);
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| !PAGE_PARAMETERS.iter().any(|p| name.eq_ignore_ascii_case(p)))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    if pairs.is_empty() {
+        first.set_query(None);
+    } else {
+        first.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    let path = PAGE_PATH.replace(url.path(), "/").into_owned();
+    first.set_path(&path);
+    first
+}
+
+pub fn extract_pagination(html: &str, page_url: &Url) -> PaginationInfo {
+    let document = Html::parse_document(html);
+    let link = |rel: &str| {
+        let selector = Selector::parse(&format!("link[rel~='{}'][href]", rel)).unwrap();
+        document
+            .select(&selector)
+            .next()
+            .and_then(|link| link.value().attr("href"))
+            .and_then(|href| page_url.join(href.trim()).ok())
+            .map(|url| url.to_string())
+    };
+    let (page, parameter) = match page_number(page_url) {
+        Some((page, parameter)) => (Some(page), parameter),
+        None => (None, None),
+    };
+
+    PaginationInfo {
+        prev: link("prev").or_else(|| link("previous")),
+        next: link("next"),
+        page,
+        parameter,
+    }
+}
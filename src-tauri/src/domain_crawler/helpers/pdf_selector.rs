@@ -1,12 +1,66 @@
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use regex::bytes::Regex;
+use reqwest::header;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+use super::images_selector::{image_client, image_permit};
+use crate::domain_crawler::crawl_cache::CrawlCache;
+use crate::domain_crawler::subresources;
+
+// PDFs above this size are flagged in the crawl report (10 MB)
+const OVERSIZED_PDF_BYTES: u64 = 10 * 1024 * 1024;
+
+// How many bytes of each PDF to download to look for metadata
+const PDF_HEADER_BYTES: u64 = 65536;
+
+static PDF_VERSION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^%PDF-(\d\.\d)").unwrap());
+// Only uncompressed info dictionaries are readable this way
+static PDF_TITLE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"/Title\s*\(((?:[^()\\]|\\.)*)\)").unwrap());
+// Linearized PDFs put the page count right at the start of the file
+static PDF_LINEARIZED_PAGES: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"/Linearized\s[^>]*?/N\s+(\d+)").unwrap());
+static PDF_PAGES: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"/Type\s*/Pages\b[^>]*?/Count\s+(\d+)").unwrap());
+
+// PDFs linked from many pages are checked once per crawl rather than once per page
+static PDF_AUDITS: Lazy<CrawlCache<PdfAudit>> = Lazy::new(CrawlCache::default);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PdfLinks {
     pdf_links: Vec<String>,
 }
 
+impl PdfLinks {
+    pub fn links(&self) -> &[String] {
+        &self.pdf_links
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PdfAudit {
+    pub url: String,
+    /// The status of the PDF itself, after following redirects
+    pub status: Option<u16>,
+    /// Where the link redirects to, when it does not point at the file directly
+    #[serde(default)]
+    pub redirected_to: Option<String>,
+    pub content_type: Option<String>,
+    pub size_bytes: Option<u64>,
+    pub version: Option<String>,
+    pub title: Option<String>,
+    pub page_count: Option<u32>,
+    pub oversized: bool,
+    pub error: Option<String>,
+}
+
+pub fn reset_pdf_cache() {
+    PDF_AUDITS.clear();
+}
+
 pub fn extract_pdf_links(body: &str, base_url: &Url) -> Option<PdfLinks> {
     let document = Html::parse_document(body);
 
@@ -40,3 +94,141 @@ pub fn extract_pdf_links(body: &str, base_url: &Url) -> Option<PdfLinks> {
         Some(PdfLinks { pdf_links })
     }
 }
+
+/// Checks every linked PDF for status, content type and size, and reads the
+/// version, title and page count from the first bytes of the file when available.
+pub async fn audit_pdf_links(pdf_links: &PdfLinks) -> Vec<PdfAudit> {
+    let mut links = pdf_links.pdf_links.clone();
+    links.sort();
+    links.dedup();
+
+    let futures = links
+        .iter()
+        .map(|url| PDF_AUDITS.get_or_init(url, || audit_pdf(url.clone())));
+
+    join_all(futures).await
+}
+
+// The image client follows redirects, so a moved PDF reports where it went instead of a 3xx
+async fn audit_pdf(url: String) -> PdfAudit {
+    let mut audit = PdfAudit {
+        url: url.clone(),
+        ..Default::default()
+    };
+    let _permit = image_permit().await;
+    let client = image_client();
+
+    // HEAD first for the cheap metadata
+    match subresources::send(client.head(&url), &url).await {
+        Ok(response) => {
+            audit.status = Some(response.status().as_u16());
+            audit.redirected_to = redirect_target(&url, &response);
+            audit.content_type = header_value(&response, header::CONTENT_TYPE);
+            audit.size_bytes =
+                header_value(&response, header::CONTENT_LENGTH).and_then(|v| v.parse().ok());
+        }
+        Err(e) => audit.error = Some(format!("HEAD request failed: {}", e)),
+    }
+
+    // Then a range request for the header bytes
+    let range = client
+        .get(&url)
+        .header(header::RANGE, format!("bytes=0-{}", PDF_HEADER_BYTES - 1));
+    let response = match subresources::send(range, &url).await {
+        Ok(response) => response,
+        Err(e) => {
+            audit.error = Some(format!("Range request failed: {}", e));
+            return audit;
+        }
+    };
+
+    let status = response.status();
+    if audit.status.map_or(true, |s| s >= 400) {
+        audit.status = Some(status.as_u16());
+        audit.redirected_to = redirect_target(&url, &response);
+    }
+
+    if !status.is_success() {
+        audit.error = Some(format!("HTTP Error: {}", status));
+        return audit;
+    }
+
+    if audit.content_type.is_none() {
+        audit.content_type = header_value(&response, header::CONTENT_TYPE);
+    }
+
+    // Content-Range carries the full size when the server honoured the range
+    if audit.size_bytes.is_none() {
+        audit.size_bytes = header_value(&response, header::CONTENT_RANGE)
+            .and_then(|v| v.rsplit('/').next().and_then(|total| total.parse().ok()));
+    }
+
+    match read_limited(response, PDF_HEADER_BYTES as usize).await {
+        Ok(bytes) => {
+            audit.version = parse_pdf_version(&bytes);
+            audit.title = parse_pdf_title(&bytes);
+            audit.page_count = parse_pdf_page_count(&bytes);
+            if audit.version.is_none() {
+                audit.error = Some("File does not start with a PDF header".to_string());
+            }
+        }
+        Err(e) => audit.error = Some(e),
+    }
+
+    audit.oversized = audit.size_bytes.is_some_and(|s| s > OVERSIZED_PDF_BYTES);
+
+    audit
+}
+
+fn redirect_target(url: &str, response: &reqwest::Response) -> Option<String> {
+    let requested = Url::parse(url).ok()?;
+    (response.url() != &requested).then(|| response.url().to_string())
+}
+
+fn header_value(response: &reqwest::Response, name: header::HeaderName) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+}
+
+// Servers that ignore the Range header return the whole file, so stop reading early
+async fn read_limited(mut response: reqwest::Response, limit: usize) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(limit);
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read PDF bytes: {}", e))?
+    {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() >= limit {
+            bytes.truncate(limit);
+            break;
+        }
+    }
+    Ok(bytes)
+}
+
+fn parse_pdf_version(bytes: &[u8]) -> Option<String> {
+    PDF_VERSION
+        .captures(bytes)
+        .and_then(|c| c.get(1))
+        .map(|m| String::from_utf8_lossy(m.as_bytes()).to_string())
+}
+
+fn parse_pdf_title(bytes: &[u8]) -> Option<String> {
+    PDF_TITLE
+        .captures(bytes)
+        .and_then(|c| c.get(1))
+        .map(|m| String::from_utf8_lossy(m.as_bytes()).trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+fn parse_pdf_page_count(bytes: &[u8]) -> Option<u32> {
+    [&*PDF_LINEARIZED_PAGES, &*PDF_PAGES].iter().find_map(|re| {
+        re.captures(bytes)
+            .and_then(|c| c.get(1))
+            .and_then(|m| String::from_utf8_lossy(m.as_bytes()).parse().ok())
+    })
+}
@@ -0,0 +1,125 @@
+use std::collections::BTreeSet;
+
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::structured_data_selector::extract_structured_data;
+
+/// SEO elements that differ between the served HTML and the rendered DOM.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum JsDependency {
+    Title,
+    MetaRobots,
+    Canonical,
+    Links,
+    StructuredData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RenderDiff {
+    pub raw_title: Option<String>,
+    pub rendered_title: Option<String>,
+    pub raw_meta_robots: Vec<String>,
+    pub rendered_meta_robots: Vec<String>,
+    pub raw_canonical: Option<String>,
+    pub rendered_canonical: Option<String>,
+    /// Link targets present in only one of the two versions
+    pub rendered_only_links: Vec<String>,
+    pub raw_only_links: Vec<String>,
+    /// Structured data types present only after rendering
+    pub rendered_only_types: Vec<String>,
+    pub js_dependencies: Vec<JsDependency>,
+}
+
+struct SeoElements {
+    title: Option<String>,
+    meta_robots: Vec<String>,
+    canonical: Option<String>,
+    links: BTreeSet<String>,
+    types: BTreeSet<String>,
+}
+
+fn elements(html: &str, page_url: &Url) -> SeoElements {
+    let document = Html::parse_document(html);
+    let title_selector = Selector::parse("head title").unwrap();
+    let robots_selector = Selector::parse("meta[name='robots'], meta[name='googlebot']").unwrap();
+    let canonical_selector = Selector::parse("link[rel='canonical']").unwrap();
+    let link_selector = Selector::parse("a[href]").unwrap();
+
+    SeoElements {
+        title: document
+            .select(&title_selector)
+            .next()
+            .map(|t| t.text().collect::<String>().trim().to_string())
+            .filter(|t| !t.is_empty()),
+        meta_robots: document
+            .select(&robots_selector)
+            .filter_map(|m| m.value().attr("content"))
+            .map(|c| c.trim().to_lowercase())
+            .collect(),
+        canonical: document
+            .select(&canonical_selector)
+            .filter_map(|l| l.value().attr("href"))
+            .find_map(|href| page_url.join(href.trim()).ok())
+            .map(|url| url.to_string()),
+        links: document
+            .select(&link_selector)
+            .filter_map(|a| a.value().attr("href"))
+            .filter_map(|href| page_url.join(href.trim()).ok())
+            .filter(|url| url.scheme().starts_with("http"))
+            .map(|mut url| {
+                url.set_fragment(None);
+                url.to_string()
+            })
+            .collect(),
+        types: extract_structured_data(html).types.into_iter().collect(),
+    }
+}
+
+/// Compares the raw response with the rendered DOM of a page.
+///
+/// # Arguments
+/// * `raw_html` - The HTML as served, before any JavaScript ran.
+/// * `rendered_html` - The DOM captured after rendering.
+/// * `page_url` - The URL of the page, used to resolve links and canonicals.
+///
+/// # Returns
+/// * `RenderDiff` - What changed and which critical elements depend on JavaScript.
+pub fn diff_rendered(raw_html: &str, rendered_html: &str, page_url: &Url) -> RenderDiff {
+    let raw = elements(raw_html, page_url);
+    let rendered = elements(rendered_html, page_url);
+
+    let mut js_dependencies = Vec::new();
+    if raw.title != rendered.title {
+        js_dependencies.push(JsDependency::Title);
+    }
+    if raw.meta_robots != rendered.meta_robots {
+        js_dependencies.push(JsDependency::MetaRobots);
+    }
+    if raw.canonical != rendered.canonical {
+        js_dependencies.push(JsDependency::Canonical);
+    }
+
+    let rendered_only_links: Vec<String> = rendered.links.difference(&raw.links).cloned().collect();
+    if !rendered_only_links.is_empty() {
+        js_dependencies.push(JsDependency::Links);
+    }
+    let rendered_only_types: Vec<String> = rendered.types.difference(&raw.types).cloned().collect();
+    if !rendered_only_types.is_empty() {
+        js_dependencies.push(JsDependency::StructuredData);
+    }
+
+    RenderDiff {
+        raw_only_links: raw.links.difference(&rendered.links).cloned().collect(),
+        rendered_only_links,
+        rendered_only_types,
+        raw_title: raw.title,
+        rendered_title: rendered.title,
+        raw_meta_robots: raw.meta_robots,
+        rendered_meta_robots: rendered.meta_robots,
+        raw_canonical: raw.canonical,
+        rendered_canonical: rendered.canonical,
+        js_dependencies,
+    }
+}
@@ -1,6 +1,14 @@
-use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
 use url::Url;
 
+use crate::domain_crawler::request_auth;
+
 pub async fn get_domain_robots(base_url: &Url) -> Option<Vec<String>> {
     let client = Client::new();
     let robots_url = base_url.join("robots.txt").unwrap();
@@ -21,3 +29,413 @@ pub async fn get_domain_robots(base_url: &Url) -> Option<Vec<String>> {
         Some(vec_robot)
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RobotsRule {
+    pub allow: bool,
+    pub pattern: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RobotsGroup {
+    pub user_agents: Vec<String>,
+    pub rules: Vec<RobotsRule>,
+    pub crawl_delay: Option<f64>,
+}
+
+/// Parsed robots.txt file.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RobotsRules {
+    pub groups: Vec<RobotsGroup>,
+    pub sitemaps: Vec<String>,
+}
+
+impl RobotsRules {
+    /// Allows everything, used when robots.txt is missing.
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Disallows everything, used while the server fails to return robots.txt.
+    pub fn disallow_all() -> Self {
+        Self {
+            groups: vec![RobotsGroup {
+                user_agents: vec!["*".to_string()],
+                rules: vec![RobotsRule {
+                    allow: false,
+                    pattern: "/".to_string(),
+                }],
+                crawl_delay: None,
+            }],
+            sitemaps: Vec::new(),
+        }
+    }
+
+    /// The rules implied by a robots.txt response that has no usable body, per RFC 9309:
+    /// client errors mean there are no rules, server errors mean nothing may be crawled.
+    pub fn for_status(status: StatusCode) -> Self {
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            Self::disallow_all()
+        } else {
+            Self::allow_all()
+        }
+    }
+
+    pub fn parse(body: &str) -> Self {
+        let mut rules = RobotsRules::default();
+        let mut current: Option<RobotsGroup> = None;
+        // Consecutive user-agent lines share the group that follows them
+        let mut collecting_agents = false;
+
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+
+            match key.as_str() {
+                "user-agent" => {
+                    if !collecting_agents {
+                        if let Some(group) = current.take() {
+                            rules.groups.push(group);
+                        }
+                        current = Some(RobotsGroup::default());
+                    }
+                    if let Some(group) = current.as_mut() {
+                        group.user_agents.push(value.to_lowercase());
+                    }
+                    collecting_agents = true;
+                }
+                "allow" | "disallow" => {
+                    collecting_agents = false;
+                    // An empty Disallow means allow everything, so it adds no rule
+                    if let (Some(group), false) = (current.as_mut(), value.is_empty()) {
+                        group.rules.push(RobotsRule {
+                            allow: key == "allow",
+                            pattern: value.to_string(),
+                        });
+                    }
+                }
+                "crawl-delay" => {
+                    collecting_agents = false;
+                    if let Some(group) = current.as_mut() {
+                        group.crawl_delay = value.parse().ok();
+                    }
+                }
+                "sitemap" => {
+                    // Sitemap directives are global and may appear anywhere
+                    rules.sitemaps.push(value.to_string());
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(group) = current {
+            rules.groups.push(group);
+        }
+
+        rules
+    }
+
+    /// Returns the groups that apply to the user agent: the most specific named
+    /// group(s) if any match, otherwise the `*` group(s).
+    fn groups_for(&self, user_agent: &str) -> Vec<&RobotsGroup> {
+        let user_agent = user_agent.to_lowercase();
+
+        let best = self
+            .groups
+            .iter()
+            .flat_map(|g| g.user_agents.iter())
+            .filter(|agent| agent.as_str() != "*" && user_agent.contains(agent.as_str()))
+            .max_by_key(|agent| agent.len());
+
+        match best {
+            Some(best) => self
+                .groups
+                .iter()
+                .filter(|g| g.user_agents.contains(best))
+                .collect(),
+            None => self
+                .groups
+                .iter()
+                .filter(|g| g.user_agents.iter().any(|a| a == "*"))
+                .collect(),
+        }
+    }
+
+    /// The rule that decides whether `url` may be crawled by `user_agent`, None when no
+    /// rule matches and the URL is allowed.
+    ///
+    /// Uses the longest matching rule, with Allow winning ties, and supports the
+    /// `*` and `$` wildcards.
+    pub fn matching_rule(&self, user_agent: &str, url: &Url) -> Option<&RobotsRule> {
+        let mut path = url.path().to_string();
+        if let Some(query) = url.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+
+        if path == "/robots.txt" {
+            return None;
+        }
+
+        self.groups_for(user_agent)
+            .into_iter()
+            .flat_map(|g| g.rules.iter())
+            .filter(|rule| pattern_matches(&rule.pattern, &path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+    }
+
+    /// Checks whether `url` may be crawled by `user_agent`.
+    pub fn is_allowed(&self, user_agent: &str, url: &Url) -> bool {
+        self.matching_rule(user_agent, url)
+            .map_or(true, |rule| rule.allow)
+    }
+
+    pub fn crawl_delay(&self, user_agent: &str) -> Option<f64> {
+        self.groups_for(user_agent)
+            .iter()
+            .find_map(|g| g.crawl_delay)
+    }
+}
+
+/// Matches a robots.txt path pattern with `*` and `$` wildcards against a path.
+///
+/// Patterns match as prefixes unless they end with `$`.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let pattern = match pattern.strip_suffix('$') {
+        Some(p) => p.to_string(),
+        None => format!("{}*", pattern),
+    };
+
+    let (p, t) = (pattern.as_bytes(), path.as_bytes());
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    // Classic greedy wildcard match with backtracking to the last `*`
+    while ti < t.len() {
+        if pi < p.len() && p[pi] == b'*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if pi < p.len() && p[pi] == t[ti] {
+            pi += 1;
+            ti += 1;
+        } else if let Some((star_pi, star_ti)) = star {
+            pi = star_pi + 1;
+            ti = star_ti + 1;
+            star = Some((star_pi, star_ti + 1));
+        } else {
+            return false;
+        }
+    }
+
+    p[pi..].iter().all(|c| *c == b'*')
+}
+
+// How long a server error keeps a host disallowed before robots.txt is requested again
+const ROBOTS_RETRY: Duration = Duration::from_secs(60);
+
+// Parsed rules of a host and when they expire, None for good
+type HostRules = (Arc<RobotsRules>, Option<Instant>);
+
+/// Fetches robots.txt once per host and keeps the parsed rules for the crawl.
+///
+/// Rules disallowing a host because its server failed are kept for `ROBOTS_RETRY` only.
+pub struct RobotsCache {
+    client: Client,
+    hosts: Mutex<HashMap<String, HostRules>>,
+}
+
+impl RobotsCache {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn rules_for(&self, url: &Url) -> Arc<RobotsRules> {
+        let host = format!("{}://{}", url.scheme(), url.host_str().unwrap_or(""));
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host,
+        };
+
+        // Hold the lock while fetching so each host is only requested once
+        let mut hosts = self.hosts.lock().await;
+        if let Some((rules, retry_at)) = hosts.get(&host) {
+            if retry_at.map_or(true, |retry_at| Instant::now() < retry_at) {
+                return rules.clone();
+            }
+        }
+
+        let (rules, temporary) = self.fetch(&host).await;
+        let rules = Arc::new(rules);
+        let retry_at = temporary.then(|| Instant::now() + ROBOTS_RETRY);
+        hosts.insert(host, (rules.clone(), retry_at));
+        rules
+    }
+
+    // Missing files are treated as "allow all", server and network errors as "disallow all"
+    // until the next attempt
+    async fn fetch(&self, host: &str) -> (RobotsRules, bool) {
+        let robots_url = format!("{}/robots.txt", host);
+
+        match request_auth::apply(self.client.get(&robots_url), &robots_url)
+            .send()
+            .await
+        {
+            Ok(response) if response.status().is_success() => match response.text().await {
+                Ok(body) => (RobotsRules::parse(&body), false),
+                Err(e) => {
+                    eprintln!("Failed to read {}: {}", robots_url, e);
+                    (RobotsRules::disallow_all(), true)
+                }
+            },
+            Ok(response) => {
+                let rules = RobotsRules::for_status(response.status());
+                let temporary = !rules.groups.is_empty();
+                if temporary {
+                    eprintln!(
+                        "{} returned {}, the host is disallowed for now",
+                        robots_url,
+                        response.status()
+                    );
+                }
+                (rules, temporary)
+            }
+            Err(e) => {
+                eprintln!("Failed to fetch {}: {}", robots_url, e);
+                (RobotsRules::disallow_all(), true)
+            }
+        }
+    }
+}
+
+/// Outcome of testing a URL against a robots.txt file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RobotsTestResult {
+    pub allowed: bool,
+    /// The rule that decided, None when no rule matched
+    pub matched_rule: Option<RobotsRule>,
+    /// User agents of the groups that apply, `*` when no group names the agent
+    pub matched_groups: Vec<Vec<String>>,
+    pub crawl_delay: Option<f64>,
+    pub sitemaps: Vec<String>,
+}
+
+// TEST A URL AGAINST A ROBOTS.TXT FILE WITH THE CRAWLER'S OWN RULES
+#[tauri::command]
+pub fn test_robots_rule(
+    robots_txt: String,
+    user_agent: String,
+    url: String,
+) -> Result<RobotsTestResult, String> {
+    let url = Url::parse(url.trim()).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    let rules = RobotsRules::parse(&robots_txt);
+    let matched_rule = rules.matching_rule(&user_agent, &url).cloned();
+    Ok(RobotsTestResult {
+        allowed: matched_rule.as_ref().map_or(true, |rule| rule.allow),
+        matched_groups: rules
+            .groups_for(&user_agent)
+            .into_iter()
+            .map(|group| group.user_agents.clone())
+            .collect(),
+        crawl_delay: rules.crawl_delay(&user_agent),
+        sitemaps: rules.sitemaps.clone(),
+        matched_rule,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "
+User-agent: *
+Disallow: /private/
+Allow: /private/open
+Disallow: /*.pdf$
+Crawl-delay: 2
+
+User-agent: Googlebot
+User-agent: Bingbot
+Disallow: /search # internal results
+Crawl-delay: 0.5
+
+Sitemap: https://example.com/sitemap.xml
+";
+
+    fn allowed(rules: &RobotsRules, user_agent: &str, path: &str) -> bool {
+        let url = Url::parse("https://example.com")
+            .unwrap()
+            .join(path)
+            .unwrap();
+        rules.is_allowed(user_agent, &url)
+    }
+
+    #[test]
+    fn parses_groups_and_sitemaps() {
+        let rules = RobotsRules::parse(ROBOTS);
+        assert_eq!(rules.groups.len(), 2);
+        assert_eq!(rules.groups[1].user_agents, ["googlebot", "bingbot"]);
+        assert_eq!(rules.sitemaps, ["https://example.com/sitemap.xml"]);
+    }
+
+    #[test]
+    fn longest_rule_wins_and_allow_breaks_ties() {
+        let rules = RobotsRules::parse(ROBOTS);
+        let agent = "RustySEO/1.0";
+        assert!(allowed(&rules, agent, "/"));
+        assert!(!allowed(&rules, agent, "/private/page"));
+        assert!(allowed(&rules, agent, "/private/open-day"));
+        assert!(!allowed(&rules, agent, "/files/report.pdf"));
+        assert!(allowed(&rules, agent, "/files/report.pdf?download=1"));
+        assert!(allowed(&rules, agent, "/robots.txt"));
+
+        let tie = RobotsRules::parse("User-agent: *\nDisallow: /page\nAllow: /page");
+        assert!(allowed(&tie, agent, "/page"));
+    }
+
+    #[test]
+    fn named_groups_replace_the_wildcard_group() {
+        let rules = RobotsRules::parse(ROBOTS);
+        let agent = "Mozilla/5.0 (compatible; Googlebot/2.1)";
+        assert!(allowed(&rules, agent, "/private/page"));
+        assert!(!allowed(&rules, agent, "/search?q=shoes"));
+        assert_eq!(rules.crawl_delay(agent), Some(0.5));
+        assert_eq!(rules.crawl_delay("RustySEO/1.0"), Some(2.0));
+    }
+
+    #[test]
+    fn only_client_errors_allow_everything() {
+        let agent = "RustySEO/1.0";
+        for status in [
+            StatusCode::NOT_FOUND,
+            StatusCode::FORBIDDEN,
+            StatusCode::GONE,
+        ] {
+            assert!(allowed(&RobotsRules::for_status(status), agent, "/page"));
+        }
+        for status in [
+            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::SERVICE_UNAVAILABLE,
+            StatusCode::TOO_MANY_REQUESTS,
+        ] {
+            let rules = RobotsRules::for_status(status);
+            assert!(!allowed(&rules, agent, "/"));
+            assert!(!allowed(&rules, "Googlebot", "/page"));
+        }
+    }
+
+    #[test]
+    fn wildcards_match_anywhere_in_the_path() {
+        assert!(pattern_matches("/*/edit", "/posts/12/edit"));
+        assert!(pattern_matches("/shop", "/shop/shoes"));
+        assert!(pattern_matches("/shop$", "/shop"));
+        assert!(!pattern_matches("/shop$", "/shop/shoes"));
+        assert!(!pattern_matches("/*.php$", "/index.php5"));
+    }
+}
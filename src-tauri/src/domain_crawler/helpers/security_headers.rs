@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+
+// Browsers only keep HSTS long enough to matter from about six months up
+const HSTS_MIN_MAX_AGE: u64 = 15_552_000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SecurityHeader {
+    StrictTransportSecurity,
+    ContentSecurityPolicy,
+    XContentTypeOptions,
+    XFrameOptions,
+    ReferrerPolicy,
+}
+
+impl SecurityHeader {
+    pub const ALL: [SecurityHeader; 5] = [
+        SecurityHeader::StrictTransportSecurity,
+        SecurityHeader::ContentSecurityPolicy,
+        SecurityHeader::XContentTypeOptions,
+        SecurityHeader::XFrameOptions,
+        SecurityHeader::ReferrerPolicy,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SecurityHeader::StrictTransportSecurity => "strict-transport-security",
+            SecurityHeader::ContentSecurityPolicy => "content-security-policy",
+            SecurityHeader::XContentTypeOptions => "x-content-type-options",
+            SecurityHeader::XFrameOptions => "x-frame-options",
+            SecurityHeader::ReferrerPolicy => "referrer-policy",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HeaderStatus {
+    Ok,
+    /// Sent, but with a value that leaves the protection partly off
+    Weak,
+    Missing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeaderCheck {
+    pub header: SecurityHeader,
+    pub value: Option<String>,
+    pub status: HeaderStatus,
+    pub note: Option<String>,
+}
+
+/// The security headers a page was served with.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SecurityHeadersAudit {
+    pub checks: Vec<HeaderCheck>,
+    /// 0 to 100, a weak header counts for half
+    pub score: u8,
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim())
+}
+
+fn check_hsts(value: &str, https: bool) -> (HeaderStatus, Option<String>) {
+    if !https {
+        return (
+            HeaderStatus::Missing,
+            Some("Browsers ignore HSTS on plain HTTP pages".to_string()),
+        );
+    }
+    let max_age = value
+        .split(';')
+        .filter_map(|directive| directive.trim().split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("max-age"))
+        .and_then(|(_, age)| age.trim().trim_matches('"').parse::<u64>().ok());
+    match max_age {
+        None => (HeaderStatus::Weak, Some("No valid max-age".to_string())),
+        Some(age) if age < HSTS_MIN_MAX_AGE => (
+            HeaderStatus::Weak,
+            Some(format!("max-age of {} seconds is under six months", age)),
+        ),
+        Some(_) => (HeaderStatus::Ok, None),
+    }
+}
+
+fn check_csp(value: &str) -> (HeaderStatus, Option<String>) {
+    let lower = value.to_lowercase();
+    if lower.contains("'unsafe-inline'") || lower.contains("'unsafe-eval'") {
+        (
+            HeaderStatus::Weak,
+            Some("Allows unsafe-inline or unsafe-eval".to_string()),
+        )
+    } else {
+        (HeaderStatus::Ok, None)
+    }
+}
+
+fn check_value(header: SecurityHeader, value: &str, https: bool) -> (HeaderStatus, Option<String>) {
+    let lower = value.to_lowercase();
+    match header {
+        SecurityHeader::StrictTransportSecurity => check_hsts(value, https),
+        SecurityHeader::ContentSecurityPolicy => check_csp(value),
+        SecurityHeader::XContentTypeOptions if lower == "nosniff" => (HeaderStatus::Ok, None),
+        SecurityHeader::XContentTypeOptions => (
+            HeaderStatus::Weak,
+            Some("Only nosniff is recognised".to_string()),
+        ),
+        SecurityHeader::XFrameOptions if lower == "deny" || lower == "sameorigin" => {
+            (HeaderStatus::Ok, None)
+        }
+        SecurityHeader::XFrameOptions => (
+            HeaderStatus::Weak,
+            Some("Use DENY or SAMEORIGIN, ALLOW-FROM is obsolete".to_string()),
+        ),
+        SecurityHeader::ReferrerPolicy => {
+            // The last recognised token wins, as in browsers
+            let policy = lower.rsplit(',').next().unwrap_or("").trim();
+            if policy == "unsafe-url" || policy == "no-referrer-when-downgrade" {
+                (
+                    HeaderStatus::Weak,
+                    Some(format!("{} leaks full URLs to other sites", policy)),
+                )
+            } else {
+                (HeaderStatus::Ok, None)
+            }
+        }
+    }
+}
+
+/// Checks the response headers for HSTS, CSP, X-Content-Type-Options, X-Frame-Options and
+/// Referrer-Policy.
+pub fn audit_security_headers(headers: &[(String, String)], https: bool) -> SecurityHeadersAudit {
+    let csp = header(headers, SecurityHeader::ContentSecurityPolicy.name());
+    // frame-ancestors supersedes X-Frame-Options in every current browser
+    let frame_ancestors = csp.is_some_and(|csp| csp.to_lowercase().contains("frame-ancestors"));
+
+    let checks: Vec<HeaderCheck> = SecurityHeader::ALL
+        .iter()
+        .map(|&security_header| {
+            let value = header(headers, security_header.name());
+            let (status, note) = match value {
+                Some(value) => check_value(security_header, value, https),
+                None if security_header == SecurityHeader::XFrameOptions && frame_ancestors => (
+                    HeaderStatus::Ok,
+                    Some("Covered by CSP frame-ancestors".to_string()),
+                ),
+                None if security_header == SecurityHeader::ContentSecurityPolicy
+                    && header(headers, "content-security-policy-report-only").is_some() =>
+                {
+                    (
+                        HeaderStatus::Weak,
+                        Some("Only sent in report-only mode".to_string()),
+                    )
+                }
+                None => (HeaderStatus::Missing, None),
+            };
+            HeaderCheck {
+                header: security_header,
+                value: value.map(String::from),
+                status,
+                note,
+            }
+        })
+        .collect();
+
+    let points: usize = checks
+        .iter()
+        .map(|check| match check.status {
+            HeaderStatus::Ok => 2,
+            HeaderStatus::Weak => 1,
+            HeaderStatus::Missing => 0,
+        })
+        .sum();
+    let score = (points * 100 / (checks.len() * 2)) as u8;
+
+    SecurityHeadersAudit { checks, score }
+}
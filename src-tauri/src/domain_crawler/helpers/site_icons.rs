@@ -0,0 +1,402 @@
+use futures::future::join_all;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+use super::images_selector::{image_client, image_permit};
+use crate::domain_crawler::results_store::StoredReport;
+use crate::domain_crawler::subresources;
+
+// Icon sizes browsers ask for when a site is installed as an app
+const INSTALL_ICON_SIZES: [&str; 2] = ["192x192", "512x512"];
+
+/// Whether an icon or manifest URL serves what it should.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FileCheck {
+    pub url: String,
+    pub status_code: Option<u16>,
+    pub content_type: Option<String>,
+    pub size: Option<u64>,
+    /// Answers 200 with an image, judged by content type or file signature
+    pub valid: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeclaredIcon {
+    pub rel: String,
+    pub sizes: Option<String>,
+    pub mime_type: Option<String>,
+    pub check: FileCheck,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestIcon {
+    pub sizes: Option<String>,
+    pub mime_type: Option<String>,
+    pub purpose: Option<String>,
+    pub check: FileCheck,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ManifestCheck {
+    pub url: String,
+    pub status_code: Option<u16>,
+    /// Parse error of the manifest body, None when it is valid JSON
+    pub parse_error: Option<String>,
+    pub name: Option<String>,
+    pub short_name: Option<String>,
+    pub start_url: Option<String>,
+    pub display: Option<String>,
+    pub theme_color: Option<String>,
+    pub background_color: Option<String>,
+    pub icons: Vec<ManifestIcon>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum IconIssue {
+    MissingFavicon,
+    BrokenIcon,
+    MissingAppleTouchIcon,
+    MissingManifest,
+    InvalidManifest,
+    ManifestMissingName,
+    ManifestMissingIcons,
+    ManifestMissingInstallSizes,
+    MissingThemeColor,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IconWarning {
+    pub issue: IconIssue,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct IconReport {
+    /// The `/favicon.ico` browsers request when a page declares no icon
+    pub favicon_ico: FileCheck,
+    /// Icons declared with `<link>` on the start page
+    pub icons: Vec<DeclaredIcon>,
+    pub manifest: Option<ManifestCheck>,
+    /// From `<meta name="theme-color">` on the start page
+    pub theme_color_meta: Option<String>,
+    pub warnings: Vec<IconWarning>,
+}
+
+impl StoredReport for IconReport {
+    const KIND: &'static str = "icons";
+}
+
+fn looks_like_image(body: &[u8]) -> bool {
+    const SIGNATURES: [&[u8]; 5] = [
+        &[0x00, 0x00, 0x01, 0x00], // ICO
+        &[0x89, b'P', b'N', b'G'],
+        b"GIF8",
+        &[0xFF, 0xD8, 0xFF], // JPEG
+        b"RIFF",             // WebP
+    ];
+    let start = String::from_utf8_lossy(&body[..body.len().min(256)]).to_lowercase();
+    SIGNATURES.iter().any(|sig| body.starts_with(sig)) || start.contains("<svg")
+}
+
+async fn fetch(url: &str) -> Result<(u16, Option<String>, Vec<u8>), String> {
+    let _permit = image_permit().await;
+    let response = subresources::send(image_client().get(url), url)
+        .await
+        .map_err(|e| e.to_string())?;
+    let status = response.status().as_u16();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+    let body = response.bytes().await.map_err(|e| e.to_string())?;
+    Ok((status, content_type, body.to_vec()))
+}
+
+async fn check_file(url: String) -> FileCheck {
+    match fetch(&url).await {
+        Ok((status, content_type, body)) => FileCheck {
+            valid: status == 200
+                && (content_type
+                    .as_deref()
+                    .is_some_and(|ct| ct.starts_with("image/"))
+                    || looks_like_image(&body)),
+            status_code: Some(status),
+            content_type,
+            size: Some(body.len() as u64),
+            error: None,
+            url,
+        },
+        Err(e) => FileCheck {
+            url,
+            error: Some(e),
+            ..Default::default()
+        },
+    }
+}
+
+fn manifest_string(manifest: &Value, key: &str) -> Option<String> {
+    manifest
+        .get(key)
+        .and_then(Value::as_str)
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+async fn check_manifest(url: Url) -> ManifestCheck {
+    let mut check = ManifestCheck {
+        url: url.to_string(),
+        ..Default::default()
+    };
+    let body = match fetch(url.as_str()).await {
+        Ok((status, _, body)) => {
+            check.status_code = Some(status);
+            if status != 200 {
+                return check;
+            }
+            body
+        }
+        Err(e) => {
+            check.parse_error = Some(e);
+            return check;
+        }
+    };
+
+    let manifest: Value = match serde_json::from_slice(&body) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            check.parse_error = Some(e.to_string());
+            return check;
+        }
+    };
+    check.name = manifest_string(&manifest, "name");
+    check.short_name = manifest_string(&manifest, "short_name");
+    check.start_url = manifest_string(&manifest, "start_url");
+    check.display = manifest_string(&manifest, "display");
+    check.theme_color = manifest_string(&manifest, "theme_color");
+    check.background_color = manifest_string(&manifest, "background_color");
+
+    // Icon sources are relative to the manifest, not to the page
+    let icons: Vec<(Value, String)> = manifest
+        .get("icons")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|icon| {
+            let src = url.join(icon.get("src")?.as_str()?.trim()).ok()?;
+            Some((icon.clone(), src.to_string()))
+        })
+        .collect();
+    let checks = join_all(icons.iter().map(|(_, src)| check_file(src.clone()))).await;
+    check.icons = icons
+        .into_iter()
+        .zip(checks)
+        .map(|((icon, _), file)| ManifestIcon {
+            sizes: manifest_string(&icon, "sizes"),
+            mime_type: manifest_string(&icon, "type"),
+            purpose: manifest_string(&icon, "purpose"),
+            check: file,
+        })
+        .collect();
+    check
+}
+
+fn warn(warnings: &mut Vec<IconWarning>, issue: IconIssue, message: String) {
+    warnings.push(IconWarning { issue, message });
+}
+
+fn collect_warnings(report: &IconReport) -> Vec<IconWarning> {
+    let mut warnings = Vec::new();
+
+    let favicons: Vec<&DeclaredIcon> = report
+        .icons
+        .iter()
+        .filter(|icon| icon.rel.split_whitespace().any(|rel| rel == "icon"))
+        .collect();
+    if !report.favicon_ico.valid && !favicons.iter().any(|icon| icon.check.valid) {
+        warn(
+            &mut warnings,
+            IconIssue::MissingFavicon,
+            "No working favicon, neither declared with <link rel=\"icon\"> nor at /favicon.ico"
+                .to_string(),
+        );
+    }
+
+    let manifest_icons = report.manifest.iter().flat_map(|m| m.icons.iter());
+    for check in report
+        .icons
+        .iter()
+        .map(|icon| &icon.check)
+        .chain(manifest_icons.map(|icon| &icon.check))
+        .filter(|check| !check.valid)
+    {
+        warn(
+            &mut warnings,
+            IconIssue::BrokenIcon,
+            format!("Icon {} does not serve an image", check.url),
+        );
+    }
+
+    if !report
+        .icons
+        .iter()
+        .any(|icon| icon.rel.starts_with("apple-touch-icon") && icon.check.valid)
+    {
+        warn(
+            &mut warnings,
+            IconIssue::MissingAppleTouchIcon,
+            "No apple-touch-icon is declared, iOS uses a screenshot of the page instead"
+                .to_string(),
+        );
+    }
+
+    let Some(manifest) = &report.manifest else {
+        warn(
+            &mut warnings,
+            IconIssue::MissingManifest,
+            "The start page links no web app manifest".to_string(),
+        );
+        return warnings;
+    };
+    if manifest.status_code != Some(200) || manifest.parse_error.is_some() {
+        warn(
+            &mut warnings,
+            IconIssue::InvalidManifest,
+            format!(
+                "Manifest {} could not be read: {}",
+                manifest.url,
+                manifest
+                    .parse_error
+                    .clone()
+                    .unwrap_or_else(|| format!("status {}", manifest.status_code.unwrap_or(0)))
+            ),
+        );
+        return warnings;
+    }
+    if manifest.name.is_none() && manifest.short_name.is_none() {
+        warn(
+            &mut warnings,
+            IconIssue::ManifestMissingName,
+            "The manifest has neither name nor short_name".to_string(),
+        );
+    }
+    if manifest.icons.is_empty() {
+        warn(
+            &mut warnings,
+            IconIssue::ManifestMissingIcons,
+            "The manifest lists no icons".to_string(),
+        );
+    } else {
+        let missing: Vec<&str> = INSTALL_ICON_SIZES
+            .iter()
+            .filter(|size| {
+                !manifest.icons.iter().any(|icon| {
+                    icon.check.valid
+                        && icon
+                            .sizes
+                            .as_deref()
+                            .is_some_and(|s| s.split_whitespace().any(|s| s == **size))
+                })
+            })
+            .copied()
+            .collect();
+        if !missing.is_empty() {
+            warn(
+                &mut warnings,
+                IconIssue::ManifestMissingInstallSizes,
+                format!("The manifest has no working {} icon", missing.join(" or ")),
+            );
+        }
+    }
+    if manifest.theme_color.is_none() && report.theme_color_meta.is_none() {
+        warn(
+            &mut warnings,
+            IconIssue::MissingThemeColor,
+            "No theme_color in the manifest and no theme-color meta tag".to_string(),
+        );
+    }
+    warnings
+}
+
+/// Checks the favicon, the declared icons and the web app manifest of a site.
+///
+/// # Arguments
+/// * `base_url` - The start URL of the crawl, its icons and manifest are checked.
+///
+/// # Returns
+/// * `IconReport` - What was found and the problems with it.
+pub async fn check_site_icons(base_url: &Url) -> IconReport {
+    let mut report = IconReport::default();
+
+    let html = match fetch(base_url.as_str()).await {
+        Ok((200, _, body)) => String::from_utf8_lossy(&body).into_owned(),
+        _ => String::new(),
+    };
+    let (declared, manifest_url) = {
+        let document = Html::parse_document(&html);
+        let icon_selector = Selector::parse(
+            "link[rel~='icon'][href], link[rel^='apple-touch-icon'][href], link[rel='mask-icon'][href]",
+        )
+        .unwrap();
+        let manifest_selector = Selector::parse("link[rel='manifest'][href]").unwrap();
+        let theme_selector = Selector::parse("meta[name='theme-color'][content]").unwrap();
+
+        report.theme_color_meta = document
+            .select(&theme_selector)
+            .next()
+            .and_then(|meta| meta.value().attr("content"))
+            .map(|content| content.trim().to_string());
+
+        let declared: Vec<(String, Option<String>, Option<String>, String)> = document
+            .select(&icon_selector)
+            .filter_map(|link| {
+                let value = link.value();
+                let url = base_url.join(value.attr("href")?.trim()).ok()?;
+                Some((
+                    value.attr("rel")?.trim().to_lowercase(),
+                    value.attr("sizes").map(String::from),
+                    value.attr("type").map(String::from),
+                    url.to_string(),
+                ))
+            })
+            .collect();
+        let manifest_url = document
+            .select(&manifest_selector)
+            .next()
+            .and_then(|link| base_url.join(link.value().attr("href")?.trim()).ok());
+        (declared, manifest_url)
+    };
+
+    let favicon_ico = base_url
+        .join("/favicon.ico")
+        .map(|url| url.to_string())
+        .unwrap_or_default();
+    let (favicon, checks, manifest) = tokio::join!(
+        check_file(favicon_ico),
+        join_all(declared.iter().map(|(.., url)| check_file(url.clone()))),
+        async {
+            match manifest_url {
+                Some(url) => Some(check_manifest(url).await),
+                None => None,
+            }
+        }
+    );
+
+    report.favicon_ico = favicon;
+    report.icons = declared
+        .into_iter()
+        .zip(checks)
+        .map(|((rel, sizes, mime_type, _), check)| DeclaredIcon {
+            rel,
+            sizes,
+            mime_type,
+            check,
+        })
+        .collect();
+    report.manifest = manifest;
+    report.warnings = collect_warnings(&report);
+    report
+}
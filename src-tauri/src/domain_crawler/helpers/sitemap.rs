@@ -1,7 +1,30 @@
-use reqwest::StatusCode;
-use std::error::Error;
+use std::collections::{HashSet, VecDeque};
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
 use url::Url;
 
+use crate::domain_crawler::request_auth;
+use crate::domain_crawler::results_store::StoredReport;
+
+// Guard rails for very large or self-referencing sitemap indexes
+const MAX_SITEMAPS: usize = 100;
+const MAX_SITEMAP_URLS: usize = 200_000;
+// The sitemap protocol's limit for one uncompressed file, also what a gzip body may inflate to
+const MAX_SITEMAP_BYTES: usize = 50 * 1024 * 1024;
+
+// Common locations checked in addition to the robots.txt Sitemap directives
+const COMMON_SITEMAP_PATHS: [&str; 4] = [
+    "sitemap.xml",
+    "sitemap_index.xml",
+    "sitemap.xml.gz",
+    "custom_sitemap.xml",
+];
+
 pub async fn get_sitemap(base: &Url) -> Result<Vec<String>, String> {
     // Multiple sitemap locations to check
     let paths = ["sitemap.xml", "sitemap_index.xml", "custom_sitemap.xml"];
@@ -35,3 +58,332 @@ pub async fn get_sitemap(base: &Url) -> Result<Vec<String>, String> {
     // Return the collected sitemap content
     Ok(sitemaps)
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SitemapEntry {
+    pub loc: String,
+    pub lastmod: Option<String>,
+    pub changefreq: Option<String>,
+    pub priority: Option<f32>,
+    pub sitemap: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SitemapReport {
+    pub sitemaps: Vec<String>,
+    pub entries: Vec<SitemapEntry>,
+    pub errors: Vec<String>,
+}
+
+enum ParsedSitemap {
+    Index(Vec<String>),
+    UrlSet(Vec<SitemapEntry>),
+}
+
+/// Discovers and parses every sitemap of a site, following sitemap indexes.
+///
+/// # Arguments
+/// * `client` - The crawler HTTP client.
+/// * `base_url` - The crawl start URL, used to resolve the common sitemap paths.
+/// * `robots_sitemaps` - Sitemap URLs declared in robots.txt.
+///
+/// # Returns
+/// * `SitemapReport` - The sitemaps that were read and every URL entry they list.
+pub async fn crawl_sitemaps(
+    client: &Client,
+    base_url: &Url,
+    robots_sitemaps: &[String],
+) -> SitemapReport {
+    let mut queue: VecDeque<(String, bool)> = VecDeque::new();
+
+    // Sitemaps declared in robots.txt are authoritative, the common paths are a guess
+    for sitemap in robots_sitemaps {
+        queue.push_back((sitemap.clone(), true));
+    }
+    for path in COMMON_SITEMAP_PATHS {
+        if let Ok(url) = base_url.join(path) {
+            queue.push_back((url.to_string(), false));
+        }
+    }
+
+    follow_sitemaps(client, queue).await
+}
+
+/// Reads one sitemap, or every sitemap an index at `sitemap_url` lists.
+pub async fn read_sitemap(client: &Client, sitemap_url: &str) -> SitemapReport {
+    follow_sitemaps(client, VecDeque::from([(sitemap_url.to_string(), true)])).await
+}
+
+// Fetches the queued sitemaps, queueing the children of indexes as they are found
+async fn follow_sitemaps(client: &Client, mut queue: VecDeque<(String, bool)>) -> SitemapReport {
+    let mut report = SitemapReport::default();
+    let mut seen = HashSet::new();
+
+    while let Some((sitemap_url, declared)) = queue.pop_front() {
+        if report.sitemaps.len() >= MAX_SITEMAPS || report.entries.len() >= MAX_SITEMAP_URLS {
+            break;
+        }
+        if !seen.insert(sitemap_url.clone()) {
+            continue;
+        }
+
+        let xml = match fetch_sitemap(client, &sitemap_url).await {
+            Ok(Some((xml, truncated))) => {
+                if truncated {
+                    report.errors.push(format!(
+                        "Sitemap {} is over 50 MB uncompressed, only its first 50 MB were read",
+                        sitemap_url
+                    ));
+                }
+                xml
+            }
+            Ok(None) => {
+                if declared {
+                    report
+                        .errors
+                        .push(format!("Sitemap not found at: {}", sitemap_url));
+                }
+                continue;
+            }
+            Err(e) => {
+                report.errors.push(e);
+                continue;
+            }
+        };
+
+        match parse_sitemap_xml(&xml, &sitemap_url) {
+            Ok(ParsedSitemap::Index(children)) => {
+                report.sitemaps.push(sitemap_url);
+                for child in children {
+                    queue.push_back((child, true));
+                }
+            }
+            Ok(ParsedSitemap::UrlSet(entries)) => {
+                report.sitemaps.push(sitemap_url);
+                let remaining = MAX_SITEMAP_URLS - report.entries.len();
+                report.entries.extend(entries.into_iter().take(remaining));
+            }
+            Err(e) => report.errors.push(e),
+        }
+    }
+
+    report
+}
+
+/// Fetches a sitemap body, transparently inflating gzipped files, and whether it was cut at
+/// the size limit.
+async fn fetch_sitemap(client: &Client, url: &str) -> Result<Option<(String, bool)>, String> {
+    let response = request_auth::apply(client.get(url), url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch sitemap {}: {}", url, e))?;
+
+    if response.status() != StatusCode::OK {
+        return Ok(None);
+    }
+
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read sitemap {}: {}", url, e))?;
+
+    decode(&bytes, url).map(Some)
+}
+
+fn decode(bytes: &[u8], url: &str) -> Result<(String, bool), String> {
+    // Gzip magic bytes, servers rarely send a Content-Encoding for .xml.gz files
+    let xml = if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut xml = Vec::new();
+        // One byte past the limit tells a file at the limit from a larger one
+        GzDecoder::new(bytes)
+            .take(MAX_SITEMAP_BYTES as u64 + 1)
+            .read_to_end(&mut xml)
+            .map_err(|e| format!("Failed to decompress sitemap {}: {}", url, e))?;
+        String::from_utf8_lossy(&xml).to_string()
+    } else {
+        String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_SITEMAP_BYTES + 1)]).to_string()
+    };
+
+    if xml.len() <= MAX_SITEMAP_BYTES {
+        return Ok((xml, false));
+    }
+    Ok((
+        complete_entries(&xml[..floor_char_boundary(&xml, MAX_SITEMAP_BYTES)]).to_string(),
+        true,
+    ))
+}
+
+// A cut sitemap keeps the entries before the cut, the one it split is dropped
+fn complete_entries(xml: &str) -> &str {
+    let end = ["</url>", "</sitemap>"]
+        .iter()
+        .filter_map(|tag| xml.rfind(tag).map(|at| at + tag.len()))
+        .max()
+        .unwrap_or(0);
+    &xml[..end]
+}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    (0..=index.min(text.len()))
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+fn parse_sitemap_xml(xml: &str, sitemap_url: &str) -> Result<ParsedSitemap, String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut is_index = false;
+    let mut is_urlset = false;
+    let mut children = Vec::new();
+    let mut entries = Vec::new();
+    let mut current: Option<SitemapEntry> = None;
+    let mut field = String::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                match name.as_str() {
+                    "sitemapindex" => is_index = true,
+                    "urlset" => is_urlset = true,
+                    "url" | "sitemap" => {
+                        current = Some(SitemapEntry {
+                            sitemap: sitemap_url.to_string(),
+                            ..Default::default()
+                        })
+                    }
+                    _ => {}
+                }
+                // Use the qualified name so extension tags like <image:loc> are ignored
+                field = String::from_utf8_lossy(e.name().as_ref()).to_string();
+            }
+            Ok(Event::Text(e)) => {
+                let text = e
+                    .unescape()
+                    .map(|t| t.trim().to_string())
+                    .unwrap_or_default();
+                set_field(&mut current, &field, text);
+            }
+            Ok(Event::CData(e)) => {
+                let text = String::from_utf8_lossy(&e).trim().to_string();
+                set_field(&mut current, &field, text);
+            }
+            Ok(Event::End(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                match name.as_str() {
+                    "url" => {
+                        if let Some(entry) = current.take().filter(|e| !e.loc.is_empty()) {
+                            entries.push(entry);
+                        }
+                    }
+                    "sitemap" => {
+                        if let Some(entry) = current.take().filter(|e| !e.loc.is_empty()) {
+                            children.push(entry.loc);
+                        }
+                    }
+                    _ => {}
+                }
+                field.clear();
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(format!(
+                    "Invalid sitemap XML at {} (position {}): {}",
+                    sitemap_url,
+                    reader.buffer_position(),
+                    e
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    if is_index {
+        Ok(ParsedSitemap::Index(children))
+    } else if is_urlset {
+        Ok(ParsedSitemap::UrlSet(entries))
+    } else {
+        Err(format!("Not a sitemap: {}", sitemap_url))
+    }
+}
+
+fn set_field(current: &mut Option<SitemapEntry>, field: &str, text: String) {
+    let Some(entry) = current.as_mut() else {
+        return;
+    };
+    match field {
+        "loc" => entry.loc = text,
+        "lastmod" => entry.lastmod = Some(text),
+        "changefreq" => entry.changefreq = Some(text),
+        "priority" => entry.priority = text.parse().ok(),
+        _ => {}
+    }
+}
+
+impl StoredReport for SitemapReport {
+    const KIND: &'static str = "sitemap";
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn urlset(urls: usize, padding: usize) -> String {
+        let mut xml =
+            String::from(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+        for i in 0..urls {
+            xml.push_str(&format!(
+                "<url><loc>https://example.com/{}</loc><!--{}--></url>",
+                i,
+                " ".repeat(padding)
+            ));
+        }
+        xml.push_str("</urlset>");
+        xml
+    }
+
+    #[test]
+    fn inflates_gzipped_sitemaps() {
+        let xml = urlset(3, 0);
+        let (decoded, truncated) = decode(&gzip(xml.as_bytes()), "sitemap.xml.gz").unwrap();
+        assert_eq!(decoded, xml);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn stops_inflating_at_the_size_limit() {
+        let bomb = gzip(&vec![b' '; MAX_SITEMAP_BYTES * 2]);
+        let (decoded, truncated) = decode(&bomb, "sitemap.xml.gz").unwrap();
+        assert!(truncated);
+        assert!(decoded.len() <= MAX_SITEMAP_BYTES);
+    }
+
+    #[test]
+    fn keeps_the_entries_before_the_cut() {
+        // Entries of about 1 MB, the 60th crosses the limit
+        let xml = urlset(60, 1024 * 1024);
+        let (decoded, truncated) = decode(&gzip(xml.as_bytes()), "sitemap.xml.gz").unwrap();
+        assert!(truncated);
+
+        let Ok(ParsedSitemap::UrlSet(entries)) = parse_sitemap_xml(&decoded, "sitemap.xml.gz")
+        else {
+            panic!("expected a urlset");
+        };
+        assert!(!entries.is_empty() && entries.len() < 60);
+        let last = entries.len() - 1;
+        assert_eq!(entries[last].loc, format!("https://example.com/{}", last));
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::domain_crawler::subresources;
+
+// Properties every page should declare for a usable social preview
+const REQUIRED_OPEN_GRAPH: [&str; 3] = ["og:title", "og:description", "og:image"];
+const REQUIRED_TWITTER: [&str; 1] = ["twitter:card"];
+
+// Structured properties that are allowed to appear more than once
+const REPEATABLE_PREFIXES: [&str; 4] = ["og:image", "og:video", "og:audio", "og:locale:alternate"];
+
+const TWITTER_CARD_TYPES: [&str; 4] = ["summary", "summary_large_image", "app", "player"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SocialTags {
+    pub open_graph: BTreeMap<String, Vec<String>>,
+    pub twitter: BTreeMap<String, Vec<String>>,
+    pub missing: Vec<String>,
+    pub duplicated: Vec<String>,
+    pub invalid: Vec<String>,
+    pub og_image_status: Option<u16>,
+    pub og_image_error: Option<String>,
+}
+
+/// Extracts `og:*` and `twitter:*` meta tags and validates the required properties.
+///
+/// # Arguments
+/// * `body` - The HTML content as a string.
+///
+/// # Returns
+/// * `SocialTags` - All social tags found, plus the missing, duplicated and invalid ones.
+pub fn extract_social_tags(body: &str) -> SocialTags {
+    let document = Html::parse_document(body);
+    let selector = Selector::parse("meta[property], meta[name]").unwrap();
+
+    let mut tags = SocialTags::default();
+
+    for element in document.select(&selector) {
+        // Open Graph uses `property`, Twitter uses `name`, but both are seen in the wild
+        let key = element
+            .value()
+            .attr("property")
+            .or_else(|| element.value().attr("name"))
+            .unwrap_or("")
+            .trim()
+            .to_lowercase();
+        let content = element.value().attr("content").unwrap_or("").trim();
+
+        let map = if key.starts_with("og:") {
+            &mut tags.open_graph
+        } else if key.starts_with("twitter:") {
+            &mut tags.twitter
+        } else {
+            continue;
+        };
+
+        map.entry(key).or_default().push(content.to_string());
+    }
+
+    for key in REQUIRED_OPEN_GRAPH {
+        if !has_value(&tags.open_graph, key) {
+            tags.missing.push(key.to_string());
+        }
+    }
+    for key in REQUIRED_TWITTER {
+        if !has_value(&tags.twitter, key) {
+            tags.missing.push(key.to_string());
+        }
+    }
+
+    for (key, values) in tags.open_graph.iter().chain(tags.twitter.iter()) {
+        let repeatable = REPEATABLE_PREFIXES.iter().any(|p| key.starts_with(p));
+        if values.len() > 1 && !repeatable {
+            tags.duplicated.push(key.clone());
+        }
+    }
+
+    if let Some(card) = tags.twitter.get("twitter:card").and_then(|v| v.first()) {
+        if !card.is_empty() && !TWITTER_CARD_TYPES.contains(&card.as_str()) {
+            tags.invalid
+                .push(format!("twitter:card has unknown type '{}'", card));
+        }
+    }
+
+    tags
+}
+
+fn has_value(map: &BTreeMap<String, Vec<String>>, key: &str) -> bool {
+    map.get(key)
+        .is_some_and(|values| values.iter().any(|v| !v.is_empty()))
+}
+
+/// Checks that the first `og:image` resolves to an image.
+pub async fn check_og_image(tags: &mut SocialTags, page_url: &Url) {
+    let Some(image) = tags
+        .open_graph
+        .get("og:image")
+        .and_then(|v| v.first())
+        .filter(|v| !v.is_empty())
+    else {
+        return;
+    };
+
+    let image_url = match page_url.join(image) {
+        Ok(url) => url,
+        Err(e) => {
+            tags.og_image_error = Some(format!("Invalid og:image URL '{}': {}", image, e));
+            return;
+        }
+    };
+
+    // The same share image is usually declared on every page, it is requested once per crawl
+    match subresources::head(image_url.as_str()).await {
+        Ok(head) => {
+            let status = head.status;
+            tags.og_image_status = Some(status.as_u16());
+
+            let content_type = head
+                .header(reqwest::header::CONTENT_TYPE)
+                .unwrap_or_default();
+
+            if !status.is_success() {
+                tags.og_image_error = Some(format!("HTTP Error: {}", status));
+            } else if !content_type.is_empty() && !content_type.contains("image") {
+                tags.og_image_error = Some(format!("Non-image content type: {}", content_type));
+            }
+        }
+        Err(e) => tags.og_image_error = Some(e),
+    }
+}
@@ -0,0 +1,278 @@
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SchemaSyntax {
+    JsonLd,
+    Microdata,
+    Rdfa,
+}
+
+/// The schema.org types the frontend reports coverage for. Anything else is kept as `Other`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SchemaType {
+    Organization,
+    LocalBusiness,
+    Person,
+    WebSite,
+    WebPage,
+    Article,
+    BreadcrumbList,
+    FAQPage,
+    Product,
+    Offer,
+    Review,
+    AggregateRating,
+    Event,
+    Recipe,
+    HowTo,
+    VideoObject,
+    Other(String),
+}
+
+impl SchemaType {
+    fn from_type(raw: &str) -> Self {
+        // Types can be full IRIs (https://schema.org/Product) or prefixed (schema:Product)
+        let name = raw.rsplit(['/', ':', '#']).next().unwrap_or(raw);
+
+        match name {
+            "Organization" | "Corporation" | "NGO" => SchemaType::Organization,
+            "LocalBusiness" | "Store" | "Restaurant" => SchemaType::LocalBusiness,
+            "Person" => SchemaType::Person,
+            "WebSite" => SchemaType::WebSite,
+            "WebPage" | "AboutPage" | "ContactPage" | "CollectionPage" => SchemaType::WebPage,
+            "Article" | "NewsArticle" | "BlogPosting" | "TechArticle" => SchemaType::Article,
+            "BreadcrumbList" => SchemaType::BreadcrumbList,
+            "FAQPage" => SchemaType::FAQPage,
+            "Product" => SchemaType::Product,
+            "Offer" | "AggregateOffer" => SchemaType::Offer,
+            "Review" => SchemaType::Review,
+            "AggregateRating" => SchemaType::AggregateRating,
+            "Event" => SchemaType::Event,
+            "Recipe" => SchemaType::Recipe,
+            "HowTo" => SchemaType::HowTo,
+            "VideoObject" => SchemaType::VideoObject,
+            other => SchemaType::Other(other.to_string()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StructuredDataItem {
+    pub syntax: SchemaSyntax,
+    pub schema_type: SchemaType,
+    pub raw_type: String,
+    pub properties: Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct StructuredData {
+    pub items: Vec<StructuredDataItem>,
+    pub types: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Extracts JSON-LD, microdata and RDFa structured data from an HTML document.
+///
+/// # Arguments
+/// * `html` - The HTML content as a string.
+///
+/// # Returns
+/// * `StructuredData` - Every typed item found on the page, the unique list of types
+///   and any JSON-LD blocks that failed to parse.
+pub fn extract_structured_data(html: &str) -> StructuredData {
+    let document = Html::parse_document(html);
+    let mut data = StructuredData::default();
+
+    extract_json_ld(&document, &mut data);
+    extract_microdata(&document, &mut data);
+    extract_rdfa(&document, &mut data);
+
+    for item in &data.items {
+        if !data.types.contains(&item.raw_type) {
+            data.types.push(item.raw_type.clone());
+        }
+    }
+
+    data
+}
+
+fn extract_json_ld(document: &Html, data: &mut StructuredData) {
+    let selector = Selector::parse("script[type=\"application/ld+json\"]").unwrap();
+
+    for script in document.select(&selector) {
+        let json_str = script.text().collect::<String>();
+        match serde_json::from_str::<Value>(json_str.trim()) {
+            Ok(value) => push_json_ld_value(value, data),
+            Err(e) => data.errors.push(format!("Invalid JSON-LD: {}", e)),
+        }
+    }
+}
+
+// Walks top level arrays and @graph containers down to the typed nodes
+fn push_json_ld_value(value: Value, data: &mut StructuredData) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                push_json_ld_value(value, data);
+            }
+        }
+        Value::Object(mut object) => {
+            if let Some(graph) = object.remove("@graph") {
+                push_json_ld_value(graph, data);
+            }
+
+            let types: Vec<String> = match object.get("@type") {
+                Some(Value::String(t)) => vec![t.clone()],
+                Some(Value::Array(ts)) => ts
+                    .iter()
+                    .filter_map(|t| t.as_str().map(String::from))
+                    .collect(),
+                _ => Vec::new(),
+            };
+
+            let properties = Value::Object(object);
+            for raw_type in types {
+                data.items.push(StructuredDataItem {
+                    syntax: SchemaSyntax::JsonLd,
+                    schema_type: SchemaType::from_type(&raw_type),
+                    raw_type,
+                    properties: properties.clone(),
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+fn extract_microdata(document: &Html, data: &mut StructuredData) {
+    let scope_selector = Selector::parse("[itemscope][itemtype]").unwrap();
+    let prop_selector = Selector::parse("[itemprop]").unwrap();
+
+    for scope in document.select(&scope_selector) {
+        let properties = collect_properties(scope, &prop_selector, "itemprop", |el| {
+            el.value().attr("itemscope").is_some()
+        });
+
+        for raw_type in scope
+            .value()
+            .attr("itemtype")
+            .unwrap_or("")
+            .split_whitespace()
+        {
+            data.items.push(StructuredDataItem {
+                syntax: SchemaSyntax::Microdata,
+                schema_type: SchemaType::from_type(raw_type),
+                raw_type: raw_type.to_string(),
+                properties: properties.clone(),
+            });
+        }
+    }
+}
+
+fn extract_rdfa(document: &Html, data: &mut StructuredData) {
+    let scope_selector = Selector::parse("[typeof]").unwrap();
+    let prop_selector = Selector::parse("[property]").unwrap();
+
+    for scope in document.select(&scope_selector) {
+        let properties = collect_properties(scope, &prop_selector, "property", |el| {
+            el.value().attr("typeof").is_some()
+        });
+
+        for raw_type in scope
+            .value()
+            .attr("typeof")
+            .unwrap_or("")
+            .split_whitespace()
+        {
+            data.items.push(StructuredDataItem {
+                syntax: SchemaSyntax::Rdfa,
+                schema_type: SchemaType::from_type(raw_type),
+                raw_type: raw_type.to_string(),
+                properties: properties.clone(),
+            });
+        }
+    }
+}
+
+/// Collects the properties that belong directly to `scope`, skipping the ones owned by
+/// a nested item.
+fn collect_properties(
+    scope: ElementRef,
+    prop_selector: &Selector,
+    prop_attr: &str,
+    is_scope: impl Fn(&ElementRef) -> bool,
+) -> Value {
+    let mut properties = Map::new();
+
+    for prop in scope.select(prop_selector) {
+        if prop.id() == scope.id() {
+            continue;
+        }
+
+        // The nearest enclosing scope is the owner of the property
+        let owner = prop
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .find(|el| is_scope(el));
+        if owner.map(|el| el.id()) != Some(scope.id()) {
+            continue;
+        }
+
+        let value = property_value(&prop, &is_scope);
+        for name in prop
+            .value()
+            .attr(prop_attr)
+            .unwrap_or("")
+            .split_whitespace()
+        {
+            let name = name.rsplit(':').next().unwrap_or(name).to_string();
+            match properties.get_mut(&name) {
+                Some(Value::Array(values)) => values.push(value.clone()),
+                Some(existing) => {
+                    let first = existing.take();
+                    *existing = Value::Array(vec![first, value.clone()]);
+                }
+                None => {
+                    properties.insert(name, value.clone());
+                }
+            }
+        }
+    }
+
+    Value::Object(properties)
+}
+
+fn property_value(prop: &ElementRef, is_scope: &impl Fn(&ElementRef) -> bool) -> Value {
+    let el = prop.value();
+
+    if is_scope(prop) {
+        let raw_type = el
+            .attr("itemtype")
+            .or_else(|| el.attr("typeof"))
+            .unwrap_or("");
+        return Value::String(format!("[{}]", raw_type));
+    }
+
+    let value = el
+        .attr("content")
+        .or_else(|| match el.name() {
+            "a" | "link" | "area" => el.attr("href"),
+            "img" | "audio" | "video" | "source" | "iframe" | "embed" => el.attr("src"),
+            "meta" => el.attr("content"),
+            "time" => el.attr("datetime"),
+            "data" | "meter" => el.attr("value"),
+            _ => None,
+        })
+        .map(String::from)
+        .unwrap_or_else(|| {
+            prop.text()
+                .collect::<String>()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        });
+
+    Value::String(value)
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use scraper::Html;
+use serde::{Deserialize, Serialize};
+
+use super::content_analyzer::visible_text;
+use super::language_selector::is_function_word;
+
+// Only the most frequent terms of a page are kept, enough for TF-IDF across the crawl
+const MAX_TERMS: usize = 200;
+const MAX_PHRASES: usize = 100;
+const MIN_TERM_CHARS: usize = 3;
+
+/// Term and phrase counts of a page's main content.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TermCounts {
+    pub total_words: usize,
+    /// Single words without function words, most frequent first
+    pub terms: Vec<(String, usize)>,
+    /// Two and three word phrases seen at least twice, most frequent first
+    pub phrases: Vec<(String, usize)>,
+}
+
+fn is_term(word: &str) -> bool {
+    word.chars().count() >= MIN_TERM_CHARS
+        && word.chars().any(char::is_alphabetic)
+        && !is_function_word(word)
+}
+
+fn top(counts: HashMap<String, usize>, limit: usize) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(limit);
+    counts
+}
+
+/// Tokenizes the main content of a page and counts its terms and 2–3 word phrases.
+pub fn count_terms(body: &str) -> TermCounts {
+    let text = visible_text(&Html::parse_document(body));
+
+    let mut terms: HashMap<String, usize> = HashMap::new();
+    let mut phrases: HashMap<String, usize> = HashMap::new();
+    let mut total_words = 0;
+
+    // Phrases do not run across sentences or other punctuation
+    for fragment in text.split(|c: char| c.is_ascii_punctuation() && c != '-' && c != '\'') {
+        let words: Vec<String> = fragment
+            .split_whitespace()
+            .map(|word| {
+                word.trim_matches(|c: char| !c.is_alphanumeric())
+                    .to_lowercase()
+            })
+            .filter(|word| !word.is_empty())
+            .collect();
+        total_words += words.len();
+
+        for word in words.iter().filter(|word| is_term(word)) {
+            *terms.entry(word.clone()).or_default() += 1;
+        }
+        for size in 2..=3 {
+            for window in words.windows(size) {
+                // "state of the art" style phrases may hold function words, but not at the edges
+                if is_term(&window[0]) && is_term(&window[size - 1]) {
+                    *phrases.entry(window.join(" ")).or_default() += 1;
+                }
+            }
+        }
+    }
+    phrases.retain(|_, count| *count > 1);
+
+    TermCounts {
+        total_words,
+        terms: top(terms, MAX_TERMS),
+        phrases: top(phrases, MAX_PHRASES),
+    }
+}
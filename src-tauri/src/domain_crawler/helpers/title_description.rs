@@ -0,0 +1,143 @@
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+// Google renders titles in 20px Arial and descriptions in 14px Arial
+const TITLE_FONT_PX: f32 = 20.0;
+const DESCRIPTION_FONT_PX: f32 = 14.0;
+
+pub const TITLE_MIN_CHARS: usize = 30;
+pub const TITLE_MAX_CHARS: usize = 60;
+pub const TITLE_MAX_PIXELS: u32 = 580;
+pub const DESCRIPTION_MIN_CHARS: usize = 70;
+pub const DESCRIPTION_MAX_CHARS: usize = 160;
+pub const DESCRIPTION_MAX_PIXELS: u32 = 920;
+
+// Arial advance widths in 1/1000 em for the printable ASCII range, starting at ' '
+const ARIAL_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SnippetIssue {
+    MissingTitle,
+    TitleTooShort,
+    TitleTooLong,
+    MultipleTitles,
+    /// Set by the crawl-wide audit when another page uses the same title
+    DuplicateTitle,
+    MissingDescription,
+    DescriptionTooShort,
+    DescriptionTooLong,
+    MultipleDescriptions,
+    DuplicateDescription,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextMetrics {
+    pub text: String,
+    pub chars: usize,
+    pub pixels: u32,
+}
+
+/// Title tag and meta description of a page, measured the way a search snippet shows them.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TitleDescriptionAudit {
+    pub title: Option<TextMetrics>,
+    pub description: Option<TextMetrics>,
+    pub issues: Vec<SnippetIssue>,
+}
+
+fn char_width(c: char) -> u16 {
+    match c {
+        ' '..='~' => ARIAL_WIDTHS[c as usize - 32],
+        // CJK and other full width scripts take a whole em
+        '\u{1100}'..='\u{11FF}' | '\u{2E80}'..='\u{A4CF}' | '\u{AC00}'..='\u{D7AF}' => 1000,
+        c if c.is_uppercase() => 667,
+        _ => 556,
+    }
+}
+
+/// Approximate rendered width of `text` in Arial at `font_px`.
+pub fn pixel_width(text: &str, font_px: f32) -> u32 {
+    let units: u32 = text.chars().map(|c| char_width(c) as u32).sum();
+    (units as f32 * font_px / 1000.0).round() as u32
+}
+
+fn measure(text: &str, font_px: f32) -> TextMetrics {
+    // Snippets collapse runs of whitespace
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    TextMetrics {
+        chars: text.chars().count(),
+        pixels: pixel_width(&text, font_px),
+        text,
+    }
+}
+
+/// Measures the `<title>` and `<meta name="description">` of a page and flags length issues.
+///
+/// # Arguments
+/// * `body` - The HTML content as a string.
+///
+/// # Returns
+/// * `TitleDescriptionAudit` - The measured texts and the issues found.
+pub fn audit_title_description(body: &str) -> TitleDescriptionAudit {
+    let document = Html::parse_document(body);
+    let title_selector = Selector::parse("head title").unwrap();
+    let description_selector = Selector::parse("meta[name='description']").unwrap();
+
+    let titles: Vec<String> = document
+        .select(&title_selector)
+        .map(|element| element.text().collect::<String>())
+        .filter(|title| !title.trim().is_empty())
+        .collect();
+    let descriptions: Vec<String> = document
+        .select(&description_selector)
+        .filter_map(|element| element.value().attr("content").map(String::from))
+        .filter(|description| !description.trim().is_empty())
+        .collect();
+
+    let mut audit = TitleDescriptionAudit {
+        title: titles.first().map(|t| measure(t, TITLE_FONT_PX)),
+        description: descriptions
+            .first()
+            .map(|d| measure(d, DESCRIPTION_FONT_PX)),
+        issues: Vec::new(),
+    };
+
+    match &audit.title {
+        None => audit.issues.push(SnippetIssue::MissingTitle),
+        Some(title) if title.chars < TITLE_MIN_CHARS => {
+            audit.issues.push(SnippetIssue::TitleTooShort)
+        }
+        Some(title) if title.chars > TITLE_MAX_CHARS || title.pixels > TITLE_MAX_PIXELS => {
+            audit.issues.push(SnippetIssue::TitleTooLong)
+        }
+        Some(_) => {}
+    }
+    if titles.len() > 1 {
+        audit.issues.push(SnippetIssue::MultipleTitles);
+    }
+
+    match &audit.description {
+        None => audit.issues.push(SnippetIssue::MissingDescription),
+        Some(description) if description.chars < DESCRIPTION_MIN_CHARS => {
+            audit.issues.push(SnippetIssue::DescriptionTooShort)
+        }
+        Some(description)
+            if description.chars > DESCRIPTION_MAX_CHARS
+                || description.pixels > DESCRIPTION_MAX_PIXELS =>
+        {
+            audit.issues.push(SnippetIssue::DescriptionTooLong)
+        }
+        Some(_) => {}
+    }
+    if descriptions.len() > 1 {
+        audit.issues.push(SnippetIssue::MultipleDescriptions);
+    }
+
+    audit
+}
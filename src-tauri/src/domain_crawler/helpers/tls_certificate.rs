@@ -0,0 +1,447 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::{timeout, Duration};
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::client::WebPkiServerVerifier;
+use tokio_rustls::rustls::crypto::{ring, CryptoProvider};
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, Error as TlsError, ProtocolVersion,
+    RootCertStore, SignatureScheme,
+};
+use tokio_rustls::TlsConnector;
+use x509_parser::prelude::{
+    ASN1Time, AttributeTypeAndValue, FromDer, GeneralName, X509Certificate, X509Name,
+};
+
+use crate::domain_crawler::proxies;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Legacy protocol versions probed with a hand-built ClientHello, rustls cannot speak them
+const LEGACY_VERSIONS: [(u8, &str); 2] = [(0x01, "TLS 1.0"), (0x02, "TLS 1.1")];
+
+// RSA and ECDHE suites with CBC and 3DES, the ones TLS 1.0 and 1.1 servers pick from
+const LEGACY_CIPHER_SUITES: [u16; 8] = [
+    0xC014, 0xC013, 0xC00A, 0xC009, 0x0035, 0x002F, 0x0039, 0x000A,
+];
+
+/// The certificate a host serves and how its TLS connection was negotiated.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TlsCertificate {
+    pub host: String,
+    pub port: u16,
+    pub issuer: Option<String>,
+    pub subject: Option<String>,
+    /// DNS names and IP addresses the certificate covers
+    pub subject_alt_names: Vec<String>,
+    /// Such as "TLS 1.3"
+    pub protocol: Option<String>,
+    /// Legacy versions the host still accepts, such as "TLS 1.0"
+    pub weak_protocols: Vec<String>,
+    pub not_before: Option<DateTime<Utc>>,
+    pub not_after: Option<DateTime<Utc>>,
+    pub days_until_expiry: Option<i64>,
+    pub hostname_mismatch: bool,
+    /// Why browsers would reject the certificate, None when it is trusted
+    pub verification_error: Option<String>,
+    /// Set when no TLS connection could be made at all
+    pub error: Option<String>,
+}
+
+/// Runs the standard web PKI checks but never fails the handshake, so an invalid
+/// certificate can still be inspected. The first failure is kept for the report.
+#[derive(Debug)]
+struct RecordingVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    failure: Mutex<Option<TlsError>>,
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, TlsError> {
+        if let Err(e) = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        ) {
+            if let Ok(mut failure) = self.failure.lock() {
+                failure.get_or_insert(e);
+            }
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+// The attribute's text when it is one of the usual string types
+fn first_text<'a>(
+    mut attributes: impl Iterator<Item = &'a AttributeTypeAndValue<'a>>,
+) -> Option<String> {
+    attributes
+        .next()
+        .and_then(|attribute| attribute.as_str().ok())
+        .map(String::from)
+}
+
+/// Picks the common name of a distinguished name, falling back to the organisation.
+fn read_name(name: &X509Name) -> Option<String> {
+    first_text(name.iter_common_name()).or_else(|| first_text(name.iter_organization()))
+}
+
+fn read_time(time: ASN1Time) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(time.timestamp(), 0)
+}
+
+fn subject_alt_names(cert: &X509Certificate) -> Vec<String> {
+    let Ok(Some(extension)) = cert.subject_alternative_name() else {
+        return Vec::new();
+    };
+    extension
+        .value
+        .general_names
+        .iter()
+        .filter_map(|name| match name {
+            GeneralName::DNSName(name) => Some(name.to_string()),
+            GeneralName::IPAddress(&[a, b, c, d]) => Some(Ipv4Addr::new(a, b, c, d).to_string()),
+            GeneralName::IPAddress(octets) => <[u8; 16]>::try_from(*octets)
+                .ok()
+                .map(|octets| Ipv6Addr::from(octets).to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Fills in the issuer, subject, SANs and validity period from a DER certificate.
+fn read_certificate(der: &[u8], certificate: &mut TlsCertificate) -> Option<()> {
+    let (_, cert) = X509Certificate::from_der(der).ok()?;
+    certificate.issuer = read_name(cert.issuer());
+    certificate.subject = read_name(cert.subject());
+    certificate.not_before = read_time(cert.validity().not_before);
+    certificate.not_after = read_time(cert.validity().not_after);
+    certificate.subject_alt_names = subject_alt_names(&cert);
+    Some(())
+}
+
+// `*.example.com` covers one label only, as in browsers
+fn name_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host
+            .split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && rest == suffix),
+        None => pattern == host,
+    }
+}
+
+fn protocol_name(version: ProtocolVersion) -> String {
+    match version {
+        ProtocolVersion::TLSv1_3 => "TLS 1.3".to_string(),
+        ProtocolVersion::TLSv1_2 => "TLS 1.2".to_string(),
+        ProtocolVersion::TLSv1_1 => "TLS 1.1".to_string(),
+        ProtocolVersion::TLSv1_0 => "TLS 1.0".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+fn verifier() -> Result<Arc<RecordingVerifier>, String> {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let inner = WebPkiServerVerifier::builder_with_provider(
+        Arc::new(roots),
+        Arc::new(ring::default_provider()),
+    )
+    .build()
+    .map_err(|e| e.to_string())?;
+    Ok(Arc::new(RecordingVerifier {
+        inner,
+        failure: Mutex::new(None),
+    }))
+}
+
+fn client_config(verifier: Arc<RecordingVerifier>) -> Result<ClientConfig, String> {
+    let provider: Arc<CryptoProvider> = Arc::new(ring::default_provider());
+    Ok(ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier)
+        .with_no_client_auth())
+}
+
+/// A connector that completes the handshake whatever the certificate, for timing connections.
+pub fn permissive_connector() -> Result<TlsConnector, String> {
+    Ok(TlsConnector::from(Arc::new(client_config(verifier()?)?)))
+}
+
+async fn handshake(host: &str, port: u16, certificate: &mut TlsCertificate) -> Result<(), String> {
+    let verifier = verifier()?;
+    let config = client_config(verifier.clone())?;
+
+    let server_name = ServerName::try_from(host.to_string()).map_err(|e| e.to_string())?;
+    let stream = timeout(CONNECT_TIMEOUT, async {
+        let tcp = proxies::connect(host, port).await?;
+        TlsConnector::from(Arc::new(config))
+            .connect(server_name, tcp)
+            .await
+            .map_err(|e| format!("TLS handshake with {}:{} failed: {}", host, port, e))
+    })
+    .await
+    .map_err(|_| format!("Timed out connecting to {}:{}", host, port))??;
+
+    let (_, connection) = stream.get_ref();
+    certificate.protocol = connection.protocol_version().map(protocol_name);
+    if let Some(leaf) = connection
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+    {
+        read_certificate(leaf.as_ref(), certificate);
+    }
+
+    let failure = verifier.failure.lock().ok().and_then(|mut f| f.take());
+    match failure {
+        Some(TlsError::InvalidCertificate(CertificateError::NotValidForName)) => {
+            certificate.hostname_mismatch = true;
+            certificate.verification_error = Some(format!("Certificate is not valid for {}", host));
+        }
+        Some(e) => certificate.verification_error = Some(e.to_string()),
+        None => {}
+    }
+
+    // An untrusted chain is reported before the name check runs, so check the names too
+    let host = host.to_lowercase();
+    if !certificate.subject_alt_names.is_empty()
+        && !certificate
+            .subject_alt_names
+            .iter()
+            .any(|name| name_matches(name, &host))
+    {
+        certificate.hostname_mismatch = true;
+    }
+    Ok(())
+}
+
+// A TLS 1.0/1.1 ClientHello with SNI and the extensions ECDHE suites need
+fn legacy_client_hello(host: &str, minor: u8) -> Vec<u8> {
+    let mut extensions = Vec::new();
+    let mut extension = |kind: u16, data: &[u8]| {
+        extensions.extend_from_slice(&kind.to_be_bytes());
+        extensions.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        extensions.extend_from_slice(data);
+    };
+    if host.parse::<std::net::IpAddr>().is_err() {
+        let name = host.as_bytes();
+        let mut sni = ((name.len() + 3) as u16).to_be_bytes().to_vec();
+        sni.push(0x00);
+        sni.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        sni.extend_from_slice(name);
+        extension(0x0000, &sni);
+    }
+    // x25519, secp256r1 and secp384r1, with uncompressed points
+    extension(0x000A, &[0x00, 0x06, 0x00, 0x1D, 0x00, 0x17, 0x00, 0x18]);
+    extension(0x000B, &[0x01, 0x00]);
+    extension(0xFF01, &[0x00]);
+
+    let mut hello = vec![0x03, minor];
+    hello.extend((0..32).map(|_| rand::random::<u8>()));
+    hello.push(0x00);
+    hello.extend_from_slice(&((LEGACY_CIPHER_SUITES.len() * 2) as u16).to_be_bytes());
+    for suite in LEGACY_CIPHER_SUITES {
+        hello.extend_from_slice(&suite.to_be_bytes());
+    }
+    hello.extend_from_slice(&[0x01, 0x00]);
+    hello.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+    hello.extend_from_slice(&extensions);
+
+    let mut handshake = vec![0x01];
+    handshake.extend_from_slice(&(hello.len() as u32).to_be_bytes()[1..]);
+    handshake.extend_from_slice(&hello);
+
+    let mut record = vec![0x16, 0x03, 0x01];
+    record.extend_from_slice(&(handshake.len() as u16).to_be_bytes());
+    record.extend_from_slice(&handshake);
+    record
+}
+
+/// Whether a ServerHello answers a hello for `3.minor` with that same version.
+fn accepts_version(response: &[u8], minor: u8) -> bool {
+    // Record header, then the handshake type and length before the server version
+    matches!(response, [0x16, 0x03, _, _, _, 0x02, _, _, _, 0x03, version, ..] if *version == minor)
+}
+
+async fn accepts_legacy_version(host: &str, port: u16, minor: u8) -> Result<bool, String> {
+    timeout(CONNECT_TIMEOUT, async {
+        let mut stream = proxies::connect(host, port).await?;
+        stream
+            .write_all(&legacy_client_hello(host, minor))
+            .await
+            .map_err(|e| e.to_string())?;
+        // Servers that refuse send an alert or close the connection
+        let mut response = [0u8; 11];
+        Ok(stream.read_exact(&mut response).await.is_ok() && accepts_version(&response, minor))
+    })
+    .await
+    .map_err(|_| format!("Timed out probing {}:{}", host, port))?
+}
+
+/// Opens a TLS connection to `host` and records the certificate it presents.
+///
+/// The certificate is read over TLS 1.2 or 1.3, TLS 1.0 and 1.1 are then probed on
+/// their own so hosts still accepting them can be flagged.
+pub async fn inspect_certificate(host: &str, port: u16) -> TlsCertificate {
+    let mut certificate = TlsCertificate {
+        host: host.to_string(),
+        port,
+        ..Default::default()
+    };
+    if let Err(e) = handshake(host, port, &mut certificate).await {
+        certificate.error = Some(e);
+    }
+    for (minor, name) in LEGACY_VERSIONS {
+        match accepts_legacy_version(host, port, minor).await {
+            Ok(true) => certificate.weak_protocols.push(name.to_string()),
+            Ok(false) => {}
+            Err(e) => eprintln!("Failed to probe {} on {}:{}: {}", name, host, port, e),
+        }
+    }
+    certificate.days_until_expiry = certificate
+        .not_after
+        .map(|not_after| (not_after - Utc::now()).num_days());
+    certificate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Issued by "Example Test CA" to example.com for 2024 and 2025, with two DNS and two IP names
+    const LEAF_PEM: &str = "\
+MIIBwzCCAWqgAwIBAgIBKjAKBggqhkjOPQQDAjAnMQswCQYDVQQGEwJVUzEYMBYG
+A1UECgwPRXhhbXBsZSBUZXN0IENBMB4XDTI0MDEwMTAwMDAwMFoXDTI2MDEwMTAw
+MDAwMFowLDEUMBIGA1UECgwLRXhhbXBsZSBPcmcxFDASBgNVBAMMC2V4YW1wbGUu
+Y29tMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEQ3T7UDq6/DzlD+h1crnAXA/9
+6rpAW0jO+roGs7R986rE4xMMMzuuJ9riRu1+e6EcR/kzRkg5z16hqmhxcFTOh6OB
+gTB/MD0GA1UdEQQ2MDSCC2V4YW1wbGUuY29tgg0qLmV4YW1wbGUuY29thwTAAAIB
+hxAgAQ24AAAAAAAAAAAAAAABMB0GA1UdDgQWBBTZa5J9eOmE8Xuud2yFP4H8ecrz
+9DAfBgNVHSMEGDAWgBTbLnjtR/dbrdd7wh3IwpMkICNnBjAKBggqhkjOPQQDAgNH
+ADBEAiBPa+Xkvz+cJSrwVRFE9Ua/9aq8+22nmlUY0QH5SxnKaAIgQVRQqHd3Weou
+3ByFw9z+jry1HCmJbbgoqDR9lZj/ABA=";
+
+    fn leaf_der() -> Vec<u8> {
+        use base64::{engine::general_purpose, Engine};
+        general_purpose::STANDARD
+            .decode(LEAF_PEM.replace('\n', ""))
+            .unwrap()
+    }
+
+    #[test]
+    fn reads_names_validity_and_alt_names() {
+        let mut certificate = TlsCertificate::default();
+        assert!(read_certificate(&leaf_der(), &mut certificate).is_some());
+
+        assert_eq!(certificate.subject.as_deref(), Some("example.com"));
+        // The CA has no common name, its organisation stands in
+        assert_eq!(certificate.issuer.as_deref(), Some("Example Test CA"));
+        assert_eq!(
+            certificate
+                .not_before
+                .map(|time| time.to_rfc3339())
+                .as_deref(),
+            Some("2024-01-01T00:00:00+00:00")
+        );
+        assert_eq!(
+            certificate
+                .not_after
+                .map(|time| time.to_rfc3339())
+                .as_deref(),
+            Some("2026-01-01T00:00:00+00:00")
+        );
+        assert_eq!(
+            certificate.subject_alt_names,
+            ["example.com", "*.example.com", "192.0.2.1", "2001:db8::1"]
+        );
+    }
+
+    #[test]
+    fn garbage_is_not_a_certificate() {
+        let mut certificate = TlsCertificate::default();
+        let der = leaf_der();
+        assert!(read_certificate(&der[..der.len() / 2], &mut certificate).is_none());
+        assert!(read_certificate(b"not a certificate", &mut certificate).is_none());
+        assert!(certificate.subject.is_none());
+    }
+
+    #[test]
+    fn wildcards_cover_one_label() {
+        assert!(name_matches("*.example.com", "www.example.com"));
+        assert!(!name_matches("*.example.com", "example.com"));
+        assert!(!name_matches("*.example.com", "a.b.example.com"));
+        assert!(name_matches("Example.com", "example.com"));
+    }
+
+    #[test]
+    fn legacy_hello_offers_the_version_and_names_the_host() {
+        let hello = legacy_client_hello("example.com", 0x01);
+        assert_eq!(&hello[..3], &[0x16, 0x03, 0x01]);
+        assert_eq!(
+            u16::from_be_bytes([hello[3], hello[4]]) as usize,
+            hello.len() - 5
+        );
+        // Handshake type, then the client version
+        assert_eq!(hello[5], 0x01);
+        assert_eq!(&hello[9..11], &[0x03, 0x01]);
+        assert!(hello.windows(11).any(|window| window == b"example.com"));
+    }
+
+    #[test]
+    fn server_hello_must_pick_the_offered_version() {
+        let server_hello = |minor: u8| {
+            [
+                0x16, 0x03, minor, 0x00, 0x4A, 0x02, 0x00, 0x00, 0x46, 0x03, minor,
+            ]
+        };
+        assert!(accepts_version(&server_hello(0x01), 0x01));
+        assert!(!accepts_version(&server_hello(0x00), 0x01));
+        // A handshake failure alert
+        let alert = [
+            0x15, 0x03, 0x01, 0x00, 0x02, 0x02, 0x28, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert!(!accepts_version(&alert, 0x01));
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackingVendor {
+    GoogleAnalytics4,
+    UniversalAnalytics,
+    GoogleTagManager,
+    MetaPixel,
+    Hotjar,
+    OneTrust,
+    Cookiebot,
+    Usercentrics,
+    Didomi,
+    CookieYes,
+}
+
+impl TrackingVendor {
+    pub fn name(self) -> &'static str {
+        match self {
+            TrackingVendor::GoogleAnalytics4 => "Google Analytics 4",
+            TrackingVendor::UniversalAnalytics => "Universal Analytics",
+            TrackingVendor::GoogleTagManager => "Google Tag Manager",
+            TrackingVendor::MetaPixel => "Meta Pixel",
+            TrackingVendor::Hotjar => "Hotjar",
+            TrackingVendor::OneTrust => "OneTrust",
+            TrackingVendor::Cookiebot => "Cookiebot",
+            TrackingVendor::Usercentrics => "Usercentrics",
+            TrackingVendor::Didomi => "Didomi",
+            TrackingVendor::CookieYes => "CookieYes",
+        }
+    }
+
+    /// Consent managers, of which a page should load a single one
+    pub fn is_consent_manager(self) -> bool {
+        matches!(
+            self,
+            TrackingVendor::OneTrust
+                | TrackingVendor::Cookiebot
+                | TrackingVendor::Usercentrics
+                | TrackingVendor::Didomi
+                | TrackingVendor::CookieYes
+        )
+    }
+}
+
+/// A tag found on a page, with its container or property ID when the snippet carries one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackingTag {
+    pub vendor: TrackingVendor,
+    pub id: String,
+    /// How many times the tag is loaded, more than one fires every hit twice
+    pub occurrences: usize,
+}
+
+// Where a signature looks: the `src` of external scripts, or the code of inline ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Slot {
+    Src,
+    Inline,
+}
+
+struct Signature {
+    vendor: TrackingVendor,
+    slot: Slot,
+    regex: Regex,
+}
+
+// Consent managers take their ID as an attribute of the script tag
+const ID_ATTRIBUTES: [&str; 3] = ["data-cbid", "data-domain-script", "data-settings-id"];
+
+static SIGNATURES: Lazy<Vec<Signature>> = Lazy::new(|| {
+    use Slot::*;
+    use TrackingVendor::*;
+
+    [
+        (
+            GoogleAnalytics4,
+            Src,
+            r"(?i)googletagmanager\.com/gtag/js\?(?:[^#]*&)?id=(G-[A-Z0-9]{4,})",
+        ),
+        (
+            GoogleAnalytics4,
+            Inline,
+            r#"gtag\(\s*['"]config['"]\s*,\s*['"](G-[A-Z0-9]{4,})['"]"#,
+        ),
+        (
+            UniversalAnalytics,
+            Src,
+            r"(?i)googletagmanager\.com/gtag/js\?(?:[^#]*&)?id=(UA-\d{4,10}-\d{1,4})",
+        ),
+        (
+            UniversalAnalytics,
+            Inline,
+            r#"['"](UA-\d{4,10}-\d{1,4})['"]"#,
+        ),
+        (
+            GoogleTagManager,
+            Src,
+            r"(?i)googletagmanager\.com/gtm\.js\?(?:[^#]*&)?id=(GTM-[A-Z0-9]{4,})",
+        ),
+        (GoogleTagManager, Inline, r#"['"](GTM-[A-Z0-9]{4,})['"]"#),
+        (
+            MetaPixel,
+            Inline,
+            r#"fbq\(\s*['"]init['"]\s*,\s*['"](\d{6,20})['"]"#,
+        ),
+        (Hotjar, Src, r"(?i)static\.hotjar\.com/c/hotjar-(\d+)\.js"),
+        (Hotjar, Inline, r"\bhjid\s*:\s*(\d+)"),
+        (
+            OneTrust,
+            Src,
+            r"(?i)cdn\.cookielaw\.org/|optanon\.blob\.core\.windows\.net/",
+        ),
+        (Cookiebot, Src, r"(?i)consent\.cookiebot\.(?:com|eu)/uc\.js"),
+        (
+            Usercentrics,
+            Src,
+            r"(?i)app\.usercentrics\.eu/browser-ui/|web\.cmp\.usercentrics\.eu/",
+        ),
+        (
+            Didomi,
+            Src,
+            r"(?i)sdk\.privacy-center\.org/([0-9a-f-]{36})/loader\.js",
+        ),
+        (
+            Didomi,
+            Inline,
+            r#"(?i)sdk\.privacy-center\.org/(?:['"]\s*\+\s*['"])?([0-9a-f-]{36})"#,
+        ),
+        (
+            CookieYes,
+            Src,
+            r"(?i)cdn-cookieyes\.com/client_data/([0-9a-f]+)/script\.js",
+        ),
+    ]
+    .into_iter()
+    .map(|(vendor, slot, pattern)| Signature {
+        vendor,
+        slot,
+        regex: Regex::new(pattern).unwrap(),
+    })
+    .collect()
+});
+
+fn src_id(signature: &Signature, src: &str, script: ElementRef) -> Option<String> {
+    let captures = signature.regex.captures(src)?;
+    let id = match captures.get(1) {
+        Some(id) => id.as_str().to_string(),
+        None => ID_ATTRIBUTES
+            .iter()
+            .find_map(|name| script.value().attr(name))
+            .map(|id| id.trim().to_string())
+            .unwrap_or_default(),
+    };
+    Some(id)
+}
+
+/// Detects the analytics, marketing and consent tags loaded by a page's scripts.
+pub fn extract_tracking(html: &str) -> Vec<TrackingTag> {
+    let document = Html::parse_document(html);
+    let script_selector = Selector::parse("script").unwrap();
+
+    // A snippet is usually a loader plus a config call, so each slot is counted on its own
+    let mut counts: BTreeMap<(TrackingVendor, String, Slot), usize> = BTreeMap::new();
+    for script in document.select(&script_selector) {
+        match script.value().attr("src") {
+            Some(src) => {
+                for signature in SIGNATURES.iter().filter(|s| s.slot == Slot::Src) {
+                    if let Some(id) = src_id(signature, src, script) {
+                        *counts.entry((signature.vendor, id, Slot::Src)).or_default() += 1;
+                    }
+                }
+            }
+            None => {
+                let code = script.text().collect::<String>();
+                for signature in SIGNATURES.iter().filter(|s| s.slot == Slot::Inline) {
+                    for captures in signature.regex.captures_iter(&code) {
+                        *counts
+                            .entry((signature.vendor, captures[1].to_string(), Slot::Inline))
+                            .or_default() += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut tags: BTreeMap<(TrackingVendor, String), usize> = BTreeMap::new();
+    for ((vendor, id, _), count) in counts {
+        let occurrences = tags.entry((vendor, id)).or_default();
+        *occurrences = (*occurrences).max(count);
+    }
+    tags.into_iter()
+        .map(|((vendor, id), occurrences)| TrackingTag {
+            vendor,
+            id,
+            occurrences,
+        })
+        .collect()
+}
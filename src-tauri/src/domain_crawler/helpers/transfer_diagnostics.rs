@@ -0,0 +1,116 @@
+use std::io::Read;
+
+use encoding_rs::{Encoding, UTF_8};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use reqwest::Version;
+use serde::{Deserialize, Serialize};
+
+/// Sent with page requests so the diagnostics see what compression the server offers.
+pub const ACCEPT_ENCODING: &str = "gzip, deflate, br";
+
+// Servers commonly skip compressing small responses, they are not worth flagging
+const MIN_COMPRESSIBLE_BYTES: usize = 1024;
+
+/// How a page travelled over the wire.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TransferDiagnostics {
+    /// Such as "HTTP/2"
+    pub http_version: String,
+    pub content_encoding: Option<String>,
+    /// Bytes received, before decompression
+    pub transfer_size: usize,
+    pub decoded_size: usize,
+    /// Decoded size divided by transfer size, None when not compressed
+    pub compression_ratio: Option<f32>,
+    /// Time to first byte of the final response in milliseconds
+    pub ttfb_ms: f64,
+    /// A text response large enough to compress that was served without compression
+    pub uncompressed: bool,
+    /// An HTTPS page that did not negotiate HTTP/2 or later, plain HTTP never does
+    pub http1_only: bool,
+}
+
+pub fn version_name(version: Version) -> String {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_11 => "HTTP/1.1",
+        Version::HTTP_2 => "HTTP/2",
+        Version::HTTP_3 => "HTTP/3",
+        _ => "Unknown",
+    }
+    .to_string()
+}
+
+fn is_compressible(content_type: Option<&str>) -> bool {
+    content_type.is_some_and(|content_type| {
+        let content_type = content_type.to_lowercase();
+        content_type.starts_with("text/")
+            || ["json", "javascript", "xml", "svg"]
+                .iter()
+                .any(|kind| content_type.contains(kind))
+    })
+}
+
+/// Decompresses a body sent with `Content-Encoding`, returning it unchanged for identity.
+pub fn decode_body(bytes: &[u8], encoding: Option<&str>) -> Result<Vec<u8>, String> {
+    let mut decoded = Vec::new();
+    let result = match encoding.map(|e| e.trim().to_lowercase()).as_deref() {
+        None | Some("") | Some("identity") => return Ok(bytes.to_vec()),
+        Some("gzip") | Some("x-gzip") => GzDecoder::new(bytes).read_to_end(&mut decoded),
+        Some("deflate") => ZlibDecoder::new(bytes).read_to_end(&mut decoded),
+        Some("br") => brotli::Decompressor::new(bytes, 4096).read_to_end(&mut decoded),
+        Some(other) => return Err(format!("Unsupported content encoding {}", other)),
+    };
+    result
+        .map(|_| decoded)
+        .map_err(|e| format!("Failed to decompress body: {}", e))
+}
+
+/// Decodes text in the charset named by the content type, UTF-8 when none is given.
+pub fn decode_text(bytes: &[u8], content_type: Option<&str>) -> String {
+    let encoding = content_type
+        .and_then(|content_type| {
+            content_type
+                .split(';')
+                .filter_map(|param| param.trim().split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+                .map(|(_, charset)| charset.trim().trim_matches('"').to_string())
+        })
+        .and_then(|charset| Encoding::for_label(charset.as_bytes()))
+        .unwrap_or(UTF_8);
+    encoding.decode(bytes).0.into_owned()
+}
+
+pub fn diagnose(
+    version: Version,
+    https: bool,
+    content_encoding: Option<&str>,
+    content_type: Option<&str>,
+    transfer_size: usize,
+    decoded_size: usize,
+    ttfb: f64,
+) -> TransferDiagnostics {
+    let content_encoding = content_encoding
+        .map(|e| e.trim().to_lowercase())
+        .filter(|e| !e.is_empty() && e != "identity");
+    let compression_ratio = match (&content_encoding, transfer_size) {
+        (Some(_), size) if size > 0 => {
+            Some(((decoded_size as f32 / size as f32) * 100.0).round() / 100.0)
+        }
+        _ => None,
+    };
+
+    TransferDiagnostics {
+        http_version: version_name(version),
+        uncompressed: content_encoding.is_none()
+            && is_compressible(content_type)
+            && decoded_size >= MIN_COMPRESSIBLE_BYTES,
+        content_encoding,
+        transfer_size,
+        decoded_size,
+        compression_ratio,
+        ttfb_ms: (ttfb * 1000.0 * 10.0).round() / 10.0,
+        http1_only: https && version <= Version::HTTP_11,
+    }
+}
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{lookup_host, TcpStream};
+use tokio::time::{timeout, Duration, Instant};
+use tokio_rustls::rustls::pki_types::ServerName;
+use url::Url;
+
+use super::tls_certificate;
+use super::transfer_diagnostics::ACCEPT_ENCODING;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+// The waterfall only needs the timing, very large bodies are cut off
+const MAX_DOWNLOAD_BYTES: usize = 10 * 1024 * 1024;
+
+/// Phase timings of a fresh connection to a page, in milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Waterfall {
+    pub dns_ms: f64,
+    pub connect_ms: f64,
+    /// None for plain HTTP
+    pub tls_ms: Option<f64>,
+    pub ttfb_ms: f64,
+    pub download_ms: f64,
+    pub total_ms: f64,
+}
+
+fn millis(since: Instant) -> f64 {
+    (since.elapsed().as_secs_f64() * 1000.0 * 10.0).round() / 10.0
+}
+
+// Sends the request and times the first byte and the rest of the body
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    request: &[u8],
+) -> Result<(f64, f64), String> {
+    let start = Instant::now();
+    stream.write_all(request).await.map_err(|e| e.to_string())?;
+
+    let mut buffer = vec![0u8; 16 * 1024];
+    let read = stream.read(&mut buffer).await.map_err(|e| e.to_string())?;
+    let ttfb = millis(start);
+    if read == 0 {
+        return Err("Connection closed before a response".to_string());
+    }
+
+    let download_start = Instant::now();
+    let mut received = read;
+    while received < MAX_DOWNLOAD_BYTES {
+        match stream.read(&mut buffer).await {
+            Ok(0) => break,
+            Ok(read) => received += read,
+            // Servers often drop TLS without close_notify once the body is sent
+            Err(_) => break,
+        }
+    }
+    Ok((ttfb, millis(download_start)))
+}
+
+async fn probe(url: &Url, user_agent: &str) -> Result<Waterfall, String> {
+    let host = url.host_str().ok_or("URL has no host")?;
+    let port = url.port_or_known_default().ok_or("URL has no port")?;
+    let started = Instant::now();
+
+    let dns_start = Instant::now();
+    let addr = lookup_host((host, port))
+        .await
+        .map_err(|e| format!("DNS lookup for {} failed: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("{} has no addresses", host))?;
+    let dns_ms = millis(dns_start);
+
+    let connect_start = Instant::now();
+    let tcp = TcpStream::connect(addr)
+        .await
+        .map_err(|e| format!("Connecting to {} failed: {}", addr, e))?;
+    let connect_ms = millis(connect_start);
+
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    // `Url` only keeps a port that is not the scheme's default, which is when Host needs it
+    let host_header = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nAccept: */*\r\nAccept-Encoding: {}\r\nConnection: close\r\n\r\n",
+        path, host_header, user_agent, ACCEPT_ENCODING
+    );
+
+    let (tls_ms, (ttfb_ms, download_ms)) = if url.scheme() == "https" {
+        let tls_start = Instant::now();
+        let server_name = ServerName::try_from(host.to_string()).map_err(|e| e.to_string())?;
+        let stream = tls_certificate::permissive_connector()?
+            .connect(server_name, tcp)
+            .await
+            .map_err(|e| format!("TLS handshake with {} failed: {}", host, e))?;
+        let tls_ms = millis(tls_start);
+        (Some(tls_ms), exchange(stream, request.as_bytes()).await?)
+    } else {
+        (None, exchange(tcp, request.as_bytes()).await?)
+    };
+
+    Ok(Waterfall {
+        dns_ms,
+        connect_ms,
+        tls_ms,
+        ttfb_ms,
+        download_ms,
+        total_ms: millis(started),
+    })
+}
+
+/// Times DNS, connect, TLS, first byte and download for `url` over a new connection.
+///
+/// The crawl's pooled connections skip the first three phases, so this opens its own
+/// connection. It goes direct and unauthenticated, proxies and credentials are not used.
+pub async fn measure(url: &Url, user_agent: &str) -> Result<Waterfall, String> {
+    timeout(PROBE_TIMEOUT, probe(url, user_agent))
+        .await
+        .map_err(|_| format!("Timed out timing {}", url))?
+}
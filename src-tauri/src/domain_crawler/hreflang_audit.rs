@@ -0,0 +1,337 @@
+use std::collections::{HashMap, HashSet};
+
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::helpers::canonical_selector::normalise_url;
+use super::helpers::hreflang_selector::is_valid_hreflang_code;
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+use super::subresources;
+
+// Targets outside the crawled set are checked with this many concurrent requests
+const TARGET_CHECK_CONCURRENCY: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum HreflangIssueKind {
+    InvalidCode,
+    NonOkTarget,
+    /// The target redirects, search engines want the final URL in the annotation
+    RedirectTarget,
+    UnreachableTarget,
+    MissingReturnTag,
+    MissingSelfReference,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HreflangIssue {
+    pub url: String,
+    pub code: String,
+    pub target: String,
+    pub kind: HreflangIssueKind,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HreflangReport {
+    pub pages_with_hreflang: usize,
+    pub annotations: usize,
+    pub issues: Vec<HreflangIssue>,
+}
+
+/// Collects the hreflang annotations and the statuses of the crawled pages one page at a time.
+#[derive(Default)]
+pub struct HreflangCollector {
+    report: HreflangReport,
+    // The resolved alternates declared by each crawled page
+    alternates: HashMap<String, HashSet<String>>,
+    statuses: HashMap<String, u16>,
+    annotations: Vec<(String, String, String, String)>,
+}
+
+impl HreflangCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        let Ok(page_url) = Url::parse(&result.url) else {
+            return;
+        };
+        let page = normalise_url(&page_url);
+        self.statuses.insert(page.clone(), result.status_code);
+        // The URLs that redirected to this page answered with the redirect
+        for hop in &result.redirect_chain {
+            if let Ok(hop_url) = Url::parse(&hop.url) {
+                self.statuses
+                    .entry(normalise_url(&hop_url))
+                    .or_insert(hop.status_code);
+            }
+        }
+
+        let Some(hreflangs) = result.hreflangs.as_ref().filter(|h| !h.is_empty()) else {
+            return;
+        };
+        self.report.pages_with_hreflang += 1;
+
+        for hreflang in hreflangs {
+            self.report.annotations += 1;
+
+            if !is_valid_hreflang_code(&hreflang.code) {
+                self.report.issues.push(HreflangIssue {
+                    url: result.url.clone(),
+                    code: hreflang.code.clone(),
+                    target: hreflang.url.clone(),
+                    kind: HreflangIssueKind::InvalidCode,
+                    detail: format!("'{}' is not a valid BCP-47 language tag", hreflang.code),
+                });
+            }
+
+            if let Ok(target) = page_url.join(&hreflang.url) {
+                let target = normalise_url(&target);
+                self.alternates
+                    .entry(page.clone())
+                    .or_default()
+                    .insert(target.clone());
+                self.annotations.push((
+                    result.url.clone(),
+                    page.clone(),
+                    hreflang.code.clone(),
+                    target,
+                ));
+            }
+        }
+    }
+
+    /// Validates hreflang codes, target status codes and return-tag reciprocity across
+    /// the collected pages.
+    ///
+    /// `client` must not follow redirects, so that redirecting targets are reported.
+    pub async fn finish(self, client: &Client) -> HreflangReport {
+        let Self {
+            mut report,
+            alternates,
+            statuses,
+            annotations,
+        } = self;
+
+        // Resolve the status of targets that were not part of the crawl
+        let unknown: HashSet<String> = annotations
+            .iter()
+            .map(|(_, _, _, target)| target.clone())
+            .filter(|target| !statuses.contains_key(target))
+            .collect();
+
+        let fetched: HashMap<String, Result<u16, String>> = stream::iter(unknown)
+            .map(|target| async move {
+                let status = subresources::send(client.head(&target), &target)
+                    .await
+                    .map(|r| r.status().as_u16())
+                    .map_err(|e| e.to_string());
+                (target, status)
+            })
+            .buffer_unordered(TARGET_CHECK_CONCURRENCY)
+            .collect()
+            .await;
+
+        for (url, page, code, target) in &annotations {
+            let issue = |kind, detail: String| HreflangIssue {
+                url: url.clone(),
+                code: code.clone(),
+                target: target.clone(),
+                kind,
+                detail,
+            };
+
+            let status = match statuses.get(target) {
+                Some(status) => Ok(*status),
+                None => fetched
+                    .get(target)
+                    .cloned()
+                    .unwrap_or_else(|| Err("Not checked".to_string())),
+            };
+
+            let redirects = matches!(status, Ok(300..=399));
+            match status {
+                Ok(200) => {}
+                Ok(status) if redirects => report.issues.push(issue(
+                    HreflangIssueKind::RedirectTarget,
+                    format!("Target returned {} and redirects", status),
+                )),
+                Ok(status) => report.issues.push(issue(
+                    HreflangIssueKind::NonOkTarget,
+                    format!("Target returned {}", status),
+                )),
+                Err(e) => report.issues.push(issue(
+                    HreflangIssueKind::UnreachableTarget,
+                    format!("Target could not be fetched: {}", e),
+                )),
+            }
+
+            // Reciprocity can only be verified for targets we crawled, redirects have no tags of their own
+            if target != page && !redirects {
+                if let Some(target_alternates) = alternates.get(target) {
+                    if !target_alternates.contains(page) {
+                        report.issues.push(issue(
+                            HreflangIssueKind::MissingReturnTag,
+                            "Target does not link back to this page".to_string(),
+                        ));
+                    }
+                } else if statuses.contains_key(target) {
+                    report.issues.push(issue(
+                        HreflangIssueKind::MissingReturnTag,
+                        "Target has no hreflang annotations".to_string(),
+                    ));
+                }
+            }
+        }
+
+        for (page, targets) in &alternates {
+            if !targets.contains(page) {
+                let url = annotations
+                    .iter()
+                    .find(|(_, p, _, _)| p == page)
+                    .map(|(url, _, _, _)| url.clone())
+                    .unwrap_or_else(|| page.clone());
+                report.issues.push(HreflangIssue {
+                    url,
+                    code: String::new(),
+                    target: page.clone(),
+                    kind: HreflangIssueKind::MissingSelfReference,
+                    detail: "Page does not include a self-referencing hreflang".to_string(),
+                });
+            }
+        }
+
+        report
+    }
+}
+
+pub fn register_issues(report: &HreflangReport, issues: &mut IssueRegistry) {
+    for issue in &report.issues {
+        let kind = match issue.kind {
+            HreflangIssueKind::InvalidCode => IssueKind::HreflangInvalidCode,
+            HreflangIssueKind::NonOkTarget | HreflangIssueKind::UnreachableTarget => {
+                IssueKind::HreflangBrokenTarget
+            }
+            HreflangIssueKind::RedirectTarget => IssueKind::HreflangToRedirect,
+            HreflangIssueKind::MissingReturnTag => IssueKind::HreflangMissingReturn,
+            HreflangIssueKind::MissingSelfReference => IssueKind::HreflangMissingSelf,
+        };
+        issues.flag(kind, &issue.url);
+    }
+}
+
+impl StoredReport for HreflangReport {
+    const KIND: &'static str = "hreflang";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain_crawler::helpers::hreflang_selector::HreflangObject;
+    use crate::domain_crawler::redirect_audit::RedirectHop;
+
+    async fn audit_hreflangs(results: &[DomainCrawlResults], client: &Client) -> HreflangReport {
+        let mut collector = HreflangCollector::default();
+        for result in results {
+            collector.add(result);
+        }
+        collector.finish(client).await
+    }
+
+    fn page(url: &str, hreflangs: &[(&str, &str)]) -> DomainCrawlResults {
+        DomainCrawlResults {
+            hreflangs: Some(
+                hreflangs
+                    .iter()
+                    .map(|(code, url)| HreflangObject {
+                        code: code.to_string(),
+                        url: url.to_string(),
+                        from_header: false,
+                    })
+                    .collect(),
+            ),
+            ..DomainCrawlResults::crawled(url)
+        }
+    }
+
+    fn issues(report: &HreflangReport) -> Vec<(&str, &str, HreflangIssueKind)> {
+        let mut issues: Vec<_> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.url.as_str(), issue.code.as_str(), issue.kind.clone()))
+            .collect();
+        issues.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        issues
+    }
+
+    #[tokio::test]
+    async fn checks_codes_return_tags_and_redirecting_targets() {
+        let mut french = page("https://example.com/fr/", &[]);
+        french.hreflangs = None;
+        french.redirect_chain = vec![RedirectHop {
+            url: "https://example.com/fr-old/".to_string(),
+            status_code: 301,
+            location: "/fr/".to_string(),
+        }];
+        let results = vec![
+            page(
+                "https://example.com/en/",
+                &[
+                    ("en", "/en/"),
+                    ("de", "/de/"),
+                    ("fr", "/fr-old/"),
+                    ("it", "/it/"),
+                ],
+            ),
+            page(
+                "https://example.com/de/",
+                &[("en", "/en/"), ("de-DE", "/de/"), ("english", "/en/")],
+            ),
+            page("https://example.com/it/", &[("it", "/it/")]),
+            french,
+        ];
+
+        // Every target was crawled, so the client is never used
+        let report = audit_hreflangs(&results, &Client::new()).await;
+        assert_eq!(report.pages_with_hreflang, 3);
+        assert_eq!(report.annotations, 8);
+        assert_eq!(
+            issues(&report),
+            [
+                (
+                    "https://example.com/de/",
+                    "english",
+                    HreflangIssueKind::InvalidCode
+                ),
+                (
+                    "https://example.com/en/",
+                    "fr",
+                    HreflangIssueKind::RedirectTarget
+                ),
+                (
+                    "https://example.com/en/",
+                    "it",
+                    HreflangIssueKind::MissingReturnTag
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn flags_pages_without_a_self_reference() {
+        let results = vec![
+            page("https://example.com/en", &[("de", "/de")]),
+            page("https://example.com/de", &[("de", "/de"), ("en", "/en")]),
+        ];
+        let report = audit_hreflangs(&results, &Client::new()).await;
+        assert_eq!(
+            issues(&report),
+            [(
+                "https://example.com/en",
+                "",
+                HreflangIssueKind::MissingSelfReference
+            )]
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::helpers::images_selector::{image_client, image_permit};
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+use super::subresources;
+
+// Typical size of the same picture as WebP and AVIF, relative to JPEG and PNG
+const WEBP_RATIO_JPEG: f64 = 0.70;
+const WEBP_RATIO_PNG: f64 = 0.74;
+const AVIF_RATIO: f64 = 0.50;
+
+// Larger images are not downloaded for transcoding
+const MAX_TRANSCODE_BYTES: usize = 5 * 1024 * 1024;
+
+/// An image with the pages that show it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportedImage {
+    pub url: String,
+    pub status_code: u16,
+    pub size_kb: u64,
+    pub content_type: String,
+    pub referrers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ImageReport {
+    /// Images above this many KB are listed as oversized
+    pub threshold_kb: u64,
+    pub unique_images: usize,
+    /// Images answering 4xx or 5xx
+    pub broken: Vec<ReportedImage>,
+    /// Images above the threshold, largest first
+    pub oversized: Vec<ReportedImage>,
+    /// Images served from another domain than the page showing them
+    pub third_party: Vec<ReportedImage>,
+    /// JPEG and PNG images that could be served as WebP or AVIF, largest saving first
+    pub modern_format_candidates: Vec<FormatRecommendation>,
+    /// Estimated KB saved by serving every candidate as WebP
+    pub estimated_savings_kb: u64,
+    /// `<img>` tags without width or height, which shift the layout when the image loads
+    pub missing_dimensions: Vec<ReportedImage>,
+}
+
+/// A JPEG or PNG image with its estimated size in a modern format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormatRecommendation {
+    pub url: String,
+    pub content_type: String,
+    pub size_kb: u64,
+    pub pages: usize,
+    pub webp_kb: u64,
+    pub avif_kb: u64,
+    /// The WebP size was measured by transcoding the image rather than estimated
+    pub measured: bool,
+}
+
+impl FormatRecommendation {
+    fn savings_kb(&self) -> u64 {
+        self.size_kb.saturating_sub(self.webp_kb)
+    }
+}
+
+pub fn register_issues(report: &ImageReport, issues: &mut IssueRegistry) {
+    for image in &report.broken {
+        issues.flag(IssueKind::BrokenImage, &image.url);
+    }
+    for image in &report.oversized {
+        issues.flag(IssueKind::OversizedImage, &image.url);
+    }
+    for image in &report.missing_dimensions {
+        issues.flag(IssueKind::MissingImageDimensions, &image.url);
+    }
+}
+
+impl StoredReport for ImageReport {
+    const KIND: &'static str = "images";
+}
+
+fn bare_host(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    Some(url.host_str()?.trim_start_matches("www.").to_lowercase())
+}
+
+struct ImageRecord {
+    status_code: u16,
+    size_kb: u64,
+    content_type: String,
+    third_party: bool,
+    missing_dimensions: bool,
+    referrers: BTreeSet<String>,
+}
+
+fn is_png(content_type: &str) -> bool {
+    content_type.to_lowercase().contains("png")
+}
+
+fn recommend_format(image: &ReportedImage) -> Option<FormatRecommendation> {
+    let content_type = image.content_type.to_lowercase();
+    let webp_ratio = if content_type.contains("jpeg") || content_type.contains("jpg") {
+        WEBP_RATIO_JPEG
+    } else if is_png(&content_type) {
+        WEBP_RATIO_PNG
+    } else {
+        return None;
+    };
+    // Images under 1 KB round to zero and have nothing to gain
+    if image.status_code != 200 || image.size_kb == 0 {
+        return None;
+    }
+    Some(FormatRecommendation {
+        url: image.url.clone(),
+        content_type: image.content_type.clone(),
+        size_kb: image.size_kb,
+        pages: image.referrers.len(),
+        webp_kb: (image.size_kb as f64 * webp_ratio).round() as u64,
+        avif_kb: (image.size_kb as f64 * AVIF_RATIO).round() as u64,
+        measured: false,
+    })
+}
+
+fn sort_recommendations(report: &mut ImageReport) {
+    report
+        .modern_format_candidates
+        .sort_by(|a, b| b.savings_kb().cmp(&a.savings_kb()).then(a.url.cmp(&b.url)));
+    report.estimated_savings_kb = report
+        .modern_format_candidates
+        .iter()
+        .map(FormatRecommendation::savings_kb)
+        .sum();
+}
+
+/// Builds the crawl-level image report from the per-page image checks, one page at a time.
+pub struct ImageCollector {
+    threshold_kb: u64,
+    images: HashMap<String, ImageRecord>,
+}
+
+impl ImageCollector {
+    pub fn new(threshold_kb: u64) -> Self {
+        Self {
+            threshold_kb,
+            images: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        let Ok(page_images) = &result.images else {
+            return;
+        };
+        let page_host = bare_host(&result.url).unwrap_or_default();
+        for (url, _, size_kb, content_type, status_code, size_not_specified) in page_images {
+            // Subdomains of the page, such as a static. host, count as first party
+            let third_party = bare_host(url).is_some_and(|host| {
+                host != page_host && !host.ends_with(&format!(".{}", page_host))
+            });
+            let record = self
+                .images
+                .entry(url.clone())
+                .or_insert_with(|| ImageRecord {
+                    status_code: *status_code,
+                    size_kb: *size_kb,
+                    content_type: content_type.clone(),
+                    third_party,
+                    missing_dimensions: false,
+                    referrers: BTreeSet::new(),
+                });
+            record.third_party |= third_party;
+            record.missing_dimensions |= *size_not_specified;
+            record.referrers.insert(result.url.clone());
+        }
+    }
+
+    pub fn finish(self) -> ImageReport {
+        finish_report(self.images, self.threshold_kb)
+    }
+}
+
+fn finish_report(images: HashMap<String, ImageRecord>, threshold_kb: u64) -> ImageReport {
+    let mut report = ImageReport {
+        threshold_kb,
+        unique_images: images.len(),
+        ..Default::default()
+    };
+    for (url, record) in images {
+        let image = ReportedImage {
+            url,
+            status_code: record.status_code,
+            size_kb: record.size_kb,
+            content_type: record.content_type,
+            referrers: record.referrers.into_iter().collect(),
+        };
+        if image.status_code >= 400 {
+            report.broken.push(image.clone());
+        }
+        if image.size_kb > threshold_kb {
+            report.oversized.push(image.clone());
+        }
+        if let Some(recommendation) = recommend_format(&image) {
+            report.modern_format_candidates.push(recommendation);
+        }
+        if record.missing_dimensions {
+            report.missing_dimensions.push(image.clone());
+        }
+        if record.third_party {
+            report.third_party.push(image);
+        }
+    }
+
+    let by_referrers = |a: &ReportedImage, b: &ReportedImage| {
+        b.referrers
+            .len()
+            .cmp(&a.referrers.len())
+            .then(a.url.cmp(&b.url))
+    };
+    report.broken.sort_by(by_referrers);
+    report.third_party.sort_by(by_referrers);
+    report.missing_dimensions.sort_by(by_referrers);
+    sort_recommendations(&mut report);
+    report
+        .oversized
+        .sort_by(|a, b| b.size_kb.cmp(&a.size_kb).then(a.url.cmp(&b.url)));
+    report
+}
+
+async fn download(url: &str) -> Result<Vec<u8>, String> {
+    let _permit = image_permit().await;
+    let mut response = subresources::send(image_client().get(url), url)
+        .await
+        .map_err(|e| format!("Failed to download image {}: {}", url, e))?;
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read image {}: {}", url, e))?
+    {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > MAX_TRANSCODE_BYTES {
+            return Err(format!("Image {} is too large to transcode", url));
+        }
+    }
+    Ok(bytes)
+}
+
+// Returns the size in bytes of the image re-encoded as lossless WebP
+fn transcode_to_webp(bytes: Vec<u8>) -> Result<usize, String> {
+    let image = image::load_from_memory(&bytes).map_err(|e| e.to_string())?;
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    image
+        .write_to(&mut buffer, image::ImageFormat::WebP)
+        .map_err(|e| e.to_string())?;
+    Ok(buffer.into_inner().len())
+}
+
+/// Replaces the estimated WebP size of up to `samples` PNG candidates with a measured one.
+///
+/// Only PNGs are transcoded: the WebP encoder is lossless, so comparing it against a lossy
+/// JPEG would overstate the size and those keep the estimate.
+pub async fn measure_savings(report: &mut ImageReport, samples: usize) {
+    let sampled: Vec<usize> = report
+        .modern_format_candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, candidate)| is_png(&candidate.content_type))
+        .map(|(index, _)| index)
+        .take(samples)
+        .collect();
+
+    for index in sampled {
+        let url = report.modern_format_candidates[index].url.clone();
+        let measured = match download(&url).await {
+            Ok(bytes) => tokio::task::spawn_blocking(move || transcode_to_webp(bytes))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|result| result),
+            Err(e) => Err(e),
+        };
+        match measured {
+            Ok(webp_bytes) => {
+                let candidate = &mut report.modern_format_candidates[index];
+                candidate.webp_kb = (webp_bytes as u64 / 1024).min(candidate.size_kb);
+                candidate.measured = true;
+            }
+            Err(e) => eprintln!("Failed to transcode {}: {}", url, e),
+        }
+    }
+    sort_recommendations(report);
+}
@@ -0,0 +1,563 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::results_store::{CrawlRecord, ResultsStore};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    pub fn weight(self) -> usize {
+        match self {
+            Severity::Low => 1,
+            Severity::Medium => 2,
+            Severity::High => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Response,
+    Links,
+    Content,
+    Indexability,
+    Performance,
+    Images,
+    Security,
+    International,
+    Accessibility,
+    Tracking,
+}
+
+/// Every kind of problem the analyzers can report, serialized as its stable issue ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    FailedToFetch,
+    ClientError,
+    ServerError,
+    Redirect,
+    BrokenLink,
+    RedirectChain,
+    RedirectLoop,
+    TemporaryRedirect,
+    RedirectToError,
+    MissingTitle,
+    DuplicateTitle,
+    TitleLength,
+    MultipleTitles,
+    MissingDescription,
+    DuplicateDescription,
+    DescriptionLength,
+    MultipleDescriptions,
+    MissingH1,
+    ThinContent,
+    DuplicateContent,
+    NonIndexable,
+    MissingCanonical,
+    InvalidCanonical,
+    CanonicalChain,
+    CanonicalToError,
+    CanonicalToNoindex,
+    CanonicalToRedirect,
+    DeepPage,
+    AmpError,
+    SlowResponse,
+    OversizedImage,
+    MissingAltText,
+    BrokenImage,
+    MissingImageDimensions,
+    MissingSecurityHeaders,
+    CertificateExpired,
+    CertificateExpiresSoon,
+    CertificateInvalid,
+    WeakTlsProtocol,
+    HreflangInvalidCode,
+    HreflangBrokenTarget,
+    HreflangToRedirect,
+    HreflangMissingReturn,
+    HreflangMissingSelf,
+    AccessibilityViolations,
+    MissingTrackingTag,
+    ConflictingTrackingTags,
+    PaginationBrokenSequence,
+    PaginationCanonicalToFirst,
+    NoindexPagination,
+}
+
+/// What the UI shows for an issue kind.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssueDefinition {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub severity: Severity,
+    pub category: Category,
+}
+
+impl IssueKind {
+    pub const ALL: [IssueKind; 50] = [
+        IssueKind::FailedToFetch,
+        IssueKind::ClientError,
+        IssueKind::ServerError,
+        IssueKind::Redirect,
+        IssueKind::BrokenLink,
+        IssueKind::RedirectChain,
+        IssueKind::RedirectLoop,
+        IssueKind::TemporaryRedirect,
+        IssueKind::RedirectToError,
+        IssueKind::MissingTitle,
+        IssueKind::DuplicateTitle,
+        IssueKind::TitleLength,
+        IssueKind::MultipleTitles,
+        IssueKind::MissingDescription,
+        IssueKind::DuplicateDescription,
+        IssueKind::DescriptionLength,
+        IssueKind::MultipleDescriptions,
+        IssueKind::MissingH1,
+        IssueKind::ThinContent,
+        IssueKind::DuplicateContent,
+        IssueKind::NonIndexable,
+        IssueKind::MissingCanonical,
+        IssueKind::InvalidCanonical,
+        IssueKind::CanonicalChain,
+        IssueKind::CanonicalToError,
+        IssueKind::CanonicalToNoindex,
+        IssueKind::CanonicalToRedirect,
+        IssueKind::DeepPage,
+        IssueKind::AmpError,
+        IssueKind::SlowResponse,
+        IssueKind::OversizedImage,
+        IssueKind::MissingAltText,
+        IssueKind::BrokenImage,
+        IssueKind::MissingImageDimensions,
+        IssueKind::MissingSecurityHeaders,
+        IssueKind::CertificateExpired,
+        IssueKind::CertificateExpiresSoon,
+        IssueKind::CertificateInvalid,
+        IssueKind::WeakTlsProtocol,
+        IssueKind::HreflangInvalidCode,
+        IssueKind::HreflangBrokenTarget,
+        IssueKind::HreflangToRedirect,
+        IssueKind::HreflangMissingReturn,
+        IssueKind::HreflangMissingSelf,
+        IssueKind::AccessibilityViolations,
+        IssueKind::MissingTrackingTag,
+        IssueKind::ConflictingTrackingTags,
+        IssueKind::PaginationBrokenSequence,
+        IssueKind::PaginationCanonicalToFirst,
+        IssueKind::NoindexPagination,
+    ];
+
+    pub fn definition(self) -> IssueDefinition {
+        use Category::*;
+        use Severity::*;
+
+        let (id, name, severity, category) = match self {
+            IssueKind::FailedToFetch => ("failed_to_fetch", "Failed to fetch", High, Response),
+            IssueKind::ClientError => ("client_error", "Client errors (4xx)", High, Response),
+            IssueKind::ServerError => ("server_error", "Server errors (5xx)", High, Response),
+            IssueKind::Redirect => ("redirect", "Redirects (3xx)", Low, Response),
+            IssueKind::BrokenLink => ("broken_link", "Broken links", High, Links),
+            IssueKind::RedirectChain => ("redirect_chain", "Long redirect chains", Medium, Links),
+            IssueKind::RedirectLoop => ("redirect_loop", "Redirect loops", High, Links),
+            IssueKind::TemporaryRedirect => {
+                ("temporary_redirect", "Temporary redirects", Low, Links)
+            }
+            IssueKind::RedirectToError => ("redirect_to_error", "Redirects to errors", High, Links),
+            IssueKind::MissingTitle => ("missing_title", "Missing title", High, Content),
+            IssueKind::DuplicateTitle => ("duplicate_title", "Duplicate titles", Medium, Content),
+            IssueKind::TitleLength => ("title_length", "Title too short or long", Low, Content),
+            IssueKind::MultipleTitles => ("multiple_titles", "Multiple titles", Medium, Content),
+            IssueKind::MissingDescription => (
+                "missing_description",
+                "Missing meta description",
+                Medium,
+                Content,
+            ),
+            IssueKind::DuplicateDescription => (
+                "duplicate_description",
+                "Duplicate meta descriptions",
+                Low,
+                Content,
+            ),
+            IssueKind::DescriptionLength => (
+                "description_length",
+                "Meta description too short or long",
+                Low,
+                Content,
+            ),
+            IssueKind::MultipleDescriptions => (
+                "multiple_descriptions",
+                "Multiple meta descriptions",
+                Medium,
+                Content,
+            ),
+            IssueKind::MissingH1 => ("missing_h1", "Missing H1", Medium, Content),
+            IssueKind::ThinContent => ("thin_content", "Thin content", Low, Content),
+            IssueKind::DuplicateContent => {
+                ("duplicate_content", "Duplicate content", Medium, Content)
+            }
+            IssueKind::NonIndexable => ("non_indexable", "Non-indexable", Medium, Indexability),
+            IssueKind::MissingCanonical => {
+                ("missing_canonical", "Missing canonical", Low, Indexability)
+            }
+            IssueKind::InvalidCanonical => (
+                "invalid_canonical",
+                "Invalid or multiple canonicals",
+                Medium,
+                Indexability,
+            ),
+            IssueKind::CanonicalChain => {
+                ("canonical_chain", "Canonical chains", Medium, Indexability)
+            }
+            IssueKind::CanonicalToError => (
+                "canonical_to_error",
+                "Canonicals to error pages",
+                High,
+                Indexability,
+            ),
+            IssueKind::CanonicalToNoindex => (
+                "canonical_to_noindex",
+                "Canonicals to noindexed pages",
+                Medium,
+                Indexability,
+            ),
+            IssueKind::CanonicalToRedirect => (
+                "canonical_to_redirect",
+                "Canonicals to redirects",
+                Medium,
+                Indexability,
+            ),
+            IssueKind::DeepPage => ("deep_page", "Sitemap pages buried deep", Low, Indexability),
+            IssueKind::AmpError => ("amp_error", "AMP errors", Medium, Indexability),
+            IssueKind::SlowResponse => ("slow_response", "Slow response", Medium, Performance),
+            IssueKind::OversizedImage => ("oversized_image", "Oversized images", Low, Performance),
+            IssueKind::MissingAltText => {
+                ("missing_alt_text", "Images missing alt text", Low, Images)
+            }
+            IssueKind::BrokenImage => ("broken_image", "Broken images", Medium, Images),
+            IssueKind::MissingImageDimensions => (
+                "missing_image_dimensions",
+                "Images without dimensions",
+                Low,
+                Images,
+            ),
+            IssueKind::MissingSecurityHeaders => (
+                "missing_security_headers",
+                "Missing security headers",
+                Low,
+                Security,
+            ),
+            IssueKind::CertificateExpired => (
+                "certificate_expired",
+                "Expired certificates",
+                High,
+                Security,
+            ),
+            IssueKind::CertificateExpiresSoon => (
+                "certificate_expires_soon",
+                "Certificates expiring soon",
+                Medium,
+                Security,
+            ),
+            IssueKind::CertificateInvalid => (
+                "certificate_invalid",
+                "Invalid certificates",
+                High,
+                Security,
+            ),
+            IssueKind::WeakTlsProtocol => {
+                ("weak_tls_protocol", "Weak TLS protocols", Medium, Security)
+            }
+            IssueKind::HreflangInvalidCode => (
+                "hreflang_invalid_code",
+                "Invalid hreflang codes",
+                Medium,
+                International,
+            ),
+            IssueKind::HreflangBrokenTarget => (
+                "hreflang_broken_target",
+                "Hreflang to broken pages",
+                Medium,
+                International,
+            ),
+            IssueKind::HreflangToRedirect => (
+                "hreflang_to_redirect",
+                "Hreflang to redirects",
+                Medium,
+                International,
+            ),
+            IssueKind::HreflangMissingReturn => (
+                "hreflang_missing_return",
+                "Hreflang without return tags",
+                Medium,
+                International,
+            ),
+            IssueKind::HreflangMissingSelf => (
+                "hreflang_missing_self",
+                "Hreflang without self reference",
+                Low,
+                International,
+            ),
+            IssueKind::AccessibilityViolations => (
+                "accessibility_violations",
+                "Accessibility violations",
+                Medium,
+                Accessibility,
+            ),
+            IssueKind::MissingTrackingTag => (
+                "missing_tracking_tag",
+                "Pages missing expected tracking tags",
+                Medium,
+                Tracking,
+            ),
+            IssueKind::ConflictingTrackingTags => (
+                "conflicting_tracking_tags",
+                "Duplicate or conflicting tracking tags",
+                Medium,
+                Tracking,
+            ),
+            IssueKind::PaginationBrokenSequence => (
+                "pagination_broken_sequence",
+                "Broken pagination sequences",
+                Medium,
+                Indexability,
+            ),
+            IssueKind::PaginationCanonicalToFirst => (
+                "pagination_canonical_to_first",
+                "Paginated pages canonicalized to page 1",
+                Medium,
+                Indexability,
+            ),
+            IssueKind::NoindexPagination => (
+                "noindex_pagination",
+                "Noindexed paginated pages",
+                Low,
+                Indexability,
+            ),
+        };
+        IssueDefinition {
+            id,
+            name,
+            severity,
+            category,
+        }
+    }
+
+    pub fn id(self) -> &'static str {
+        self.definition().id
+    }
+
+    pub fn name(self) -> &'static str {
+        self.definition().name
+    }
+
+    pub fn severity(self) -> Severity {
+        self.definition().severity
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.id() == id)
+    }
+}
+
+/// One kind of issue found in a crawl and the URLs it affects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Issue {
+    pub id: IssueKind,
+    pub name: String,
+    pub severity: Severity,
+    pub category: Category,
+    pub count: usize,
+    pub urls: Vec<String>,
+}
+
+impl Issue {
+    fn new(kind: IssueKind, urls: Vec<String>) -> Self {
+        let definition = kind.definition();
+        Issue {
+            id: kind,
+            name: definition.name.to_string(),
+            severity: definition.severity,
+            category: definition.category,
+            count: urls.len(),
+            urls,
+        }
+    }
+}
+
+/// Where the analyzers of a crawl register what they found.
+#[derive(Debug, Default)]
+pub struct IssueRegistry {
+    found: BTreeMap<IssueKind, BTreeSet<String>>,
+}
+
+impl IssueRegistry {
+    /// Registers an issue on a URL, several analyzers may report the same one.
+    pub fn flag(&mut self, kind: IssueKind, url: &str) {
+        self.found.entry(kind).or_default().insert(url.to_string());
+    }
+
+    /// The registered issues, worst first.
+    pub fn into_issues(self) -> Vec<Issue> {
+        let mut issues: Vec<Issue> = self
+            .found
+            .into_iter()
+            .map(|(kind, urls)| Issue::new(kind, urls.into_iter().collect()))
+            .collect();
+        sort_issues(&mut issues);
+        issues
+    }
+}
+
+fn sort_issues(issues: &mut [Issue]) {
+    issues.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then(b.count.cmp(&a.count))
+            .then(a.id.cmp(&b.id))
+    });
+}
+
+/// Issue counts of one crawl, a point of the trend chart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueTrendPoint {
+    pub crawl: CrawlRecord,
+    pub total: usize,
+    pub by_severity: BTreeMap<Severity, usize>,
+    pub by_issue: BTreeMap<IssueKind, usize>,
+}
+
+/// The issue URLs that appeared or went away between an older and a newer crawl.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueComparison {
+    pub crawl_a: CrawlRecord,
+    pub crawl_b: CrawlRecord,
+    pub new: Vec<Issue>,
+    pub fixed: Vec<Issue>,
+}
+
+/// Issues of `after` and their URLs that are not in `before`.
+fn subtract(after: &[Issue], before: &[Issue]) -> Vec<Issue> {
+    let before: BTreeMap<IssueKind, BTreeSet<&str>> = before
+        .iter()
+        .map(|issue| (issue.id, issue.urls.iter().map(String::as_str).collect()))
+        .collect();
+
+    let mut changed: Vec<Issue> = after
+        .iter()
+        .filter_map(|issue| {
+            let seen = before.get(&issue.id);
+            let urls: Vec<String> = issue
+                .urls
+                .iter()
+                .filter(|url| seen.map_or(true, |seen| !seen.contains(url.as_str())))
+                .cloned()
+                .collect();
+            (!urls.is_empty()).then(|| Issue::new(issue.id, urls))
+        })
+        .collect();
+    sort_issues(&mut changed);
+    changed
+}
+
+pub fn compare_issues(
+    crawl_a: CrawlRecord,
+    crawl_b: CrawlRecord,
+    issues_a: &[Issue],
+    issues_b: &[Issue],
+) -> IssueComparison {
+    IssueComparison {
+        crawl_a,
+        crawl_b,
+        new: subtract(issues_b, issues_a),
+        fixed: subtract(issues_a, issues_b),
+    }
+}
+
+async fn crawl_record(store: &ResultsStore, crawl_id: i64) -> Result<CrawlRecord, String> {
+    store
+        .crawl(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Crawl {} not found", crawl_id))
+}
+
+#[tauri::command]
+pub fn list_issue_types() -> Vec<IssueDefinition> {
+    IssueKind::ALL
+        .into_iter()
+        .map(IssueKind::definition)
+        .collect()
+}
+
+// GET THE ISSUES OF A STORED CRAWL, WORST FIRST
+#[tauri::command]
+pub async fn get_crawl_issues(crawl_id: i64) -> Result<Vec<Issue>, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    store.issues(crawl_id).await.map_err(|e| e.to_string())
+}
+
+// GET THE ISSUE COUNTS OF EVERY STORED CRAWL OF A DOMAIN, OLDEST FIRST
+#[tauri::command]
+pub async fn get_issue_trend(domain: String) -> Result<Vec<IssueTrendPoint>, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let counts = store
+        .issue_counts(&domain)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut points: Vec<IssueTrendPoint> = store
+        .list_crawls()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|crawl| crawl.domain == domain && crawl.status == "completed")
+        .map(|crawl| IssueTrendPoint {
+            crawl,
+            total: 0,
+            by_severity: BTreeMap::new(),
+            by_issue: BTreeMap::new(),
+        })
+        .collect();
+    points.reverse();
+
+    for (crawl_id, issue_id, count) in counts {
+        let (Some(point), Some(kind)) = (
+            points.iter_mut().find(|point| point.crawl.id == crawl_id),
+            IssueKind::from_id(&issue_id),
+        ) else {
+            continue;
+        };
+        point.total += count;
+        *point.by_severity.entry(kind.severity()).or_insert(0) += count;
+        point.by_issue.insert(kind, count);
+    }
+    Ok(points)
+}
+
+// LIST THE ISSUES THAT ARE NEW OR FIXED BETWEEN TWO STORED CRAWLS OF THE SAME DOMAIN
+#[tauri::command]
+pub async fn compare_crawl_issues(crawl_a: i64, crawl_b: i64) -> Result<IssueComparison, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let record_a = crawl_record(&store, crawl_a).await?;
+    let record_b = crawl_record(&store, crawl_b).await?;
+    if record_a.domain != record_b.domain {
+        return Err(format!(
+            "Cannot compare crawls of different domains: {} and {}",
+            record_a.domain, record_b.domain
+        ));
+    }
+
+    let issues_a = store.issues(crawl_a).await.map_err(|e| e.to_string())?;
+    let issues_b = store.issues(crawl_b).await.map_err(|e| e.to_string())?;
+    Ok(compare_issues(record_a, record_b, &issues_a, &issues_b))
+}
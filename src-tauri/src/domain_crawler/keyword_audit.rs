@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::helpers::term_analysis::TermCounts;
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+// Terms and phrases listed per page
+const TOP_PER_PAGE: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeightedTerm {
+    pub term: String,
+    pub count: usize,
+    /// Occurrences per 100 words of the page
+    pub density: f64,
+    /// Occurrences weighted by how few other pages use the term
+    pub tf_idf: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageKeywords {
+    pub url: String,
+    pub total_words: usize,
+    pub terms: Vec<WeightedTerm>,
+    pub phrases: Vec<WeightedTerm>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct KeywordReport {
+    /// Pages with text, the corpus the weights are computed against
+    pub documents: usize,
+    pub pages: Vec<PageKeywords>,
+}
+
+impl StoredReport for KeywordReport {
+    const KIND: &'static str = "keywords";
+}
+
+fn weigh(
+    counts: &[(String, usize)],
+    document_frequency: &HashMap<String, usize>,
+    documents: usize,
+    total_words: usize,
+) -> Vec<WeightedTerm> {
+    let mut weighted: Vec<WeightedTerm> = counts
+        .iter()
+        .map(|(term, count)| {
+            let df = document_frequency.get(term.as_str()).copied().unwrap_or(1);
+            // Smoothed so a term on every page still weighs a little
+            let idf = (1.0 + documents as f64 / df as f64).ln();
+            let tf = *count as f64 / total_words.max(1) as f64;
+            WeightedTerm {
+                term: term.clone(),
+                count: *count,
+                density: (tf * 100.0 * 100.0).round() / 100.0,
+                tf_idf: (tf * idf * 10000.0).round() / 10000.0,
+            }
+        })
+        .collect();
+    weighted.sort_by(|a, b| {
+        b.tf_idf
+            .total_cmp(&a.tf_idf)
+            .then_with(|| a.term.cmp(&b.term))
+    });
+    weighted.truncate(TOP_PER_PAGE);
+    weighted
+}
+
+/// Weighs each page's terms and phrases with TF-IDF against every crawled page.
+#[derive(Default)]
+pub struct KeywordCollector {
+    pages: Vec<(String, TermCounts)>,
+    document_frequency: HashMap<String, usize>,
+}
+
+impl KeywordCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        if result.status_code != 200 || result.term_counts.total_words == 0 {
+            return;
+        }
+        let counts = &result.term_counts;
+        for (term, _) in counts.terms.iter().chain(counts.phrases.iter()) {
+            *self.document_frequency.entry(term.clone()).or_default() += 1;
+        }
+        self.pages.push((result.url.clone(), counts.clone()));
+    }
+
+    pub fn finish(self) -> KeywordReport {
+        let documents = self.pages.len();
+        let document_frequency = self.document_frequency;
+        KeywordReport {
+            documents,
+            pages: self
+                .pages
+                .into_iter()
+                .map(|(url, counts)| PageKeywords {
+                    url,
+                    total_words: counts.total_words,
+                    terms: weigh(
+                        &counts.terms,
+                        &document_frequency,
+                        documents,
+                        counts.total_words,
+                    ),
+                    phrases: weigh(
+                        &counts.phrases,
+                        &document_frequency,
+                        documents,
+                        counts.total_words,
+                    ),
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::stream::{self, StreamExt};
+use reqwest::{header::LOCATION, Client, StatusCode};
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::results_store::StoredReport;
+use crate::domain_crawler::issues::{IssueKind, IssueRegistry};
+use crate::domain_crawler::{proxies, session, subresources, user_agents};
+use crate::settings::settings::Settings;
+
+// Maximum number of redirects followed when resolving a link
+const MAX_REDIRECT_HOPS: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokenLink {
+    pub url: String,
+    pub internal: bool,
+    pub status: Option<u16>,
+    pub redirect_target: Option<String>,
+    pub error: Option<String>,
+    pub referrers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BrokenLinksReport {
+    pub checked: usize,
+    /// Internal links to pages the crawl never fetched, they are not requested separately
+    pub unchecked: usize,
+    pub broken: Vec<BrokenLink>,
+}
+
+#[derive(Debug, Default)]
+struct LinkRecord {
+    internal: bool,
+    referrers: BTreeSet<String>,
+}
+
+/// Collects every anchor href across the crawl so each unique link is only verified once.
+#[derive(Debug, Default)]
+pub struct LinkChecker {
+    links: HashMap<String, LinkRecord>,
+    // What the crawl got for the pages it fetched, internal links are judged by it
+    crawled: HashMap<String, LinkOutcome>,
+}
+
+#[derive(Debug, Clone)]
+struct LinkOutcome {
+    status: Option<u16>,
+    redirect_target: Option<String>,
+    error: Option<String>,
+}
+
+impl LinkChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Records all `http(s)` anchor hrefs found on `page`.
+    pub fn collect(&mut self, page: &Url, body: &str, base_url: &Url) {
+        let document = Html::parse_document(body);
+        let selector = Selector::parse("a[href]").unwrap();
+
+        for element in document.select(&selector) {
+            let Some(href) = element.value().attr("href") else {
+                continue;
+            };
+
+            let Ok(mut url) = page.join(href.trim()) else {
+                continue;
+            };
+
+            if url.scheme() != "http" && url.scheme() != "https" {
+                continue;
+            }
+            url.set_fragment(None);
+
+            let record = self.links.entry(url.to_string()).or_default();
+            record.internal = url.host_str() == base_url.host_str();
+            record.referrers.insert(page.to_string());
+        }
+    }
+
+    /// Records the response the crawl got for `url`, a status of 0 is a page that failed to load.
+    pub fn record_crawled(&mut self, url: &str, status_code: u16, redirect_target: Option<String>) {
+        let outcome = match status_code {
+            0 => LinkOutcome {
+                status: None,
+                redirect_target,
+                error: Some("Failed to load during the crawl".to_string()),
+            },
+            status => LinkOutcome {
+                status: Some(status),
+                redirect_target,
+                error: None,
+            },
+        };
+        self.crawled.insert(url.to_string(), outcome);
+    }
+
+    /// Verifies every collected link and returns the ones that are broken.
+    ///
+    /// Internal links take the status the crawl recorded, only external links are requested,
+    /// paced like the crawl's other subresource requests.
+    pub async fn verify(self, settings: &Settings) -> BrokenLinksReport {
+        let client = match proxies::apply(Client::builder())
+            .timeout(Duration::from_secs(settings.links_request_timeout))
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(user_agents::current())
+            .cookie_provider(session::cookies())
+            .build()
+        {
+            Ok(client) => Arc::new(client),
+            Err(e) => {
+                eprintln!("Failed to build link checker client: {}", e);
+                return BrokenLinksReport::default();
+            }
+        };
+
+        let Self { links, crawled } = self;
+        let (internal, external): (Vec<_>, Vec<_>) =
+            links.into_iter().partition(|(_, record)| record.internal);
+
+        let unchecked = internal
+            .iter()
+            .filter(|(url, _)| !crawled.contains_key(url))
+            .count();
+        let known: Vec<_> = internal
+            .into_iter()
+            .filter_map(|(url, record)| {
+                let outcome = crawled.get(&url)?.clone();
+                Some((url, record, outcome))
+            })
+            .collect();
+        let checked = known.len() + external.len();
+
+        let concurrency = settings.links_max_concurrent_requests.max(1);
+        let fetched = stream::iter(external)
+            .map(|(url, record)| {
+                let client = client.clone();
+                async move {
+                    let outcome = check_link(&client, &url).await;
+                    (url, record, outcome)
+                }
+            })
+            .buffer_unordered(concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        let broken = known
+            .into_iter()
+            .chain(fetched)
+            .filter_map(|(url, record, outcome)| {
+                let is_broken =
+                    outcome.error.is_some() || outcome.status.map_or(true, |s| s >= 400);
+
+                is_broken.then(|| BrokenLink {
+                    url,
+                    internal: record.internal,
+                    status: outcome.status,
+                    redirect_target: outcome.redirect_target,
+                    error: outcome.error,
+                    referrers: record.referrers.into_iter().collect(),
+                })
+            })
+            .collect();
+
+        BrokenLinksReport {
+            checked,
+            unchecked,
+            broken,
+        }
+    }
+}
+
+/// HEAD request with a GET fallback for servers that reject HEAD.
+async fn head_then_get(client: &Client, url: &str) -> Result<reqwest::Response, reqwest::Error> {
+    match subresources::send(client.head(url), url).await {
+        Ok(response)
+            if response.status() != StatusCode::METHOD_NOT_ALLOWED
+                && response.status() != StatusCode::NOT_IMPLEMENTED =>
+        {
+            Ok(response)
+        }
+        _ => subresources::send(client.get(url), url).await,
+    }
+}
+
+/// Follows redirects manually so the first redirect target can be reported.
+async fn check_link(client: &Client, url: &str) -> LinkOutcome {
+    let mut current = url.to_string();
+    let mut redirect_target = None;
+
+    for _ in 0..=MAX_REDIRECT_HOPS {
+        let response = match head_then_get(client, &current).await {
+            Ok(response) => response,
+            Err(e) => {
+                return LinkOutcome {
+                    status: None,
+                    redirect_target,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+
+        let status = response.status();
+        if !status.is_redirection() {
+            return LinkOutcome {
+                status: Some(status.as_u16()),
+                redirect_target,
+                error: None,
+            };
+        }
+
+        let next = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|location| Url::parse(&current).ok()?.join(location).ok());
+
+        match next {
+            Some(next) => {
+                redirect_target.get_or_insert_with(|| next.to_string());
+                current = next.to_string();
+            }
+            None => {
+                return LinkOutcome {
+                    status: Some(status.as_u16()),
+                    redirect_target,
+                    error: Some("Redirect without a valid Location header".to_string()),
+                }
+            }
+        }
+    }
+
+    LinkOutcome {
+        status: None,
+        redirect_target,
+        error: Some(format!("More than {} redirects", MAX_REDIRECT_HOPS)),
+    }
+}
+
+pub fn register_issues(report: &BrokenLinksReport, issues: &mut IssueRegistry) {
+    for link in &report.broken {
+        issues.flag(IssueKind::BrokenLink, &link.url);
+    }
+}
+
+impl StoredReport for BrokenLinksReport {
+    const KIND: &'static str = "broken_links";
+}
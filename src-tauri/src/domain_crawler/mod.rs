@@ -1,10 +1,71 @@
+pub mod a11y;
+pub mod alt_text_audit;
+pub mod amp_audit;
+pub mod anchor_text;
+pub mod asset_audit;
+pub mod bing_webmaster;
+pub mod canonical_audit;
+pub mod cdp;
+pub mod competitors;
+pub mod config_profiles;
+pub mod crawl_audits;
+pub mod crawl_cache;
+pub mod crawl_control;
+pub mod crawl_depth;
+pub mod crawl_diff;
+pub mod crawl_progress;
+pub mod crawl_scope;
+pub mod crawl_state_store;
+pub mod crawl_timing;
+pub mod crawler_config;
+pub mod custom_search;
 pub mod database;
 pub mod db_deep;
+pub mod device_comparison;
 pub mod domain_commands;
 pub mod domain_crawler;
+pub mod domain_info;
+pub mod duplicate_content;
+pub mod entity_audit;
+pub mod events;
 pub mod excel;
+pub mod exports;
 pub mod extractors;
+pub mod frontier;
+pub mod ga4;
+pub mod gsc;
 pub mod helpers;
+pub mod hreflang_audit;
+pub mod image_audit;
+pub mod issues;
+pub mod keyword_audit;
+pub mod link_checker;
+pub mod link_graph;
 pub mod models;
+pub mod orphans;
 pub mod page_speed;
+pub mod pagination_audit;
+pub mod preflight;
+pub mod proxies;
+pub mod rate_limiter;
+pub mod redirect_audit;
+pub mod render_audit;
+pub mod renderer;
+pub mod reports;
+pub mod request_auth;
+pub mod response_cache;
+pub mod results_store;
+pub mod scheduler;
+pub mod screenshots;
+pub mod security_headers_audit;
+pub mod session;
+pub mod sitemap_gap;
+pub mod spell_check;
+pub mod subresources;
+pub mod title_description_audit;
+pub mod tls_audit;
+pub mod tracking_audit;
+pub mod url_normalizer;
 pub mod user_agents;
+pub mod wayback;
+pub mod webhooks;
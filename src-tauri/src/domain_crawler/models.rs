@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
@@ -8,14 +8,47 @@ use url::Url;
 use crate::crawler::libs::LinkStatus;
 
 use super::{
+    a11y::rules::A11yAudit,
     helpers::{
-        alt_tags::AltTags, anchor_links::InternalExternalLinks, cross_origin::SecuritySummary,
-        css_selector::CSS, hreflang_selector::HreflangObject, html_size_calculator::Sizes,
-        iframe_selector::Iframe, indexability::Indexability, javascript_selector::JavaScript,
-        links_status_code_checker::LinkCheckResults, meta_robots_selector::MetaRobots,
-        pdf_selector::PdfLinks, text_ratio::TextRatio, title_selector::TitleDetails,
+        alt_tags::AltTags,
+        amp_selector::AmpInfo,
+        anchor_links::InternalExternalLinks,
+        assets_selector::PageAssets,
+        canonical_selector::CanonicalAudit,
+        content_analyzer::ContentAnalysis,
+        cross_origin::SecuritySummary,
+        css_selector::CSS,
+        flesch_reader::Readability,
+        font_selector::FontAudit,
+        headings_selector::HeadingOutline,
+        hreflang_selector::HreflangObject,
+        html_size_calculator::Sizes,
+        iframe_selector::Iframe,
+        images_selector::{ImageCandidate, ImageDimensions},
+        indexability::Indexability,
+        javascript_selector::JavaScript,
+        language_selector::LanguageAudit,
+        lazy_loading::LazyLoadingAudit,
+        links_status_code_checker::LinkCheckResults,
+        media_selector::MediaElement,
+        meta_robots_selector::MetaRobots,
+        pagination_selector::PaginationInfo,
+        pdf_selector::{PdfAudit, PdfLinks},
+        render_diff::RenderDiff,
+        security_headers::SecurityHeadersAudit,
+        social_tags_selector::SocialTags,
+        structured_data_selector::StructuredData,
+        term_analysis::TermCounts,
+        text_ratio::TextRatio,
+        title_description::TitleDescriptionAudit,
+        title_selector::TitleDetails,
+        tracking_selector::TrackingTag,
+        transfer_diagnostics::TransferDiagnostics,
+        waterfall::Waterfall,
     },
-    page_speed::model::LighthouseResult,
+    page_speed::{lab::LabVitals, model::LighthouseResult},
+    redirect_audit::RedirectHop,
+    spell_check::SpellingAudit,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,37 +74,116 @@ pub struct DomainCrawlResults {
     pub url: String,
     pub title: Option<Vec<TitleDetails>>,
     pub description: String,
+    #[serde(default)]
+    pub title_description: TitleDescriptionAudit,
     pub headings: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub heading_outline: HeadingOutline,
     pub javascript: JavaScript,
     pub images: Result<Vec<(String, String, u64, String, u16, bool)>, String>,
+    pub image_candidates: Vec<ImageCandidate>,
+    pub image_dimensions: Vec<ImageDimensions>,
+    /// Video and audio elements and video player embeds
+    #[serde(default)]
+    pub media: Vec<MediaElement>,
+    /// Images and iframes lazy loaded above the fold or eager below it
+    #[serde(default)]
+    pub lazy_loading: LazyLoadingAudit,
+    /// External scripts and stylesheets with their sizes and cache headers
+    #[serde(default)]
+    pub assets: PageAssets,
+    /// Web fonts declared in the page's CSS and font preloads
+    #[serde(default)]
+    pub fonts: FontAudit,
     pub status_code: u16,
+    pub blocked_by_robots: bool,
+    /// Clicks from the start URL when the page was first discovered
+    #[serde(default)]
+    pub crawl_depth: Option<usize>,
+    /// Whether the page changed since the previous crawl, None the first time it is seen
+    #[serde(default)]
+    pub changed_since_last_crawl: Option<bool>,
     pub anchor_links: Option<InternalExternalLinks>,
     pub inoutlinks_status_codes: LinkCheckResults,
     pub indexability: Indexability,
     pub alt_tags: AltTags,
     pub schema: Option<String>,
+    pub structured_data: StructuredData,
+    pub social_tags: SocialTags,
     pub css: CSS,
     pub iframe: Option<Iframe>,
     pub word_count: usize,
+    #[serde(default)]
+    pub content: ContentAnalysis,
     pub response_time: Option<f64>, // Response time in seconds
     pub mobile: bool,
     pub canonicals: Option<Vec<String>>,
+    pub canonical: CanonicalAudit,
+    /// Whether the page is AMP and the AMP version it links to
+    #[serde(default)]
+    pub amp: AmpInfo,
+    #[serde(default)]
+    pub pagination: PaginationInfo,
     pub meta_robots: MetaRobots,
     pub content_type: String,
     pub content_length: usize,
     pub text_ratio: Option<Vec<TextRatio>>,
     pub redirection: Option<String>,
+    #[serde(default)]
+    pub redirect_chain: Vec<RedirectHop>,
+    /// The HTML as served and after JavaScript ran, only kept when rendering is on
+    #[serde(default)]
+    pub raw_html: Option<String>,
+    #[serde(default)]
+    pub rendered_html: Option<String>,
+    #[serde(default)]
+    pub render_diff: Option<RenderDiff>,
     pub keywords: Vec<(String, usize)>,
+    /// Term and phrase counts of the main content, weighed across the crawl
+    #[serde(default)]
+    pub term_counts: TermCounts,
     pub page_size: Vec<Sizes>,
     pub hreflangs: Option<Vec<HreflangObject>>,
     pub language: Option<String>,
+    /// The detected language of the text against `<html lang>` and hreflang
+    #[serde(default)]
+    pub language_audit: LanguageAudit,
     pub flesch: Result<(f64, String), String>,
+    /// Reading ease and grade levels of the main content
+    #[serde(default)]
+    pub readability: Option<Readability>,
+    /// Misspelled words with where they appear, None when no dictionary covers the page
+    #[serde(default)]
+    pub spelling: Option<SpellingAudit>,
+    /// WCAG-mapped issues found in the markup
+    #[serde(default)]
+    pub accessibility: A11yAudit,
     pub extractor: Extractor,
+    /// Values of the user's extraction rules, by rule name
+    #[serde(default)]
+    pub custom_extraction: BTreeMap<String, Vec<String>>,
+    /// Occurrences of the user's search patterns, by pattern name
+    #[serde(default)]
+    pub custom_search: BTreeMap<String, usize>,
+    /// Analytics, marketing and consent tags loaded by the page
+    #[serde(default)]
+    pub tracking: Vec<TrackingTag>,
     pub headers: Vec<(String, String)>,
     pub pdf_files: Vec<String>,
+    pub pdf_audits: Vec<PdfAudit>,
     pub https: bool,
     pub cross_origin: SecuritySummary,
+    #[serde(default)]
+    pub security_headers: SecurityHeadersAudit,
+    #[serde(default)]
+    pub transfer: TransferDiagnostics,
+    /// DNS, connect, TLS, first byte and download times, only with `waterfall_timing` on
+    #[serde(default)]
+    pub waterfall: Option<Waterfall>,
     pub psi_results: Result<Vec<Value>, String>,
+    /// Core Web Vitals measured in the renderer, for crawls without a PageSpeed key
+    #[serde(default)]
+    pub lab_vitals: Option<LabVitals>,
 }
 
 // Implement Default for DomainCrawlResults
@@ -81,10 +193,21 @@ impl Default for DomainCrawlResults {
             url: String::new(),
             title: None,
             description: String::new(),
+            title_description: TitleDescriptionAudit::default(),
             headings: HashMap::new(),
+            heading_outline: HeadingOutline::default(),
             javascript: JavaScript::default(),
             images: Ok(Vec::new()),
+            image_candidates: Vec::new(),
+            image_dimensions: Vec::new(),
+            media: Vec::new(),
+            lazy_loading: LazyLoadingAudit::default(),
+            assets: PageAssets::default(),
+            fonts: FontAudit::default(),
             status_code: 0, // Default to 0 for failed URLs
+            blocked_by_robots: false,
+            crawl_depth: None,
+            changed_since_last_crawl: None,
             anchor_links: None,
             inoutlinks_status_codes: LinkCheckResults {
                 page: String::new(),
@@ -95,25 +218,44 @@ impl Default for DomainCrawlResults {
             indexability: Indexability::default(),
             alt_tags: AltTags::default(),
             schema: None,
+            structured_data: StructuredData::default(),
+            social_tags: SocialTags::default(),
             css: CSS::default(),
             iframe: None,
             word_count: 0,
+            content: ContentAnalysis::default(),
             response_time: None,
             mobile: false,
             canonicals: None,
+            canonical: CanonicalAudit::default(),
+            amp: AmpInfo::default(),
+            pagination: PaginationInfo::default(),
             meta_robots: MetaRobots::default(),
             content_type: String::new(),
             content_length: 0,
             text_ratio: None,
             redirection: None,
+            redirect_chain: Vec::new(),
+            raw_html: None,
+            rendered_html: None,
+            render_diff: None,
             keywords: Vec::new(),
+            term_counts: TermCounts::default(),
             page_size: Vec::new(),
             hreflangs: None,
             language: None,
+            language_audit: LanguageAudit::default(),
             flesch: Ok((0.0, String::new())),
+            readability: None,
+            spelling: None,
+            accessibility: A11yAudit::default(),
             extractor: Extractor::default(),
+            custom_extraction: BTreeMap::new(),
+            custom_search: BTreeMap::new(),
+            tracking: Vec::new(),
             headers: Vec::new(),
             pdf_files: Vec::new(),
+            pdf_audits: Vec::new(),
             https: false,
             cross_origin: SecuritySummary {
                 total_unsafe_anchors: 0,
@@ -122,7 +264,23 @@ impl Default for DomainCrawlResults {
                 total_missing_cors: 0,
                 total_inline_scripts: 0,
             },
+            security_headers: SecurityHeadersAudit::default(),
+            transfer: TransferDiagnostics::default(),
+            waterfall: None,
             psi_results: Ok(Vec::new()),
+            lab_vitals: None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl DomainCrawlResults {
+    /// A page at `url` that answered 200, for tests to fill in the fields they check.
+    pub fn crawled(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            status_code: 200,
+            ..Default::default()
         }
     }
 }
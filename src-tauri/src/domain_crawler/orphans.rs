@@ -0,0 +1,163 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::helpers::canonical_selector::normalise_url;
+use super::results_store::ResultsStore;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OrphanSourceKind {
+    Sitemap,
+    SearchConsole,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanSource {
+    pub kind: OrphanSourceKind,
+    /// The sitemap file, or the Search Console impressions of the page
+    pub detail: Option<String>,
+}
+
+/// A URL known to exist that no crawled page links to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanPage {
+    pub url: String,
+    /// None when the crawler never fetched the URL
+    pub status_code: Option<u16>,
+    pub sources: Vec<OrphanSource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OrphanReport {
+    pub crawl_id: i64,
+    pub linked_urls: usize,
+    pub sitemap_urls: usize,
+    pub gsc_urls: usize,
+    pub orphans: Vec<OrphanPage>,
+}
+
+fn key(url: &str) -> Option<String> {
+    Url::parse(url).ok().map(|url| normalise_url(&url))
+}
+
+/// Finds URLs reachable only through the sitemaps or Search Console.
+///
+/// # Arguments
+/// * `start` - The start URL of the crawl, never an orphan.
+/// * `pages` - The crawled (url, status code) pairs.
+/// * `links` - The internal (source, target) links found by the crawler.
+/// * `sitemap_urls` - The (url, sitemap) pairs listed in the sitemaps.
+/// * `gsc_pages` - The Search Console pages with their impressions.
+pub fn find_orphans(
+    crawl_id: i64,
+    start: &str,
+    pages: &[(String, u16)],
+    links: &[(String, String)],
+    sitemap_urls: &[(String, String)],
+    gsc_pages: &[(String, f64)],
+) -> OrphanReport {
+    let linked: HashSet<String> = links
+        .iter()
+        .filter(|(source, target)| source != target)
+        .filter_map(|(_, target)| key(target))
+        .collect();
+    let start = key(start);
+
+    let mut orphans: BTreeMap<String, OrphanPage> = BTreeMap::new();
+    let mut add = |url: &str, source: OrphanSource| {
+        let Some(k) = key(url) else {
+            return;
+        };
+        if linked.contains(&k) || start.as_ref() == Some(&k) {
+            return;
+        }
+        let orphan = orphans.entry(k).or_insert_with(|| OrphanPage {
+            url: url.to_string(),
+            status_code: None,
+            sources: Vec::new(),
+        });
+        if !orphan.sources.iter().any(|s| s.kind == source.kind) {
+            orphan.sources.push(source);
+        }
+    };
+
+    for (url, sitemap) in sitemap_urls {
+        add(
+            url,
+            OrphanSource {
+                kind: OrphanSourceKind::Sitemap,
+                detail: Some(sitemap.clone()),
+            },
+        );
+    }
+    for (url, impressions) in gsc_pages {
+        add(
+            url,
+            OrphanSource {
+                kind: OrphanSourceKind::SearchConsole,
+                detail: Some(format!("{} impressions", impressions)),
+            },
+        );
+    }
+    let statuses: HashMap<String, u16> = pages
+        .iter()
+        .filter_map(|(url, status)| key(url).map(|k| (k, *status)))
+        .collect();
+
+    let orphans = orphans
+        .into_iter()
+        .map(|(k, mut orphan)| {
+            orphan.status_code = statuses.get(&k).copied();
+            orphan
+        })
+        .collect();
+
+    OrphanReport {
+        crawl_id,
+        linked_urls: linked.len(),
+        sitemap_urls: sitemap_urls.len(),
+        gsc_urls: gsc_pages.len(),
+        orphans,
+    }
+}
+
+// FIND THE ORPHAN PAGES OF A STORED CRAWL
+#[tauri::command]
+pub async fn get_orphan_pages(crawl_id: i64) -> Result<OrphanReport, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let crawl = store
+        .crawl(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Crawl {} not found", crawl_id))?;
+
+    let pages: Vec<(String, u16)> = store
+        .page_rows(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|page| (page.url, page.status_code))
+        .collect();
+    let links = store.links(crawl_id).await.map_err(|e| e.to_string())?;
+    let sitemap_urls = store
+        .sitemap_urls(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Search Console rows are per page and query, sum them per page
+    let mut impressions: BTreeMap<String, f64> = BTreeMap::new();
+    for row in store.gsc_rows(crawl_id).await.map_err(|e| e.to_string())? {
+        *impressions.entry(row.page).or_insert(0.0) += row.impressions;
+    }
+    let gsc_pages: Vec<(String, f64)> = impressions.into_iter().collect();
+
+    Ok(find_orphans(
+        crawl_id,
+        &crawl.domain,
+        &pages,
+        &links,
+        &sitemap_urls,
+        &gsc_pages,
+    ))
+}
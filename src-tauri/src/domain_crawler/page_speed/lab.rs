@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::time::Duration;
+use url::Url;
+
+use crate::domain_crawler::renderer;
+
+// Late images and web fonts still move LCP and CLS after the load event
+const MAX_SETTLE: Duration = Duration::from_secs(5);
+const LOAD_TIMEOUT: Duration = Duration::from_secs(45);
+
+// Records the entries as the page loads, buffered entries cover what happened before
+const OBSERVER_SCRIPT: &str = r#"(() => {
+  const vitals = window.__rustyseoVitals = { lcp: null, fcp: null, cls: 0, tasks: [] };
+  const observe = (type, callback) => {
+    try {
+      new PerformanceObserver((list) => list.getEntries().forEach(callback))
+        .observe({ type, buffered: true });
+    } catch (e) {}
+  };
+  observe('largest-contentful-paint', (entry) => {
+    vitals.lcp = entry.renderTime || entry.loadTime || entry.startTime;
+  });
+  observe('paint', (entry) => {
+    if (entry.name === 'first-contentful-paint') vitals.fcp = entry.startTime;
+  });
+  let session = 0, first = 0, last = 0;
+  observe('layout-shift', (entry) => {
+    if (entry.hadRecentInput) return;
+    if (session && entry.startTime - last < 1000 && entry.startTime - first < 5000) {
+      session += entry.value;
+    } else {
+      session = entry.value;
+      first = entry.startTime;
+    }
+    last = entry.startTime;
+    vitals.cls = Math.max(vitals.cls, session);
+  });
+  observe('longtask', (entry) => {
+    vitals.tasks.push({ start: entry.startTime, duration: entry.duration });
+  });
+})();"#;
+
+const COLLECT_SCRIPT: &str = r#"JSON.stringify((() => {
+  const vitals = window.__rustyseoVitals || { tasks: [] };
+  const navigation = performance.getEntriesByType('navigation')[0];
+  const fcp = vitals.fcp || 0;
+  const blocking = vitals.tasks
+    .filter((task) => task.start >= fcp)
+    .reduce((total, task) => total + Math.max(0, task.duration - 50), 0);
+  return {
+    lcp_ms: vitals.lcp,
+    fcp_ms: vitals.fcp,
+    cls: vitals.cls,
+    tbt_ms: blocking,
+    long_tasks: vitals.tasks.length,
+    ttfb_ms: navigation ? navigation.responseStart : null,
+    load_ms: navigation && navigation.loadEventEnd > 0 ? navigation.loadEventEnd : null,
+  };
+})())"#;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum VitalRating {
+    Good,
+    NeedsImprovement,
+    Poor,
+}
+
+impl VitalRating {
+    fn rate(value: f64, good: f64, poor: f64) -> Self {
+        if value <= good {
+            VitalRating::Good
+        } else if value <= poor {
+            VitalRating::NeedsImprovement
+        } else {
+            VitalRating::Poor
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Measurement {
+    lcp_ms: Option<f64>,
+    fcp_ms: Option<f64>,
+    cls: Option<f64>,
+    tbt_ms: Option<f64>,
+    #[serde(default)]
+    long_tasks: usize,
+    ttfb_ms: Option<f64>,
+    load_ms: Option<f64>,
+}
+
+/// Core Web Vitals measured in the crawl's own browser, without network or CPU throttling.
+///
+/// Interaction to Next Paint needs real input, Total Blocking Time stands in for it the way
+/// Lighthouse does.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LabVitals {
+    pub lcp_ms: Option<f64>,
+    pub fcp_ms: Option<f64>,
+    pub cls: Option<f64>,
+    pub tbt_ms: Option<f64>,
+    pub long_tasks: usize,
+    pub ttfb_ms: Option<f64>,
+    pub load_ms: Option<f64>,
+    /// Ratings use the Core Web Vitals thresholds, and Lighthouse's for TBT
+    pub lcp_rating: Option<VitalRating>,
+    pub cls_rating: Option<VitalRating>,
+    pub tbt_rating: Option<VitalRating>,
+}
+
+fn round(value: Option<f64>, decimals: i32) -> Option<f64> {
+    let factor = 10f64.powi(decimals);
+    value.map(|v| (v * factor).round() / factor)
+}
+
+/// Loads `url` in a fresh tab and reads its paint, layout shift and long task entries.
+///
+/// Returns `None` when rendering is off for this crawl.
+pub async fn measure(url: &Url) -> Option<Result<LabVitals, String>> {
+    let devtools = renderer::devtools().await?;
+    Some(
+        async {
+            let mut devtools = devtools?;
+            devtools.call("Page.enable", json!({})).await?;
+            devtools
+                .call(
+                    "Page.addScriptToEvaluateOnNewDocument",
+                    json!({ "source": OBSERVER_SCRIPT }),
+                )
+                .await?;
+            if !renderer::navigate(&mut devtools, url, LOAD_TIMEOUT).await? {
+                return Err(format!("{} did not finish loading", url));
+            }
+            let settle = renderer::budget().unwrap_or(MAX_SETTLE).min(MAX_SETTLE);
+            renderer::pump(&mut devtools, None, settle).await?;
+
+            let result = devtools
+                .call(
+                    "Runtime.evaluate",
+                    json!({ "expression": COLLECT_SCRIPT, "returnByValue": true }),
+                )
+                .await?;
+            let measurement: Measurement = result
+                .pointer("/result/value")
+                .and_then(Value::as_str)
+                .and_then(|value| serde_json::from_str(value).ok())
+                .ok_or_else(|| format!("Failed to read the performance entries of {}", url))?;
+
+            Ok(LabVitals {
+                lcp_rating: measurement
+                    .lcp_ms
+                    .map(|lcp| VitalRating::rate(lcp, 2500.0, 4000.0)),
+                cls_rating: measurement.cls.map(|cls| VitalRating::rate(cls, 0.1, 0.25)),
+                tbt_rating: measurement
+                    .tbt_ms
+                    .map(|tbt| VitalRating::rate(tbt, 200.0, 600.0)),
+                lcp_ms: round(measurement.lcp_ms, 0),
+                fcp_ms: round(measurement.fcp_ms, 0),
+                cls: round(measurement.cls, 3),
+                tbt_ms: round(measurement.tbt_ms, 0),
+                long_tasks: measurement.long_tasks,
+                ttfb_ms: round(measurement.ttfb_ms, 0),
+                load_ms: round(measurement.load_ms, 0),
+            })
+        }
+        .await,
+    )
+}
@@ -1,3 +1,5 @@
 pub mod bulk;
+pub mod lab;
 pub mod model;
+pub mod psi;
 pub mod store_key;
@@ -0,0 +1,315 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Semaphore;
+use tokio::time::Duration;
+use url::Url;
+
+use crate::domain_crawler::rate_limiter::HostRateLimiter;
+use crate::domain_crawler::results_store::ResultsStore;
+use crate::AppState;
+
+const PSI_ENDPOINT: &str = "https://www.googleapis.com/pagespeedonline/v5/runPagespeed";
+const CATEGORIES: [&str; 4] = ["performance", "accessibility", "best-practices", "seo"];
+
+// Scores younger than this are reused instead of calling the API again
+const CACHE_TTL_HOURS: i64 = 24;
+const MAX_PARALLEL_REQUESTS: usize = 4;
+const DEFAULT_TOP_N: usize = 20;
+
+static PSI_CLIENT: Lazy<Client> = Lazy::new(|| {
+    Client::builder()
+        .timeout(Duration::from_secs(90))
+        .build()
+        .unwrap_or_default()
+});
+
+// The PSI API allows a few hundred requests per minute per key
+static PSI_LIMITER: Lazy<HostRateLimiter> =
+    Lazy::new(|| HostRateLimiter::new(Duration::from_millis(300), 4));
+
+/// Core Web Vitals and Lighthouse category scores of one URL and strategy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PsiScores {
+    pub url: String,
+    pub strategy: String,
+    pub performance: Option<f64>,
+    pub accessibility: Option<f64>,
+    pub best_practices: Option<f64>,
+    pub seo: Option<f64>,
+    /// Lab metrics from the Lighthouse run
+    pub lcp_ms: Option<f64>,
+    pub fcp_ms: Option<f64>,
+    pub tbt_ms: Option<f64>,
+    pub cls: Option<f64>,
+    pub ttfb_ms: Option<f64>,
+    /// 75th percentile field metrics from the Chrome UX Report, when Google has them
+    pub field_lcp_ms: Option<f64>,
+    pub field_inp_ms: Option<f64>,
+    pub field_cls: Option<f64>,
+    pub field_category: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+    pub error: Option<String>,
+}
+
+fn category_score(lighthouse: &Value, category: &str) -> Option<f64> {
+    lighthouse
+        .pointer(&format!("/categories/{}/score", category))
+        .and_then(Value::as_f64)
+        .map(|score| (score * 100.0).round())
+}
+
+fn audit_value(lighthouse: &Value, audit: &str) -> Option<f64> {
+    lighthouse
+        .pointer(&format!("/audits/{}/numericValue", audit))
+        .and_then(Value::as_f64)
+}
+
+fn field_percentile(experience: &Value, metric: &str) -> Option<f64> {
+    experience
+        .pointer(&format!("/metrics/{}/percentile", metric))
+        .and_then(Value::as_f64)
+}
+
+/// Pulls the scores out of a PageSpeed Insights v5 response.
+pub fn parse_psi_response(url: &str, strategy: &str, value: &Value) -> PsiScores {
+    let mut scores = PsiScores {
+        url: url.to_string(),
+        strategy: strategy.to_string(),
+        fetched_at: Utc::now(),
+        ..Default::default()
+    };
+
+    if let Some(lighthouse) = value.get("lighthouseResult") {
+        scores.performance = category_score(lighthouse, "performance");
+        scores.accessibility = category_score(lighthouse, "accessibility");
+        scores.best_practices = category_score(lighthouse, "best-practices");
+        scores.seo = category_score(lighthouse, "seo");
+        scores.lcp_ms = audit_value(lighthouse, "largest-contentful-paint");
+        scores.fcp_ms = audit_value(lighthouse, "first-contentful-paint");
+        scores.tbt_ms = audit_value(lighthouse, "total-blocking-time");
+        scores.cls = audit_value(lighthouse, "cumulative-layout-shift");
+        scores.ttfb_ms = audit_value(lighthouse, "server-response-time");
+    }
+
+    if let Some(experience) = value.get("loadingExperience") {
+        scores.field_lcp_ms = field_percentile(experience, "LARGEST_CONTENTFUL_PAINT_MS");
+        scores.field_inp_ms = field_percentile(experience, "INTERACTION_TO_NEXT_PAINT");
+        // CrUX reports CLS multiplied by 100
+        scores.field_cls =
+            field_percentile(experience, "CUMULATIVE_LAYOUT_SHIFT_SCORE").map(|cls| cls / 100.0);
+        scores.field_category = experience
+            .get("overall_category")
+            .and_then(Value::as_str)
+            .map(String::from);
+    }
+
+    scores
+}
+
+async fn fetch_scores(url: &str, strategy: &str, api_key: &str) -> PsiScores {
+    let endpoint = Url::parse(PSI_ENDPOINT).expect("valid PSI endpoint");
+    PSI_LIMITER.acquire(&endpoint).await;
+
+    let mut query: Vec<(&str, &str)> = vec![("url", url), ("strategy", strategy), ("key", api_key)];
+    query.extend(CATEGORIES.iter().map(|category| ("category", *category)));
+
+    let result: Result<Value, String> = async {
+        let response = PSI_CLIENT
+            .get(PSI_ENDPOINT)
+            .query(&query)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+        let value: Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        match value.get("error") {
+            Some(error) => Err(format!("API error: {}", error)),
+            None => Ok(value),
+        }
+    }
+    .await;
+
+    match result {
+        Ok(value) => parse_psi_response(url, strategy, &value),
+        Err(error) => PsiScores {
+            url: url.to_string(),
+            strategy: strategy.to_string(),
+            fetched_at: Utc::now(),
+            error: Some(error),
+            ..Default::default()
+        },
+    }
+}
+
+/// Picks the pages worth testing when no URLs are given: successful HTML pages, shallowest first.
+async fn top_urls(
+    store: &ResultsStore,
+    crawl_id: i64,
+    top_n: usize,
+) -> Result<Vec<String>, String> {
+    let mut rows = store
+        .page_rows(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|row| row.status_code == 200 && row.content_type.contains("html"))
+        .collect::<Vec<_>>();
+
+    rows.sort_by_key(|row| {
+        let depth = Url::parse(&row.url)
+            .map(|url| {
+                url.path_segments()
+                    .map_or(0, |s| s.filter(|p| !p.is_empty()).count())
+            })
+            .unwrap_or(usize::MAX);
+        (depth, row.url.len())
+    });
+
+    Ok(rows.into_iter().take(top_n).map(|row| row.url).collect())
+}
+
+/// Runs PageSpeed Insights for the given URLs, reusing cached scores, and stores
+/// the results with the crawl.
+pub async fn run_psi(
+    crawl_id: i64,
+    urls: Vec<String>,
+    strategies: Vec<String>,
+    api_key: String,
+) -> Result<Vec<PsiScores>, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let max_age = ChronoDuration::hours(CACHE_TTL_HOURS);
+    let permits = Arc::new(Semaphore::new(MAX_PARALLEL_REQUESTS));
+    let api_key = Arc::new(api_key);
+
+    let jobs = urls.iter().flat_map(|url| {
+        strategies.iter().map(|strategy| {
+            let store = store.clone();
+            let permits = permits.clone();
+            let api_key = api_key.clone();
+            let url = url.clone();
+            let strategy = strategy.clone();
+            async move {
+                if let Ok(Some(cached)) = store.cached_psi(&url, &strategy, max_age).await {
+                    return cached;
+                }
+                let _permit = permits.acquire().await.expect("semaphore closed");
+                fetch_scores(&url, &strategy, &api_key).await
+            }
+        })
+    });
+
+    let scores = join_all(jobs).await;
+    store
+        .insert_psi(crawl_id, &scores)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(scores)
+}
+
+// RUN PAGESPEED INSIGHTS ON SELECTED OR TOP N PAGES OF A STORED CRAWL
+#[tauri::command]
+pub async fn run_psi_for_crawl(
+    crawl_id: i64,
+    urls: Option<Vec<String>>,
+    top_n: Option<usize>,
+    strategies: Option<Vec<String>>,
+    api_key: Option<String>,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<Vec<PsiScores>, String> {
+    let api_key = match api_key {
+        Some(key) => key,
+        None => settings_state
+            .settings
+            .read()
+            .await
+            .page_speed_bulk_api_key
+            .clone()
+            .flatten()
+            .ok_or("No PSI API key configured")?,
+    };
+
+    let urls = match urls {
+        Some(urls) if !urls.is_empty() => urls,
+        _ => {
+            let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+            top_urls(&store, crawl_id, top_n.unwrap_or(DEFAULT_TOP_N)).await?
+        }
+    };
+
+    let strategies = strategies
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| vec!["mobile".to_string(), "desktop".to_string()]);
+    if let Some(bad) = strategies
+        .iter()
+        .find(|s| s.as_str() != "mobile" && s.as_str() != "desktop")
+    {
+        return Err(format!("Unknown PSI strategy: {}", bad));
+    }
+
+    run_psi(crawl_id, urls, strategies, api_key).await
+}
+
+// GET THE PAGESPEED SCORES STORED FOR A CRAWL
+#[tauri::command]
+pub async fn get_psi_scores(crawl_id: i64) -> Result<Vec<PsiScores>, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    store.psi_scores(crawl_id).await.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn lab_and_field_metrics_are_read() {
+        let response = json!({
+            "lighthouseResult": {
+                "categories": {
+                    "performance": { "score": 0.874 },
+                    "seo": { "score": 1.0 }
+                },
+                "audits": {
+                    "largest-contentful-paint": { "numericValue": 2450.5 },
+                    "cumulative-layout-shift": { "numericValue": 0.08 }
+                }
+            },
+            "loadingExperience": {
+                "overall_category": "AVERAGE",
+                "metrics": {
+                    "INTERACTION_TO_NEXT_PAINT": { "percentile": 210 },
+                    "CUMULATIVE_LAYOUT_SHIFT_SCORE": { "percentile": 12 }
+                }
+            }
+        });
+
+        let scores = parse_psi_response("https://example.com/", "mobile", &response);
+        assert_eq!(scores.strategy, "mobile");
+        assert_eq!(scores.performance, Some(87.0));
+        assert_eq!(scores.seo, Some(100.0));
+        assert_eq!(scores.accessibility, None);
+        assert_eq!(scores.lcp_ms, Some(2450.5));
+        assert_eq!(scores.cls, Some(0.08));
+        assert_eq!(scores.field_inp_ms, Some(210.0));
+        assert_eq!(scores.field_cls, Some(0.12));
+        assert_eq!(scores.field_category.as_deref(), Some("AVERAGE"));
+    }
+
+    #[test]
+    fn pages_without_field_data_only_have_lab_metrics() {
+        let response = json!({ "lighthouseResult": { "categories": {}, "audits": {} } });
+        let scores = parse_psi_response("https://example.com/", "desktop", &response);
+        assert_eq!(scores.field_lcp_ms, None);
+        assert_eq!(scores.field_category, None);
+        assert_eq!(scores.error, None);
+    }
+}
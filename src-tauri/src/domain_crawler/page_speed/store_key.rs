@@ -8,10 +8,8 @@ use crate::settings;
 #[tauri::command]
 pub async fn read_page_speed_bulk_api_key() -> Result<(), String> {
     // Get the config file path
-    let file_path: PathBuf = ProjectDirs::from("", "", "rustyseo")
-        .ok_or("Failed to get config directory")?
-        .config_dir()
-        .join("api_keys.toml");
+    let dirs = ProjectDirs::from("", "", "rustyseo").ok_or("Failed to get config directory")?;
+    let file_path: PathBuf = crate::projects::registry::config_dir(&dirs).join("api_keys.toml");
 
     // Read and parse the file
     let file_content = fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
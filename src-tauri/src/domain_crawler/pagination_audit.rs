@@ -0,0 +1,311 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::canonical_audit::is_noindex;
+use super::helpers::canonical_selector::{normalise_url, same_url};
+use super::helpers::pagination_selector::{first_page_url, page_number, PaginationInfo};
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+// Numbers past this span of a series are not listed as missing, `?page=99999` is a stray link
+const MAX_SERIES_SPAN: u32 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum PaginationIssue {
+    /// A prev/next link to an error page or one that does not link back, or a gap in the numbers
+    BrokenSequence,
+    /// A later page canonicalizes to the first one, hiding the items it lists
+    CanonicalToFirstPage,
+    /// A later page is noindexed
+    Noindexed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaginationWarning {
+    pub url: String,
+    pub issue: PaginationIssue,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaginatedPage {
+    pub url: String,
+    pub page: u32,
+}
+
+/// The crawled pages of one paginated listing, in page order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaginationSeries {
+    pub first_url: String,
+    pub pages: Vec<PaginatedPage>,
+    /// Numbers between the first and last crawled page that were not found, leaving out
+    /// pages the crawl limits stopped at
+    pub missing_pages: Vec<u32>,
+    /// The series declares rel=prev/next, not only numbered URLs
+    pub uses_rel_links: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PaginationReport {
+    pub paginated_pages: usize,
+    pub series: Vec<PaginationSeries>,
+    pub warnings: Vec<PaginationWarning>,
+}
+
+pub fn register_issues(report: &PaginationReport, issues: &mut IssueRegistry) {
+    for warning in &report.warnings {
+        let kind = match warning.issue {
+            PaginationIssue::BrokenSequence => IssueKind::PaginationBrokenSequence,
+            PaginationIssue::CanonicalToFirstPage => IssueKind::PaginationCanonicalToFirst,
+            PaginationIssue::Noindexed => IssueKind::NoindexPagination,
+        };
+        issues.flag(kind, &warning.url);
+    }
+}
+
+impl StoredReport for PaginationReport {
+    const KIND: &'static str = "pagination";
+}
+
+fn points_to(link: Option<&str>, target: &Url) -> bool {
+    link.and_then(|link| Url::parse(link).ok())
+        .is_some_and(|link| same_url(&link, target))
+}
+
+// What the checks need of a page, as part of a series or as the target of a prev/next link
+struct PaginationPage {
+    url: String,
+    status_code: u16,
+    pagination: PaginationInfo,
+    canonical: Option<String>,
+    noindex: bool,
+}
+
+/// Collects the crawled pages one at a time for the pagination checks.
+#[derive(Default)]
+pub struct PaginationCollector {
+    pages: Vec<PaginationPage>,
+    by_url: HashMap<String, usize>,
+}
+
+impl PaginationCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        if let Ok(url) = Url::parse(&result.url) {
+            self.by_url.insert(normalise_url(&url), self.pages.len());
+        }
+        self.pages.push(PaginationPage {
+            url: result.url.clone(),
+            status_code: result.status_code,
+            pagination: result.pagination.clone(),
+            canonical: result.canonical.resolved.clone(),
+            noindex: is_noindex(result),
+        });
+    }
+
+    /// Groups paginated pages into series and checks their links, canonicals and robots
+    /// directives.
+    ///
+    /// `limited` holds the paginated URLs the depth or page limit kept out of the crawl.
+    pub fn finish(self, limited: &HashSet<String>) -> PaginationReport {
+        let mut report = PaginationReport::default();
+        let mut series: BTreeMap<String, PaginationSeries> = BTreeMap::new();
+
+        for result in self
+            .pages
+            .iter()
+            .filter(|r| r.status_code == 200 && r.pagination.is_paginated())
+        {
+            let Ok(url) = Url::parse(&result.url) else {
+                continue;
+            };
+            let pagination = &result.pagination;
+            let page = pagination.page.unwrap_or(1);
+            let first = first_page_url(&url);
+            report.paginated_pages += 1;
+
+            let entry = series
+                .entry(normalise_url(&first))
+                .or_insert_with(|| PaginationSeries {
+                    first_url: first.to_string(),
+                    pages: Vec::new(),
+                    missing_pages: Vec::new(),
+                    uses_rel_links: false,
+                });
+            entry.pages.push(PaginatedPage {
+                url: result.url.clone(),
+                page,
+            });
+            entry.uses_rel_links |= pagination.prev.is_some() || pagination.next.is_some();
+
+            let mut warn = |issue: PaginationIssue, message: String| {
+                report.warnings.push(PaginationWarning {
+                    url: result.url.clone(),
+                    issue,
+                    message,
+                })
+            };
+
+            // Only targets that were crawled can be checked
+            for (rel, link, back) in [
+                ("next", &pagination.next, "prev"),
+                ("prev", &pagination.prev, "next"),
+            ] {
+                let Some(target_url) = link.as_deref().and_then(|link| Url::parse(link).ok())
+                else {
+                    continue;
+                };
+                if same_url(&target_url, &url) {
+                    warn(
+                        PaginationIssue::BrokenSequence,
+                        format!("rel={} points to the page itself", rel),
+                    );
+                    continue;
+                }
+                let Some(target) = self
+                    .by_url
+                    .get(&normalise_url(&target_url))
+                    .map(|&i| &self.pages[i])
+                else {
+                    continue;
+                };
+                if target.status_code != 200 {
+                    warn(
+                        PaginationIssue::BrokenSequence,
+                        format!("rel={} {} returned {}", rel, target.url, target.status_code),
+                    );
+                    continue;
+                }
+                let back_link = match back {
+                    "prev" => target.pagination.prev.as_deref(),
+                    _ => target.pagination.next.as_deref(),
+                };
+                if !points_to(back_link, &url) {
+                    warn(
+                        PaginationIssue::BrokenSequence,
+                        format!(
+                            "rel={} {} does not link back with rel={}",
+                            rel, target.url, back
+                        ),
+                    );
+                }
+            }
+
+            if page > 1 {
+                let to_first = result
+                    .canonical
+                    .as_deref()
+                    .and_then(|canonical| Url::parse(canonical).ok())
+                    .is_some_and(|canonical| {
+                        same_url(&first_page_url(&canonical), &first)
+                            && !matches!(page_number(&canonical), Some((n, _)) if n != 1)
+                    });
+                if to_first {
+                    warn(
+                        PaginationIssue::CanonicalToFirstPage,
+                        format!("Page {} canonicalizes to the first page", page),
+                    );
+                }
+            }
+
+            if (page > 1 || pagination.prev.is_some()) && result.noindex {
+                warn(
+                    PaginationIssue::Noindexed,
+                    format!("Page {} of the series is noindexed", page),
+                );
+            }
+        }
+
+        // Series and page numbers the crawl was not allowed to reach
+        let limited: HashSet<(String, u32)> = limited
+            .iter()
+            .filter_map(|url| Url::parse(url).ok())
+            .filter_map(|url| {
+                let (page, _) = page_number(&url)?;
+                Some((normalise_url(&first_page_url(&url)), page))
+            })
+            .collect();
+
+        for (key, entry) in series.iter_mut() {
+            entry
+                .pages
+                .sort_by(|a, b| a.page.cmp(&b.page).then(a.url.cmp(&b.url)));
+            let (Some(low), Some(high)) = (entry.pages.first(), entry.pages.last()) else {
+                continue;
+            };
+            let crawled: HashSet<u32> = entry.pages.iter().map(|p| p.page).collect();
+            let end = high.page.min(low.page.saturating_add(MAX_SERIES_SPAN));
+            entry.missing_pages = (low.page..end)
+                .filter(|n| !crawled.contains(n) && !limited.contains(&(key.clone(), *n)))
+                .collect();
+            if !entry.missing_pages.is_empty() {
+                let numbers: Vec<String> = entry.missing_pages.iter().map(u32::to_string).collect();
+                report.warnings.push(PaginationWarning {
+                    url: entry.first_url.clone(),
+                    issue: PaginationIssue::BrokenSequence,
+                    message: format!("Pages {} of the series were not found", numbers.join(", ")),
+                });
+            }
+        }
+
+        report.series = series.into_values().collect();
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn audit_pagination(
+        results: &[DomainCrawlResults],
+        limited: &HashSet<String>,
+    ) -> PaginationReport {
+        let mut collector = PaginationCollector::default();
+        for result in results {
+            collector.add(result);
+        }
+        collector.finish(limited)
+    }
+
+    fn page(url: &str) -> DomainCrawlResults {
+        let parsed = Url::parse(url).unwrap();
+        DomainCrawlResults {
+            pagination: PaginationInfo {
+                page: page_number(&parsed).map(|(page, _)| page),
+                ..Default::default()
+            },
+            ..DomainCrawlResults::crawled(url)
+        }
+    }
+
+    #[test]
+    fn pages_left_out_by_limits_are_not_gaps() {
+        let results = [
+            page("https://example.com/blog?page=1"),
+            page("https://example.com/blog?page=2"),
+            page("https://example.com/blog?page=5"),
+        ];
+        let limited = HashSet::from(["https://example.com/blog?page=4".to_string()]);
+
+        let report = audit_pagination(&results, &limited);
+        assert_eq!(report.series.len(), 1);
+        assert_eq!(report.series[0].missing_pages, [3]);
+    }
+
+    #[test]
+    fn stray_page_numbers_do_not_blow_up_the_gap_list() {
+        let results = [
+            page("https://example.com/blog?page=1"),
+            page("https://example.com/blog?page=4000000000"),
+        ];
+
+        let report = audit_pagination(&results, &HashSet::new());
+        assert_eq!(
+            report.series[0].missing_pages.len(),
+            MAX_SERIES_SPAN as usize - 1
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use futures::future::join_all;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+use trust_dns_resolver::proto::rr::{RData, RecordType};
+use trust_dns_resolver::TokioAsyncResolver;
+use url::Url;
+
+use super::helpers::canonical_selector::get_canonical;
+use super::proxies;
+use super::request_auth;
+use super::results_store::StoredReport;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DnsRecords {
+    pub host: String,
+    pub a: Vec<String>,
+    pub aaaa: Vec<String>,
+    pub cname: Vec<String>,
+    /// Set when the resolver itself failed, a missing record type is not an error
+    pub error: Option<String>,
+}
+
+/// How one of the http/https and www/non-www variants of the start URL answers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariantCheck {
+    pub url: String,
+    pub status_code: Option<u16>,
+    pub location: Option<String>,
+    pub canonical: Option<String>,
+    pub error: Option<String>,
+}
+
+impl VariantCheck {
+    fn serves(&self) -> bool {
+        self.status_code == Some(200)
+    }
+
+    fn redirects(&self) -> bool {
+        self.status_code.is_some_and(|c| (300..400).contains(&c)) && self.location.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum PreflightIssue {
+    Unresolvable,
+    HttpsUnavailable,
+    HttpNotRedirected,
+    DuplicateHostVariants,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightWarning {
+    pub issue: PreflightIssue,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PreflightReport {
+    pub dns: Vec<DnsRecords>,
+    pub variants: Vec<VariantCheck>,
+    pub warnings: Vec<PreflightWarning>,
+}
+
+impl StoredReport for PreflightReport {
+    const KIND: &'static str = "preflight";
+}
+
+async fn resolve(resolver: &TokioAsyncResolver, host: &str) -> DnsRecords {
+    let mut records = DnsRecords {
+        host: host.to_string(),
+        ..Default::default()
+    };
+    // The trailing dot stops the resolver from trying search domains
+    let fqdn = format!("{}.", host.trim_end_matches('.'));
+
+    match resolver.ipv4_lookup(fqdn.as_str()).await {
+        Ok(lookup) => records.a = lookup.iter().map(|ip| ip.to_string()).collect(),
+        Err(e) => records.error = Some(e.to_string()),
+    }
+    if let Ok(lookup) = resolver.ipv6_lookup(fqdn.as_str()).await {
+        records.aaaa = lookup.iter().map(|ip| ip.to_string()).collect();
+    }
+    if let Ok(lookup) = resolver.lookup(fqdn.as_str(), RecordType::CNAME).await {
+        records.cname = lookup
+            .iter()
+            .filter_map(|data| match data {
+                RData::CNAME(name) => Some(name.to_string()),
+                _ => None,
+            })
+            .collect();
+    }
+    if !records.aaaa.is_empty() || !records.cname.is_empty() {
+        records.error = None;
+    }
+    records
+}
+
+async fn check_variant(client: &Client, url: Url) -> VariantCheck {
+    let mut check = VariantCheck {
+        url: url.to_string(),
+        status_code: None,
+        location: None,
+        canonical: None,
+        error: None,
+    };
+    let response = match request_auth::apply(client.get(url.as_str()), url.as_str())
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            check.error = Some(e.to_string());
+            return check;
+        }
+    };
+
+    check.status_code = Some(response.status().as_u16());
+    check.location = response
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|location| url.join(location).ok())
+        .map(|location| location.to_string());
+    if check.serves() {
+        if let Ok(body) = response.text().await {
+            check.canonical = get_canonical(&body)
+                .and_then(|c| c.canonicals.into_iter().next())
+                .and_then(|canonical| url.join(&canonical).ok())
+                .map(|canonical| canonical.to_string());
+        }
+    }
+    check
+}
+
+fn variant_urls(base_url: &Url) -> Vec<Url> {
+    let Some(host) = base_url.host_str() else {
+        return Vec::new();
+    };
+    let bare = host.trim_start_matches("www.");
+    let www = format!("www.{}", bare);
+
+    let mut urls = Vec::new();
+    for scheme in ["https", "http"] {
+        for host in [bare, www.as_str()] {
+            if let Ok(url) = Url::parse(&format!("{}://{}/", scheme, host)) {
+                urls.push(url);
+            }
+        }
+    }
+    urls
+}
+
+fn host_of(url: &str) -> Option<String> {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(String::from))
+}
+
+fn find_warnings(dns: &[DnsRecords], variants: &[VariantCheck]) -> Vec<PreflightWarning> {
+    let mut warnings = Vec::new();
+    let warn = |issue: PreflightIssue, message: String| PreflightWarning { issue, message };
+
+    for records in dns {
+        if records.a.is_empty() && records.aaaa.is_empty() {
+            let reason = records.error.as_deref().unwrap_or("no A or AAAA records");
+            warnings.push(warn(
+                PreflightIssue::Unresolvable,
+                format!("{} does not resolve: {}", records.host, reason),
+            ));
+        }
+    }
+
+    let (https, http): (Vec<&VariantCheck>, Vec<&VariantCheck>) =
+        variants.iter().partition(|v| v.url.starts_with("https://"));
+
+    if !https.iter().any(|v| v.serves() || v.redirects()) {
+        warnings.push(warn(
+            PreflightIssue::HttpsUnavailable,
+            "Neither host variant answers over HTTPS".to_string(),
+        ));
+    }
+
+    for variant in http.iter().filter(|v| v.serves()) {
+        warnings.push(warn(
+            PreflightIssue::HttpNotRedirected,
+            format!(
+                "{} serves content over HTTP instead of redirecting to HTTPS",
+                variant.url
+            ),
+        ));
+    }
+
+    // www and non-www both answering 200 is duplicate content unless a canonical picks one
+    for scheme in [&https, &http] {
+        let serving: Vec<&&VariantCheck> = scheme.iter().filter(|v| v.serves()).collect();
+        if serving.len() < 2 {
+            continue;
+        }
+        let canonical_hosts: Vec<Option<String>> = serving
+            .iter()
+            .map(|v| v.canonical.as_deref().and_then(host_of))
+            .collect();
+        let agreed = canonical_hosts[0].is_some()
+            && canonical_hosts
+                .iter()
+                .all(|host| *host == canonical_hosts[0]);
+        if !agreed {
+            warnings.push(warn(
+                PreflightIssue::DuplicateHostVariants,
+                format!(
+                    "{} both return 200 without a canonical pointing to one of them",
+                    serving
+                        .iter()
+                        .map(|v| v.url.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" and ")
+                ),
+            ));
+        }
+    }
+    warnings
+}
+
+/// Resolves the start host and probes its http/https and www/non-www variants.
+///
+/// Results are only reported, a crawl through a proxy may reach hosts the local
+/// resolver does not know.
+pub async fn run(base_url: &Url, user_agent: &str) -> Result<PreflightReport, String> {
+    let client = proxies::apply(Client::builder())
+        .user_agent(user_agent)
+        .timeout(REQUEST_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let urls = variant_urls(base_url);
+    let mut hosts: Vec<String> = urls
+        .iter()
+        .filter_map(|url| url.host_str().map(String::from))
+        .collect();
+    hosts.sort();
+    hosts.dedup();
+
+    let dns = match TokioAsyncResolver::tokio_from_system_conf() {
+        Ok(resolver) => join_all(hosts.iter().map(|host| resolve(&resolver, host))).await,
+        Err(e) => hosts
+            .iter()
+            .map(|host| DnsRecords {
+                host: host.clone(),
+                error: Some(e.to_string()),
+                ..Default::default()
+            })
+            .collect(),
+    };
+    let variants = join_all(urls.into_iter().map(|url| check_variant(&client, url))).await;
+
+    Ok(PreflightReport {
+        warnings: find_warnings(&dns, &variants),
+        dns,
+        variants,
+    })
+}
@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+use url::Url;
+
+// Latency thresholds used to speed up or slow down a host
+const FAST_RESPONSE: Duration = Duration::from_millis(300);
+const SLOW_RESPONSE: Duration = Duration::from_secs(2);
+
+// Bounds for the adaptive per-host interval
+const MIN_BACKOFF_INTERVAL: Duration = Duration::from_millis(500);
+const MAX_INTERVAL: Duration = Duration::from_secs(30);
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+struct HostState {
+    tokens: f64,
+    last_refill: Instant,
+    interval: Duration,
+    /// Floor the interval never adapts below, from the host's robots.txt `Crawl-delay`
+    min_interval: Duration,
+    blocked_until: Option<Instant>,
+}
+
+/// Per-host token bucket: each host gets `burst` requests up front and then one
+/// request every `interval`.
+///
+/// The interval adapts to the host: it grows on 429/503 responses and slow
+/// responses, honours `Retry-After`, and shrinks again while the host answers fast.
+pub struct HostRateLimiter {
+    hosts: Mutex<HashMap<String, HostState>>,
+    interval: Duration,
+    burst: u32,
+}
+
+impl HostRateLimiter {
+    pub fn new(interval: Duration, burst: u32) -> Self {
+        Self {
+            hosts: Mutex::new(HashMap::new()),
+            interval,
+            burst: burst.max(1),
+        }
+    }
+
+    fn host_key(url: &Url) -> String {
+        url.host_str().unwrap_or("").to_string()
+    }
+
+    fn new_host(&self) -> HostState {
+        HostState {
+            tokens: self.burst as f64,
+            last_refill: Instant::now(),
+            interval: self.interval,
+            min_interval: Duration::ZERO,
+            blocked_until: None,
+        }
+    }
+
+    /// Spaces the requests to the host of `url` at least `delay` apart, without bursts.
+    pub async fn set_min_interval(&self, url: &Url, delay: Duration) {
+        let delay = delay.min(MAX_INTERVAL);
+        let mut hosts = self.hosts.lock().await;
+        let state = hosts
+            .entry(Self::host_key(url))
+            .or_insert_with(|| self.new_host());
+        if state.min_interval != delay {
+            state.min_interval = delay;
+            state.interval = state.interval.max(delay);
+            state.tokens = state.tokens.min(1.0);
+        }
+    }
+
+    /// Waits until the host of `url` has a token available and takes it.
+    pub async fn acquire(&self, url: &Url) {
+        let host = Self::host_key(url);
+
+        loop {
+            let wait = {
+                let mut hosts = self.hosts.lock().await;
+                let state = hosts.entry(host.clone()).or_insert_with(|| self.new_host());
+
+                match state.blocked_until {
+                    Some(until) if until > Instant::now() => until - Instant::now(),
+                    _ => {
+                        state.blocked_until = None;
+
+                        if state.interval.is_zero() {
+                            return;
+                        }
+
+                        // A crawl delay allows a single request per interval
+                        let burst = if state.min_interval.is_zero() {
+                            self.burst as f64
+                        } else {
+                            1.0
+                        };
+
+                        // Refill based on the time since the last request
+                        let elapsed = state.last_refill.elapsed();
+                        state.tokens = (state.tokens
+                            + elapsed.as_secs_f64() / state.interval.as_secs_f64())
+                        .min(burst);
+                        state.last_refill = Instant::now();
+
+                        if state.tokens >= 1.0 {
+                            state.tokens -= 1.0;
+                            return;
+                        }
+
+                        state.interval.mul_f64(1.0 - state.tokens)
+                    }
+                }
+            };
+
+            sleep(wait).await;
+        }
+    }
+
+    /// Feeds a response back into the scheduler so the host interval can adapt.
+    ///
+    /// Returns the `Retry-After` delay when the server asked for one.
+    pub async fn record_response(
+        &self,
+        url: &Url,
+        latency: Duration,
+        response: &reqwest::Response,
+    ) -> Option<Duration> {
+        self.record(url, latency, response.status(), response.headers())
+            .await
+    }
+
+    async fn record(
+        &self,
+        url: &Url,
+        latency: Duration,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Option<Duration> {
+        let retry_after = parse_retry_after(headers);
+
+        let mut hosts = self.hosts.lock().await;
+        let state = hosts
+            .entry(Self::host_key(url))
+            .or_insert_with(|| self.new_host());
+
+        if status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE {
+            state.interval = (state.interval * 2)
+                .clamp(MIN_BACKOFF_INTERVAL, MAX_INTERVAL)
+                .max(state.min_interval);
+            state.tokens = 0.0;
+            if let Some(delay) = retry_after {
+                state.blocked_until = Some(Instant::now() + delay);
+            }
+        } else if latency > SLOW_RESPONSE {
+            state.interval = state
+                .interval
+                .mul_f64(1.25)
+                .max(MIN_BACKOFF_INTERVAL)
+                .min(MAX_INTERVAL);
+        } else if latency < FAST_RESPONSE && status.is_success() {
+            // Fast hosts may go down to a quarter of the configured delay, never below the crawl delay
+            state.interval = state
+                .interval
+                .mul_f64(0.9)
+                .max(self.interval / 4)
+                .max(state.min_interval);
+        }
+
+        retry_after
+    }
+}
+
+/// Parses a `Retry-After` header given either in seconds or as an HTTP date.
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    let delay = match value.parse::<u64>() {
+        Ok(seconds) => Duration::from_secs(seconds),
+        Err(_) => {
+            let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+            (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                .to_std()
+                .unwrap_or_default()
+        }
+    };
+
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn url(host: &str) -> Url {
+        Url::parse(&format!("https://{}/", host)).unwrap()
+    }
+
+    fn retry_after(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    async fn interval_of(limiter: &HostRateLimiter, host: &str) -> Duration {
+        limiter.hosts.lock().await[host].interval
+    }
+
+    #[tokio::test]
+    async fn burst_is_free_then_requests_are_spaced() {
+        let limiter = HostRateLimiter::new(Duration::from_millis(200), 2);
+        let started = Instant::now();
+        limiter.acquire(&url("example.com")).await;
+        limiter.acquire(&url("example.com")).await;
+        assert!(started.elapsed() < Duration::from_millis(100));
+
+        limiter.acquire(&url("example.com")).await;
+        assert!(started.elapsed() >= Duration::from_millis(150));
+
+        // Other hosts have buckets of their own
+        let other = Instant::now();
+        limiter.acquire(&url("other.com")).await;
+        assert!(other.elapsed() < Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn retry_after_blocks_the_host() {
+        let limiter = HostRateLimiter::new(Duration::ZERO, 1);
+        let delay = limiter
+            .record(
+                &url("example.com"),
+                Duration::from_millis(50),
+                StatusCode::TOO_MANY_REQUESTS,
+                &retry_after("1"),
+            )
+            .await;
+        assert_eq!(delay, Some(Duration::from_secs(1)));
+
+        let started = Instant::now();
+        limiter.acquire(&url("example.com")).await;
+        assert!(started.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    async fn interval_adapts_to_the_host() {
+        let limiter = HostRateLimiter::new(Duration::from_millis(100), 1);
+        let host = url("example.com");
+        let record = |status, latency| {
+            let limiter = &limiter;
+            let host = &host;
+            async move {
+                limiter
+                    .record(host, latency, status, &HeaderMap::new())
+                    .await
+            }
+        };
+
+        // Throttling doubles the interval, never below the backoff floor
+        record(StatusCode::SERVICE_UNAVAILABLE, Duration::from_millis(50)).await;
+        assert_eq!(
+            interval_of(&limiter, "example.com").await,
+            MIN_BACKOFF_INTERVAL
+        );
+        record(StatusCode::TOO_MANY_REQUESTS, Duration::from_millis(50)).await;
+        assert_eq!(
+            interval_of(&limiter, "example.com").await,
+            MIN_BACKOFF_INTERVAL * 2
+        );
+
+        // Fast answers bring it down to a quarter of the configured interval at most
+        for _ in 0..100 {
+            record(StatusCode::OK, Duration::from_millis(10)).await;
+        }
+        assert_eq!(
+            interval_of(&limiter, "example.com").await,
+            Duration::from_millis(25)
+        );
+
+        // A crawl delay is a floor the fast answers cannot go under
+        limiter
+            .set_min_interval(&host, Duration::from_millis(80))
+            .await;
+        for _ in 0..10 {
+            record(StatusCode::OK, Duration::from_millis(10)).await;
+        }
+        assert_eq!(
+            interval_of(&limiter, "example.com").await,
+            Duration::from_millis(80)
+        );
+    }
+
+    #[test]
+    fn retry_after_is_capped() {
+        assert_eq!(
+            parse_retry_after(&retry_after("120")),
+            Some(MAX_RETRY_AFTER)
+        );
+        assert_eq!(
+            parse_retry_after(&retry_after("Wed, 21 Oct 2015 07:28:00 GMT")),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after(&retry_after("soon")), None);
+    }
+}
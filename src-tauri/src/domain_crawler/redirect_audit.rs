@@ -0,0 +1,125 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+/// One redirect response on the way from the requested URL to the final page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedirectHop {
+    pub url: String,
+    pub status_code: u16,
+    pub location: String,
+}
+
+impl RedirectHop {
+    pub fn target(&self) -> Option<Url> {
+        Url::parse(&self.url).ok()?.join(&self.location).ok()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedirectChain {
+    pub url: String,
+    pub hops: Vec<RedirectHop>,
+    pub final_url: Option<String>,
+    pub final_status: u16,
+    pub is_loop: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedirectReport {
+    /// Chains with more hops than this were reported as long
+    pub threshold: usize,
+    pub long_chains: Vec<RedirectChain>,
+    pub loops: Vec<RedirectChain>,
+    /// 302 and 307 hops that end on a working page, these usually should be 301s
+    pub temporary_redirects: Vec<RedirectHop>,
+    pub redirects_to_errors: Vec<RedirectChain>,
+}
+
+pub fn register_issues(report: &RedirectReport, issues: &mut IssueRegistry) {
+    for chain in &report.long_chains {
+        issues.flag(IssueKind::RedirectChain, &chain.url);
+    }
+    for chain in &report.loops {
+        issues.flag(IssueKind::RedirectLoop, &chain.url);
+    }
+    for hop in &report.temporary_redirects {
+        issues.flag(IssueKind::TemporaryRedirect, &hop.url);
+    }
+    for chain in &report.redirects_to_errors {
+        issues.flag(IssueKind::RedirectToError, &chain.url);
+    }
+}
+
+impl StoredReport for RedirectReport {
+    const KIND: &'static str = "redirects";
+}
+
+/// Whether the last hop points back at a URL already seen in the chain.
+pub fn is_loop(hops: &[RedirectHop]) -> bool {
+    let Some(target) = hops.last().and_then(RedirectHop::target) else {
+        return false;
+    };
+    let seen: HashSet<&str> = hops.iter().map(|hop| hop.url.as_str()).collect();
+    seen.contains(target.as_str())
+}
+
+/// Groups the redirect chains recorded during the crawl into the report categories, one page
+/// at a time.
+pub struct RedirectCollector {
+    report: RedirectReport,
+}
+
+impl RedirectCollector {
+    pub fn new(threshold: usize) -> Self {
+        Self {
+            report: RedirectReport {
+                threshold,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        if result.redirect_chain.is_empty() {
+            return;
+        }
+        let report = &mut self.report;
+        let hops = &result.redirect_chain;
+        let chain = RedirectChain {
+            url: result.url.clone(),
+            hops: hops.clone(),
+            final_url: hops.last().and_then(RedirectHop::target).map(String::from),
+            final_status: result.status_code,
+            is_loop: is_loop(hops),
+        };
+
+        if chain.is_loop {
+            report.loops.push(chain);
+            return;
+        }
+
+        if (200..300).contains(&chain.final_status) {
+            report.temporary_redirects.extend(
+                hops.iter()
+                    .filter(|hop| hop.status_code == 302 || hop.status_code == 307)
+                    .cloned(),
+            );
+        }
+        if chain.final_status >= 400 || chain.final_status == 0 {
+            report.redirects_to_errors.push(chain.clone());
+        }
+        if hops.len() > report.threshold {
+            report.long_chains.push(chain);
+        }
+    }
+
+    pub fn finish(self) -> RedirectReport {
+        self.report
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use super::helpers::render_diff::JsDependency;
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsDependentPage {
+    pub url: String,
+    pub dependencies: Vec<JsDependency>,
+    pub rendered_only_links: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RenderReport {
+    pub pages_rendered: usize,
+    /// Pages whose title, robots, canonical, links or structured data only appear after rendering
+    pub js_dependent: Vec<JsDependentPage>,
+}
+
+impl StoredReport for RenderReport {
+    const KIND: &'static str = "render";
+}
+
+/// Lists the rendered pages that rely on JavaScript for critical SEO elements.
+#[derive(Default)]
+pub struct RenderCollector {
+    report: RenderReport,
+}
+
+impl RenderCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        let Some(diff) = &result.render_diff else {
+            return;
+        };
+        self.report.pages_rendered += 1;
+
+        if !diff.js_dependencies.is_empty() {
+            self.report.js_dependent.push(JsDependentPage {
+                url: result.url.clone(),
+                dependencies: diff.js_dependencies.clone(),
+                rendered_only_links: diff.rendered_only_links.len(),
+            });
+        }
+    }
+
+    pub fn finish(self) -> RenderReport {
+        self.report
+    }
+}
@@ -0,0 +1,403 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+use base64::{engine::general_purpose, Engine};
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::time::{timeout, Duration, Instant};
+use url::Url;
+
+use super::cdp::DevTools;
+use super::proxies;
+use super::request_auth;
+use super::session;
+use crate::settings::settings::Settings;
+
+// Each render is a full browser process, keep only a few alive at once
+const MAX_CONCURRENT_RENDERS: usize = 4;
+
+#[cfg(target_os = "windows")]
+const CHROME_CANDIDATES: [&str; 4] = [
+    r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+    r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe",
+    r"C:\Program Files\Microsoft\Edge\Application\msedge.exe",
+    r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe",
+];
+#[cfg(target_os = "macos")]
+const CHROME_CANDIDATES: [&str; 3] = [
+    "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+    "/Applications/Chromium.app/Contents/MacOS/Chromium",
+    "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const CHROME_CANDIDATES: [&str; 4] = [
+    "google-chrome",
+    "google-chrome-stable",
+    "chromium",
+    "chromium-browser",
+];
+
+/// Headless browser used to render pages for the current crawl.
+#[derive(Debug, Clone)]
+pub struct Renderer {
+    chrome: PathBuf,
+    user_agent: String,
+    /// Time the page gets to run its scripts before the DOM is captured
+    budget: Duration,
+    /// Browser window size, Chrome's default when None
+    window_size: Option<(u32, u32)>,
+    /// `--proxy-server` values, each launch takes the next one
+    proxies: Arc<Vec<String>>,
+    next_proxy: Arc<AtomicUsize>,
+}
+
+// Set at the start of each crawl, None when rendering is off
+static RENDERER: Lazy<RwLock<Option<Renderer>>> = Lazy::new(|| RwLock::new(None));
+static RENDER_SLOTS: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(MAX_CONCURRENT_RENDERS));
+
+fn on_path(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Finds a Chrome, Chromium or Edge binary, preferring the path from the settings.
+pub fn find_chrome(settings: &Settings) -> Option<PathBuf> {
+    if !settings.chrome_path.trim().is_empty() {
+        let path = PathBuf::from(settings.chrome_path.trim());
+        return path.is_file().then_some(path);
+    }
+
+    CHROME_CANDIDATES.iter().find_map(|candidate| {
+        let path = Path::new(candidate);
+        if path.is_absolute() {
+            path.is_file().then(|| path.to_path_buf())
+        } else {
+            on_path(candidate)
+        }
+    })
+}
+
+/// Sets up rendering for a new crawl, failing when it is enabled but no browser is found.
+pub fn configure(settings: &Settings, user_agent: &str) -> Result<(), String> {
+    let renderer = if settings.render_javascript {
+        let chrome = find_chrome(settings).ok_or(
+            "JavaScript rendering is enabled but no Chrome or Chromium browser was found, \
+             set chrome_path in the settings",
+        )?;
+        Some(Renderer {
+            chrome,
+            user_agent: user_agent.to_string(),
+            budget: Duration::from_millis(settings.render_wait_ms),
+            window_size: None,
+            proxies: Arc::new(proxies::browser_proxies(settings)?),
+            next_proxy: Arc::new(AtomicUsize::new(0)),
+        })
+    } else {
+        None
+    };
+
+    *RENDERER.write().map_err(|e| e.to_string())? = renderer;
+    Ok(())
+}
+
+pub fn is_active() -> bool {
+    RENDERER.read().map(|r| r.is_some()).unwrap_or(false)
+}
+
+/// Loads `url` in headless Chrome and returns the DOM after scripts ran, along with what
+/// `expression` evaluates to in the same tab.
+///
+/// Returns `None` when rendering is off for this crawl.
+pub async fn render_evaluating(
+    url: &Url,
+    expression: &str,
+) -> Option<Result<(String, Value), String>> {
+    let renderer = RENDERER.read().ok()?.clone()?;
+    Some(renderer.render_evaluating(url, expression).await)
+}
+
+/// Time pages get to run their scripts in this crawl, None when rendering is off.
+pub fn budget() -> Option<Duration> {
+    RENDERER.read().ok()?.as_ref().map(|r| r.budget)
+}
+
+/// Opens a DevTools session in the crawl's browser.
+///
+/// Returns `None` when rendering is off for this crawl.
+pub async fn devtools() -> Option<Result<DevTools, String>> {
+    let renderer = RENDERER.read().ok()?.clone()?;
+    Some(renderer.devtools().await)
+}
+
+async fn evaluate(devtools: &mut DevTools, expression: &str) -> Result<Value, String> {
+    let result = devtools
+        .call(
+            "Runtime.evaluate",
+            json!({ "expression": expression, "returnByValue": true }),
+        )
+        .await?;
+    Ok(result
+        .pointer("/result/value")
+        .cloned()
+        .unwrap_or(Value::Null))
+}
+
+async fn outer_html(devtools: &mut DevTools, url: &Url) -> Result<String, String> {
+    let html = evaluate(devtools, "document.documentElement.outerHTML").await?;
+    match html.as_str() {
+        Some(html) if !html.trim().is_empty() => Ok(html.to_string()),
+        _ => Err(format!("Rendering {} returned an empty document", url)),
+    }
+}
+
+// With interception on, every request of the tab waits until it is continued
+async fn continue_request(devtools: &mut DevTools, params: &Value) -> Result<(), String> {
+    let request_id = params.get("requestId").cloned().unwrap_or(Value::Null);
+    let url = params
+        .pointer("/request/url")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let extra = request_auth::headers_for(url);
+    if extra.is_empty() {
+        devtools
+            .send("Fetch.continueRequest", json!({ "requestId": request_id }))
+            .await?;
+        return Ok(());
+    }
+
+    let mut headers: Vec<Value> = params
+        .pointer("/request/headers")
+        .and_then(Value::as_object)
+        .map(|original| {
+            original
+                .iter()
+                .filter(|(name, _)| !extra.iter().any(|(extra, _)| extra.eq_ignore_ascii_case(name)))
+                .map(|(name, value)| json!({ "name": name, "value": value.as_str().unwrap_or_default() }))
+                .collect()
+        })
+        .unwrap_or_default();
+    headers.extend(
+        extra
+            .iter()
+            .map(|(name, value)| json!({ "name": name, "value": value })),
+    );
+    devtools
+        .send(
+            "Fetch.continueRequest",
+            json!({ "requestId": request_id, "headers": headers }),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Handles the events of a tab for up to `limit`, or until the `until` event fires.
+///
+/// Returns whether `until` fired. Paused requests are continued with the crawl's credentials.
+pub async fn pump(
+    devtools: &mut DevTools,
+    until: Option<&str>,
+    limit: Duration,
+) -> Result<bool, String> {
+    let deadline = Instant::now() + limit;
+    while Instant::now() < deadline {
+        let Some(event) = devtools
+            .next_event(deadline.saturating_duration_since(Instant::now()))
+            .await
+        else {
+            break;
+        };
+        let method = event.get("method").and_then(Value::as_str);
+        if method == Some("Fetch.requestPaused") {
+            continue_request(devtools, event.get("params").unwrap_or(&Value::Null)).await?;
+        } else if method.is_some() && method == until {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Opens `url` in a DevTools tab and waits up to `limit` for its load event.
+///
+/// Like the crawler's own requests, only requests to the crawled site get its credentials,
+/// and the tab starts with the session cookies of the crawl.
+pub async fn navigate(devtools: &mut DevTools, url: &Url, limit: Duration) -> Result<bool, String> {
+    devtools.call("Page.enable", json!({})).await?;
+    let cookies: Vec<Value> = session::cookies_for(url)
+        .into_iter()
+        .map(|(name, value)| json!({ "name": name, "value": value, "url": url.as_str() }))
+        .collect();
+    if !cookies.is_empty() {
+        devtools
+            .call("Network.setCookies", json!({ "cookies": cookies }))
+            .await?;
+    }
+    if request_auth::is_configured() {
+        devtools
+            .call(
+                "Fetch.enable",
+                json!({ "patterns": [{ "urlPattern": "*" }] }),
+            )
+            .await?;
+    }
+    // Its reply waits for the document request, which is paused until the events are handled
+    devtools
+        .send("Page.navigate", json!({ "url": url.as_str() }))
+        .await?;
+    pump(devtools, Some("Page.loadEventFired"), limit).await
+}
+
+impl Renderer {
+    /// A one-off renderer outside of a crawl, for example to load a page with a mobile viewport.
+    pub fn new(
+        settings: &Settings,
+        user_agent: &str,
+        window_size: Option<(u32, u32)>,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            chrome: find_chrome(settings).ok_or("No Chrome or Chromium browser was found")?,
+            user_agent: user_agent.to_string(),
+            budget: Duration::from_millis(settings.render_wait_ms),
+            window_size,
+            proxies: Arc::new(proxies::browser_proxies(settings)?),
+            next_proxy: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    // Headless Chrome with the flags shared by DOM dumps and screenshots
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.chrome);
+        command
+            .arg("--headless=new")
+            .arg("--disable-gpu")
+            .arg("--hide-scrollbars")
+            .arg("--mute-audio")
+            .arg("--no-first-run")
+            .arg(format!("--user-agent={}", self.user_agent))
+            .arg(format!("--virtual-time-budget={}", self.budget.as_millis()));
+        if let Some((width, height)) = self.window_size {
+            command.arg(format!("--window-size={},{}", width, height));
+        }
+        if !self.proxies.is_empty() {
+            let next = self.next_proxy.fetch_add(1, Ordering::Relaxed);
+            command.arg(format!(
+                "--proxy-server={}",
+                self.proxies[next % self.proxies.len()]
+            ));
+        }
+        command.kill_on_drop(true);
+        command
+    }
+
+    // Virtual time runs faster than the wall clock, leave room for slow networks
+    fn time_limit(&self) -> Duration {
+        self.budget * 3 + Duration::from_secs(30)
+    }
+
+    // The command line cannot pass credentials or cookies, pages that need them are loaded
+    // over DevTools
+    fn needs_session(&self, url: &Url) -> bool {
+        !request_auth::headers_for(url.as_str()).is_empty() || !session::cookies_for(url).is_empty()
+    }
+
+    async fn run(&self, mut command: Command, url: &Url) -> Result<Vec<u8>, String> {
+        let _slot = RENDER_SLOTS.acquire().await.map_err(|e| e.to_string())?;
+
+        let output = timeout(self.time_limit(), command.arg(url.as_str()).output())
+            .await
+            .map_err(|_| format!("Rendering {} timed out", url))?
+            .map_err(|e| format!("Failed to start {:?}: {}", self.chrome, e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "Rendering {} failed: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(output.stdout)
+    }
+
+    // Loads `url` in a DevTools tab sized like the window and lets its scripts run
+    async fn open(&self, url: &Url) -> Result<DevTools, String> {
+        let mut devtools = self.devtools().await?;
+        if let Some((width, height)) = self.window_size {
+            devtools
+                .call(
+                    "Emulation.setDeviceMetricsOverride",
+                    json!({ "width": width, "height": height, "deviceScaleFactor": 1, "mobile": false }),
+                )
+                .await?;
+        }
+        if !navigate(&mut devtools, url, self.time_limit()).await? {
+            return Err(format!("Rendering {} timed out", url));
+        }
+        pump(&mut devtools, None, self.budget).await?;
+        Ok(devtools)
+    }
+
+    pub async fn render(&self, url: &Url) -> Result<String, String> {
+        let html = if self.needs_session(url) {
+            let mut devtools = self.open(url).await?;
+            outer_html(&mut devtools, url).await?
+        } else {
+            let mut command = self.command();
+            command.arg("--dump-dom");
+            String::from_utf8_lossy(&self.run(command, url).await?).into_owned()
+        };
+        if html.trim().is_empty() {
+            return Err(format!("Rendering {} returned an empty document", url));
+        }
+        Ok(html)
+    }
+
+    /// Renders `url` in a DevTools tab and evaluates `expression` once the DOM is captured.
+    pub async fn render_evaluating(
+        &self,
+        url: &Url,
+        expression: &str,
+    ) -> Result<(String, Value), String> {
+        let mut devtools = self.open(url).await?;
+        let html = outer_html(&mut devtools, url).await?;
+        let value = evaluate(&mut devtools, expression).await?;
+        Ok((html, value))
+    }
+
+    /// Starts the browser with a debugging port, for measurements `--dump-dom` cannot make.
+    pub async fn devtools(&self) -> Result<DevTools, String> {
+        let slot = RENDER_SLOTS.acquire().await.map_err(|e| e.to_string())?;
+        let mut command = self.command();
+        command.arg("--remote-debugging-port=0").arg("about:blank");
+        DevTools::launch(command, slot).await
+    }
+
+    /// Saves a PNG of the browser window after the page ran its scripts.
+    pub async fn screenshot(&self, url: &Url, path: &Path) -> Result<(), String> {
+        if self.needs_session(url) {
+            let mut devtools = self.open(url).await?;
+            let result = devtools
+                .call("Page.captureScreenshot", json!({ "format": "png" }))
+                .await?;
+            let png = result
+                .get("data")
+                .and_then(Value::as_str)
+                .and_then(|data| general_purpose::STANDARD.decode(data).ok())
+                .ok_or_else(|| format!("Rendering {} produced no screenshot", url))?;
+            tokio::fs::write(path, png)
+                .await
+                .map_err(|e| format!("Failed to save the screenshot of {}: {}", url, e))?;
+        } else {
+            let mut command = self.command();
+            command.arg(format!("--screenshot={}", path.display()));
+            self.run(command, url).await?;
+        }
+        if !path.is_file() {
+            return Err(format!("Rendering {} produced no screenshot", url));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::summary::{AuditSummary, IssueCount, Severity};
+
+const BRAND_COLOR: &str = "#1e3a8a";
+
+const STYLES: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Roboto, Helvetica, Arial, sans-serif; margin: 0; color: #1f2937; background: #f3f4f6; }
+header { background: BRAND; color: #fff; padding: 24px 40px; }
+header h1 { margin: 0; font-size: 24px; }
+header p { margin: 4px 0 0; opacity: 0.8; }
+main { padding: 24px 40px; }
+section { background: #fff; border-radius: 8px; padding: 20px; margin-bottom: 20px; box-shadow: 0 1px 2px rgba(0,0,0,0.05); }
+h2 { margin-top: 0; font-size: 18px; color: BRAND; }
+.cards { display: flex; gap: 16px; flex-wrap: wrap; }
+.card { flex: 1; min-width: 140px; background: #f9fafb; border-radius: 6px; padding: 12px 16px; }
+.card strong { display: block; font-size: 28px; }
+table { width: 100%; border-collapse: collapse; font-size: 14px; }
+th, td { text-align: left; padding: 8px; border-bottom: 1px solid #e5e7eb; vertical-align: top; }
+.severity { font-size: 12px; font-weight: bold; padding: 2px 8px; border-radius: 10px; color: #fff; }
+.high { background: #dc2626; } .medium { background: #d97706; } .low { background: #2563eb; }
+.charts { display: grid; grid-template-columns: repeat(auto-fit, minmax(280px, 1fr)); gap: 20px; }
+.bar { display: flex; align-items: center; margin: 4px 0; font-size: 13px; }
+.bar span { width: 110px; }
+.bar div { background: BRAND; height: 14px; border-radius: 3px; margin-right: 8px; }
+.examples { color: #6b7280; font-size: 12px; word-break: break-all; }
+footer { text-align: center; color: #9ca3af; font-size: 12px; padding: 16px; }
+"#;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn severity_class(severity: Severity) -> &'static str {
+    match severity {
+        Severity::High => "high",
+        Severity::Medium => "medium",
+        Severity::Low => "low",
+    }
+}
+
+fn issues_table(out: &mut String, issues: &[IssueCount], with_examples: bool) {
+    out.push_str("<table><tr><th>Issue</th><th>Severity</th><th>Pages</th>");
+    if with_examples {
+        out.push_str("<th>Examples</th>");
+    }
+    out.push_str("</tr>");
+
+    for issue in issues {
+        let _ = write!(
+            out,
+            "<tr><td>{}</td><td><span class=\"severity {}\">{:?}</span></td><td>{}</td>",
+            escape(&issue.name),
+            severity_class(issue.severity),
+            issue.severity,
+            issue.count
+        );
+        if with_examples {
+            let examples: Vec<String> = issue.examples.iter().map(|url| escape(url)).collect();
+            let _ = write!(out, "<td class=\"examples\">{}</td>", examples.join("<br>"));
+        }
+        out.push_str("</tr>");
+    }
+
+    out.push_str("</table>");
+}
+
+fn bar_chart(out: &mut String, title: &str, data: &BTreeMap<String, usize>) {
+    let max = data.values().copied().max().unwrap_or(0).max(1);
+
+    let _ = write!(out, "<div><h3>{}</h3>", escape(title));
+    for (label, count) in data {
+        let _ = write!(
+            out,
+            "<div class=\"bar\"><span>{}</span><div style=\"width:{}px\"></div>{}</div>",
+            escape(label),
+            count * 200 / max,
+            count
+        );
+    }
+    out.push_str("</div>");
+}
+
+/// Renders the audit summary as a single self-contained HTML page.
+///
+/// The chart data is also embedded as JSON so the page can be re-styled with a charting library.
+pub fn render(summary: &AuditSummary) -> String {
+    let mut out = String::new();
+    let total_issues: usize = summary.issues.iter().map(|issue| issue.count).sum();
+    let high: usize = summary
+        .issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::High)
+        .map(|issue| issue.count)
+        .sum();
+
+    let _ = write!(
+        out,
+        "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"utf-8\">\
+         <title>RustySEO audit - {domain}</title><style>{styles}</style></head><body>\
+         <header><h1>RustySEO Audit Report</h1><p>{domain} &middot; crawl #{id} &middot; {date}</p></header><main>",
+        domain = escape(&summary.crawl.domain),
+        styles = STYLES.replace("BRAND", BRAND_COLOR),
+        id = summary.crawl.id,
+        date = escape(&summary.crawl.started_at),
+    );
+
+    let _ = write!(
+        out,
+        "<section><h2>Overview</h2><div class=\"cards\">\
+         <div class=\"card\"><strong>{}</strong>Pages crawled</div>\
+         <div class=\"card\"><strong>{}</strong>Issues found</div>\
+         <div class=\"card\"><strong>{}</strong>High severity</div>\
+         <div class=\"card\"><strong>{}</strong>Issue types</div></div></section>",
+        summary.pages,
+        total_issues,
+        high,
+        summary.issues.len()
+    );
+
+    out.push_str("<section><h2>Top problems</h2>");
+    issues_table(&mut out, &summary.top_problems, true);
+    out.push_str("</section>");
+
+    out.push_str("<section><h2>Charts</h2><div class=\"charts\">");
+    bar_chart(&mut out, "Status codes", &summary.charts.status_codes);
+    bar_chart(&mut out, "Response times", &summary.charts.response_times);
+    bar_chart(&mut out, "Word count", &summary.charts.word_counts);
+    bar_chart(&mut out, "Indexability", &summary.charts.indexability);
+    out.push_str("</div></section>");
+
+    out.push_str("<section><h2>All issues</h2>");
+    issues_table(&mut out, &summary.issues, true);
+    out.push_str("</section>");
+
+    // Keep `</script>` sequences in URLs from closing the data block
+    let chart_json = serde_json::to_string(&summary.charts)
+        .unwrap_or_default()
+        .replace("</", "<\\/");
+    let _ = write!(
+        out,
+        "</main><footer>Generated by RustySEO</footer>\
+         <script type=\"application/json\" id=\"chart-data\">{}</script></body></html>",
+        chart_json
+    );
+
+    out
+}
@@ -0,0 +1,55 @@
+pub mod html;
+pub mod pdf;
+pub mod summary;
+
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::results_store::ResultsStore;
+use crate::projects::registry::ProjectRoots;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Html,
+    Pdf,
+}
+
+fn reports_dir(roots: &ProjectRoots) -> Result<PathBuf, String> {
+    let dir = roots.data_dir.join("reports");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Renders the audit summary of a crawl stored in the `roots` project and returns the path of
+/// the written file.
+pub async fn render_report(
+    roots: &ProjectRoots,
+    crawl_id: i64,
+    format: ReportFormat,
+) -> Result<PathBuf, String> {
+    let store = ResultsStore::open_in(&roots.db_dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    let summary = summary::summarise_crawl(&store, crawl_id).await?;
+
+    let (bytes, extension) = match format {
+        ReportFormat::Html => (html::render(&summary).into_bytes(), "html"),
+        ReportFormat::Pdf => (pdf::render(&summary), "pdf"),
+    };
+
+    let path = reports_dir(roots)?.join(format!("rustyseo-audit-{}.{}", crawl_id, extension));
+    fs::write(&path, bytes).map_err(|e| format!("Failed to write report: {}", e))?;
+
+    println!("Audit report written to {:?}", path);
+    Ok(path)
+}
+
+// GENERATE A STANDALONE AUDIT REPORT FOR A STORED CRAWL
+#[tauri::command]
+pub async fn generate_report(crawl_id: i64, format: ReportFormat) -> Result<String, String> {
+    render_report(&ProjectRoots::active()?, crawl_id, format)
+        .await
+        .map(|path| path.to_string_lossy().to_string())
+}
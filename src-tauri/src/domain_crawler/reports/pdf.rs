@@ -0,0 +1,229 @@
+use super::summary::{AuditSummary, IssueCount};
+
+// A4 in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+const MAX_LINE_CHARS: usize = 95;
+
+#[derive(Clone, Copy)]
+enum Style {
+    Title,
+    Heading,
+    Body,
+    Small,
+}
+
+impl Style {
+    // Font resource and size
+    fn font(self) -> (&'static str, f32) {
+        match self {
+            Style::Title => ("F2", 20.0),
+            Style::Heading => ("F2", 13.0),
+            Style::Body => ("F1", 10.0),
+            Style::Small => ("F1", 8.0),
+        }
+    }
+}
+
+/// Lays text out top to bottom and starts a new page when one is full.
+struct Layout {
+    pages: Vec<String>,
+    current: String,
+    y: f32,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            current: String::new(),
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    fn line(&mut self, text: &str, style: Style) {
+        let (font, size) = style.font();
+        let leading = size * 1.4;
+
+        for chunk in wrap(text, MAX_LINE_CHARS * 10 / size as usize) {
+            if self.y - leading < MARGIN {
+                self.pages.push(std::mem::take(&mut self.current));
+                self.y = PAGE_HEIGHT - MARGIN;
+            }
+            self.y -= leading;
+            self.current.push_str(&format!(
+                "BT /{} {} Tf {} {} Td ({}) Tj ET\n",
+                font,
+                size,
+                MARGIN,
+                self.y,
+                escape(&chunk)
+            ));
+        }
+    }
+
+    fn gap(&mut self) {
+        self.y -= 8.0;
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if !self.current.is_empty() || self.pages.is_empty() {
+            self.pages.push(self.current);
+        }
+        self.pages
+    }
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(20);
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars
+        .chunks(width)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+// The built-in fonts only cover Latin-1, anything else is replaced
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '(' => "\\(".to_string(),
+            ')' => "\\)".to_string(),
+            '\\' => "\\\\".to_string(),
+            c if c.is_ascii() && !c.is_ascii_control() => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+fn issue_lines(layout: &mut Layout, issues: &[IssueCount]) {
+    for issue in issues {
+        layout.line(
+            &format!(
+                "{:?} - {}: {} pages",
+                issue.severity, issue.name, issue.count
+            ),
+            Style::Body,
+        );
+        for url in &issue.examples {
+            layout.line(&format!("    {}", url), Style::Small);
+        }
+    }
+}
+
+/// Renders the audit summary as a plain text PDF using the standard Helvetica fonts.
+pub fn render(summary: &AuditSummary) -> Vec<u8> {
+    let mut layout = Layout::new();
+
+    layout.line("RustySEO Audit Report", Style::Title);
+    layout.line(
+        &format!(
+            "{} - crawl #{} - {}",
+            summary.crawl.domain, summary.crawl.id, summary.crawl.started_at
+        ),
+        Style::Body,
+    );
+    layout.gap();
+
+    let total_issues: usize = summary.issues.iter().map(|issue| issue.count).sum();
+    layout.line("Overview", Style::Heading);
+    layout.line(&format!("Pages crawled: {}", summary.pages), Style::Body);
+    layout.line(&format!("Issues found: {}", total_issues), Style::Body);
+    layout.gap();
+
+    layout.line("Top problems", Style::Heading);
+    issue_lines(&mut layout, &summary.top_problems);
+    layout.gap();
+
+    let charts = [
+        ("Status codes", &summary.charts.status_codes),
+        ("Response times", &summary.charts.response_times),
+        ("Word count", &summary.charts.word_counts),
+        ("Indexability", &summary.charts.indexability),
+    ];
+    for (title, data) in charts {
+        layout.line(title, Style::Heading);
+        let max = data.values().copied().max().unwrap_or(0).max(1);
+        for (label, count) in data {
+            layout.line(
+                &format!(
+                    "{:<14} {:<40} {}",
+                    label,
+                    "#".repeat(count * 40 / max),
+                    count
+                ),
+                Style::Body,
+            );
+        }
+        layout.gap();
+    }
+
+    layout.line("All issues", Style::Heading);
+    issue_lines(&mut layout, &summary.issues);
+
+    write_document(&layout.finish())
+}
+
+fn write_document(pages: &[String]) -> Vec<u8> {
+    // 1 catalog, 2 page tree, 3 and 4 fonts, then a page and a content stream per page
+    let mut objects: Vec<String> = Vec::new();
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", 5 + i * 2))
+        .collect();
+
+    objects.push("<< /Type /Catalog /Pages 2 0 R >>".to_string());
+    objects.push(format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        pages.len()
+    ));
+    objects.push(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    );
+    objects.push(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    );
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            6 + i * 2
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    }
+
+    let mut out = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+
+    let xref = out.len();
+    out.push_str(&format!(
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objects.len() + 1
+    ));
+    for offset in offsets {
+        out.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    out.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    ));
+
+    out.into_bytes()
+}
@@ -0,0 +1,246 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain_crawler::helpers::canonical_selector::CanonicalKind;
+pub use crate::domain_crawler::issues::Severity;
+use crate::domain_crawler::issues::{IssueKind, IssueRegistry};
+use crate::domain_crawler::models::DomainCrawlResults;
+use crate::domain_crawler::results_store::{to_page_row, CrawlRecord, ResultsStore};
+
+// Thresholds shared with the rest of the audits
+const SLOW_RESPONSE_SECS: f64 = 2.0;
+const THIN_CONTENT_WORDS: usize = 200;
+const EXAMPLE_URLS: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueCount {
+    pub name: String,
+    pub severity: Severity,
+    pub count: usize,
+    pub examples: Vec<String>,
+}
+
+/// Bucketed data for the charts of the report, label to count.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChartData {
+    pub status_codes: BTreeMap<String, usize>,
+    pub response_times: BTreeMap<String, usize>,
+    pub word_counts: BTreeMap<String, usize>,
+    pub indexability: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditSummary {
+    pub crawl: CrawlRecord,
+    pub pages: usize,
+    pub issues: Vec<IssueCount>,
+    /// The issues weighted by severity, worst first
+    pub top_problems: Vec<IssueCount>,
+    pub charts: ChartData,
+}
+
+#[derive(Default)]
+struct Collector {
+    pages: usize,
+    issues: HashMap<IssueKind, (usize, Vec<String>)>,
+    titles: HashMap<String, Vec<String>>,
+    charts: ChartData,
+}
+
+impl Collector {
+    fn flag(&mut self, kind: IssueKind, url: &str) {
+        let entry = self.issues.entry(kind).or_default();
+        entry.0 += 1;
+        if entry.1.len() < EXAMPLE_URLS {
+            entry.1.push(url.to_string());
+        }
+    }
+
+    fn add(&mut self, page: &DomainCrawlResults) {
+        let row = to_page_row(page);
+        self.pages += 1;
+
+        let status_bucket = match row.status_code {
+            0 => "Failed".to_string(),
+            code => format!("{}xx", code / 100),
+        };
+        *self.charts.status_codes.entry(status_bucket).or_insert(0) += 1;
+
+        let time_bucket = match row.response_time {
+            Some(t) if t < 0.5 => "< 0.5s",
+            Some(t) if t < 1.0 => "0.5s - 1s",
+            Some(t) if t < SLOW_RESPONSE_SECS => "1s - 2s",
+            Some(_) => "> 2s",
+            None => "Unknown",
+        };
+        *self
+            .charts
+            .response_times
+            .entry(time_bucket.to_string())
+            .or_insert(0) += 1;
+
+        let words_bucket = match row.word_count {
+            0..=199 => "0 - 199",
+            200..=499 => "200 - 499",
+            500..=999 => "500 - 999",
+            _ => "1000+",
+        };
+        *self
+            .charts
+            .word_counts
+            .entry(words_bucket.to_string())
+            .or_insert(0) += 1;
+
+        let indexable = if row.indexability > 0.5 {
+            "Indexable"
+        } else {
+            "Non-indexable"
+        };
+        *self
+            .charts
+            .indexability
+            .entry(indexable.to_string())
+            .or_insert(0) += 1;
+
+        for kind in page_issues(page) {
+            self.flag(kind, &row.url);
+        }
+        if (200..300).contains(&row.status_code) && row.content_type.contains("html") {
+            if let Some(title) = row
+                .title
+                .as_deref()
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+            {
+                self.titles
+                    .entry(title.to_string())
+                    .or_default()
+                    .push(row.url.clone());
+            }
+        }
+    }
+}
+
+/// The issues of a single page, without the ones that need the whole crawl.
+pub fn page_issues(page: &DomainCrawlResults) -> Vec<IssueKind> {
+    let row = to_page_row(page);
+    let mut issues = Vec::new();
+    match row.status_code {
+        0 => issues.push(IssueKind::FailedToFetch),
+        400..=499 => issues.push(IssueKind::ClientError),
+        500..=599 => issues.push(IssueKind::ServerError),
+        300..=399 => issues.push(IssueKind::Redirect),
+        _ => {}
+    }
+
+    // Content checks only make sense for pages that loaded
+    if !(200..300).contains(&row.status_code) || !row.content_type.contains("html") {
+        return issues;
+    }
+
+    if row
+        .title
+        .as_deref()
+        .map_or(true, |title| title.trim().is_empty())
+    {
+        issues.push(IssueKind::MissingTitle);
+    }
+    if row.description.trim().is_empty() {
+        issues.push(IssueKind::MissingDescription);
+    }
+    if row.h1.as_deref().map_or(true, |h1| h1.trim().is_empty()) {
+        issues.push(IssueKind::MissingH1);
+    }
+    if row.word_count < THIN_CONTENT_WORDS {
+        issues.push(IssueKind::ThinContent);
+    }
+    if row.response_time.is_some_and(|t| t > SLOW_RESPONSE_SECS) {
+        issues.push(IssueKind::SlowResponse);
+    }
+    if row.indexability <= 0.5 {
+        issues.push(IssueKind::NonIndexable);
+    }
+    if !page.alt_tags.without_alt_tags.is_empty() {
+        issues.push(IssueKind::MissingAltText);
+    }
+    match page.canonical.kind {
+        CanonicalKind::Missing => issues.push(IssueKind::MissingCanonical),
+        CanonicalKind::Multiple | CanonicalKind::Invalid => {
+            issues.push(IssueKind::InvalidCanonical)
+        }
+        _ => {}
+    }
+    issues
+}
+
+/// Registers the issues of a crawled page with the issue engine.
+pub fn register_page_issues(page: &DomainCrawlResults, issues: &mut IssueRegistry) {
+    for kind in page_issues(page) {
+        issues.flag(kind, &page.url);
+    }
+}
+
+/// Reads a stored crawl once and counts the issues the report shows.
+pub async fn summarise_crawl(store: &ResultsStore, crawl_id: i64) -> Result<AuditSummary, String> {
+    let crawl = store
+        .crawl(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Crawl {} not found", crawl_id))?;
+
+    let collector = Arc::new(Mutex::new(Collector::default()));
+    let pages = collector.clone();
+    store
+        .for_each_page(crawl_id, move |page| {
+            pages.lock().map_err(|e| e.to_string())?.add(&page);
+            Ok(())
+        })
+        .await?;
+
+    let mut collector = std::mem::take(&mut *collector.lock().map_err(|e| e.to_string())?);
+
+    let duplicates: Vec<String> = collector
+        .titles
+        .values()
+        .filter(|urls| urls.len() > 1)
+        .flatten()
+        .cloned()
+        .collect();
+    for url in &duplicates {
+        collector.flag(IssueKind::DuplicateTitle, url);
+    }
+
+    let broken = store
+        .broken_links(crawl_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    for link in &broken {
+        collector.flag(IssueKind::BrokenLink, &link.url);
+    }
+
+    let mut issues: Vec<IssueCount> = collector
+        .issues
+        .into_iter()
+        .map(|(kind, (count, examples))| IssueCount {
+            name: kind.name().to_string(),
+            severity: kind.severity(),
+            count,
+            examples,
+        })
+        .collect();
+    issues.sort_by(|a, b| b.severity.cmp(&a.severity).then(b.count.cmp(&a.count)));
+
+    let mut top_problems = issues.clone();
+    top_problems.sort_by_key(|issue| std::cmp::Reverse(issue.count * issue.severity.weight()));
+    top_problems.truncate(5);
+
+    Ok(AuditSummary {
+        crawl,
+        pages: collector.pages,
+        issues,
+        top_problems,
+        charts: collector.charts,
+    })
+}
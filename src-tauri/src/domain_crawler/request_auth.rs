@@ -0,0 +1,139 @@
+use std::sync::RwLock;
+
+use base64::{engine::general_purpose, Engine};
+use once_cell::sync::Lazy;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::RequestBuilder;
+use url::Url;
+
+use crate::settings::settings::Settings;
+
+/// Credentials and extra headers sent with every request to the crawled site.
+#[derive(Debug, Clone, Default)]
+struct RequestAuth {
+    /// Only this host and its subdomains get the credentials, never third-party links
+    host: String,
+    basic: Option<(String, Option<String>)>,
+    bearer: Option<String>,
+    headers: HeaderMap,
+}
+
+// Set at the start of each crawl, None when no credentials or headers are configured
+static REQUEST_AUTH: Lazy<RwLock<Option<RequestAuth>>> = Lazy::new(|| RwLock::new(None));
+
+/// Parses `custom_headers` entries written as `Name: value`.
+pub fn parse_headers(lines: &[String]) -> Result<HeaderMap, String> {
+    let mut headers = HeaderMap::new();
+    for line in lines.iter().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Custom header \"{}\" must be written as Name: value", line))?;
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|e| format!("Invalid header name \"{}\": {}", name.trim(), e))?;
+        let value = HeaderValue::from_str(value.trim())
+            .map_err(|e| format!("Invalid value for header {}: {}", name, e))?;
+        headers.append(name, value);
+    }
+    Ok(headers)
+}
+
+/// Sets up the credentials and headers for a crawl of `base_url`.
+pub fn configure(settings: &Settings, base_url: &Url) -> Result<(), String> {
+    let headers = parse_headers(&settings.custom_headers)?;
+    let username = settings.auth_username.trim();
+    let bearer = settings.auth_bearer_token.trim();
+
+    if !username.is_empty() && !bearer.is_empty() {
+        return Err("Set either auth_username or auth_bearer_token, not both".to_string());
+    }
+
+    let auth = if username.is_empty() && bearer.is_empty() && headers.is_empty() {
+        None
+    } else {
+        Some(RequestAuth {
+            host: base_url
+                .host_str()
+                .ok_or("Invalid URL")?
+                .trim_start_matches("www.")
+                .to_lowercase(),
+            basic: (!username.is_empty()).then(|| {
+                (
+                    username.to_string(),
+                    (!settings.auth_password.is_empty()).then(|| settings.auth_password.clone()),
+                )
+            }),
+            bearer: (!bearer.is_empty()).then(|| bearer.to_string()),
+            headers,
+        })
+    };
+
+    *REQUEST_AUTH.write().map_err(|e| e.to_string())? = auth;
+    Ok(())
+}
+
+fn applies_to(auth: &RequestAuth, url: &str) -> bool {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+        .is_some_and(|host| {
+            let host = host.trim_start_matches("www.");
+            host == auth.host || host.ends_with(&format!(".{}", auth.host))
+        })
+}
+
+fn auth_for(url: &str) -> Option<RequestAuth> {
+    REQUEST_AUTH
+        .read()
+        .ok()?
+        .as_ref()
+        .filter(|auth| applies_to(auth, url))
+        .cloned()
+}
+
+/// Whether the crawl sends credentials or custom headers to any URL.
+pub fn is_configured() -> bool {
+    REQUEST_AUTH.read().is_ok_and(|auth| auth.is_some())
+}
+
+/// The credentials and custom headers for `url` as name and value pairs, for the headless
+/// browser which cannot take a `RequestBuilder`.
+pub fn headers_for(url: &str) -> Vec<(String, String)> {
+    let Some(auth) = auth_for(url) else {
+        return Vec::new();
+    };
+
+    let mut headers: Vec<(String, String)> = auth
+        .headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    if let Some((username, password)) = auth.basic {
+        let credentials = format!("{}:{}", username, password.unwrap_or_default());
+        headers.push((
+            "Authorization".to_string(),
+            format!("Basic {}", general_purpose::STANDARD.encode(credentials)),
+        ));
+    }
+    if let Some(token) = auth.bearer {
+        headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+    }
+    headers
+}
+
+/// Adds the crawl's credentials and custom headers to a request for `url`.
+///
+/// Requests to other sites are left untouched so credentials never leak to external links.
+pub fn apply(request: RequestBuilder, url: &str) -> RequestBuilder {
+    let Some(auth) = auth_for(url) else {
+        return request;
+    };
+
+    let mut request = request.headers(auth.headers);
+    if let Some((username, password)) = auth.basic {
+        request = request.basic_auth(username, password);
+    }
+    if let Some(token) = auth.bearer {
+        request = request.bearer_auth(token);
+    }
+    request
+}
@@ -0,0 +1,247 @@
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use reqwest::header::{IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use reqwest::RequestBuilder;
+use rusqlite::{params, OptionalExtension};
+use url::Url;
+
+use super::database::{Database, DatabaseError};
+use super::models::DomainCrawlResults;
+use super::{request_auth, user_agents};
+use crate::settings::settings::Settings;
+
+const CACHE_DB: &str = "response_cache.db";
+// Pages not crawled again for this long are forgotten
+const MAX_AGE_DAYS: u32 = 90;
+
+// Open for the current crawl, None when conditional requests are turned off
+static CACHE: Lazy<RwLock<Option<Arc<Pool<SqliteConnectionManager>>>>> =
+    Lazy::new(|| RwLock::new(None));
+
+/// A page as it was last crawled, reused when the server answers 304 Not Modified.
+pub struct CachedPage {
+    pub result: DomainCrawlResults,
+    /// The links found on the page, queued again without parsing it
+    pub links: Vec<Url>,
+}
+
+/// Opens the validator cache for a crawl, kept across crawls so re-crawls can revalidate.
+pub fn configure(settings: &Settings, db_dir: &Path) -> Result<(), String> {
+    let pool = if settings.conditional_requests {
+        Some(open(db_dir).map_err(|e| format!("Response cache unavailable: {}", e))?)
+    } else {
+        None
+    };
+    *CACHE.write().map_err(|e| e.to_string())? = pool;
+    Ok(())
+}
+
+fn open(db_dir: &Path) -> Result<Arc<Pool<SqliteConnectionManager>>, DatabaseError> {
+    let pool = Database::in_dir(db_dir, CACHE_DB)?.get_pool();
+    let conn = pool.get()?;
+    // `responses` was keyed by URL alone and kept the HTML of every page
+    conn.execute_batch(
+        r#"
+        DROP TABLE IF EXISTS responses;
+        CREATE TABLE IF NOT EXISTS validators (
+            url TEXT NOT NULL,
+            user_agent TEXT NOT NULL,
+            auth TEXT NOT NULL,
+            etag TEXT,
+            last_modified TEXT,
+            body_hash TEXT NOT NULL,
+            links TEXT NOT NULL,
+            result TEXT NOT NULL,
+            stored_at INTEGER NOT NULL,
+            PRIMARY KEY (url, user_agent, auth)
+        );
+        "#,
+    )?;
+    conn.execute(
+        "DELETE FROM validators WHERE stored_at < strftime('%s', 'now') - ?1",
+        params![MAX_AGE_DAYS * 24 * 60 * 60],
+    )?;
+    Ok(pool)
+}
+
+fn pool() -> Option<Arc<Pool<SqliteConnectionManager>>> {
+    CACHE.read().ok().and_then(|pool| pool.clone())
+}
+
+// FNV-1a, stable across builds unlike the std hasher, so hashes from older crawls still compare
+fn hash(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// What a cached answer depends on besides the URL: the user agent and the credentials sent.
+struct Key {
+    url: String,
+    user_agent: String,
+    auth: String,
+}
+
+impl Key {
+    fn of(url: &str) -> Self {
+        // Only a hash of the credentials is kept on disk
+        let auth = request_auth::headers_for(url)
+            .iter()
+            .map(|(name, value)| format!("{}: {}\n", name, value))
+            .collect::<String>();
+        Self {
+            url: url.to_string(),
+            user_agent: user_agents::current(),
+            auth: if auth.is_empty() {
+                String::new()
+            } else {
+                hash(&auth)
+            },
+        }
+    }
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+        .filter(|value| !value.is_empty())
+}
+
+/// Adds `If-None-Match` and `If-Modified-Since` when `url` was cached with validators.
+pub async fn conditional(request: RequestBuilder, url: &str) -> RequestBuilder {
+    let Some(pool) = pool() else {
+        return request;
+    };
+    let key = Key::of(url);
+    let validators: Option<(Option<String>, Option<String>)> =
+        tokio::task::spawn_blocking(move || {
+            pool.get()
+                .ok()?
+                .query_row(
+                    "SELECT etag, last_modified FROM validators
+                     WHERE url = ?1 AND user_agent = ?2 AND auth = ?3",
+                    params![key.url, key.user_agent, key.auth],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .ok()
+                .flatten()
+        })
+        .await
+        .ok()
+        .flatten();
+
+    let Some((etag, last_modified)) = validators else {
+        return request;
+    };
+    let request = match etag {
+        Some(etag) => request.header(IF_NONE_MATCH, etag),
+        None => request,
+    };
+    match last_modified {
+        Some(last_modified) => request.header(IF_MODIFIED_SINCE, last_modified),
+        None => request,
+    }
+}
+
+/// The last crawl of `url`, for answering a 304.
+pub async fn cached(url: &str) -> Option<CachedPage> {
+    let pool = pool()?;
+    let key = Key::of(url);
+    let (links, result): (String, String) = tokio::task::spawn_blocking(move || {
+        pool.get()
+            .ok()?
+            .query_row(
+                "SELECT links, result FROM validators
+                 WHERE url = ?1 AND user_agent = ?2 AND auth = ?3",
+                params![key.url, key.user_agent, key.auth],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .ok()
+            .flatten()
+    })
+    .await
+    .ok()??;
+
+    let links: Vec<String> = serde_json::from_str(&links).ok()?;
+    Some(CachedPage {
+        result: serde_json::from_str(&result).ok()?,
+        links: links
+            .iter()
+            .filter_map(|link| Url::parse(link).ok())
+            .collect(),
+    })
+}
+
+/// Saves the validators and result of a freshly downloaded page, without its HTML.
+///
+/// Returns whether the body changed since the previous crawl, None when it was never cached.
+pub async fn store(
+    url: &str,
+    headers: &[(String, String)],
+    body: &str,
+    links: &[Url],
+    result: &DomainCrawlResults,
+) -> Option<bool> {
+    let pool = pool()?;
+    let key = Key::of(url);
+    let body_hash = hash(body);
+    let etag = header(headers, "etag").map(String::from);
+    let last_modified = header(headers, "last-modified").map(String::from);
+    let links: Vec<&str> = links.iter().map(|link| link.as_str()).collect();
+    // A 304 reuses the audits of the page, the markup they were taken from is not needed
+    let result = DomainCrawlResults {
+        raw_html: None,
+        rendered_html: None,
+        ..result.clone()
+    };
+    let serialized = serde_json::to_string(&links)
+        .and_then(|links| serde_json::to_string(&result).map(|result| (links, result)));
+
+    let stored: Result<Option<bool>, DatabaseError> = match serialized {
+        Ok((links, result)) => tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let previous: Option<String> = conn
+                .query_row(
+                    "SELECT body_hash FROM validators
+                     WHERE url = ?1 AND user_agent = ?2 AND auth = ?3",
+                    params![key.url, key.user_agent, key.auth],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            conn.execute(
+                "INSERT OR REPLACE INTO validators (url, user_agent, auth, etag, last_modified,
+                    body_hash, links, result, stored_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, strftime('%s', 'now'))",
+                params![
+                    key.url,
+                    key.user_agent,
+                    key.auth,
+                    etag,
+                    last_modified,
+                    body_hash,
+                    links,
+                    result,
+                ],
+            )?;
+            Ok(previous.map(|previous| previous != body_hash))
+        })
+        .await
+        .unwrap_or_else(|e| Err(e.into())),
+        Err(e) => Err(e.into()),
+    };
+
+    stored.unwrap_or_else(|e| {
+        eprintln!("Failed to cache response for {}: {}", url, e);
+        None
+    })
+}
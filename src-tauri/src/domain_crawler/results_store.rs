@@ -0,0 +1,1291 @@
+use std::path::Path;
+
+use rusqlite::{params, params_from_iter, OptionalExtension, ToSql};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::database::{Database, DatabaseError};
+use super::ga4::Ga4Row;
+use super::gsc::GscRow;
+use super::helpers::anchor_links::{resolved_internal_anchors, resolved_internal_links};
+use super::helpers::sitemap::SitemapEntry;
+use super::issues::{Issue, IssueKind, IssueRegistry};
+use super::link_checker::BrokenLink;
+use super::models::DomainCrawlResults;
+use super::page_speed::psi::PsiScores;
+use crate::ai::briefs::ContentBrief;
+use crate::backlinks::provider::Backlink;
+
+const RESULTS_DB: &str = "crawl_store.db";
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// A stored crawl, one per run of the domain crawler.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawlRecord {
+    pub id: i64,
+    pub domain: String,
+    pub status: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub pages: usize,
+    /// The user agent profile and string the crawl ran with
+    pub user_agent_profile: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// The flat columns of a stored page, enough to render the results table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageRow {
+    pub url: String,
+    pub status_code: u16,
+    pub title: Option<String>,
+    pub description: String,
+    pub h1: Option<String>,
+    pub word_count: usize,
+    pub response_time: Option<f64>,
+    pub content_type: String,
+    pub content_length: usize,
+    pub indexability: f32,
+}
+
+/// Paging, filtering and sorting options for `query_crawl_results`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageQuery {
+    pub page: usize,
+    pub page_size: usize,
+    pub sort_by: Option<String>,
+    pub descending: bool,
+    /// Matches URLs, titles and descriptions containing the text
+    pub search: Option<String>,
+    pub status_code: Option<u16>,
+    /// Status class such as 2, 3, 4 or 5
+    pub status_class: Option<u16>,
+    pub indexable: Option<bool>,
+    pub content_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageSlice {
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub rows: Vec<PageRow>,
+}
+
+// Only these columns are accepted as sort keys
+fn sort_column(key: &str) -> Option<&'static str> {
+    match key {
+        "url" => Some("url"),
+        "status_code" => Some("status_code"),
+        "title" => Some("title"),
+        "h1" => Some("h1"),
+        "word_count" => Some("word_count"),
+        "response_time" => Some("response_time"),
+        "content_type" => Some("content_type"),
+        "content_length" => Some("content_length"),
+        "indexability" => Some("indexability"),
+        _ => None,
+    }
+}
+
+/// A crawl-level report kept with its crawl, one per crawl and kind.
+pub trait StoredReport: Serialize + DeserializeOwned + Send + 'static {
+    const KIND: &'static str;
+}
+
+/// Page-level crawl results on disk, keyed by crawl id and URL.
+#[derive(Clone)]
+pub struct ResultsStore {
+    db: Database,
+}
+
+impl ResultsStore {
+    pub async fn open() -> Result<Self, DatabaseError> {
+        Self::open_db(Database::new(RESULTS_DB)?).await
+    }
+
+    /// Opens the store of a project other than the active one.
+    pub async fn open_in(db_dir: &Path) -> Result<Self, DatabaseError> {
+        Self::open_db(Database::in_dir(db_dir, RESULTS_DB)?).await
+    }
+
+    async fn open_db(db: Database) -> Result<Self, DatabaseError> {
+        let pool = db.get_pool();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS crawls (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    domain TEXT NOT NULL,
+                    status TEXT NOT NULL DEFAULT 'running',
+                    started_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    finished_at TIMESTAMP,
+                    user_agent_profile TEXT,
+                    user_agent TEXT
+                );
+                CREATE TABLE IF NOT EXISTS crawl_pages (
+                    crawl_id INTEGER NOT NULL,
+                    url TEXT NOT NULL,
+                    status_code INTEGER NOT NULL,
+                    title TEXT,
+                    description TEXT NOT NULL,
+                    h1 TEXT,
+                    word_count INTEGER NOT NULL,
+                    response_time REAL,
+                    content_type TEXT NOT NULL,
+                    content_length INTEGER NOT NULL,
+                    indexability REAL NOT NULL,
+                    data TEXT NOT NULL,
+                    PRIMARY KEY (crawl_id, url)
+                );
+                CREATE INDEX IF NOT EXISTS idx_crawl_pages_status ON crawl_pages(crawl_id, status_code);
+                CREATE TABLE IF NOT EXISTS crawl_psi (
+                    crawl_id INTEGER NOT NULL,
+                    url TEXT NOT NULL,
+                    strategy TEXT NOT NULL,
+                    fetched_at TEXT NOT NULL,
+                    failed INTEGER NOT NULL,
+                    data TEXT NOT NULL,
+                    PRIMARY KEY (crawl_id, url, strategy)
+                );
+                CREATE INDEX IF NOT EXISTS idx_crawl_psi_url ON crawl_psi(url, strategy);
+                CREATE TABLE IF NOT EXISTS crawl_gsc (
+                    crawl_id INTEGER NOT NULL,
+                    page TEXT NOT NULL,
+                    query TEXT NOT NULL,
+                    clicks REAL NOT NULL,
+                    impressions REAL NOT NULL,
+                    ctr REAL NOT NULL,
+                    position REAL NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_crawl_gsc_crawl ON crawl_gsc(crawl_id);
+                CREATE TABLE IF NOT EXISTS crawl_ga4 (
+                    crawl_id INTEGER NOT NULL,
+                    landing_page TEXT NOT NULL,
+                    sessions REAL NOT NULL,
+                    engaged_sessions REAL NOT NULL,
+                    average_session_duration REAL NOT NULL,
+                    conversions REAL NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_crawl_ga4_crawl ON crawl_ga4(crawl_id);
+                CREATE TABLE IF NOT EXISTS crawl_backlinks (
+                    crawl_id INTEGER NOT NULL,
+                    source_url TEXT NOT NULL,
+                    target_url TEXT NOT NULL,
+                    anchor TEXT,
+                    nofollow INTEGER NOT NULL,
+                    source_authority REAL,
+                    first_seen TEXT
+                );
+                CREATE INDEX IF NOT EXISTS idx_crawl_backlinks_crawl ON crawl_backlinks(crawl_id);
+                CREATE TABLE IF NOT EXISTS competitors (
+                    domain TEXT NOT NULL,
+                    competitor TEXT NOT NULL,
+                    PRIMARY KEY (domain, competitor)
+                );
+                CREATE TABLE IF NOT EXISTS crawl_links (
+                    crawl_id INTEGER NOT NULL,
+                    source TEXT NOT NULL,
+                    target TEXT NOT NULL,
+                    PRIMARY KEY (crawl_id, source, target)
+                );
+                CREATE TABLE IF NOT EXISTS crawl_anchors (
+                    crawl_id INTEGER NOT NULL,
+                    source TEXT NOT NULL,
+                    target TEXT NOT NULL,
+                    anchor TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_crawl_anchors_target ON crawl_anchors(crawl_id, target);
+                CREATE TABLE IF NOT EXISTS crawl_sitemap_urls (
+                    crawl_id INTEGER NOT NULL,
+                    url TEXT NOT NULL,
+                    sitemap TEXT NOT NULL,
+                    PRIMARY KEY (crawl_id, url)
+                );
+                CREATE TABLE IF NOT EXISTS crawl_broken_links (
+                    crawl_id INTEGER NOT NULL,
+                    url TEXT NOT NULL,
+                    data TEXT NOT NULL,
+                    PRIMARY KEY (crawl_id, url)
+                );
+                CREATE TABLE IF NOT EXISTS crawl_issues (
+                    crawl_id INTEGER NOT NULL,
+                    issue_id TEXT NOT NULL,
+                    count INTEGER NOT NULL,
+                    PRIMARY KEY (crawl_id, issue_id)
+                );
+                CREATE TABLE IF NOT EXISTS crawl_issue_urls (
+                    crawl_id INTEGER NOT NULL,
+                    issue_id TEXT NOT NULL,
+                    url TEXT NOT NULL,
+                    PRIMARY KEY (crawl_id, issue_id, url)
+                );
+                CREATE TABLE IF NOT EXISTS crawl_briefs (
+                    crawl_id INTEGER NOT NULL,
+                    url TEXT NOT NULL,
+                    data TEXT NOT NULL,
+                    PRIMARY KEY (crawl_id, url)
+                );
+                CREATE TABLE IF NOT EXISTS crawl_reports (
+                    crawl_id INTEGER NOT NULL,
+                    kind TEXT NOT NULL,
+                    data TEXT NOT NULL,
+                    PRIMARY KEY (crawl_id, kind)
+                );
+                "#,
+            )?;
+
+            // Stores created before crawl profiles existed lack the user agent columns
+            let columns = conn
+                .prepare("SELECT name FROM pragma_table_info('crawls')")?
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            for column in ["user_agent_profile", "user_agent"] {
+                if !columns.iter().any(|c| c == column) {
+                    conn.execute(&format!("ALTER TABLE crawls ADD COLUMN {} TEXT", column), [])?;
+                }
+            }
+            Ok::<_, DatabaseError>(())
+        })
+        .await??;
+
+        Ok(Self { db })
+    }
+
+    pub async fn create_crawl(
+        &self,
+        domain: &str,
+        user_agent_profile: &str,
+        user_agent: &str,
+    ) -> Result<i64, DatabaseError> {
+        let pool = self.db.get_pool();
+        let domain = domain.to_string();
+        let user_agent_profile = user_agent_profile.to_string();
+        let user_agent = user_agent.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            conn.execute(
+                "INSERT INTO crawls (domain, user_agent_profile, user_agent) VALUES (?1, ?2, ?3)",
+                params![domain, user_agent_profile, user_agent],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+        .await?
+    }
+
+    pub async fn finish_crawl(&self, crawl_id: i64, status: &str) -> Result<(), DatabaseError> {
+        let pool = self.db.get_pool();
+        let status = status.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            conn.execute(
+                "UPDATE crawls SET status = ?2, finished_at = CURRENT_TIMESTAMP WHERE id = ?1",
+                params![crawl_id, status],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn insert_pages(
+        &self,
+        crawl_id: i64,
+        pages: &[DomainCrawlResults],
+    ) -> Result<(), DatabaseError> {
+        if pages.is_empty() {
+            return Ok(());
+        }
+
+        let rows = pages
+            .iter()
+            .map(|page| Ok((to_page_row(page), serde_json::to_string(page)?)))
+            .collect::<Result<Vec<_>, DatabaseError>>()?;
+        // The internal link graph is stored as one row per distinct source and target
+        let links: Vec<(String, String)> = pages
+            .iter()
+            .flat_map(|page| {
+                internal_link_targets(page)
+                    .into_iter()
+                    .map(|target| (page.url.clone(), target))
+            })
+            .collect();
+        let anchors: Vec<(String, Vec<(String, String)>)> = pages
+            .iter()
+            .map(|page| (page.url.clone(), internal_anchors(page)))
+            .collect();
+
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT OR REPLACE INTO crawl_pages (crawl_id, url, status_code, title,
+                        description, h1, word_count, response_time, content_type,
+                        content_length, indexability, data)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                )?;
+                for (row, data) in &rows {
+                    stmt.execute(params![
+                        crawl_id,
+                        row.url,
+                        row.status_code,
+                        row.title,
+                        row.description,
+                        row.h1,
+                        row.word_count,
+                        row.response_time,
+                        row.content_type,
+                        row.content_length,
+                        row.indexability,
+                        data
+                    ])?;
+                }
+
+                let mut link_stmt = tx.prepare_cached(
+                    "INSERT OR IGNORE INTO crawl_links (crawl_id, source, target)
+                     VALUES (?1, ?2, ?3)",
+                )?;
+                for (source, target) in &links {
+                    link_stmt.execute(params![crawl_id, source, target])?;
+                }
+
+                // Every link keeps its anchor, so replace the anchors of re-inserted pages
+                let mut clear_anchors = tx.prepare_cached(
+                    "DELETE FROM crawl_anchors WHERE crawl_id = ?1 AND source = ?2",
+                )?;
+                let mut anchor_stmt = tx.prepare_cached(
+                    "INSERT INTO crawl_anchors (crawl_id, source, target, anchor)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )?;
+                for (source, page_anchors) in &anchors {
+                    clear_anchors.execute(params![crawl_id, source])?;
+                    for (target, anchor) in page_anchors {
+                        anchor_stmt.execute(params![crawl_id, source, target, anchor])?;
+                    }
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn insert_broken_links(
+        &self,
+        crawl_id: i64,
+        links: &[BrokenLink],
+    ) -> Result<(), DatabaseError> {
+        let rows = links
+            .iter()
+            .map(|link| Ok((link.url.clone(), serde_json::to_string(link)?)))
+            .collect::<Result<Vec<_>, DatabaseError>>()?;
+
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT OR REPLACE INTO crawl_broken_links (crawl_id, url, data)
+                     VALUES (?1, ?2, ?3)",
+                )?;
+                for (url, data) in &rows {
+                    stmt.execute(params![crawl_id, url, data])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn replace_issues(
+        &self,
+        crawl_id: i64,
+        issues: &[Issue],
+    ) -> Result<(), DatabaseError> {
+        let rows: Vec<(&'static str, usize, Vec<String>)> = issues
+            .iter()
+            .map(|issue| (issue.id.id(), issue.count, issue.urls.clone()))
+            .collect();
+
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let tx = conn.transaction()?;
+            tx.execute(
+                "DELETE FROM crawl_issues WHERE crawl_id = ?1",
+                params![crawl_id],
+            )?;
+            tx.execute(
+                "DELETE FROM crawl_issue_urls WHERE crawl_id = ?1",
+                params![crawl_id],
+            )?;
+            {
+                let mut counts = tx.prepare_cached(
+                    "INSERT INTO crawl_issues (crawl_id, issue_id, count) VALUES (?1, ?2, ?3)",
+                )?;
+                let mut urls = tx.prepare_cached(
+                    "INSERT OR IGNORE INTO crawl_issue_urls (crawl_id, issue_id, url)
+                     VALUES (?1, ?2, ?3)",
+                )?;
+                for (issue_id, count, affected) in &rows {
+                    counts.execute(params![crawl_id, issue_id, *count as i64])?;
+                    for url in affected {
+                        urls.execute(params![crawl_id, issue_id, url])?;
+                    }
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// The issues of a crawl with every affected URL, worst first.
+    pub async fn issues(&self, crawl_id: i64) -> Result<Vec<Issue>, DatabaseError> {
+        let pool = self.db.get_pool();
+        let rows = tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt =
+                conn.prepare("SELECT issue_id, url FROM crawl_issue_urls WHERE crawl_id = ?1")?;
+            let rows = stmt
+                .query_map(params![crawl_id], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok::<_, DatabaseError>(rows)
+        })
+        .await??;
+
+        // IDs of issue kinds that no longer exist are skipped
+        let mut registry = IssueRegistry::default();
+        for (issue_id, url) in rows {
+            if let Some(kind) = IssueKind::from_id(&issue_id) {
+                registry.flag(kind, &url);
+            }
+        }
+        Ok(registry.into_issues())
+    }
+
+    /// The issue counts of every crawl of a domain as (crawl id, issue id, count).
+    pub async fn issue_counts(
+        &self,
+        domain: &str,
+    ) -> Result<Vec<(i64, String, usize)>, DatabaseError> {
+        let pool = self.db.get_pool();
+        let domain = domain.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT i.crawl_id, i.issue_id, i.count FROM crawl_issues i
+                 JOIN crawls c ON c.id = i.crawl_id
+                 WHERE c.domain = ?1",
+            )?;
+            let counts = stmt
+                .query_map(params![domain], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get::<_, i64>(2)? as usize))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(counts)
+        })
+        .await?
+    }
+
+    /// The internal links of a crawl as (source, target) pairs.
+    pub async fn links(&self, crawl_id: i64) -> Result<Vec<(String, String)>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt =
+                conn.prepare("SELECT source, target FROM crawl_links WHERE crawl_id = ?1")?;
+            let links = stmt
+                .query_map(params![crawl_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(links)
+        })
+        .await?
+    }
+
+    /// The anchor texts of a crawl as (source, target, anchor) triples.
+    pub async fn anchors(
+        &self,
+        crawl_id: i64,
+    ) -> Result<Vec<(String, String, String)>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt = conn
+                .prepare("SELECT source, target, anchor FROM crawl_anchors WHERE crawl_id = ?1")?;
+            let anchors = stmt
+                .query_map(params![crawl_id], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(anchors)
+        })
+        .await?
+    }
+
+    pub async fn insert_sitemap_entries(
+        &self,
+        crawl_id: i64,
+        entries: &[SitemapEntry],
+    ) -> Result<(), DatabaseError> {
+        let rows: Vec<(String, String)> = entries
+            .iter()
+            .map(|entry| (entry.loc.clone(), entry.sitemap.clone()))
+            .collect();
+
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT OR IGNORE INTO crawl_sitemap_urls (crawl_id, url, sitemap)
+                     VALUES (?1, ?2, ?3)",
+                )?;
+                for (url, sitemap) in &rows {
+                    stmt.execute(params![crawl_id, url, sitemap])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// The sitemap URLs of a crawl as (url, sitemap) pairs.
+    pub async fn sitemap_urls(
+        &self,
+        crawl_id: i64,
+    ) -> Result<Vec<(String, String)>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt =
+                conn.prepare("SELECT url, sitemap FROM crawl_sitemap_urls WHERE crawl_id = ?1")?;
+            let urls = stmt
+                .query_map(params![crawl_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(urls)
+        })
+        .await?
+    }
+
+    pub async fn broken_links(&self, crawl_id: i64) -> Result<Vec<BrokenLink>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt =
+                conn.prepare("SELECT data FROM crawl_broken_links WHERE crawl_id = ?1")?;
+            let rows = stmt.query_map(params![crawl_id], |row| row.get::<_, String>(0))?;
+
+            let mut links = Vec::new();
+            for data in rows {
+                links.push(serde_json::from_str(&data?)?);
+            }
+            Ok(links)
+        })
+        .await?
+    }
+
+    pub async fn insert_psi(
+        &self,
+        crawl_id: i64,
+        scores: &[PsiScores],
+    ) -> Result<(), DatabaseError> {
+        let rows = scores
+            .iter()
+            .map(|score| {
+                Ok((
+                    score.url.clone(),
+                    score.strategy.clone(),
+                    score.fetched_at.to_rfc3339(),
+                    score.error.is_some(),
+                    serde_json::to_string(score)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, DatabaseError>>()?;
+
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT OR REPLACE INTO crawl_psi (crawl_id, url, strategy, fetched_at, failed, data)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )?;
+                for (url, strategy, fetched_at, failed, data) in &rows {
+                    stmt.execute(params![crawl_id, url, strategy, fetched_at, failed, data])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn psi_scores(&self, crawl_id: i64) -> Result<Vec<PsiScores>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt = conn
+                .prepare("SELECT data FROM crawl_psi WHERE crawl_id = ?1 ORDER BY url, strategy")?;
+            let rows = stmt.query_map(params![crawl_id], |row| row.get::<_, String>(0))?;
+
+            let mut scores = Vec::new();
+            for data in rows {
+                scores.push(serde_json::from_str(&data?)?);
+            }
+            Ok(scores)
+        })
+        .await?
+    }
+
+    /// The newest successful scores of a URL from any crawl, if younger than `max_age`.
+    pub async fn cached_psi(
+        &self,
+        url: &str,
+        strategy: &str,
+        max_age: chrono::Duration,
+    ) -> Result<Option<PsiScores>, DatabaseError> {
+        let pool = self.db.get_pool();
+        let url = url.to_string();
+        let strategy = strategy.to_string();
+        let since = (chrono::Utc::now() - max_age).to_rfc3339();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let data: Option<String> = conn
+                .query_row(
+                    "SELECT data FROM crawl_psi
+                     WHERE url = ?1 AND strategy = ?2 AND failed = 0 AND fetched_at >= ?3
+                     ORDER BY fetched_at DESC LIMIT 1",
+                    params![url, strategy, since],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(data.map(|data| serde_json::from_str(&data)).transpose()?)
+        })
+        .await?
+    }
+
+    /// Replaces the Search Console rows stored for a crawl.
+    pub async fn replace_gsc_rows(
+        &self,
+        crawl_id: i64,
+        rows: &[GscRow],
+    ) -> Result<(), DatabaseError> {
+        let rows = rows.to_vec();
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM crawl_gsc WHERE crawl_id = ?1", params![crawl_id])?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT INTO crawl_gsc (crawl_id, page, query, clicks, impressions, ctr, position)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )?;
+                for row in &rows {
+                    stmt.execute(params![
+                        crawl_id,
+                        row.page,
+                        row.query,
+                        row.clicks,
+                        row.impressions,
+                        row.ctr,
+                        row.position
+                    ])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn gsc_rows(&self, crawl_id: i64) -> Result<Vec<GscRow>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT page, query, clicks, impressions, ctr, position
+                 FROM crawl_gsc WHERE crawl_id = ?1",
+            )?;
+            let rows = stmt
+                .query_map(params![crawl_id], |row| {
+                    Ok(GscRow {
+                        page: row.get(0)?,
+                        query: row.get(1)?,
+                        clicks: row.get(2)?,
+                        impressions: row.get(3)?,
+                        ctr: row.get(4)?,
+                        position: row.get(5)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })
+        .await?
+    }
+
+    /// Replaces the GA4 landing page rows stored for a crawl.
+    pub async fn replace_ga4_rows(
+        &self,
+        crawl_id: i64,
+        rows: &[Ga4Row],
+    ) -> Result<(), DatabaseError> {
+        let rows = rows.to_vec();
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM crawl_ga4 WHERE crawl_id = ?1", params![crawl_id])?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT INTO crawl_ga4 (crawl_id, landing_page, sessions, engaged_sessions, average_session_duration, conversions)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )?;
+                for row in &rows {
+                    stmt.execute(params![
+                        crawl_id,
+                        row.landing_page,
+                        row.sessions,
+                        row.engaged_sessions,
+                        row.average_session_duration,
+                        row.conversions
+                    ])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn ga4_rows(&self, crawl_id: i64) -> Result<Vec<Ga4Row>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT landing_page, sessions, engaged_sessions, average_session_duration, conversions
+                 FROM crawl_ga4 WHERE crawl_id = ?1",
+            )?;
+            let rows = stmt
+                .query_map(params![crawl_id], |row| {
+                    Ok(Ga4Row {
+                        landing_page: row.get(0)?,
+                        sessions: row.get(1)?,
+                        engaged_sessions: row.get(2)?,
+                        average_session_duration: row.get(3)?,
+                        conversions: row.get(4)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })
+        .await?
+    }
+
+    /// Replaces the backlinks pulled for a crawl.
+    pub async fn replace_backlinks(
+        &self,
+        crawl_id: i64,
+        links: &[Backlink],
+    ) -> Result<(), DatabaseError> {
+        let links = links.to_vec();
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            let tx = conn.transaction()?;
+            tx.execute(
+                "DELETE FROM crawl_backlinks WHERE crawl_id = ?1",
+                params![crawl_id],
+            )?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT INTO crawl_backlinks (crawl_id, source_url, target_url, anchor, nofollow, source_authority, first_seen)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )?;
+                for link in &links {
+                    stmt.execute(params![
+                        crawl_id,
+                        link.source_url,
+                        link.target_url,
+                        link.anchor,
+                        link.nofollow,
+                        link.source_authority,
+                        link.first_seen
+                    ])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn backlinks(&self, crawl_id: i64) -> Result<Vec<Backlink>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT source_url, target_url, anchor, nofollow, source_authority, first_seen
+                 FROM crawl_backlinks WHERE crawl_id = ?1",
+            )?;
+            let links = stmt
+                .query_map(params![crawl_id], |row| {
+                    Ok(Backlink {
+                        source_url: row.get(0)?,
+                        target_url: row.get(1)?,
+                        anchor: row.get(2)?,
+                        nofollow: row.get(3)?,
+                        source_authority: row.get(4)?,
+                        first_seen: row.get(5)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(links)
+        })
+        .await?
+    }
+
+    /// Files `competitor` under `domain`, both as bare hosts.
+    pub async fn add_competitor(
+        &self,
+        domain: &str,
+        competitor: &str,
+    ) -> Result<(), DatabaseError> {
+        let domain = domain.to_string();
+        let competitor = competitor.to_string();
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            conn.execute(
+                "INSERT OR IGNORE INTO competitors (domain, competitor) VALUES (?1, ?2)",
+                params![domain, competitor],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn remove_competitor(
+        &self,
+        domain: &str,
+        competitor: &str,
+    ) -> Result<(), DatabaseError> {
+        let domain = domain.to_string();
+        let competitor = competitor.to_string();
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            conn.execute(
+                "DELETE FROM competitors WHERE domain = ?1 AND competitor = ?2",
+                params![domain, competitor],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn competitors(&self, domain: &str) -> Result<Vec<String>, DatabaseError> {
+        let domain = domain.to_string();
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT competitor FROM competitors WHERE domain = ?1 ORDER BY competitor",
+            )?;
+            let competitors = stmt
+                .query_map(params![domain], |row| row.get(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(competitors)
+        })
+        .await?
+    }
+
+    /// Saves the content brief of a page, replacing an earlier one for the same crawl.
+    pub async fn save_brief(
+        &self,
+        crawl_id: i64,
+        brief: &ContentBrief,
+    ) -> Result<(), DatabaseError> {
+        let url = brief.url.clone();
+        let data = serde_json::to_string(brief)?;
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            conn.execute(
+                "INSERT OR REPLACE INTO crawl_briefs (crawl_id, url, data) VALUES (?1, ?2, ?3)",
+                params![crawl_id, url, data],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn briefs(&self, crawl_id: i64) -> Result<Vec<ContentBrief>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt =
+                conn.prepare("SELECT data FROM crawl_briefs WHERE crawl_id = ?1 ORDER BY url")?;
+            let rows = stmt.query_map(params![crawl_id], |row| row.get::<_, String>(0))?;
+
+            let mut briefs = Vec::new();
+            for data in rows {
+                briefs.push(serde_json::from_str(&data?)?);
+            }
+            Ok(briefs)
+        })
+        .await?
+    }
+
+    /// Files a crawl-level report under its crawl, replacing the one stored before.
+    pub async fn save_report<R: StoredReport>(
+        &self,
+        crawl_id: i64,
+        report: &R,
+    ) -> Result<(), DatabaseError> {
+        let data = serde_json::to_string(report)?;
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            conn.execute(
+                "INSERT OR REPLACE INTO crawl_reports (crawl_id, kind, data) VALUES (?1, ?2, ?3)",
+                params![crawl_id, R::KIND, data],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn report<R: StoredReport>(&self, crawl_id: i64) -> Result<Option<R>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let data: Option<String> = conn
+                .query_row(
+                    "SELECT data FROM crawl_reports WHERE crawl_id = ?1 AND kind = ?2",
+                    params![crawl_id, R::KIND],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(data.map(|data| serde_json::from_str(&data)).transpose()?)
+        })
+        .await?
+    }
+
+    pub async fn crawl(&self, crawl_id: i64) -> Result<Option<CrawlRecord>, DatabaseError> {
+        Ok(self
+            .list_crawls()
+            .await?
+            .into_iter()
+            .find(|crawl| crawl.id == crawl_id))
+    }
+
+    /// Every stored page of a crawl with its full payload, in URL order.
+    pub async fn pages(&self, crawl_id: i64) -> Result<Vec<DomainCrawlResults>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt =
+                conn.prepare("SELECT data FROM crawl_pages WHERE crawl_id = ?1 ORDER BY url")?;
+            let rows = stmt
+                .query_map(params![crawl_id], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.iter()
+                .map(|data| Ok(serde_json::from_str(data)?))
+                .collect()
+        })
+        .await?
+    }
+
+    /// The flat columns of every page of a crawl, without the full JSON payload.
+    pub async fn page_rows(&self, crawl_id: i64) -> Result<Vec<PageRow>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT url, status_code, title, description, h1, word_count, response_time,
+                    content_type, content_length, indexability
+                 FROM crawl_pages WHERE crawl_id = ?1 ORDER BY url",
+            )?;
+            let rows = stmt
+                .query_map(params![crawl_id], read_page_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })
+        .await?
+    }
+
+    /// Calls `f` on every stored page of a crawl, one row at a time, in URL order.
+    ///
+    /// Runs on a blocking thread so `f` may do synchronous IO. Returns the number of pages visited.
+    pub async fn for_each_page<F>(&self, crawl_id: i64, mut f: F) -> Result<usize, String>
+    where
+        F: FnMut(DomainCrawlResults) -> Result<(), String> + Send + 'static,
+    {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get().map_err(|e| e.to_string())?;
+            let mut stmt = conn
+                .prepare("SELECT data FROM crawl_pages WHERE crawl_id = ?1 ORDER BY url")
+                .map_err(|e| e.to_string())?;
+            let mut rows = stmt.query(params![crawl_id]).map_err(|e| e.to_string())?;
+
+            let mut count = 0;
+            while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+                let data: String = row.get(0).map_err(|e| e.to_string())?;
+                let page = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+                f(page)?;
+                count += 1;
+            }
+            Ok(count)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    pub async fn list_crawls(&self) -> Result<Vec<CrawlRecord>, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT c.id, c.domain, c.status, c.started_at, c.finished_at,
+                    (SELECT COUNT(*) FROM crawl_pages p WHERE p.crawl_id = c.id),
+                    c.user_agent_profile, c.user_agent
+                 FROM crawls c ORDER BY c.id DESC",
+            )?;
+            let crawls = stmt
+                .query_map([], |row| {
+                    Ok(CrawlRecord {
+                        id: row.get(0)?,
+                        domain: row.get(1)?,
+                        status: row.get(2)?,
+                        started_at: row.get(3)?,
+                        finished_at: row.get(4)?,
+                        pages: row.get(5)?,
+                        user_agent_profile: row.get(6)?,
+                        user_agent: row.get(7)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(crawls)
+        })
+        .await?
+    }
+
+    pub async fn query_pages(
+        &self,
+        crawl_id: i64,
+        query: PageQuery,
+    ) -> Result<PageSlice, DatabaseError> {
+        let pool = self.db.get_pool();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+
+            let mut filters = vec!["crawl_id = ?".to_string()];
+            let mut values: Vec<Box<dyn ToSql>> = vec![Box::new(crawl_id)];
+
+            if let Some(search) = query.search.as_ref().filter(|s| !s.trim().is_empty()) {
+                filters.push("(url LIKE ? OR title LIKE ? OR description LIKE ?)".to_string());
+                let pattern = format!("%{}%", search.trim());
+                for _ in 0..3 {
+                    values.push(Box::new(pattern.clone()));
+                }
+            }
+            if let Some(code) = query.status_code {
+                filters.push("status_code = ?".to_string());
+                values.push(Box::new(code));
+            }
+            if let Some(class) = query.status_class {
+                filters.push("status_code / 100 = ?".to_string());
+                values.push(Box::new(class));
+            }
+            if let Some(indexable) = query.indexable {
+                filters.push(if indexable {
+                    "indexability > 0.5".to_string()
+                } else {
+                    "indexability <= 0.5".to_string()
+                });
+            }
+            if let Some(content_type) = &query.content_type {
+                filters.push("content_type LIKE ?".to_string());
+                values.push(Box::new(format!("%{}%", content_type)));
+            }
+
+            let where_clause = filters.join(" AND ");
+
+            let total: usize = conn.query_row(
+                &format!("SELECT COUNT(*) FROM crawl_pages WHERE {}", where_clause),
+                params_from_iter(values.iter()),
+                |row| row.get(0),
+            )?;
+
+            let order = query
+                .sort_by
+                .as_deref()
+                .and_then(sort_column)
+                .unwrap_or("url");
+            let direction = if query.descending { "DESC" } else { "ASC" };
+            let page_size = match query.page_size {
+                0 => 100,
+                size => size.min(MAX_PAGE_SIZE),
+            };
+
+            // A page past what SQLite can offset is past the last row as well
+            let Some(offset) = query
+                .page
+                .checked_mul(page_size)
+                .filter(|&offset| offset < total)
+            else {
+                return Ok(PageSlice {
+                    total,
+                    page: query.page,
+                    page_size,
+                    rows: Vec::new(),
+                });
+            };
+
+            let sql = format!(
+                "SELECT url, status_code, title, description, h1, word_count, response_time,
+                    content_type, content_length, indexability
+                 FROM crawl_pages WHERE {} ORDER BY {} {} LIMIT {} OFFSET {}",
+                where_clause, order, direction, page_size, offset
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt
+                .query_map(params_from_iter(values.iter()), read_page_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(PageSlice {
+                total,
+                page: query.page,
+                page_size,
+                rows,
+            })
+        })
+        .await?
+    }
+
+    /// Loads the full stored result of one page.
+    pub async fn get_page(
+        &self,
+        crawl_id: i64,
+        url: &str,
+    ) -> Result<Option<DomainCrawlResults>, DatabaseError> {
+        let pool = self.db.get_pool();
+        let url = url.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            let data: Option<String> = conn
+                .query_row(
+                    "SELECT data FROM crawl_pages WHERE crawl_id = ?1 AND url = ?2",
+                    params![crawl_id, url],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(data.map(|data| serde_json::from_str(&data)).transpose()?)
+        })
+        .await?
+    }
+}
+
+fn read_page_row(row: &rusqlite::Row) -> rusqlite::Result<PageRow> {
+    Ok(PageRow {
+        url: row.get(0)?,
+        status_code: row.get(1)?,
+        title: row.get(2)?,
+        description: row.get(3)?,
+        h1: row.get(4)?,
+        word_count: row.get(5)?,
+        response_time: row.get(6)?,
+        content_type: row.get(7)?,
+        content_length: row.get(8)?,
+        indexability: row.get(9)?,
+    })
+}
+
+/// Distinct internal link targets of a page, resolved against its URL.
+pub(crate) fn internal_link_targets(page: &DomainCrawlResults) -> Vec<String> {
+    let (Some(links), Ok(page_url)) = (&page.anchor_links, Url::parse(&page.url)) else {
+        return Vec::new();
+    };
+    let mut targets: Vec<String> = resolved_internal_links(links, &page_url)
+        .into_iter()
+        .map(|url| url.to_string())
+        .filter(|target| *target != page.url)
+        .collect();
+    targets.sort();
+    targets.dedup();
+    targets
+}
+
+/// Internal link targets of a page with their anchor texts, self links excluded.
+pub(crate) fn internal_anchors(page: &DomainCrawlResults) -> Vec<(String, String)> {
+    let (Some(links), Ok(page_url)) = (&page.anchor_links, Url::parse(&page.url)) else {
+        return Vec::new();
+    };
+    resolved_internal_anchors(links, &page_url)
+        .into_iter()
+        .map(|(url, anchor)| (url.to_string(), anchor))
+        .filter(|(target, _)| *target != page.url)
+        .collect()
+}
+
+pub(crate) fn to_page_row(page: &DomainCrawlResults) -> PageRow {
+    PageRow {
+        url: page.url.clone(),
+        status_code: page.status_code,
+        title: page
+            .title
+            .as_ref()
+            .and_then(|titles| titles.first())
+            .map(|title| title.title.clone()),
+        description: page.description.clone(),
+        h1: page.headings.get("h1").and_then(|h1| h1.first()).cloned(),
+        word_count: page.word_count,
+        response_time: page.response_time,
+        content_type: page.content_type.clone(),
+        content_length: page.content_length,
+        indexability: page.indexability.indexability,
+    }
+}
+
+// LIST THE STORED CRAWLS
+#[tauri::command]
+pub async fn list_stored_crawls() -> Result<Vec<CrawlRecord>, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    store.list_crawls().await.map_err(|e| e.to_string())
+}
+
+// PAGE, FILTER AND SORT THE RESULTS OF A STORED CRAWL
+#[tauri::command]
+pub async fn query_crawl_results(crawl_id: i64, query: PageQuery) -> Result<PageSlice, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    store
+        .query_pages(crawl_id, query)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// GET THE FULL RESULT OF A SINGLE PAGE
+#[tauri::command]
+pub async fn get_crawl_page(crawl_id: i64, url: String) -> Result<DomainCrawlResults, String> {
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    store
+        .get_page(crawl_id, &url)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No stored result for {}", url))
+}
@@ -0,0 +1,388 @@
+use std::{fs, path::PathBuf};
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, TimeZone, Utc};
+use directories::ProjectDirs;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tokio::sync::Mutex;
+use tokio::time::{interval, Duration};
+use uuid::Uuid;
+
+use super::crawl_control::{self, CrawlGuard};
+use super::domain_commands;
+use crate::email::report::{self, ReportAttachment};
+use crate::projects::registry::{self, ProjectRoots};
+use crate::settings::settings::load_settings_from;
+
+// How often the scheduler looks for due crawls
+const TICK: Duration = Duration::from_secs(60);
+// Keep the history of each schedule bounded
+const MAX_HISTORY: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+}
+
+/// One finished scheduled crawl.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledRun {
+    pub crawl_id: Option<i64>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub pages: usize,
+    pub error: Option<String>,
+}
+
+/// A recurring crawl of one project's domain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawlSchedule {
+    pub id: String,
+    /// Id of the project the schedule crawls with, empty for the default workspace
+    pub project: String,
+    pub domain: String,
+    pub frequency: Frequency,
+    /// Local hour of the day the crawl starts at
+    pub hour: u32,
+    /// Day of the week for weekly crawls, 0 is Monday
+    pub weekday: Option<u32>,
+    pub enabled: bool,
+    pub next_run: DateTime<Utc>,
+    /// Crawl profile the crawl runs with, the current settings when unset
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Recipients of the crawl summary, no email when empty
+    #[serde(default)]
+    pub email_to: Vec<String>,
+    #[serde(default)]
+    pub email_attachment: Option<ReportAttachment>,
+    #[serde(default)]
+    pub history: Vec<ScheduledRun>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ScheduledCrawlEvent {
+    schedule_id: String,
+    project: String,
+    domain: String,
+    crawl_id: Option<i64>,
+    pages: usize,
+    error: Option<String>,
+}
+
+// Serialises reads and writes of the schedules file
+static SCHEDULES_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+impl CrawlSchedule {
+    /// The first start time strictly after `after` that matches the schedule.
+    fn next_run_after(&self, after: DateTime<Utc>) -> DateTime<Utc> {
+        let local = after.with_timezone(&Local);
+        let mut day = local.date_naive();
+
+        for _ in 0..15 {
+            let weekday_matches = match self.frequency {
+                Frequency::Daily => true,
+                Frequency::Weekly => {
+                    day.weekday().num_days_from_monday() == self.weekday.unwrap_or(0)
+                }
+            };
+
+            if weekday_matches {
+                if let Some(start) = day
+                    .and_hms_opt(self.hour, 0, 0)
+                    .and_then(|start| Local.from_local_datetime(&start).earliest())
+                {
+                    let start = start.with_timezone(&Utc);
+                    if start > after {
+                        return start;
+                    }
+                }
+            }
+
+            day += ChronoDuration::days(1);
+        }
+
+        after + ChronoDuration::days(1)
+    }
+}
+
+// Each project keeps its own schedules, None is the default workspace
+fn schedules_path(project: Option<&str>) -> Result<PathBuf, String> {
+    let dirs = ProjectDirs::from("", "", "rustyseo").ok_or("Failed to get config directory")?;
+    let config_dir = registry::config_dir_of(&dirs, project);
+    fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+    Ok(config_dir.join("crawl_schedules.json"))
+}
+
+fn read_schedules(project: Option<&str>) -> Result<Vec<CrawlSchedule>, String> {
+    let path = schedules_path(project)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse schedules: {}", e))
+}
+
+fn write_schedules(project: Option<&str>, schedules: &[CrawlSchedule]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(schedules).map_err(|e| e.to_string())?;
+    fs::write(schedules_path(project)?, json).map_err(|e| e.to_string())
+}
+
+fn workspaces() -> Result<Vec<Option<String>>, String> {
+    Ok(std::iter::once(None)
+        .chain(
+            registry::load_index()?
+                .projects
+                .into_iter()
+                .map(|project| Some(project.id)),
+        )
+        .collect())
+}
+
+// The project id a schedule is added to, the active project when none is given
+fn resolve_project(project: &str) -> Result<Option<String>, String> {
+    let project = project.trim();
+    if project.is_empty() {
+        return Ok(registry::active_project());
+    }
+    registry::load_index()?
+        .projects
+        .into_iter()
+        .find(|p| p.id == project || p.name.eq_ignore_ascii_case(project))
+        .map(|p| Some(p.id))
+        .ok_or_else(|| format!("Project {} not found", project))
+}
+
+/// Starts the background loop that runs due crawls, called once from the app setup.
+pub fn start(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(TICK);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = run_due_schedules(&app_handle).await {
+                eprintln!("Crawl scheduler error: {}", e);
+            }
+        }
+    });
+}
+
+async fn run_due_schedules(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let now = Utc::now();
+    let due = {
+        let _guard = SCHEDULES_LOCK.lock().await;
+        let mut due = None;
+        for project in workspaces()? {
+            if let Some(schedule) = read_schedules(project.as_deref())?
+                .into_iter()
+                .find(|schedule| schedule.enabled && schedule.next_run <= now)
+            {
+                due = Some((project, schedule));
+                break;
+            }
+        }
+        due
+    };
+
+    // One crawl at a time, a due schedule waits for the running crawl to finish
+    let Some((project, schedule)) = due else {
+        return Ok(());
+    };
+    let Ok(crawl) = crawl_control::try_start() else {
+        return Ok(());
+    };
+
+    run_schedule(app_handle, project.as_deref(), schedule, crawl).await
+}
+
+async fn run_schedule(
+    app_handle: &tauri::AppHandle,
+    project: Option<&str>,
+    schedule: CrawlSchedule,
+    crawl: CrawlGuard,
+) -> Result<(), String> {
+    println!(
+        "Running scheduled crawl of {} for {}",
+        schedule.domain, schedule.project
+    );
+
+    // The crawl runs with the settings and databases of the schedule's project, the
+    // project open in the app stays as it is
+    let started_at = Utc::now();
+    let roots = ProjectRoots::of(project)?;
+    let settings = load_settings_from(&roots.config_path).await?;
+    let result = domain_commands::crawl_with_profile(
+        &schedule.domain,
+        app_handle.clone().into(),
+        &roots,
+        settings.clone(),
+        false,
+        schedule.profile.as_deref(),
+        crawl,
+    )
+    .await;
+    let crawl_id = result.as_ref().ok().and_then(|outcome| outcome.crawl_id);
+
+    // A failed email does not fail the crawl, it is logged like the crawl errors
+    if let (Ok(_), Some(crawl_id)) = (&result, crawl_id) {
+        if !schedule.email_to.is_empty() {
+            if let Err(e) = report::send_crawl_report(
+                &roots,
+                &settings,
+                &schedule.email_to,
+                crawl_id,
+                schedule.email_attachment,
+            )
+            .await
+            {
+                eprintln!("Failed to email the crawl of {}: {}", schedule.domain, e);
+            }
+        }
+    }
+
+    let run = ScheduledRun {
+        crawl_id,
+        started_at,
+        finished_at: Utc::now(),
+        pages: result.as_ref().map(|outcome| outcome.pages).unwrap_or(0),
+        error: result.err(),
+    };
+
+    {
+        let _guard = SCHEDULES_LOCK.lock().await;
+        let mut schedules = read_schedules(project)?;
+        if let Some(stored) = schedules.iter_mut().find(|s| s.id == schedule.id) {
+            stored.next_run = stored.next_run_after(Utc::now());
+            stored.history.push(run.clone());
+            if stored.history.len() > MAX_HISTORY {
+                let excess = stored.history.len() - MAX_HISTORY;
+                stored.history.drain(..excess);
+            }
+        }
+        write_schedules(project, &schedules)?;
+    }
+
+    let event = ScheduledCrawlEvent {
+        schedule_id: schedule.id,
+        project: schedule.project,
+        domain: schedule.domain,
+        crawl_id: run.crawl_id,
+        pages: run.pages,
+        error: run.error,
+    };
+    app_handle
+        .emit("scheduled_crawl_complete", event)
+        .map_err(|e| format!("Failed to emit event: {}", e))
+}
+
+fn recipients(addresses: Vec<String>) -> Result<Vec<String>, String> {
+    let addresses: Vec<String> = addresses
+        .into_iter()
+        .map(|address| address.trim().to_string())
+        .filter(|address| !address.is_empty())
+        .collect();
+    for address in &addresses {
+        crate::email::message::check_mailbox(address)?;
+    }
+    Ok(addresses)
+}
+
+#[tauri::command]
+pub async fn list_crawl_schedules() -> Result<Vec<CrawlSchedule>, String> {
+    let _guard = SCHEDULES_LOCK.lock().await;
+    read_schedules(registry::active_project().as_deref())
+}
+
+#[tauri::command]
+pub async fn add_crawl_schedule(
+    project: String,
+    domain: String,
+    frequency: Frequency,
+    hour: u32,
+    weekday: Option<u32>,
+    profile: Option<String>,
+) -> Result<CrawlSchedule, String> {
+    if hour > 23 {
+        return Err("Hour must be between 0 and 23".to_string());
+    }
+    if frequency == Frequency::Weekly && weekday.map_or(true, |day| day > 6) {
+        return Err("Weekly schedules need a weekday between 0 (Monday) and 6".to_string());
+    }
+    let workspace = resolve_project(&project)?;
+
+    let mut schedule = CrawlSchedule {
+        id: Uuid::new_v4().to_string(),
+        project: workspace.clone().unwrap_or_default(),
+        domain,
+        frequency,
+        hour,
+        weekday,
+        enabled: true,
+        next_run: Utc::now(),
+        profile: profile.filter(|name| !name.trim().is_empty()),
+        email_to: Vec::new(),
+        email_attachment: None,
+        history: Vec::new(),
+    };
+    schedule.next_run = schedule.next_run_after(Utc::now());
+
+    let _guard = SCHEDULES_LOCK.lock().await;
+    let mut schedules = read_schedules(workspace.as_deref())?;
+    schedules.push(schedule.clone());
+    write_schedules(workspace.as_deref(), &schedules)?;
+
+    Ok(schedule)
+}
+
+#[tauri::command]
+pub async fn remove_crawl_schedule(id: String) -> Result<(), String> {
+    let _guard = SCHEDULES_LOCK.lock().await;
+    let project = registry::active_project();
+    let mut schedules = read_schedules(project.as_deref())?;
+    schedules.retain(|schedule| schedule.id != id);
+    write_schedules(project.as_deref(), &schedules)
+}
+
+#[tauri::command]
+pub async fn set_crawl_schedule_enabled(id: String, enabled: bool) -> Result<(), String> {
+    let _guard = SCHEDULES_LOCK.lock().await;
+    let project = registry::active_project();
+    let mut schedules = read_schedules(project.as_deref())?;
+    let schedule = schedules
+        .iter_mut()
+        .find(|schedule| schedule.id == id)
+        .ok_or_else(|| format!("Schedule {} not found", id))?;
+
+    schedule.enabled = enabled;
+    // Re-enabling should not fire immediately for runs missed while disabled
+    if enabled {
+        schedule.next_run = schedule.next_run_after(Utc::now());
+    }
+    write_schedules(project.as_deref(), &schedules)
+}
+
+// MAIL THE SUMMARY OF EVERY RUN OF A SCHEDULE, NO RECIPIENTS TURNS IT OFF
+#[tauri::command]
+pub async fn set_crawl_schedule_email(
+    id: String,
+    recipients: Vec<String>,
+    attachment: Option<ReportAttachment>,
+) -> Result<CrawlSchedule, String> {
+    let recipients = self::recipients(recipients)?;
+
+    let _guard = SCHEDULES_LOCK.lock().await;
+    let project = registry::active_project();
+    let mut schedules = read_schedules(project.as_deref())?;
+    let schedule = schedules
+        .iter_mut()
+        .find(|schedule| schedule.id == id)
+        .ok_or_else(|| format!("Schedule {} not found", id))?;
+
+    schedule.email_to = recipients;
+    schedule.email_attachment = attachment;
+    let schedule = schedule.clone();
+    write_schedules(project.as_deref(), &schedules)?;
+    Ok(schedule)
+}
@@ -0,0 +1,234 @@
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use base64::{engine::general_purpose, Engine};
+use directories::ProjectDirs;
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::renderer::{self, Renderer};
+use super::user_agents::GOOGLEBOT_SMARTPHONE;
+use crate::settings::settings::Settings;
+
+const DESKTOP_VIEWPORT: (u32, u32) = (1920, 1080);
+const MOBILE_VIEWPORT: (u32, u32) = (412, 915);
+// Chrome has no full-page flag, pages are shot in a window this tall and the empty bottom cut off
+const FULL_PAGE_HEIGHT: u32 = 8000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ScreenshotViewport {
+    Desktop,
+    Mobile,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ScreenshotKind {
+    AboveTheFold,
+    FullPage,
+}
+
+impl ScreenshotViewport {
+    fn size(&self) -> (u32, u32) {
+        match self {
+            ScreenshotViewport::Desktop => DESKTOP_VIEWPORT,
+            ScreenshotViewport::Mobile => MOBILE_VIEWPORT,
+        }
+    }
+}
+
+fn file_name(viewport: ScreenshotViewport, kind: ScreenshotKind) -> String {
+    let viewport = match viewport {
+        ScreenshotViewport::Desktop => "desktop",
+        ScreenshotViewport::Mobile => "mobile",
+    };
+    let kind = match kind {
+        ScreenshotKind::AboveTheFold => "fold",
+        ScreenshotKind::FullPage => "full",
+    };
+    format!("{}-{}.png", viewport, kind)
+}
+
+/// A stored screenshot of a crawled page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Screenshot {
+    pub viewport: ScreenshotViewport,
+    pub kind: ScreenshotKind,
+    pub width: u32,
+    pub height: u32,
+    pub path: String,
+    /// PNG data URI, ready for an `<img>` tag
+    pub data: String,
+}
+
+struct Capture {
+    // The folder of the crawl, in the project it was started for
+    dir: PathBuf,
+    shots: Vec<(ScreenshotViewport, ScreenshotKind, Renderer)>,
+}
+
+// Set at the start of each crawl, None when screenshots are off
+static CAPTURE: Lazy<RwLock<Option<Capture>>> = Lazy::new(|| RwLock::new(None));
+
+fn screenshots_dir(crawl_id: i64) -> Result<PathBuf, String> {
+    let dirs = ProjectDirs::from("", "", "rustyseo").ok_or("Failed to get project directories")?;
+    Ok(crawl_dir(
+        &crate::projects::registry::data_dir(&dirs),
+        crawl_id,
+    ))
+}
+
+fn crawl_dir(data_dir: &Path, crawl_id: i64) -> PathBuf {
+    data_dir.join("screenshots").join(crawl_id.to_string())
+}
+
+// Stable across runs, unlike the std hasher, so stored screenshots can be found again
+fn url_key(url: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in url.trim_end_matches('/').bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Turns capturing on for a crawl when it renders pages and has an id in the results store,
+/// `crawl` being that id and the data folder of the crawl's project.
+pub fn configure(
+    settings: &Settings,
+    user_agent: &str,
+    crawl: Option<(i64, &Path)>,
+) -> Result<(), String> {
+    // A failed setup leaves screenshots off rather than capturing for the previous crawl
+    *CAPTURE.write().map_err(|e| e.to_string())? = None;
+    let capture = match crawl {
+        Some((crawl_id, data_dir)) if settings.capture_screenshots && renderer::is_active() => {
+            let mut kinds = vec![ScreenshotKind::AboveTheFold];
+            if settings.screenshot_full_page {
+                kinds.push(ScreenshotKind::FullPage);
+            }
+            let mut shots = Vec::new();
+            for viewport in [ScreenshotViewport::Desktop, ScreenshotViewport::Mobile] {
+                // Sniffing sites only serve their mobile layout to a mobile user agent
+                let agent = match viewport {
+                    ScreenshotViewport::Desktop => user_agent,
+                    ScreenshotViewport::Mobile => GOOGLEBOT_SMARTPHONE,
+                };
+                for kind in &kinds {
+                    let (width, height) = viewport.size();
+                    let height = match kind {
+                        ScreenshotKind::AboveTheFold => height,
+                        ScreenshotKind::FullPage => FULL_PAGE_HEIGHT,
+                    };
+                    let renderer = Renderer::new(settings, agent, Some((width, height)))?;
+                    shots.push((viewport, *kind, renderer));
+                }
+            }
+            Some(Capture {
+                dir: crawl_dir(data_dir, crawl_id),
+                shots,
+            })
+        }
+        _ => None,
+    };
+
+    *CAPTURE.write().map_err(|e| e.to_string())? = capture;
+    Ok(())
+}
+
+pub fn is_active() -> bool {
+    CAPTURE.read().map(|c| c.is_some()).unwrap_or(false)
+}
+
+/// Cuts off the rows below the content that only repeat the page's bottom colour.
+fn trim_full_page(path: &Path, min_height: u32) -> Result<(), String> {
+    let image = image::open(path).map_err(|e| e.to_string())?.to_rgba8();
+    let (width, height) = image.dimensions();
+    if height == 0 {
+        return Ok(());
+    }
+    let bottom: Vec<_> = (0..width)
+        .map(|x| *image.get_pixel(x, height - 1))
+        .collect();
+    let content_height = (0..height)
+        .rev()
+        .find(|&y| (0..width).any(|x| *image.get_pixel(x, y) != bottom[x as usize]))
+        .map_or(0, |y| y + 1)
+        .max(min_height)
+        .min(height);
+    if content_height < height {
+        image::imageops::crop_imm(&image, 0, 0, width, content_height)
+            .to_image()
+            .save(path)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Takes the configured screenshots of a rendered page, stored under the crawl and its URL.
+pub async fn capture(page_url: Url) {
+    let (dir, shots) = match CAPTURE.read() {
+        Ok(capture) => match capture.as_ref() {
+            Some(capture) => (
+                capture.dir.join(url_key(page_url.as_str())),
+                capture.shots.clone(),
+            ),
+            None => return,
+        },
+        Err(_) => return,
+    };
+    if let Err(e) = tokio::fs::create_dir_all(&dir).await {
+        eprintln!("Failed to create screenshot directory {:?}: {}", dir, e);
+        return;
+    }
+
+    join_all(shots.into_iter().map(|(viewport, kind, renderer)| {
+        let path = dir.join(file_name(viewport, kind));
+        let page_url = &page_url;
+        async move {
+            let result = match renderer.screenshot(page_url, &path).await {
+                Ok(()) if kind == ScreenshotKind::FullPage => {
+                    let min_height = viewport.size().1;
+                    tokio::task::spawn_blocking(move || trim_full_page(&path, min_height))
+                        .await
+                        .map_err(|e| e.to_string())
+                        .and_then(|trimmed| trimmed)
+                }
+                result => result,
+            };
+            if let Err(e) = result {
+                eprintln!("Screenshot of {} failed: {}", page_url, e);
+            }
+        }
+    }))
+    .await;
+}
+
+// GET THE SCREENSHOTS TAKEN OF A PAGE DURING A CRAWL
+#[tauri::command]
+pub async fn get_page_screenshots(crawl_id: i64, url: String) -> Result<Vec<Screenshot>, String> {
+    let dir = screenshots_dir(crawl_id)?.join(url_key(&url));
+    let mut screenshots = Vec::new();
+    for viewport in [ScreenshotViewport::Desktop, ScreenshotViewport::Mobile] {
+        for kind in [ScreenshotKind::AboveTheFold, ScreenshotKind::FullPage] {
+            let path = dir.join(file_name(viewport, kind));
+            let Ok(bytes) = tokio::fs::read(&path).await else {
+                continue;
+            };
+            let (width, height) = image::image_dimensions(&path).map_err(|e| e.to_string())?;
+            screenshots.push(Screenshot {
+                viewport,
+                kind,
+                width,
+                height,
+                path: path.to_string_lossy().into_owned(),
+                data: format!(
+                    "data:image/png;base64,{}",
+                    general_purpose::STANDARD.encode(bytes)
+                ),
+            });
+        }
+    }
+    Ok(screenshots)
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+use super::helpers::security_headers::{HeaderStatus, SecurityHeader};
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+// Lowest scoring pages listed in the scorecard
+const WORST_PAGES: usize = 50;
+
+/// How many pages send one header, and how well.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeaderCoverage {
+    pub header: SecurityHeader,
+    pub ok: usize,
+    pub weak: usize,
+    pub missing: usize,
+    /// Share of pages with a passing header, 0 to 100
+    pub coverage: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageSecurityScore {
+    pub url: String,
+    pub score: u8,
+    pub missing: Vec<SecurityHeader>,
+}
+
+/// Site-level security header scorecard.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SecurityHeadersReport {
+    pub pages_checked: usize,
+    pub average_score: u8,
+    pub headers: Vec<HeaderCoverage>,
+    pub worst_pages: Vec<PageSecurityScore>,
+}
+
+// The report only keeps the worst pages, so the issues are flagged as the pages come in
+pub fn register_page_issues(result: &DomainCrawlResults, issues: &mut IssueRegistry) {
+    let missing = result
+        .security_headers
+        .checks
+        .iter()
+        .any(|check| check.status == HeaderStatus::Missing);
+    if result.status_code == 200 && missing {
+        issues.flag(IssueKind::MissingSecurityHeaders, &result.url);
+    }
+}
+
+impl StoredReport for SecurityHeadersReport {
+    const KIND: &'static str = "security_headers";
+}
+
+/// Aggregates the per-page header checks into coverage per header and the weakest pages.
+pub struct SecurityHeadersCollector {
+    headers: Vec<HeaderCoverage>,
+    pages: Vec<PageSecurityScore>,
+}
+
+impl Default for SecurityHeadersCollector {
+    fn default() -> Self {
+        Self {
+            headers: SecurityHeader::ALL
+                .iter()
+                .map(|&header| HeaderCoverage {
+                    header,
+                    ok: 0,
+                    weak: 0,
+                    missing: 0,
+                    coverage: 0.0,
+                })
+                .collect(),
+            pages: Vec::new(),
+        }
+    }
+}
+
+impl SecurityHeadersCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        // Redirects and errors are often served by another layer, only pages count
+        if result.status_code != 200 || result.security_headers.checks.is_empty() {
+            return;
+        }
+        let audit = &result.security_headers;
+        for check in &audit.checks {
+            if let Some(coverage) = self.headers.iter_mut().find(|c| c.header == check.header) {
+                match check.status {
+                    HeaderStatus::Ok => coverage.ok += 1,
+                    HeaderStatus::Weak => coverage.weak += 1,
+                    HeaderStatus::Missing => coverage.missing += 1,
+                }
+            }
+        }
+        self.pages.push(PageSecurityScore {
+            url: result.url.clone(),
+            score: audit.score,
+            missing: audit
+                .checks
+                .iter()
+                .filter(|check| check.status == HeaderStatus::Missing)
+                .map(|check| check.header)
+                .collect(),
+        });
+    }
+
+    pub fn finish(self) -> SecurityHeadersReport {
+        let Self {
+            mut headers,
+            mut pages,
+        } = self;
+        let pages_checked = pages.len();
+        if pages_checked > 0 {
+            for coverage in &mut headers {
+                coverage.coverage =
+                    ((coverage.ok as f32 / pages_checked as f32) * 1000.0).round() / 10.0;
+            }
+        }
+        let average_score = match pages_checked {
+            0 => 0,
+            n => (pages.iter().map(|p| p.score as usize).sum::<usize>() / n) as u8,
+        };
+
+        pages.sort_by(|a, b| a.score.cmp(&b.score).then(a.url.cmp(&b.url)));
+        pages.truncate(WORST_PAGES);
+
+        SecurityHeadersReport {
+            pages_checked,
+            average_score,
+            headers,
+            worst_pages: pages.into_iter().filter(|page| page.score < 100).collect(),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::HeaderValue;
+use reqwest::Client;
+use scraper::{Html, Selector};
+use url::Url;
+
+use super::request_auth;
+use crate::settings::settings::Settings;
+
+/// Cookie store shared by every crawler client.
+///
+/// Clients keep the provider they were built with, so the jar behind it is swapped at the
+/// start of each crawl instead of rebuilding the long-lived image client.
+#[derive(Debug, Default)]
+pub struct SessionCookies(RwLock<Arc<Jar>>);
+
+impl SessionCookies {
+    fn jar(&self) -> Arc<Jar> {
+        self.0
+            .read()
+            .map(|jar| jar.clone())
+            .unwrap_or_else(|_| Arc::new(Jar::default()))
+    }
+
+    fn reset(&self) -> Arc<Jar> {
+        let jar = Arc::new(Jar::default());
+        if let Ok(mut current) = self.0.write() {
+            *current = jar.clone();
+        }
+        jar
+    }
+}
+
+impl CookieStore for SessionCookies {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        self.jar().set_cookies(cookie_headers, url)
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        self.jar().cookies(url)
+    }
+}
+
+static SESSION: Lazy<Arc<SessionCookies>> = Lazy::new(|| Arc::new(SessionCookies::default()));
+
+/// The cookie provider to pass to `ClientBuilder::cookie_provider`.
+pub fn cookies() -> Arc<SessionCookies> {
+    SESSION.clone()
+}
+
+/// The session cookies the crawler would send to `url`, as name and value pairs.
+pub fn cookies_for(url: &Url) -> Vec<(String, String)> {
+    let Some(header) = SESSION.cookies(url) else {
+        return Vec::new();
+    };
+    header
+        .to_str()
+        .unwrap_or_default()
+        .split(';')
+        .filter_map(|pair| {
+            let (name, value) = pair.trim().split_once('=')?;
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Parses a Netscape `cookies.txt` export into `Set-Cookie` strings and the URL they belong to.
+fn parse_cookies_file(contents: &str) -> Vec<(String, Url)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            // curl marks HttpOnly cookies with a prefix on an otherwise commented line
+            let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+            if line.trim().is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 7 {
+                return None;
+            }
+            let (domain, path, secure, name, value) =
+                (fields[0], fields[2], fields[3], fields[5], fields[6]);
+            let host = domain.trim_start_matches('.');
+            let scheme = if secure.eq_ignore_ascii_case("TRUE") {
+                "https"
+            } else {
+                "http"
+            };
+            let url = Url::parse(&format!("{}://{}{}", scheme, host, path)).ok()?;
+
+            let mut cookie = format!("{}={}; Path={}", name, value, path);
+            if domain.starts_with('.') {
+                cookie.push_str(&format!("; Domain={}", host));
+            }
+            if scheme == "https" {
+                cookie.push_str("; Secure");
+            }
+            Some((cookie, url))
+        })
+        .collect()
+}
+
+/// Reads the login form at `login_url`, keeps its hidden inputs (CSRF tokens and the like)
+/// and submits it with the configured fields.
+async fn login(client: &Client, login_url: &str, fields: &[String]) -> Result<(), String> {
+    let login_url = Url::parse(login_url).map_err(|e| format!("Invalid login_url: {}", e))?;
+
+    let page = request_auth::apply(client.get(login_url.as_str()), login_url.as_str())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to load the login page: {}", e))?;
+    let page_url = page.url().clone();
+    let body = page
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read the login page: {}", e))?;
+
+    let mut form: Vec<(String, String)> = Vec::new();
+    let mut action = page_url.clone();
+    {
+        let document = Html::parse_document(&body);
+        let form_selector = Selector::parse("form").unwrap();
+        let hidden_selector = Selector::parse("input[type='hidden'][name]").unwrap();
+
+        // The login form is the first one with a password field
+        let password_selector = Selector::parse("input[type='password']").unwrap();
+        if let Some(element) = document
+            .select(&form_selector)
+            .find(|form| form.select(&password_selector).next().is_some())
+        {
+            if let Some(target) = element.value().attr("action") {
+                action = page_url
+                    .join(target)
+                    .map_err(|e| format!("Invalid login form action: {}", e))?;
+            }
+            form = element
+                .select(&hidden_selector)
+                .filter_map(|input| {
+                    let name = input.value().attr("name")?;
+                    Some((
+                        name.to_string(),
+                        input.value().attr("value").unwrap_or_default().to_string(),
+                    ))
+                })
+                .collect();
+        }
+    }
+
+    for field in fields {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("Login field \"{}\" must be written as name=value", field))?;
+        form.retain(|(existing, _)| existing != name.trim());
+        form.push((name.trim().to_string(), value.to_string()));
+    }
+
+    let response = request_auth::apply(client.post(action.as_str()), action.as_str())
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| format!("Login request failed: {}", e))?;
+
+    if response.status().is_client_error() || response.status().is_server_error() {
+        return Err(format!("Login failed with status {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Starts a fresh cookie session for a crawl of `base_url`.
+///
+/// Cookies from `session_cookies` and `cookies_file` are imported first, then the form login
+/// runs when `login_url` is set so the crawl starts already signed in.
+pub async fn start(settings: &Settings, base_url: &Url, client: &Client) -> Result<(), String> {
+    let jar = SESSION.reset();
+
+    for cookie in settings
+        .session_cookies
+        .iter()
+        .filter(|c| !c.trim().is_empty())
+    {
+        jar.add_cookie_str(cookie.trim(), base_url);
+    }
+
+    let cookies_file = settings.cookies_file.trim();
+    if !cookies_file.is_empty() {
+        let contents = tokio::fs::read_to_string(cookies_file)
+            .await
+            .map_err(|e| format!("Failed to read cookies file {}: {}", cookies_file, e))?;
+        for (cookie, url) in parse_cookies_file(&contents) {
+            jar.add_cookie_str(&cookie, &url);
+        }
+    }
+
+    let login_url = settings.login_url.trim();
+    if !login_url.is_empty() {
+        login(client, login_url, &settings.login_fields).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::helpers::anchor_links::resolved_internal_links;
+use super::helpers::canonical_selector::normalise_url;
+use super::helpers::sitemap::SitemapReport;
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SitemapStatusIssue {
+    pub url: String,
+    pub status_code: u16,
+    pub sitemap: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SitemapGapReport {
+    pub sitemap_urls: usize,
+    pub crawled_urls: usize,
+    /// Listed in a sitemap but never linked from a crawled page
+    pub orphan_pages: Vec<String>,
+    /// Found by the crawl but not listed in any sitemap
+    pub missing_from_sitemap: Vec<String>,
+    /// Sitemap entries that did not return a 200
+    pub non_200_entries: Vec<SitemapStatusIssue>,
+}
+
+/// Collects the statuses and internal links of the crawled pages one page at a time.
+#[derive(Default)]
+pub struct SitemapGapCollector {
+    crawled_urls: usize,
+    linked: HashSet<String>,
+    // Status code and whether robots.txt blocked the page
+    statuses: HashMap<String, (u16, bool)>,
+    // Pages that answered 200, as crawled and normalised
+    ok_pages: Vec<(String, Option<String>)>,
+}
+
+impl SitemapGapCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        self.crawled_urls += 1;
+        let page_url = Url::parse(&result.url).ok();
+        if result.status_code == 200 {
+            self.ok_pages
+                .push((result.url.clone(), page_url.as_ref().map(normalise_url)));
+        }
+        let Some(page_url) = page_url else {
+            return;
+        };
+        self.statuses.insert(
+            normalise_url(&page_url),
+            (result.status_code, result.blocked_by_robots),
+        );
+
+        if let Some(links) = &result.anchor_links {
+            for target in resolved_internal_links(links, &page_url) {
+                // Links back to the page itself do not make it discoverable
+                if target != page_url {
+                    self.linked.insert(normalise_url(&target));
+                }
+            }
+        }
+    }
+
+    /// Compares the sitemap entries against what the crawl found and linked to.
+    pub fn finish(self, sitemaps: &SitemapReport) -> SitemapGapReport {
+        let mut report = SitemapGapReport {
+            sitemap_urls: sitemaps.entries.len(),
+            crawled_urls: self.crawled_urls,
+            ..Default::default()
+        };
+
+        let mut in_sitemap = HashSet::new();
+        for entry in &sitemaps.entries {
+            let Ok(url) = Url::parse(&entry.loc) else {
+                continue;
+            };
+            let key = normalise_url(&url);
+            if !in_sitemap.insert(key.clone()) {
+                continue;
+            }
+
+            if !self.linked.contains(&key) {
+                report.orphan_pages.push(entry.loc.clone());
+            }
+
+            if let Some(&(status_code, blocked_by_robots)) = self.statuses.get(&key) {
+                if status_code != 200 && !blocked_by_robots {
+                    report.non_200_entries.push(SitemapStatusIssue {
+                        url: entry.loc.clone(),
+                        status_code,
+                        sitemap: entry.sitemap.clone(),
+                    });
+                }
+            }
+        }
+
+        for (url, key) in self.ok_pages {
+            // URLs that do not parse cannot be matched, they are not reported
+            let in_map = key.map_or(true, |key| in_sitemap.contains(&key));
+            if !in_map {
+                report.missing_from_sitemap.push(url);
+            }
+        }
+
+        report
+    }
+}
+
+impl StoredReport for SitemapGapReport {
+    const KIND: &'static str = "sitemap_gap";
+}
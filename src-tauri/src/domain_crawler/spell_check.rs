@@ -0,0 +1,316 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::{fs, path::PathBuf};
+
+use directories::ProjectDirs;
+use once_cell::sync::Lazy;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::settings::settings::Settings;
+
+const IGNORE_FILE: &str = "spelling_ignore.json";
+const DICTIONARY_DIR: &str = "dictionaries";
+// Characters of surrounding text kept with each occurrence
+const CONTEXT_CHARS: usize = 40;
+
+// Dictionaries and ignore list of the current crawl, None when spell checking is off
+static CHECKER: Lazy<RwLock<Option<Arc<SpellChecker>>>> = Lazy::new(|| RwLock::new(None));
+
+// Serializes edits of the ignore list file
+static IGNORE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SpellingLocation {
+    Title,
+    MetaDescription,
+    Heading,
+    Body,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpellingOccurrence {
+    pub location: SpellingLocation,
+    pub count: usize,
+    /// Text around the first occurrence in this location
+    pub context: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Misspelling {
+    pub word: String,
+    pub occurrences: Vec<SpellingOccurrence>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SpellingAudit {
+    pub language: String,
+    pub checked_words: usize,
+    pub misspellings: Vec<Misspelling>,
+}
+
+#[derive(Default)]
+struct SpellChecker {
+    // Language code to its lowercased words, loaded on first use
+    dictionaries: RwLock<HashMap<String, Option<Arc<HashSet<String>>>>>,
+    ignored: HashSet<String>,
+}
+
+fn config_dir() -> Result<PathBuf, String> {
+    let dirs = ProjectDirs::from("", "", "rustyseo").ok_or("Failed to get config directory")?;
+    let config_dir = dirs.config_dir();
+    fs::create_dir_all(config_dir).map_err(|e| e.to_string())?;
+    Ok(config_dir.to_path_buf())
+}
+
+fn read_ignore_list() -> Result<Vec<String>, String> {
+    let path = config_dir()?.join(IGNORE_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse ignore list: {}", e))
+}
+
+fn write_ignore_list(words: &[String]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(words).map_err(|e| e.to_string())?;
+    fs::write(config_dir()?.join(IGNORE_FILE), json).map_err(|e| e.to_string())
+}
+
+/// Reads `<lang>.dic` or `<lang>.txt` from the dictionaries folder of the config directory.
+///
+/// Both plain word lists and Hunspell `.dic` files are read. Hunspell affix flags are
+/// dropped, so a Hunspell dictionary only knows its stems and a full word list works better.
+fn load_dictionary(language: &str) -> Option<HashSet<String>> {
+    let dir = config_dir().ok()?.join(DICTIONARY_DIR);
+    let content = ["dic", "txt"]
+        .iter()
+        .find_map(|ext| fs::read_to_string(dir.join(format!("{}.{}", language, ext))).ok())?;
+
+    let words: HashSet<String> = content
+        .lines()
+        .filter_map(|line| line.split('/').next())
+        .map(|word| word.trim().to_lowercase())
+        // The first line of a Hunspell file is the word count
+        .filter(|word| !word.is_empty() && !word.chars().all(|c| c.is_ascii_digit()))
+        .collect();
+    (!words.is_empty()).then_some(words)
+}
+
+impl SpellChecker {
+    fn dictionary(&self, language: &str) -> Option<Arc<HashSet<String>>> {
+        if let Some(loaded) = self.dictionaries.read().ok()?.get(language) {
+            return loaded.clone();
+        }
+        let loaded = load_dictionary(language).map(Arc::new);
+        if loaded.is_none() {
+            eprintln!("No spelling dictionary for {}", language);
+        }
+        self.dictionaries
+            .write()
+            .ok()?
+            .insert(language.to_string(), loaded.clone());
+        loaded
+    }
+}
+
+/// Loads the ignore list for a crawl, dictionaries are read when a language first comes up.
+pub fn configure(settings: &Settings) -> Result<(), String> {
+    let checker = if settings.spell_check {
+        let ignored = read_ignore_list()?
+            .into_iter()
+            .map(|word| word.to_lowercase())
+            .collect();
+        Some(Arc::new(SpellChecker {
+            ignored,
+            ..Default::default()
+        }))
+    } else {
+        None
+    };
+    *CHECKER.write().map_err(|e| e.to_string())? = checker;
+    Ok(())
+}
+
+// Acronyms, product names in camel case, numbers, URLs and e-mail addresses are not words
+fn is_checkable(word: &str) -> bool {
+    let mut chars = word.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    word.chars().count() > 2
+        && word
+            .chars()
+            .all(|c| c.is_alphabetic() || c == '\'' || c == '-')
+        && first.is_alphabetic()
+        && !chars.any(char::is_uppercase)
+}
+
+fn known(word: &str, dictionary: &HashSet<String>, ignored: &HashSet<String>) -> bool {
+    let lower = word.to_lowercase();
+    let bare = lower
+        .trim_end_matches("'s")
+        .trim_end_matches('\'')
+        .to_string();
+    let known_word = |w: &str| dictionary.contains(w) || ignored.contains(w);
+    known_word(&lower)
+        || known_word(&bare)
+        || (bare.contains('-')
+            && bare
+                .split('-')
+                .all(|part| part.is_empty() || known_word(part)))
+}
+
+fn context(text: &str, start: usize, end: usize) -> String {
+    let before: String = text[..start]
+        .chars()
+        .rev()
+        .take(CONTEXT_CHARS)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    let after: String = text[end..].chars().take(CONTEXT_CHARS).collect();
+    format!("{}{}{}", before, &text[start..end], after)
+        .trim()
+        .to_string()
+}
+
+/// Checks the spelling of the title, meta description, headings and body text of a page.
+///
+/// # Arguments
+/// * `body` - The HTML content as a string.
+/// * `language` - ISO 639-1 code of the text, picks the dictionary.
+///
+/// # Returns
+/// * `Option<SpellingAudit>` - None when spell checking is off or there is no dictionary.
+pub fn check_page(body: &str, language: Option<&str>) -> Option<SpellingAudit> {
+    let checker = CHECKER.read().ok()?.clone()?;
+    let language = language?.to_lowercase();
+    let dictionary = checker.dictionary(&language)?;
+
+    let document = Html::parse_document(body);
+    let texts = |selector: &str| -> Vec<String> {
+        let selector = Selector::parse(selector).unwrap();
+        document
+            .select(&selector)
+            .map(|element| element.text().collect::<Vec<_>>().join(" "))
+            .collect()
+    };
+    let description_selector = Selector::parse("meta[name='description'][content]").unwrap();
+    let sections: [(SpellingLocation, Vec<String>); 4] = [
+        (SpellingLocation::Title, texts("title")),
+        (
+            SpellingLocation::MetaDescription,
+            document
+                .select(&description_selector)
+                .filter_map(|meta| meta.value().attr("content").map(String::from))
+                .collect(),
+        ),
+        (SpellingLocation::Heading, texts("h1, h2, h3, h4, h5, h6")),
+        (
+            SpellingLocation::Body,
+            texts("p, li, td, th, blockquote, figcaption, dd, dt"),
+        ),
+    ];
+
+    let mut audit = SpellingAudit {
+        language,
+        ..Default::default()
+    };
+    // Word to its occurrences per location
+    let mut found: BTreeMap<String, BTreeMap<SpellingLocation, (usize, String)>> = BTreeMap::new();
+    for (location, texts) in sections {
+        for text in texts {
+            let mut offset = 0;
+            for raw in text.split_whitespace() {
+                let start = offset + text[offset..].find(raw).unwrap_or(0);
+                offset = start + raw.len();
+                let word = raw.trim_matches(|c: char| !c.is_alphanumeric());
+                if !is_checkable(word) {
+                    continue;
+                }
+                audit.checked_words += 1;
+                if known(word, &dictionary, &checker.ignored) {
+                    continue;
+                }
+                let entry = found
+                    .entry(word.to_lowercase())
+                    .or_default()
+                    .entry(location)
+                    .or_insert_with(|| (0, context(&text, start, start + raw.len())));
+                entry.0 += 1;
+            }
+        }
+    }
+
+    audit.misspellings = found
+        .into_iter()
+        .map(|(word, locations)| Misspelling {
+            word,
+            occurrences: locations
+                .into_iter()
+                .map(|(location, (count, context))| SpellingOccurrence {
+                    location,
+                    count,
+                    context,
+                })
+                .collect(),
+        })
+        .collect();
+    Some(audit)
+}
+
+#[tauri::command]
+pub async fn list_spelling_ignore() -> Result<Vec<String>, String> {
+    let _guard = IGNORE_LOCK.lock().await;
+    read_ignore_list()
+}
+
+#[tauri::command]
+pub async fn add_spelling_ignore(words: Vec<String>) -> Result<Vec<String>, String> {
+    let _guard = IGNORE_LOCK.lock().await;
+    let mut ignored = read_ignore_list()?;
+    for word in words {
+        let word = word.trim().to_lowercase();
+        if !word.is_empty() && !ignored.contains(&word) {
+            ignored.push(word);
+        }
+    }
+    ignored.sort();
+    write_ignore_list(&ignored)?;
+    Ok(ignored)
+}
+
+#[tauri::command]
+pub async fn remove_spelling_ignore(word: String) -> Result<Vec<String>, String> {
+    let _guard = IGNORE_LOCK.lock().await;
+    let mut ignored = read_ignore_list()?;
+    let word = word.trim().to_lowercase();
+    ignored.retain(|w| *w != word);
+    write_ignore_list(&ignored)?;
+    Ok(ignored)
+}
+
+/// Lists the languages with a dictionary in the dictionaries folder.
+#[tauri::command]
+pub async fn list_spelling_dictionaries() -> Result<Vec<String>, String> {
+    let dir = config_dir()?.join(DICTIONARY_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut languages: Vec<String> = fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "dic" || ext == "txt")
+        })
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    languages.sort();
+    languages.dedup();
+    Ok(languages)
+}
@@ -0,0 +1,85 @@
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+use reqwest::header::{HeaderMap, USER_AGENT};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use tokio::time::Instant;
+use url::Url;
+
+use super::crawl_cache::CrawlCache;
+use super::helpers::images_selector::{image_client, image_permit};
+use super::rate_limiter::HostRateLimiter;
+use super::{request_auth, user_agents};
+
+// The crawl's per-host pacing, set when it starts
+static RATE_LIMITER: Lazy<RwLock<Option<Arc<HostRateLimiter>>>> = Lazy::new(|| RwLock::new(None));
+
+// HEAD answers of the crawl by URL, a file linked from many pages is asked about once
+static HEADS: Lazy<CrawlCache<Result<Head, String>>> = Lazy::new(CrawlCache::default);
+
+/// What a HEAD request for a file used by the crawled pages answered.
+#[derive(Debug, Clone)]
+pub struct Head {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+}
+
+impl Head {
+    pub fn header(&self, name: reqwest::header::HeaderName) -> Option<String> {
+        self.headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    }
+}
+
+/// Paces the subresource requests of a new crawl with its rate limiter and forgets the
+/// answers of the previous one.
+pub fn configure(rate_limiter: Arc<HostRateLimiter>) {
+    if let Ok(mut current) = RATE_LIMITER.write() {
+        *current = Some(rate_limiter);
+    }
+    HEADS.clear();
+}
+
+/// Sends `request` for `url` like the crawler's own requests: paced per host, with the
+/// crawl's credentials and user agent.
+pub async fn send(request: RequestBuilder, url: &str) -> Result<Response, reqwest::Error> {
+    let rate_limiter = RATE_LIMITER.read().ok().and_then(|limiter| limiter.clone());
+    let paced = rate_limiter.zip(Url::parse(url).ok());
+    if let Some((rate_limiter, url)) = &paced {
+        rate_limiter.acquire(url).await;
+    }
+
+    let started = Instant::now();
+    let response = request_auth::apply(request, url)
+        .header(USER_AGENT, user_agents::current())
+        .send()
+        .await?;
+    if let Some((rate_limiter, url)) = &paced {
+        rate_limiter
+            .record_response(url, started.elapsed(), &response)
+            .await;
+    }
+    Ok(response)
+}
+
+/// HEAD request for `url` through the shared image client, sent once per crawl.
+pub async fn head(url: &str) -> Result<Head, String> {
+    HEADS
+        .get_or_init(url, || async {
+            let _permit = image_permit().await;
+            let response = send(image_client().head(url), url).await.map_err(|e| {
+                if e.is_timeout() {
+                    format!("Timeout while checking {}", url)
+                } else {
+                    format!("Failed to send request for {}: {}", url, e)
+                }
+            })?;
+            Ok(Head {
+                status: response.status(),
+                headers: response.headers().clone(),
+            })
+        })
+        .await
+}
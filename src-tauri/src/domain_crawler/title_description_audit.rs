@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::helpers::title_description::SnippetIssue;
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+use crate::ai::suggestions::SnippetSuggestion;
+
+/// A title or description shared by more than one page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub text: String,
+    pub urls: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageSnippetIssues {
+    pub url: String,
+    pub issues: Vec<SnippetIssue>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub h1: Option<String>,
+    /// Rewrite proposed by the configured AI provider, filled in on request
+    #[serde(default)]
+    pub suggestion: Option<SnippetSuggestion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TitleDescriptionReport {
+    pub pages_checked: usize,
+    pub missing_titles: usize,
+    pub missing_descriptions: usize,
+    pub duplicate_titles: Vec<DuplicateGroup>,
+    pub duplicate_descriptions: Vec<DuplicateGroup>,
+    pub pages: Vec<PageSnippetIssues>,
+}
+
+pub fn register_issues(report: &TitleDescriptionReport, issues: &mut IssueRegistry) {
+    for page in &report.pages {
+        for issue in &page.issues {
+            let kind = match issue {
+                SnippetIssue::MissingTitle => IssueKind::MissingTitle,
+                SnippetIssue::TitleTooShort | SnippetIssue::TitleTooLong => IssueKind::TitleLength,
+                SnippetIssue::MultipleTitles => IssueKind::MultipleTitles,
+                SnippetIssue::DuplicateTitle => IssueKind::DuplicateTitle,
+                SnippetIssue::MissingDescription => IssueKind::MissingDescription,
+                SnippetIssue::DescriptionTooShort | SnippetIssue::DescriptionTooLong => {
+                    IssueKind::DescriptionLength
+                }
+                SnippetIssue::MultipleDescriptions => IssueKind::MultipleDescriptions,
+                SnippetIssue::DuplicateDescription => IssueKind::DuplicateDescription,
+            };
+            issues.flag(kind, &page.url);
+        }
+    }
+}
+
+impl StoredReport for TitleDescriptionReport {
+    const KIND: &'static str = "titles_descriptions";
+}
+
+// Duplicates are matched case-insensitively, largest groups first
+fn duplicate_groups(texts: HashMap<String, (String, Vec<String>)>) -> Vec<DuplicateGroup> {
+    let mut groups: Vec<DuplicateGroup> = texts
+        .into_values()
+        .filter(|(_, urls)| urls.len() > 1)
+        .map(|(text, urls)| DuplicateGroup { text, urls })
+        .collect();
+    groups.sort_by(|a, b| b.urls.len().cmp(&a.urls.len()).then(a.text.cmp(&b.text)));
+    groups
+}
+
+/// Collects the per-page title and description checks one page at a time.
+#[derive(Default)]
+pub struct TitleDescriptionCollector {
+    report: TitleDescriptionReport,
+    titles: HashMap<String, (String, Vec<String>)>,
+    descriptions: HashMap<String, (String, Vec<String>)>,
+    pages: Vec<PageSnippetIssues>,
+}
+
+impl TitleDescriptionCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        // Only pages that rendered as HTML have a snippet to audit
+        if result.status_code != 200 || !result.content_type.contains("text/html") {
+            return;
+        }
+        let audit = &result.title_description;
+        self.report.pages_checked += 1;
+
+        if audit.issues.contains(&SnippetIssue::MissingTitle) {
+            self.report.missing_titles += 1;
+        }
+        if audit.issues.contains(&SnippetIssue::MissingDescription) {
+            self.report.missing_descriptions += 1;
+        }
+
+        if let Some(title) = &audit.title {
+            self.titles
+                .entry(title.text.to_lowercase())
+                .or_insert_with(|| (title.text.clone(), Vec::new()))
+                .1
+                .push(result.url.clone());
+        }
+        if let Some(description) = &audit.description {
+            self.descriptions
+                .entry(description.text.to_lowercase())
+                .or_insert_with(|| (description.text.clone(), Vec::new()))
+                .1
+                .push(result.url.clone());
+        }
+
+        self.pages.push(PageSnippetIssues {
+            url: result.url.clone(),
+            issues: audit.issues.clone(),
+            title: audit.title.as_ref().map(|title| title.text.clone()),
+            description: audit
+                .description
+                .as_ref()
+                .map(|description| description.text.clone()),
+            h1: result
+                .headings
+                .get("h1")
+                .and_then(|h1s| h1s.iter().find(|h1| !h1.is_empty()))
+                .cloned(),
+            suggestion: None,
+        });
+    }
+
+    /// Aggregates the collected checks and finds duplicates across the crawl.
+    pub fn finish(self) -> TitleDescriptionReport {
+        let Self {
+            mut report,
+            titles,
+            descriptions,
+            mut pages,
+        } = self;
+
+        report.duplicate_titles = duplicate_groups(titles);
+        report.duplicate_descriptions = duplicate_groups(descriptions);
+
+        // Flag every page that belongs to a duplicate group
+        let by_url: HashMap<String, usize> = pages
+            .iter()
+            .enumerate()
+            .map(|(i, page)| (page.url.clone(), i))
+            .collect();
+        for (groups, issue) in [
+            (&report.duplicate_titles, SnippetIssue::DuplicateTitle),
+            (
+                &report.duplicate_descriptions,
+                SnippetIssue::DuplicateDescription,
+            ),
+        ] {
+            for url in groups.iter().flat_map(|group| group.urls.iter()) {
+                if let Some(&i) = by_url.get(url) {
+                    pages[i].issues.push(issue.clone());
+                }
+            }
+        }
+
+        report.pages = pages.into_iter().filter(|p| !p.issues.is_empty()).collect();
+
+        report
+    }
+}
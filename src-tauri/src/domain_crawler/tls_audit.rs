@@ -0,0 +1,224 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::helpers::tls_certificate::{self, TlsCertificate};
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TlsIssue {
+    Expired,
+    ExpiresSoon,
+    HostnameMismatch,
+    Untrusted,
+    Unreachable,
+    /// The host still accepts TLS 1.0 or 1.1
+    WeakProtocol,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsWarning {
+    pub host: String,
+    pub issue: TlsIssue,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TlsReport {
+    /// Certificates expiring within this many days are flagged
+    pub expiry_warning_days: i64,
+    pub certificates: Vec<TlsCertificate>,
+    pub warnings: Vec<TlsWarning>,
+}
+
+// The `host:port` a page is served from, as warnings name it
+fn page_host(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    if url.scheme() != "https" {
+        return None;
+    }
+    Some(format!(
+        "{}:{}",
+        url.host_str()?,
+        url.port_or_known_default()?
+    ))
+}
+
+/// Collects the HTTPS hosts of the crawl and the pages served from each.
+#[derive(Default)]
+pub struct TlsCollector {
+    // Keyed by `host:port`, as warnings name hosts
+    pages: BTreeMap<String, Vec<String>>,
+    hosts: BTreeSet<(String, u16)>,
+}
+
+impl TlsCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        let Some(host) = page_host(&result.url) else {
+            return;
+        };
+        self.pages.entry(host).or_default().push(result.url.clone());
+        if let Ok(url) = Url::parse(&result.url) {
+            if let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) {
+                self.hosts.insert((host.to_string(), port));
+            }
+        }
+    }
+
+    /// Inspects the certificate of every HTTPS host the crawl reached.
+    pub async fn audit(&self, expiry_warning_days: i64) -> TlsReport {
+        let certificates = join_all(
+            self.hosts
+                .iter()
+                .map(|(host, port)| tls_certificate::inspect_certificate(host, *port)),
+        )
+        .await;
+
+        TlsReport {
+            expiry_warning_days,
+            warnings: certificates
+                .iter()
+                .flat_map(|certificate| warnings_for(certificate, expiry_warning_days))
+                .collect(),
+            certificates,
+        }
+    }
+
+    // Certificates belong to hosts, every crawled page of the host is flagged
+    pub fn register_issues(&self, report: &TlsReport, issues: &mut IssueRegistry) {
+        for warning in &report.warnings {
+            let kind = match warning.issue {
+                TlsIssue::Expired => IssueKind::CertificateExpired,
+                TlsIssue::ExpiresSoon => IssueKind::CertificateExpiresSoon,
+                TlsIssue::HostnameMismatch | TlsIssue::Untrusted | TlsIssue::Unreachable => {
+                    IssueKind::CertificateInvalid
+                }
+                TlsIssue::WeakProtocol => IssueKind::WeakTlsProtocol,
+            };
+            for url in self.pages.get(&warning.host).into_iter().flatten() {
+                issues.flag(kind, url);
+            }
+        }
+    }
+}
+
+impl StoredReport for TlsReport {
+    const KIND: &'static str = "tls";
+}
+
+fn warnings_for(certificate: &TlsCertificate, expiry_warning_days: i64) -> Vec<TlsWarning> {
+    let host = format!("{}:{}", certificate.host, certificate.port);
+    let warning = |issue: TlsIssue, message: String| TlsWarning {
+        host: host.clone(),
+        issue,
+        message,
+    };
+
+    // A host that only speaks TLS 1.0 or 1.1 fails the handshake, the probe still finds it
+    let mut warnings: Vec<TlsWarning> = certificate
+        .weak_protocols
+        .iter()
+        .map(|protocol| {
+            warning(
+                TlsIssue::WeakProtocol,
+                format!("{} is still accepted", protocol),
+            )
+        })
+        .collect();
+    if let Some(error) = &certificate.error {
+        warnings.push(warning(TlsIssue::Unreachable, error.clone()));
+        return warnings;
+    }
+
+    match certificate.days_until_expiry {
+        Some(days) if days < 0 => warnings.push(warning(
+            TlsIssue::Expired,
+            format!("Certificate expired {} days ago", -days),
+        )),
+        Some(days) if days <= expiry_warning_days => warnings.push(warning(
+            TlsIssue::ExpiresSoon,
+            format!("Certificate expires in {} days", days),
+        )),
+        _ => {}
+    }
+    if certificate.hostname_mismatch {
+        warnings.push(warning(
+            TlsIssue::HostnameMismatch,
+            format!(
+                "Certificate does not cover {}, it is issued for {}",
+                certificate.host,
+                certificate.subject_alt_names.join(", ")
+            ),
+        ));
+    }
+    // Expiry and name problems are already reported on their own
+    if let Some(error) = &certificate.verification_error {
+        let expired = warnings.iter().any(|w| w.issue == TlsIssue::Expired);
+        if !expired && !certificate.hostname_mismatch {
+            warnings.push(warning(TlsIssue::Untrusted, error.clone()));
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_issues_are_flagged_on_its_pages() {
+        let certificate = TlsCertificate {
+            host: "example.com".to_string(),
+            port: 443,
+            weak_protocols: vec!["TLS 1.0".to_string()],
+            days_until_expiry: Some(90),
+            ..Default::default()
+        };
+        let report = TlsReport {
+            expiry_warning_days: 14,
+            warnings: warnings_for(&certificate, 14),
+            certificates: vec![certificate],
+        };
+        let results = [
+            DomainCrawlResults::crawled("https://example.com/"),
+            DomainCrawlResults::crawled("https://example.com/about"),
+            DomainCrawlResults::crawled("https://example.com:8443/admin"),
+            DomainCrawlResults::crawled("https://other.com/"),
+        ];
+
+        let mut collector = TlsCollector::default();
+        for result in &results {
+            collector.add(result);
+        }
+        let mut issues = IssueRegistry::default();
+        collector.register_issues(&report, &mut issues);
+        let issues = issues.into_issues();
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].id, IssueKind::WeakTlsProtocol);
+        assert_eq!(
+            issues[0].urls,
+            ["https://example.com/", "https://example.com/about"]
+        );
+    }
+
+    #[test]
+    fn weak_protocols_are_reported_when_the_handshake_fails() {
+        let certificate = TlsCertificate {
+            host: "legacy.example.com".to_string(),
+            port: 443,
+            weak_protocols: vec!["TLS 1.0".to_string()],
+            error: Some("TLS handshake failed".to_string()),
+            ..Default::default()
+        };
+        let issues: Vec<TlsIssue> = warnings_for(&certificate, 14)
+            .into_iter()
+            .map(|warning| warning.issue)
+            .collect();
+        assert_eq!(issues, [TlsIssue::WeakProtocol, TlsIssue::Unreachable]);
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::helpers::tracking_selector::{TrackingTag, TrackingVendor};
+use super::issues::{IssueKind, IssueRegistry};
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TrackingIssue {
+    /// An expected container or property ID is not on the page
+    MissingExpected,
+    /// The same tag is loaded more than once, so every hit is counted twice
+    Duplicate,
+    /// Several IDs of one vendor, or several consent managers, on one page
+    Conflicting,
+    /// An ID of a vendor with expected IDs that is not one of them
+    Unexpected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingWarning {
+    pub url: String,
+    pub issue: TrackingIssue,
+    pub message: String,
+}
+
+/// How many pages load one tag.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagUsage {
+    pub vendor: TrackingVendor,
+    pub id: String,
+    pub pages: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpectedTag {
+    pub id: String,
+    /// The vendor the ID belongs to, unknown until a page loads it
+    pub vendor: Option<TrackingVendor>,
+    pub pages_with: usize,
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TrackingReport {
+    pub pages_checked: usize,
+    pub pages_without_tags: usize,
+    pub tags: Vec<TagUsage>,
+    pub expected: Vec<ExpectedTag>,
+    pub warnings: Vec<TrackingWarning>,
+}
+
+pub fn register_issues(report: &TrackingReport, issues: &mut IssueRegistry) {
+    for warning in &report.warnings {
+        let kind = match warning.issue {
+            TrackingIssue::MissingExpected => IssueKind::MissingTrackingTag,
+            _ => IssueKind::ConflictingTrackingTags,
+        };
+        issues.flag(kind, &warning.url);
+    }
+}
+
+impl StoredReport for TrackingReport {
+    const KIND: &'static str = "tracking";
+}
+
+// Google IDs carry their vendor in the prefix, the others are known once a page loads them
+fn expected_vendor(
+    id: &str,
+    seen: &BTreeMap<(TrackingVendor, String), usize>,
+) -> Option<TrackingVendor> {
+    let upper = id.to_ascii_uppercase();
+    if upper.starts_with("GTM-") {
+        Some(TrackingVendor::GoogleTagManager)
+    } else if upper.starts_with("G-") {
+        Some(TrackingVendor::GoogleAnalytics4)
+    } else if upper.starts_with("UA-") {
+        Some(TrackingVendor::UniversalAnalytics)
+    } else {
+        seen.keys()
+            .find(|(_, seen_id)| seen_id.eq_ignore_ascii_case(id))
+            .map(|(vendor, _)| *vendor)
+    }
+}
+
+fn has_tag(tags: &[TrackingTag], id: &str) -> bool {
+    tags.iter().any(|tag| tag.id.eq_ignore_ascii_case(id))
+}
+
+/// Collects the tracking tags of every HTML page one page at a time.
+#[derive(Default)]
+pub struct TrackingCollector {
+    pages: Vec<(String, Vec<TrackingTag>)>,
+    seen: BTreeMap<(TrackingVendor, String), usize>,
+}
+
+impl TrackingCollector {
+    pub fn add(&mut self, page: &DomainCrawlResults) {
+        if page.status_code != 200 || !page.content_type.contains("html") {
+            return;
+        }
+        for tag in &page.tracking {
+            *self.seen.entry((tag.vendor, tag.id.clone())).or_default() += 1;
+        }
+        self.pages.push((page.url.clone(), page.tracking.clone()));
+    }
+
+    /// Checks every collected page for missing expected tags and for duplicate or conflicting ones.
+    pub fn finish(self, expected_ids: &[String]) -> TrackingReport {
+        audit_tracking(&self.pages, self.seen, expected_ids)
+    }
+}
+
+fn audit_tracking(
+    pages: &[(String, Vec<TrackingTag>)],
+    seen: BTreeMap<(TrackingVendor, String), usize>,
+    expected_ids: &[String],
+) -> TrackingReport {
+    let mut expected: Vec<ExpectedTag> = expected_ids
+        .iter()
+        .map(|id| ExpectedTag {
+            id: id.clone(),
+            vendor: expected_vendor(id, &seen),
+            pages_with: 0,
+            missing: Vec::new(),
+        })
+        .collect();
+    let expected_vendors: BTreeSet<TrackingVendor> =
+        expected.iter().filter_map(|tag| tag.vendor).collect();
+
+    let mut report = TrackingReport {
+        pages_checked: pages.len(),
+        ..Default::default()
+    };
+    for (url, tags) in pages {
+        if tags.is_empty() {
+            report.pages_without_tags += 1;
+        }
+        let mut warn = |issue: TrackingIssue, message: String| {
+            report.warnings.push(TrackingWarning {
+                url: url.clone(),
+                issue,
+                message,
+            })
+        };
+
+        for tag in &mut expected {
+            if has_tag(tags, &tag.id) {
+                tag.pages_with += 1;
+            } else {
+                tag.missing.push(url.clone());
+                warn(
+                    TrackingIssue::MissingExpected,
+                    format!("Expected tag {} is not loaded", tag.id),
+                );
+            }
+        }
+
+        for tag in tags.iter().filter(|tag| tag.occurrences > 1) {
+            warn(
+                TrackingIssue::Duplicate,
+                format!(
+                    "{} {} is loaded {} times",
+                    tag.vendor.name(),
+                    tag.id,
+                    tag.occurrences
+                ),
+            );
+        }
+
+        let mut ids: BTreeMap<TrackingVendor, Vec<&str>> = BTreeMap::new();
+        for tag in tags.iter().filter(|tag| !tag.id.is_empty()) {
+            ids.entry(tag.vendor).or_default().push(&tag.id);
+        }
+        for (vendor, ids) in &ids {
+            if expected_vendors.contains(vendor) {
+                for id in ids
+                    .iter()
+                    .filter(|id| !expected_ids.iter().any(|e| e.eq_ignore_ascii_case(id)))
+                {
+                    warn(
+                        TrackingIssue::Unexpected,
+                        format!("{} {} is not one of the expected IDs", vendor.name(), id),
+                    );
+                }
+            } else if ids.len() > 1 {
+                warn(
+                    TrackingIssue::Conflicting,
+                    format!("Several {} IDs: {}", vendor.name(), ids.join(", ")),
+                );
+            }
+        }
+
+        let consent: BTreeSet<TrackingVendor> = tags
+            .iter()
+            .map(|tag| tag.vendor)
+            .filter(|vendor| vendor.is_consent_manager())
+            .collect();
+        if consent.len() > 1 {
+            let names: Vec<&str> = consent.iter().map(|vendor| vendor.name()).collect();
+            warn(
+                TrackingIssue::Conflicting,
+                format!("Several consent managers: {}", names.join(", ")),
+            );
+        }
+    }
+
+    report.tags = seen
+        .into_iter()
+        .map(|((vendor, id), pages)| TagUsage { vendor, id, pages })
+        .collect();
+    report
+        .tags
+        .sort_by(|a, b| b.pages.cmp(&a.pages).then(a.vendor.cmp(&b.vendor)));
+    report.expected = expected;
+    report
+}
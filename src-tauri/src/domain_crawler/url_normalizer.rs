@@ -0,0 +1,227 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use url::{form_urlencoded, Url};
+
+use super::models::DomainCrawlResults;
+use super::results_store::StoredReport;
+use crate::settings::settings::Settings;
+
+// Session identifiers never change the content, they are always dropped
+const SESSION_PARAMS: [&str; 6] = [
+    "jsessionid",
+    "phpsessid",
+    "sid",
+    "sessionid",
+    "session_id",
+    "aspsessionid",
+];
+
+/// Rewrites URLs to one canonical form before they enter the frontier.
+#[derive(Debug, Clone)]
+pub struct UrlNormalizer {
+    /// Parameter names to drop, a trailing `*` matches a prefix such as `utm_*`
+    strip: Vec<String>,
+    sort: bool,
+}
+
+impl UrlNormalizer {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            strip: settings
+                .strip_query_params
+                .iter()
+                .map(|p| p.trim().to_lowercase())
+                .filter(|p| !p.is_empty())
+                .collect(),
+            sort: settings.sort_query_params,
+        }
+    }
+
+    fn strips(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        SESSION_PARAMS.contains(&name.as_str())
+            || self.strip.iter().any(|p| match p.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == *p,
+            })
+    }
+
+    /// Drops the fragment, session IDs and stripped parameters and sorts the rest.
+    ///
+    /// Hosts are already lowercased and default ports removed by `Url` itself.
+    pub fn normalize(&self, url: &Url) -> Url {
+        let mut url = url.clone();
+        url.set_fragment(None);
+
+        // Java servers append the session to the path as `;jsessionid=...`
+        if let Some(position) = url.path().to_lowercase().find(";jsessionid=") {
+            let path = url.path()[..position].to_string();
+            url.set_path(&path);
+        }
+
+        // Raw segments keep the site's own encoding and valueless `?foo` parameters
+        let Some(query) = url.query().map(str::to_string) else {
+            return url;
+        };
+        let segments: Vec<&str> = query.split('&').collect();
+        let decoded = |segment: &str| {
+            form_urlencoded::parse(segment.as_bytes())
+                .next()
+                .map(|(name, value)| (name.into_owned(), value.into_owned()))
+                .unwrap_or_default()
+        };
+        let mut kept: Vec<&str> = segments
+            .iter()
+            .copied()
+            .filter(|segment| !segment.is_empty() && !self.strips(&decoded(segment).0))
+            .collect();
+        if self.sort {
+            kept.sort_by_cached_key(|segment| decoded(segment));
+        }
+
+        if kept.is_empty() {
+            url.set_query(None);
+        } else if kept != segments {
+            url.set_query(Some(&kept.join("&")));
+        }
+        url
+    }
+}
+
+/// Crawled URLs that only differ in their query string.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterGroup {
+    /// The URL without its query string
+    pub base: String,
+    /// The parameter names seen across the group
+    pub parameters: Vec<String>,
+    pub urls: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ParameterReport {
+    /// Discovered links that normalization rewrote before queueing
+    pub normalized_links: usize,
+    pub parameterized_urls: usize,
+    pub groups: Vec<ParameterGroup>,
+}
+
+impl StoredReport for ParameterReport {
+    const KIND: &'static str = "parameters";
+}
+
+/// Groups crawled URLs by their query-less form to find parameter permutations of one page.
+#[derive(Default)]
+pub struct ParameterCollector {
+    by_base: BTreeMap<String, (BTreeSet<String>, Vec<String>)>,
+    parameterized_urls: usize,
+}
+
+impl ParameterCollector {
+    pub fn add(&mut self, result: &DomainCrawlResults) {
+        let Ok(url) = Url::parse(&result.url) else {
+            return;
+        };
+        if url.query().is_none() {
+            // The bare page still belongs in the group of its variants
+            self.by_base
+                .entry(url.to_string())
+                .or_default()
+                .1
+                .push(url.to_string());
+            return;
+        }
+
+        self.parameterized_urls += 1;
+        let mut base = url.clone();
+        base.set_query(None);
+        let group = self.by_base.entry(base.to_string()).or_default();
+        group
+            .0
+            .extend(url.query_pairs().map(|(name, _)| name.into_owned()));
+        group.1.push(url.to_string());
+    }
+
+    pub fn finish(self, normalized_links: usize) -> ParameterReport {
+        let mut groups: Vec<ParameterGroup> = self
+            .by_base
+            .into_iter()
+            .filter(|(_, (parameters, urls))| urls.len() > 1 && !parameters.is_empty())
+            .map(|(base, (parameters, urls))| ParameterGroup {
+                base,
+                parameters: parameters.into_iter().collect(),
+                urls,
+            })
+            .collect();
+        groups.sort_by(|a, b| b.urls.len().cmp(&a.urls.len()).then(a.base.cmp(&b.base)));
+
+        ParameterReport {
+            normalized_links,
+            parameterized_urls: self.parameterized_urls,
+            groups,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalizer(strip: &[&str], sort: bool) -> UrlNormalizer {
+        UrlNormalizer {
+            strip: strip.iter().map(|p| p.to_string()).collect(),
+            sort,
+        }
+    }
+
+    fn normalize(normalizer: &UrlNormalizer, url: &str) -> String {
+        normalizer.normalize(&Url::parse(url).unwrap()).to_string()
+    }
+
+    #[test]
+    fn keeps_untouched_queries_as_written() {
+        let normalizer = normalizer(&["utm_*"], false);
+        assert_eq!(
+            normalize(&normalizer, "https://example.com/a?foo&q=caf%C3%A9+bar"),
+            "https://example.com/a?foo&q=caf%C3%A9+bar"
+        );
+        assert_eq!(
+            normalize(&normalizer, "https://example.com/a?b=2&a=1#top"),
+            "https://example.com/a?b=2&a=1"
+        );
+    }
+
+    #[test]
+    fn strips_session_and_configured_parameters() {
+        let normalizer = normalizer(&["utm_*", "ref"], false);
+        assert_eq!(
+            normalize(
+                &normalizer,
+                "https://example.com/a?utm_source=x&foo&REF=1&PHPSESSID=abc&q=a%20b"
+            ),
+            "https://example.com/a?foo&q=a%20b"
+        );
+        assert_eq!(
+            normalize(&normalizer, "https://example.com/a?utm_medium=email"),
+            "https://example.com/a"
+        );
+        assert_eq!(
+            normalize(&normalizer, "https://example.com/a;jsessionid=123?x=1"),
+            "https://example.com/a?x=1"
+        );
+    }
+
+    #[test]
+    fn sorts_parameters_only_when_enabled() {
+        let sorted = normalizer(&[], true);
+        assert_eq!(
+            normalize(&sorted, "https://example.com/?b=2&a&c=%41"),
+            "https://example.com/?a&b=2&c=%41"
+        );
+        assert_eq!(
+            normalize(&sorted, "https://example.com/?a=1&b=2"),
+            "https://example.com/?a=1&b=2"
+        );
+    }
+}
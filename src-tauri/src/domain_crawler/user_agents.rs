@@ -1,3 +1,11 @@
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::settings::Settings;
+
 pub fn agents() -> Vec<String> {
     vec![
         // Desktop Chrome (Various OS/versions)
@@ -56,3 +64,83 @@ pub fn agents() -> Vec<String> {
         "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 VivoBrowser/9.8.0.0".to_string(),
     ]
 }
+
+pub const GOOGLEBOT_DESKTOP: &str = "Mozilla/5.0 AppleWebKit/537.36 (KHTML, like Gecko; compatible; Googlebot/2.1; +http://www.google.com/bot.html) Chrome/120.0.0.0 Safari/537.36";
+pub const GOOGLEBOT_SMARTPHONE: &str = "Mozilla/5.0 (Linux; Android 6.0.1; Nexus 5X Build/MMB29P) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+
+/// Which user agent a crawl identifies itself with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserAgentProfile {
+    /// A random agent from the configured `user_agents` list
+    Rotate,
+    GooglebotDesktop,
+    GooglebotSmartphone,
+    Custom,
+}
+
+impl UserAgentProfile {
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "googlebot_desktop" => Self::GooglebotDesktop,
+            "googlebot_smartphone" => Self::GooglebotSmartphone,
+            "custom" => Self::Custom,
+            _ => Self::Rotate,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rotate => "rotate",
+            Self::GooglebotDesktop => "googlebot_desktop",
+            Self::GooglebotSmartphone => "googlebot_smartphone",
+            Self::Custom => "custom",
+        }
+    }
+}
+
+// The agent picked for the running crawl, shared by every helper that makes requests
+static CRAWL_USER_AGENT: Lazy<RwLock<Option<String>>> = Lazy::new(|| RwLock::new(None));
+
+/// Picks the user agent for a crawl from the configured profile.
+pub fn resolve(settings: &Settings) -> Result<(UserAgentProfile, String), String> {
+    let profile = UserAgentProfile::from_setting(&settings.user_agent_profile);
+    let user_agent = match profile {
+        UserAgentProfile::Rotate => settings
+            .user_agents
+            .choose(&mut rand::thread_rng())
+            .cloned()
+            .ok_or("No user agents configured")?,
+        UserAgentProfile::GooglebotDesktop => GOOGLEBOT_DESKTOP.to_string(),
+        UserAgentProfile::GooglebotSmartphone => GOOGLEBOT_SMARTPHONE.to_string(),
+        UserAgentProfile::Custom => {
+            let custom = settings.custom_user_agent.trim();
+            if custom.is_empty() {
+                return Err("The custom user agent profile needs custom_user_agent set".to_string());
+            }
+            custom.to_string()
+        }
+    };
+    Ok((profile, user_agent))
+}
+
+/// Makes `user_agent` the agent of every request of the current crawl.
+pub fn set_current(user_agent: &str) {
+    if let Ok(mut current) = CRAWL_USER_AGENT.write() {
+        *current = Some(user_agent.to_string());
+    }
+}
+
+/// The agent of the current crawl, or a random one when no crawl has started.
+pub fn current() -> String {
+    CRAWL_USER_AGENT
+        .read()
+        .ok()
+        .and_then(|current| current.clone())
+        .unwrap_or_else(|| {
+            agents()
+                .choose(&mut rand::thread_rng())
+                .cloned()
+                .unwrap_or_default()
+        })
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+
+const CDX_URL: &str = "https://web.archive.org/cdx/search/cdx";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+// The archive throttles clients that open many connections
+const CONCURRENT_LOOKUPS: usize = 3;
+
+/// The Wayback Machine captures of one URL, counted once per day.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WaybackHistory {
+    pub url: String,
+    /// Days with at least one capture
+    pub snapshots: usize,
+    /// Captures whose content differs from the one before, a hint at redesigns
+    pub changes: usize,
+    pub first_capture: Option<String>,
+    pub last_capture: Option<String>,
+    pub last_status: Option<u16>,
+    pub last_snapshot_url: Option<String>,
+    pub snapshots_per_year: BTreeMap<String, usize>,
+    pub error: Option<String>,
+}
+
+// Timestamps are "yyyyMMddhhmmss"
+fn capture_date(timestamp: &str) -> Option<String> {
+    if timestamp.len() < 8 || !timestamp.is_ascii() {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}",
+        &timestamp[..4],
+        &timestamp[4..6],
+        &timestamp[6..8]
+    ))
+}
+
+async fn lookup(client: &reqwest::Client, url: &str) -> Result<WaybackHistory, String> {
+    let rows: Vec<Vec<String>> = client
+        .get(CDX_URL)
+        .query(&[
+            ("url", url),
+            ("output", "json"),
+            ("fl", "timestamp,statuscode,digest"),
+            // One capture per day keeps popular pages from returning huge lists
+            ("collapse", "timestamp:8"),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Wayback Machine request failed: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Wayback Machine answered: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Wayback Machine response: {}", e))?;
+
+    let mut history = WaybackHistory {
+        url: url.to_string(),
+        ..Default::default()
+    };
+    let mut previous_digest: Option<&str> = None;
+    // The first row names the fields
+    for row in rows.iter().skip(1) {
+        let [timestamp, status, digest] = row.as_slice() else {
+            continue;
+        };
+        let Some(date) = capture_date(timestamp) else {
+            continue;
+        };
+        history.snapshots += 1;
+        *history
+            .snapshots_per_year
+            .entry(date[..4].to_string())
+            .or_insert(0) += 1;
+        if previous_digest.is_some_and(|previous| previous != digest) {
+            history.changes += 1;
+        }
+        previous_digest = Some(digest);
+
+        if history.first_capture.is_none() {
+            history.first_capture = Some(date.clone());
+        }
+        history.last_capture = Some(date);
+        history.last_status = status.parse().ok();
+        history.last_snapshot_url =
+            Some(format!("https://web.archive.org/web/{}/{}", timestamp, url));
+    }
+    Ok(history)
+}
+
+/// Looks up the Wayback Machine history of URLs, in the order they were given.
+///
+/// A URL the archive cannot be asked about carries the error instead of failing the batch.
+pub async fn lookup_history(urls: Vec<String>) -> Result<Vec<WaybackHistory>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let client = &client;
+
+    let history = stream::iter(urls)
+        .map(|url| async move {
+            lookup(client, &url)
+                .await
+                .unwrap_or_else(|e| WaybackHistory {
+                    url,
+                    error: Some(e),
+                    ..Default::default()
+                })
+        })
+        .buffered(CONCURRENT_LOOKUPS)
+        .collect()
+        .await;
+    Ok(history)
+}
+
+// GET THE WAYBACK MACHINE SNAPSHOTS OF SELECTED URLS
+#[tauri::command]
+pub async fn get_wayback_history(urls: Vec<String>) -> Result<Vec<WaybackHistory>, String> {
+    let urls: Vec<String> = urls
+        .into_iter()
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .collect();
+    if urls.is_empty() {
+        return Err("No URLs to look up".to_string());
+    }
+    lookup_history(urls).await
+}
@@ -0,0 +1,545 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use reqwest::{Client, StatusCode};
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::fs;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::projects::registry::ProjectRoots;
+
+use super::crawl_diff::diff_crawls;
+use super::results_store::{CrawlRecord, ResultsStore};
+
+// Next to the settings file, so each project keeps its own webhooks
+const WEBHOOKS_FILE: &str = "webhooks.json";
+const SIGNATURE_HEADER: &str = "X-RustySEO-Signature";
+// Unix seconds the signature was made at, receivers reject old ones so bodies cannot be replayed
+const TIMESTAMP_HEADER: &str = "X-RustySEO-Timestamp";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_ATTEMPTS: u32 = 4;
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+// Deliveries still running, awaited before a headless run exits
+static PENDING: Lazy<Mutex<Vec<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    CrawlCompleted,
+    CrawlFailed,
+    ThresholdExceeded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// The payload as JSON
+    Json,
+    /// A `{"text": ...}` message for Slack and the chat tools that take its incoming webhooks
+    Slack,
+}
+
+/// The counts of a finished crawl that thresholds can watch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// Pages answering 404 that did not in the previous crawl of the domain
+    New404s,
+    NewBrokenLinks,
+    RemovedPages,
+    ClientErrors,
+    ServerErrors,
+    FailedPages,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Threshold {
+    pub metric: Metric,
+    /// Fires when the count goes above this
+    pub above: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    #[serde(default = "default_format")]
+    pub format: WebhookFormat,
+    pub events: Vec<WebhookEvent>,
+    #[serde(default)]
+    pub thresholds: Vec<Threshold>,
+    /// Signs each body and its timestamp with HMAC-SHA256 when set
+    #[serde(default)]
+    pub secret: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_format() -> WebhookFormat {
+    WebhookFormat::Json
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThresholdBreach {
+    pub metric: Metric,
+    pub value: usize,
+    pub above: usize,
+}
+
+/// What a webhook receives.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub domain: String,
+    pub crawl: Option<CrawlRecord>,
+    pub error: Option<String>,
+    pub metrics: BTreeMap<Metric, usize>,
+    pub breaches: Vec<ThresholdBreach>,
+    pub sent_at: String,
+}
+
+impl WebhookPayload {
+    fn slack_text(&self) -> String {
+        let pages = self.crawl.as_ref().map_or(0, |crawl| crawl.pages);
+        match self.event {
+            WebhookEvent::CrawlCompleted => {
+                format!("Crawl of {} finished with {} pages", self.domain, pages)
+            }
+            WebhookEvent::CrawlFailed => format!(
+                "Crawl of {} failed: {}",
+                self.domain,
+                self.error.as_deref().unwrap_or("unknown error")
+            ),
+            WebhookEvent::ThresholdExceeded => {
+                let breaches: Vec<String> = self
+                    .breaches
+                    .iter()
+                    .map(|breach| {
+                        format!(
+                            "• {:?}: {} (limit {})",
+                            breach.metric, breach.value, breach.above
+                        )
+                    })
+                    .collect();
+                format!(
+                    "Crawl of {} crossed its thresholds:\n{}",
+                    self.domain,
+                    breaches.join("\n")
+                )
+            }
+        }
+    }
+}
+
+fn webhooks_path(roots: &ProjectRoots) -> PathBuf {
+    roots.config_file(WEBHOOKS_FILE)
+}
+
+async fn load_webhooks(roots: &ProjectRoots) -> Result<Vec<Webhook>, String> {
+    let path = webhooks_path(roots);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Failed to read webhooks: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse webhooks: {}", e))
+}
+
+async fn save_webhooks(roots: &ProjectRoots, webhooks: &[Webhook]) -> Result<(), String> {
+    let path = webhooks_path(roots);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(webhooks).map_err(|e| e.to_string())?;
+    fs::write(&path, json)
+        .await
+        .map_err(|e| format!("Failed to write webhooks: {}", e))
+}
+
+/// The counts of a finished crawl, the new ones measured against the previous crawl of the domain.
+async fn crawl_metrics(
+    store: &ResultsStore,
+    crawl: &CrawlRecord,
+) -> Result<BTreeMap<Metric, usize>, String> {
+    let rows = store.page_rows(crawl.id).await.map_err(|e| e.to_string())?;
+    let broken = store
+        .broken_links(crawl.id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut metrics = BTreeMap::new();
+    let count = |class: u16| rows.iter().filter(|r| r.status_code / 100 == class).count();
+    metrics.insert(Metric::ClientErrors, count(4));
+    metrics.insert(Metric::ServerErrors, count(5));
+    metrics.insert(
+        Metric::FailedPages,
+        rows.iter().filter(|r| r.status_code == 0).count(),
+    );
+
+    let previous = store
+        .list_crawls()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|c| c.id < crawl.id && c.domain == crawl.domain && c.status == "completed");
+    let (new_404s, new_broken, removed) = match previous {
+        Some(previous) => {
+            let previous_rows = store
+                .page_rows(previous.id)
+                .await
+                .map_err(|e| e.to_string())?;
+            let previous_broken = store
+                .broken_links(previous.id)
+                .await
+                .map_err(|e| e.to_string())?;
+            let diff = diff_crawls(
+                previous,
+                crawl.clone(),
+                &previous_rows,
+                &rows,
+                &previous_broken,
+                broken,
+            );
+            let changed = diff
+                .status_changes
+                .iter()
+                .filter(|change| change.after == 404)
+                .count();
+            let new_pages: HashSet<&str> = diff.new_pages.iter().map(String::as_str).collect();
+            let added = rows
+                .iter()
+                .filter(|r| r.status_code == 404 && new_pages.contains(r.url.as_str()))
+                .count();
+            (
+                changed + added,
+                diff.newly_broken_links.len(),
+                diff.removed_pages.len(),
+            )
+        }
+        // A first crawl has nothing to compare with, everything it finds is new
+        None => (
+            rows.iter().filter(|r| r.status_code == 404).count(),
+            broken.len(),
+            0,
+        ),
+    };
+    metrics.insert(Metric::New404s, new_404s);
+    metrics.insert(Metric::NewBrokenLinks, new_broken);
+    metrics.insert(Metric::RemovedPages, removed);
+    Ok(metrics)
+}
+
+fn breaches(webhook: &Webhook, metrics: &BTreeMap<Metric, usize>) -> Vec<ThresholdBreach> {
+    webhook
+        .thresholds
+        .iter()
+        .filter_map(|threshold| {
+            let value = metrics.get(&threshold.metric).copied().unwrap_or(0);
+            (value > threshold.above).then_some(ThresholdBreach {
+                metric: threshold.metric,
+                value,
+                above: threshold.above,
+            })
+        })
+        .collect()
+}
+
+// Signs `timestamp.body`, so a captured signature is only good with its own timestamp
+fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let mut context = hmac::Context::with_key(&key);
+    context.update(format!("{}.", timestamp).as_bytes());
+    context.update(body);
+    let tag = context.sign();
+    let hex: String = tag
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+/// Posts the payload, retrying with a doubling delay on network errors, 429 and 5xx.
+async fn deliver(
+    client: &Client,
+    webhook: &Webhook,
+    payload: &WebhookPayload,
+) -> Result<(), String> {
+    let body = match webhook.format {
+        WebhookFormat::Json => serde_json::to_vec(payload),
+        WebhookFormat::Slack => serde_json::to_vec(&json!({ "text": payload.slack_text() })),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let mut delay = RETRY_DELAY;
+    let mut last_error = String::new();
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request = client
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .body(body.clone());
+        if !webhook.secret.is_empty() {
+            let timestamp = Utc::now().timestamp();
+            request = request
+                .header(TIMESTAMP_HEADER, timestamp.to_string())
+                .header(SIGNATURE_HEADER, sign(&webhook.secret, timestamp, &body));
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                last_error = format!("answered {}", status);
+                if status != StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+                    break;
+                }
+            }
+            Err(e) => last_error = e.to_string(),
+        }
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+    Err(format!("Webhook {} {}", webhook.name, last_error))
+}
+
+async fn notify(
+    roots: ProjectRoots,
+    domain: String,
+    crawl_id: Option<i64>,
+    error: Option<String>,
+) -> Result<(), String> {
+    let webhooks: Vec<Webhook> = load_webhooks(&roots)
+        .await?
+        .into_iter()
+        .filter(|webhook| webhook.enabled)
+        .collect();
+    if webhooks.is_empty() {
+        return Ok(());
+    }
+
+    let store = ResultsStore::open_in(&roots.db_dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    let crawl = match crawl_id {
+        Some(id) => store.crawl(id).await.map_err(|e| e.to_string())?,
+        None => None,
+    };
+    let metrics = match (&crawl, &error) {
+        (Some(crawl), None) => crawl_metrics(&store, crawl).await?,
+        _ => BTreeMap::new(),
+    };
+
+    let client = Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    for webhook in webhooks {
+        let mut events = Vec::new();
+        match &error {
+            Some(_) => events.push((WebhookEvent::CrawlFailed, Vec::new())),
+            None => {
+                events.push((WebhookEvent::CrawlCompleted, Vec::new()));
+                let breaches = breaches(&webhook, &metrics);
+                if !breaches.is_empty() {
+                    events.push((WebhookEvent::ThresholdExceeded, breaches));
+                }
+            }
+        }
+
+        for (event, breaches) in events {
+            if !webhook.events.contains(&event) {
+                continue;
+            }
+            let payload = WebhookPayload {
+                event,
+                domain: domain.clone(),
+                crawl: crawl.clone(),
+                error: error.clone(),
+                metrics: metrics.clone(),
+                breaches,
+                sent_at: Utc::now().to_rfc3339(),
+            };
+            if let Err(e) = deliver(&client, &webhook, &payload).await {
+                eprintln!("{}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Sends the webhooks of the project a crawl ran for, once it finished or failed with `error`,
+/// in the background.
+pub async fn crawl_finished(
+    roots: &ProjectRoots,
+    domain: &str,
+    crawl_id: Option<i64>,
+    error: Option<String>,
+) {
+    let (roots, domain) = (roots.clone(), domain.to_string());
+    let handle = tokio::spawn(async move {
+        if let Err(e) = notify(roots, domain, crawl_id, error).await {
+            eprintln!("Failed to send crawl webhooks: {}", e);
+        }
+    });
+    let mut pending = PENDING.lock().await;
+    pending.retain(|delivery| !delivery.is_finished());
+    pending.push(handle);
+}
+
+/// Waits for the deliveries still running.
+pub async fn flush() {
+    let pending = std::mem::take(&mut *PENDING.lock().await);
+    for delivery in pending {
+        let _ = delivery.await;
+    }
+}
+
+#[tauri::command]
+pub async fn list_webhooks() -> Result<Vec<Webhook>, String> {
+    load_webhooks(&ProjectRoots::active()?).await
+}
+
+// ADD A WEBHOOK, OR REPLACE THE ONE WITH THE SAME ID
+#[tauri::command]
+pub async fn save_webhook(mut webhook: Webhook) -> Result<Webhook, String> {
+    let url = reqwest::Url::parse(webhook.url.trim())
+        .map_err(|e| format!("Invalid webhook URL: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("Webhook URLs must use http or https".to_string());
+    }
+    if webhook.events.is_empty() {
+        return Err("Pick at least one event for the webhook".to_string());
+    }
+    webhook.url = url.to_string();
+    if webhook.id.is_empty() {
+        webhook.id = Uuid::new_v4().to_string();
+    }
+
+    let roots = ProjectRoots::active()?;
+    let mut webhooks = load_webhooks(&roots).await?;
+    match webhooks.iter_mut().find(|w| w.id == webhook.id) {
+        Some(existing) => *existing = webhook.clone(),
+        None => webhooks.push(webhook.clone()),
+    }
+    save_webhooks(&roots, &webhooks).await?;
+    Ok(webhook)
+}
+
+#[tauri::command]
+pub async fn delete_webhook(id: String) -> Result<(), String> {
+    let roots = ProjectRoots::active()?;
+    let mut webhooks = load_webhooks(&roots).await?;
+    let count = webhooks.len();
+    webhooks.retain(|webhook| webhook.id != id);
+    if webhooks.len() == count {
+        return Err(format!("Webhook {} not found", id));
+    }
+    save_webhooks(&roots, &webhooks).await
+}
+
+// SEND A SAMPLE PAYLOAD TO A WEBHOOK TO CHECK IT IS REACHABLE
+#[tauri::command]
+pub async fn test_webhook(id: String) -> Result<(), String> {
+    let webhook = load_webhooks(&ProjectRoots::active()?)
+        .await?
+        .into_iter()
+        .find(|webhook| webhook.id == id)
+        .ok_or_else(|| format!("Webhook {} not found", id))?;
+    let payload = WebhookPayload {
+        event: WebhookEvent::CrawlCompleted,
+        domain: "https://example.com/".to_string(),
+        crawl: None,
+        error: None,
+        metrics: BTreeMap::new(),
+        breaches: Vec::new(),
+        sent_at: Utc::now().to_rfc3339(),
+    };
+    let client = Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    deliver(&client, &webhook, &payload).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_is_hmac_sha256_of_timestamp_and_body() {
+        // Computed independently: HMAC-SHA256("whsec_test", "1700000000.<body>")
+        assert_eq!(
+            sign(
+                "whsec_test",
+                1_700_000_000,
+                br#"{"event":"crawl_completed"}"#
+            ),
+            "sha256=47fb0ff025a7fe6bc7d9c85525426d4a1c942dabdda81f6d0aff68d77327b8e9"
+        );
+    }
+
+    #[test]
+    fn signature_covers_the_timestamp() {
+        let body = br#"{"event":"crawl_completed"}"#;
+        assert_ne!(
+            sign("whsec_test", 1_700_000_000, body),
+            sign("whsec_test", 1_700_000_001, body)
+        );
+        assert_ne!(
+            sign("whsec_test", 1_700_000_000, body),
+            sign("other", 1_700_000_000, body)
+        );
+    }
+
+    #[test]
+    fn thresholds_fire_above_their_value() {
+        let webhook = Webhook {
+            id: String::new(),
+            name: "alerts".to_string(),
+            url: "https://hooks.example.com/".to_string(),
+            format: WebhookFormat::Json,
+            events: vec![WebhookEvent::ThresholdExceeded],
+            thresholds: vec![
+                Threshold {
+                    metric: Metric::New404s,
+                    above: 5,
+                },
+                Threshold {
+                    metric: Metric::ServerErrors,
+                    above: 0,
+                },
+                Threshold {
+                    metric: Metric::RemovedPages,
+                    above: 0,
+                },
+            ],
+            secret: String::new(),
+            enabled: true,
+        };
+        let metrics = BTreeMap::from([(Metric::New404s, 5), (Metric::ServerErrors, 2)]);
+
+        let breaches = breaches(&webhook, &metrics);
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].metric, Metric::ServerErrors);
+        assert_eq!(breaches[0].value, 2);
+    }
+}
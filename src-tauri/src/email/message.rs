@@ -0,0 +1,206 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::Utc;
+use uuid::Uuid;
+
+// RFC 2045 caps encoded lines at 76 characters
+const LINE_LENGTH: usize = 76;
+
+pub struct Attachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// A plain text email with optional attachments.
+pub struct Email {
+    /// A bare address or `Name <address>`
+    pub from: String,
+    pub to: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    pub attachments: Vec<Attachment>,
+}
+
+/// The address part of `Name <address>`, or the whole mailbox when bare.
+pub fn address(mailbox: &str) -> &str {
+    let mailbox = mailbox.trim();
+    match (mailbox.rfind('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &mailbox[start + 1..end],
+        _ => mailbox,
+    }
+}
+
+/// Checks a mailbox is a single plausible address that cannot break out of its header.
+pub fn check_mailbox(mailbox: &str) -> Result<(), String> {
+    let bare = address(mailbox);
+    let valid = !mailbox.contains(['\r', '\n'])
+        && bare
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'))
+        && !bare.contains([' ', ',', '<', '>']);
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid email address: {}", mailbox))
+    }
+}
+
+fn wrapped_base64(data: &[u8]) -> String {
+    let encoded = STANDARD.encode(data);
+    let mut wrapped = String::with_capacity(encoded.len() + encoded.len() / LINE_LENGTH * 2 + 2);
+    for chunk in encoded.as_bytes().chunks(LINE_LENGTH) {
+        // Base64 output is ASCII, every chunk is valid UTF-8
+        wrapped.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+        wrapped.push_str("\r\n");
+    }
+    wrapped
+}
+
+// Non-ASCII header text goes as an RFC 2047 encoded word
+fn header_text(text: &str) -> String {
+    let text = text.replace(['\r', '\n'], " ");
+    if text.is_ascii() {
+        text
+    } else {
+        format!("=?UTF-8?B?{}?=", STANDARD.encode(text.as_bytes()))
+    }
+}
+
+impl Email {
+    /// The message as sent after DATA, with CRLF line endings.
+    pub fn to_mime(&self) -> String {
+        let boundary = format!("rustyseo-{}", Uuid::new_v4().simple());
+        let domain = address(&self.from)
+            .split_once('@')
+            .map_or("localhost", |(_, domain)| domain);
+
+        let mut message = String::new();
+        message.push_str(&format!("From: {}\r\n", self.from.trim()));
+        message.push_str(&format!("To: {}\r\n", self.to.join(", ")));
+        message.push_str(&format!("Subject: {}\r\n", header_text(&self.subject)));
+        message.push_str(&format!("Date: {}\r\n", Utc::now().to_rfc2822()));
+        message.push_str(&format!(
+            "Message-ID: <{}@{}>\r\n",
+            Uuid::new_v4().simple(),
+            domain
+        ));
+        message.push_str("MIME-Version: 1.0\r\n");
+        message.push_str(&format!(
+            "Content-Type: multipart/mixed; boundary=\"{}\"\r\n\r\n",
+            boundary
+        ));
+
+        message.push_str(&format!("--{}\r\n", boundary));
+        message.push_str("Content-Type: text/plain; charset=utf-8\r\n");
+        message.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+        message.push_str(&wrapped_base64(self.body.as_bytes()));
+
+        for attachment in &self.attachments {
+            let filename = attachment.filename.replace(['"', '\r', '\n'], "");
+            message.push_str(&format!("--{}\r\n", boundary));
+            message.push_str(&format!(
+                "Content-Type: {}; name=\"{}\"\r\n",
+                attachment.content_type, filename
+            ));
+            message.push_str("Content-Transfer-Encoding: base64\r\n");
+            message.push_str(&format!(
+                "Content-Disposition: attachment; filename=\"{}\"\r\n\r\n",
+                filename
+            ));
+            message.push_str(&wrapped_base64(&attachment.data));
+        }
+        message.push_str(&format!("--{}--\r\n", boundary));
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(subject: &str, body: &str, attachments: Vec<Attachment>) -> Email {
+        Email {
+            from: "RustySEO <reports@example.com>".to_string(),
+            to: vec!["team@example.com".to_string()],
+            subject: subject.to_string(),
+            body: body.to_string(),
+            attachments,
+        }
+    }
+
+    // The decoded first part of every boundary-delimited section
+    fn parts(mime: &str) -> Vec<&str> {
+        let boundary = mime
+            .split("boundary=\"")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .unwrap();
+        mime.split(&format!("--{}", boundary))
+            .skip(1)
+            .filter(|part| !part.starts_with("--"))
+            .collect()
+    }
+
+    fn decoded_body(part: &str) -> Vec<u8> {
+        let (_, encoded) = part.split_once("\r\n\r\n").unwrap();
+        STANDARD.decode(encoded.replace("\r\n", "")).unwrap()
+    }
+
+    #[test]
+    fn mailboxes_cannot_inject_headers() {
+        assert_eq!(
+            address("RustySEO <reports@example.com>"),
+            "reports@example.com"
+        );
+        assert!(check_mailbox("reports@example.com").is_ok());
+        assert!(check_mailbox("reports@example.com\r\nBcc: all@example.com").is_err());
+        assert!(check_mailbox("a@example.com, b@example.com").is_err());
+    }
+
+    #[test]
+    fn message_uses_crlf_and_short_lines() {
+        let body = "Weekly report ".repeat(40);
+        let mime = email("Report", &body, Vec::new()).to_mime();
+
+        assert!(mime.ends_with("--\r\n"));
+        assert!(!mime.replace("\r\n", "").contains('\n'));
+        let (_, content) = mime.split_once("\r\n\r\n").unwrap();
+        assert!(content.split("\r\n").all(|line| line.len() <= LINE_LENGTH));
+        assert_eq!(decoded_body(parts(&mime)[0]), body.as_bytes());
+    }
+
+    #[test]
+    fn non_ascii_subjects_are_encoded_words() {
+        let mime = email("Raport tygodniowy – błędy\r\nBcc: x@y.z", "", Vec::new()).to_mime();
+        let subject = mime
+            .lines()
+            .find(|line| line.starts_with("Subject: "))
+            .unwrap();
+        let encoded = subject
+            .trim_start_matches("Subject: =?UTF-8?B?")
+            .trim_end_matches("?=");
+        let decoded = String::from_utf8(STANDARD.decode(encoded).unwrap()).unwrap();
+
+        assert_eq!(decoded, "Raport tygodniowy – błędy  Bcc: x@y.z");
+        assert!(!mime.contains("\r\nBcc:"));
+    }
+
+    #[test]
+    fn attachments_are_separate_parts() {
+        let attachment = Attachment {
+            filename: "crawl \"final\".csv".to_string(),
+            content_type: "text/csv".to_string(),
+            data: b"url,status\r\nhttps://example.com/,200\r\n".to_vec(),
+        };
+        let mime = email("Report", "See attached", vec![attachment]).to_mime();
+        let parts = parts(&mime);
+
+        assert_eq!(parts.len(), 2);
+        assert!(parts[1].contains("filename=\"crawl final.csv\""));
+        assert_eq!(
+            decoded_body(parts[1]),
+            b"url,status\r\nhttps://example.com/,200\r\n"
+        );
+    }
+}
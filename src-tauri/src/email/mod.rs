@@ -0,0 +1,3 @@
+pub mod message;
+pub mod report;
+pub mod smtp;
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain_crawler::exports;
+use crate::domain_crawler::reports::{self, summary, ReportFormat};
+use crate::domain_crawler::results_store::ResultsStore;
+use crate::projects::registry::ProjectRoots;
+use crate::settings::settings::Settings;
+use crate::AppState;
+
+use super::message::{Attachment, Email};
+use super::smtp::{self, SmtpConfig};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportAttachment {
+    Xlsx,
+    Pdf,
+}
+
+fn summary_body(summary: &summary::AuditSummary) -> String {
+    let crawl = &summary.crawl;
+    let mut body = format!(
+        "Crawl of {} ({})\nStarted: {}\nFinished: {}\nPages: {}\n",
+        crawl.domain,
+        crawl.status,
+        crawl.started_at,
+        crawl.finished_at.as_deref().unwrap_or("-"),
+        summary.pages,
+    );
+
+    if summary.top_problems.is_empty() {
+        body.push_str("\nNo issues found.\n");
+    } else {
+        body.push_str("\nTop problems:\n");
+        for issue in &summary.top_problems {
+            body.push_str(&format!(
+                "- {} ({:?}): {} pages\n",
+                issue.name, issue.severity, issue.count
+            ));
+            for url in &issue.examples {
+                body.push_str(&format!("    {}\n", url));
+            }
+        }
+    }
+    body.push_str("\nSent by RustySEO\n");
+    body
+}
+
+async fn attachment(
+    roots: &ProjectRoots,
+    crawl_id: i64,
+    kind: ReportAttachment,
+) -> Result<Attachment, String> {
+    match kind {
+        ReportAttachment::Xlsx => {
+            let filename = format!("rustyseo-crawl-{}.xlsx", crawl_id);
+            // A directory of its own, so concurrent reports of one crawl do not overwrite
+            // each other. It is removed with the report when dropped
+            let dir = tempfile::tempdir()
+                .map_err(|e| format!("Failed to create a temporary directory: {}", e))?;
+            let path = dir.path().join(&filename);
+            exports::xlsx::export_report(roots, crawl_id, path.clone()).await?;
+            let data = std::fs::read(&path).map_err(|e| format!("Failed to read report: {}", e));
+            Ok(Attachment {
+                filename,
+                content_type: "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+                    .to_string(),
+                data: data?,
+            })
+        }
+        ReportAttachment::Pdf => {
+            let path = reports::render_report(roots, crawl_id, ReportFormat::Pdf).await?;
+            Ok(Attachment {
+                filename: format!("rustyseo-audit-{}.pdf", crawl_id),
+                content_type: "application/pdf".to_string(),
+                data: std::fs::read(&path).map_err(|e| format!("Failed to read report: {}", e))?,
+            })
+        }
+    }
+}
+
+/// Mails the summary of a stored crawl to the recipients, with the report attached if asked.
+pub async fn send_crawl_report(
+    roots: &ProjectRoots,
+    settings: &Settings,
+    recipients: &[String],
+    crawl_id: i64,
+    report: Option<ReportAttachment>,
+) -> Result<(), String> {
+    let config = SmtpConfig::from_settings(settings)?;
+    let store = ResultsStore::open_in(&roots.db_dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    let summary = summary::summarise_crawl(&store, crawl_id).await?;
+
+    let attachments = match report {
+        Some(kind) => vec![attachment(roots, crawl_id, kind).await?],
+        None => Vec::new(),
+    };
+    let email = Email {
+        from: config.from.clone(),
+        to: recipients.to_vec(),
+        subject: format!(
+            "RustySEO crawl of {}: {} pages",
+            summary.crawl.domain, summary.pages
+        ),
+        body: summary_body(&summary),
+        attachments,
+    };
+    smtp::send(&config, &email).await?;
+
+    println!(
+        "Crawl report {} emailed to {}",
+        crawl_id,
+        recipients.join(", ")
+    );
+    Ok(())
+}
+
+// SEND A TEST EMAIL TO CHECK THE SMTP SETTINGS
+#[tauri::command]
+pub async fn send_test_email(
+    to: String,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let settings = settings_state.settings.read().await.clone();
+    let config = SmtpConfig::from_settings(&settings)?;
+    let email = Email {
+        from: config.from.clone(),
+        to: vec![to],
+        subject: "RustySEO test email".to_string(),
+        body: "Your SMTP settings work, crawl reports can be emailed from RustySEO.\n".to_string(),
+        attachments: Vec::new(),
+    };
+    smtp::send(&config, &email).await
+}
+
+#[tauri::command]
+pub async fn email_crawl_report(
+    crawl_id: i64,
+    recipients: Vec<String>,
+    attachment: Option<ReportAttachment>,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let settings = settings_state.settings.read().await.clone();
+    send_crawl_report(
+        &ProjectRoots::active()?,
+        &settings,
+        &recipients,
+        crawl_id,
+        attachment,
+    )
+    .await
+}
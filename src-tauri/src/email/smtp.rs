@@ -0,0 +1,379 @@
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+use crate::settings::settings::Settings;
+
+use super::message::{address, check_mailbox, Email};
+
+const SEND_TIMEOUT: Duration = Duration::from_secs(60);
+const EHLO_NAME: &str = "localhost";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Security {
+    /// TLS from the first byte, usually port 465
+    Tls,
+    /// Plain connection upgraded with STARTTLS, usually port 587
+    StartTls,
+    /// No encryption, for relays on the local network
+    None,
+}
+
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: Security,
+    pub username: String,
+    pub password: String,
+    pub from: String,
+}
+
+impl SmtpConfig {
+    pub fn from_settings(settings: &Settings) -> Result<Self, String> {
+        let host = settings.smtp_host.trim();
+        if host.is_empty() {
+            return Err("Set the SMTP server to send emails".to_string());
+        }
+        check_mailbox(&settings.smtp_from).map_err(|_| "Set a valid sender address".to_string())?;
+        let security = match settings.smtp_security.trim().to_lowercase().as_str() {
+            "tls" | "ssl" => Security::Tls,
+            "starttls" => Security::StartTls,
+            "none" => Security::None,
+            other => return Err(format!("Unknown SMTP security: {}", other)),
+        };
+        Ok(SmtpConfig {
+            host: host.to_string(),
+            port: settings.smtp_port,
+            security,
+            username: settings.smtp_username.trim().to_string(),
+            password: settings.smtp_password.clone(),
+            from: settings.smtp_from.trim().to_string(),
+        })
+    }
+}
+
+fn tls_connector() -> Result<TlsConnector, String> {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+async fn start_tls(host: &str, tcp: TcpStream) -> Result<TlsStream<TcpStream>, String> {
+    let server_name = ServerName::try_from(host.to_string()).map_err(|e| e.to_string())?;
+    tls_connector()?
+        .connect(server_name, tcp)
+        .await
+        .map_err(|e| format!("TLS handshake with {} failed: {}", host, e))
+}
+
+struct Session<S> {
+    stream: BufReader<S>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Session<S> {
+    fn new(stream: S) -> Self {
+        Session {
+            stream: BufReader::new(stream),
+        }
+    }
+
+    // A reply spans lines like "250-first" up to the last one, "250 last"
+    async fn reply(&mut self, expected: u16) -> Result<String, String> {
+        let mut text = String::new();
+        loop {
+            let mut line = String::new();
+            let read = self
+                .stream
+                .read_line(&mut line)
+                .await
+                .map_err(|e| format!("SMTP read failed: {}", e))?;
+            if read == 0 {
+                return Err("The SMTP server closed the connection".to_string());
+            }
+            text.push_str(&line);
+            if line.as_bytes().get(3) != Some(&b'-') {
+                break;
+            }
+        }
+        let code: u16 = text.get(..3).and_then(|c| c.parse().ok()).unwrap_or(0);
+        if code / 100 != expected / 100 {
+            return Err(format!("SMTP server answered: {}", text.trim()));
+        }
+        Ok(text)
+    }
+
+    async fn write(&mut self, data: &str) -> Result<(), String> {
+        let stream = self.stream.get_mut();
+        stream
+            .write_all(data.as_bytes())
+            .await
+            .map_err(|e| format!("SMTP write failed: {}", e))?;
+        stream
+            .flush()
+            .await
+            .map_err(|e| format!("SMTP write failed: {}", e))
+    }
+
+    async fn command(&mut self, line: &str, expected: u16) -> Result<String, String> {
+        self.write(&format!("{}\r\n", line)).await?;
+        self.reply(expected).await
+    }
+
+    async fn authenticate(&mut self, config: &SmtpConfig, ehlo: &str) -> Result<(), String> {
+        if config.username.is_empty() {
+            return Ok(());
+        }
+        let methods = ehlo
+            .lines()
+            .filter_map(|line| line.get(4..))
+            .find(|line| line.to_uppercase().starts_with("AUTH"))
+            .map(str::to_uppercase)
+            .unwrap_or_default();
+        if methods.contains("PLAIN") {
+            let token = STANDARD.encode(format!("\0{}\0{}", config.username, config.password));
+            self.command(&format!("AUTH PLAIN {}", token), 235).await?;
+        } else if methods.contains("LOGIN") {
+            self.command("AUTH LOGIN", 334).await?;
+            self.command(&STANDARD.encode(&config.username), 334)
+                .await?;
+            self.command(&STANDARD.encode(&config.password), 235)
+                .await?;
+        } else {
+            return Err("The SMTP server offers no PLAIN or LOGIN authentication".to_string());
+        }
+        Ok(())
+    }
+
+    async fn deliver(
+        &mut self,
+        config: &SmtpConfig,
+        email: &Email,
+        ehlo: &str,
+    ) -> Result<(), String> {
+        self.authenticate(config, ehlo).await?;
+        self.command(&format!("MAIL FROM:<{}>", address(&email.from)), 250)
+            .await?;
+        for recipient in &email.to {
+            self.command(&format!("RCPT TO:<{}>", address(recipient)), 250)
+                .await?;
+        }
+        self.command("DATA", 354).await?;
+
+        self.write(&dot_stuffed(&email.to_mime())).await?;
+        self.reply(250).await?;
+
+        // The message is accepted, a failed goodbye does not matter
+        let _ = self.command("QUIT", 221).await;
+        Ok(())
+    }
+}
+
+// Lines starting with a dot get a second one, a lone dot ends the message
+fn dot_stuffed(message: &str) -> String {
+    let mut data: String = message
+        .split("\r\n")
+        .map(|line| match line.starts_with('.') {
+            true => format!(".{}\r\n", line),
+            false => format!("{}\r\n", line),
+        })
+        .collect();
+    data.push_str(".\r\n");
+    data
+}
+
+async fn send_email(config: &SmtpConfig, email: &Email) -> Result<(), String> {
+    if email.to.is_empty() {
+        return Err("No recipients to send to".to_string());
+    }
+    for mailbox in &email.to {
+        check_mailbox(mailbox)?;
+    }
+    if config.security == Security::None && !config.username.is_empty() {
+        return Err(
+            "Refusing to send the SMTP password over an unencrypted connection".to_string(),
+        );
+    }
+
+    let tcp = TcpStream::connect((config.host.as_str(), config.port))
+        .await
+        .map_err(|e| {
+            format!(
+                "Connecting to {}:{} failed: {}",
+                config.host, config.port, e
+            )
+        })?;
+    let ehlo_command = format!("EHLO {}", EHLO_NAME);
+
+    match config.security {
+        Security::Tls => {
+            let mut session = Session::new(start_tls(&config.host, tcp).await?);
+            session.reply(220).await?;
+            let ehlo = session.command(&ehlo_command, 250).await?;
+            session.deliver(config, email, &ehlo).await
+        }
+        Security::StartTls => {
+            let mut plain = Session::new(tcp);
+            plain.reply(220).await?;
+            let ehlo = plain.command(&ehlo_command, 250).await?;
+            if !ehlo.to_uppercase().contains("STARTTLS") {
+                return Err("The SMTP server does not offer STARTTLS".to_string());
+            }
+            plain.command("STARTTLS", 220).await?;
+
+            let tcp = plain.stream.into_inner();
+            let mut session = Session::new(start_tls(&config.host, tcp).await?);
+            // The capabilities can change once encrypted, so they are asked again
+            let ehlo = session.command(&ehlo_command, 250).await?;
+            session.deliver(config, email, &ehlo).await
+        }
+        Security::None => {
+            let mut session = Session::new(tcp);
+            session.reply(220).await?;
+            let ehlo = session.command(&ehlo_command, 250).await?;
+            session.deliver(config, email, &ehlo).await
+        }
+    }
+}
+
+/// Sends an email through the configured SMTP server.
+pub async fn send(config: &SmtpConfig, email: &Email) -> Result<(), String> {
+    timeout(SEND_TIMEOUT, send_email(config, email))
+        .await
+        .map_err(|_| format!("Timed out sending email through {}", config.host))?
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{duplex, DuplexStream};
+
+    use super::*;
+
+    // Answers every command with `replies` in turn and returns the lines it was sent
+    async fn fake_server(stream: DuplexStream, replies: &[&str]) -> Vec<String> {
+        let mut stream = BufReader::new(stream);
+        let mut received = Vec::new();
+        let mut replies = replies.iter();
+        let mut in_data = false;
+        loop {
+            let mut line = String::new();
+            if stream.read_line(&mut line).await.unwrap() == 0 {
+                return received;
+            }
+            let command = line.trim_end().to_string();
+            received.push(command.clone());
+            if in_data && command != "." {
+                continue;
+            }
+            in_data = command == "DATA";
+            let Some(reply) = replies.next() else {
+                return received;
+            };
+            stream
+                .get_mut()
+                .write_all(format!("{}\r\n", reply).as_bytes())
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn dot_lines_are_stuffed() {
+        let data = dot_stuffed("Subject: x\r\n\r\n.\r\n..twice\r\nnot.first");
+        assert_eq!(
+            data,
+            "Subject: x\r\n\r\n..\r\n...twice\r\nnot.first\r\n.\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn delivers_with_auth_plain() {
+        let config = SmtpConfig {
+            host: "localhost".to_string(),
+            port: 587,
+            security: Security::None,
+            username: "user".to_string(),
+            password: "secret".to_string(),
+            from: "reports@example.com".to_string(),
+        };
+        let email = Email {
+            from: "RustySEO <reports@example.com>".to_string(),
+            to: vec!["team@example.com".to_string()],
+            subject: "Report".to_string(),
+            body: "Done".to_string(),
+            attachments: Vec::new(),
+        };
+
+        let (client, server) = duplex(64 * 1024);
+        let replies = [
+            "235 ok",
+            "250 ok",
+            "250 ok",
+            "354 go",
+            "250 queued",
+            "221 bye",
+        ];
+        let server = tokio::spawn(async move { fake_server(server, &replies).await });
+        let ehlo = "250-localhost\r\n250-AUTH LOGIN PLAIN\r\n250 8BITMIME\r\n";
+        Session::new(client)
+            .deliver(&config, &email, ehlo)
+            .await
+            .unwrap();
+        let received = server.await.unwrap();
+
+        assert_eq!(
+            received[0],
+            format!("AUTH PLAIN {}", STANDARD.encode("\0user\0secret"))
+        );
+        assert_eq!(received[1], "MAIL FROM:<reports@example.com>");
+        assert_eq!(received[2], "RCPT TO:<team@example.com>");
+        assert_eq!(received[3], "DATA");
+        assert!(received.contains(&"Subject: Report".to_string()));
+        assert_eq!(received[received.len() - 2], ".");
+        assert_eq!(received[received.len() - 1], "QUIT");
+    }
+
+    #[tokio::test]
+    async fn rejected_commands_stop_the_delivery() {
+        let config = SmtpConfig {
+            host: "localhost".to_string(),
+            port: 25,
+            security: Security::None,
+            username: String::new(),
+            password: String::new(),
+            from: "reports@example.com".to_string(),
+        };
+        let email = Email {
+            from: config.from.clone(),
+            to: vec!["nobody@example.com".to_string()],
+            subject: "Report".to_string(),
+            body: String::new(),
+            attachments: Vec::new(),
+        };
+
+        let (client, server) = duplex(64 * 1024);
+        let replies = ["250 ok", "550 no such user"];
+        let server = tokio::spawn(async move { fake_server(server, &replies).await });
+        let error = Session::new(client)
+            .deliver(&config, &email, "250 localhost")
+            .await
+            .unwrap_err();
+
+        assert!(error.contains("550 no such user"));
+        assert!(!server.await.unwrap().contains(&"DATA".to_string()));
+    }
+}
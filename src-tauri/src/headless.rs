@@ -0,0 +1,310 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+
+use rusqlite::{params, Connection};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::domain_crawler::config_profiles::{self, ConfigProfile};
+use crate::domain_crawler::events::CrawlEvents;
+use crate::domain_crawler::exports;
+use crate::domain_crawler::models::DomainCrawlResults;
+use crate::domain_crawler::results_store::{to_page_row, ResultsStore};
+use crate::domain_crawler::{crawl_control, domain_commands, domain_crawler, webhooks};
+use crate::projects::registry::ProjectRoots;
+use crate::settings::settings::Settings;
+
+const USAGE: &str =
+    "Usage: RustySEO crawl <config.toml> [--out <dir>] [--format csv,json,sqlite,xlsx]";
+const FORMATS: [&str; 4] = ["csv", "json", "sqlite", "xlsx"];
+
+/// What to crawl and where to write it, read from the TOML file given on the command line.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HeadlessConfig {
+    /// A domain to crawl by following links
+    pub domain: Option<String>,
+    /// Or exactly these URLs, and those of `sitemap`
+    pub urls: Vec<String>,
+    pub sitemap: Option<String>,
+    /// A saved crawl profile applied over the app settings
+    pub profile: Option<String>,
+    /// Settings written over the profile, with the same keys a profile takes
+    pub settings: BTreeMap<String, Value>,
+    pub output_dir: Option<String>,
+    pub formats: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HeadlessArgs {
+    pub config: PathBuf,
+    pub output_dir: Option<PathBuf>,
+    pub formats: Option<Vec<String>>,
+}
+
+impl HeadlessArgs {
+    /// The headless arguments when the app was started with `crawl`, None to start the UI.
+    pub fn from_env() -> Option<Result<Self, String>> {
+        let mut args = std::env::args().skip(1);
+        if args.next().as_deref() != Some("crawl") {
+            return None;
+        }
+        Some(Self::parse(args))
+    }
+
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut config = None;
+        let mut output_dir = None;
+        let mut formats = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--out" => {
+                    output_dir = Some(PathBuf::from(args.next().ok_or("--out needs a directory")?))
+                }
+                "--format" => {
+                    formats = Some(
+                        args.next()
+                            .ok_or("--format needs a list of formats")?
+                            .split(',')
+                            .map(|format| format.trim().to_lowercase())
+                            .filter(|format| !format.is_empty())
+                            .collect(),
+                    )
+                }
+                other if other.starts_with("--") => {
+                    return Err(format!("Unknown option {}", other));
+                }
+                _ if config.is_none() => config = Some(PathBuf::from(arg)),
+                _ => return Err(format!("Unexpected argument {}", arg)),
+            }
+        }
+        Ok(HeadlessArgs {
+            config: config.ok_or("Missing the config file")?,
+            output_dir,
+            formats,
+        })
+    }
+}
+
+fn read_config(path: &Path) -> Result<HeadlessConfig, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    toml::from_str(&contents).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Runs one crawl from a config file without the UI and writes its outputs, for CI and cron jobs.
+///
+/// Returns the process exit code. No Tauri app is built, the crawler reports to the console.
+pub async fn run(args: Result<HeadlessArgs, String>, settings: Arc<RwLock<Settings>>) -> i32 {
+    let args = match args {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return 2;
+        }
+    };
+
+    let settings = settings.read().await.clone();
+    let result = crawl(args, settings, CrawlEvents::Console).await;
+    // The process exits right after, the webhooks of the crawl must be out first
+    webhooks::flush().await;
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Headless crawl failed: {}", e);
+            1
+        }
+    }
+}
+
+async fn crawl(args: HeadlessArgs, settings: Settings, events: CrawlEvents) -> Result<(), String> {
+    let config = read_config(&args.config)?;
+    let formats = match args.formats {
+        Some(formats) => formats,
+        None if config.formats.is_empty() => vec!["csv".to_string(), "json".to_string()],
+        None => config
+            .formats
+            .iter()
+            .map(|format| format.trim().to_lowercase())
+            .collect(),
+    };
+    if let Some(format) = formats
+        .iter()
+        .find(|format| !FORMATS.contains(&format.as_str()))
+    {
+        return Err(format!("Unknown output format {}", format));
+    }
+    let output_dir = args
+        .output_dir
+        .or_else(|| config.output_dir.as_ref().map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("rustyseo-output"));
+    std::fs::create_dir_all(&output_dir)
+        .map_err(|e| format!("Failed to create {}: {}", output_dir.display(), e))?;
+
+    let roots = ProjectRoots::active()?;
+    let settings =
+        config_profiles::crawl_settings(&roots, settings, config.profile.as_deref()).await?;
+    let settings = ConfigProfile {
+        name: args.config.display().to_string(),
+        description: String::new(),
+        settings: config.settings,
+    }
+    .apply(&settings)?;
+
+    let (target, list) = match &config.domain {
+        Some(domain) if config.urls.is_empty() => (domain.trim().to_string(), None),
+        Some(_) => return Err("Set either a domain or a list of URLs, not both".to_string()),
+        None => {
+            let (first, list) = domain_commands::url_list(&config.urls, config.sitemap)?;
+            (first, Some(list))
+        }
+    };
+
+    let crawl = crawl_control::try_start()?;
+    let db = domain_commands::crawl_database(&roots).await?;
+    let outcome = domain_crawler::crawl_domain(
+        &target,
+        events,
+        &roots,
+        Ok(db),
+        settings,
+        false,
+        list,
+        crawl,
+    )
+    .await?;
+    println!("Crawled {} pages of {}", outcome.pages, target);
+
+    // Every format is written from the results store, a page at a time
+    let crawl_id = outcome
+        .crawl_id
+        .ok_or("The crawl was not saved to the results store")?;
+    let store = ResultsStore::open_in(&roots.db_dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    for format in &formats {
+        let path = match format.as_str() {
+            "json" => {
+                let path = output_dir.join("pages.json");
+                write_json(&store, crawl_id, &path).await?;
+                path
+            }
+            "sqlite" => {
+                let path = output_dir.join("crawl.sqlite");
+                write_sqlite(&store, crawl_id, &path).await?;
+                path
+            }
+            "csv" => {
+                let path = output_dir.join("pages.csv");
+                exports::csv::export_pages(crawl_id, path.clone()).await?;
+                path
+            }
+            _ => {
+                let path = output_dir.join("report.xlsx");
+                exports::xlsx::export_report(&roots, crawl_id, path.clone()).await?;
+                path
+            }
+        };
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+// The pages as one JSON array, without the HTML they keep for rendering
+async fn write_json(store: &ResultsStore, crawl_id: i64, path: &Path) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+    let out = Arc::new(StdMutex::new(BufWriter::new(file)));
+    out.lock()
+        .map_err(|e| e.to_string())?
+        .write_all(b"[")
+        .map_err(|e| e.to_string())?;
+
+    let sink = out.clone();
+    let mut first = true;
+    store
+        .for_each_page(crawl_id, move |mut page| {
+            strip_html(&mut page);
+            let mut out = sink.lock().map_err(|e| e.to_string())?;
+            if !std::mem::take(&mut first) {
+                out.write_all(b",").map_err(|e| e.to_string())?;
+            }
+            serde_json::to_writer_pretty(&mut *out, &page).map_err(|e| e.to_string())
+        })
+        .await?;
+
+    let mut out = out.lock().map_err(|e| e.to_string())?;
+    out.write_all(b"]").map_err(|e| e.to_string())?;
+    out.flush().map_err(|e| e.to_string())
+}
+
+// A standalone database, the flat page columns for queries and the whole page as JSON
+async fn write_sqlite(store: &ResultsStore, crawl_id: i64, path: &Path) -> Result<(), String> {
+    if path.exists() {
+        std::fs::remove_file(path).map_err(|e| format!("Failed to replace {:?}: {}", path, e))?;
+    }
+    let conn = Connection::open(path).map_err(|e| e.to_string())?;
+    conn.execute_batch(
+        r#"
+        CREATE TABLE pages (
+            url TEXT PRIMARY KEY,
+            status_code INTEGER NOT NULL,
+            title TEXT,
+            description TEXT NOT NULL,
+            h1 TEXT,
+            word_count INTEGER NOT NULL,
+            response_time REAL,
+            content_type TEXT NOT NULL,
+            content_length INTEGER NOT NULL,
+            indexability REAL NOT NULL,
+            data TEXT NOT NULL
+        );
+        BEGIN;
+        "#,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let conn = Arc::new(StdMutex::new(conn));
+    let sink = conn.clone();
+    store
+        .for_each_page(crawl_id, move |mut page| {
+            strip_html(&mut page);
+            let row = to_page_row(&page);
+            let data = serde_json::to_string(&page).map_err(|e| e.to_string())?;
+            let conn = sink.lock().map_err(|e| e.to_string())?;
+            let mut stmt = conn
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO pages (url, status_code, title, description, h1, word_count,
+                        response_time, content_type, content_length, indexability, data)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                )
+                .map_err(|e| e.to_string())?;
+            stmt.execute(params![
+                row.url,
+                row.status_code,
+                row.title,
+                row.description,
+                row.h1,
+                row.word_count as i64,
+                row.response_time,
+                row.content_type,
+                row.content_length as i64,
+                row.indexability as f64,
+                data,
+            ])
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await?;
+
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    conn.execute_batch("COMMIT").map_err(|e| e.to_string())
+}
+
+fn strip_html(page: &mut DomainCrawlResults) {
+    page.raw_html = None;
+    page.rendered_html = None;
+}
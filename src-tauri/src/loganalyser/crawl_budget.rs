@@ -0,0 +1,334 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use futures::stream::{self, StreamExt};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tokio::sync::Mutex;
+use trust_dns_resolver::TokioAsyncResolver;
+
+use super::analyser::ProgressUpdate;
+use super::helpers::check_hostname::verify_crawler_ip;
+use super::helpers::crawler_type::{search_bot, SEARCH_BOTS};
+use super::helpers::log_files::for_each_line;
+use super::helpers::parse_logs::{parse_log_line, LogEntry};
+
+// Concurrent reverse DNS lookups while verifying crawler IPs
+const DNS_CONCURRENCY: usize = 32;
+
+// Report of the most recently analysed logs, joined with crawls afterwards
+static LAST_REPORT: Lazy<Mutex<Option<CrawlBudgetReport>>> = Lazy::new(|| Mutex::new(None));
+
+/// Why a crawler hit spent budget on a URL that should not be crawled.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum WasteReason {
+    Redirect,
+    ClientError,
+    ServerError,
+    /// A query string on an otherwise successful URL
+    Parameters,
+}
+
+impl WasteReason {
+    fn of(entry: &LogEntry) -> Option<Self> {
+        match entry.status {
+            // A 304 answers a conditional request cheaply, it is not a redirect
+            304 => None,
+            300..=399 => Some(WasteReason::Redirect),
+            400..=499 => Some(WasteReason::ClientError),
+            500..=599 => Some(WasteReason::ServerError),
+            _ if entry.query.as_deref().is_some_and(|q| !q.is_empty()) => {
+                Some(WasteReason::Parameters)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Verified search engine crawler activity on one URL path.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathCrawlStats {
+    pub path: String,
+    pub hits: usize,
+    pub bots: BTreeMap<String, usize>,
+    pub status_codes: BTreeMap<u16, usize>,
+    pub first_crawled: Option<String>,
+    pub last_crawled: Option<String>,
+    /// Days with at least one hit
+    pub days_crawled: usize,
+    /// Average hits per day over the whole span of the logs
+    pub hits_per_day: f64,
+    pub wasted_hits: usize,
+    pub waste: BTreeMap<WasteReason, usize>,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BotSummary {
+    pub name: String,
+    pub hits: usize,
+    pub verified_hits: usize,
+    /// Hits with the crawler's user agent from an address that is not the crawler's
+    pub spoofed_hits: usize,
+    pub unique_ips: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrawlBudgetReport {
+    pub files: Vec<String>,
+    pub lines: usize,
+    /// Lines that are not in the combined log format
+    pub unparsed_lines: usize,
+    pub verified_hits: usize,
+    pub spoofed_hits: usize,
+    pub bots: Vec<BotSummary>,
+    pub status_codes: BTreeMap<u16, usize>,
+    pub wasted_hits: usize,
+    /// Share of the verified hits spent on wasted URLs, in percent
+    pub wasted_percentage: f32,
+    pub log_start_time: Option<String>,
+    pub log_finish_time: Option<String>,
+    /// Most crawled paths first
+    pub paths: Vec<PathCrawlStats>,
+}
+
+pub async fn store_report(report: CrawlBudgetReport) {
+    *LAST_REPORT.lock().await = Some(report);
+}
+
+pub async fn last_report() -> Option<CrawlBudgetReport> {
+    LAST_REPORT.lock().await.clone()
+}
+
+#[derive(Default)]
+struct PathTally {
+    stats: PathCrawlStats,
+    days: HashSet<NaiveDate>,
+    first: Option<NaiveDateTime>,
+    last: Option<NaiveDateTime>,
+}
+
+// Index into SEARCH_BOTS of the crawler a user agent claims to be
+fn bot_index(user_agent: &str) -> Option<usize> {
+    let bot = search_bot(user_agent)?;
+    SEARCH_BOTS.iter().position(|b| b.name == bot.name)
+}
+
+fn earliest(current: Option<NaiveDateTime>, time: NaiveDateTime) -> Option<NaiveDateTime> {
+    Some(current.map_or(time, |current| current.min(time)))
+}
+
+fn latest(current: Option<NaiveDateTime>, time: NaiveDateTime) -> Option<NaiveDateTime> {
+    Some(current.map_or(time, |current| current.max(time)))
+}
+
+fn format_time(time: Option<NaiveDateTime>) -> Option<String> {
+    time.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+fn emit_progress(app: &tauri::AppHandle, files: &[PathBuf], index: usize, phase: &str) {
+    let update = ProgressUpdate {
+        current_file: index + 1,
+        total_files: files.len(),
+        percentage: (index as f32 / files.len().max(1) as f32) * 100.0,
+        filename: files
+            .get(index)
+            .map(|f| f.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        phase: phase.to_string(),
+    };
+    let _ = app.emit("progress-update", update);
+}
+
+// First pass, the addresses every claimed search engine crawler came from
+fn collect_crawler_ips(
+    files: &[PathBuf],
+    app: &tauri::AppHandle,
+) -> Result<HashSet<(usize, String)>, String> {
+    let mut ips = HashSet::new();
+    for (index, file) in files.iter().enumerate() {
+        emit_progress(app, files, index, "reading");
+        for_each_line(file, |line| {
+            let Some(entry) = parse_log_line(line) else {
+                return;
+            };
+            if let Some(bot_index) = bot_index(&entry.user_agent) {
+                ips.insert((bot_index, entry.ip));
+            }
+        })?;
+    }
+    Ok(ips)
+}
+
+async fn verify_ips(
+    ips: HashSet<(usize, String)>,
+) -> Result<HashMap<(usize, String), bool>, String> {
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()
+        .map_err(|e| format!("Failed to set up the DNS resolver: {}", e))?;
+    let resolver = &resolver;
+    Ok(stream::iter(ips)
+        .map(|(bot_index, ip)| async move {
+            let verified = match ip.parse::<IpAddr>() {
+                Ok(address) => {
+                    verify_crawler_ip(resolver, address, SEARCH_BOTS[bot_index].hostnames).await
+                }
+                Err(_) => false,
+            };
+            ((bot_index, ip), verified)
+        })
+        .buffer_unordered(DNS_CONCURRENCY)
+        .collect()
+        .await)
+}
+
+// Second pass, tallies the hits of verified crawlers per path
+fn tally_paths(
+    files: &[PathBuf],
+    verified: &HashMap<(usize, String), bool>,
+    app: &tauri::AppHandle,
+) -> Result<CrawlBudgetReport, String> {
+    let mut report = CrawlBudgetReport {
+        files: files
+            .iter()
+            .map(|f| f.to_string_lossy().into_owned())
+            .collect(),
+        ..Default::default()
+    };
+    let mut bots: Vec<BotSummary> = SEARCH_BOTS
+        .iter()
+        .map(|bot| BotSummary {
+            name: bot.name.to_string(),
+            ..Default::default()
+        })
+        .collect();
+    let mut paths: HashMap<String, PathTally> = HashMap::new();
+    let mut first: Option<NaiveDateTime> = None;
+    let mut last: Option<NaiveDateTime> = None;
+
+    for (index, file) in files.iter().enumerate() {
+        emit_progress(app, files, index, "analysing");
+        report.lines += for_each_line(file, |line| {
+            if line.trim().is_empty() {
+                return;
+            }
+            let Some(entry) = parse_log_line(line) else {
+                report.unparsed_lines += 1;
+                return;
+            };
+            let Some(bot_index) = bot_index(&entry.user_agent) else {
+                return;
+            };
+
+            let summary = &mut bots[bot_index];
+            summary.hits += 1;
+            if !verified
+                .get(&(bot_index, entry.ip.clone()))
+                .copied()
+                .unwrap_or(false)
+            {
+                summary.spoofed_hits += 1;
+                report.spoofed_hits += 1;
+                return;
+            }
+            summary.verified_hits += 1;
+            report.verified_hits += 1;
+            *report.status_codes.entry(entry.status).or_default() += 1;
+            first = earliest(first, entry.timestamp);
+            last = latest(last, entry.timestamp);
+
+            let tally = paths.entry(entry.path.clone()).or_default();
+            tally.stats.hits += 1;
+            tally.stats.bytes += entry.response_size;
+            *tally
+                .stats
+                .bots
+                .entry(SEARCH_BOTS[bot_index].name.to_string())
+                .or_default() += 1;
+            *tally.stats.status_codes.entry(entry.status).or_default() += 1;
+            tally.days.insert(entry.timestamp.date());
+            tally.first = earliest(tally.first, entry.timestamp);
+            tally.last = latest(tally.last, entry.timestamp);
+            if let Some(reason) = WasteReason::of(&entry) {
+                tally.stats.wasted_hits += 1;
+                *tally.stats.waste.entry(reason).or_default() += 1;
+                report.wasted_hits += 1;
+            }
+        })?;
+    }
+
+    // Spoofed hits come from all over, count the addresses of the real crawler
+    for ((bot_index, _), _) in verified.iter().filter(|(_, verified)| **verified) {
+        bots[*bot_index].unique_ips += 1;
+    }
+
+    let log_days = match (first, last) {
+        (Some(first), Some(last)) => (last.date() - first.date()).num_days() + 1,
+        _ => 1,
+    } as f64;
+    let mut paths: Vec<PathCrawlStats> = paths
+        .into_iter()
+        .map(|(path, tally)| {
+            let mut stats = tally.stats;
+            stats.path = path;
+            stats.days_crawled = tally.days.len();
+            stats.hits_per_day = ((stats.hits as f64 / log_days) * 100.0).round() / 100.0;
+            stats.first_crawled = format_time(tally.first);
+            stats.last_crawled = format_time(tally.last);
+            stats
+        })
+        .collect();
+    paths.sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.path.cmp(&b.path)));
+
+    report.wasted_percentage = if report.verified_hits > 0 {
+        (report.wasted_hits as f32 / report.verified_hits as f32) * 100.0
+    } else {
+        0.0
+    };
+    report.bots = bots.into_iter().filter(|bot| bot.hits > 0).collect();
+    report.log_start_time = format_time(first);
+    report.log_finish_time = format_time(last);
+    report.paths = paths;
+    Ok(report)
+}
+
+/// Reads access logs straight from disk and reports how search engines crawled the site.
+///
+/// Crawlers are told apart by user agent and confirmed through reverse and forward DNS, so
+/// scrapers posing as Googlebot do not skew the numbers. The files are streamed twice, first
+/// for the crawler addresses and then for the per-path tally, and never held in memory.
+pub async fn analyse_log_files(
+    files: Vec<String>,
+    app: tauri::AppHandle,
+) -> Result<CrawlBudgetReport, String> {
+    let files: Vec<PathBuf> = files.into_iter().map(PathBuf::from).collect();
+    if let Some(missing) = files.iter().find(|file| !Path::new(file).is_file()) {
+        return Err(format!("Log file not found: {:?}", missing));
+    }
+
+    let (files, ips) = {
+        let app = app.clone();
+        tokio::task::spawn_blocking(move || {
+            let ips = collect_crawler_ips(&files, &app)?;
+            Ok::<_, String>((files, ips))
+        })
+        .await
+        .map_err(|e| e.to_string())??
+    };
+
+    println!("Verifying {} crawler addresses", ips.len());
+    emit_progress(&app, &files, 0, "verifying");
+    let verified = verify_ips(ips).await?;
+
+    let report = tokio::task::spawn_blocking(move || tally_paths(&files, &verified, &app))
+        .await
+        .map_err(|e| e.to_string())??;
+    println!(
+        "Analysed {} log lines, {} verified crawler hits",
+        report.lines, report.verified_hits
+    );
+    store_report(report.clone()).await;
+    Ok(report)
+}
@@ -37,3 +37,32 @@ pub async fn reverse_lookup(ip: String) -> Option<String> {
         .next()
         .map(|name| name.to_string())
 }
+
+/// Whether `ip` really belongs to a crawler with one of `hostnames`.
+///
+/// Its reverse DNS has to end in one of them, and that host has to resolve back to `ip`,
+/// a PTR record alone can be set to anything by whoever owns the address.
+pub async fn verify_crawler_ip(
+    resolver: &TokioAsyncResolver,
+    ip: IpAddr,
+    hostnames: &[&str],
+) -> bool {
+    let Ok(names) = resolver.reverse_lookup(ip).await else {
+        return false;
+    };
+    for name in names.iter() {
+        let host = name.to_string().trim_end_matches('.').to_lowercase();
+        let matches = hostnames
+            .iter()
+            .any(|suffix| host == *suffix || host.ends_with(&format!(".{}", suffix)));
+        if !matches {
+            continue;
+        }
+        if let Ok(addresses) = resolver.lookup_ip(host.as_str()).await {
+            if addresses.iter().any(|address| address == ip) {
+                return true;
+            }
+        }
+    }
+    false
+}
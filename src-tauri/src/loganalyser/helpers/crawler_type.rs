@@ -17,3 +17,53 @@ pub fn is_crawler(user_agent: &str) -> bool {
         .iter()
         .any(|indicator| ua_lower.contains(indicator))
 }
+
+/// A search engine crawler that can be verified through its reverse DNS.
+#[derive(Debug)]
+pub struct SearchBot {
+    pub name: &'static str,
+    // Lowercase user agent token
+    token: &'static str,
+    /// Domains the reverse DNS of a genuine crawler IP ends with
+    pub hostnames: &'static [&'static str],
+}
+
+// Hostnames as documented by each search engine for verifying its crawler
+pub const SEARCH_BOTS: [SearchBot; 6] = [
+    SearchBot {
+        name: "Googlebot",
+        token: "googlebot",
+        hostnames: &["googlebot.com", "google.com"],
+    },
+    SearchBot {
+        name: "Bingbot",
+        token: "bingbot",
+        hostnames: &["search.msn.com"],
+    },
+    SearchBot {
+        name: "YandexBot",
+        token: "yandex",
+        hostnames: &["yandex.ru", "yandex.net", "yandex.com"],
+    },
+    SearchBot {
+        name: "Baiduspider",
+        token: "baiduspider",
+        hostnames: &["baidu.com", "baidu.jp"],
+    },
+    SearchBot {
+        name: "Applebot",
+        token: "applebot",
+        hostnames: &["applebot.apple.com"],
+    },
+    SearchBot {
+        name: "Yahoo Slurp",
+        token: "slurp",
+        hostnames: &["crawl.yahoo.net"],
+    },
+];
+
+/// The search engine crawler a user agent claims to be.
+pub fn search_bot(user_agent: &str) -> Option<&'static SearchBot> {
+    let ua_lower = user_agent.to_lowercase();
+    SEARCH_BOTS.iter().find(|bot| ua_lower.contains(bot.token))
+}
@@ -0,0 +1,38 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+
+/// Streams the lines of an access log, inflating rotated `.gz` archives on the fly.
+///
+/// Lines that are not valid UTF-8 are read lossily rather than ending the file early.
+/// Returns the number of lines read.
+pub fn for_each_line(path: &Path, mut on_line: impl FnMut(&str)) -> Result<usize, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+
+    // Gzip magic bytes, archives are not always named .gz
+    let mut magic = [0u8; 2];
+    let gzipped = file.read_exact(&mut magic).is_ok() && magic == [0x1f, 0x8b];
+    let file = File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let mut reader: Box<dyn BufRead> = if gzipped {
+        Box::new(BufReader::new(MultiGzDecoder::new(file)))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+
+    let mut buffer = Vec::new();
+    let mut lines = 0;
+    loop {
+        buffer.clear();
+        let read = reader
+            .read_until(b'\n', &mut buffer)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        if read == 0 {
+            break;
+        }
+        lines += 1;
+        on_line(&String::from_utf8_lossy(&buffer));
+    }
+    Ok(lines)
+}
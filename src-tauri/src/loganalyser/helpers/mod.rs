@@ -3,4 +3,5 @@ pub mod check_hostname;
 pub mod country_extractor;
 pub mod crawler_type;
 pub mod google_ip_fetcher;
+pub mod log_files;
 pub mod parse_logs;
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use std::io::{self, Write};
 use std::net::{AddrParseError, IpAddr};
 use std::str::FromStr;
-use std::sync::Mutex;
+use std::sync::{Mutex, RwLock};
 use tauri::{Emitter, Manager};
 
 use super::google_ip_fetcher::get_google_ip_ranges;
@@ -17,6 +17,8 @@ pub struct LogEntry {
     pub timestamp: NaiveDateTime,
     pub method: String,
     pub path: String,
+    /// The query string, without the `?`
+    pub query: Option<String>,
     pub status: u16,
     pub user_agent: String,
     pub referer: Option<String>,
@@ -32,7 +34,6 @@ pub struct LogEntry {
 #[derive(Debug)]
 pub enum IpVerificationError {
     InvalidIp(AddrParseError),
-    InvalidCidr(ipnet::PrefixLenError),
 }
 
 impl From<AddrParseError> for IpVerificationError {
@@ -41,12 +42,6 @@ impl From<AddrParseError> for IpVerificationError {
     }
 }
 
-impl From<ipnet::PrefixLenError> for IpVerificationError {
-    fn from(err: ipnet::PrefixLenError) -> Self {
-        IpVerificationError::InvalidCidr(err)
-    }
-}
-
 // Use a static variable to cache taxonomies
 static TAXONOMIES: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
 static LOG_NUMBER: Lazy<Mutex<i32>> = Lazy::new(|| Mutex::new(0));
@@ -82,6 +77,9 @@ fn classify_taxonomy(path: &str) -> String {
 /// https://developers.google.com/search/docs/crawling-indexing/verifying-googlebot
 static GOOGLE_IP_RANGES: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
 
+// The same ranges parsed once, every log line is checked against them
+static GOOGLE_NETWORKS: Lazy<RwLock<Vec<IpNet>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
 #[tauri::command]
 pub async fn fetch_google_ip_ranges() -> Result<Vec<String>, String> {
     // If we already have the ranges cached, return them
@@ -100,6 +98,15 @@ pub async fn fetch_google_ip_ranges() -> Result<Vec<String>, String> {
         let mut ranges = GOOGLE_IP_RANGES.lock().map_err(|e| e.to_string())?;
         *ranges = fetched_ranges.clone();
     }
+    let networks = fetched_ranges
+        .iter()
+        .filter_map(|cidr| {
+            IpNet::from_str(cidr)
+                .map_err(|e| eprintln!("Failed to parse CIDR {}: {}", cidr, e))
+                .ok()
+        })
+        .collect();
+    *GOOGLE_NETWORKS.write().map_err(|e| e.to_string())? = networks;
 
     Ok(fetched_ranges.iter().map(|n| n.to_string()).collect())
 }
@@ -108,22 +115,9 @@ fn is_google_verified(ip: &str) -> Result<bool, IpVerificationError> {
     // Parse the input IP address
     let ip_addr = IpAddr::from_str(ip)?;
 
-    // Get a lock on the Mutex to access the ranges
-    let ranges = GOOGLE_IP_RANGES.lock().unwrap();
-
     // Check if the IP is within any of Google's verified CIDR ranges
-    for cidr in ranges.iter() {
-        let net = IpNet::from_str(cidr).map_err(|e| {
-            eprintln!("Failed to parse CIDR {}: {}", cidr, e);
-            IpVerificationError::InvalidCidr(ipnet::PrefixLenError)
-        })?;
-
-        if net.contains(&ip_addr) {
-            return Ok(true);
-        }
-    }
-
-    Ok(false)
+    let networks = GOOGLE_NETWORKS.read().unwrap();
+    Ok(networks.iter().any(|net| net.contains(&ip_addr)))
 }
 
 fn detect_file_type(path: &str) -> Option<String> {
@@ -215,15 +209,58 @@ fn detect_bot(user_agent: &str) -> Option<String> {
     Some("Human".to_string())
 }
 
-pub fn parse_log_entries(log: &str) -> Vec<LogEntry> {
-    let re = Regex::new(r#"(?x)
+// Apache and Nginx "combined" format, a size of "-" is logged for empty bodies
+static COMBINED_LOG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?x)
         ^(\S+)\s+\S+\s+\S+\s+\[([^\]]+)\]\s+                              # IP and timestamp
-        "(GET|POST|PUT|DELETE|HEAD|OPTIONS|PATCH)\s+([^?"]+)(?:\?[^"]*)?\s+HTTP/[0-9.]+"\s+  # Method and path
-        (\d{3})\s+(\d+)\s+                                                # Status and response size
+        "(GET|POST|PUT|DELETE|HEAD|OPTIONS|PATCH)\s+([^?"]+)(?:\?([^"]*))?\s+HTTP/[0-9.]+"\s+  # Method, path and query
+        (\d{3})\s+(\d+|-)\s+                                             # Status and response size
         "([^"]*)"\s+                                                      # Referer
         "([^"]*)"                                                         # User agent
-    "#).expect("Invalid regex pattern");
+    "#).expect("Invalid regex pattern")
+});
+
+/// Parses one access log line, `None` when it is not in the combined log format.
+pub fn parse_log_line(line: &str) -> Option<LogEntry> {
+    let caps = COMBINED_LOG.captures(line.trim())?;
+    let timestamp = match NaiveDateTime::parse_from_str(&caps[2], "%d/%b/%Y:%H:%M:%S %z") {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Error parsing timestamp '{}' - {}", &caps[2], e);
+            NaiveDateTime::from_timestamp_opt(0, 0).unwrap()
+        }
+    };
+
+    let referer = match caps[8].trim() {
+        "-" => None,
+        ref r => Some(r.to_string()),
+    };
+
+    let user_agent = caps[9].to_string();
+    let crawler_type = detect_bot(&user_agent).unwrap_or_default();
+    let browser = detect_browser(&user_agent).unwrap_or_default();
+    let ip = caps[1].to_string();
+    let verified = is_google_verified(&ip).unwrap_or(false); // Default to false on error
+
+    Some(LogEntry {
+        ip,
+        timestamp,
+        method: caps[3].to_string(),
+        path: caps[4].to_string(),
+        query: caps.get(5).map(|query| query.as_str().to_string()),
+        status: caps[6].parse().unwrap_or(0),
+        user_agent,
+        referer,
+        response_size: caps[7].parse().unwrap_or(0),
+        crawler_type,
+        browser,
+        file_type: detect_file_type(&caps[4]).unwrap_or_default(),
+        verified,
+        taxonomy: classify_taxonomy(&caps[4]),
+    })
+}
 
+pub fn parse_log_entries(log: &str) -> Vec<LogEntry> {
     println!("Log contains {} lines", log.lines().count());
 
     let mut entries = Vec::new();
@@ -237,46 +274,8 @@ pub fn parse_log_entries(log: &str) -> Vec<LogEntry> {
         print!("\rParsing line {}...", i + 1);
         io::stdout().flush().unwrap();
 
-        if let Some(caps) = re.captures(line) {
-            let timestamp = match NaiveDateTime::parse_from_str(&caps[2], "%d/%b/%Y:%H:%M:%S %z") {
-                Ok(t) => t,
-                Err(e) => {
-                    eprintln!(
-                        "Error parsing timestamp on line {}: '{}' - {}",
-                        i + 1,
-                        &caps[2],
-                        e
-                    );
-                    NaiveDateTime::from_timestamp_opt(0, 0).unwrap()
-                }
-            };
-
-            let referer = match caps[7].trim() {
-                "-" => None,
-                ref r => Some(r.to_string()),
-            };
-
-            let user_agent = caps[8].to_string();
-            let crawler_type = detect_bot(&user_agent).unwrap_or_default();
-            let browser = detect_browser(&user_agent).unwrap_or_default();
-            let ip = caps[1].to_string();
-            let verified = is_google_verified(&ip).unwrap_or(false); // Default to false on error
-
-            entries.push(LogEntry {
-                ip,
-                timestamp,
-                method: caps[3].to_string(),
-                path: caps[4].to_string(),
-                status: caps[5].parse().unwrap_or(0),
-                user_agent,
-                referer,
-                response_size: caps[6].parse().unwrap_or(0),
-                crawler_type,
-                browser,
-                file_type: detect_file_type(&caps[4]).unwrap_or_default(),
-                verified,
-                taxonomy: classify_taxonomy(&caps[4]),
-            });
+        if let Some(entry) = parse_log_line(line) {
+            entries.push(entry);
         }
     }
 
@@ -3,6 +3,8 @@ use super::{
     database::{add_data_to_serverlog_db, create_serverlog_db},
 };
 use crate::loganalyser::analyser::{analyse_log, LogAnalysisResult, LogInput};
+use crate::loganalyser::crawl_budget::{analyse_log_files, CrawlBudgetReport};
+use crate::loganalyser::log_crawl_join::{join_with_crawl, LogCrawlReport};
 
 #[tauri::command]
 pub fn check_logs_command(
@@ -32,3 +34,18 @@ pub fn check_logs_command(
         Err(e) => Err(e),
     }
 }
+
+// ANALYSE ACCESS LOG FILES FOR THE CRAWL BUDGET OF VERIFIED SEARCH ENGINE BOTS
+#[tauri::command]
+pub async fn analyse_crawl_budget_command(
+    files: Vec<String>,
+    app: tauri::AppHandle,
+) -> Result<CrawlBudgetReport, String> {
+    analyse_log_files(files, app).await
+}
+
+// JOIN THE ANALYSED LOGS WITH A STORED CRAWL
+#[tauri::command]
+pub async fn join_logs_with_crawl_command(crawl_id: i64) -> Result<LogCrawlReport, String> {
+    join_with_crawl(crawl_id).await
+}
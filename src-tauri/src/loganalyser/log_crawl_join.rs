@@ -0,0 +1,181 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::domain_crawler::helpers::indexability::IndexabilityVerdict;
+use crate::domain_crawler::results_store::ResultsStore;
+
+use super::crawl_budget::{self, PathCrawlStats};
+
+// Extensions of paths that are pages rather than assets, an orphaned stylesheet is no news
+const PAGE_EXTENSIONS: [&str; 5] = ["html", "htm", "php", "asp", "aspx"];
+
+/// The verified crawler hits of one crawled URL.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageLogHits {
+    pub url: String,
+    pub status_code: u16,
+    pub indexable: bool,
+    pub crawl_depth: Option<usize>,
+    pub hits: usize,
+    pub hits_per_day: f64,
+    pub last_crawled: Option<String>,
+}
+
+/// A path from the logs together with how often crawlers got each status for it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoggedPath {
+    pub path: String,
+    pub hits: usize,
+    pub status_codes: BTreeMap<u16, usize>,
+    pub last_crawled: Option<String>,
+}
+
+impl From<&PathCrawlStats> for LoggedPath {
+    fn from(stats: &PathCrawlStats) -> Self {
+        LoggedPath {
+            path: stats.path.clone(),
+            hits: stats.hits,
+            status_codes: stats.status_codes.clone(),
+            last_crawled: stats.last_crawled.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogCrawlReport {
+    pub crawl_id: i64,
+    pub crawled_pages: usize,
+    /// Crawled pages search engines requested at least once
+    pub hit_pages: usize,
+    pub pages: Vec<PageLogHits>,
+    /// Indexable pages the crawl found but no search engine requested
+    pub never_hit: Vec<String>,
+    /// Paths crawlers keep requesting that answer 404 or 410, most hits first
+    pub not_found_hits: Vec<LoggedPath>,
+    /// Paths crawlers keep requesting that redirect, most hits first
+    pub redirect_hits: Vec<LoggedPath>,
+    /// Pages search engines request that the crawl never reached through links
+    pub orphans: Vec<LoggedPath>,
+}
+
+// Log paths and crawled URLs are compared the way the url crate normalizes paths
+fn normalize_path(path: &str) -> String {
+    Url::parse("http://localhost")
+        .and_then(|base| base.join(path))
+        .map(|url| url.path().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+fn is_page_path(path: &str) -> bool {
+    let last = path.rsplit('/').next().unwrap_or("");
+    match last.rsplit_once('.') {
+        Some((_, extension)) => PAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()),
+        None => true,
+    }
+}
+
+fn hits_with(stats: &PathCrawlStats, statuses: impl Fn(u16) -> bool) -> usize {
+    stats
+        .status_codes
+        .iter()
+        .filter(|(status, _)| statuses(**status))
+        .map(|(_, hits)| hits)
+        .sum()
+}
+
+/// Joins the most recently analysed logs onto a stored crawl.
+///
+/// URLs are matched on their path alone, access logs do not record the host.
+pub async fn join_with_crawl(crawl_id: i64) -> Result<LogCrawlReport, String> {
+    let logs = crawl_budget::last_report()
+        .await
+        .ok_or("Analyse the log files before joining them with a crawl")?;
+    let mut logged: HashMap<String, &PathCrawlStats> = HashMap::new();
+    for stats in &logs.paths {
+        logged.insert(normalize_path(&stats.path), stats);
+    }
+
+    let store = ResultsStore::open().await.map_err(|e| e.to_string())?;
+    let collected = Arc::new(Mutex::new(Vec::new()));
+    let sink = collected.clone();
+    store
+        .for_each_page(crawl_id, move |page| {
+            let Ok(url) = Url::parse(&page.url) else {
+                return Ok(());
+            };
+            let hits = PageLogHits {
+                url: page.url,
+                status_code: page.status_code,
+                indexable: page.indexability.verdict == IndexabilityVerdict::Indexable,
+                crawl_depth: page.crawl_depth,
+                ..Default::default()
+            };
+            sink.lock()
+                .map_err(|e| e.to_string())?
+                .push((url.path().to_string(), hits));
+            Ok(())
+        })
+        .await?;
+    let crawled = std::mem::take(&mut *collected.lock().map_err(|e| e.to_string())?);
+
+    let mut report = LogCrawlReport {
+        crawl_id,
+        crawled_pages: crawled.len(),
+        ..Default::default()
+    };
+    let mut crawled_paths = HashSet::new();
+    for (path, mut page) in crawled {
+        if let Some(stats) = logged.get(&path) {
+            page.hits = stats.hits;
+            page.hits_per_day = stats.hits_per_day;
+            page.last_crawled = stats.last_crawled.clone();
+            report.hit_pages += 1;
+        } else if page.indexable && page.status_code == 200 {
+            report.never_hit.push(page.url.clone());
+        }
+        crawled_paths.insert(path);
+        report.pages.push(page);
+    }
+    report
+        .pages
+        .sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.url.cmp(&b.url)));
+    report.never_hit.sort();
+
+    // Log paths come most hit first, orphans keep that order
+    let mut not_found = Vec::new();
+    let mut redirects = Vec::new();
+    for stats in &logs.paths {
+        let path = normalize_path(&stats.path);
+        let not_found_hits = hits_with(stats, |status| status == 404 || status == 410);
+        if not_found_hits > 0 {
+            not_found.push((not_found_hits, LoggedPath::from(stats)));
+        }
+        let redirect_hits = hits_with(stats, |status| {
+            (300..400).contains(&status) && status != 304
+        });
+        if redirect_hits > 0 {
+            redirects.push((redirect_hits, LoggedPath::from(stats)));
+        }
+        let succeeded = hits_with(stats, |status| status == 200 || status == 304) > 0;
+        if succeeded && is_page_path(&path) && !crawled_paths.contains(&path) {
+            report.orphans.push(stats.into());
+        }
+    }
+    // The most requested errors waste the most budget
+    not_found.sort_by_key(|(hits, _)| std::cmp::Reverse(*hits));
+    redirects.sort_by_key(|(hits, _)| std::cmp::Reverse(*hits));
+    report.not_found_hits = not_found.into_iter().map(|(_, path)| path).collect();
+    report.redirect_hits = redirects.into_iter().map(|(_, path)| path).collect();
+
+    println!(
+        "Joined {} log paths with {} pages of crawl {}, {} orphans",
+        logs.paths.len(),
+        report.crawled_pages,
+        crawl_id,
+        report.orphans.len()
+    );
+    Ok(report)
+}
@@ -1,6 +1,8 @@
 pub mod analyser;
+pub mod crawl_budget;
 pub mod database;
 pub mod helpers;
 pub mod log_commands;
+pub mod log_crawl_join;
 pub mod log_state;
 pub mod logs_db;
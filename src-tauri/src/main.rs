@@ -22,9 +22,15 @@ use tokio::sync::Mutex;
 use tokio::sync::RwLock;
 use toml;
 
+pub mod ai;
+pub mod api_server;
+pub mod backlinks;
 pub mod chat;
 pub mod crawler;
 pub mod domain_crawler;
+pub mod email;
+pub mod headless;
+pub mod projects;
 pub mod settings;
 pub mod users;
 
@@ -46,6 +52,7 @@ pub mod genai;
 pub mod gsc;
 mod image_converter;
 pub mod loganalyser;
+pub mod rank_tracker;
 pub mod server;
 pub mod version;
 
@@ -156,6 +163,11 @@ async fn main() {
 
     // initialise the dbs
     let _start_db = crawler::db::databases_start();
+
+    // `crawl <config.toml>` runs a single crawl without the UI and exits
+    if let Some(args) = headless::HeadlessArgs::from_env() {
+        std::process::exit(headless::run(args, settings).await);
+    }
     // let _domain_results_db = domain_crawler::database::add_data().await;
 
     // Start the server
@@ -174,6 +186,13 @@ async fn main() {
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .manage(LinkResult { links: vec![] })
         .manage(AppState { settings })
+        .setup(|app| {
+            // Recurring crawls run in the background for the lifetime of the app
+            domain_crawler::scheduler::start(app.handle().clone());
+            rank_tracker::tracker::start(app.handle().clone());
+            api_server::start_if_enabled(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             crawl,
             fetch_page_speed,
@@ -223,6 +242,8 @@ async fn main() {
             commands::read_matched_keywords_from_db_command,
             commands::fetch_keywords_summarized_matched_command,
             domain_commands::domain_crawl_command,
+            domain_commands::list_crawl_command,
+            domain_commands::audit_url,
             domain_commands::create_excel,
             domain_commands::create_excel_main_table,
             // DEEP CRAWL DATABASE STUFF
@@ -236,13 +257,142 @@ async fn main() {
             domain_commands::generate_links_table_xlsx_command,
             commands::open_configs_with_native_editor,
             loganalyser::log_commands::check_logs_command,
+            loganalyser::log_commands::analyse_crawl_budget_command,
+            loganalyser::log_commands::join_logs_with_crawl_command,
             loganalyser::helpers::parse_logs::set_taxonomies,
             loganalyser::helpers::parse_logs::fetch_google_ip_ranges,
             loganalyser::helpers::check_hostname::reverse_lookup,
             domain_commands::get_url_diff_command,
+            domain_commands::get_broken_links_command,
+            domain_commands::get_canonical_report_command,
+            domain_commands::get_hreflang_report_command,
+            domain_commands::get_sitemap_report_command,
+            domain_commands::get_sitemap_gap_report,
+            domain_commands::get_redirect_report_command,
+            domain_commands::get_title_description_report_command,
+            domain_commands::get_duplicate_content_report_command,
+            domain_commands::get_render_report_command,
+            domain_commands::get_parameter_report_command,
+            domain_commands::get_depth_report_command,
+            domain_commands::get_security_headers_report_command,
+            domain_commands::get_tls_report_command,
+            domain_commands::get_tracking_report_command,
+            domain_commands::get_pagination_report_command,
+            domain_commands::get_preflight_report_command,
+            domain_commands::get_timing_report_command,
+            domain_commands::get_asset_report_command,
+            domain_commands::get_icon_report_command,
+            domain_commands::get_amp_report_command,
+            domain_commands::get_alt_text_report_command,
+            domain_commands::get_image_report_command,
+            domain_commands::get_keyword_report_command,
+            domain_commands::get_page_keywords_command,
+            domain_commands::get_entity_report_command,
+            domain_commands::get_a11y_report_command,
+            domain_crawler::crawler_config::get_crawler_config,
+            domain_crawler::crawler_config::set_crawler_config,
+            domain_crawler::config_profiles::list_config_profiles,
+            domain_crawler::config_profiles::save_config_profile,
+            domain_crawler::config_profiles::delete_config_profile,
+            domain_crawler::config_profiles::export_config_profile,
+            domain_crawler::config_profiles::import_config_profile,
+            domain_crawler::crawl_control::pause_crawl,
+            domain_crawler::crawl_control::resume_crawl,
+            domain_crawler::crawl_control::cancel_crawl,
+            domain_crawler::crawl_control::get_crawl_status,
+            domain_crawler::crawl_state_store::get_resumable_crawls,
+            domain_crawler::crawl_state_store::discard_saved_crawl,
+            domain_crawler::results_store::list_stored_crawls,
+            domain_crawler::results_store::query_crawl_results,
+            domain_crawler::results_store::get_crawl_page,
+            domain_crawler::crawl_diff::compare_crawls,
+            domain_crawler::issues::list_issue_types,
+            domain_crawler::issues::get_crawl_issues,
+            domain_crawler::issues::get_issue_trend,
+            domain_crawler::issues::compare_crawl_issues,
+            domain_crawler::extractors::custom::list_extraction_rules,
+            domain_crawler::extractors::custom::save_extraction_rule,
+            domain_crawler::extractors::custom::delete_extraction_rule,
+            domain_crawler::extractors::custom::test_extraction_rule,
+            domain_crawler::extractors::custom::get_custom_extractions,
+            domain_crawler::custom_search::list_search_patterns,
+            domain_crawler::custom_search::save_search_pattern,
+            domain_crawler::custom_search::delete_search_pattern,
+            domain_crawler::custom_search::get_custom_search_report,
+            domain_crawler::custom_search::get_crawl_custom_search,
+            domain_crawler::exports::csv::export_crawl_csv,
+            domain_crawler::exports::sitemap::export_sitemap,
+            domain_crawler::exports::sitemap::export_hreflang_sitemap,
+            domain_crawler::exports::policies::generate_crawl_policies,
+            domain_crawler::exports::policies::export_crawl_policies,
+            domain_crawler::exports::xlsx::export_crawl_xlsx,
+            domain_crawler::reports::generate_report,
+            domain_crawler::scheduler::list_crawl_schedules,
+            domain_crawler::scheduler::add_crawl_schedule,
+            domain_crawler::scheduler::remove_crawl_schedule,
+            domain_crawler::scheduler::set_crawl_schedule_enabled,
+            domain_crawler::scheduler::set_crawl_schedule_email,
+            rank_tracker::tracker::add_tracked_keyword,
+            rank_tracker::tracker::list_tracked_keywords,
+            rank_tracker::tracker::remove_tracked_keyword,
+            rank_tracker::tracker::check_tracked_keyword,
+            rank_tracker::tracker::get_keyword_rank_trend,
+            rank_tracker::tracker::get_url_rank_trend,
+            domain_crawler::screenshots::get_page_screenshots,
+            domain_crawler::helpers::robots::test_robots_rule,
+            domain_crawler::spell_check::list_spelling_ignore,
+            domain_crawler::spell_check::add_spelling_ignore,
+            domain_crawler::spell_check::remove_spelling_ignore,
+            domain_crawler::spell_check::list_spelling_dictionaries,
+            ai::suggestions::generate_snippet_suggestions,
+            ai::briefs::generate_content_brief,
+            ai::briefs::get_content_briefs,
+            ai::ollama::list_ollama_models,
+            ai::ollama::pull_ollama_model,
+            ai::ollama::select_ollama_model,
             domain_crawler::page_speed::store_key::read_page_speed_bulk_api_key,
             domain_crawler::page_speed::store_key::check_page_speed_bulk,
             domain_crawler::page_speed::store_key::toggle_page_speed_bulk,
+            domain_crawler::page_speed::psi::run_psi_for_crawl,
+            domain_crawler::page_speed::psi::get_psi_scores,
+            domain_crawler::gsc::fetch_gsc_for_crawl,
+            domain_crawler::gsc::get_gsc_page_metrics,
+            domain_crawler::ga4::fetch_ga4_for_crawl,
+            domain_crawler::ga4::get_ga4_page_metrics,
+            domain_crawler::bing_webmaster::generate_indexnow_key,
+            domain_crawler::bing_webmaster::submit_indexnow_urls,
+            domain_crawler::bing_webmaster::submit_crawl_to_indexnow,
+            domain_crawler::bing_webmaster::get_bing_crawl_stats,
+            domain_crawler::wayback::get_wayback_history,
+            domain_crawler::domain_info::get_domain_info,
+            domain_crawler::domain_info::get_crawl_domain_info,
+            backlinks::join::fetch_backlinks_for_crawl,
+            backlinks::join::get_backlink_page_metrics,
+            domain_crawler::competitors::add_competitor,
+            domain_crawler::competitors::remove_competitor,
+            domain_crawler::competitors::list_competitors,
+            domain_crawler::competitors::competitor_crawl_command,
+            domain_crawler::competitors::compare_competitor_crawls,
+            projects::commands::list_projects,
+            projects::commands::create_project,
+            projects::commands::rename_project,
+            projects::commands::set_project_domains,
+            projects::commands::delete_project,
+            projects::commands::switch_project,
+            api_server::start_api_server,
+            api_server::stop_api_server,
+            api_server::get_api_server_status,
+            api_server::regenerate_api_token,
+            domain_crawler::webhooks::list_webhooks,
+            domain_crawler::webhooks::save_webhook,
+            domain_crawler::webhooks::delete_webhook,
+            domain_crawler::webhooks::test_webhook,
+            email::report::send_test_email,
+            email::report::email_crawl_report,
+            domain_crawler::link_graph::get_link_graph,
+            domain_crawler::orphans::get_orphan_pages,
+            domain_crawler::anchor_text::get_anchor_report,
+            domain_crawler::device_comparison::compare_mobile_desktop,
             remove_all_logs_from_serverlog_db,
             loganalyser::database::read_logs_from_db,
             loganalyser::database::delete_log_from_db,
@@ -257,12 +407,12 @@ fn add_api_key(key: String, api_type: String) -> Result<String, String> {
     // Create config directory
     let project_dirs = ProjectDirs::from("", "", "rustyseo")
         .ok_or_else(|| "Failed to get project directories".to_string())?;
-    let config_dir = project_dirs.config_dir();
+    let config_dir = projects::registry::config_dir(&project_dirs);
 
     println!("Config directory: {:?}", config_dir);
     println!("project_dirs: {:?}", project_dirs);
 
-    std::fs::create_dir_all(config_dir)
+    std::fs::create_dir_all(&config_dir)
         .map_err(|e| format!("Failed to create config directory: {}", e))?;
 
     if api_type == "page_speed" {
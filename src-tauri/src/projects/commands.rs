@@ -0,0 +1,171 @@
+use chrono::Utc;
+use directories::ProjectDirs;
+use uuid::Uuid;
+
+use crate::domain_crawler::crawl_control;
+use crate::settings::settings::{init_settings, Settings};
+use crate::AppState;
+
+use super::registry::{self, Project, ProjectIndex};
+
+fn project_dirs() -> Result<ProjectDirs, String> {
+    ProjectDirs::from("", "", "rustyseo").ok_or("Failed to get project directories".to_string())
+}
+
+fn clean_name(name: &str, index: &ProjectIndex, id: Option<&str>) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("A project needs a name".to_string());
+    }
+    if index
+        .projects
+        .iter()
+        .any(|project| Some(project.id.as_str()) != id && project.name.eq_ignore_ascii_case(name))
+    {
+        return Err(format!("A project named {} already exists", name));
+    }
+    Ok(name.to_string())
+}
+
+fn clean_domains(domains: Vec<String>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for domain in domains.iter().map(|d| d.trim()).filter(|d| !d.is_empty()) {
+        if !cleaned.iter().any(|d| d.eq_ignore_ascii_case(domain)) {
+            cleaned.push(domain.to_string());
+        }
+    }
+    cleaned
+}
+
+fn ensure_idle() -> Result<(), String> {
+    if crawl_control::status() != crawl_control::CrawlStatus::Idle {
+        return Err("A crawl is running, wait for it to finish first".to_string());
+    }
+    Ok(())
+}
+
+fn update_project(id: &str, update: impl FnOnce(&mut Project)) -> Result<Project, String> {
+    let mut index = registry::load_index()?;
+    let project = index
+        .projects
+        .iter_mut()
+        .find(|project| project.id == id)
+        .ok_or_else(|| format!("Project {} not found", id))?;
+    update(project);
+    let project = project.clone();
+    registry::save_index(&index)?;
+    Ok(project)
+}
+
+// LIST THE PROJECTS AND THE ONE IN USE
+#[tauri::command]
+pub async fn list_projects() -> Result<ProjectIndex, String> {
+    registry::load_index()
+}
+
+// CREATE A PROJECT, STARTING FROM THE DEFAULT SETTINGS OR A COPY OF THE CURRENT ONES
+#[tauri::command]
+pub async fn create_project(
+    name: String,
+    domains: Vec<String>,
+    copy_settings: Option<bool>,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<Project, String> {
+    let mut index = registry::load_index()?;
+    let project = Project {
+        id: Uuid::new_v4().to_string(),
+        name: clean_name(&name, &index, None)?,
+        domains: clean_domains(domains),
+        created_at: Utc::now(),
+    };
+
+    let settings = if copy_settings.unwrap_or(false) {
+        settings_state.settings.read().await.clone()
+    } else {
+        Settings::new()
+    };
+    let dirs = project_dirs()?;
+    let db_dir = registry::project_db_dir(dirs.data_dir(), &project.id);
+    tokio::fs::create_dir_all(&db_dir)
+        .await
+        .map_err(|e| format!("Failed to create project dir: {}", e))?;
+    let toml_str =
+        toml::to_string(&settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    tokio::fs::write(registry::project_config_path(&dirs, &project.id), toml_str)
+        .await
+        .map_err(|e| format!("Failed to write project config: {}", e))?;
+
+    index.projects.push(project.clone());
+    registry::save_index(&index)?;
+    Ok(project)
+}
+
+#[tauri::command]
+pub async fn rename_project(id: String, name: String) -> Result<Project, String> {
+    let name = clean_name(&name, &registry::load_index()?, Some(&id))?;
+    update_project(&id, |project| project.name = name)
+}
+
+#[tauri::command]
+pub async fn set_project_domains(id: String, domains: Vec<String>) -> Result<Project, String> {
+    let domains = clean_domains(domains);
+    update_project(&id, |project| project.domains = domains)
+}
+
+// DELETE A PROJECT WITH ITS SETTINGS AND CRAWL HISTORY
+#[tauri::command]
+pub async fn delete_project(id: String) -> Result<(), String> {
+    if registry::active_project().as_deref() == Some(id.as_str()) {
+        return Err("Switch to another project before deleting this one".to_string());
+    }
+    let mut index = registry::load_index()?;
+    if index.find(&id).is_none() {
+        return Err(format!("Project {} not found", id));
+    }
+    index.projects.retain(|project| project.id != id);
+    registry::save_index(&index)?;
+
+    let dir = registry::project_dir(project_dirs()?.data_dir(), &id);
+    if dir.exists() {
+        tokio::fs::remove_dir_all(&dir)
+            .await
+            .map_err(|e| format!("Failed to delete project files: {}", e))?;
+    }
+    Ok(())
+}
+
+// SWITCH TO A PROJECT, OR BACK TO THE DEFAULT WORKSPACE WITH NONE, AND LOAD ITS SETTINGS
+#[tauri::command]
+pub async fn switch_project(
+    id: Option<String>,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<Option<Project>, String> {
+    ensure_idle()?;
+    let project = match &id {
+        Some(id) => Some(
+            registry::load_index()?
+                .find(id)
+                .cloned()
+                .ok_or_else(|| format!("Project {} not found", id))?,
+        ),
+        None => None,
+    };
+
+    activate(id, &settings_state).await?;
+    Ok(project)
+}
+
+/// Makes `id` the active project and loads its settings, for callers that checked no crawl runs.
+pub(crate) async fn activate(id: Option<String>, settings_state: &AppState) -> Result<(), String> {
+    let previous = registry::active_project();
+    registry::set_active_project(id)?;
+    let settings = match init_settings().await {
+        Ok(settings) => settings,
+        Err(e) => {
+            registry::set_active_project(previous)?;
+            return Err(e);
+        }
+    };
+    *settings_state.settings.write().await = settings;
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+const INDEX_FILE: &str = "projects.json";
+const CONFIG_FILE: &str = "configs.toml";
+
+// The project every settings and database path resolves against, None for the default workspace
+static ACTIVE: Lazy<RwLock<Option<String>>> =
+    Lazy::new(|| RwLock::new(load_index().ok().and_then(|index| index.active)));
+
+/// A workspace with its own settings file and crawl databases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    pub domains: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The list of projects and the one in use, kept in the config directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectIndex {
+    pub active: Option<String>,
+    pub projects: Vec<Project>,
+}
+
+impl ProjectIndex {
+    pub fn find(&self, id: &str) -> Option<&Project> {
+        self.projects.iter().find(|project| project.id == id)
+    }
+}
+
+fn project_dirs() -> Result<ProjectDirs, String> {
+    ProjectDirs::from("", "", "rustyseo").ok_or("Failed to get project directories".to_string())
+}
+
+fn index_path() -> Result<PathBuf, String> {
+    Ok(project_dirs()?.config_dir().join(INDEX_FILE))
+}
+
+/// Reads the project index, empty when no project was ever created.
+pub fn load_index() -> Result<ProjectIndex, String> {
+    let path = index_path()?;
+    if !path.exists() {
+        return Ok(ProjectIndex::default());
+    }
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read projects: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse projects: {}", e))
+}
+
+pub fn save_index(index: &ProjectIndex) -> Result<(), String> {
+    let path = index_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write projects: {}", e))
+}
+
+pub fn active_project() -> Option<String> {
+    ACTIVE.read().ok().and_then(|active| active.clone())
+}
+
+/// Switches the paths of later settings and database calls, and remembers it for the next start.
+pub fn set_active_project(id: Option<String>) -> Result<(), String> {
+    let mut index = load_index()?;
+    index.active = id.clone();
+    save_index(&index)?;
+    *ACTIVE.write().map_err(|e| e.to_string())? = id;
+    Ok(())
+}
+
+/// The settings file and folders of one project, resolved once so a crawl keeps using them
+/// whichever project is made active while it runs.
+#[derive(Debug, Clone)]
+pub struct ProjectRoots {
+    pub project: Option<String>,
+    /// The settings file, the crawl rules, webhooks and profiles sit next to it
+    pub config_path: PathBuf,
+    pub data_dir: PathBuf,
+    pub db_dir: PathBuf,
+}
+
+impl ProjectRoots {
+    pub fn of(project: Option<&str>) -> Result<Self, String> {
+        let dirs = project_dirs()?;
+        Ok(match project {
+            Some(id) => Self {
+                project: Some(id.to_string()),
+                config_path: project_config_path(&dirs, id),
+                data_dir: project_dir(dirs.data_dir(), id),
+                db_dir: project_db_dir(dirs.data_dir(), id),
+            },
+            None => Self {
+                project: None,
+                config_path: dirs.config_dir().join(CONFIG_FILE),
+                data_dir: dirs.data_dir().to_path_buf(),
+                db_dir: dirs.data_dir().join("db"),
+            },
+        })
+    }
+
+    pub fn active() -> Result<Self, String> {
+        Self::of(active_project().as_deref())
+    }
+
+    /// A file kept next to the settings of the project.
+    pub fn config_file(&self, name: &str) -> PathBuf {
+        self.config_path.with_file_name(name)
+    }
+}
+
+/// The directory holding the settings and databases of a project.
+pub fn project_dir(data_dir: &Path, id: &str) -> PathBuf {
+    data_dir.join("projects").join(id)
+}
+
+pub fn project_db_dir(data_dir: &Path, id: &str) -> PathBuf {
+    project_dir(data_dir, id).join("db")
+}
+
+/// Where the crawl databases live: the active project's folder, or the shared one without a project.
+pub fn db_dir(data_dir: &Path) -> PathBuf {
+    match active_project() {
+        Some(id) => project_db_dir(data_dir, &id),
+        None => data_dir.join("db"),
+    }
+}
+
+pub fn project_config_path(dirs: &ProjectDirs, id: &str) -> PathBuf {
+    project_dir(dirs.data_dir(), id).join(CONFIG_FILE)
+}
+
+/// The settings file of the active project, or the app-wide one without a project.
+pub fn config_path(dirs: &ProjectDirs) -> PathBuf {
+    match active_project() {
+        Some(id) => project_config_path(dirs, &id),
+        None => dirs.config_dir().join(CONFIG_FILE),
+    }
+}
+
+/// Where the active project keeps the credentials and output the app puts in its data
+/// folder, that folder itself without a project.
+pub fn data_dir(dirs: &ProjectDirs) -> PathBuf {
+    match active_project() {
+        Some(id) => project_dir(dirs.data_dir(), &id),
+        None => dirs.data_dir().to_path_buf(),
+    }
+}
+
+/// Where the active project keeps the files the app puts in its config folder.
+pub fn config_dir(dirs: &ProjectDirs) -> PathBuf {
+    config_dir_of(dirs, active_project().as_deref())
+}
+
+pub fn config_dir_of(dirs: &ProjectDirs, project: Option<&str>) -> PathBuf {
+    match project {
+        Some(id) => project_dir(dirs.data_dir(), id),
+        None => dirs.config_dir().to_path_buf(),
+    }
+}
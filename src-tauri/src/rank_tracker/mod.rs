@@ -0,0 +1,3 @@
+pub mod providers;
+pub mod store;
+pub mod tracker;
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::settings::settings::Settings;
+
+const SERPAPI_URL: &str = "https://serpapi.com/search.json";
+const VALUESERP_URL: &str = "https://api.valueserp.com/search";
+const SERPER_URL: &str = "https://google.serper.dev/search";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+// Most APIs cap a single request at 100 results
+const MAX_DEPTH: u64 = 100;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Device {
+    Desktop,
+    Mobile,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum SerpProviderKind {
+    SerpApi,
+    ValueSerp,
+    Serper,
+}
+
+/// One organic result of a search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerpResult {
+    pub position: u32,
+    pub url: String,
+    pub title: String,
+}
+
+/// A SERP API account, picked and keyed through the `serp_*` settings.
+pub struct SerpProvider {
+    pub kind: SerpProviderKind,
+    api_key: String,
+    depth: u64,
+    client: Client,
+}
+
+impl SerpProvider {
+    pub fn from_settings(settings: &Settings) -> Result<Self, String> {
+        let kind = match settings.serp_provider.trim().to_lowercase().as_str() {
+            "serpapi" => SerpProviderKind::SerpApi,
+            "valueserp" => SerpProviderKind::ValueSerp,
+            "serper" => SerpProviderKind::Serper,
+            other => return Err(format!("Unknown SERP provider: {}", other)),
+        };
+        let api_key = settings.serp_api_key.trim().to_string();
+        if api_key.is_empty() {
+            return Err("Set the SERP API key to track rankings".to_string());
+        }
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            kind,
+            api_key,
+            depth: settings.serp_depth.clamp(10, MAX_DEPTH),
+            client,
+        })
+    }
+
+    /// Fetches the organic Google results of `keyword`, optionally as seen from `location`.
+    pub async fn search(
+        &self,
+        keyword: &str,
+        location: Option<&str>,
+        device: Device,
+    ) -> Result<Vec<SerpResult>, String> {
+        let device = match device {
+            Device::Desktop => "desktop",
+            Device::Mobile => "mobile",
+        };
+        let num = self.depth.to_string();
+        let mut query = vec![("q", keyword), ("num", num.as_str()), ("device", device)];
+        if let Some(location) = location {
+            query.push(("location", location));
+        }
+
+        let request = match self.kind {
+            SerpProviderKind::SerpApi => {
+                query.extend([("engine", "google"), ("api_key", self.api_key.as_str())]);
+                self.client.get(SERPAPI_URL).query(&query)
+            }
+            SerpProviderKind::ValueSerp => {
+                query.push(("api_key", self.api_key.as_str()));
+                self.client.get(VALUESERP_URL).query(&query)
+            }
+            SerpProviderKind::Serper => {
+                let mut body = json!({ "q": keyword, "num": self.depth, "device": device });
+                if let Some(location) = location {
+                    body["location"] = json!(location);
+                }
+                self.client
+                    .post(SERPER_URL)
+                    .header("X-API-KEY", &self.api_key)
+                    .json(&body)
+            }
+        };
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach the SERP API: {}", e))?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to read the SERP API response: {}", e))?;
+        if !status.is_success() {
+            let message = body
+                .get("error")
+                .or_else(|| body.get("message"))
+                .map(|m| m.to_string())
+                .unwrap_or_default();
+            return Err(format!("SERP API answered {}: {}", status, message));
+        }
+
+        let key = match self.kind {
+            SerpProviderKind::Serper => "organic",
+            SerpProviderKind::SerpApi | SerpProviderKind::ValueSerp => "organic_results",
+        };
+        let results = body
+            .get(key)
+            .and_then(Value::as_array)
+            .map(|results| {
+                results
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, result)| {
+                        Some(SerpResult {
+                            // Not every provider numbers its results
+                            position: result
+                                .get("position")
+                                .and_then(Value::as_u64)
+                                .map(|p| p as u32)
+                                .unwrap_or(index as u32 + 1),
+                            url: result.get("link")?.as_str()?.to_string(),
+                            title: result
+                                .get("title")
+                                .and_then(Value::as_str)
+                                .unwrap_or_default()
+                                .to_string(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(results)
+    }
+}
@@ -0,0 +1,272 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::crawler::db::open_db_connection;
+use crate::domain_crawler::scheduler::Frequency;
+
+use super::providers::Device;
+
+const RANK_DB: &str = "rank_tracker.db";
+
+/// A keyword whose position is tracked for one domain, search location and device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedKeyword {
+    pub id: String,
+    pub keyword: String,
+    pub location: Option<String>,
+    /// Results on this host or its subdomains count as ranking
+    pub domain: String,
+    pub device: Device,
+    pub frequency: Frequency,
+    pub created_at: DateTime<Utc>,
+    pub next_check: DateTime<Utc>,
+}
+
+/// The position of a keyword at one check, `None` when the domain was not in the results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankPoint {
+    pub keyword_id: String,
+    pub checked_at: DateTime<Utc>,
+    pub position: Option<u32>,
+    pub url: Option<String>,
+    pub title: Option<String>,
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn parse_time(value: String) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, e.into())
+        })
+}
+
+fn device_name(device: Device) -> &'static str {
+    match device {
+        Device::Desktop => "desktop",
+        Device::Mobile => "mobile",
+    }
+}
+
+fn frequency_name(frequency: Frequency) -> &'static str {
+    match frequency {
+        Frequency::Daily => "daily",
+        Frequency::Weekly => "weekly",
+    }
+}
+
+fn keyword_from_row(row: &Row) -> rusqlite::Result<TrackedKeyword> {
+    Ok(TrackedKeyword {
+        id: row.get(0)?,
+        keyword: row.get(1)?,
+        location: row.get(2)?,
+        domain: row.get(3)?,
+        device: match row.get::<_, String>(4)?.as_str() {
+            "mobile" => Device::Mobile,
+            _ => Device::Desktop,
+        },
+        frequency: match row.get::<_, String>(5)?.as_str() {
+            "weekly" => Frequency::Weekly,
+            _ => Frequency::Daily,
+        },
+        created_at: parse_time(row.get(6)?)?,
+        next_check: parse_time(row.get(7)?)?,
+    })
+}
+
+fn point_from_row(row: &Row) -> rusqlite::Result<RankPoint> {
+    Ok(RankPoint {
+        keyword_id: row.get(0)?,
+        checked_at: parse_time(row.get(1)?)?,
+        position: row.get(2)?,
+        url: row.get(3)?,
+        title: row.get(4)?,
+    })
+}
+
+const KEYWORD_COLUMNS: &str =
+    "id, keyword, location, domain, device, frequency, created_at, next_check";
+const POINT_COLUMNS: &str = "keyword_id, checked_at, position, url, title";
+
+/// Tracked keywords and the history of their positions, in their own SQLite database.
+pub struct RankStore {
+    conn: Connection,
+}
+
+impl RankStore {
+    pub fn open() -> Result<Self, String> {
+        let conn = open_db_connection(RANK_DB).map_err(|e| e.to_string())?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS tracked_keywords (
+                id TEXT PRIMARY KEY,
+                keyword TEXT NOT NULL,
+                location TEXT,
+                domain TEXT NOT NULL,
+                device TEXT NOT NULL,
+                frequency TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                next_check TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS rank_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                keyword_id TEXT NOT NULL,
+                checked_at TEXT NOT NULL,
+                position INTEGER,
+                url TEXT,
+                title TEXT
+            );
+            CREATE INDEX IF NOT EXISTS rank_history_keyword ON rank_history (keyword_id, checked_at);
+            CREATE INDEX IF NOT EXISTS rank_history_url ON rank_history (url, checked_at);",
+        )
+        .map_err(|e| format!("Failed to create the rank tracker tables: {}", e))?;
+        Ok(Self { conn })
+    }
+
+    pub fn add_keyword(&self, keyword: &TrackedKeyword) -> Result<(), String> {
+        self.conn
+            .execute(
+                &format!(
+                    "INSERT INTO tracked_keywords ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    KEYWORD_COLUMNS
+                ),
+                params![
+                    keyword.id,
+                    keyword.keyword,
+                    keyword.location,
+                    keyword.domain,
+                    device_name(keyword.device),
+                    frequency_name(keyword.frequency),
+                    format_time(&keyword.created_at),
+                    format_time(&keyword.next_check),
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Removes a keyword together with its history.
+    pub fn remove_keyword(&self, id: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "DELETE FROM rank_history WHERE keyword_id = ?1",
+                params![id],
+            )
+            .map_err(|e| e.to_string())?;
+        self.conn
+            .execute("DELETE FROM tracked_keywords WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn keyword(&self, id: &str) -> Result<Option<TrackedKeyword>, String> {
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM tracked_keywords WHERE id = ?1",
+                    KEYWORD_COLUMNS
+                ),
+                params![id],
+                keyword_from_row,
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn keywords(&self) -> Result<Vec<TrackedKeyword>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {} FROM tracked_keywords ORDER BY keyword, location",
+                KEYWORD_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let keywords = stmt
+            .query_map([], keyword_from_row)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(keywords)
+    }
+
+    /// Keywords whose next check is due at `now`.
+    pub fn due_keywords(&self, now: &DateTime<Utc>) -> Result<Vec<TrackedKeyword>, String> {
+        Ok(self
+            .keywords()?
+            .into_iter()
+            .filter(|keyword| keyword.next_check <= *now)
+            .collect())
+    }
+
+    pub fn set_next_check(&self, id: &str, next_check: &DateTime<Utc>) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE tracked_keywords SET next_check = ?1 WHERE id = ?2",
+                params![format_time(next_check), id],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn record(&self, point: &RankPoint) -> Result<(), String> {
+        self.conn
+            .execute(
+                &format!(
+                    "INSERT INTO rank_history ({}) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    POINT_COLUMNS
+                ),
+                params![
+                    point.keyword_id,
+                    format_time(&point.checked_at),
+                    point.position,
+                    point.url,
+                    point.title,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// The checks of a keyword since `since`, oldest first.
+    pub fn history(
+        &self,
+        keyword_id: &str,
+        since: &DateTime<Utc>,
+    ) -> Result<Vec<RankPoint>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {} FROM rank_history WHERE keyword_id = ?1 AND checked_at >= ?2
+                 ORDER BY checked_at",
+                POINT_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let points = stmt
+            .query_map(params![keyword_id, format_time(since)], point_from_row)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(points)
+    }
+
+    /// The checks since `since` in which `url` was the ranking page, oldest first.
+    pub fn url_history(&self, url: &str, since: &DateTime<Utc>) -> Result<Vec<RankPoint>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {} FROM rank_history WHERE url = ?1 AND checked_at >= ?2
+                 ORDER BY checked_at",
+                POINT_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let points = stmt
+            .query_map(params![url, format_time(since)], point_from_row)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(points)
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+use tokio::time::{interval, Duration};
+use url::Url;
+use uuid::Uuid;
+
+use crate::domain_crawler::scheduler::Frequency;
+use crate::AppState;
+
+use super::providers::{Device, SerpProvider, SerpResult};
+use super::store::{RankPoint, RankStore, TrackedKeyword};
+
+// How often the tracker looks for due keywords
+const TICK: Duration = Duration::from_secs(300);
+// SERP APIs bill per search, a backlog is worked off a few keywords at a time
+const CHECKS_PER_TICK: usize = 10;
+const DEFAULT_TREND_DAYS: i64 = 90;
+
+/// The positions of a keyword over time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeywordTrend {
+    pub keyword: TrackedKeyword,
+    pub points: Vec<RankPoint>,
+    pub latest: Option<u32>,
+    pub best: Option<u32>,
+    pub worst: Option<u32>,
+    /// Positions gained between the first and the latest check, negative when it dropped
+    pub change: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlKeywordTrend {
+    pub keyword_id: String,
+    pub keyword: String,
+    pub location: Option<String>,
+    pub points: Vec<RankPoint>,
+}
+
+/// The keywords a page ranked for and its positions for each of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlTrend {
+    pub url: String,
+    pub keywords: Vec<UrlKeywordTrend>,
+}
+
+fn next_check(frequency: Frequency) -> chrono::DateTime<Utc> {
+    let days = match frequency {
+        Frequency::Daily => 1,
+        Frequency::Weekly => 7,
+    };
+    Utc::now() + ChronoDuration::days(days)
+}
+
+// The best placed result on the tracked domain or one of its subdomains
+fn ranking_result<'a>(results: &'a [SerpResult], domain: &str) -> Option<&'a SerpResult> {
+    let domain = domain.trim().trim_start_matches("www.").to_lowercase();
+    results
+        .iter()
+        .filter(|result| {
+            Url::parse(&result.url)
+                .ok()
+                .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+                .map(|host| {
+                    let host = host.trim_start_matches("www.");
+                    host == domain || host.ends_with(&format!(".{}", domain))
+                })
+                .unwrap_or(false)
+        })
+        .min_by_key(|result| result.position)
+}
+
+// The host a keyword is tracked for, from a bare domain or a full URL
+fn tracked_domain(domain: &str) -> Result<String, String> {
+    let domain = domain.trim();
+    let url = if domain.contains("://") {
+        domain.to_string()
+    } else {
+        format!("https://{}", domain)
+    };
+    Url::parse(&url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+        .ok_or_else(|| format!("Invalid domain: {}", domain))
+}
+
+async fn check_keyword(
+    provider: &SerpProvider,
+    keyword: &TrackedKeyword,
+) -> Result<RankPoint, String> {
+    let results = provider
+        .search(
+            &keyword.keyword,
+            keyword.location.as_deref(),
+            keyword.device,
+        )
+        .await?;
+    let ranking = ranking_result(&results, &keyword.domain);
+    let point = RankPoint {
+        keyword_id: keyword.id.clone(),
+        checked_at: Utc::now(),
+        position: ranking.map(|result| result.position),
+        url: ranking.map(|result| result.url.clone()),
+        title: ranking.map(|result| result.title.clone()),
+    };
+
+    let store = RankStore::open()?;
+    store.record(&point)?;
+    store.set_next_check(&keyword.id, &next_check(keyword.frequency))?;
+    Ok(point)
+}
+
+/// Starts the background loop that checks due keywords, called once from the app setup.
+pub fn start(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(TICK);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = run_due_checks(&app_handle).await {
+                eprintln!("Rank tracker error: {}", e);
+            }
+        }
+    });
+}
+
+async fn run_due_checks(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let settings = app_handle.state::<AppState>().settings.read().await.clone();
+    // Tracking stays idle until a SERP API is set up
+    if settings.serp_api_key.trim().is_empty() {
+        return Ok(());
+    }
+    let due = RankStore::open()?.due_keywords(&Utc::now())?;
+    if due.is_empty() {
+        return Ok(());
+    }
+    let provider = SerpProvider::from_settings(&settings)?;
+
+    for keyword in due.iter().take(CHECKS_PER_TICK) {
+        match check_keyword(&provider, keyword).await {
+            Ok(point) => {
+                if let Err(err) = app_handle.emit("rank_check_complete", &point) {
+                    eprintln!("Failed to emit rank check: {}", err);
+                }
+            }
+            Err(e) => eprintln!("Rank check of {:?} failed: {}", keyword.keyword, e),
+        }
+    }
+    Ok(())
+}
+
+// TRACK A KEYWORD FOR A DOMAIN
+#[tauri::command]
+pub async fn add_tracked_keyword(
+    keyword: String,
+    domain: String,
+    location: Option<String>,
+    device: Option<Device>,
+    frequency: Option<Frequency>,
+) -> Result<TrackedKeyword, String> {
+    let keyword = keyword.trim().to_string();
+    if keyword.is_empty() {
+        return Err("The keyword is empty".to_string());
+    }
+    let tracked = TrackedKeyword {
+        id: Uuid::new_v4().to_string(),
+        keyword,
+        location: location
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty()),
+        domain: tracked_domain(&domain)?,
+        device: device.unwrap_or(Device::Desktop),
+        frequency: frequency.unwrap_or(Frequency::Daily),
+        created_at: Utc::now(),
+        // The first check happens on the next tick
+        next_check: Utc::now(),
+    };
+    RankStore::open()?.add_keyword(&tracked)?;
+    Ok(tracked)
+}
+
+#[tauri::command]
+pub async fn list_tracked_keywords() -> Result<Vec<TrackedKeyword>, String> {
+    RankStore::open()?.keywords()
+}
+
+#[tauri::command]
+pub async fn remove_tracked_keyword(id: String) -> Result<(), String> {
+    RankStore::open()?.remove_keyword(&id)
+}
+
+// CHECK THE POSITION OF A TRACKED KEYWORD RIGHT AWAY
+#[tauri::command]
+pub async fn check_tracked_keyword(
+    id: String,
+    settings_state: tauri::State<'_, AppState>,
+) -> Result<RankPoint, String> {
+    let settings = settings_state.settings.read().await.clone();
+    let keyword = RankStore::open()?
+        .keyword(&id)?
+        .ok_or_else(|| format!("Tracked keyword {} not found", id))?;
+    let provider = SerpProvider::from_settings(&settings)?;
+    check_keyword(&provider, &keyword).await
+}
+
+// POSITIONS OF A KEYWORD OVER THE LAST DAYS
+#[tauri::command]
+pub async fn get_keyword_rank_trend(id: String, days: Option<i64>) -> Result<KeywordTrend, String> {
+    let store = RankStore::open()?;
+    let keyword = store
+        .keyword(&id)?
+        .ok_or_else(|| format!("Tracked keyword {} not found", id))?;
+    let since = Utc::now() - ChronoDuration::days(days.unwrap_or(DEFAULT_TREND_DAYS));
+    let points = store.history(&id, &since)?;
+
+    let positions: Vec<u32> = points.iter().filter_map(|point| point.position).collect();
+    let latest = points.last().and_then(|point| point.position);
+    let change = match (points.first().and_then(|p| p.position), latest) {
+        (Some(first), Some(latest)) => Some(first as i64 - latest as i64),
+        _ => None,
+    };
+    Ok(KeywordTrend {
+        keyword,
+        latest,
+        best: positions.iter().min().copied(),
+        worst: positions.iter().max().copied(),
+        change,
+        points,
+    })
+}
+
+// POSITIONS OF A PAGE FOR EVERY KEYWORD IT RANKED FOR
+#[tauri::command]
+pub async fn get_url_rank_trend(url: String, days: Option<i64>) -> Result<UrlTrend, String> {
+    let store = RankStore::open()?;
+    let since = Utc::now() - ChronoDuration::days(days.unwrap_or(DEFAULT_TREND_DAYS));
+    let mut by_keyword: BTreeMap<String, Vec<RankPoint>> = BTreeMap::new();
+    for point in store.url_history(&url, &since)? {
+        by_keyword
+            .entry(point.keyword_id.clone())
+            .or_default()
+            .push(point);
+    }
+
+    let mut keywords = Vec::new();
+    for (keyword_id, points) in by_keyword {
+        let Some(keyword) = store.keyword(&keyword_id)? else {
+            continue;
+        };
+        keywords.push(UrlKeywordTrend {
+            keyword_id,
+            keyword: keyword.keyword,
+            location: keyword.location,
+            points,
+        });
+    }
+    Ok(UrlTrend { url, keywords })
+}
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::fmt::format;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use sysinfo::{ProcessExt, System, SystemExt};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
@@ -15,6 +15,7 @@ use crate::domain_crawler::{self, user_agents};
 use crate::loganalyser::log_state::set_taxonomies;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct Settings {
     pub crawl_timeout: u64,
     pub client_timeout: u64,
@@ -38,6 +39,87 @@ pub struct Settings {
     pub page_speed_bulk: bool,
     pub page_speed_bulk_api_key: Option<Option<String>>,
     pub log_batchsize: usize,
+    pub images_request_timeout: u64,
+    pub images_connect_timeout: u64,
+    pub images_pool_max_idle_per_host: usize,
+    pub images_pool_idle_timeout: u64,
+    pub images_decode_dimensions: bool,
+    pub images_decode_max_bytes: usize,
+    pub link_checker: bool,
+    pub respect_robots: bool,
+    pub sitemap_discovery: bool,
+    pub per_host_delay_ms: u64,
+    pub per_host_burst: u32,
+    pub max_image_checks: usize,
+    pub redirect_chain_threshold: usize,
+    pub thin_content_threshold: usize,
+    pub near_duplicate_threshold: f64,
+    pub render_javascript: bool,
+    pub chrome_path: String,
+    pub render_wait_ms: u64,
+    pub user_agent_profile: String,
+    pub custom_user_agent: String,
+    pub auth_username: String,
+    pub auth_password: String,
+    pub auth_bearer_token: String,
+    pub custom_headers: Vec<String>,
+    pub session_cookies: Vec<String>,
+    pub cookies_file: String,
+    pub login_url: String,
+    pub login_fields: Vec<String>,
+    pub proxies: Vec<String>,
+    pub proxy_rotate: bool,
+    pub proxy_max_failures: u32,
+    pub scope_include: Vec<String>,
+    pub scope_exclude: Vec<String>,
+    pub scope_include_subdomains: bool,
+    pub scope_protocol: String,
+    pub max_crawl_depth: usize,
+    pub max_crawl_urls: usize,
+    pub strip_query_params: Vec<String>,
+    pub sort_query_params: bool,
+    pub important_page_max_depth: usize,
+    pub disk_frontier: bool,
+    pub conditional_requests: bool,
+    pub tls_expiry_warning_days: i64,
+    pub preflight_checks: bool,
+    pub waterfall_timing: bool,
+    pub icon_checks: bool,
+    pub large_image_threshold_kb: u64,
+    pub transcode_image_samples: usize,
+    pub spell_check: bool,
+    pub entity_extraction: bool,
+    pub entity_extraction_pages: usize,
+    pub ai_provider: String,
+    pub ai_base_url: String,
+    pub ai_model: String,
+    pub ai_api_key: String,
+    pub ai_requests_per_minute: u64,
+    pub ai_batch_size: usize,
+    pub capture_screenshots: bool,
+    pub screenshot_full_page: bool,
+    pub lab_vitals: bool,
+    pub serp_provider: String,
+    pub serp_api_key: String,
+    pub serp_depth: u64,
+    pub indexnow_key: String,
+    pub indexnow_key_location: String,
+    pub bing_webmaster_api_key: String,
+    pub backlinks_provider: String,
+    pub backlinks_api_key: String,
+    pub backlinks_endpoint: String,
+    pub backlinks_limit: u64,
+    pub api_server_enabled: bool,
+    pub api_server_port: u16,
+    pub api_server_token: String,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_security: String,
+    pub smtp_username: String,
+    pub smtp_password: String,
+    pub smtp_from: String,
+    pub custom_search: bool,
+    pub tracking_expected_ids: Vec<String>,
 }
 
 impl Settings {
@@ -65,13 +147,97 @@ impl Settings {
             page_speed_bulk: false,
             page_speed_bulk_api_key: None,
             log_batchsize: 2,
+            images_request_timeout: 5,
+            images_connect_timeout: 3,
+            images_pool_max_idle_per_host: 10,
+            images_pool_idle_timeout: 90,
+            images_decode_dimensions: false,
+            images_decode_max_bytes: 65536,
+            link_checker: true,
+            respect_robots: true,
+            sitemap_discovery: true,
+            per_host_delay_ms: 250,
+            per_host_burst: 5,
+            max_image_checks: 20,
+            redirect_chain_threshold: 1,
+            thin_content_threshold: 200,
+            near_duplicate_threshold: 0.9,
+            render_javascript: false,
+            chrome_path: String::new(),
+            render_wait_ms: 5000,
+            user_agent_profile: "rotate".to_string(),
+            custom_user_agent: String::new(),
+            auth_username: String::new(),
+            auth_password: String::new(),
+            auth_bearer_token: String::new(),
+            custom_headers: Vec::new(),
+            session_cookies: Vec::new(),
+            cookies_file: String::new(),
+            login_url: String::new(),
+            login_fields: Vec::new(),
+            proxies: Vec::new(),
+            proxy_rotate: false,
+            proxy_max_failures: 3,
+            scope_include: Vec::new(),
+            scope_exclude: Vec::new(),
+            scope_include_subdomains: true,
+            scope_protocol: "any".to_string(),
+            max_crawl_depth: 0,
+            max_crawl_urls: 0,
+            strip_query_params: ["utm_*", "gclid", "fbclid", "msclkid", "mc_cid", "mc_eid"]
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            sort_query_params: true,
+            important_page_max_depth: 3,
+            disk_frontier: false,
+            conditional_requests: true,
+            tls_expiry_warning_days: 30,
+            preflight_checks: true,
+            waterfall_timing: false,
+            icon_checks: true,
+            large_image_threshold_kb: 100,
+            transcode_image_samples: 0,
+            spell_check: false,
+            entity_extraction: false,
+            entity_extraction_pages: 50,
+            ai_provider: "ollama".to_string(),
+            ai_base_url: String::new(),
+            ai_model: String::new(),
+            ai_api_key: String::new(),
+            ai_requests_per_minute: 20,
+            ai_batch_size: 5,
+            capture_screenshots: false,
+            screenshot_full_page: false,
+            lab_vitals: false,
+            serp_provider: "serpapi".to_string(),
+            serp_api_key: String::new(),
+            serp_depth: 100,
+            indexnow_key: String::new(),
+            indexnow_key_location: String::new(),
+            bing_webmaster_api_key: String::new(),
+            backlinks_provider: "majestic".to_string(),
+            backlinks_api_key: String::new(),
+            backlinks_endpoint: String::new(),
+            backlinks_limit: 1000,
+            api_server_enabled: false,
+            api_server_port: 7878,
+            api_server_token: String::new(),
+            smtp_host: String::new(),
+            smtp_port: 587,
+            smtp_security: "starttls".to_string(),
+            smtp_username: String::new(),
+            smtp_password: String::new(),
+            smtp_from: String::new(),
+            custom_search: true,
+            tracking_expected_ids: Vec::new(),
         }
     }
 
     pub fn config_path() -> Result<PathBuf, String> {
         ProjectDirs::from("", "", "rustyseo")
             .ok_or("Failed to determine config directory".to_string())
-            .map(|dirs| dirs.config_dir().join("configs.toml"))
+            .map(|dirs| crate::projects::registry::config_path(&dirs))
     }
 
     // Delete the file
@@ -89,8 +255,12 @@ impl Default for Settings {
 
 /// Loads settings from file (returns error if file doesn't exist)
 pub async fn load_settings() -> Result<Settings, String> {
-    let config_path = Settings::config_path()?;
-    let contents = fs::read_to_string(&config_path)
+    load_settings_from(&Settings::config_path()?).await
+}
+
+/// Loads the settings file of a project that may not be the active one.
+pub async fn load_settings_from(config_path: &Path) -> Result<Settings, String> {
+    let contents = fs::read_to_string(config_path)
         .await
         .map_err(|e| format!("Failed to read config: {}", e))?;
     toml::from_str(&contents).map_err(|e| format!("Failed to parse config: {}", e))
@@ -168,6 +338,22 @@ pub fn print_settings(settings: &Settings) {
     println!("Links Max Retries: {}", settings.links_max_retries);
     println!("Links Retry Delay: {}", settings.links_retry_delay);
     println!("Links Request Timeout: {}", settings.links_request_timeout);
+    println!(
+        "Images Request Timeout: {}",
+        settings.images_request_timeout
+    );
+    println!(
+        "Images Connect Timeout: {}",
+        settings.images_connect_timeout
+    );
+    println!(
+        "Images Pool Max Idle Per Host: {}",
+        settings.images_pool_max_idle_per_host
+    );
+    println!(
+        "Images Pool Idle Timeout: {}",
+        settings.images_pool_idle_timeout
+    );
     println!("Taxonomies: {:?}", settings.taxonomies);
     println!("Rusty ID: {}", settings.rustyid);
     println!("Page Speed Bulkd: {}", settings.page_speed_bulk);
@@ -292,6 +478,432 @@ pub async fn override_settings(updates: &str) -> Result<Settings, String> {
         settings.links_request_timeout = val as u64;
     }
 
+    if let Some(val) = updates
+        .get("images_request_timeout")
+        .and_then(|v| v.as_integer())
+    {
+        settings.images_request_timeout = val as u64;
+    }
+
+    if let Some(val) = updates
+        .get("images_connect_timeout")
+        .and_then(|v| v.as_integer())
+    {
+        settings.images_connect_timeout = val as u64;
+    }
+
+    if let Some(val) = updates
+        .get("images_pool_max_idle_per_host")
+        .and_then(|v| v.as_integer())
+    {
+        settings.images_pool_max_idle_per_host = val as usize;
+    }
+
+    if let Some(val) = updates
+        .get("images_pool_idle_timeout")
+        .and_then(|v| v.as_integer())
+    {
+        settings.images_pool_idle_timeout = val as u64;
+    }
+
+    if let Some(val) = updates
+        .get("images_decode_dimensions")
+        .and_then(|v| v.as_bool())
+    {
+        settings.images_decode_dimensions = val;
+    }
+
+    if let Some(val) = updates
+        .get("images_decode_max_bytes")
+        .and_then(|v| v.as_integer())
+    {
+        settings.images_decode_max_bytes = val as usize;
+    }
+
+    if let Some(val) = updates.get("link_checker").and_then(|v| v.as_bool()) {
+        settings.link_checker = val;
+    }
+
+    if let Some(val) = updates.get("respect_robots").and_then(|v| v.as_bool()) {
+        settings.respect_robots = val;
+    }
+
+    if let Some(val) = updates.get("sitemap_discovery").and_then(|v| v.as_bool()) {
+        settings.sitemap_discovery = val;
+    }
+
+    if let Some(val) = updates
+        .get("per_host_delay_ms")
+        .and_then(|v| v.as_integer())
+    {
+        settings.per_host_delay_ms = val as u64;
+    }
+
+    if let Some(val) = updates.get("per_host_burst").and_then(|v| v.as_integer()) {
+        settings.per_host_burst = val as u32;
+    }
+
+    if let Some(val) = updates.get("max_image_checks").and_then(|v| v.as_integer()) {
+        settings.max_image_checks = val as usize;
+    }
+
+    if let Some(val) = updates
+        .get("redirect_chain_threshold")
+        .and_then(|v| v.as_integer())
+    {
+        settings.redirect_chain_threshold = val as usize;
+    }
+
+    if let Some(val) = updates
+        .get("thin_content_threshold")
+        .and_then(|v| v.as_integer())
+    {
+        settings.thin_content_threshold = val as usize;
+    }
+
+    if let Some(val) = updates
+        .get("near_duplicate_threshold")
+        .and_then(|v| v.as_float())
+    {
+        settings.near_duplicate_threshold = val;
+    }
+
+    if let Some(val) = updates.get("render_javascript").and_then(|v| v.as_bool()) {
+        settings.render_javascript = val;
+    }
+
+    if let Some(val) = updates.get("chrome_path").and_then(|v| v.as_str()) {
+        settings.chrome_path = val.to_string();
+    }
+
+    if let Some(val) = updates.get("render_wait_ms").and_then(|v| v.as_integer()) {
+        settings.render_wait_ms = val as u64;
+    }
+
+    if let Some(val) = updates.get("user_agent_profile").and_then(|v| v.as_str()) {
+        settings.user_agent_profile = val.to_string();
+    }
+
+    if let Some(val) = updates.get("custom_user_agent").and_then(|v| v.as_str()) {
+        settings.custom_user_agent = val.to_string();
+    }
+
+    if let Some(val) = updates.get("auth_username").and_then(|v| v.as_str()) {
+        settings.auth_username = val.to_string();
+    }
+
+    if let Some(val) = updates.get("auth_password").and_then(|v| v.as_str()) {
+        settings.auth_password = val.to_string();
+    }
+
+    if let Some(val) = updates.get("auth_bearer_token").and_then(|v| v.as_str()) {
+        settings.auth_bearer_token = val.to_string();
+    }
+
+    if let Some(val) = updates.get("custom_headers").and_then(|v| v.as_array()) {
+        settings.custom_headers = val
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_string())
+            .collect();
+    }
+
+    if let Some(val) = updates.get("session_cookies").and_then(|v| v.as_array()) {
+        settings.session_cookies = val
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_string())
+            .collect();
+    }
+
+    if let Some(val) = updates.get("cookies_file").and_then(|v| v.as_str()) {
+        settings.cookies_file = val.to_string();
+    }
+
+    if let Some(val) = updates.get("login_url").and_then(|v| v.as_str()) {
+        settings.login_url = val.to_string();
+    }
+
+    if let Some(val) = updates.get("login_fields").and_then(|v| v.as_array()) {
+        settings.login_fields = val
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_string())
+            .collect();
+    }
+
+    if let Some(val) = updates.get("proxies").and_then(|v| v.as_array()) {
+        settings.proxies = val
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_string())
+            .collect();
+    }
+
+    if let Some(val) = updates.get("proxy_rotate").and_then(|v| v.as_bool()) {
+        settings.proxy_rotate = val;
+    }
+
+    if let Some(val) = updates
+        .get("proxy_max_failures")
+        .and_then(|v| v.as_integer())
+    {
+        settings.proxy_max_failures = val as u32;
+    }
+
+    if let Some(val) = updates.get("scope_include").and_then(|v| v.as_array()) {
+        settings.scope_include = val
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_string())
+            .collect();
+    }
+
+    if let Some(val) = updates.get("scope_exclude").and_then(|v| v.as_array()) {
+        settings.scope_exclude = val
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_string())
+            .collect();
+    }
+
+    if let Some(val) = updates
+        .get("scope_include_subdomains")
+        .and_then(|v| v.as_bool())
+    {
+        settings.scope_include_subdomains = val;
+    }
+
+    if let Some(val) = updates.get("scope_protocol").and_then(|v| v.as_str()) {
+        settings.scope_protocol = val.to_string();
+    }
+
+    if let Some(val) = updates.get("max_crawl_depth").and_then(|v| v.as_integer()) {
+        settings.max_crawl_depth = val as usize;
+    }
+
+    if let Some(val) = updates.get("max_crawl_urls").and_then(|v| v.as_integer()) {
+        settings.max_crawl_urls = val as usize;
+    }
+
+    if let Some(val) = updates.get("strip_query_params").and_then(|v| v.as_array()) {
+        settings.strip_query_params = val
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_string())
+            .collect();
+    }
+
+    if let Some(val) = updates.get("sort_query_params").and_then(|v| v.as_bool()) {
+        settings.sort_query_params = val;
+    }
+
+    if let Some(val) = updates
+        .get("important_page_max_depth")
+        .and_then(|v| v.as_integer())
+    {
+        settings.important_page_max_depth = val as usize;
+    }
+
+    if let Some(val) = updates.get("disk_frontier").and_then(|v| v.as_bool()) {
+        settings.disk_frontier = val;
+    }
+
+    if let Some(val) = updates
+        .get("conditional_requests")
+        .and_then(|v| v.as_bool())
+    {
+        settings.conditional_requests = val;
+    }
+
+    if let Some(val) = updates
+        .get("tls_expiry_warning_days")
+        .and_then(|v| v.as_integer())
+    {
+        settings.tls_expiry_warning_days = val;
+    }
+
+    if let Some(val) = updates.get("preflight_checks").and_then(|v| v.as_bool()) {
+        settings.preflight_checks = val;
+    }
+
+    if let Some(val) = updates.get("waterfall_timing").and_then(|v| v.as_bool()) {
+        settings.waterfall_timing = val;
+    }
+
+    if let Some(val) = updates.get("icon_checks").and_then(|v| v.as_bool()) {
+        settings.icon_checks = val;
+    }
+
+    if let Some(val) = updates
+        .get("large_image_threshold_kb")
+        .and_then(|v| v.as_integer())
+    {
+        settings.large_image_threshold_kb = val as u64;
+    }
+
+    if let Some(val) = updates
+        .get("transcode_image_samples")
+        .and_then(|v| v.as_integer())
+    {
+        settings.transcode_image_samples = val as usize;
+    }
+
+    if let Some(val) = updates.get("spell_check").and_then(|v| v.as_bool()) {
+        settings.spell_check = val;
+    }
+
+    if let Some(val) = updates.get("entity_extraction").and_then(|v| v.as_bool()) {
+        settings.entity_extraction = val;
+    }
+
+    if let Some(val) = updates
+        .get("entity_extraction_pages")
+        .and_then(|v| v.as_integer())
+    {
+        settings.entity_extraction_pages = val as usize;
+    }
+
+    if let Some(val) = updates.get("ai_provider").and_then(|v| v.as_str()) {
+        settings.ai_provider = val.to_string();
+    }
+
+    if let Some(val) = updates.get("ai_base_url").and_then(|v| v.as_str()) {
+        settings.ai_base_url = val.to_string();
+    }
+
+    if let Some(val) = updates.get("ai_model").and_then(|v| v.as_str()) {
+        settings.ai_model = val.to_string();
+    }
+
+    if let Some(val) = updates.get("ai_api_key").and_then(|v| v.as_str()) {
+        settings.ai_api_key = val.to_string();
+    }
+
+    if let Some(val) = updates
+        .get("ai_requests_per_minute")
+        .and_then(|v| v.as_integer())
+    {
+        settings.ai_requests_per_minute = val as u64;
+    }
+
+    if let Some(val) = updates.get("ai_batch_size").and_then(|v| v.as_integer()) {
+        settings.ai_batch_size = val as usize;
+    }
+
+    if let Some(val) = updates.get("capture_screenshots").and_then(|v| v.as_bool()) {
+        settings.capture_screenshots = val;
+    }
+
+    if let Some(val) = updates
+        .get("screenshot_full_page")
+        .and_then(|v| v.as_bool())
+    {
+        settings.screenshot_full_page = val;
+    }
+
+    if let Some(val) = updates.get("lab_vitals").and_then(|v| v.as_bool()) {
+        settings.lab_vitals = val;
+    }
+
+    if let Some(val) = updates.get("serp_provider").and_then(|v| v.as_str()) {
+        settings.serp_provider = val.to_string();
+    }
+
+    if let Some(val) = updates.get("serp_api_key").and_then(|v| v.as_str()) {
+        settings.serp_api_key = val.to_string();
+    }
+
+    if let Some(val) = updates.get("serp_depth").and_then(|v| v.as_integer()) {
+        settings.serp_depth = val as u64;
+    }
+
+    if let Some(val) = updates.get("indexnow_key").and_then(|v| v.as_str()) {
+        settings.indexnow_key = val.to_string();
+    }
+
+    if let Some(val) = updates
+        .get("indexnow_key_location")
+        .and_then(|v| v.as_str())
+    {
+        settings.indexnow_key_location = val.to_string();
+    }
+
+    if let Some(val) = updates
+        .get("bing_webmaster_api_key")
+        .and_then(|v| v.as_str())
+    {
+        settings.bing_webmaster_api_key = val.to_string();
+    }
+
+    if let Some(val) = updates.get("backlinks_provider").and_then(|v| v.as_str()) {
+        settings.backlinks_provider = val.to_string();
+    }
+
+    if let Some(val) = updates.get("backlinks_api_key").and_then(|v| v.as_str()) {
+        settings.backlinks_api_key = val.to_string();
+    }
+
+    if let Some(val) = updates.get("backlinks_endpoint").and_then(|v| v.as_str()) {
+        settings.backlinks_endpoint = val.to_string();
+    }
+
+    if let Some(val) = updates.get("backlinks_limit").and_then(|v| v.as_integer()) {
+        settings.backlinks_limit = val as u64;
+    }
+
+    if let Some(val) = updates.get("api_server_enabled").and_then(|v| v.as_bool()) {
+        settings.api_server_enabled = val;
+    }
+
+    if let Some(val) = updates.get("api_server_port").and_then(|v| v.as_integer()) {
+        settings.api_server_port = val as u16;
+    }
+
+    if let Some(val) = updates.get("api_server_token").and_then(|v| v.as_str()) {
+        settings.api_server_token = val.to_string();
+    }
+
+    if let Some(val) = updates.get("smtp_host").and_then(|v| v.as_str()) {
+        settings.smtp_host = val.to_string();
+    }
+
+    if let Some(val) = updates.get("smtp_port").and_then(|v| v.as_integer()) {
+        settings.smtp_port = val as u16;
+    }
+
+    if let Some(val) = updates.get("smtp_security").and_then(|v| v.as_str()) {
+        settings.smtp_security = val.to_string();
+    }
+
+    if let Some(val) = updates.get("smtp_username").and_then(|v| v.as_str()) {
+        settings.smtp_username = val.to_string();
+    }
+
+    if let Some(val) = updates.get("smtp_password").and_then(|v| v.as_str()) {
+        settings.smtp_password = val.to_string();
+    }
+
+    if let Some(val) = updates.get("smtp_from").and_then(|v| v.as_str()) {
+        settings.smtp_from = val.to_string();
+    }
+
+    if let Some(val) = updates.get("custom_search").and_then(|v| v.as_bool()) {
+        settings.custom_search = val;
+    }
+
+    if let Some(val) = updates
+        .get("tracking_expected_ids")
+        .and_then(|v| v.as_array())
+    {
+        settings.tracking_expected_ids = val
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+    }
+
     if let Some(val) = updates.get("page_speed_bulk").and_then(|v| v.as_bool()) {
         settings.page_speed_bulk = val;
     }